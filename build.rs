@@ -0,0 +1,9 @@
+//! Generates the gRPC worker service from proto/worker.proto (see src/grpc.rs)
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/worker.proto"], &["proto"])?;
+    Ok(())
+}
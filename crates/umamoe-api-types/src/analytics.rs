@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ingest::IngestRowError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsEventType {
+    /// A search was run (properties: search, result_count, page, duration_ms, filter_count)
+    SearchPerformed,
+    /// A result's trainer ID or share link was copied (properties: search, target, position)
+    ResultCopied,
+    /// A search filter was set (properties: search, filter, value)
+    FilterUsed,
+}
+
+impl AnalyticsEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalyticsEventType::SearchPerformed => "search_performed",
+            AnalyticsEventType::ResultCopied => "result_copied",
+            AnalyticsEventType::FilterUsed => "filter_used",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyticsEvent {
+    #[serde(rename = "type")]
+    pub event_type: AnalyticsEventType,
+    /// When the event happened on the client (default: when it was received)
+    pub occurred_at: Option<DateTime<Utc>>,
+    /// Checked against the event type's schema
+    #[serde(default)]
+    pub properties: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AnalyticsEventBatch {
+    /// Random ID the frontend keeps per browser session, to group events
+    #[validate(length(min = 8, max = 64))]
+    pub session_id: Option<String>,
+    #[validate(length(min = 1, max = 50))]
+    pub events: Vec<AnalyticsEvent>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AnalyticsEventResponse {
+    pub accepted: usize,
+    pub rejected: usize,
+    /// Why events were rejected; `index` is their position in the batch
+    pub errors: Vec<IngestRowError>,
+}
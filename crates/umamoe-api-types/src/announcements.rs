@@ -0,0 +1,81 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "text", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementKind {
+    /// Planned downtime or degraded service
+    Maintenance,
+    /// Scraped data is arriving late
+    ScrapeDelay,
+    /// A new feature (changelog entry)
+    Feature,
+    Info,
+}
+
+impl AnnouncementKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnnouncementKind::Maintenance => "maintenance",
+            AnnouncementKind::ScrapeDelay => "scrape_delay",
+            AnnouncementKind::Feature => "feature",
+            AnnouncementKind::Info => "info",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Announcement {
+    pub announcement_id: i32,
+    pub kind: AnnouncementKind,
+    pub title: String,
+    pub body: String,
+    /// Where to read more (optional)
+    pub link_url: Option<String>,
+    /// false for drafts, which only admins see
+    pub published: bool,
+    /// Shown from this time on
+    pub published_at: NaiveDateTime,
+    /// Hidden after this time (None: until deleted)
+    pub expires_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnnouncementListParams {
+    /// Only announcements published after this time, e.g. the newest
+    /// published_at the client has seen
+    pub since: Option<NaiveDateTime>,
+    /// Also list expired announcements, for a changelog (default false)
+    pub include_expired: Option<bool>,
+    /// Announcements to return (1-100, default 20)
+    pub limit: Option<i64>,
+}
+
+/// Body of creating or replacing an announcement
+#[derive(Debug, Deserialize, Validate)]
+pub struct AnnouncementRequest {
+    pub kind: AnnouncementKind,
+    #[validate(length(min = 1, max = 200))]
+    pub title: String,
+    #[serde(default)]
+    #[validate(length(max = 5000))]
+    pub body: String,
+    #[validate(length(max = 2048))]
+    pub link_url: Option<String>,
+    /// Save as a draft with false (default true)
+    #[serde(default = "default_published")]
+    pub published: bool,
+    /// When it starts showing (default: now)
+    pub published_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+fn default_published() -> bool {
+    true
+}
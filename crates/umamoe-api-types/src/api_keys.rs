@@ -0,0 +1,39 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// An API key as listed by the admin API; the key itself is only returned on creation
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct ApiKey {
+    pub key_id: i32,
+    pub name: String,
+    /// First characters of the key, to tell keys apart
+    pub key_prefix: String,
+    /// Any of "search", "tasks", "circles", "admin"
+    pub scopes: Vec<String>,
+    pub rate_limit_per_minute: i32,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ApiKeyCreateRequest {
+    /// Who the key is for, e.g. "discord-bot"
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(min = 1))]
+    pub scopes: Vec<String>,
+    /// Requests per minute before the key gets 429s (default 120)
+    #[validate(range(min = 1, max = 100000))]
+    pub rate_limit_per_minute: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyCreateResponse {
+    #[serde(flatten)]
+    pub key: ApiKey,
+    /// Send as X-Api-Key; it can't be retrieved again
+    pub api_key: String,
+}
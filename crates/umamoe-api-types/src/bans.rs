@@ -0,0 +1,37 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// A ban as listed by the admin API
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct BannedClient {
+    pub ban_id: i32,
+    /// Banned network, e.g. "203.0.113.7/32"
+    pub ip_range: Option<String>,
+    pub api_key_id: Option<i32>,
+    pub reason: String,
+    /// "admin" or "auto"
+    pub source: String,
+    pub created_at: NaiveDateTime,
+    /// None for permanent bans
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BanCreateRequest {
+    /// IP address or CIDR range
+    pub ip: Option<String>,
+    pub api_key_id: Option<i32>,
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+    /// How long the ban lasts; permanent when omitted
+    #[validate(range(min = 1))]
+    pub duration_secs: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BanListParams {
+    /// Also list expired bans (default false)
+    pub include_expired: Option<bool>,
+}
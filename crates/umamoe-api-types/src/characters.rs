@@ -0,0 +1,41 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::ids::CharaId;
+
+/// A character ID that had no name in the DB table or the bundled list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingCharacterName {
+    pub chara_id: CharaId,
+    /// Name lookups that fell through to the placeholder since startup
+    pub lookups: u64,
+    pub first_seen: NaiveDateTime,
+    pub last_seen: NaiveDateTime,
+}
+
+/// Where character names currently come from and which IDs are missing
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CharacterNameReport {
+    /// Names loaded from the character_names table
+    pub db_names: usize,
+    /// Names in the list bundled with the binary
+    pub bundled_names: usize,
+    pub missing: Vec<MissingCharacterName>,
+}
+
+/// A character_names row, as imported from game master data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterNameEntry {
+    /// Base character ID (1001, not the card variant 100101)
+    pub chara_id: CharaId,
+    pub name: String,
+}
+
+/// Result of a game data import
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameDataImportResult {
+    /// "characters" or "support-cards"
+    pub kind: String,
+    /// Rows inserted or updated
+    pub imported: usize,
+}
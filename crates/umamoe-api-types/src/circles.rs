@@ -0,0 +1,391 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use super::ids::{CharaId, CircleId, ViewerId};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Circle {
+    pub circle_id: CircleId,
+    pub name: String,
+    pub comment: Option<String>,
+    pub leader_viewer_id: Option<ViewerId>,
+    pub leader_name: Option<String>,
+    pub member_count: Option<i32>,
+    pub join_style: Option<i32>,
+    pub policy: Option<i32>,
+    pub created_at: Option<NaiveDateTime>,
+    pub last_updated: Option<NaiveDateTime>,
+    pub monthly_rank: Option<i32>,
+    pub monthly_point: Option<i64>,
+    pub last_month_rank: Option<i32>,
+    pub last_month_point: Option<i64>,
+    pub archived: Option<bool>,
+    pub yesterday_updated: Option<NaiveDateTime>,
+    pub yesterday_points: Option<i64>,
+    pub yesterday_rank: Option<i32>,
+    /// Estimated final monthly points from the last 7 days' trend (current month only)
+    pub projected_point: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CircleMemberFansMonthly {
+    pub id: i32,
+    pub circle_id: CircleId,
+    pub viewer_id: ViewerId,
+    pub trainer_name: Option<String>,
+    pub year: i32,
+    pub month: i32,
+    pub daily_fans: Vec<i32>,
+    pub last_updated: Option<NaiveDateTime>,
+    /// Latest cumulative fan count this month
+    pub total_fans: i64,
+    /// Fans gained since the previous recorded day
+    pub today_fans: i64,
+    /// The member's best inheritance record (only with include=records)
+    #[serde(default)]
+    pub inheritance: Option<MemberInheritanceSummary>,
+}
+
+/// Summary of a member's best inheritance record (highest parent rank, then affinity)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MemberInheritanceSummary {
+    pub inheritance_id: i32,
+    pub main_parent_id: CharaId,
+    pub parent_left_id: CharaId,
+    pub parent_right_id: CharaId,
+    pub parent_rank: i32,
+    pub parent_rarity: i32,
+    pub win_count: i32,
+    /// Base + race affinity of the parent combination
+    pub affinity_score: i32,
+    /// Sparks of the main parent itself
+    pub main_blue_factors: i32,
+    pub main_pink_factors: i32,
+    pub main_green_factors: i32,
+    pub main_white_factors: Vec<i32>,
+}
+
+/// One daily snapshot of a circle's monthly standing
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct CircleRankSnapshot {
+    pub snapshot_date: NaiveDate,
+    pub monthly_rank: Option<i32>,
+    pub monthly_point: Option<i64>,
+    pub member_count: Option<i32>,
+}
+
+/// A member joining or leaving a circle, detected between scrapes
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct CircleMemberEvent {
+    pub viewer_id: ViewerId,
+    pub trainer_name: Option<String>,
+    /// "joined" or "left"
+    pub event_type: String,
+    pub detected_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct CircleModerationOverride {
+    pub circle_id: CircleId,
+    pub name_override: Option<String>,
+    pub comment_override: Option<String>,
+    pub hide_comment: bool,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CircleModerationRequest {
+    pub name_override: Option<String>,
+    pub comment_override: Option<String>,
+    #[serde(default)]
+    pub hide_comment: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleQueryParams {
+    /// Query by viewer ID - will find their circle
+    pub viewer_id: Option<ViewerId>,
+    /// Query by circle ID directly
+    pub circle_id: Option<CircleId>,
+    /// Filter members by month (1-12)
+    pub month: Option<i32>,
+    /// Filter members by year
+    pub year: Option<i32>,
+    /// Sort members by total_fans, today_fans or name (default: viewer_id)
+    pub sort_by: Option<String>,
+    /// Sort direction (asc, desc)
+    pub sort_dir: Option<String>,
+    /// Member page number (0-indexed)
+    pub page: Option<i64>,
+    /// Members per page (default: all, max: 100)
+    pub limit: Option<i64>,
+    /// Comma-separated extras per member: records (best inheritance record)
+    pub include: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CircleListParams {
+    /// Page number (0-indexed)
+    #[serde(default)]
+    pub page: Option<i64>,
+    /// Results per page
+    #[serde(default)]
+    pub limit: Option<i64>,
+    /// Search by circle name (partial match)
+    pub name: Option<String>,
+    /// Minimum member count
+    pub min_members: Option<i32>,
+    /// Minimum monthly rank (lower is better)
+    pub max_rank: Option<i32>,
+    /// Sort by field (name, member_count, monthly_rank, monthly_point)
+    pub sort_by: Option<String>,
+    /// Sort direction (asc, desc)
+    pub sort_dir: Option<String>,
+    /// Collation for name sorting: ja (default) or root
+    pub sort_locale: Option<String>,
+    /// General search query (circle ID/name, leader ID/name, member ID/name)
+    pub query: Option<String>,
+    /// Show the standings of a past month (requires month)
+    pub year: Option<i32>,
+    /// Show the standings of a past month (1-12, requires year)
+    pub month: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleRefreshParams {
+    /// Viewer whose circle should be (re-)fetched
+    pub viewer_id: ViewerId,
+    /// Report what would happen without queueing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleHistoryParams {
+    /// First day to include (YYYY-MM-DD), defaults to 30 days before `to`
+    pub from: Option<chrono::NaiveDate>,
+    /// Last day to include (YYYY-MM-DD), defaults to today
+    pub to: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleHistoryResponse {
+    pub circle_id: CircleId,
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    pub history: Vec<CircleRankSnapshot>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleStatsParams {
+    /// Month of member fan data (1-12, default: current competition month)
+    pub month: Option<i32>,
+    /// Year of member fan data
+    pub year: Option<i32>,
+}
+
+/// A member and the fans they gained this month
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct CircleContributor {
+    pub viewer_id: ViewerId,
+    pub trainer_name: Option<String>,
+    pub month_fans: i64,
+}
+
+/// Member fan statistics for one month of a circle
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleStats {
+    pub circle_id: CircleId,
+    pub year: i32,
+    pub month: i32,
+    /// Members with fan data this month
+    pub member_count: i64,
+    /// Members who gained fans on the latest recorded day
+    pub active_members: i64,
+    /// Mean of each member's average daily fan gain
+    pub avg_daily_fans: f64,
+    /// Median of each member's average daily fan gain
+    pub median_daily_fans: f64,
+    pub top_contributor: Option<CircleContributor>,
+    pub bottom_contributor: Option<CircleContributor>,
+    /// Circle-wide fans gained on the latest recorded day
+    pub today_fans: i64,
+    /// Circle-wide fans gained on the recorded day before that
+    pub yesterday_fans: i64,
+    /// Change of today_fans over yesterday_fans in percent (None without a previous day)
+    pub day_over_day_growth: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleAwardsParams {
+    /// Month of the awards (1-12, default: last competition month)
+    pub month: Option<i32>,
+    /// Year of the awards
+    pub year: Option<i32>,
+}
+
+/// A member award for one month of a circle
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct CircleAward {
+    /// "most_fans", "most_consistent" or "biggest_day"
+    pub award: String,
+    pub viewer_id: ViewerId,
+    pub trainer_name: Option<String>,
+    /// Fans gained (most_fans, biggest_day) or days with a fan gain (most_consistent)
+    pub value: i64,
+    /// Day of the gain (biggest_day only)
+    pub award_date: Option<NaiveDate>,
+    pub computed_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleAwardsResponse {
+    pub circle_id: CircleId,
+    pub year: i32,
+    pub month: i32,
+    pub awards: Vec<CircleAward>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemberFanHistoryParams {
+    /// First day to include (YYYY-MM-DD), defaults to the first recorded day
+    pub from: Option<NaiveDate>,
+    /// Last day to include (YYYY-MM-DD), defaults to the last recorded day
+    pub to: Option<NaiveDate>,
+}
+
+/// One day of a member's fan history; days without data have no fan count
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct MemberFanDay {
+    pub date: NaiveDate,
+    /// Circle the member was recorded in that day
+    pub circle_id: Option<CircleId>,
+    /// Cumulative fan count
+    pub fans: Option<i64>,
+    /// Fans gained since the previous recorded day
+    pub gained: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemberFanHistoryResponse {
+    pub viewer_id: ViewerId,
+    pub trainer_name: Option<String>,
+    /// Continuous daily series from the first to the last day in range
+    pub days: Vec<MemberFanDay>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleMemberEventsParams {
+    /// Page number (0-indexed)
+    pub page: Option<i64>,
+    /// Results per page (max 200)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleMemberEventsResponse {
+    pub circle_id: CircleId,
+    pub events: Vec<CircleMemberEvent>,
+    pub page: i64,
+    pub limit: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleResponse {
+    pub circle: Circle,
+    /// Share of this month's tracked circles with fewer points, in percent (None without points)
+    pub percentile: Option<f64>,
+    pub members: Vec<CircleMemberFansMonthly>,
+    /// Members in the selected month, before pagination
+    pub total_members: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleBulkParams {
+    /// Comma-separated circle IDs (max 50)
+    pub ids: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleBulkResponse {
+    /// Found circles, in the order requested
+    pub circles: Vec<Circle>,
+    /// Requested IDs that don't exist
+    pub missing: Vec<CircleId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleWithRank {
+    #[serde(flatten)]
+    pub circle: Circle,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleListResponse {
+    pub circles: Vec<CircleWithRank>,
+    pub total: i64,
+    pub page: i64,
+    pub limit: i64,
+    pub total_pages: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleWatchResponse {
+    pub circle_id: CircleId,
+    pub watching: bool,
+    /// Circles on this watchlist after the change
+    pub watched_count: i64,
+}
+
+/// A watched circle's current standing and its change since yesterday
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct WatchedCircle {
+    pub circle_id: CircleId,
+    pub name: String,
+    pub member_count: Option<i32>,
+    pub monthly_rank: Option<i32>,
+    pub monthly_point: Option<i64>,
+    pub yesterday_rank: Option<i32>,
+    /// Places gained since yesterday (positive = moved up)
+    pub rank_delta: Option<i32>,
+    /// Points gained since yesterday
+    pub point_delta: Option<i64>,
+    pub last_updated: Option<NaiveDateTime>,
+    pub watched_since: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WatchedCirclesResponse {
+    pub circles: Vec<WatchedCircle>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleWebhookRequest {
+    /// Discord webhook URL (https://discord.com/api/webhooks/...)
+    pub webhook_url: String,
+    /// Notify when the circle enters or leaves the top N (default: 100 and 500)
+    pub thresholds: Option<Vec<i32>>,
+    /// Notify when the circle and this rival trade places
+    pub rival_circle_id: Option<CircleId>,
+}
+
+/// A circle's rank-change webhook; the URL itself is never returned
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct CircleWebhook {
+    pub circle_id: CircleId,
+    /// Notifications are only sent once the webhook is verified
+    pub verified: bool,
+    /// Code to put into the circle comment to verify the webhook (unverified only)
+    pub verification_code: Option<String>,
+    pub thresholds: Vec<i32>,
+    pub rival_circle_id: Option<CircleId>,
+}
@@ -0,0 +1,160 @@
+//! Thin async client for the public API
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    AccountId, BatchSubmissionResponse, CircleBulkParams, CircleBulkResponse, CircleId,
+    CircleListParams, CircleListResponse, CircleQueryParams, CircleResponse, SearchResponse,
+    TrainerBatchSubmissionRequest, TrainerSubmissionRequest, UnifiedAccountRecord,
+    UnifiedSearchParams,
+};
+
+pub const DEFAULT_BASE_URL: &str = "https://uma.moe";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The API answered with an error body ({"error", "status"})
+    #[error("API error {status}: {message}")]
+    Api { status: u16, message: String },
+}
+
+pub type Result<T> = std::result::Result<T, ApiError>;
+
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Default for ApiClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_BASE_URL)
+    }
+}
+
+impl ApiClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Use an existing reqwest client (shared connection pool, custom user agent, ...)
+    pub fn with_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// GET /api/v3/search
+    pub async fn search(
+        &self,
+        params: &UnifiedSearchParams,
+    ) -> Result<SearchResponse<UnifiedAccountRecord>> {
+        self.get("/api/v3/search", params).await
+    }
+
+    /// GET /api/v4/circles
+    pub async fn circle(&self, params: &CircleQueryParams) -> Result<CircleResponse> {
+        self.get("/api/v4/circles", params).await
+    }
+
+    /// GET /api/v4/circles/list
+    pub async fn list_circles(&self, params: &CircleListParams) -> Result<CircleListResponse> {
+        self.get("/api/v4/circles/list", params).await
+    }
+
+    /// GET /api/v4/circles/bulk (up to 50 circles, without members)
+    pub async fn circles_bulk(&self, ids: &[CircleId]) -> Result<CircleBulkResponse> {
+        let params = CircleBulkParams {
+            ids: ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        };
+        self.get("/api/v4/circles/bulk", &params).await
+    }
+
+    /// POST /api/tasks/submit
+    pub async fn submit_trainer(&self, trainer_id: AccountId) -> Result<serde_json::Value> {
+        self.post("/api/tasks/submit", &TrainerSubmissionRequest { trainer_id })
+            .await
+    }
+
+    /// POST /api/tasks/submit-batch
+    pub async fn submit_trainer_batch(
+        &self,
+        request: &TrainerBatchSubmissionRequest,
+    ) -> Result<BatchSubmissionResponse> {
+        self.post("/api/tasks/submit-batch", request).await
+    }
+
+    async fn get<Q: Serialize, T: DeserializeOwned>(&self, path: &str, params: &Q) -> Result<T> {
+        let response = self
+            .http
+            .get(format!("{}{}", self.base_url, path))
+            .query(&query_pairs(params))
+            .send()
+            .await?;
+        parse_response(response).await
+    }
+
+    async fn post<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T> {
+        let response = self
+            .http
+            .post(format!("{}{}", self.base_url, path))
+            .json(body)
+            .send()
+            .await?;
+        parse_response(response).await
+    }
+}
+
+async fn parse_response<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response.json().await?);
+    }
+
+    let message = response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|body| body.get("error").and_then(|e| e.as_str()).map(str::to_string))
+        .unwrap_or_else(|| status.to_string());
+
+    Err(ApiError::Api {
+        status: status.as_u16(),
+        message,
+    })
+}
+
+/// Flatten query params the way the API parses them: unset fields are left out
+/// and lists become repeated keys (blue_sparks=10&blue_sparks=23)
+fn query_pairs<Q: Serialize>(params: &Q) -> Vec<(String, String)> {
+    let serde_json::Value::Object(fields) = serde_json::to_value(params).unwrap_or_default() else {
+        return Vec::new();
+    };
+
+    let mut pairs = Vec::new();
+    for (key, value) in fields {
+        match value {
+            serde_json::Value::Null => {}
+            serde_json::Value::Array(items) => {
+                pairs.extend(items.into_iter().map(|item| (key.clone(), scalar_to_string(item))))
+            }
+            value => pairs.push((key, scalar_to_string(value))),
+        }
+    }
+    pairs
+}
+
+fn scalar_to_string(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
@@ -38,3 +38,31 @@ where
 
     deserializer.deserialize_any(StringOrVec)
 }
+
+/// Trainer IDs are 9-12 ASCII digits
+pub(crate) fn validate_account_id<T: AsRef<str>>(
+    account_id: &T,
+) -> Result<(), validator::ValidationError> {
+    let account_id = account_id.as_ref().trim();
+    if (9..=12).contains(&account_id.len()) && account_id.chars().all(|c| c.is_ascii_digit()) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("account_id")
+            .with_message("must be 9-12 digits".into()))
+    }
+}
+
+/// Spark filters are groups of comma-separated spark IDs ("10101,10102");
+/// empty entries are allowed and ignored
+pub(crate) fn validate_spark_groups(groups: &[String]) -> Result<(), validator::ValidationError> {
+    let invalid = groups
+        .iter()
+        .flat_map(|group| group.split(','))
+        .map(str::trim)
+        .find(|spark| !spark.is_empty() && spark.parse::<i32>().is_err());
+    match invalid {
+        Some(spark) => Err(validator::ValidationError::new("spark")
+            .with_message(format!("'{}' is not a spark ID", spark).into())),
+        None => Ok(()),
+    }
+}
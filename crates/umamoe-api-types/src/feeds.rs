@@ -0,0 +1,73 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ids::{AccountId, CharaId};
+
+/// Thresholds a record has to meet to appear in the notable feed
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct NotabilityRule {
+    pub rule_id: String,
+    pub label: String,
+    pub min_blue_stars: Option<i32>,
+    pub min_pink_stars: Option<i32>,
+    pub min_green_stars: Option<i32>,
+    pub min_white_stars: Option<i32>,
+    pub min_win_count: Option<i32>,
+    pub enabled: bool,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct NotabilityRuleRequest {
+    #[validate(length(min = 1, max = 64))]
+    pub rule_id: String,
+    #[validate(length(min = 1, max = 100))]
+    pub label: String,
+    #[validate(range(min = 0))]
+    pub min_blue_stars: Option<i32>,
+    #[validate(range(min = 0))]
+    pub min_pink_stars: Option<i32>,
+    #[validate(range(min = 0))]
+    pub min_green_stars: Option<i32>,
+    #[validate(range(min = 0))]
+    pub min_white_stars: Option<i32>,
+    #[validate(range(min = 0))]
+    pub min_win_count: Option<i32>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Newly ingested inheritance record matching at least one notability rule
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct NotableRecord {
+    pub account_id: AccountId,
+    pub trainer_name: Option<String>,
+    pub main_parent_id: CharaId,
+    pub win_count: i32,
+    pub blue_stars_sum: i32,
+    pub pink_stars_sum: i32,
+    /// Labels of the rules the record matched
+    pub matched_rules: Vec<String>,
+    pub ingested_at: NaiveDateTime,
+}
+
+/// Newly ingested high-rank inheritance record (new inheritances feed)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct NewInheritanceRecord {
+    pub inheritance_id: i32,
+    pub account_id: AccountId,
+    pub trainer_name: Option<String>,
+    pub main_parent_id: CharaId,
+    pub parent_rank: i32,
+    pub win_count: i32,
+    pub white_count: i32,
+    pub ingested_at: NaiveDateTime,
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Typed identifiers. The database stores these as plain TEXT/BIGINT/INT columns
+// (and several joins cast between them), so each newtype is transparent for
+// both serde and sqlx - the wrapper only exists to stop mixing them up in Rust.
+
+/// Trainer account ID (the 9-12 digit in-game trainer ID, stored as TEXT)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[serde(transparent)]
+#[cfg_attr(feature = "sqlx", sqlx(transparent))]
+pub struct AccountId(pub String);
+
+/// Viewer ID used by circle data (same number as the account ID, stored as BIGINT)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[serde(transparent)]
+#[cfg_attr(feature = "sqlx", sqlx(transparent))]
+pub struct ViewerId(pub i64);
+
+/// Circle ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[serde(transparent)]
+#[cfg_attr(feature = "sqlx", sqlx(transparent))]
+pub struct CircleId(pub i64);
+
+/// Character ID - either a base character (1007) or a card/outfit variant (100701)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[serde(transparent)]
+#[cfg_attr(feature = "sqlx", sqlx(transparent))]
+pub struct CharaId(pub i32);
+
+/// Support card ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[serde(transparent)]
+#[cfg_attr(feature = "sqlx", sqlx(transparent))]
+pub struct CardId(pub i32);
+
+/// A trained character in a trainer's roster (not a CharaId - the same
+/// character trained twice has two of these)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[serde(transparent)]
+#[cfg_attr(feature = "sqlx", sqlx(transparent))]
+pub struct TrainedCharaId(pub i32);
+
+impl AccountId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The same trainer as a circle viewer ID, if the account ID is numeric
+    pub fn to_viewer_id(&self) -> Option<ViewerId> {
+        self.0.parse().ok().map(ViewerId)
+    }
+}
+
+impl ViewerId {
+    /// The same trainer as a (TEXT) account ID
+    pub fn to_account_id(self) -> AccountId {
+        AccountId(self.0.to_string())
+    }
+}
+
+impl CharaId {
+    /// Strip the outfit suffix from a card variant (100701 -> 1007)
+    pub fn base(self) -> CharaId {
+        if self.0 > 100000 {
+            CharaId(self.0 / 100)
+        } else {
+            self
+        }
+    }
+}
+
+impl From<String> for AccountId {
+    fn from(value: String) -> Self {
+        AccountId(value)
+    }
+}
+
+impl From<&str> for AccountId {
+    fn from(value: &str) -> Self {
+        AccountId(value.to_string())
+    }
+}
+
+impl AsRef<str> for AccountId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for AccountId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Display for ViewerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for CircleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for CharaId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for TrainedCharaId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for CardId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ids::{AccountId, CardId, CharaId, CircleId, ViewerId};
+
+/// Who wrote a batch of rows; stored as the rows' `source` provenance
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct IngestSource {
+    #[validate(length(min = 1, max = 128))]
+    pub worker_id: String,
+    /// Game server the data was scraped from (e.g. "jp")
+    #[validate(length(max = 32))]
+    pub server: Option<String>,
+    /// Scrape batch, so a bad batch can be rolled back (see /api/admin/ingest/rollback)
+    #[validate(length(min = 1, max = 128))]
+    pub batch_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct TrainerIngest {
+    pub account_id: AccountId,
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    pub follower_num: Option<i32>,
+    #[validate(length(max = 64))]
+    pub status: Option<String>,
+    /// In-game profile comment; only matched against pending deletion and
+    /// claim requests (see /api/privacy and /api/tasks/claims), never stored
+    #[serde(default)]
+    #[validate(length(max = 512))]
+    pub comment: Option<String>,
+}
+
+/// Trainers to upsert, up to 500 per request (as for the other ingest requests)
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TrainerIngestRequest {
+    #[validate(nested)]
+    pub source: IngestSource,
+    #[validate(length(min = 1, max = 500), nested)]
+    pub trainers: Vec<TrainerIngest>,
+}
+
+/// A trainer's inheritance (legacy parent) record; sparks and factors use the
+/// v1 encoding, the v2 columns are derived from them
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct InheritanceIngest {
+    pub account_id: AccountId,
+    pub main_parent_id: CharaId,
+    pub parent_left_id: CharaId,
+    pub parent_right_id: CharaId,
+    #[validate(range(min = 0))]
+    pub parent_rank: i32,
+    #[validate(range(min = 0))]
+    pub parent_rarity: i32,
+    #[serde(default)]
+    pub blue_sparks: Vec<i32>,
+    #[serde(default)]
+    pub pink_sparks: Vec<i32>,
+    #[serde(default)]
+    pub green_sparks: Vec<i32>,
+    #[serde(default)]
+    pub white_sparks: Vec<i32>,
+    #[validate(range(min = 0))]
+    pub win_count: i32,
+    #[validate(range(min = 0))]
+    pub white_count: i32,
+    pub main_blue_factors: i32,
+    pub main_pink_factors: i32,
+    pub main_green_factors: i32,
+    #[serde(default)]
+    pub main_white_factors: Vec<i32>,
+    #[validate(range(min = 0))]
+    pub main_white_count: i32,
+    #[serde(default)]
+    pub blue_stars_sum: i32,
+    #[serde(default)]
+    pub pink_stars_sum: i32,
+    #[serde(default)]
+    pub green_stars_sum: i32,
+    #[serde(default)]
+    pub white_stars_sum: i32,
+    #[serde(default)]
+    pub affinity_scores: Option<Vec<i32>>,
+    #[serde(default)]
+    pub base_affinity: i32,
+    #[serde(default)]
+    pub race_affinity: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct InheritanceIngestRequest {
+    #[validate(nested)]
+    pub source: IngestSource,
+    #[validate(length(min = 1, max = 500), nested)]
+    pub records: Vec<InheritanceIngest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SupportCardIngest {
+    pub account_id: AccountId,
+    pub support_card_id: CardId,
+    #[validate(range(min = 0, max = 4))]
+    pub limit_break_count: Option<i32>,
+    #[validate(range(min = 0))]
+    pub experience: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SupportCardIngestRequest {
+    #[validate(nested)]
+    pub source: IngestSource,
+    #[validate(length(min = 1, max = 500), nested)]
+    pub cards: Vec<SupportCardIngest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CircleIngest {
+    pub circle_id: CircleId,
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    #[validate(length(max = 512))]
+    pub comment: Option<String>,
+    pub leader_viewer_id: Option<ViewerId>,
+    #[validate(range(min = 0))]
+    pub member_count: Option<i32>,
+    pub join_style: Option<i32>,
+    pub policy: Option<i32>,
+    pub monthly_rank: Option<i32>,
+    pub monthly_point: Option<i64>,
+    pub last_month_rank: Option<i32>,
+    pub last_month_point: Option<i64>,
+}
+
+/// One member's fan counts for a month (one entry per day so far)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CircleMemberIngest {
+    pub viewer_id: ViewerId,
+    #[validate(range(min = 2020, max = 2100))]
+    pub year: i32,
+    #[validate(range(min = 1, max = 12))]
+    pub month: i32,
+    #[validate(length(max = 31))]
+    pub daily_fans: Vec<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CircleIngestRequest {
+    #[validate(nested)]
+    pub source: IngestSource,
+    #[validate(nested)]
+    pub circle: CircleIngest,
+    #[serde(default)]
+    #[validate(length(max = 30), nested)]
+    pub members: Vec<CircleMemberIngest>,
+}
+
+/// A member's monthly fan counts in a bulk load, with its circle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkCircleMemberRow {
+    pub circle_id: CircleId,
+    pub viewer_id: ViewerId,
+    pub year: i32,
+    pub month: i32,
+    pub daily_fans: Vec<i64>,
+}
+
+/// Member rows of many circles at once, up to 20000; rows are checked one by
+/// one and bad ones reported instead of failing the whole load
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct BulkCircleMembersRequest {
+    #[validate(nested)]
+    pub source: IngestSource,
+    #[validate(length(min = 1, max = 20000))]
+    pub members: Vec<BulkCircleMemberRow>,
+}
+
+/// Why a row of a bulk load was skipped; `index` is its position in the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestRowError {
+    pub index: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BulkIngestResponse {
+    pub inserted: u64,
+    pub updated: u64,
+    pub failed: usize,
+    pub errors: Vec<IngestRowError>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IngestResponse {
+    pub inserted: u64,
+    pub updated: u64,
+    /// Rows left out because the trainer's data was deleted on request
+    #[serde(default)]
+    pub skipped: u64,
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ids::{AccountId, CharaId};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Inheritance {
+    pub inheritance_id: i32,
+    pub account_id: AccountId,
+    pub main_parent_id: CharaId,
+    pub parent_left_id: CharaId,
+    pub parent_right_id: CharaId,
+    pub parent_rank: i32,
+    pub parent_rarity: i32,
+    pub blue_sparks: Vec<i32>,
+    pub pink_sparks: Vec<i32>,
+    pub green_sparks: Vec<i32>,
+    pub white_sparks: Vec<i32>,
+    pub win_count: i32,
+    pub white_count: i32,
+    pub main_blue_factors: i32,
+    pub main_pink_factors: i32,
+    pub main_green_factors: i32,
+    pub main_white_factors: Vec<i32>,
+    pub main_white_count: i32,
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub blue_stars_sum: i32,
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub pink_stars_sum: i32,
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub green_stars_sum: i32,
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub white_stars_sum: i32,
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub affinity_score: Option<i32>,
+    /// Decayed upvotes minus reports; only set when searching with sort_by=community_score
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub community_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "text", rename_all = "lowercase"))]
+#[serde(rename_all = "lowercase")]
+pub enum InheritanceVoteKind {
+    /// A good record
+    Up,
+    /// Wrong, outdated or abusive data; counts against the record
+    Report,
+}
+
+impl InheritanceVoteKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InheritanceVoteKind::Up => "up",
+            InheritanceVoteKind::Report => "report",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct InheritanceVoteRequest {
+    pub kind: InheritanceVoteKind,
+    /// Why the record is reported
+    #[validate(length(max = 200))]
+    pub reason: Option<String>,
+}
+
+/// A record's community votes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct InheritanceVoteSummary {
+    pub inheritance_id: i32,
+    pub upvotes: i32,
+    pub reports: i32,
+    /// Upvotes minus weighted reports, each decayed by its age
+    pub community_score: f64,
+    /// The caller's current vote
+    #[cfg_attr(feature = "sqlx", sqlx(default))]
+    pub your_vote: Option<InheritanceVoteKind>,
+}
@@ -0,0 +1,82 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use super::ids::AccountId;
+
+/// What a global leaderboard ranks trainers by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardKind {
+    /// Win count of the trainer's inheritance record
+    WinCount,
+    /// White sparks on the trainer's inheritance record
+    WhiteCount,
+    /// White sparks on the record's main parent
+    MainWhiteCount,
+    /// Days in a row the trainer had room on their friend list
+    AvailabilityStreak,
+}
+
+impl LeaderboardKind {
+    pub const ALL: [LeaderboardKind; 4] = [
+        LeaderboardKind::WinCount,
+        LeaderboardKind::WhiteCount,
+        LeaderboardKind::MainWhiteCount,
+        LeaderboardKind::AvailabilityStreak,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaderboardKind::WinCount => "win_count",
+            LeaderboardKind::WhiteCount => "white_count",
+            LeaderboardKind::MainWhiteCount => "main_white_count",
+            LeaderboardKind::AvailabilityStreak => "availability_streak",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == kind)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaderboardParams {
+    /// Snapshot day (default: the latest snapshot)
+    pub date: Option<NaiveDate>,
+    /// Day to compute rank changes against, e.g. a week earlier; the latest
+    /// snapshot on or before it is used (default: the snapshot before `date`)
+    pub compare_to: Option<NaiveDate>,
+    /// Entries to return (1-100, default 100)
+    pub limit: Option<i64>,
+}
+
+/// A trainer's place on a leaderboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct LeaderboardEntry {
+    pub rank: i32,
+    /// Rank in the compared snapshot (None if the trainer wasn't on it)
+    pub previous_rank: Option<i32>,
+    /// Places gained since the compared snapshot (negative when dropping)
+    pub rank_delta: Option<i32>,
+    pub account_id: AccountId,
+    pub trainer_name: Option<String>,
+    pub follower_num: Option<i32>,
+    /// The ranked record (not set for availability_streak)
+    pub inheritance_id: Option<i32>,
+    pub main_parent_id: Option<i32>,
+    /// The ranked value: wins, white sparks or streak days
+    pub value: i64,
+    /// Change of value since the compared snapshot
+    pub value_delta: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaderboardResponse {
+    pub kind: LeaderboardKind,
+    pub snapshot_date: NaiveDate,
+    /// The snapshot rank changes are computed against
+    pub compared_to: Option<NaiveDate>,
+    pub computed_at: NaiveDateTime,
+    pub entries: Vec<LeaderboardEntry>,
+}
@@ -0,0 +1,59 @@
+//! Request and response types of the honse.moe / uma.moe API.
+//!
+//! Shared by the backend and Rust consumers such as the Discord bot. Enable the
+//! `client` feature for a thin reqwest-based client.
+
+mod analytics;
+mod announcements;
+mod api_keys;
+mod bans;
+mod characters;
+mod circles;
+mod common;
+mod feeds;
+mod ids;
+mod ingest;
+mod inheritance;
+mod leaderboards;
+mod matchmaking;
+mod moderation;
+mod privacy;
+mod provenance;
+mod retention;
+mod search;
+mod sparks;
+mod stats;
+mod support_cards;
+mod tasks;
+mod team_stadium;
+mod users;
+mod workers;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+// Re-export everything from each module except common (items from common are imported directly where needed)
+pub use analytics::*;
+pub use announcements::*;
+pub use api_keys::*;
+pub use bans::*;
+pub use characters::*;
+pub use circles::*;
+pub use feeds::*;
+pub use ids::*;
+pub use ingest::*;
+pub use inheritance::*;
+pub use leaderboards::*;
+pub use matchmaking::*;
+pub use moderation::*;
+pub use privacy::*;
+pub use provenance::*;
+pub use retention::*;
+pub use search::*;
+pub use sparks::*;
+pub use stats::*;
+pub use support_cards::*;
+pub use tasks::*;
+pub use team_stadium::*;
+pub use users::*;
+pub use workers::*;
@@ -0,0 +1,58 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ids::{AccountId, CharaId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "text", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum MatchmakingStatus {
+    /// No matching trainer is free yet; polling the ticket tries again
+    Waiting,
+    /// A trainer is reserved to the ticket
+    Matched,
+}
+
+/// Body of opening a matchmaking ticket
+#[derive(Debug, Deserialize, Validate)]
+pub struct MatchmakingRequest {
+    /// Wanted main parent: a base character (1007) or a specific card (100701)
+    pub main_parent_id: CharaId,
+    #[validate(range(min = 1))]
+    pub min_parent_rank: Option<i32>,
+    #[validate(range(min = 0))]
+    pub min_win_count: Option<i32>,
+}
+
+/// The trainer suggested to a ticket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct MatchmakingSuggestion {
+    pub account_id: AccountId,
+    pub trainer_name: Option<String>,
+    pub follower_num: Option<i32>,
+    pub inheritance_id: i32,
+    pub main_parent_id: CharaId,
+    pub parent_rank: i32,
+    pub win_count: i32,
+    pub white_count: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct MatchmakingTicket {
+    pub ticket_id: String,
+    pub status: MatchmakingStatus,
+    pub main_parent_id: CharaId,
+    pub min_parent_rank: Option<i32>,
+    pub min_win_count: Option<i32>,
+    /// Set while matched
+    #[cfg_attr(feature = "sqlx", sqlx(skip))]
+    pub suggestion: Option<MatchmakingSuggestion>,
+    /// The suggestion is held for this ticket until then
+    pub reserved_until: Option<NaiveDateTime>,
+    /// The ticket is dropped after this time unless used again
+    pub expires_at: NaiveDateTime,
+}
@@ -0,0 +1,101 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ids::AccountId;
+
+/// What a content report is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "text", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum ContentReportKind {
+    /// A trainer's name (target_id: account ID)
+    TrainerName,
+    /// A circle's name (target_id: circle ID)
+    CircleName,
+    /// A circle's comment (target_id: circle ID)
+    CircleComment,
+}
+
+impl ContentReportKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentReportKind::TrainerName => "trainer_name",
+            ContentReportKind::CircleName => "circle_name",
+            ContentReportKind::CircleComment => "circle_comment",
+        }
+    }
+}
+
+/// Body of reporting offensive text
+#[derive(Debug, Deserialize, Validate)]
+pub struct ContentReportRequest {
+    pub target_kind: ContentReportKind,
+    #[validate(length(min = 1, max = 20))]
+    pub target_id: String,
+    #[validate(length(max = 500))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerationQueueParams {
+    /// open (default), actioned or dismissed
+    pub status: Option<String>,
+    pub page: Option<i64>,
+    /// Targets per page (max 100, default 50)
+    pub limit: Option<i64>,
+}
+
+/// A reported text with its reports
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct ModerationQueueItem {
+    pub target_kind: ContentReportKind,
+    pub target_id: String,
+    /// The text as it comes from the game (None if the target is gone)
+    pub current_text: Option<String>,
+    /// The name or comment shown in its place, if already overridden
+    pub override_text: Option<String>,
+    pub reports: i64,
+    /// Reasons given, newest first
+    pub reasons: Vec<String>,
+    pub first_reported_at: NaiveDateTime,
+    pub last_reported_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    /// Replace the text everywhere it is shown
+    Mask,
+    /// Close the reports and leave the text as is
+    Dismiss,
+}
+
+/// Body of resolving the open reports on a target
+#[derive(Debug, Deserialize, Validate)]
+pub struct ModerationResolveRequest {
+    pub target_kind: ContentReportKind,
+    #[validate(length(min = 1, max = 20))]
+    pub target_id: String,
+    pub action: ModerationAction,
+    /// Shown instead when masking (default: asterisks; a masked comment is
+    /// hidden instead)
+    #[validate(length(min = 1, max = 200))]
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TrainerModerationOverride {
+    pub account_id: AccountId,
+    pub name: Option<String>,
+    pub display_name_override: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TrainerModerationRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub display_name_override: String,
+}
@@ -0,0 +1,28 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::ids::AccountId;
+
+/// A started deletion request: the code goes into the trainer's in-game comment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyVerificationResponse {
+    pub account_id: AccountId,
+    pub code: String,
+    pub expires_at: NaiveDateTime,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyDeletionRequest {
+    /// The code issued for this trainer
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyDeletionResponse {
+    pub account_id: AccountId,
+    pub deleted_at: NaiveDateTime,
+    /// Rows removed per table
+    pub removed: BTreeMap<String, u64>,
+}
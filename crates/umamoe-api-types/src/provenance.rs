@@ -0,0 +1,61 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ids::{AccountId, CardId};
+
+/// Where a stored record came from: {"worker_id", "server", "batch_id"} as written by the scraper
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct RecordProvenance {
+    pub source: Option<serde_json::Value>,
+    pub ingested_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct SupportCardProvenance {
+    pub support_card_id: CardId,
+    pub source: Option<serde_json::Value>,
+    pub ingested_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrainerProvenance {
+    pub account_id: AccountId,
+    pub inheritance: Option<RecordProvenance>,
+    pub support_cards: Vec<SupportCardProvenance>,
+}
+
+/// Rows written by one scrape batch
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct BatchProvenanceSummary {
+    pub batch_id: String,
+    pub inheritance_rows: i64,
+    pub support_card_rows: i64,
+    pub first_ingested_at: Option<NaiveDateTime>,
+    pub last_ingested_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct IngestRollbackRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub batch_id: String,
+    /// Report counts without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IngestRollbackResponse {
+    pub batch_id: String,
+    pub dry_run: bool,
+    /// Rows reverted to the version the batch replaced
+    pub inheritance_restored: u64,
+    /// Rows the batch created, moved to record_versions
+    pub inheritance_removed: u64,
+    pub support_cards_restored: u64,
+    pub support_cards_removed: u64,
+    pub affected_trainers: usize,
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Rows changed by one run of the trainer retention job
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionRunResult {
+    /// Trainers newly flagged stale
+    pub flagged: u64,
+    /// Stale trainers that fell back within the retention window
+    pub restored: u64,
+    pub duration_ms: u64,
+}
+
+/// Trainer retention policy and what the job has done since this server started
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionStatus {
+    /// Months without an update before a trainer is flagged (None: retention off)
+    pub retention_months: Option<u32>,
+    /// Trainers currently flagged stale
+    pub stale_trainers: i64,
+    pub last_run_at: Option<chrono::NaiveDateTime>,
+    pub last_run: Option<RetentionRunResult>,
+    /// Error of the last run, if it failed
+    pub last_error: Option<String>,
+    pub total_flagged: u64,
+    pub total_restored: u64,
+}
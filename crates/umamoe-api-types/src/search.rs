@@ -1,6 +1,8 @@
-use crate::models::common::deserialize_vec_string_from_query;
+use crate::common::{deserialize_vec_string_from_query, validate_account_id, validate_spark_groups};
+use crate::ids::{AccountId, CardId, CharaId};
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResponse<T> {
@@ -12,34 +14,40 @@ pub struct SearchResponse<T> {
 }
 
 // V3 Search API models
-#[allow(dead_code)] // Some fields are parsed for API compatibility but not yet used
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Validate)]
 pub struct UnifiedSearchParams {
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub page: Option<i64>,
+    /// Values over 100 are capped at 100
     #[serde(default)]
+    #[validate(range(min = 1))]
     pub limit: Option<i64>,
     #[serde(default)]
-    pub search_type: Option<String>, // "inheritance", "support_cards", or "all" (default)
+    pub search_type: Option<String>, // "inheritance", "support_cards", "combined" (card + main parent on the same account) or "all" (default)
 
     // Inheritance filtering
     #[serde(default)]
-    pub main_parent_id: Option<i32>,
+    pub main_parent_id: Option<CharaId>,
     #[serde(default)]
-    pub parent_left_id: Option<i32>,
+    pub parent_left_id: Option<CharaId>,
     #[serde(default)]
-    pub parent_right_id: Option<i32>,
+    pub parent_right_id: Option<CharaId>,
     #[serde(default)]
     pub parent_rank: Option<i32>,
     #[serde(default)]
     pub parent_rarity: Option<i32>,
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub blue_sparks: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub pink_sparks: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub green_sparks: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub white_sparks: Vec<String>,
     // 9-star spark filtering (searches across all stat types)
     #[serde(default)]
@@ -50,73 +58,102 @@ pub struct UnifiedSearchParams {
     pub green_sparks_9star: Option<bool>,
     // Main parent spark filtering
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub main_parent_blue_sparks: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub main_parent_pink_sparks: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub main_parent_green_sparks: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub main_parent_white_sparks: Vec<String>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_win_count: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_white_count: Option<i32>,
 
     // Star sum filtering
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_blue_stars_sum: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub max_blue_stars_sum: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_pink_stars_sum: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub max_pink_stars_sum: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_green_stars_sum: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub max_green_stars_sum: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_white_stars_sum: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub max_white_stars_sum: Option<i32>,
 
     // Main inherit filtering
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_main_blue_factors: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_main_pink_factors: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_main_green_factors: Option<i32>,
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub main_white_factors: Vec<String>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_main_white_count: Option<i32>,
 
     // Optional white skill scoring (soft preference, not hard filter)
     // These take skill TYPE IDs only (factor_id), not encoded values
     // Scoring: COUNT(DISTINCT types) * 100 + SUM(levels) - prioritizes more matches over higher levels
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub optional_white_sparks: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_vec_string_from_query")]
+    #[validate(custom(function = "validate_spark_groups"))]
     pub optional_main_white_factors: Vec<String>,
 
     // Support card filtering
     #[serde(default)]
-    pub support_card_id: Option<i32>,
+    pub support_card_id: Option<CardId>,
     #[serde(default)]
+    #[validate(range(min = 0, max = 4))]
     pub min_limit_break: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0, max = 4))]
     pub max_limit_break: Option<i32>,
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub min_experience: Option<i32>,
 
     // Common filtering
     #[serde(default)]
-    pub trainer_id: Option<String>, // Direct trainer ID lookup
+    #[validate(custom(function = "validate_account_id"))]
+    pub trainer_id: Option<AccountId>, // Direct trainer ID lookup
     #[serde(default)]
     pub trainer_name: Option<String>, // Trainer name search
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub max_follower_num: Option<i32>,
+    /// Include trainers flagged stale by the retention job (always included for trainer_id lookups)
+    #[serde(default)]
+    pub include_stale: bool,
     #[serde(default)]
     pub sort_by: Option<String>,
     #[serde(default)]
@@ -124,21 +161,46 @@ pub struct UnifiedSearchParams {
 
     // Affinity calculation
     #[serde(default)]
-    pub player_chara_id: Option<i32>, // Character ID for affinity score calculation (p0)
+    pub player_chara_id: Option<CharaId>, // Character ID for affinity score calculation (p0)
     #[serde(default)]
-    pub player_chara_id_2: Option<i32>, // Second character ID for dual-parent training (p2)
+    pub player_chara_id_2: Option<CharaId>, // Second character ID for dual-parent training (p2)
 
     // Desired main character filter
     #[serde(default)]
-    pub desired_main_chara_id: Option<i32>, // Filter inheritances where main parent is this character (p0 parent)
+    pub desired_main_chara_id: Option<CharaId>, // Filter inheritances where main parent is this character (p0 parent)
+
+    // Affinity formula version (defaults to AFFINITY_VERSION)
+    #[serde(default)]
+    pub affinity_version: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnifiedAccountRecord {
-    pub account_id: String,
+    pub account_id: AccountId,
     pub trainer_name: String,
     pub follower_num: Option<i32>,
     pub last_updated: Option<NaiveDateTime>,
     pub inheritance: Option<super::inheritance::Inheritance>,
     pub support_card: Option<super::support_cards::SupportCard>, // Single best support card, not array
 }
+
+/// One frozen row of a search snapshot
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchSnapshotEntry {
+    pub rank: i64,
+    pub account_id: AccountId,
+    pub trainer_name: String,
+    pub inheritance_id: Option<i32>,
+    pub affinity_score: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct SearchSnapshot {
+    pub snapshot_id: String,
+    pub query: String,
+    pub total: String,
+    #[cfg_attr(feature = "sqlx", sqlx(json))]
+    pub entries: Vec<SearchSnapshotEntry>,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Progress and consistency of the v2 spark encoding rollout
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SparkEncodingStatus {
+    /// Encoding searches currently run against ("v1" or "v2")
+    pub active_encoding: String,
+    pub total_rows: i64,
+    /// Rows whose v2 columns have not been backfilled yet
+    pub pending_backfill: i64,
+    /// Backfilled rows compared against their v1 columns
+    pub checked_rows: i64,
+    /// Checked rows whose v2 values differ from the converted v1 values
+    pub mismatched_rows: i64,
+    /// Up to 20 inheritance IDs of mismatched rows
+    pub mismatched_ids: Vec<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SparkEncodingStatusParams {
+    /// Most recent backfilled rows to compare (default 10000)
+    #[validate(range(min = 1, max = 1000000))]
+    pub sample: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SparkBackfillRequest {
+    /// Rows per UPDATE (default 1000)
+    #[validate(range(min = 1, max = 10000))]
+    pub batch_size: Option<i64>,
+    /// Upper bound on rows backfilled by this request (default 100000)
+    #[validate(range(min = 1))]
+    pub max_rows: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SparkBackfillResponse {
+    pub updated: i64,
+    pub batches: u32,
+    /// Rows still waiting for backfill
+    pub remaining: i64,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SparkCompareRequest {
+    /// Search query string, exactly as sent to /api/v3/search
+    #[validate(length(max = 4096))]
+    pub query: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SparkCompareResponse {
+    pub query: String,
+    pub v1_count: i64,
+    pub v2_count: i64,
+    pub matches: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SparkEncodingRequest {
+    /// "v1" or "v2"
+    pub encoding: String,
+    /// Switch to v2 even though the backfill is incomplete
+    #[serde(default)]
+    pub force: bool,
+}
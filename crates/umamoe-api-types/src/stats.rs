@@ -0,0 +1,368 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ids::CharaId;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatsResponse {
+    pub today: TodayStats,
+    pub rolling_averages: RollingStats,
+    pub daily_data: Vec<DailyStatsResponse>,
+    pub totals: TotalStats,
+    /// Refresh state of the materialized views behind these numbers
+    #[serde(default)]
+    pub views: Vec<MaterializedViewStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaterializedViewStatus {
+    pub view: String,
+    pub refresh_interval_secs: u64,
+    /// Last successful refresh by this server (None until the first one)
+    pub last_refreshed_at: Option<chrono::NaiveDateTime>,
+    /// Error of the last attempt, if it failed
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ViewRefreshParams {
+    /// Refresh only this view (default: all)
+    pub view: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ViewRefreshResponse {
+    pub views: Vec<MaterializedViewStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UploadStatsParams {
+    /// "day" (default) or "week"
+    pub granularity: Option<String>,
+    /// How many buckets back from the current one (default 30 days / 12 weeks)
+    #[validate(range(min = 1, max = 365))]
+    pub buckets: Option<i32>,
+}
+
+/// Response of GET /api/stats/uploads
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadStatsResponse {
+    pub granularity: String,
+    /// Oldest bucket first, including empty ones
+    pub buckets: Vec<UploadBucket>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct UploadBucket {
+    /// First day of the bucket (Monday for weeks)
+    pub bucket_start: chrono::NaiveDate,
+    /// Newly tracked inheritance rows
+    pub inheritance: i64,
+    /// Newly tracked support_card rows
+    pub support_cards: i64,
+}
+
+/// Message pushed by the GET /api/stats/live WebSocket
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LiveStats {
+    /// Counts over the last 60 seconds, as seen by this server instance
+    pub searches_per_min: u64,
+    pub tasks_completed_per_min: u64,
+    /// Trainer IDs queued for their first lookup
+    pub new_trainers_per_min: u64,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// Response of GET /api/admin/db-stats
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DbStatsResponse {
+    pub database_bytes: i64,
+    /// User tables, largest first
+    pub tables: Vec<TableStats>,
+    pub indexes: Vec<IndexStats>,
+    pub connections: ConnectionStats,
+    /// Slowest statements by mean time (None when pg_stat_statements isn't installed)
+    pub slow_queries: Option<Vec<SlowQueryStats>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TableStats {
+    pub table_name: String,
+    /// Row count from the statistics collector (approximate, no COUNT(*))
+    pub live_rows: i64,
+    pub dead_rows: i64,
+    /// Share of dead tuples - a cheap bloat estimate until the next (auto)vacuum
+    pub dead_ratio: f64,
+    pub table_bytes: i64,
+    pub index_bytes: i64,
+    pub total_bytes: i64,
+    pub last_autovacuum: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_autoanalyze: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct IndexStats {
+    pub index_name: String,
+    pub table_name: String,
+    pub index_bytes: i64,
+    pub scans: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConnectionStats {
+    /// Connections held by this server's pool
+    pub pool_size: u32,
+    pub pool_idle: u32,
+    pub pool_max: u32,
+    /// Backends connected to this database, by state (active, idle, ...)
+    pub backends: std::collections::BTreeMap<String, i64>,
+    pub max_connections: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct SlowQueryStats {
+    pub query: String,
+    pub calls: i64,
+    pub total_exec_ms: f64,
+    pub mean_exec_ms: f64,
+    pub rows: i64,
+}
+
+/// Response of GET /api/admin/cache
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheStatsResponse {
+    pub entry_count: usize,
+    pub total_size_bytes: usize,
+    /// Expired entries not yet removed by the cleanup task
+    pub expired_count: usize,
+    /// Lookup counters since startup
+    pub hits: u64,
+    pub misses: u64,
+    pub sets: u64,
+    pub hit_rate: f64,
+    /// Stale entries served while a background refresh replaced them
+    pub stale_hits: u64,
+    pub revalidations: u64,
+    /// Misses that waited for a concurrent identical load instead of querying again
+    pub coalesced: u64,
+    /// Runs of the background cleanup task and the expired entries it removed
+    pub cleanup_runs: u64,
+    pub expired_removed: u64,
+    /// Largest entries first
+    pub top_keys: Vec<CacheKeyStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheKeyStats {
+    pub key: String,
+    pub size_bytes: usize,
+    pub expires_in_secs: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CacheKeysParams {
+    /// Number of top keys to return (default 20)
+    #[validate(range(min = 1, max = 1000))]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheInvalidateParams {
+    /// Only remove keys in this namespace, e.g. "search" or "count"
+    pub namespace: Option<String>,
+    /// Only remove keys starting with this (default: everything)
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheInvalidateResponse {
+    pub removed: usize,
+}
+
+/// Turnstile verification outcomes and circuit breaker state (per instance, since startup)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TurnstileStats {
+    pub breaker_open: bool,
+    pub breaker_open_until: Option<chrono::DateTime<chrono::Utc>>,
+    pub consecutive_failures: u32,
+    pub breaker_trips: u64,
+    /// Whether known-good clients are let through while verification is unavailable
+    pub fail_open: bool,
+    pub verified: u64,
+    pub rejected: u64,
+    /// Verifications that failed after all retries
+    pub errors: u64,
+    pub fail_open_allowed: u64,
+    /// Calls made to Cloudflare (including retries) and their latency
+    pub calls: u64,
+    pub avg_latency_ms: f64,
+    pub max_latency_ms: u64,
+    pub known_good_ips: usize,
+}
+
+/// One row of the admin audit log
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct AdminAuditEntry {
+    pub id: i64,
+    pub action: String,
+    pub details: serde_json::Value,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AdminAuditLogParams {
+    /// Only entries with this action, e.g. "tasks.requeue" or "admin.request"
+    pub action: Option<String>,
+    /// Only entries older than this ID (for paging back)
+    pub before_id: Option<i64>,
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TodayStats {
+    pub total_visitors: i32,
+    pub unique_visitors: i32,
+    pub inheritance_uploads: i32,
+    pub total_inheritance_records: i32,
+    pub total_support_card_records: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct DailyStatsResponse {
+    pub date: chrono::NaiveDate,
+    pub total_visits: i64,
+    pub unique_visitors: i64,
+    pub inheritance_uploads: i64,
+    pub support_card_uploads: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotalStats {
+    pub total_records: i64,
+    pub inheritance_records: i64,
+    pub support_card_records: i64,
+    pub total_votes: i64,
+    pub total_visitors: i64,
+    pub total_accounts_tracked: i64,
+    pub total_circles_tracked: i64,
+    pub total_characters: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RollingStats {
+    pub visitors_7_day: f64,
+    pub visitors_30_day: f64,
+    pub unique_visitors_7_day: f64,
+    pub unique_visitors_30_day: f64,
+    pub uploads_7_day: f64,
+    pub uploads_30_day: f64,
+}
+
+/// How often a card variant is used as main parent
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CharacterCardStats {
+    pub main_parent_id: CharaId,
+    pub main_count: i64,
+    /// Average parent_rank of records with this card as main parent
+    pub avg_parent_rank: Option<f64>,
+}
+
+/// Inheritance usage of one character across all of its card variants
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CharacterStats {
+    /// Base character ID (1001, ...)
+    pub chara_id: CharaId,
+    pub name: String,
+    /// Records with this character as main parent (main_chara)
+    pub main_count: i64,
+    /// Appearances as left or right grandparent
+    pub grandparent_count: i64,
+    /// Average parent_rank of records with this character as main parent
+    pub avg_parent_rank: Option<f64>,
+    /// Main parent usage per card variant, most used first
+    pub cards: Vec<CharacterCardStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CharacterStatsResponse {
+    /// Inheritance records counted
+    pub total_records: i64,
+    /// Characters ordered by main_count, then grandparent_count
+    pub characters: Vec<CharacterStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FriendlistReportResponse {
+    pub success: bool,
+    pub message: String,
+    /// Distinct reports since the trainer was last refreshed
+    pub report_count: i64,
+    /// Whether this report queued a recheck of the trainer
+    pub recheck_queued: bool,
+}
+
+/// Friendlist-full reports for one trainer
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct FriendlistReportStats {
+    pub trainer_id: String,
+    /// Distinct reporters ever
+    pub total_reports: i64,
+    /// Distinct reports since the trainer was last refreshed
+    pub reports_since_update: i64,
+    pub last_reported_at: Option<chrono::NaiveDateTime>,
+    /// Reports since the last refresh that queue a recheck
+    pub recheck_threshold: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct TeamStadiumStatsParams {
+    /// Only teams of this class (1-6)
+    #[validate(range(min = 1, max = 6))]
+    pub team_class: Option<i32>,
+    /// Base character (1007) or a specific card (100701)
+    pub chara_id: Option<CharaId>,
+}
+
+/// Team stadium usage of one character across all of its card variants
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TeamStadiumCharacterStats {
+    /// Base character ID (1001, ...)
+    pub chara_id: CharaId,
+    #[cfg_attr(feature = "sqlx", sqlx(skip))]
+    pub name: String,
+    /// Team members using this character
+    pub usage_count: i64,
+    pub avg_rank_score: Option<f64>,
+}
+
+/// Usage within one distance or team class
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TeamStadiumBreakdown {
+    /// distance_type (1 = sprint ... 5 = dirt) or team class (1-6)
+    pub key: i32,
+    pub usage_count: i64,
+    pub avg_rank_score: Option<f64>,
+}
+
+/// Response of GET /api/stats/team-stadium
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TeamStadiumStatsResponse {
+    /// Team members matching the filters
+    pub total_members: i64,
+    /// Most used first
+    pub characters: Vec<TeamStadiumCharacterStats>,
+    /// By distance_type
+    pub distances: Vec<TeamStadiumBreakdown>,
+    /// By team class; members without a recorded class are left out
+    pub classes: Vec<TeamStadiumBreakdown>,
+}
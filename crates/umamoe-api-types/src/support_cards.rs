@@ -0,0 +1,69 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use super::ids::{AccountId, CardId, CharaId};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct SupportCard {
+    pub account_id: AccountId,
+    pub support_card_id: CardId,
+    pub limit_break_count: Option<i32>,
+    pub experience: i32,
+}
+
+/// Master data of a support card (support_card_meta table)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct SupportCardMeta {
+    pub support_card_id: CardId,
+    pub name: String,
+    /// 1 = R, 2 = SR, 3 = SSR
+    pub rarity: i32,
+    /// speed, stamina, power, guts, wit, friend or group
+    pub card_type: String,
+    pub chara_id: Option<CharaId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupportCardLeaderboardParams {
+    /// ISO week, e.g. "2026-W42" (default: the latest computed week)
+    pub week: Option<String>,
+    /// usage (default) or mlb_rate
+    pub sort_by: Option<String>,
+}
+
+/// One support card's usage and MLB standing in a week
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct SupportCardLeaderboardEntry {
+    pub support_card_id: CardId,
+    /// From support_card_meta (None until the card's master data is imported)
+    pub name: Option<String>,
+    pub rarity: Option<i32>,
+    pub card_type: Option<String>,
+    /// Trainers lending the card
+    pub owners: i32,
+    /// owners / trainers (0-1)
+    pub usage_rate: f64,
+    pub usage_rank: i32,
+    /// Owners lending it max limit broken
+    pub mlb_count: i32,
+    /// mlb_count / owners (0-1)
+    pub mlb_rate: f64,
+    /// Cards with too few owners for a meaningful rate rank after all others
+    pub mlb_rank: i32,
+    pub avg_limit_break: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupportCardLeaderboardResponse {
+    /// ISO week, e.g. "2026-W42"
+    pub week: String,
+    /// Monday of the week
+    pub week_start: NaiveDate,
+    /// Trainers lending any support card that week (the sample size)
+    pub trainers: i32,
+    pub computed_at: NaiveDateTime,
+    pub cards: Vec<SupportCardLeaderboardEntry>,
+}
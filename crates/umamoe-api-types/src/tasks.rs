@@ -0,0 +1,251 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ids::AccountId;
+use crate::common::validate_account_id;
+
+// Task priorities - workers claim lower values first
+/// Refresh requested by a trainer who verified ownership of the account
+pub const PRIORITY_CLAIMED_REFRESH: i32 = -1;
+/// Forced update after a user reported the trainer as unavailable
+pub const PRIORITY_FORCED_UPDATE: i32 = 0;
+/// New trainer ID submitted for friend search
+pub const PRIORITY_SUBMISSION: i32 = 1;
+/// Trainer ID onboarded through a bulk submission
+pub const PRIORITY_BATCH_SUBMISSION: i32 = 3;
+/// Automatic recheck triggered by copy counts
+pub const PRIORITY_RECHECK: i32 = 5;
+
+// Task-related models for background job processing
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Task {
+    pub id: i32,
+    pub task_type: String,
+    pub task_data: serde_json::Value,
+    pub priority: i32,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: Option<NaiveDateTime>,
+    pub worker_id: Option<String>,
+    pub error_message: Option<String>,
+    pub account_id: Option<AccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateTaskRequest {
+    #[validate(length(min = 1, max = 64))]
+    pub task_type: String,
+    pub task_data: serde_json::Value,
+    #[validate(range(min = 0, max = 10))]
+    pub priority: Option<i32>,
+    #[validate(custom(function = "validate_account_id"))]
+    pub account_id: Option<AccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TrainerSubmissionRequest {
+    #[validate(custom(function = "validate_account_id"))]
+    pub trainer_id: AccountId,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TrainerBatchSubmissionRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub trainer_ids: Vec<AccountId>,
+}
+
+/// Per-ID result of a bulk submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchSubmissionStatus {
+    Queued,
+    AlreadyKnown,
+    AlreadyPending,
+    DuplicateInBatch,
+    Invalid,
+    /// The trainer's data was deleted on request and isn't collected again
+    Deleted,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchSubmissionResult {
+    pub trainer_id: AccountId,
+    pub status: BatchSubmissionStatus,
+    pub task_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchSubmissionResponse {
+    pub dry_run: bool,
+    pub queued: usize,
+    pub results: Vec<BatchSubmissionResult>,
+}
+
+/// A started claim: the code goes into the trainer's in-game comment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainerClaimVerificationResponse {
+    pub trainer_id: AccountId,
+    pub code: String,
+    pub expires_at: NaiveDateTime,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainerClaimRequest {
+    /// The code issued for this trainer
+    pub code: String,
+}
+
+/// An issued claim; the token is only ever returned here
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainerClaimResponse {
+    pub trainer_id: AccountId,
+    /// Send as X-Claim-Token to POST /api/tasks/claimed-refresh/:trainer_id
+    pub claim_token: String,
+    pub verified_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TaskResponse {
+    pub id: i32,
+    pub task_type: String,
+    pub task_data: serde_json::Value,
+    pub priority: i32,
+    pub status: String,
+    pub account_id: Option<AccountId>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DryRunParams {
+    /// Run validation and dedup checks without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// What a task-creating request did (or would do in dry-run mode)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskOutcome {
+    TaskCreated,
+    Duplicate,
+    Throttled,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DryRunResponse {
+    pub dry_run: bool,
+    pub outcome: TaskOutcome,
+    pub task_type: String,
+    pub existing_task_id: Option<i32>,
+    pub message: String,
+}
+
+/// Result of a stale task sweep
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TaskReapResult {
+    /// Tasks returned to pending
+    pub requeued: u64,
+    /// Tasks that ran out of attempts and were marked failed
+    pub failed: u64,
+}
+
+/// Result of a completed task archival run
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TaskArchiveResult {
+    /// Tasks moved to tasks_archive
+    pub archived: u64,
+}
+
+/// Claim policy for a task type
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TaskTypePolicy {
+    pub task_type: String,
+    pub lane_priority: i32,
+    pub max_in_flight: Option<i32>,
+    pub paused: bool,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TaskTypePolicyRequest {
+    #[validate(length(min = 1, max = 64))]
+    pub task_type: String,
+    #[validate(range(min = -100, max = 1000))]
+    pub lane_priority: i32,
+    #[validate(range(min = 0))]
+    pub max_in_flight: Option<i32>,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ClaimTasksRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub worker_id: String,
+    /// Restrict the claim to these task types
+    pub task_types: Option<Vec<String>>,
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CompleteTaskRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub worker_id: String,
+    pub success: bool,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TaskHeartbeatRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub worker_id: String,
+}
+
+/// Filters for re-queueing failed tasks; all filters are combined with AND
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TaskRequeueRequest {
+    pub task_type: Option<String>,
+    /// Case-insensitive substring of the task's error message
+    #[validate(length(min = 1, max = 200))]
+    pub error_class: Option<String>,
+    /// Only tasks that failed at least this many minutes ago
+    #[validate(range(min = 0))]
+    pub min_age_minutes: Option<i64>,
+    /// Only tasks that failed at most this many minutes ago
+    #[validate(range(min = 0))]
+    pub max_age_minutes: Option<i64>,
+    /// Upper bound on tasks re-queued by this request (default 500)
+    #[validate(range(min = 1, max = 5000))]
+    pub max_tasks: Option<i64>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskRequeueResponse {
+    pub dry_run: bool,
+    /// Tasks re-queued, or that would be re-queued in dry-run mode
+    pub requeued: i64,
+    pub batches: u32,
+}
+
+/// A recorded task status transition
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TaskEvent {
+    pub id: i64,
+    pub task_id: i32,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub worker_id: Option<String>,
+    pub actor: Option<String>,
+    pub error_message: Option<String>,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,63 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::ids::{AccountId, CharaId, TrainedCharaId};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TeamStadiumSearchParams {
+    pub page: Option<i64>,
+    /// Results per page (max 100, default 20)
+    pub limit: Option<i64>,
+    /// Base character (1007) or a specific card (100701)
+    pub chara_id: Option<CharaId>,
+    /// 1 = sprint, 2 = mile, 3 = medium, 4 = long, 5 = dirt
+    pub distance_type: Option<i32>,
+    pub running_style: Option<i32>,
+    pub min_score: Option<i32>,
+    pub max_score: Option<i32>,
+    pub trainer_id: Option<AccountId>,
+    /// rank_score (default) or updated_at
+    pub sort_by: Option<String>,
+    /// asc or desc (default)
+    pub sort_dir: Option<String>,
+}
+
+/// A trained character in a trainer's team stadium team
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct TeamStadiumMember {
+    pub account_id: AccountId,
+    pub trainer_name: Option<String>,
+    pub distance_type: i32,
+    /// Slot within the distance's team
+    pub member_id: i32,
+    pub trained_chara_id: Option<TrainedCharaId>,
+    /// Character card (e.g. 100701)
+    pub card_id: CharaId,
+    pub running_style: Option<i32>,
+    pub speed: Option<i32>,
+    pub stamina: Option<i32>,
+    pub power: Option<i32>,
+    pub guts: Option<i32>,
+    pub wiz: Option<i32>,
+    pub rank_score: Option<i32>,
+    pub skills: Option<Vec<i32>>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// One distance's team of a trainer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamStadiumTeam {
+    pub distance_type: i32,
+    /// Sum of the members' rank scores
+    pub total_score: i64,
+    pub members: Vec<TeamStadiumMember>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainerTeamsResponse {
+    pub account_id: AccountId,
+    pub trainer_name: Option<String>,
+    /// By distance_type
+    pub teams: Vec<TeamStadiumTeam>,
+}
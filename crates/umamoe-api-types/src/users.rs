@@ -0,0 +1,177 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ids::AccountId;
+use crate::common::validate_account_id;
+
+/// A user account: anonymous, or signed in with Discord
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct User {
+    pub user_id: i64,
+    /// Discord display name; None for anonymous accounts
+    pub display_name: Option<String>,
+    pub discord_linked: bool,
+    pub created_at: NaiveDateTime,
+}
+
+/// A signed-in account; send the token as `Authorization: Bearer <token>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSession {
+    pub token: String,
+    /// When the token stops being accepted; sign in again for a new one
+    pub expires_at: DateTime<Utc>,
+    pub user: User,
+}
+
+/// The `code` Discord redirected back with (response_type=code)
+#[derive(Debug, Deserialize, Validate)]
+pub struct DiscordLoginRequest {
+    #[validate(length(min = 1, max = 256))]
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "text", rename_all = "lowercase"))]
+#[serde(rename_all = "lowercase")]
+pub enum BookmarkType {
+    /// target_id is the trainer's account ID
+    Trainer,
+    /// target_id is the record's inheritance_id
+    Inheritance,
+    /// target_id is the circle ID
+    Circle,
+}
+
+impl BookmarkType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookmarkType::Trainer => "trainer",
+            BookmarkType::Inheritance => "inheritance",
+            BookmarkType::Circle => "circle",
+        }
+    }
+}
+
+/// A bookmark with what it points to, as far as that still exists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Bookmark {
+    pub target_type: BookmarkType,
+    pub target_id: String,
+    pub created_at: NaiveDateTime,
+    /// Trainer name (of the record's trainer for inheritance) or circle name
+    pub name: Option<String>,
+    /// The trainer's follower count; their friend list is full at 1000
+    pub follower_num: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BookmarkListParams {
+    /// Only bookmarks of this type
+    #[serde(rename = "type")]
+    pub target_type: Option<BookmarkType>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct UserNotification {
+    pub notification_id: i64,
+    /// "friend_slots_open": a bookmarked trainer's friend list has room again
+    pub kind: String,
+    pub account_id: Option<AccountId>,
+    /// Details of the change (for friend_slots_open: trainer_name, follower_num,
+    /// previous_follower_num)
+    pub data: serde_json::Value,
+    pub created_at: NaiveDateTime,
+    pub read_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct NotificationListParams {
+    /// Only notifications not marked read yet
+    #[serde(default)]
+    pub unread: bool,
+    /// Newest first, up to 100 (default 50)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MarkNotificationsReadRequest {
+    /// Mark notifications up to and including this ID (default: all)
+    pub up_to: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "text", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    /// Posted to a Discord webhook
+    Discord,
+    /// Sent to a browser push subscription
+    WebPush,
+}
+
+impl NotificationChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannel::Discord => "discord",
+            NotificationChannel::WebPush => "web_push",
+        }
+    }
+}
+
+/// A browser's PushSubscription, as returned by `subscription.toJSON()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushSubscriptionInfo {
+    pub endpoint: String,
+    pub keys: PushSubscriptionKeys,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushSubscriptionKeys {
+    /// The browser's P-256 public key (base64url)
+    pub p256dh: String,
+    /// The browser's auth secret (base64url)
+    pub auth: String,
+}
+
+/// Get notified once a full trainer's friend list has room again
+#[derive(Debug, Deserialize, Validate)]
+pub struct AvailabilitySubscriptionRequest {
+    #[validate(custom(function = "validate_account_id"))]
+    pub account_id: AccountId,
+    pub channel: NotificationChannel,
+    /// Required for the discord channel
+    pub webhook_url: Option<String>,
+    /// Required for the web_push channel
+    pub push_subscription: Option<PushSubscriptionInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct AvailabilitySubscription {
+    pub subscription_id: i64,
+    pub account_id: AccountId,
+    pub trainer_name: Option<String>,
+    pub follower_num: Option<i32>,
+    pub channel: NotificationChannel,
+    /// waiting (friend list still full), pending (delivering), delivered or
+    /// failed
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub triggered_at: Option<NaiveDateTime>,
+    pub delivered_at: Option<NaiveDateTime>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+}
+
+/// The VAPID public key to pass as `applicationServerKey` to
+/// `pushManager.subscribe()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VapidKeyResponse {
+    pub public_key: String,
+}
@@ -0,0 +1,54 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Scheduled window during which workers should stop claiming tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub starts_at: NaiveDateTime,
+    pub ends_at: NaiveDateTime,
+    pub reason: Option<String>,
+}
+
+/// Central worker fleet settings (single row)
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct WorkerFleetSettings {
+    pub paused: bool,
+    pub claim_endpoints: Vec<String>,
+    pub poll_interval_secs: i32,
+    pub idle_poll_interval_secs: i32,
+    #[cfg_attr(feature = "sqlx", sqlx(json))]
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct WorkerFleetSettingsRequest {
+    pub paused: bool,
+    #[validate(length(min = 1, max = 16))]
+    pub claim_endpoints: Vec<String>,
+    #[validate(range(min = 1, max = 3600))]
+    pub poll_interval_secs: i32,
+    #[validate(range(min = 1, max = 3600))]
+    pub idle_poll_interval_secs: i32,
+    #[serde(default)]
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+}
+
+/// Configuration advertised to workers
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkerConfig {
+    /// True when the fleet is paused or a maintenance window is active
+    pub paused: bool,
+    pub claim_endpoints: Vec<String>,
+    pub poll_interval_secs: i32,
+    pub idle_poll_interval_secs: i32,
+    /// Task types workers should not claim right now
+    pub paused_task_types: Vec<String>,
+    /// Active and upcoming maintenance windows
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+    pub issued_at: NaiveDateTime,
+    /// Workers should re-fetch the config after this time
+    pub expires_at: NaiveDateTime,
+}
@@ -0,0 +1,18 @@
+-- Migration: Trainer claims and claimed refresh lane
+-- Date: 2026-10-16
+-- Purpose: Let trainers who verified ownership of their account request
+--          a refresh that is scheduled ahead of anonymous recheck triggers
+
+-- Verified trainer claims. The claim token is issued once ownership has been
+-- verified and only its SHA-256 hash is stored here.
+CREATE TABLE IF NOT EXISTS trainer_claims (
+    trainer_id TEXT PRIMARY KEY,
+    claim_token_hash TEXT NOT NULL,
+    verified_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_refresh_at TIMESTAMP
+);
+
+-- Workers pick tasks by priority, then age
+CREATE INDEX IF NOT EXISTS idx_tasks_pending_priority
+ON tasks (priority, created_at)
+WHERE status = 'pending';
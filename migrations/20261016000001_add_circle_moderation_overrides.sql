@@ -0,0 +1,11 @@
+-- Migration: Circle moderation overrides
+-- Date: 2026-10-16
+-- Purpose: Admin-managed replacements for circle names/comments coming from the game
+
+CREATE TABLE IF NOT EXISTS circle_moderation_overrides (
+    circle_id BIGINT PRIMARY KEY,
+    name_override TEXT,
+    comment_override TEXT,
+    hide_comment BOOLEAN NOT NULL DEFAULT false,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,12 @@
+-- Migration: Pending task dedup indexes
+-- Date: 2026-10-16
+-- Purpose: Task-creating endpoints check for an existing pending task for the
+--          same trainer/viewer before inserting a new one
+
+CREATE INDEX IF NOT EXISTS idx_tasks_pending_trainer
+ON tasks (task_type, (task_data->>'id'))
+WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_tasks_pending_viewer
+ON tasks (task_type, (task_data->>'viewer_id'))
+WHERE status = 'pending';
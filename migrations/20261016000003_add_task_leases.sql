@@ -0,0 +1,13 @@
+-- Migration: Task leases and attempts
+-- Date: 2026-10-16
+-- Purpose: Track when a worker claimed a task and how often it has been
+--          retried, so tasks stranded by crashed workers can be reaped
+
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS attempts INTEGER NOT NULL DEFAULT 0,
+ADD COLUMN IF NOT EXISTS claimed_at TIMESTAMP;
+
+-- The reaper scans in-flight tasks by claim time
+CREATE INDEX IF NOT EXISTS idx_tasks_in_flight
+ON tasks (COALESCE(claimed_at, updated_at, created_at))
+WHERE status IN ('claimed', 'processing');
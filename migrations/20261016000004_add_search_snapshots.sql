@@ -0,0 +1,12 @@
+-- Migration: Search snapshot permalinks
+-- Date: 2026-10-16
+-- Purpose: Freeze a search result page under a permalink so shared lists
+--          don't change as trainers update or become unavailable
+
+CREATE TABLE IF NOT EXISTS search_snapshots (
+    snapshot_id TEXT PRIMARY KEY,
+    query TEXT NOT NULL,
+    total TEXT NOT NULL,
+    entries JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,21 @@
+-- Migration: Per-task-type priority lanes
+-- Date: 2026-10-16
+-- Purpose: Let user-facing task types preempt bulk backfills and cap how many
+--          tasks of each type may be in flight at once
+
+CREATE TABLE IF NOT EXISTS task_type_policies (
+    task_type TEXT PRIMARY KEY,
+    -- Lower lanes are claimed first; unknown task types fall into lane 100
+    lane_priority INTEGER NOT NULL DEFAULT 100,
+    -- NULL means no cap on claimed/processing tasks of this type
+    max_in_flight INTEGER,
+    paused BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO task_type_policies (task_type, lane_priority, max_in_flight)
+VALUES
+    ('friend/search', 0, NULL),
+    ('friend/recheck', 10, NULL),
+    ('fetch_circle', 50, 20)
+ON CONFLICT (task_type) DO NOTHING;
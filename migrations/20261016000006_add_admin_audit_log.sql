@@ -0,0 +1,13 @@
+-- Migration: Admin audit log
+-- Date: 2026-10-16
+-- Purpose: Record bulk admin operations on the task queue
+
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    action TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_created_at
+ON admin_audit_log (created_at DESC);
@@ -0,0 +1,42 @@
+-- Migration: Task audit history
+-- Date: 2026-10-16
+-- Purpose: Record every task status transition so tasks bouncing between
+--          workers can be debugged. A trigger captures transitions made by
+--          the API and by workers writing to the table directly alike.
+
+CREATE TABLE IF NOT EXISTS task_events (
+    id BIGSERIAL PRIMARY KEY,
+    task_id INTEGER NOT NULL,
+    from_status TEXT,
+    to_status TEXT NOT NULL,
+    worker_id TEXT,
+    -- Set by the API via the umamoe.actor setting (e.g. 'reaper', 'admin')
+    actor TEXT,
+    error_message TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_events_task_id
+ON task_events (task_id, created_at);
+
+CREATE OR REPLACE FUNCTION record_task_event() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' OR NEW.status IS DISTINCT FROM OLD.status THEN
+        INSERT INTO task_events (task_id, from_status, to_status, worker_id, actor, error_message)
+        VALUES (
+            NEW.id,
+            CASE WHEN TG_OP = 'UPDATE' THEN OLD.status END,
+            NEW.status,
+            COALESCE(NEW.worker_id, CASE WHEN TG_OP = 'UPDATE' THEN OLD.worker_id END),
+            NULLIF(current_setting('umamoe.actor', true), ''),
+            NEW.error_message
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_tasks_record_event ON tasks;
+CREATE TRIGGER trg_tasks_record_event
+AFTER INSERT OR UPDATE OF status ON tasks
+FOR EACH ROW EXECUTE FUNCTION record_task_event();
@@ -0,0 +1,57 @@
+-- Migration: Registered task-type catalog
+-- Date: 2026-10-16
+-- Purpose: Only accept known task types through the generic task endpoint and
+--          validate their task_data against a per-type JSON Schema
+
+CREATE TABLE IF NOT EXISTS task_types (
+    task_type TEXT PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT '',
+    payload_schema JSONB NOT NULL DEFAULT '{}'::jsonb,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO task_types (task_type, description, payload_schema)
+VALUES
+    (
+        'friend/search',
+        'Look up a trainer by ID and scrape their inheritance and support card',
+        '{
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": { "type": "string", "pattern": "^[0-9]{9,12}$" },
+                "action": { "type": "string" },
+                "reason": { "type": "string" }
+            },
+            "additionalProperties": false
+        }'::jsonb
+    ),
+    (
+        'friend/recheck',
+        'Re-scrape a known trainer whose listing may be stale',
+        '{
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": { "type": "string", "pattern": "^[0-9]{9,12}$" },
+                "action": { "type": "string" },
+                "reason": { "type": "string" },
+                "copy_count": { "type": "integer", "minimum": 0 }
+            },
+            "additionalProperties": false
+        }'::jsonb
+    ),
+    (
+        'fetch_circle',
+        'Fetch the circle and monthly fan counts for a viewer',
+        '{
+            "type": "object",
+            "required": ["viewer_id"],
+            "properties": {
+                "viewer_id": { "type": "integer", "minimum": 1 }
+            },
+            "additionalProperties": false
+        }'::jsonb
+    )
+ON CONFLICT (task_type) DO NOTHING;
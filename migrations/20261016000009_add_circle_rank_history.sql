@@ -0,0 +1,14 @@
+-- Migration: Circle ranking history
+-- Date: 2026-10-16
+-- Purpose: Daily snapshots of circle monthly point/rank for charting a
+--          circle's progress over the month
+
+CREATE TABLE IF NOT EXISTS circle_rank_history (
+    circle_id BIGINT NOT NULL,
+    snapshot_date DATE NOT NULL,
+    monthly_rank INTEGER,
+    monthly_point BIGINT,
+    member_count INTEGER,
+    recorded_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (circle_id, snapshot_date)
+);
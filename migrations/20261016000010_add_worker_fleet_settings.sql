@@ -0,0 +1,19 @@
+-- Migration: Worker fleet settings
+-- Date: 2026-10-16
+-- Purpose: Central configuration for the scraper fleet (claim endpoints,
+--          polling intervals, pause flag, maintenance windows), served to
+--          workers via GET /api/workers/config
+
+CREATE TABLE IF NOT EXISTS worker_fleet_settings (
+    -- Single-row table
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    paused BOOLEAN NOT NULL DEFAULT FALSE,
+    claim_endpoints TEXT[] NOT NULL DEFAULT ARRAY['/api/workers/claim'],
+    poll_interval_secs INTEGER NOT NULL DEFAULT 5,
+    idle_poll_interval_secs INTEGER NOT NULL DEFAULT 30,
+    -- [{"starts_at": "...", "ends_at": "...", "reason": "..."}]
+    maintenance_windows JSONB NOT NULL DEFAULT '[]'::jsonb,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO worker_fleet_settings (id) VALUES (TRUE) ON CONFLICT (id) DO NOTHING;
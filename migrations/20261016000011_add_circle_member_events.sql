@@ -0,0 +1,72 @@
+-- Migration: Circle member join/leave tracking
+-- Date: 2026-10-16
+-- Purpose: Keep the last known member set of each circle and record join/leave
+--          events when scrapes of circle_member_fans_monthly change it
+
+CREATE TABLE IF NOT EXISTS circle_member_roster (
+    circle_id BIGINT NOT NULL,
+    viewer_id BIGINT NOT NULL,
+    joined_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_seen_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (circle_id, viewer_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_circle_member_roster_viewer
+ON circle_member_roster (viewer_id);
+
+CREATE TABLE IF NOT EXISTS circle_member_events (
+    id BIGSERIAL PRIMARY KEY,
+    circle_id BIGINT NOT NULL,
+    viewer_id BIGINT NOT NULL,
+    event_type TEXT NOT NULL CHECK (event_type IN ('joined', 'left')),
+    detected_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_circle_member_events_circle
+ON circle_member_events (circle_id, detected_at DESC);
+
+-- Joins (and moves between circles) are detected as member rows are written.
+-- Leaves without joining another circle are detected by a periodic job that
+-- looks for roster members missing from a circle's latest scrape.
+CREATE OR REPLACE FUNCTION track_circle_membership() RETURNS TRIGGER AS $$
+DECLARE
+    tracked_since TIMESTAMP;
+    is_new BOOLEAN;
+BEGIN
+    -- Rows for past months describe old memberships
+    IF make_date(NEW.year, NEW.month, 1) < date_trunc('month', CURRENT_DATE - 1) THEN
+        RETURN NEW;
+    END IF;
+
+    -- The viewer moved here from another circle
+    WITH moved AS (
+        DELETE FROM circle_member_roster
+        WHERE viewer_id = NEW.viewer_id AND circle_id <> NEW.circle_id
+        RETURNING circle_id, viewer_id
+    )
+    INSERT INTO circle_member_events (circle_id, viewer_id, event_type)
+    SELECT circle_id, viewer_id, 'left' FROM moved;
+
+    SELECT MIN(joined_at) INTO tracked_since
+    FROM circle_member_roster
+    WHERE circle_id = NEW.circle_id;
+
+    INSERT INTO circle_member_roster (circle_id, viewer_id, joined_at, last_seen_at)
+    VALUES (NEW.circle_id, NEW.viewer_id, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+    ON CONFLICT (circle_id, viewer_id) DO UPDATE SET last_seen_at = EXCLUDED.last_seen_at
+    RETURNING (xmax = 0) INTO is_new;
+
+    -- The first scrape of a circle seeds its roster without emitting joins
+    IF is_new AND tracked_since IS NOT NULL AND tracked_since < CURRENT_TIMESTAMP - INTERVAL '10 minutes' THEN
+        INSERT INTO circle_member_events (circle_id, viewer_id, event_type)
+        VALUES (NEW.circle_id, NEW.viewer_id, 'joined');
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_circle_member_fans_track_membership ON circle_member_fans_monthly;
+CREATE TRIGGER trg_circle_member_fans_track_membership
+AFTER INSERT OR UPDATE ON circle_member_fans_monthly
+FOR EACH ROW EXECUTE FUNCTION track_circle_membership();
@@ -0,0 +1,54 @@
+-- Migration: Per-record data provenance
+-- Date: 2026-10-16
+-- Purpose: Track which worker/game server/scrape batch wrote each inheritance
+--          and support_card row, so bad batches can be traced and rolled back.
+--
+-- Writers either set `source` explicitly or set the session setting
+-- umamoe.source to a JSON object before writing, e.g.
+--   SELECT set_config('umamoe.source', '{"worker_id":"w1","server":"jp","batch_id":"..."}', true);
+-- Rows written without either get a NULL source.
+
+ALTER TABLE inheritance
+ADD COLUMN IF NOT EXISTS source JSONB,
+ADD COLUMN IF NOT EXISTS ingested_at TIMESTAMP;
+
+ALTER TABLE support_card
+ADD COLUMN IF NOT EXISTS source JSONB,
+ADD COLUMN IF NOT EXISTS ingested_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_inheritance_source_batch
+ON inheritance ((source->>'batch_id'))
+WHERE source IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_support_card_source_batch
+ON support_card ((source->>'batch_id'))
+WHERE source IS NOT NULL;
+
+CREATE OR REPLACE FUNCTION stamp_record_provenance() RETURNS TRIGGER AS $$
+DECLARE
+    session_source JSONB := NULLIF(current_setting('umamoe.source', true), '')::jsonb;
+BEGIN
+    NEW.ingested_at := CURRENT_TIMESTAMP;
+
+    -- Only fill in the session source when the writer didn't set one itself
+    IF TG_OP = 'INSERT' THEN
+        IF NEW.source IS NULL THEN
+            NEW.source := session_source;
+        END IF;
+    ELSIF NEW.source IS NOT DISTINCT FROM OLD.source THEN
+        NEW.source := session_source;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_inheritance_provenance ON inheritance;
+CREATE TRIGGER trg_inheritance_provenance
+BEFORE INSERT OR UPDATE ON inheritance
+FOR EACH ROW EXECUTE FUNCTION stamp_record_provenance();
+
+DROP TRIGGER IF EXISTS trg_support_card_provenance ON support_card;
+CREATE TRIGGER trg_support_card_provenance
+BEFORE INSERT OR UPDATE ON support_card
+FOR EACH ROW EXECUTE FUNCTION stamp_record_provenance();
@@ -0,0 +1,91 @@
+-- Migration: Record versions for ingestion rollback
+-- Date: 2026-10-16
+-- Purpose: Keep the version of an inheritance/support_card row that a scrape
+--          batch replaced, and archive deleted rows, so a bad batch can be
+--          rolled back to the data it overwrote
+
+CREATE TABLE IF NOT EXISTS record_versions (
+    id BIGSERIAL PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    -- account_id for inheritance, account_id:support_card_id for support_card
+    record_key TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    data JSONB NOT NULL,
+    -- 'update', 'delete' or 'rollback:<batch_id>'
+    reason TEXT NOT NULL,
+    -- Batch whose write replaced this version (NULL for deletes)
+    replaced_by_batch TEXT,
+    archived_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_record_versions_batch
+ON record_versions (replaced_by_batch, table_name, record_key)
+WHERE replaced_by_batch IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_record_versions_account
+ON record_versions (account_id);
+
+-- Updates are only versioned when the new data is attributed to a batch,
+-- since those are the only writes a rollback can target.
+CREATE OR REPLACE FUNCTION archive_record_version() RETURNS TRIGGER AS $$
+DECLARE
+    old_row JSONB := to_jsonb(OLD);
+    new_batch TEXT;
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        new_batch := NEW.source->>'batch_id';
+        IF new_batch IS NULL THEN
+            RETURN NULL;
+        END IF;
+    END IF;
+
+    INSERT INTO record_versions (table_name, record_key, account_id, data, reason, replaced_by_batch)
+    VALUES (
+        TG_TABLE_NAME,
+        CASE
+            WHEN TG_TABLE_NAME = 'support_card' THEN (old_row->>'account_id') || ':' || (old_row->>'support_card_id')
+            ELSE old_row->>'account_id'
+        END,
+        old_row->>'account_id',
+        old_row,
+        COALESCE(NULLIF(current_setting('umamoe.version_reason', true), ''), lower(TG_OP)),
+        new_batch
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_inheritance_versions ON inheritance;
+CREATE TRIGGER trg_inheritance_versions
+AFTER UPDATE OR DELETE ON inheritance
+FOR EACH ROW EXECUTE FUNCTION archive_record_version();
+
+DROP TRIGGER IF EXISTS trg_support_card_versions ON support_card;
+CREATE TRIGGER trg_support_card_versions
+AFTER UPDATE OR DELETE ON support_card
+FOR EACH ROW EXECUTE FUNCTION archive_record_version();
+
+-- Restored rows keep the provenance they had before the rolled-back batch
+CREATE OR REPLACE FUNCTION stamp_record_provenance() RETURNS TRIGGER AS $$
+DECLARE
+    session_source JSONB := NULLIF(current_setting('umamoe.source', true), '')::jsonb;
+BEGIN
+    IF current_setting('umamoe.restoring', true) = 'on' THEN
+        RETURN NEW;
+    END IF;
+
+    NEW.ingested_at := CURRENT_TIMESTAMP;
+
+    -- Only fill in the session source when the writer didn't set one itself
+    IF TG_OP = 'INSERT' THEN
+        IF NEW.source IS NULL THEN
+            NEW.source := session_source;
+        END IF;
+    ELSIF NEW.source IS NOT DISTINCT FROM OLD.source THEN
+        NEW.source := session_source;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,31 @@
+-- Migration: Normalized names and ICU collations
+-- Date: 2026-10-16
+-- Purpose: Match trainer/circle names regardless of Unicode width and case
+--          (NFKC folds half-width kana and full-width Latin, lower() folds
+--          case), and sort names with ICU collations instead of byte order
+
+ALTER TABLE trainer
+ADD COLUMN IF NOT EXISTS name_normalized TEXT
+GENERATED ALWAYS AS (lower(normalize(name, NFKC))) STORED;
+
+ALTER TABLE circles
+ADD COLUMN IF NOT EXISTS name_normalized TEXT
+GENERATED ALWAYS AS (lower(normalize(name, NFKC))) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_trainer_name_normalized_trgm
+ON trainer USING gin (name_normalized gin_trgm_ops);
+
+CREATE INDEX IF NOT EXISTS idx_circles_name_normalized_trgm
+ON circles USING gin (name_normalized gin_trgm_ops);
+
+-- Collations used by name sorting. Servers built without ICU fall back to
+-- byte order so queries referencing the collations keep working.
+DO $$
+BEGIN
+    CREATE COLLATION IF NOT EXISTS name_ja (provider = icu, locale = 'ja-u-kn-true');
+    CREATE COLLATION IF NOT EXISTS name_root (provider = icu, locale = 'und-u-kn-true');
+EXCEPTION WHEN OTHERS THEN
+    CREATE COLLATION IF NOT EXISTS name_ja FROM "C";
+    CREATE COLLATION IF NOT EXISTS name_root FROM "C";
+END
+$$;
@@ -0,0 +1,31 @@
+-- Migration: Notable record feed
+-- Date: 2026-10-16
+-- Purpose: Configurable rules deciding which newly ingested inheritance records
+--          show up in the /feeds/notable.xml feed
+
+-- A record is notable when it satisfies every threshold set on at least one
+-- enabled rule; NULL thresholds are ignored
+CREATE TABLE IF NOT EXISTS notability_rules (
+    rule_id TEXT PRIMARY KEY,
+    -- Shown as the entry category in the feed
+    label TEXT NOT NULL,
+    min_blue_stars INTEGER,
+    min_pink_stars INTEGER,
+    min_green_stars INTEGER,
+    min_white_stars INTEGER,
+    min_win_count INTEGER,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO notability_rules (rule_id, label, min_blue_stars, min_pink_stars, min_win_count)
+VALUES
+    ('nine_star_blue', '9★ blue sparks', 9, NULL, NULL),
+    ('nine_star_pink', '9★ pink sparks', NULL, 9, NULL),
+    ('high_win_count', 'High win count', NULL, NULL, 40)
+ON CONFLICT (rule_id) DO NOTHING;
+
+-- The feed lists the newest ingested records first
+CREATE INDEX IF NOT EXISTS idx_inheritance_ingested_at
+ON inheritance (ingested_at DESC)
+WHERE ingested_at IS NOT NULL;
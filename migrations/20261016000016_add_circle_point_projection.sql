@@ -0,0 +1,45 @@
+-- Migration: End-of-month circle point projection
+-- Date: 2026-10-16
+-- Purpose: Estimate a circle's final monthly points from its members' recent daily gains
+
+-- daily_fans holds each member's cumulative fan count per day of the month (0 = no data),
+-- so a day's circle gain is the sum of member increases over the previous day.
+-- The last 7 recorded days are averaged with linearly decreasing weights (newest = 7)
+-- and extrapolated over the days left in the current JST month.
+-- Returns NULL when there is not enough member data this month.
+CREATE OR REPLACE FUNCTION circle_projected_point(p_circle_id BIGINT, p_current_point BIGINT)
+RETURNS BIGINT AS $$
+    WITH month AS (
+        SELECT
+            EXTRACT(YEAR FROM now_jst)::INTEGER AS year,
+            EXTRACT(MONTH FROM now_jst)::INTEGER AS month,
+            EXTRACT(DAY FROM date_trunc('month', now_jst) + INTERVAL '1 month - 1 day')::INTEGER AS days_in_month
+        FROM (SELECT CURRENT_TIMESTAMP AT TIME ZONE 'Asia/Tokyo' AS now_jst) n
+    ),
+    member_days AS (
+        SELECT x.d, x.f, lag(x.f) OVER (PARTITION BY cm.id ORDER BY x.d) AS prev
+        FROM circle_member_fans_monthly cm
+        JOIN month m ON cm.year = m.year AND cm.month = m.month
+        CROSS JOIN LATERAL unnest(cm.daily_fans) WITH ORDINALITY AS x(f, d)
+        WHERE cm.circle_id = p_circle_id
+    ),
+    circle_days AS (
+        SELECT d, SUM(f - prev) AS gain
+        FROM member_days
+        WHERE f > 0 AND prev > 0
+        GROUP BY d
+        ORDER BY d DESC
+        LIMIT 7
+    ),
+    weighted AS (
+        SELECT
+            MAX(d) AS last_day,
+            SUM(gain * (8 - age)) / NULLIF(SUM(8 - age), 0) AS daily_gain
+        FROM (SELECT d, gain, row_number() OVER (ORDER BY d DESC) AS age FROM circle_days) r
+    )
+    SELECT CASE
+        WHEN w.daily_gain IS NULL OR p_current_point IS NULL THEN NULL
+        ELSE p_current_point + ROUND(GREATEST(w.daily_gain, 0) * GREATEST(m.days_in_month - w.last_day, 0))::BIGINT
+    END
+    FROM weighted w, month m
+$$ LANGUAGE sql STABLE COST 1000;
@@ -0,0 +1,215 @@
+-- Migration: Wider spark encoding (v2), dual-write phase
+-- Date: 2026-10-16
+-- Purpose: Spark values are packed as factor_id * 10 + level (v1), which caps
+--          levels at 9 and only leaves room for ~100 factors per colour. v2
+--          packs factor_id * 1000 + level into parallel *_v2 columns.
+--
+-- Rollout:
+--   1. This migration adds the v2 columns (NULL, no rewrite) and a trigger that
+--      keeps both encodings in sync for every write, whichever side the writer sets.
+--   2. POST /api/admin/sparks/backfill fills v2 for existing rows in small batches.
+--   3. GET /api/admin/sparks/status and POST /api/admin/sparks/compare verify the
+--      data and search results match between encodings.
+--   4. SPARK_ENCODING=v2 (or PUT /api/admin/sparks/encoding) switches search to v2.
+--   5. Once no writer relies on v1, the v1 columns can be dropped in a later migration.
+
+ALTER TABLE inheritance
+ADD COLUMN IF NOT EXISTS blue_sparks_v2 INTEGER[],
+ADD COLUMN IF NOT EXISTS pink_sparks_v2 INTEGER[],
+ADD COLUMN IF NOT EXISTS green_sparks_v2 INTEGER[],
+ADD COLUMN IF NOT EXISTS white_sparks_v2 INTEGER[],
+ADD COLUMN IF NOT EXISTS main_blue_factors_v2 INTEGER,
+ADD COLUMN IF NOT EXISTS main_pink_factors_v2 INTEGER,
+ADD COLUMN IF NOT EXISTS main_green_factors_v2 INTEGER,
+ADD COLUMN IF NOT EXISTS main_white_factors_v2 INTEGER[];
+
+-- Conversions between encodings. v2 -> v1 returns NULL (scalars) or drops the
+-- entry (arrays) when a value does not fit the v1 encoding.
+CREATE OR REPLACE FUNCTION spark_v1_to_v2(spark INTEGER) RETURNS INTEGER AS $$
+    SELECT CASE WHEN spark IS NULL OR spark <= 0 THEN spark ELSE (spark / 10) * 1000 + spark % 10 END
+$$ LANGUAGE sql IMMUTABLE PARALLEL SAFE;
+
+CREATE OR REPLACE FUNCTION spark_v1_to_v2(sparks INTEGER[]) RETURNS INTEGER[] AS $$
+    SELECT CASE WHEN sparks IS NULL THEN NULL ELSE
+        COALESCE((SELECT array_agg(spark_v1_to_v2(s) ORDER BY n) FROM unnest(sparks) WITH ORDINALITY AS x(s, n)), '{}')
+    END
+$$ LANGUAGE sql IMMUTABLE PARALLEL SAFE;
+
+CREATE OR REPLACE FUNCTION spark_v2_to_v1(spark INTEGER) RETURNS INTEGER AS $$
+    SELECT CASE
+        WHEN spark IS NULL OR spark <= 0 THEN spark
+        WHEN spark % 1000 > 9 OR spark / 1000 > 214748363 THEN NULL
+        ELSE (spark / 1000) * 10 + spark % 1000
+    END
+$$ LANGUAGE sql IMMUTABLE PARALLEL SAFE;
+
+CREATE OR REPLACE FUNCTION spark_v2_to_v1(sparks INTEGER[]) RETURNS INTEGER[] AS $$
+    SELECT CASE WHEN sparks IS NULL THEN NULL ELSE
+        COALESCE((
+            SELECT array_agg(spark_v2_to_v1(s) ORDER BY n)
+            FROM unnest(sparks) WITH ORDINALITY AS x(s, n)
+            WHERE spark_v2_to_v1(s) IS NOT NULL
+        ), '{}')
+    END
+$$ LANGUAGE sql IMMUTABLE PARALLEL SAFE;
+
+-- Optional white spark scoring for either encoding (multiplier 10 = v1, 1000 = v2)
+CREATE OR REPLACE FUNCTION calculate_sparks_score(sparks int[], factor_ids int[], multiplier int) RETURNS int AS $$
+DECLARE
+    score int := 0;
+    spark int;
+    factor int;
+    seen_factors int[] := '{}';
+BEGIN
+    IF sparks IS NULL OR factor_ids IS NULL OR array_length(factor_ids, 1) IS NULL THEN
+        RETURN 0;
+    END IF;
+
+    FOREACH spark IN ARRAY sparks LOOP
+        factor := spark / multiplier;
+
+        IF factor = ANY(factor_ids) THEN
+            score := score + (spark % multiplier);
+
+            IF NOT (factor = ANY(seen_factors)) THEN
+                seen_factors := array_append(seen_factors, factor);
+                score := score + 100;
+            END IF;
+        END IF;
+    END LOOP;
+
+    RETURN score;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE PARALLEL SAFE;
+
+-- Dual write: whichever encoding the writer changed is converted into the other,
+-- so v2-only writers still get the v1 columns filled in.
+CREATE OR REPLACE FUNCTION sync_spark_encodings() RETURNS TRIGGER AS $$
+DECLARE
+    is_insert BOOLEAN := TG_OP = 'INSERT';
+    v2_written BOOLEAN := FALSE;
+BEGIN
+    -- Arrays
+    IF is_insert THEN
+        IF NEW.blue_sparks_v2 IS NULL THEN NEW.blue_sparks_v2 := spark_v1_to_v2(NEW.blue_sparks);
+        ELSIF NEW.blue_sparks IS NULL THEN NEW.blue_sparks := spark_v2_to_v1(NEW.blue_sparks_v2); v2_written := TRUE; END IF;
+        IF NEW.pink_sparks_v2 IS NULL THEN NEW.pink_sparks_v2 := spark_v1_to_v2(NEW.pink_sparks);
+        ELSIF NEW.pink_sparks IS NULL THEN NEW.pink_sparks := spark_v2_to_v1(NEW.pink_sparks_v2); v2_written := TRUE; END IF;
+        IF NEW.green_sparks_v2 IS NULL THEN NEW.green_sparks_v2 := spark_v1_to_v2(NEW.green_sparks);
+        ELSIF NEW.green_sparks IS NULL THEN NEW.green_sparks := spark_v2_to_v1(NEW.green_sparks_v2); v2_written := TRUE; END IF;
+        IF NEW.white_sparks_v2 IS NULL THEN NEW.white_sparks_v2 := spark_v1_to_v2(NEW.white_sparks);
+        ELSIF NEW.white_sparks IS NULL THEN NEW.white_sparks := spark_v2_to_v1(NEW.white_sparks_v2); v2_written := TRUE; END IF;
+        IF NEW.main_white_factors_v2 IS NULL THEN NEW.main_white_factors_v2 := spark_v1_to_v2(NEW.main_white_factors);
+        ELSIF NEW.main_white_factors IS NULL THEN NEW.main_white_factors := spark_v2_to_v1(NEW.main_white_factors_v2); END IF;
+        IF NEW.main_blue_factors_v2 IS NULL THEN NEW.main_blue_factors_v2 := spark_v1_to_v2(NEW.main_blue_factors);
+        ELSIF NEW.main_blue_factors IS NULL THEN NEW.main_blue_factors := spark_v2_to_v1(NEW.main_blue_factors_v2); END IF;
+        IF NEW.main_pink_factors_v2 IS NULL THEN NEW.main_pink_factors_v2 := spark_v1_to_v2(NEW.main_pink_factors);
+        ELSIF NEW.main_pink_factors IS NULL THEN NEW.main_pink_factors := spark_v2_to_v1(NEW.main_pink_factors_v2); END IF;
+        IF NEW.main_green_factors_v2 IS NULL THEN NEW.main_green_factors_v2 := spark_v1_to_v2(NEW.main_green_factors);
+        ELSIF NEW.main_green_factors IS NULL THEN NEW.main_green_factors := spark_v2_to_v1(NEW.main_green_factors_v2); END IF;
+    ELSE
+        -- A v2 change wins when the writer set v2 without touching v1; otherwise
+        -- v2 follows v1 (legacy writers, and backfill of rows without v2)
+        IF NEW.blue_sparks_v2 IS DISTINCT FROM OLD.blue_sparks_v2 AND NEW.blue_sparks IS NOT DISTINCT FROM OLD.blue_sparks THEN
+            NEW.blue_sparks := spark_v2_to_v1(NEW.blue_sparks_v2); v2_written := TRUE;
+        ELSE NEW.blue_sparks_v2 := spark_v1_to_v2(NEW.blue_sparks); END IF;
+        IF NEW.pink_sparks_v2 IS DISTINCT FROM OLD.pink_sparks_v2 AND NEW.pink_sparks IS NOT DISTINCT FROM OLD.pink_sparks THEN
+            NEW.pink_sparks := spark_v2_to_v1(NEW.pink_sparks_v2); v2_written := TRUE;
+        ELSE NEW.pink_sparks_v2 := spark_v1_to_v2(NEW.pink_sparks); END IF;
+        IF NEW.green_sparks_v2 IS DISTINCT FROM OLD.green_sparks_v2 AND NEW.green_sparks IS NOT DISTINCT FROM OLD.green_sparks THEN
+            NEW.green_sparks := spark_v2_to_v1(NEW.green_sparks_v2); v2_written := TRUE;
+        ELSE NEW.green_sparks_v2 := spark_v1_to_v2(NEW.green_sparks); END IF;
+        IF NEW.white_sparks_v2 IS DISTINCT FROM OLD.white_sparks_v2 AND NEW.white_sparks IS NOT DISTINCT FROM OLD.white_sparks THEN
+            NEW.white_sparks := spark_v2_to_v1(NEW.white_sparks_v2); v2_written := TRUE;
+        ELSE NEW.white_sparks_v2 := spark_v1_to_v2(NEW.white_sparks); END IF;
+        IF NEW.main_white_factors_v2 IS DISTINCT FROM OLD.main_white_factors_v2 AND NEW.main_white_factors IS NOT DISTINCT FROM OLD.main_white_factors THEN
+            NEW.main_white_factors := spark_v2_to_v1(NEW.main_white_factors_v2);
+        ELSE NEW.main_white_factors_v2 := spark_v1_to_v2(NEW.main_white_factors); END IF;
+        IF NEW.main_blue_factors_v2 IS DISTINCT FROM OLD.main_blue_factors_v2 AND NEW.main_blue_factors IS NOT DISTINCT FROM OLD.main_blue_factors THEN
+            NEW.main_blue_factors := spark_v2_to_v1(NEW.main_blue_factors_v2);
+        ELSE NEW.main_blue_factors_v2 := spark_v1_to_v2(NEW.main_blue_factors); END IF;
+        IF NEW.main_pink_factors_v2 IS DISTINCT FROM OLD.main_pink_factors_v2 AND NEW.main_pink_factors IS NOT DISTINCT FROM OLD.main_pink_factors THEN
+            NEW.main_pink_factors := spark_v2_to_v1(NEW.main_pink_factors_v2);
+        ELSE NEW.main_pink_factors_v2 := spark_v1_to_v2(NEW.main_pink_factors); END IF;
+        IF NEW.main_green_factors_v2 IS DISTINCT FROM OLD.main_green_factors_v2 AND NEW.main_green_factors IS NOT DISTINCT FROM OLD.main_green_factors THEN
+            NEW.main_green_factors := spark_v2_to_v1(NEW.main_green_factors_v2);
+        ELSE NEW.main_green_factors_v2 := spark_v1_to_v2(NEW.main_green_factors); END IF;
+    END IF;
+
+    -- Star sums are computed by the writer (the ingestor fills them for v1
+    -- rows; there is no star sum trigger anymore). v2 writers get them filled
+    -- in here, level-accurate above 9.
+    IF v2_written THEN
+        NEW.blue_stars_sum := COALESCE((SELECT SUM(x % 1000) FROM unnest(NEW.blue_sparks_v2) AS x), 0);
+        NEW.pink_stars_sum := COALESCE((SELECT SUM(x % 1000) FROM unnest(NEW.pink_sparks_v2) AS x), 0);
+        NEW.green_stars_sum := COALESCE((SELECT SUM(x % 1000) FROM unnest(NEW.green_sparks_v2) AS x), 0);
+        NEW.white_stars_sum := COALESCE((SELECT SUM(x % 1000) FROM unnest(NEW.white_sparks_v2) AS x), 0);
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_inheritance_spark_sync ON inheritance;
+CREATE TRIGGER trg_inheritance_spark_sync
+BEFORE INSERT OR UPDATE ON inheritance
+FOR EACH ROW EXECUTE FUNCTION sync_spark_encodings();
+
+-- Maintenance writes (the v2 backfill) must not look like new data: skip
+-- provenance stamping and record versioning when umamoe.maintenance is on
+CREATE OR REPLACE FUNCTION archive_record_version() RETURNS TRIGGER AS $$
+DECLARE
+    old_row JSONB := to_jsonb(OLD);
+    new_batch TEXT;
+BEGIN
+    IF current_setting('umamoe.maintenance', true) = 'on' THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP = 'UPDATE' THEN
+        new_batch := NEW.source->>'batch_id';
+        IF new_batch IS NULL THEN
+            RETURN NULL;
+        END IF;
+    END IF;
+
+    INSERT INTO record_versions (table_name, record_key, account_id, data, reason, replaced_by_batch)
+    VALUES (
+        TG_TABLE_NAME,
+        CASE
+            WHEN TG_TABLE_NAME = 'support_card' THEN (old_row->>'account_id') || ':' || (old_row->>'support_card_id')
+            ELSE old_row->>'account_id'
+        END,
+        old_row->>'account_id',
+        old_row,
+        COALESCE(NULLIF(current_setting('umamoe.version_reason', true), ''), lower(TG_OP)),
+        new_batch
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION stamp_record_provenance() RETURNS TRIGGER AS $$
+DECLARE
+    session_source JSONB := NULLIF(current_setting('umamoe.source', true), '')::jsonb;
+BEGIN
+    IF current_setting('umamoe.restoring', true) = 'on'
+        OR current_setting('umamoe.maintenance', true) = 'on' THEN
+        RETURN NEW;
+    END IF;
+
+    NEW.ingested_at := CURRENT_TIMESTAMP;
+
+    -- Only fill in the session source when the writer didn't set one itself
+    IF TG_OP = 'INSERT' THEN
+        IF NEW.source IS NULL THEN
+            NEW.source := session_source;
+        END IF;
+    ELSIF NEW.source IS NOT DISTINCT FROM OLD.source THEN
+        NEW.source := session_source;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,14 @@
+-- no-transaction
+-- Migration: Indexes for the v2 spark columns
+-- Date: 2026-10-16
+-- Purpose: Same GIN/B-tree indexes as the v1 spark columns, built concurrently
+--          so writes to inheritance are not blocked while they build
+
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_inheritance_blue_sparks_v2_gin ON inheritance USING gin (blue_sparks_v2 gin__int_ops);
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_inheritance_pink_sparks_v2_gin ON inheritance USING gin (pink_sparks_v2 gin__int_ops);
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_inheritance_green_sparks_v2_gin ON inheritance USING gin (green_sparks_v2 gin__int_ops);
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_inheritance_white_sparks_v2_gin ON inheritance USING gin (white_sparks_v2 gin__int_ops);
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_inheritance_main_white_factors_v2_gin ON inheritance USING gin (main_white_factors_v2 gin__int_ops);
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_inheritance_main_blue_factors_v2 ON inheritance (main_blue_factors_v2);
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_inheritance_main_pink_factors_v2 ON inheritance (main_pink_factors_v2);
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_inheritance_main_green_factors_v2 ON inheritance (main_green_factors_v2);
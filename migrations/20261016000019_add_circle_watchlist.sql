@@ -0,0 +1,13 @@
+-- Migration: Circle watchlist
+-- Date: 2026-10-16
+-- Purpose: Let clients track a handful of rival circles without loading the
+--          full circle list
+
+-- Watched circles per client. Clients generate their own watch token and send
+-- it in the X-Watch-Token header; only its SHA-256 hash is stored here.
+CREATE TABLE IF NOT EXISTS circle_watchlist (
+    watch_token_hash TEXT NOT NULL,
+    circle_id BIGINT NOT NULL REFERENCES circles(circle_id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (watch_token_hash, circle_id)
+);
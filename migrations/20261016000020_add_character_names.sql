@@ -0,0 +1,14 @@
+-- Migration: Character names table
+-- Date: 2026-10-16
+-- Purpose: Replace the hardcoded character name table. Names are looked up
+--          here first, then in data/character_names.json bundled with the
+--          binary, then fall back to "Character {id}". IDs that hit the
+--          fallback are listed by GET /api/admin/character-names; after adding
+--          rows here, POST /api/admin/character-names/reload picks them up.
+
+-- Keyed by base character ID (1001 = Special Week), not the card variant ID
+CREATE TABLE IF NOT EXISTS character_names (
+    chara_id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,25 @@
+-- Migration: Completed task archive
+-- Date: 2026-10-16
+-- Purpose: Move completed tasks out of the hot tasks table so claims and
+--          pending lookups only touch the small set of live rows. Archived
+--          tasks are still served by the task GET endpoints.
+
+CREATE TABLE IF NOT EXISTS tasks_archive (
+    id INTEGER PRIMARY KEY,
+    task_type TEXT NOT NULL,
+    task_data JSONB NOT NULL,
+    priority INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP,
+    worker_id TEXT,
+    error_message TEXT,
+    account_id TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    archived_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- The archiver picks completed tasks by completion time
+CREATE INDEX IF NOT EXISTS idx_tasks_completed
+ON tasks (COALESCE(updated_at, created_at))
+WHERE status = 'completed';
@@ -0,0 +1,23 @@
+-- Migration: Circle awards
+-- Date: 2026-10-16
+-- Purpose: Store per-circle member awards for each finished month, computed
+--          by a monthly rollup job, for end-of-month recap posts
+
+-- award: 'most_fans' (value = fans gained that month),
+--        'most_consistent' (value = days with a fan gain),
+--        'biggest_day' (value = fans gained on award_date)
+CREATE TABLE IF NOT EXISTS circle_awards (
+    circle_id BIGINT NOT NULL REFERENCES circles(circle_id) ON DELETE CASCADE,
+    year INTEGER NOT NULL,
+    month INTEGER NOT NULL,
+    award TEXT NOT NULL,
+    viewer_id BIGINT NOT NULL,
+    value BIGINT NOT NULL,
+    award_date DATE,
+    computed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (circle_id, year, month, award)
+);
+
+-- The rollup job checks whether a month has been computed yet
+CREATE INDEX IF NOT EXISTS idx_circle_awards_month
+ON circle_awards (year, month);
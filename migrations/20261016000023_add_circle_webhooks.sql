@@ -0,0 +1,22 @@
+-- Migration: Circle rank-change webhooks
+-- Date: 2026-10-16
+-- Purpose: Let circle leaders get Discord notifications when their circle
+--          crosses rank thresholds or trades places with a rival
+
+-- One Discord webhook per circle. The leader picks a manage token (sent in the
+-- X-Webhook-Token header, only its SHA-256 hash is stored) and proves
+-- ownership by putting verification_code into the circle comment.
+CREATE TABLE IF NOT EXISTS circle_webhooks (
+    circle_id BIGINT PRIMARY KEY REFERENCES circles(circle_id) ON DELETE CASCADE,
+    webhook_url TEXT NOT NULL,
+    manage_token_hash TEXT NOT NULL,
+    verification_code TEXT NOT NULL,
+    verified_at TIMESTAMP,
+    rank_thresholds INTEGER[] NOT NULL DEFAULT '{100,500}',
+    rival_circle_id BIGINT REFERENCES circles(circle_id) ON DELETE SET NULL,
+    -- Events already posted on notified_on (competition date), to avoid repeats
+    notified_on DATE,
+    notified_events TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
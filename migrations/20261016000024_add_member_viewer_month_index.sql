@@ -0,0 +1,12 @@
+-- Migration: Viewer-to-circle lookup index
+-- Date: 2026-10-16
+-- Purpose: Resolve a viewer's current circle (latest month first) with an
+--          index-only scan instead of picking an arbitrary month's row
+
+CREATE INDEX IF NOT EXISTS idx_circle_member_fans_viewer_month
+ON circle_member_fans_monthly (viewer_id, year DESC, month DESC) INCLUDE (circle_id);
+
+-- Covered by the index above
+DROP INDEX IF EXISTS idx_circle_member_fans_viewer;
+
+ANALYZE circle_member_fans_monthly;
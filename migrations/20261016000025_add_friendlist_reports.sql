@@ -0,0 +1,16 @@
+-- Migration: Friendlist-full reports
+-- Date: 2026-10-16
+-- Purpose: Keep "friend list full" reports so repeated reports can trigger a
+--          recheck of the trainer
+
+-- One row per trainer and reporter. Reporters are identified by the SHA-256
+-- of their IP; a repeat report from the same IP counts again after 24 hours.
+CREATE TABLE IF NOT EXISTS friendlist_reports (
+    trainer_id TEXT NOT NULL,
+    reporter_hash TEXT NOT NULL,
+    reported_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (trainer_id, reporter_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_friendlist_reports_trainer_time
+ON friendlist_reports (trainer_id, reported_at DESC);
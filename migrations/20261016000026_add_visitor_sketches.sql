@@ -0,0 +1,12 @@
+-- Migration: Visitor sketches
+-- Date: 2026-10-16
+-- Purpose: Count unique visitors server-side with one HyperLogLog sketch per day
+--          instead of trusting the client's daily-visit counter
+
+-- 4096 one-byte registers per day, each holding the highest rank seen for it.
+-- Visitors are hashed (salted IP + user agent) before they reach the sketch,
+-- and the rank alone cannot be traced back to a visitor.
+CREATE TABLE IF NOT EXISTS visitor_sketches (
+    date DATE PRIMARY KEY,
+    registers BYTEA NOT NULL
+);
@@ -0,0 +1,20 @@
+-- Migration: Record creation time
+-- Date: 2026-10-16
+-- Purpose: Know when inheritance and support_card rows were first tracked, for
+--          the upload volume time series (ingested_at changes on every update)
+
+-- Added without a default first so existing rows aren't rewritten; their
+-- creation time is unknown and stays NULL. Only new rows get the default.
+ALTER TABLE inheritance ADD COLUMN IF NOT EXISTS created_at TIMESTAMP;
+ALTER TABLE inheritance ALTER COLUMN created_at SET DEFAULT CURRENT_TIMESTAMP;
+
+ALTER TABLE support_card ADD COLUMN IF NOT EXISTS created_at TIMESTAMP;
+ALTER TABLE support_card ALTER COLUMN created_at SET DEFAULT CURRENT_TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_inheritance_created_at
+ON inheritance (created_at)
+WHERE created_at IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_support_card_created_at
+ON support_card (created_at)
+WHERE created_at IS NOT NULL;
@@ -0,0 +1,17 @@
+-- Migration: Support card metadata
+-- Date: 2026-10-16
+-- Purpose: Support card names/rarity/type imported from game master data
+--          (GAME_DATA_DIR at startup or POST /api/admin/game-data/support-cards),
+--          replacing the "Support Card {id}" placeholder on share pages
+
+CREATE TABLE IF NOT EXISTS support_card_meta (
+    support_card_id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    -- 1 = R, 2 = SR, 3 = SSR
+    rarity INTEGER NOT NULL,
+    -- speed, stamina, power, guts, wit, friend or group
+    card_type TEXT NOT NULL,
+    -- Base character the card depicts, if any
+    chara_id INTEGER,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
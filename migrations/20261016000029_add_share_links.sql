@@ -0,0 +1,16 @@
+-- Migration: Short share links
+-- Date: 2026-10-16
+-- Purpose: Opaque tokens for long share URLs (POST /api/share/shorten),
+--          resolved by GET /s/x/{token}
+
+CREATE TABLE IF NOT EXISTS share_links (
+    token TEXT PRIMARY KEY,
+    -- Path (with query and fragment) on honse.moe the token redirects to
+    target TEXT NOT NULL,
+    hits BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP
+);
+
+-- Shortening the same URL twice returns the same token
+CREATE UNIQUE INDEX IF NOT EXISTS idx_share_links_target ON share_links (target);
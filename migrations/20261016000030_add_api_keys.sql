@@ -0,0 +1,19 @@
+-- Migration: API keys
+-- Date: 2026-10-16
+-- Purpose: Keys for trusted integrations (Discord bots, partner sites), sent as
+--          X-Api-Key instead of a Turnstile token. Minted and revoked via
+--          /api/admin/api-keys; only a SHA-256 of each key is stored.
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    key_id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    -- First characters of the key, to tell keys apart in listings
+    key_prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    -- Any of search, tasks, circles, admin
+    scopes TEXT[] NOT NULL,
+    rate_limit_per_minute INTEGER NOT NULL DEFAULT 120,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP,
+    revoked_at TIMESTAMP
+);
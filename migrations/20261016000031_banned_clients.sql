@@ -0,0 +1,22 @@
+-- Migration: Banned clients
+-- Date: 2026-10-16
+-- Purpose: Ban list checked before any other middleware. A ban covers an IP
+--          range, an API key, or both; temporary bans have an expiry. Bans are
+--          added by admins (/api/admin/bans) or automatically after repeated
+--          Turnstile failures or rate-limit violations.
+
+CREATE TABLE IF NOT EXISTS banned_clients (
+    ban_id SERIAL PRIMARY KEY,
+    -- A single address is stored as /32 (or /128)
+    ip_range CIDR,
+    api_key_id INTEGER REFERENCES api_keys(key_id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    -- 'admin' or 'auto'
+    source TEXT NOT NULL DEFAULT 'admin',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- NULL for permanent bans
+    expires_at TIMESTAMP,
+    CONSTRAINT banned_clients_target CHECK (ip_range IS NOT NULL OR api_key_id IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_banned_clients_expires_at ON banned_clients (expires_at);
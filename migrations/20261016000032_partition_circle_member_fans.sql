@@ -0,0 +1,206 @@
+-- Migration: Partition circle_member_fans_monthly by month
+-- Date: 2026-10-16
+-- Purpose: The table gains a row per circle member every month while nearly all
+--          queries read a single month. Range partitions on (year, month) let
+--          those queries skip every other month, and old months can be moved
+--          out whole (archive_circle_member_fans_partitions) instead of
+--          deleting rows.
+--          (year, month, circle_id, viewer_id) becomes the primary key, so
+--          duplicate rows for a member and month are merged, keeping the
+--          latest one. Rows that don't make it into the partitioned table
+--          (the superseded duplicates, and months outside 1-12 that fit no
+--          partition) are moved to archive.circle_member_fans_quarantine.
+
+CREATE SCHEMA IF NOT EXISTS archive;
+
+ALTER TABLE circle_member_fans_monthly RENAME TO circle_member_fans_monthly_unpartitioned;
+DROP TRIGGER IF EXISTS trg_circle_member_fans_track_membership ON circle_member_fans_monthly_unpartitioned;
+
+CREATE TABLE circle_member_fans_monthly (
+    id INTEGER NOT NULL DEFAULT nextval('circle_member_fans_monthly_id_seq'),
+    circle_id BIGINT NOT NULL,
+    viewer_id BIGINT NOT NULL,
+    year INTEGER NOT NULL,
+    month INTEGER NOT NULL,
+    daily_fans BIGINT[] NOT NULL,
+    last_updated TIMESTAMP,
+    CONSTRAINT circle_member_fans_monthly_month_pkey PRIMARY KEY (year, month, circle_id, viewer_id)
+) PARTITION BY RANGE (year, month);
+
+ALTER SEQUENCE circle_member_fans_monthly_id_seq OWNED BY circle_member_fans_monthly.id;
+
+-- Partitions are named circle_member_fans_monthly_YYYY_MM. Months are created
+-- ahead of time by the server and on demand by ingestion; writers that insert
+-- directly need the month's partition to exist.
+CREATE OR REPLACE FUNCTION ensure_circle_member_fans_partition(p_year INTEGER, p_month INTEGER)
+RETURNS TEXT AS $$
+DECLARE
+    partition_name TEXT := format('circle_member_fans_monthly_%s_%s', p_year, lpad(p_month::text, 2, '0'));
+BEGIN
+    IF p_month NOT BETWEEN 1 AND 12 THEN
+        RAISE EXCEPTION 'invalid month %-%', p_year, p_month;
+    END IF;
+
+    IF to_regclass(format('public.%I', partition_name)) IS NULL THEN
+        -- Serializes concurrent writers creating the same month
+        PERFORM pg_advisory_xact_lock(hashtext('circle_member_fans_partition'));
+        EXECUTE format(
+            'CREATE TABLE IF NOT EXISTS public.%I PARTITION OF circle_member_fans_monthly FOR VALUES FROM (%s, %s) TO (%s, %s)',
+            partition_name,
+            p_year, p_month,
+            CASE WHEN p_month = 12 THEN p_year + 1 ELSE p_year END,
+            CASE WHEN p_month = 12 THEN 1 ELSE p_month + 1 END
+        );
+    END IF;
+
+    RETURN partition_name;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Detaches the partitions of months before (p_before_year, p_before_month) and
+-- moves them to the archive schema, returning their names. A month written
+-- again after it was archived is merged into the archived table.
+CREATE OR REPLACE FUNCTION archive_circle_member_fans_partitions(p_before_year INTEGER, p_before_month INTEGER)
+RETURNS SETOF TEXT AS $$
+DECLARE
+    part RECORD;
+BEGIN
+    FOR part IN
+        SELECT c.relname
+        FROM pg_inherits i
+        JOIN pg_class c ON c.oid = i.inhrelid
+        WHERE i.inhparent = 'public.circle_member_fans_monthly'::regclass
+          AND c.relname ~ '^circle_member_fans_monthly_[0-9]{4}_[0-9]{2}$'
+          AND (substr(c.relname, 28, 4)::INTEGER, substr(c.relname, 33, 2)::INTEGER)
+              < (p_before_year, p_before_month)
+        ORDER BY c.relname
+    LOOP
+        EXECUTE format('ALTER TABLE circle_member_fans_monthly DETACH PARTITION public.%I', part.relname);
+        IF to_regclass(format('archive.%I', part.relname)) IS NULL THEN
+            EXECUTE format('ALTER TABLE public.%I SET SCHEMA archive', part.relname);
+        ELSE
+            EXECUTE format(
+                'INSERT INTO archive.%I SELECT * FROM public.%I
+                 ON CONFLICT (year, month, circle_id, viewer_id) DO UPDATE
+                 SET daily_fans = EXCLUDED.daily_fans, last_updated = EXCLUDED.last_updated',
+                part.relname, part.relname
+            );
+            EXECUTE format('DROP TABLE public.%I', part.relname);
+        END IF;
+        RETURN NEXT part.relname;
+    END LOOP;
+END;
+$$ LANGUAGE plpgsql;
+
+SELECT ensure_circle_member_fans_partition(year, month)
+FROM (
+    SELECT DISTINCT year, month
+    FROM circle_member_fans_monthly_unpartitioned
+    WHERE year IS NOT NULL AND month BETWEEN 1 AND 12
+) months;
+
+-- The current and next competition months' partitions are created from
+-- COMPETITION_TIMEZONE once the competition month functions exist
+-- (20261016000046_add_competition_month_functions.sql)
+
+INSERT INTO circle_member_fans_monthly (id, circle_id, viewer_id, year, month, daily_fans, last_updated)
+SELECT DISTINCT ON (year, month, circle_id, viewer_id)
+    id, circle_id, viewer_id, year, month, daily_fans, last_updated
+FROM circle_member_fans_monthly_unpartitioned
+WHERE year IS NOT NULL AND month BETWEEN 1 AND 12
+ORDER BY year, month, circle_id, viewer_id, last_updated DESC NULLS LAST, id DESC;
+
+-- Everything left behind, with the reason, for review
+CREATE TABLE archive.circle_member_fans_quarantine AS
+SELECT NULL::TEXT AS reason, u.*
+FROM circle_member_fans_monthly_unpartitioned u
+WITH NO DATA;
+
+INSERT INTO archive.circle_member_fans_quarantine
+SELECT
+    CASE WHEN u.year IS NOT NULL AND u.month BETWEEN 1 AND 12 THEN 'duplicate' ELSE 'invalid_month' END,
+    u.*
+FROM circle_member_fans_monthly_unpartitioned u
+WHERE NOT EXISTS (
+    SELECT 1 FROM circle_member_fans_monthly p
+    WHERE p.year = u.year AND p.month = u.month
+      AND p.circle_id = u.circle_id AND p.viewer_id = u.viewer_id
+      AND p.id = u.id
+);
+
+DO $$
+DECLARE
+    source_rows BIGINT;
+    moved_rows BIGINT;
+    quarantined_rows BIGINT;
+BEGIN
+    SELECT COUNT(*) INTO source_rows FROM circle_member_fans_monthly_unpartitioned;
+    SELECT COUNT(*) INTO moved_rows FROM circle_member_fans_monthly;
+    SELECT COUNT(*) INTO quarantined_rows FROM archive.circle_member_fans_quarantine;
+
+    IF moved_rows + quarantined_rows <> source_rows THEN
+        RAISE EXCEPTION 'circle_member_fans_monthly: % rows, % moved and % quarantined',
+            source_rows, moved_rows, quarantined_rows;
+    END IF;
+    IF quarantined_rows > 0 THEN
+        RAISE WARNING 'circle_member_fans_monthly: % rows moved to archive.circle_member_fans_quarantine',
+            quarantined_rows;
+    END IF;
+END $$;
+
+DROP TABLE circle_member_fans_monthly_unpartitioned;
+
+-- Same lookups as before; (year, month, circle_id) is covered by the primary key
+CREATE INDEX idx_circle_member_fans_search
+ON circle_member_fans_monthly (circle_id, year, month);
+
+CREATE INDEX idx_circle_member_fans_viewer_month
+ON circle_member_fans_monthly (viewer_id, year DESC, month DESC) INCLUDE (circle_id);
+
+CREATE INDEX idx_circle_member_fans_viewer_text
+ON circle_member_fans_monthly ((viewer_id::text));
+
+CREATE TRIGGER trg_circle_member_fans_track_membership
+AFTER INSERT OR UPDATE ON circle_member_fans_monthly
+FOR EACH ROW EXECUTE FUNCTION track_circle_membership();
+
+-- Filter on the month directly so only its partition is scanned
+CREATE OR REPLACE FUNCTION circle_projected_point(p_circle_id BIGINT, p_current_point BIGINT)
+RETURNS BIGINT AS $$
+    WITH month AS (
+        SELECT
+            EXTRACT(YEAR FROM now_jst)::INTEGER AS year,
+            EXTRACT(MONTH FROM now_jst)::INTEGER AS month,
+            EXTRACT(DAY FROM date_trunc('month', now_jst) + INTERVAL '1 month - 1 day')::INTEGER AS days_in_month
+        FROM (SELECT CURRENT_TIMESTAMP AT TIME ZONE 'Asia/Tokyo' AS now_jst) n
+    ),
+    member_days AS (
+        SELECT x.d, x.f, lag(x.f) OVER (PARTITION BY cm.id ORDER BY x.d) AS prev
+        FROM circle_member_fans_monthly cm
+        CROSS JOIN LATERAL unnest(cm.daily_fans) WITH ORDINALITY AS x(f, d)
+        WHERE cm.circle_id = p_circle_id
+          AND cm.year = (SELECT year FROM month)
+          AND cm.month = (SELECT month FROM month)
+    ),
+    circle_days AS (
+        SELECT d, SUM(f - prev) AS gain
+        FROM member_days
+        WHERE f > 0 AND prev > 0
+        GROUP BY d
+        ORDER BY d DESC
+        LIMIT 7
+    ),
+    weighted AS (
+        SELECT
+            MAX(d) AS last_day,
+            SUM(gain * (8 - age)) / NULLIF(SUM(8 - age), 0) AS daily_gain
+        FROM (SELECT d, gain, row_number() OVER (ORDER BY d DESC) AS age FROM circle_days) r
+    )
+    SELECT CASE
+        WHEN w.daily_gain IS NULL OR p_current_point IS NULL THEN NULL
+        ELSE p_current_point + ROUND(GREATEST(w.daily_gain, 0) * GREATEST(m.days_in_month - w.last_day, 0))::BIGINT
+    END
+    FROM weighted w, month m
+$$ LANGUAGE sql STABLE COST 1000;
+
+ANALYZE circle_member_fans_monthly;
@@ -0,0 +1,30 @@
+-- Migration: Trainer retention flag
+-- Date: 2026-10-16
+-- Purpose: Trainers not refreshed for TRAINER_RETENTION_MONTHS are flagged stale
+--          by the retention job and left out of searches unless
+--          include_stale=true. Rows stay in place, so a trainer that gets
+--          refreshed again simply loses the flag.
+
+ALTER TABLE trainer ADD COLUMN IF NOT EXISTS stale BOOLEAN NOT NULL DEFAULT false;
+
+-- Candidates for flagging
+CREATE INDEX IF NOT EXISTS idx_trainer_last_updated_fresh
+ON trainer (last_updated) WHERE NOT stale;
+
+CREATE INDEX IF NOT EXISTS idx_trainer_stale
+ON trainer (account_id) WHERE stale;
+
+-- New data for a trainer makes it current again right away
+CREATE OR REPLACE FUNCTION clear_trainer_stale() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.stale := false;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_trainer_clear_stale ON trainer;
+CREATE TRIGGER trg_trainer_clear_stale
+BEFORE UPDATE OF last_updated ON trainer
+FOR EACH ROW
+WHEN (NEW.last_updated IS DISTINCT FROM OLD.last_updated AND OLD.stale)
+EXECUTE FUNCTION clear_trainer_stale();
@@ -0,0 +1,22 @@
+-- Migration: Trainer data deletion on request
+-- Date: 2026-10-16
+-- Purpose: Trainers can have their data deleted after proving they own the
+--          account (a code placed in their in-game comment). Deleted accounts
+--          are remembered so ingestion and task submission don't collect
+--          them again.
+
+-- Open requests; the code must show up in the trainer's comment as scraped
+-- by a worker before expires_at, which sets verified_at
+CREATE TABLE IF NOT EXISTS privacy_deletion_requests (
+    account_id TEXT PRIMARY KEY,
+    code TEXT NOT NULL,
+    requested_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL,
+    verified_at TIMESTAMP
+);
+
+-- Accounts whose data was deleted; nothing else about them is kept
+CREATE TABLE IF NOT EXISTS privacy_deletions (
+    account_id TEXT PRIMARY KEY,
+    deleted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,40 @@
+-- Migration: User accounts with bookmarks
+-- Date: 2026-10-16
+-- Purpose: Lightweight accounts (anonymous, or signed in with Discord) that
+--          bookmark trainers, inheritance records and circles, and are
+--          notified when a bookmarked trainer's friend list has room again.
+
+CREATE TABLE IF NOT EXISTS users (
+    user_id BIGSERIAL PRIMARY KEY,
+    -- NULL for anonymous accounts
+    discord_id TEXT UNIQUE,
+    display_name TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_seen_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- target_id: the trainer's account_id, the inheritance_id or the circle_id
+CREATE TABLE IF NOT EXISTS user_bookmarks (
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    target_type TEXT NOT NULL CHECK (target_type IN ('trainer', 'inheritance', 'circle')),
+    target_id TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, target_type, target_id)
+);
+
+-- Who bookmarked a trainer, when ingestion sees its friend list open up
+CREATE INDEX IF NOT EXISTS idx_user_bookmarks_target
+ON user_bookmarks (target_type, target_id);
+
+CREATE TABLE IF NOT EXISTS user_notifications (
+    notification_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    account_id TEXT,
+    data JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    read_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_notifications_user
+ON user_notifications (user_id, notification_id DESC);
@@ -0,0 +1,37 @@
+-- Migration: Trainer availability notifications
+-- Date: 2026-10-16
+-- Purpose: Users subscribe to a trainer whose friend list is full; once a
+--          scrape shows it below 1000 followers again, a dispatcher delivers
+--          the notification to a Discord webhook or a browser (web push),
+--          retrying failed deliveries with backoff.
+
+-- status: waiting (friend list still full), pending (opened up, to deliver),
+-- delivered, or failed (gave up, or the webhook/push subscription is gone)
+CREATE TABLE IF NOT EXISTS availability_subscriptions (
+    subscription_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    account_id TEXT NOT NULL,
+    channel TEXT NOT NULL CHECK (channel IN ('discord', 'web_push')),
+    -- Discord webhook URL, or the push service endpoint
+    endpoint TEXT NOT NULL,
+    -- Web push: the browser's p256dh public key and auth secret (base64url)
+    p256dh TEXT,
+    auth TEXT,
+    status TEXT NOT NULL DEFAULT 'waiting'
+        CHECK (status IN ('waiting', 'pending', 'delivered', 'failed')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    triggered_at TIMESTAMP,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP,
+    last_error TEXT,
+    delivered_at TIMESTAMP,
+    UNIQUE (user_id, account_id, channel, endpoint)
+);
+
+-- Subscriptions to trigger when a trainer is ingested
+CREATE INDEX IF NOT EXISTS idx_availability_subscriptions_waiting
+ON availability_subscriptions (account_id) WHERE status = 'waiting';
+
+-- Deliveries due
+CREATE INDEX IF NOT EXISTS idx_availability_subscriptions_pending
+ON availability_subscriptions (next_attempt_at) WHERE status = 'pending';
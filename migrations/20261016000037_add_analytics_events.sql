@@ -0,0 +1,30 @@
+-- Migration: Product analytics events
+-- Date: 2026-10-16
+-- Purpose: Schema-validated frontend events posted to /api/events (searches,
+--          copied results, filters used), kept when ClickHouse isn't configured.
+--          Nothing identifying is stored: no IP or account, only the random
+--          session ID the frontend picks.
+
+CREATE TABLE IF NOT EXISTS analytics_events (
+    event_id BIGSERIAL PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    occurred_at TIMESTAMP NOT NULL,
+    received_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    session_id TEXT,
+    properties JSONB NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX IF NOT EXISTS idx_analytics_events_type_time
+ON analytics_events (event_type, occurred_at);
+
+-- Append-only: events are never rewritten (old ones may still be deleted)
+CREATE OR REPLACE FUNCTION analytics_events_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'analytics_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS analytics_events_no_update ON analytics_events;
+CREATE TRIGGER analytics_events_no_update
+BEFORE UPDATE ON analytics_events
+FOR EACH ROW EXECUTE FUNCTION analytics_events_append_only();
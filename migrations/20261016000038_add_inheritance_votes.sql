@@ -0,0 +1,40 @@
+-- Migration: Community votes on inheritance records
+-- Date: 2026-10-16
+-- Purpose: Visitors upvote or report inheritance records (one vote per client
+--          fingerprint and record); the per-record aggregate backs
+--          sort_by=community_score in the unified search. Votes lose half
+--          their weight every COMMUNITY_SCORE_HALF_LIFE_DAYS so records that
+--          collected votes long ago don't stay on top.
+
+CREATE TABLE IF NOT EXISTS inheritance_votes (
+    inheritance_id INTEGER NOT NULL REFERENCES inheritance(inheritance_id) ON DELETE CASCADE,
+    -- Salted hash of the voter's IP and user agent
+    voter_hash TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('up', 'report')),
+    reason TEXT,
+    voted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (inheritance_id, voter_hash)
+);
+
+-- score is the sum of vote weights (+1 per upvote, negative per report), each
+-- decayed to scored_at; decayed_community_score brings it forward to now
+CREATE TABLE IF NOT EXISTS inheritance_community_scores (
+    inheritance_id INTEGER PRIMARY KEY REFERENCES inheritance(inheritance_id) ON DELETE CASCADE,
+    upvotes INTEGER NOT NULL DEFAULT 0,
+    reports INTEGER NOT NULL DEFAULT 0,
+    score DOUBLE PRECISION NOT NULL DEFAULT 0,
+    scored_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Decay a score from scored_at to now (the exponent is capped so very old
+-- scores become ~0 instead of underflowing)
+CREATE OR REPLACE FUNCTION decayed_community_score(
+    score DOUBLE PRECISION,
+    scored_at TIMESTAMP,
+    half_life_secs DOUBLE PRECISION
+) RETURNS DOUBLE PRECISION AS $$
+    SELECT score * power(
+        0.5::double precision,
+        LEAST(GREATEST(EXTRACT(EPOCH FROM (LOCALTIMESTAMP - scored_at)), 0) / half_life_secs, 1000)
+    )
+$$ LANGUAGE sql STABLE PARALLEL SAFE;
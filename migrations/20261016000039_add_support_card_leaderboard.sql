@@ -0,0 +1,22 @@
+-- Migration: Support card leaderboard
+-- Date: 2026-10-16
+-- Purpose: Weekly snapshot of how many trainers lend each support card and how
+--          many of those are max limit broken, for the community tier list
+
+-- week_start is the Monday of the ISO week (competition timezone).
+-- trainers is the week's sample size: non-stale trainers lending any card.
+-- MLB = limit_break_count 4; rates are fractions (0-1) of trainers / owners.
+CREATE TABLE IF NOT EXISTS support_card_leaderboard (
+    week_start DATE NOT NULL,
+    support_card_id INTEGER NOT NULL,
+    trainers INTEGER NOT NULL,
+    owners INTEGER NOT NULL,
+    usage_rate DOUBLE PRECISION NOT NULL,
+    usage_rank INTEGER NOT NULL,
+    mlb_count INTEGER NOT NULL,
+    mlb_rate DOUBLE PRECISION NOT NULL,
+    mlb_rank INTEGER NOT NULL,
+    avg_limit_break DOUBLE PRECISION,
+    computed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (week_start, support_card_id)
+);
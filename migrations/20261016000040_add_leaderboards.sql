@@ -0,0 +1,37 @@
+-- Migration: Global leaderboards
+-- Date: 2026-10-16
+-- Purpose: Daily top-100 snapshots of trainers/records per leaderboard kind,
+--          computed by a scheduled job so leaderboard pages don't run searches.
+--          Rank changes are derived by comparing two snapshots.
+
+-- kind: 'win_count', 'white_count', 'main_white_count' (value of the trainer's
+--       inheritance record) or 'availability_streak' (days in a row the
+--       trainer had room on their friend list)
+CREATE TABLE IF NOT EXISTS leaderboard_snapshots (
+    kind TEXT NOT NULL,
+    snapshot_date DATE NOT NULL,
+    computed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (kind, snapshot_date)
+);
+
+CREATE TABLE IF NOT EXISTS leaderboard_entries (
+    kind TEXT NOT NULL,
+    snapshot_date DATE NOT NULL,
+    account_id TEXT NOT NULL,
+    inheritance_id INTEGER,
+    value BIGINT NOT NULL,
+    rank INTEGER NOT NULL,
+    PRIMARY KEY (kind, snapshot_date, account_id),
+    FOREIGN KEY (kind, snapshot_date) REFERENCES leaderboard_snapshots(kind, snapshot_date) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_leaderboard_entries_rank
+ON leaderboard_entries (kind, snapshot_date, rank);
+
+-- Trainers with room on their friend list as of checked_on (the last job run),
+-- available on every run since `since`. Rows of trainers that filled up are removed.
+CREATE TABLE IF NOT EXISTS trainer_availability_streaks (
+    account_id TEXT PRIMARY KEY REFERENCES trainer(account_id) ON DELETE CASCADE,
+    since DATE NOT NULL,
+    checked_on DATE NOT NULL
+);
@@ -0,0 +1,23 @@
+-- Migration: Announcements
+-- Date: 2026-10-16
+-- Purpose: Notices shown by the frontend (maintenance windows, scrape delays,
+--          new features), managed through the admin API instead of deploys
+
+-- Visible from published_at (scheduled when in the future) until expires_at;
+-- drafts (published = false) are only listed to admins
+CREATE TABLE IF NOT EXISTS announcements (
+    announcement_id SERIAL PRIMARY KEY,
+    kind TEXT NOT NULL CHECK (kind IN ('maintenance', 'scrape_delay', 'feature', 'info')),
+    title TEXT NOT NULL,
+    body TEXT NOT NULL DEFAULT '',
+    link_url TEXT,
+    published BOOLEAN NOT NULL DEFAULT true,
+    published_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (expires_at IS NULL OR expires_at > published_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_announcements_published_at
+ON announcements (published_at DESC) WHERE published;
@@ -0,0 +1,49 @@
+-- Migration: Team stadium browsing
+-- Date: 2026-10-16
+-- Purpose: team_stadium predates the migrations and was only counted so far.
+--          Record the columns /api/team-stadium reads (creating the table on
+--          fresh databases) and index the search filters.
+
+-- One row per team member: distance_type 1 = sprint, 2 = mile, 3 = medium,
+-- 4 = long, 5 = dirt; member_id is the slot within that distance's team;
+-- card_id is the character card (chara_id * 100 + outfit)
+CREATE TABLE IF NOT EXISTS team_stadium (
+    account_id TEXT NOT NULL,
+    distance_type INTEGER NOT NULL,
+    member_id INTEGER NOT NULL,
+    trained_chara_id INTEGER,
+    card_id INTEGER NOT NULL,
+    running_style INTEGER,
+    speed INTEGER,
+    stamina INTEGER,
+    power INTEGER,
+    guts INTEGER,
+    wiz INTEGER,
+    rank_score INTEGER,
+    skills INTEGER[],
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, distance_type, member_id)
+);
+
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS trained_chara_id INTEGER;
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS running_style INTEGER;
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS speed INTEGER;
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS stamina INTEGER;
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS power INTEGER;
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS guts INTEGER;
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS wiz INTEGER;
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS rank_score INTEGER;
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS skills INTEGER[];
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_team_stadium_account
+ON team_stadium (account_id, distance_type, member_id);
+
+CREATE INDEX IF NOT EXISTS idx_team_stadium_card_score
+ON team_stadium (card_id, rank_score DESC);
+
+CREATE INDEX IF NOT EXISTS idx_team_stadium_chara_score
+ON team_stadium ((card_id / 100), rank_score DESC);
+
+CREATE INDEX IF NOT EXISTS idx_team_stadium_distance_score
+ON team_stadium (distance_type, rank_score DESC);
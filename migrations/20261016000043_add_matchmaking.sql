@@ -0,0 +1,36 @@
+-- Migration: Friend matchmaking queue
+-- Date: 2026-10-16
+-- Purpose: Users looking for a parent open a ticket; the backend suggests an
+--          available trainer with that inheritance and reserves them to the
+--          ticket for a few minutes, so concurrent users get different IDs.
+
+-- status: waiting (no trainer free yet) or matched (account_id reserved until
+-- reserved_until). requester_hash identifies the client (salted hash of IP
+-- and user agent); each client keeps one ticket. Tickets are deleted when
+-- cancelled or past expires_at.
+CREATE TABLE IF NOT EXISTS matchmaking_tickets (
+    ticket_id TEXT PRIMARY KEY,
+    requester_hash TEXT NOT NULL,
+    -- Base character (1007) or card (100701) of the wanted main parent
+    main_parent_id INTEGER NOT NULL,
+    min_parent_rank INTEGER,
+    min_win_count INTEGER,
+    status TEXT NOT NULL DEFAULT 'waiting'
+        CHECK (status IN ('waiting', 'matched')),
+    account_id TEXT,
+    reserved_until TIMESTAMP,
+    -- Trainers suggested before and passed on
+    skipped TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_matchmaking_tickets_requester
+ON matchmaking_tickets (requester_hash);
+
+-- Trainers currently held by a ticket
+CREATE INDEX IF NOT EXISTS idx_matchmaking_tickets_reserved
+ON matchmaking_tickets (account_id, reserved_until) WHERE status = 'matched';
+
+CREATE INDEX IF NOT EXISTS idx_matchmaking_tickets_expires
+ON matchmaking_tickets (expires_at);
@@ -0,0 +1,31 @@
+-- Migration: Content reports and trainer name overrides
+-- Date: 2026-10-16
+-- Purpose: Users report offensive trainer names and circle names/comments into
+--          a moderation queue; admins resolve them by masking the text (a
+--          trainer display_name_override, or the circle moderation override)
+--          or dismissing the reports.
+
+-- Shown instead of the game-sourced name wherever a trainer name is read
+ALTER TABLE trainer ADD COLUMN IF NOT EXISTS display_name_override TEXT;
+
+-- target_id is the trainer account ID or the circle ID. reporter_hash
+-- identifies the client (salted hash of IP and user agent); each client has
+-- one open report per target.
+CREATE TABLE IF NOT EXISTS content_reports (
+    report_id BIGSERIAL PRIMARY KEY,
+    target_kind TEXT NOT NULL
+        CHECK (target_kind IN ('trainer_name', 'circle_name', 'circle_comment')),
+    target_id TEXT NOT NULL,
+    reason TEXT,
+    reporter_hash TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'actioned', 'dismissed')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    resolved_at TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_content_reports_open_reporter
+ON content_reports (target_kind, target_id, reporter_hash) WHERE status = 'open';
+
+CREATE INDEX IF NOT EXISTS idx_content_reports_status
+ON content_reports (status, target_kind, target_id);
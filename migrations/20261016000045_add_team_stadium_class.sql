@@ -0,0 +1,11 @@
+-- Migration: Team stadium class
+-- Date: 2026-10-16
+-- Purpose: /api/stats/team-stadium breaks usage down by team class, which the
+--          scraper records per member row alongside the team it read.
+
+-- Team class of the trainer when the team was scraped (1 = lowest, 6 = highest);
+-- NULL for rows scraped before the class was recorded
+ALTER TABLE team_stadium ADD COLUMN IF NOT EXISTS team_class INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_team_stadium_class
+ON team_stadium (team_class, (card_id / 100));
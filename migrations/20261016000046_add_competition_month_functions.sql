@@ -0,0 +1,135 @@
+-- Migration: Competition month boundaries
+-- Date: 2026-10-16
+-- Purpose: Ranking months start at 12:00 on the 1st in the competition timezone
+--          (COMPETITION_TIMEZONE). The server hands the offset to every
+--          connection as umamoe.competition_offset, and these functions derive
+--          the boundaries from it the same way src/competition.rs does, replacing
+--          the Asia/Tokyo and Europe/Berlin constants in the objects below.
+
+-- Competition UTC offset; sessions that didn't set umamoe.competition_offset
+-- (psql, cron) get the server default, JST
+CREATE OR REPLACE FUNCTION competition_offset() RETURNS INTERVAL AS $$
+    SELECT COALESCE(NULLIF(current_setting('umamoe.competition_offset', true), ''), '+09:00')::INTERVAL
+$$ LANGUAGE sql STABLE;
+
+-- Start of a ranking month, p_months relative to the current one
+CREATE OR REPLACE FUNCTION competition_month_start(p_months INTEGER DEFAULT 0)
+RETURNS TIMESTAMPTZ AS $$
+    SELECT (
+        date_trunc('month', (CURRENT_TIMESTAMP AT TIME ZONE competition_offset()) - INTERVAL '12 hours')
+        + make_interval(months => p_months)
+        + INTERVAL '12 hours'
+    ) AT TIME ZONE competition_offset()
+$$ LANGUAGE sql STABLE;
+
+-- Ranking day: the competition date, rolling over at 12:00 like the months, so
+-- the last day of a month holds its final standings
+CREATE OR REPLACE FUNCTION competition_date() RETURNS DATE AS $$
+    SELECT ((CURRENT_TIMESTAMP AT TIME ZONE competition_offset()) - INTERVAL '12 hours')::DATE
+$$ LANGUAGE sql STABLE;
+
+-- circles.last_updated is server time, which Postgres compares against the
+-- boundaries in the session TimeZone
+DROP MATERIALIZED VIEW IF EXISTS circle_live_ranks;
+
+CREATE MATERIALIZED VIEW circle_live_ranks AS
+SELECT
+    circle_id,
+    RANK() OVER (ORDER BY monthly_point DESC NULLS LAST) as live_rank,
+    RANK() OVER (ORDER BY yesterday_points DESC NULLS LAST) as live_yesterday_rank
+FROM circles
+WHERE (archived IS NULL OR archived = false)
+  AND last_updated >= competition_month_start(0)
+  AND last_updated < competition_month_start(1);
+
+CREATE UNIQUE INDEX idx_circle_live_ranks_id ON circle_live_ranks (circle_id);
+CREATE INDEX idx_circle_live_ranks_rank ON circle_live_ranks (live_rank);
+
+-- Member fan partitions for the current and next competition months; the
+-- server keeps creating them ahead of time from here on
+SELECT ensure_circle_member_fans_partition(
+    EXTRACT(YEAR FROM month_start)::INTEGER,
+    EXTRACT(MONTH FROM month_start)::INTEGER
+)
+FROM (
+    SELECT competition_month_start(months) AT TIME ZONE competition_offset() AS month_start
+    FROM generate_series(0, 1) AS months
+) upcoming;
+
+-- Projects over the current competition month
+CREATE OR REPLACE FUNCTION circle_projected_point(p_circle_id BIGINT, p_current_point BIGINT)
+RETURNS BIGINT AS $$
+    WITH month AS (
+        SELECT
+            EXTRACT(YEAR FROM month_start)::INTEGER AS year,
+            EXTRACT(MONTH FROM month_start)::INTEGER AS month,
+            EXTRACT(DAY FROM date_trunc('month', month_start) + INTERVAL '1 month - 1 day')::INTEGER AS days_in_month
+        FROM (SELECT competition_month_start() AT TIME ZONE competition_offset() AS month_start) n
+    ),
+    member_days AS (
+        SELECT x.d, x.f, lag(x.f) OVER (PARTITION BY cm.id ORDER BY x.d) AS prev
+        FROM circle_member_fans_monthly cm
+        CROSS JOIN LATERAL unnest(cm.daily_fans) WITH ORDINALITY AS x(f, d)
+        WHERE cm.circle_id = p_circle_id
+          AND cm.year = (SELECT year FROM month)
+          AND cm.month = (SELECT month FROM month)
+    ),
+    circle_days AS (
+        SELECT d, SUM(f - prev) AS gain
+        FROM member_days
+        WHERE f > 0 AND prev > 0
+        GROUP BY d
+        ORDER BY d DESC
+        LIMIT 7
+    ),
+    weighted AS (
+        SELECT
+            MAX(d) AS last_day,
+            SUM(gain * (8 - age)) / NULLIF(SUM(8 - age), 0) AS daily_gain
+        FROM (SELECT d, gain, row_number() OVER (ORDER BY d DESC) AS age FROM circle_days) r
+    )
+    SELECT CASE
+        WHEN w.daily_gain IS NULL OR p_current_point IS NULL THEN NULL
+        ELSE p_current_point + ROUND(GREATEST(w.daily_gain, 0) * GREATEST(m.days_in_month - w.last_day, 0))::BIGINT
+    END
+    FROM weighted w, month m
+$$ LANGUAGE sql STABLE COST 1000;
+
+-- Past months are the ones before the current competition month
+CREATE OR REPLACE FUNCTION track_circle_membership() RETURNS TRIGGER AS $$
+DECLARE
+    tracked_since TIMESTAMP;
+    is_new BOOLEAN;
+BEGIN
+    -- Rows for past months describe old memberships
+    IF make_date(NEW.year, NEW.month, 1) < (competition_month_start() AT TIME ZONE competition_offset())::DATE THEN
+        RETURN NEW;
+    END IF;
+
+    -- The viewer moved here from another circle
+    WITH moved AS (
+        DELETE FROM circle_member_roster
+        WHERE viewer_id = NEW.viewer_id AND circle_id <> NEW.circle_id
+        RETURNING circle_id, viewer_id
+    )
+    INSERT INTO circle_member_events (circle_id, viewer_id, event_type)
+    SELECT circle_id, viewer_id, 'left' FROM moved;
+
+    SELECT MIN(joined_at) INTO tracked_since
+    FROM circle_member_roster
+    WHERE circle_id = NEW.circle_id;
+
+    INSERT INTO circle_member_roster (circle_id, viewer_id, joined_at, last_seen_at)
+    VALUES (NEW.circle_id, NEW.viewer_id, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+    ON CONFLICT (circle_id, viewer_id) DO UPDATE SET last_seen_at = EXCLUDED.last_seen_at
+    RETURNING (xmax = 0) INTO is_new;
+
+    -- The first scrape of a circle seeds its roster without emitting joins
+    IF is_new AND tracked_since IS NOT NULL AND tracked_since < CURRENT_TIMESTAMP - INTERVAL '10 minutes' THEN
+        INSERT INTO circle_member_events (circle_id, viewer_id, event_type)
+        VALUES (NEW.circle_id, NEW.viewer_id, 'joined');
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,6 @@
+-- Migration: User token versions
+-- Date: 2026-10-16
+-- Purpose: User tokens carry the account's token_version and are only accepted
+--          while it matches, so bumping it signs the account out everywhere.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS token_version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,15 @@
+-- Migration: Trainer claim verification
+-- Date: 2026-10-16
+-- Purpose: Issue trainer claims (see add_trainer_claims) once the trainer has
+--          proven ownership with a code in their in-game comment, the same
+--          check data deletion requests use.
+
+-- Open claim requests; the code must show up in the trainer's comment as
+-- scraped by a worker before expires_at, which sets verified_at
+CREATE TABLE IF NOT EXISTS trainer_claim_requests (
+    trainer_id TEXT PRIMARY KEY,
+    code TEXT NOT NULL,
+    requested_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL,
+    verified_at TIMESTAMP
+);
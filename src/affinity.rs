@@ -0,0 +1,69 @@
+use crate::errors::AppError;
+use crate::models::CharaId;
+
+/// Latest affinity formula, used when neither the request nor AFFINITY_VERSION picks one
+pub(crate) const LATEST_VERSION: u32 = 1;
+
+/// A versioned affinity formula
+///
+/// The game occasionally changes how affinity is computed. Each formula keeps its
+/// own version number so clients that pin `affinity_version=` keep getting the
+/// scores they were built against while new formulas ship alongside.
+pub trait AffinityFormula: Send + Sync {
+    fn version(&self) -> u32;
+
+    /// SQL expression scoring an inheritance row (aliased `i`) for the player character
+    fn sql_expression(&self, player_chara_id: Option<CharaId>) -> String;
+}
+
+/// v1: base (or per-character indexed) affinity plus race affinity
+///
+/// The expressions must stay byte-for-byte identical to the expression indexes
+/// on inheritance, otherwise affinity sorting falls back to a full scan.
+struct BasePlusRace;
+
+impl AffinityFormula for BasePlusRace {
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn sql_expression(&self, player_chara_id: Option<CharaId>) -> String {
+        match player_chara_id {
+            None => "(COALESCE(i.base_affinity, 0) + COALESCE(i.race_affinity, 0))".to_string(),
+            Some(p_val) => {
+                let array_index = p_val.base().0 - 1000;
+                format!(
+                    "(COALESCE(i.affinity_scores[{}], 0) + COALESCE(i.race_affinity, 0))",
+                    array_index
+                )
+            }
+        }
+    }
+}
+
+static FORMULAS: &[&dyn AffinityFormula] = &[&BasePlusRace];
+
+/// Default formula version (AFFINITY_VERSION, falling back to the latest)
+pub fn default_version() -> u32 {
+    crate::config::get().affinity_version
+}
+
+/// Look up the formula for a requested version (or the default)
+pub fn resolve(version: Option<u32>) -> Result<&'static dyn AffinityFormula, AppError> {
+    let version = version.unwrap_or_else(default_version);
+    FORMULAS
+        .iter()
+        .copied()
+        .find(|f| f.version() == version)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Unknown affinity_version {}. Supported: {}",
+                version,
+                FORMULAS
+                    .iter()
+                    .map(|f| f.version().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+}
@@ -0,0 +1,220 @@
+//! Product analytics: frontend events posted to /api/events, checked against a
+//! JSON schema per event type and written to the append-only analytics_events
+//! table, or forwarded to ClickHouse when CLICKHOUSE_URL is set.
+//!
+//! The schemas don't allow free-form fields, so events can't carry anything
+//! identifying beyond the frontend's random session ID.
+
+use chrono::NaiveDateTime;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::config::ClickHouseConfig;
+use crate::errors::AppError;
+use crate::models::AnalyticsEventType;
+
+/// Largest properties object accepted, serialized
+pub const MAX_PROPERTIES_BYTES: usize = 2048;
+
+/// An event that passed validation, ready to store
+pub struct ValidEvent {
+    pub event_type: AnalyticsEventType,
+    pub occurred_at: NaiveDateTime,
+    pub properties: Value,
+}
+
+/// Properties schema per event type
+fn schema(event_type: AnalyticsEventType) -> Value {
+    let search = json!({
+        "type": "string",
+        "enum": ["inheritance", "support_cards", "combined", "all", "circles"]
+    });
+    let count = json!({ "type": "integer", "minimum": 0 });
+    match event_type {
+        AnalyticsEventType::SearchPerformed => json!({
+            "type": "object",
+            "properties": {
+                "search": search,
+                "result_count": count,
+                "page": count,
+                "duration_ms": count,
+                "filter_count": count
+            },
+            "required": ["search"],
+            "additionalProperties": false
+        }),
+        AnalyticsEventType::ResultCopied => json!({
+            "type": "object",
+            "properties": {
+                "search": search,
+                "target": { "type": "string", "enum": ["trainer_id", "share_link"] },
+                "position": count
+            },
+            "required": ["target"],
+            "additionalProperties": false
+        }),
+        AnalyticsEventType::FilterUsed => json!({
+            "type": "object",
+            "properties": {
+                "search": search,
+                "filter": { "type": "string", "pattern": "^[a-z0-9_]{1,64}$" },
+                "value": {
+                    "oneOf": [
+                        { "type": "string", "maxLength": 64 },
+                        { "type": "number" },
+                        { "type": "boolean" },
+                        {
+                            "type": "array",
+                            "maxItems": 20,
+                            "items": { "type": ["string", "number"], "maxLength": 64 }
+                        }
+                    ]
+                }
+            },
+            "required": ["search", "filter"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+static VALIDATORS: OnceLock<HashMap<AnalyticsEventType, jsonschema::Validator>> = OnceLock::new();
+
+fn validators() -> &'static HashMap<AnalyticsEventType, jsonschema::Validator> {
+    VALIDATORS.get_or_init(|| {
+        [
+            AnalyticsEventType::SearchPerformed,
+            AnalyticsEventType::ResultCopied,
+            AnalyticsEventType::FilterUsed,
+        ]
+        .into_iter()
+        .map(|event_type| {
+            let validator = jsonschema::validator_for(&schema(event_type))
+                .expect("analytics event schemas are valid");
+            (event_type, validator)
+        })
+        .collect()
+    })
+}
+
+/// Check an event's properties against its type's schema
+pub fn validate_properties(event_type: AnalyticsEventType, properties: &Value) -> Result<(), String> {
+    if properties.to_string().len() > MAX_PROPERTIES_BYTES {
+        return Err(format!("properties exceed {} bytes", MAX_PROPERTIES_BYTES));
+    }
+    let errors: Vec<String> = validators()[&event_type]
+        .iter_errors(properties)
+        .take(3)
+        .map(|e| {
+            let path = e.instance_path.to_string();
+            format!("{}: {}", if path.is_empty() { "/" } else { &path }, e)
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+/// Store a batch of validated events (the whole batch or nothing)
+pub async fn store(
+    pool: &PgPool,
+    session_id: Option<&str>,
+    events: &[ValidEvent],
+) -> Result<(), AppError> {
+    match &crate::config::get().clickhouse {
+        Some(clickhouse) => forward_to_clickhouse(clickhouse, session_id, events).await,
+        None => insert_events(pool, session_id, events).await,
+    }
+}
+
+async fn insert_events(
+    pool: &PgPool,
+    session_id: Option<&str>,
+    events: &[ValidEvent],
+) -> Result<(), AppError> {
+    let mut event_types = Vec::with_capacity(events.len());
+    let mut occurred_at = Vec::with_capacity(events.len());
+    let mut properties = Vec::with_capacity(events.len());
+    for event in events {
+        event_types.push(event.event_type.as_str());
+        occurred_at.push(event.occurred_at);
+        properties.push(&event.properties);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO analytics_events (event_type, occurred_at, session_id, properties)
+        SELECT e.event_type, e.occurred_at, $4, e.properties
+        FROM unnest($1::text[], $2::timestamp[], $3::jsonb[]) AS e(event_type, occurred_at, properties)
+        "#,
+    )
+    .bind(event_types)
+    .bind(occurred_at)
+    .bind(properties)
+    .bind(session_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+static CLICKHOUSE_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Insert the events as JSONEachRow over ClickHouse's HTTP interface
+async fn forward_to_clickhouse(
+    clickhouse: &ClickHouseConfig,
+    session_id: Option<&str>,
+    events: &[ValidEvent],
+) -> Result<(), AppError> {
+    let client = CLICKHOUSE_CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default()
+    });
+
+    let received_at = chrono::Utc::now().naive_utc();
+    let format = |at: NaiveDateTime| at.format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+    let body: String = events
+        .iter()
+        .map(|event| {
+            json!({
+                "event_type": event.event_type.as_str(),
+                "occurred_at": format(event.occurred_at),
+                "received_at": format(received_at),
+                "session_id": session_id,
+                "properties": event.properties.to_string(),
+            })
+            .to_string()
+                + "\n"
+        })
+        .collect();
+
+    let mut request = client
+        .post(&clickhouse.url)
+        .query(&[
+            ("query", format!("INSERT INTO {} FORMAT JSONEachRow", clickhouse.table)),
+            ("async_insert", "1".to_string()),
+            ("wait_for_async_insert", "0".to_string()),
+        ])
+        .body(body);
+    if let Some(user) = &clickhouse.user {
+        request = request.header("X-ClickHouse-User", user);
+    }
+    if let Some(password) = &clickhouse.password {
+        request = request.header("X-ClickHouse-Key", password);
+    }
+
+    match request.send().await.and_then(|response| response.error_for_status()) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            tracing::warn!("⚠️ Forwarding analytics events to ClickHouse failed: {}", e);
+            Err(AppError::ServiceUnavailable(
+                "Analytics storage unavailable".to_string(),
+            ))
+        }
+    }
+}
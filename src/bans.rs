@@ -0,0 +1,254 @@
+//! Ban list: IP ranges and API keys refused before any other middleware runs.
+//!
+//! Active bans are kept in memory and reloaded from banned_clients every
+//! minute (and right after a change here), so a ban made on one instance
+//! reaches the others within that time. Clients that keep failing Turnstile
+//! or hitting rate limits are banned automatically for a while.
+
+use dashmap::DashMap;
+use ipnet::IpNet;
+use serde_json::json;
+use sqlx::PgPool;
+use std::net::IpAddr;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{error, warn};
+
+use crate::errors::AppError;
+use crate::models::BannedClient;
+
+struct ActiveBan {
+    ban_id: i32,
+    ip_range: Option<IpNet>,
+    /// Hash of the banned API key (see api_key::hash_key)
+    key_hash: Option<String>,
+    expires_at: Option<Instant>,
+}
+
+static BANS: OnceLock<RwLock<Vec<ActiveBan>>> = OnceLock::new();
+
+/// Auto-ban strikes per client ("ip:..." / "key:..."): (window start, count)
+static STRIKES: OnceLock<DashMap<String, (Instant, u32)>> = OnceLock::new();
+
+fn get_bans() -> &'static RwLock<Vec<ActiveBan>> {
+    BANS.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+fn get_strikes() -> &'static DashMap<String, (Instant, u32)> {
+    STRIKES.get_or_init(DashMap::new)
+}
+
+/// An IP address (as a single-host network) or CIDR range, with host bits cleared
+pub fn parse_ip_range(value: &str) -> Option<IpNet> {
+    let value = value.trim();
+    value
+        .parse::<IpNet>()
+        .or_else(|_| value.parse::<IpAddr>().map(IpNet::from))
+        .ok()
+        .map(|net| net.trunc())
+}
+
+/// Which ban, if any, covers a client
+pub fn find_ban(ip: Option<IpAddr>, key_hash: Option<&str>) -> Option<i32> {
+    let bans = get_bans().read().ok()?;
+    let now = Instant::now();
+    bans.iter()
+        .filter(|ban| ban.expires_at.is_none_or(|expires_at| expires_at > now))
+        .find(|ban| {
+            let ip_match = ban
+                .ip_range
+                .zip(ip)
+                .is_some_and(|(range, ip)| range.contains(&ip));
+            let key_match = ban.key_hash.as_deref().is_some_and(|hash| Some(hash) == key_hash);
+            ip_match || key_match
+        })
+        .map(|ban| ban.ban_id)
+}
+
+/// Whether any bans are active (lets the middleware skip hashing keys otherwise)
+pub fn is_empty() -> bool {
+    get_bans().read().map(|bans| bans.is_empty()).unwrap_or(true)
+}
+
+/// Replace the in-memory list with the active bans in the database
+pub async fn reload(pool: &PgPool) -> Result<usize, AppError> {
+    let rows = sqlx::query_as::<_, (i32, Option<String>, Option<String>, Option<f64>)>(
+        r#"
+        SELECT b.ban_id, b.ip_range::text, k.key_hash,
+               EXTRACT(EPOCH FROM b.expires_at - CURRENT_TIMESTAMP)::float8
+        FROM banned_clients b
+        LEFT JOIN api_keys k ON k.key_id = b.api_key_id
+        WHERE b.expires_at IS NULL OR b.expires_at > CURRENT_TIMESTAMP
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let now = Instant::now();
+    let bans: Vec<ActiveBan> = rows
+        .into_iter()
+        .map(|(ban_id, ip_range, key_hash, expires_in)| ActiveBan {
+            ban_id,
+            ip_range: ip_range.as_deref().and_then(parse_ip_range),
+            key_hash,
+            expires_at: expires_in.map(|secs| now + Duration::from_secs_f64(secs.max(0.0))),
+        })
+        .collect();
+
+    let count = bans.len();
+    if let Ok(mut active) = get_bans().write() {
+        *active = bans;
+    }
+    Ok(count)
+}
+
+/// Add a ban and apply it right away
+pub async fn ban(
+    pool: &PgPool,
+    ip_range: Option<IpNet>,
+    api_key_id: Option<i32>,
+    reason: &str,
+    source: &str,
+    duration: Option<Duration>,
+) -> Result<BannedClient, AppError> {
+    let ban = sqlx::query_as::<_, BannedClient>(
+        r#"
+        INSERT INTO banned_clients (ip_range, api_key_id, reason, source, expires_at)
+        VALUES ($1::cidr, $2, $3, $4, CURRENT_TIMESTAMP + $5 * INTERVAL '1 second')
+        RETURNING ban_id, ip_range::text AS ip_range, api_key_id, reason, source,
+                  created_at, expires_at
+        "#,
+    )
+    .bind(ip_range.map(|net| net.to_string()))
+    .bind(api_key_id)
+    .bind(reason)
+    .bind(source)
+    .bind(duration.map(|d| d.as_secs() as i64))
+    .fetch_one(pool)
+    .await?;
+
+    reload(pool).await?;
+    Ok(ban)
+}
+
+/// Remove a ban; None if it doesn't exist
+pub async fn lift(pool: &PgPool, ban_id: i32) -> Result<Option<BannedClient>, AppError> {
+    let ban = sqlx::query_as::<_, BannedClient>(
+        r#"
+        DELETE FROM banned_clients
+        WHERE ban_id = $1
+        RETURNING ban_id, ip_range::text AS ip_range, api_key_id, reason, source,
+                  created_at, expires_at
+        "#,
+    )
+    .bind(ban_id)
+    .fetch_optional(pool)
+    .await?;
+
+    reload(pool).await?;
+    Ok(ban)
+}
+
+/// Count a strike against a client; true when it just reached `threshold`
+/// within the auto-ban window (0 disables)
+fn strike(client: String, threshold: u32) -> bool {
+    if threshold == 0 {
+        return false;
+    }
+
+    let window = crate::config::get().auto_ban.window;
+    let now = Instant::now();
+    let mut entry = get_strikes().entry(client).or_insert((now, 0));
+    if now.duration_since(entry.0) >= window {
+        *entry = (now, 0);
+    }
+    entry.1 = entry.1.saturating_add(1);
+    entry.1 == threshold
+}
+
+/// A failed Turnstile verification; bans the IP after too many
+///
+/// `client_ip` must come from `turnstile::client_ip`, so a client can't get
+/// someone else banned by forging forwarding headers.
+pub fn record_turnstile_failure(pool: &PgPool, client_ip: IpAddr) {
+    let threshold = crate::config::get().auto_ban.turnstile_failures;
+    if strike(format!("ip:{}", client_ip), threshold) {
+        auto_ban(pool, client_ip, None, format!("{} failed Turnstile verifications", threshold));
+    }
+}
+
+/// A request answered with 429; bans the API key it used (or else the IP) after too many
+pub fn record_rate_limit_violation(pool: &PgPool, client_ip: IpAddr, key_hash: Option<String>) {
+    let threshold = crate::config::get().auto_ban.rate_limit_violations;
+    let client = match &key_hash {
+        Some(hash) => format!("key:{}", hash),
+        None => format!("ip:{}", client_ip),
+    };
+    if strike(client, threshold) {
+        auto_ban(pool, client_ip, key_hash, format!("{} rate-limited requests", threshold));
+    }
+}
+
+fn auto_ban(pool: &PgPool, client_ip: IpAddr, key_hash: Option<String>, reason: String) {
+    let pool = pool.clone();
+    let client_range = IpNet::from(client_ip);
+
+    // Written in the background so the request that tripped the ban isn't held up
+    tokio::spawn(async move {
+        let duration = crate::config::get().auto_ban.duration;
+        let result = async {
+            let (ip_range, api_key_id) = match &key_hash {
+                Some(hash) => {
+                    let key_id = sqlx::query_scalar::<_, i32>(
+                        "SELECT key_id FROM api_keys WHERE key_hash = $1",
+                    )
+                    .bind(hash)
+                    .fetch_optional(&pool)
+                    .await?;
+                    match key_id {
+                        Some(key_id) => (None, Some(key_id)),
+                        None => (Some(client_range), None),
+                    }
+                }
+                None => (Some(client_range), None),
+            };
+
+            let ban = ban(&pool, ip_range, api_key_id, &reason, "auto", Some(duration)).await?;
+            sqlx::query("INSERT INTO admin_audit_log (action, details) VALUES ($1, $2)")
+                .bind("ban.auto")
+                .bind(json!({
+                    "ban_id": ban.ban_id,
+                    "ip_range": &ban.ip_range,
+                    "api_key_id": ban.api_key_id,
+                    "reason": &ban.reason,
+                    "duration_secs": duration.as_secs()
+                }))
+                .execute(&pool)
+                .await?;
+            Ok::<_, AppError>(ban)
+        }
+        .await;
+
+        match result {
+            Ok(ban) => warn!(
+                "🚫 Auto-banned {} for {}s: {}",
+                ban.ip_range
+                    .clone()
+                    .or_else(|| ban.api_key_id.map(|id| format!("API key {}", id)))
+                    .unwrap_or_default(),
+                duration.as_secs(),
+                ban.reason
+            ),
+            Err(e) => error!("❌ Failed to auto-ban {}: {}", client_ip, e),
+        }
+    });
+}
+
+/// Drop strike windows that have run out, returning how many were removed
+pub fn cleanup_strikes() -> usize {
+    let window = crate::config::get().auto_ban.window;
+    let strikes = get_strikes();
+    let before_count = strikes.len();
+    strikes.retain(|_, (started, _)| started.elapsed() < window);
+    before_count.saturating_sub(strikes.len())
+}
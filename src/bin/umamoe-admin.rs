@@ -0,0 +1,344 @@
+//! Maintenance commands against the server's database
+//!
+//! Reads the same environment (and .env) as the server; only DATABASE_URL is
+//! required. Run `umamoe-admin --help` for the commands.
+
+use clap::{Parser, Subcommand};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+
+use honsemoe_backend_v2::config::{self, Config};
+use honsemoe_backend_v2::{competition, database, views};
+
+#[derive(Parser)]
+#[command(name = "umamoe-admin", about = "Maintenance commands for the uma.moe database")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Inspect or repair the migration history
+    Migrate {
+        #[command(subcommand)]
+        command: MigrateCommand,
+    },
+    /// Check the database connection, settings and schema
+    Db {
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+    /// Check how competition month boundaries resolve in the database
+    Tz {
+        #[command(subcommand)]
+        command: TzCommand,
+    },
+    /// Refresh materialized views now instead of waiting for the server
+    RefreshViews {
+        /// Views to refresh (default: all)
+        views: Vec<String>,
+    },
+    /// Rebuild the indexes of tables, or single indexes, without blocking writes
+    Reindex {
+        /// Tables whose indexes are rebuilt
+        tables: Vec<String>,
+        /// Single indexes to rebuild
+        #[arg(long = "index")]
+        indexes: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// Forget failed migrations so the server runs them again on startup
+    Repair {
+        /// Forget this migration even if it is recorded as successful
+        #[arg(long)]
+        version: Option<i64>,
+        /// Drop an index the failed migration left half-built (repeatable)
+        #[arg(long = "drop-index")]
+        drop_indexes: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbCommand {
+    /// Print server settings and compare applied migrations with the embedded ones
+    Check,
+}
+
+#[derive(Subcommand)]
+enum TzCommand {
+    /// Print the current competition month and where its 12:00 rollover falls in server time
+    Check,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+    let config = config::init(Config::from_env()?);
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::new("warn"))
+        .init();
+
+    let pool = database::create_pool(&config.database_url, &config.db_session).await?;
+    println!("Connected to {}", database::display_name(&config.database_url));
+
+    let result = match cli.command {
+        Command::Migrate {
+            command: MigrateCommand::Repair { version, drop_indexes },
+        } => migrate_repair(&pool, version, &drop_indexes).await,
+        Command::Db { command: DbCommand::Check } => db_check(&pool).await,
+        Command::Tz { command: TzCommand::Check } => tz_check(&pool).await,
+        Command::RefreshViews { views } => refresh_views(&pool, &views).await,
+        Command::Reindex { tables, indexes } => reindex(&pool, &tables, &indexes).await,
+    };
+
+    pool.close().await;
+    result
+}
+
+/// Versions of the embedded (up) migrations
+fn embedded_versions() -> Vec<i64> {
+    database::MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .collect()
+}
+
+async fn migrate_repair(
+    pool: &PgPool,
+    version: Option<i64>,
+    drop_indexes: &[String],
+) -> anyhow::Result<()> {
+    let forgotten: Vec<(i64, String)> = sqlx::query_as(
+        "DELETE FROM _sqlx_migrations
+         WHERE NOT success OR version = $1
+         RETURNING version, description",
+    )
+    .bind(version)
+    .fetch_all(pool)
+    .await?;
+
+    if forgotten.is_empty() {
+        println!("No failed migrations recorded");
+    }
+    for (version, description) in &forgotten {
+        println!("Forgot migration {} ({})", version, description);
+    }
+
+    for index in drop_indexes {
+        let Some(index) = resolve_relation(pool, index).await? else {
+            println!("Index {} does not exist", index);
+            continue;
+        };
+        let mut conn = database::acquire_long_running(pool).await?;
+        sqlx::query(&format!("DROP INDEX CONCURRENTLY IF EXISTS {}", index))
+            .execute(&mut conn)
+            .await?;
+        println!("Dropped index {}", index);
+    }
+
+    if !forgotten.is_empty() {
+        println!("The server applies these migrations again on its next start");
+    }
+    Ok(())
+}
+
+async fn db_check(pool: &PgPool) -> anyhow::Result<()> {
+    let row = sqlx::query(
+        "SELECT version(), NOW()::text AS now, current_setting('TimeZone') AS timezone,
+                current_setting('statement_timeout') AS statement_timeout",
+    )
+    .fetch_one(pool)
+    .await?;
+    println!("Server: {}", row.get::<String, _>("version"));
+    println!("DB now: {} ({})", row.get::<String, _>("now"), row.get::<String, _>("timezone"));
+    println!("Statement timeout: {}", row.get::<String, _>("statement_timeout"));
+
+    // Timestamps are written as server wall-clock time, so these should be without time zone
+    let columns: Vec<(String, String)> = sqlx::query_as(
+        "SELECT table_name::text, data_type::text
+         FROM information_schema.columns
+         WHERE table_schema = 'public' AND column_name = 'last_updated'
+           AND table_name IN ('circles', 'trainer', 'circle_member_fans_monthly')
+         ORDER BY table_name",
+    )
+    .fetch_all(pool)
+    .await?;
+    for (table, data_type) in columns {
+        println!("{}.last_updated: {}", table, data_type);
+    }
+
+    let applied: HashMap<i64, bool> =
+        match sqlx::query_as::<_, (i64, bool)>("SELECT version, success FROM _sqlx_migrations")
+            .fetch_all(pool)
+            .await
+        {
+            Ok(rows) => rows.into_iter().collect(),
+            Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("42P01") => {
+                println!("Migrations: _sqlx_migrations table does not exist");
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+    let expected = embedded_versions();
+    let pending: Vec<i64> = expected
+        .iter()
+        .filter(|version| !applied.contains_key(version))
+        .copied()
+        .collect();
+    let mut failed: Vec<i64> = applied
+        .iter()
+        .filter(|(_, success)| !**success)
+        .map(|(version, _)| *version)
+        .collect();
+    failed.sort_unstable();
+    let mut unknown: Vec<i64> = applied
+        .keys()
+        .filter(|version| !expected.contains(version))
+        .copied()
+        .collect();
+    unknown.sort_unstable();
+
+    println!(
+        "Migrations: {} embedded, {} applied, pending {:?}, failed {:?}",
+        expected.len(),
+        applied.values().filter(|success| **success).count(),
+        pending,
+        failed
+    );
+    if !failed.is_empty() {
+        println!("Run `umamoe-admin migrate repair` to retry the failed migrations");
+    }
+    if !unknown.is_empty() {
+        println!("Applied but not embedded in this build: {:?}", unknown);
+    }
+    Ok(())
+}
+
+async fn tz_check(pool: &PgPool) -> anyhow::Result<()> {
+    let row = sqlx::query(&format!(
+        "SELECT {now} AS now_competition,
+                ({now})::date AS today_competition,
+                ({start})::timestamp AS month_start,
+                ({next})::timestamp AS next_month_start,
+                competition_offset()::text AS db_offset,
+                current_setting('TimeZone') AS timezone",
+        now = competition::now_sql(),
+        start = competition::month_start_sql(0),
+        next = competition::month_start_sql(1),
+    ))
+    .fetch_one(pool)
+    .await?;
+
+    let db_now: chrono::NaiveDateTime = row.get("now_competition");
+    let app_now = competition::now().naive_local();
+    println!("Competition timezone: UTC{}", competition::offset());
+    println!("Now (competition time): database {}, this host {}", db_now, app_now);
+    println!(
+        "Today (competition time): {}",
+        row.get::<chrono::NaiveDate, _>("today_competition")
+    );
+    println!(
+        "Ranking month: {:?}, rolling over at {:02}:00 competition time",
+        competition::current_month(),
+        competition::MONTH_ROLLOVER_HOUR
+    );
+    println!(
+        "Current ranking month starts at {}, the next at {} (database TimeZone {})",
+        row.get::<chrono::NaiveDateTime, _>("month_start"),
+        row.get::<chrono::NaiveDateTime, _>("next_month_start"),
+        row.get::<String, _>("timezone")
+    );
+    println!("Competition offset seen by the database: {}", row.get::<String, _>("db_offset"));
+
+    let skew = (db_now - app_now).num_seconds().abs();
+    if skew > 5 {
+        println!("⚠️ Database and host clocks differ by {}s", skew);
+    }
+    Ok(())
+}
+
+async fn refresh_views(pool: &PgPool, names: &[String]) -> anyhow::Result<()> {
+    let selected: Vec<&views::MaterializedView> = if names.is_empty() {
+        views::MATERIALIZED_VIEWS.iter().collect()
+    } else {
+        names
+            .iter()
+            .map(|name| {
+                views::find(name).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unknown view {} (known: {})",
+                        name,
+                        views::MATERIALIZED_VIEWS
+                            .iter()
+                            .map(|view| view.name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })
+            })
+            .collect::<anyhow::Result<_>>()?
+    };
+
+    let mut failures = 0;
+    for view in selected {
+        let started = std::time::Instant::now();
+        match view.refresh(pool).await {
+            Ok(()) => println!("Refreshed {} in {:.1}s", view.name, started.elapsed().as_secs_f64()),
+            Err(e) => {
+                failures += 1;
+                println!("Failed to refresh {}: {}", view.name, e);
+            }
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} view(s) failed to refresh", failures);
+    }
+    Ok(())
+}
+
+async fn reindex(pool: &PgPool, tables: &[String], indexes: &[String]) -> anyhow::Result<()> {
+    if tables.is_empty() && indexes.is_empty() {
+        anyhow::bail!("Name at least one table or --index");
+    }
+
+    let targets = tables
+        .iter()
+        .map(|name| ("TABLE", name))
+        .chain(indexes.iter().map(|name| ("INDEX", name)));
+    for (kind, name) in targets {
+        let relation = resolve_relation(pool, name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("{} does not exist", name))?;
+
+        // CONCURRENTLY can't run inside a transaction, so this uses a plain connection
+        let started = std::time::Instant::now();
+        let mut conn = database::acquire_long_running(pool).await?;
+        sqlx::query(&format!("REINDEX {} CONCURRENTLY {}", kind, relation))
+            .execute(&mut conn)
+            .await?;
+        println!(
+            "Reindexed {} {} in {:.1}s",
+            kind.to_lowercase(),
+            relation,
+            started.elapsed().as_secs_f64()
+        );
+    }
+    Ok(())
+}
+
+/// The relation's name as Postgres quotes it, if it exists
+async fn resolve_relation(pool: &PgPool, name: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT to_regclass($1)::text")
+        .bind(name)
+        .fetch_one(pool)
+        .await
+}
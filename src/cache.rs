@@ -1,26 +1,57 @@
-use dashmap::DashMap;
-use serde::Serialize;
-use std::sync::OnceLock;
+use dashmap::{DashMap, DashSet};
+use serde::{de::DeserializeOwned, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, OwnedMutexGuard};
 
 /// Maximum number of cache entries before eviction kicks in
 const MAX_CACHE_ENTRIES: usize = 1000;
 
+/// How long "nothing found" outcomes are cached: long enough to absorb bots
+/// probing IDs, short enough that data fetched meanwhile shows up soon
+pub const NEGATIVE_TTL: Duration = Duration::from_secs(60);
+
 /// Global cache storage
 static CACHE: OnceLock<DashMap<String, CacheEntry>> = OnceLock::new();
 
+/// Keys with a background refresh in flight (see get_or_revalidate)
+static REVALIDATING: OnceLock<DashSet<String>> = OnceLock::new();
+
+/// Keys being loaded after a miss, so concurrent misses wait for one load (see lock_load)
+static LOADING: OnceLock<DashMap<String, Arc<Mutex<()>>>> = OnceLock::new();
+
+/// Lookup counters since startup
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+static SETS: AtomicU64 = AtomicU64::new(0);
+
+/// Stale values served while refreshing, and the background refreshes started
+static STALE_HITS: AtomicU64 = AtomicU64::new(0);
+static REVALIDATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Misses that waited for another caller's load of the same key
+static COALESCED: AtomicU64 = AtomicU64::new(0);
+
+/// Cleanup task counters since startup
+static CLEANUP_RUNS: AtomicU64 = AtomicU64::new(0);
+static EXPIRED_REMOVED: AtomicU64 = AtomicU64::new(0);
+
 /// Cache entry with expiration and access tracking
 #[derive(Clone)]
 struct CacheEntry {
     data: String,
+    /// After this the entry is refreshed on its next get_or_revalidate (same as
+    /// expires_at for entries stored with set)
+    stale_at: Instant,
     expires_at: Instant,
     last_accessed: Instant,
-    #[allow(dead_code)]
     size_bytes: usize,
 }
 
 fn get_cache() -> &'static DashMap<String, CacheEntry> {
-    CACHE.get_or_init(|| DashMap::new())
+    CACHE.get_or_init(DashMap::new)
 }
 
 /// Get cached data if it exists and hasn't expired
@@ -35,6 +66,7 @@ pub fn get<T: for<'de> serde::Deserialize<'de>>(key: &str) -> Option<T> {
 
             // Try to deserialize
             if let Ok(data) = serde_json::from_str(&entry.data) {
+                HITS.fetch_add(1, Ordering::Relaxed);
                 return Some(data);
             }
         } else {
@@ -44,11 +76,22 @@ pub fn get<T: for<'de> serde::Deserialize<'de>>(key: &str) -> Option<T> {
         }
     }
 
+    MISSES.fetch_add(1, Ordering::Relaxed);
     None
 }
 
 /// Set cached data with TTL (time to live)
 pub fn set<T: Serialize>(key: &str, data: &T, ttl: Duration) -> Result<(), serde_json::Error> {
+    set_with_soft_ttl(key, data, ttl, ttl)
+}
+
+/// Set cached data that turns stale after `soft_ttl` and expires after `hard_ttl`
+fn set_with_soft_ttl<T: Serialize>(
+    key: &str,
+    data: &T,
+    soft_ttl: Duration,
+    hard_ttl: Duration,
+) -> Result<(), serde_json::Error> {
     let cache = get_cache();
 
     // Evict old entries if cache is too large
@@ -62,15 +105,135 @@ pub fn set<T: Serialize>(key: &str, data: &T, ttl: Duration) -> Result<(), serde
 
     let entry = CacheEntry {
         data: json_data,
-        expires_at: now + ttl,
+        stale_at: now + soft_ttl.min(hard_ttl),
+        expires_at: now + hard_ttl,
         last_accessed: now,
         size_bytes,
     };
 
     cache.insert(key.to_string(), entry);
+    SETS.fetch_add(1, Ordering::Relaxed);
     Ok(())
 }
 
+/// Get a cached value, loading it with `load` when missing and refreshing it in
+/// the background once stale (stale-while-revalidate)
+///
+/// Until `soft_ttl` the cached value is returned as-is. Between `soft_ttl` and
+/// `hard_ttl` it is still returned right away, while `load` runs in a spawned
+/// task to replace it (one refresh per key at a time). Only a missing or
+/// expired entry makes the caller wait for `load`, which concurrent callers
+/// missing the same key share.
+pub async fn get_or_revalidate<T, E, F, Fut>(
+    key: &str,
+    soft_ttl: Duration,
+    hard_ttl: Duration,
+    load: F,
+) -> Result<T, E>
+where
+    T: Serialize + DeserializeOwned + Send + 'static,
+    E: std::fmt::Display + Send + 'static,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+{
+    let now = Instant::now();
+    let cached = get_cache().get_mut(key).and_then(|mut entry| {
+        if now >= entry.expires_at {
+            return None;
+        }
+        entry.last_accessed = now;
+        let value = serde_json::from_str::<T>(&entry.data).ok()?;
+        Some((value, now >= entry.stale_at))
+    });
+
+    match cached {
+        Some((value, false)) => {
+            HITS.fetch_add(1, Ordering::Relaxed);
+            Ok(value)
+        }
+        Some((value, true)) => {
+            STALE_HITS.fetch_add(1, Ordering::Relaxed);
+            let revalidating = REVALIDATING.get_or_init(DashSet::new);
+            if revalidating.insert(key.to_string()) {
+                REVALIDATIONS.fetch_add(1, Ordering::Relaxed);
+                let key = key.to_string();
+                let refresh = load();
+                tokio::spawn(async move {
+                    match refresh.await {
+                        Ok(fresh) => {
+                            let _ = set_with_soft_ttl(&key, &fresh, soft_ttl, hard_ttl);
+                        }
+                        Err(e) => tracing::warn!("⚠️ Background refresh of cache key {} failed: {}", key, e),
+                    }
+                    revalidating.remove(&key);
+                });
+            }
+            Ok(value)
+        }
+        None => {
+            MISSES.fetch_add(1, Ordering::Relaxed);
+            let _loading = lock_load(key).await;
+            // Another caller may have loaded it while we waited
+            if let Some(value) = get_fresh::<T>(key) {
+                return Ok(value);
+            }
+            let value = load().await?;
+            let _ = set_with_soft_ttl(key, &value, soft_ttl, hard_ttl);
+            Ok(value)
+        }
+    }
+}
+
+/// Held while loading a key after a cache miss; see lock_load
+pub struct LoadGuard {
+    key: String,
+    _lock: OwnedMutexGuard<()>,
+}
+
+impl Drop for LoadGuard {
+    fn drop(&mut self) {
+        // Forget the lock once nobody else holds or waits for it (the map and this guard)
+        if let Some(loading) = LOADING.get() {
+            loading.remove_if(&self.key, |_, lock| Arc::strong_count(lock) <= 2);
+        }
+    }
+}
+
+/// Wait until no other caller is loading `key`, then hold it until the guard drops
+///
+/// Callers check the cache again after this returns: concurrent identical
+/// requests then run their query once, the rest picking up the stored result.
+pub async fn lock_load(key: &str) -> LoadGuard {
+    let lock = LOADING
+        .get_or_init(DashMap::new)
+        .entry(key.to_string())
+        .or_default()
+        .clone();
+
+    let guard = match lock.clone().try_lock_owned() {
+        Ok(guard) => guard,
+        Err(_) => {
+            COALESCED.fetch_add(1, Ordering::Relaxed);
+            lock.lock_owned().await
+        }
+    };
+    LoadGuard {
+        key: key.to_string(),
+        _lock: guard,
+    }
+}
+
+/// A cached value that is neither expired nor stale, without touching the counters
+fn get_fresh<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let now = Instant::now();
+    let mut entry = get_cache().get_mut(key)?;
+    if now >= entry.stale_at {
+        return None;
+    }
+    entry.last_accessed = now;
+    serde_json::from_str(&entry.data).ok()
+}
+
 /// Evict least recently used entries to free up space
 /// Removes 20% of entries (sorted by last_accessed time)
 fn evict_lru_entries() {
@@ -106,17 +269,20 @@ fn evict_lru_entries() {
 
 /// Clear all expired cache entries
 #[allow(dead_code)]
-pub fn cleanup_expired() {
+pub fn cleanup_expired() -> usize {
     let cache = get_cache();
     let now = Instant::now();
 
     let before_count = cache.len();
     cache.retain(|_, entry| now < entry.expires_at);
-    let removed = before_count - cache.len();
+    let removed = before_count.saturating_sub(cache.len());
 
+    CLEANUP_RUNS.fetch_add(1, Ordering::Relaxed);
+    EXPIRED_REMOVED.fetch_add(removed as u64, Ordering::Relaxed);
     if removed > 0 {
         tracing::info!("🧹 Cleaned up {} expired cache entries", removed);
     }
+    removed
 }
 
 /// Clear specific cache key
@@ -126,15 +292,83 @@ pub fn invalidate(key: &str) {
     cache.remove(key);
 }
 
+/// Groups of related cache keys, each under its own key prefix, so everything
+/// derived from the same data can be dropped together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Namespace {
+    /// Search result pages
+    Search,
+    /// Filtered search result counts
+    Count,
+    /// Share pages and their JSON data
+    Share,
+    Stats,
+    Feeds,
+    /// Whole responses stored by the response cache middleware
+    Http,
+    /// Last good responses, served in degraded mode while the database is unreachable
+    Fallback,
+}
+
+impl Namespace {
+    pub const ALL: &'static [Namespace] = &[
+        Namespace::Search,
+        Namespace::Count,
+        Namespace::Share,
+        Namespace::Stats,
+        Namespace::Feeds,
+        Namespace::Http,
+        Namespace::Fallback,
+    ];
+
+    /// Namespaces computed from stored inheritance/support card data
+    pub const TRAINER_DATA: &'static [Namespace] = &[Namespace::Search, Namespace::Count];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Namespace::Search => "search",
+            Namespace::Count => "count",
+            Namespace::Share => "share",
+            Namespace::Stats => "stats",
+            Namespace::Feeds => "feeds",
+            Namespace::Http => "http",
+            Namespace::Fallback => "fallback",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|ns| ns.as_str() == value)
+    }
+
+    /// Full cache key for `key` within this namespace
+    pub fn key(self, key: &str) -> String {
+        format!("{}:{}", self.as_str(), key)
+    }
+}
+
+/// Clear every key in the given namespaces, returning how many were removed
+pub fn invalidate_namespaces(namespaces: &[Namespace]) -> usize {
+    namespaces
+        .iter()
+        .map(|ns| invalidate_prefix(&ns.key("")))
+        .sum()
+}
+
+/// Clear every cache key starting with `prefix`, returning how many were removed
+pub fn invalidate_prefix(prefix: &str) -> usize {
+    let cache = get_cache();
+    let before_count = cache.len();
+    cache.retain(|key, _| !key.starts_with(prefix));
+    before_count.saturating_sub(cache.len())
+}
+
 /// Clear all cache
-#[allow(dead_code)]
 pub fn clear_all() {
     let cache = get_cache();
     cache.clear();
 }
 
 /// Get cache statistics
-#[allow(dead_code)]
 pub fn stats() -> CacheStats {
     let cache = get_cache();
     let now = Instant::now();
@@ -153,14 +387,49 @@ pub fn stats() -> CacheStats {
         entry_count: cache.len(),
         total_size_bytes: total_size,
         expired_count,
+        hits: HITS.load(Ordering::Relaxed),
+        misses: MISSES.load(Ordering::Relaxed),
+        sets: SETS.load(Ordering::Relaxed),
+        stale_hits: STALE_HITS.load(Ordering::Relaxed),
+        revalidations: REVALIDATIONS.load(Ordering::Relaxed),
+        coalesced: COALESCED.load(Ordering::Relaxed),
+        cleanup_runs: CLEANUP_RUNS.load(Ordering::Relaxed),
+        expired_removed: EXPIRED_REMOVED.load(Ordering::Relaxed),
     }
 }
 
+/// The largest entries as (key, size in bytes, time until expiry)
+pub fn largest_entries(limit: usize) -> Vec<(String, usize, Duration)> {
+    let cache = get_cache();
+    let now = Instant::now();
+
+    let mut entries: Vec<(String, usize, Duration)> = cache
+        .iter()
+        .map(|entry| {
+            (
+                entry.key().clone(),
+                entry.value().size_bytes,
+                entry.value().expires_at.saturating_duration_since(now),
+            )
+        })
+        .collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries.truncate(limit);
+    entries
+}
+
 /// Cache statistics
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct CacheStats {
     pub entry_count: usize,
     pub total_size_bytes: usize,
     pub expired_count: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub sets: u64,
+    pub stale_hits: u64,
+    pub revalidations: u64,
+    pub coalesced: u64,
+    pub cleanup_runs: u64,
+    pub expired_removed: u64,
 }
@@ -0,0 +1,172 @@
+use dashmap::DashMap;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use crate::models::{CharaId, CharacterNameReport, MissingCharacterName};
+
+/// Names bundled with the binary, used when the character_names table has no entry
+const BUNDLED_NAMES_JSON: &str = include_str!("../data/character_names.json");
+
+/// Names from the character_names table, keyed by base character ID (1001, ...)
+static DB_NAMES: OnceLock<RwLock<HashMap<i32, String>>> = OnceLock::new();
+static BUNDLED_NAMES: OnceLock<HashMap<i32, String>> = OnceLock::new();
+
+/// Character IDs that fell through to the "Character {id}" placeholder
+static MISSING: OnceLock<DashMap<i32, MissingCharacterName>> = OnceLock::new();
+
+fn get_db_names() -> &'static RwLock<HashMap<i32, String>> {
+    DB_NAMES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn get_bundled_names() -> &'static HashMap<i32, String> {
+    BUNDLED_NAMES.get_or_init(|| {
+        let names: HashMap<String, String> = match serde_json::from_str(BUNDLED_NAMES_JSON) {
+            Ok(names) => names,
+            Err(e) => {
+                tracing::error!("❌ Bundled character names are invalid: {}", e);
+                return HashMap::new();
+            }
+        };
+        names
+            .into_iter()
+            .filter_map(|(id, name)| id.parse().ok().map(|id| (id, name)))
+            .collect()
+    })
+}
+
+fn get_missing() -> &'static DashMap<i32, MissingCharacterName> {
+    MISSING.get_or_init(DashMap::new)
+}
+
+fn lookup(chara_id: i32) -> Option<String> {
+    if let Some(name) = get_db_names()
+        .read()
+        .ok()
+        .and_then(|names| names.get(&chara_id).cloned())
+    {
+        return Some(name);
+    }
+    get_bundled_names().get(&chara_id).cloned()
+}
+
+fn record_missing(chara_id: i32, lookups: u64) {
+    let now = chrono::Utc::now().naive_utc();
+    get_missing()
+        .entry(chara_id)
+        .and_modify(|missing| {
+            missing.lookups += lookups;
+            missing.last_seen = now;
+        })
+        .or_insert(MissingCharacterName {
+            chara_id: CharaId(chara_id),
+            lookups,
+            first_seen: now,
+            last_seen: now,
+        });
+}
+
+/// Display name of a character or card variant (100101 and 1001 both resolve to 1001).
+/// Falls back from the character_names table to the bundled list, then to a
+/// "Character {id}" placeholder; placeholder IDs show up in the admin report.
+pub fn character_name(character_id: CharaId) -> String {
+    let chara_id = character_id.base().0;
+    if let Some(name) = lookup(chara_id) {
+        return name;
+    }
+
+    if !get_missing().contains_key(&chara_id) {
+        tracing::warn!("⚠️ No name for character {}, using placeholder", chara_id);
+    }
+    record_missing(chara_id, 1);
+    format!("Character {}", chara_id)
+}
+
+/// Load (or reload) names from the character_names table.
+/// IDs that now resolve are dropped from the missing report.
+/// Returns the number of names loaded.
+pub async fn load_character_names(pool: &PgPool) -> usize {
+    let rows = match sqlx::query_as::<_, (i32, String)>(
+        "SELECT chara_id, name FROM character_names",
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!("⚠️ Failed to load character names, using bundled list: {}", e);
+            return 0;
+        }
+    };
+
+    let count = rows.len();
+    if let Ok(mut names) = get_db_names().write() {
+        *names = rows.into_iter().collect();
+    }
+    get_missing().retain(|chara_id, _| lookup(*chara_id).is_none());
+
+    tracing::info!(
+        "🐴 Loaded {} character names ({} bundled)",
+        count,
+        get_bundled_names().len()
+    );
+    count
+}
+
+/// Warn about characters that appear in inheritance data but have no name,
+/// so new characters are noticed right after a game update
+pub async fn report_unknown_characters(pool: PgPool) {
+    // Loose index scan over idx_inheritance_main_chara instead of a full DISTINCT
+    let chara_ids = match sqlx::query_scalar::<_, i32>(
+        r#"
+        WITH RECURSIVE ids AS (
+            SELECT MIN(main_chara_id) AS id FROM inheritance
+            UNION ALL
+            SELECT (SELECT MIN(main_chara_id) FROM inheritance WHERE main_chara_id > ids.id)
+            FROM ids
+            WHERE ids.id IS NOT NULL
+        )
+        SELECT id FROM ids WHERE id IS NOT NULL AND id > 0
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    {
+        Ok(ids) => ids,
+        Err(e) => {
+            tracing::warn!("⚠️ Failed to check character names: {}", e);
+            return;
+        }
+    };
+
+    let unknown: Vec<i32> = chara_ids
+        .into_iter()
+        .filter(|chara_id| lookup(*chara_id).is_none())
+        .collect();
+
+    if unknown.is_empty() {
+        return;
+    }
+
+    for chara_id in &unknown {
+        record_missing(*chara_id, 0);
+    }
+    tracing::warn!(
+        "⚠️ {} character(s) in inheritance data have no name: {:?} (add them to character_names)",
+        unknown.len(),
+        unknown
+    );
+}
+
+/// Current name sources and the IDs that fell through to the placeholder
+pub fn report() -> CharacterNameReport {
+    let mut missing: Vec<MissingCharacterName> =
+        get_missing().iter().map(|entry| entry.value().clone()).collect();
+    missing.sort_by_key(|missing| missing.chara_id);
+
+    CharacterNameReport {
+        db_names: get_db_names().read().map(|names| names.len()).unwrap_or(0),
+        bundled_names: get_bundled_names().len(),
+        missing,
+    }
+}
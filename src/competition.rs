@@ -0,0 +1,86 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
+
+/// UTC offset month boundaries are computed in (COMPETITION_TIMEZONE, e.g. "+09:00", default JST)
+///
+/// A fixed offset rather than a zone name so Rust and Postgres agree on it exactly.
+pub fn offset() -> FixedOffset {
+    crate::config::get().competition_offset
+}
+
+/// Ranking months start at this hour on the 1st, competition time; the SQL
+/// functions in the competition month migration apply the same rule
+pub const MONTH_ROLLOVER_HOUR: i64 = 12;
+
+/// Current time in the competition timezone
+pub fn now() -> DateTime<FixedOffset> {
+    Utc::now().with_timezone(&offset())
+}
+
+/// Current competition (year, month)
+pub fn current_month() -> (i32, i32) {
+    month_at(now())
+}
+
+/// Competition (year, month) a moment in competition time belongs to
+pub fn month_at(at: DateTime<FixedOffset>) -> (i32, i32) {
+    let ranking_day = at - chrono::Duration::hours(MONTH_ROLLOVER_HOUR);
+    (ranking_day.year(), ranking_day.month() as i32)
+}
+
+/// The month before the current one - the latest finished month
+pub fn previous_month() -> (i32, i32) {
+    let (year, month) = current_month();
+    if month == 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    }
+}
+
+/// Monday of the current competition week (ISO weeks)
+pub fn current_week_start() -> NaiveDate {
+    let today = now().date_naive();
+    today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64)
+}
+
+/// The (year, month) `months` after (or before, if negative) the given one
+pub fn add_months(year: i32, month: i32, months: i32) -> (i32, i32) {
+    let index = year * 12 + (month - 1) + months;
+    (index.div_euclid(12), index.rem_euclid(12) + 1)
+}
+
+/// SQL expression for CURRENT_TIMESTAMP as wall-clock time in the competition timezone
+pub fn now_sql() -> String {
+    // INTERVAL offsets use ISO signs, unlike POSIX zone strings such as '+09'
+    format!("(CURRENT_TIMESTAMP AT TIME ZONE INTERVAL '{}')", offset())
+}
+
+/// SQL expression for the start of a ranking month (timestamptz); `months` is
+/// relative to the current one. Evaluated by competition_month_start() from the
+/// offset the connection was given (see database::pool_options).
+pub fn month_start_sql(months: i32) -> String {
+    format!("competition_month_start({})", months)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jst(date: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(date).unwrap()
+    }
+
+    #[test]
+    fn month_rolls_over_at_noon_on_the_first() {
+        assert_eq!(month_at(jst("2026-11-01T11:59:59+09:00")), (2026, 10));
+        assert_eq!(month_at(jst("2026-11-01T12:00:00+09:00")), (2026, 11));
+        assert_eq!(month_at(jst("2026-11-30T23:59:59+09:00")), (2026, 11));
+    }
+
+    #[test]
+    fn january_before_noon_is_still_december() {
+        assert_eq!(month_at(jst("2027-01-01T06:00:00+09:00")), (2026, 12));
+        assert_eq!(add_months(2026, 12, 1), (2027, 1));
+        assert_eq!(add_months(2027, 1, -1), (2026, 12));
+    }
+}
@@ -0,0 +1,658 @@
+//! Typed configuration, read from the environment once at startup.
+//!
+//! `Config::from_env` checks every setting and reports all missing or invalid
+//! ones together. Handlers read it from `AppState::config`; code running
+//! without the state (middleware, background jobs, lazily initialised
+//! statics) uses `config::get()`.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::FixedOffset;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use crate::sparks::SparkEncoding;
+
+const DEFAULT_ALLOWED_ORIGINS: &[&str] = &[
+    "https://honse.moe",
+    "https://www.honse.moe",
+    "https://uma.moe",
+    "https://www.uma.moe",
+    "http://honse.moe",
+    "http://www.honse.moe",
+    "http://uma.moe",
+    "http://www.uma.moe",
+];
+
+/// Database used in MOCK_MODE when DATABASE_URL isn't set (never connected to)
+const MOCK_DATABASE_URL: &str = "postgres://localhost/umamoe";
+
+const DEFAULT_JOURNAL_PATH: &str = "submission-journal.jsonl";
+const DEFAULT_FEED_URL: &str = "https://honse.moe/feeds/notable.xml";
+
+/// The game's month resets in JST unless COMPETITION_TIMEZONE says otherwise
+const DEFAULT_COMPETITION_OFFSET_SECS: i32 = 9 * 3600;
+
+static CONFIG: OnceLock<Arc<Config>> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// DEBUG_MODE: INFO logging and permissive CORS
+    pub debug_mode: bool,
+    /// LOG_FORMAT=json: one JSON object per log line
+    pub json_logs: bool,
+    /// MOCK_MODE: serve bundled fixtures without a database
+    pub mock_mode: bool,
+
+    pub database_url: String,
+    /// REPLICA_DATABASE_URLS: read replicas for read-only queries (comma-separated)
+    pub replica_database_urls: Vec<String>,
+    pub skip_migrations: bool,
+    pub db_session: DbSessionConfig,
+
+    pub host: String,
+    pub port: u16,
+    pub unix_socket_path: Option<PathBuf>,
+    /// Permissions of the socket file (UNIX_SOCKET_MODE, octal)
+    pub unix_socket_mode: Option<u32>,
+    /// CORS origins outside debug mode
+    pub allowed_origins: Vec<String>,
+    pub max_request_body_bytes: usize,
+    pub shutdown_timeout: Duration,
+
+    /// Bearer token for the admin API; unset disables token access
+    pub admin_token: Option<String>,
+    /// Key the worker config and worker requests are signed with; unset disables the worker API
+    pub worker_signing_secret: Option<String>,
+    /// Networks worker requests may come from; empty allows any
+    pub worker_allowed_ips: Vec<ipnet::IpNet>,
+    /// Reverse proxies whose X-Forwarded-For / X-Real-IP / Forwarded headers
+    /// are believed; with none, the peer address is always the client
+    pub trusted_proxies: Vec<ipnet::IpNet>,
+    /// How far a signed worker request's timestamp may be from now
+    pub worker_signature_max_age: Duration,
+    /// Port of the gRPC worker service (on HOST); unset disables it
+    pub grpc_port: Option<u16>,
+    /// How often StreamTasks looks for tasks to push to a worker with free slots
+    pub grpc_task_poll_interval: Duration,
+    /// Secret mixed into visitor fingerprints; unset uses a random one per process
+    pub visitor_hash_salt: Option<String>,
+    pub turnstile: TurnstileConfig,
+    pub auto_ban: AutoBanConfig,
+    pub graphql: GraphqlConfig,
+    pub users: UserConfig,
+    pub notifications: NotificationConfig,
+    /// Where /api/events goes; None writes to the analytics_events table
+    pub clickhouse: Option<ClickHouseConfig>,
+
+    pub task_lease_secs: f64,
+    pub task_max_attempts: i32,
+    pub task_archive_after_hours: i32,
+    pub task_reaper_interval: Duration,
+    /// Months without an update before a trainer is flagged stale (None: never)
+    pub trainer_retention_months: Option<u32>,
+    /// Months of circle member fan counts kept before the current one (None keeps all)
+    pub circle_fans_retention_months: Option<u32>,
+    pub cache_cleanup_interval: Duration,
+    pub token_cleanup_interval: Duration,
+    /// Refresh interval per materialized view name
+    pub view_refresh_intervals: HashMap<&'static str, Duration>,
+
+    pub game_data_dir: Option<PathBuf>,
+    /// None when SUBMISSION_JOURNAL_PATH is set but empty (journal disabled)
+    pub submission_journal_path: Option<String>,
+    pub moderation_wordlist: Option<PathBuf>,
+    pub notable_feed_url: String,
+    pub websub_hub_url: Option<String>,
+    /// Lowest parent_rank listed in /feeds/new-inheritances.xml
+    pub new_inheritances_feed_min_rank: i32,
+    /// Distinct reports that queue a friend list recheck
+    pub friendlist_report_threshold: i64,
+    /// Time after which an inheritance vote counts half in community_score
+    pub community_score_half_life: Duration,
+    /// How long a matchmaking suggestion is held for the ticket it went to
+    pub matchmaking_reservation: Duration,
+    /// UTC offset month boundaries are computed in
+    pub competition_offset: FixedOffset,
+    /// Affinity formula used when a request doesn't pick one
+    pub affinity_version: u32,
+    /// Spark encoding searches start with (overridable at runtime)
+    pub spark_encoding: SparkEncoding,
+}
+
+#[derive(Debug, Clone)]
+pub struct TurnstileConfig {
+    pub secret_key: Option<String>,
+    /// TURNSTILE_BYPASS: skip verification (development only)
+    pub bypass: bool,
+    pub timeout: Duration,
+    pub max_retries: u32,
+    pub breaker_threshold: u32,
+    pub breaker_cooldown_secs: u64,
+    pub fail_open: bool,
+}
+
+/// Limits on queries to /api/graphql, checked before anything is resolved
+#[derive(Debug, Clone)]
+pub struct GraphqlConfig {
+    /// Deepest field nesting a query may use
+    pub max_depth: usize,
+    /// Highest complexity (selected fields, list fields weighted by their limit)
+    pub max_complexity: usize,
+}
+
+/// User accounts (/api/users)
+#[derive(Debug, Clone)]
+pub struct UserConfig {
+    /// USER_TOKEN_SECRET: key user tokens are signed with; unset disables accounts
+    pub token_secret: Option<String>,
+    /// USER_TOKEN_TTL_DAYS: how long a token is accepted after it was issued
+    pub token_ttl: Duration,
+    /// Sign-in with Discord; None when the DISCORD_* settings aren't set
+    pub discord: Option<DiscordOAuthConfig>,
+    /// Most bookmarks one account may keep
+    pub max_bookmarks: i64,
+}
+
+/// A Discord application for OAuth2 sign-in
+#[derive(Debug, Clone)]
+pub struct DiscordOAuthConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    /// Registered redirect URI the frontend receives the code on
+    pub redirect_uri: String,
+}
+
+/// Trainer availability notifications (Discord webhooks and web push)
+#[derive(Debug, Clone)]
+pub struct NotificationConfig {
+    /// How often the dispatcher delivers triggered notifications
+    pub dispatch_interval: Duration,
+    /// Delivery attempts before a notification is given up on
+    pub max_attempts: i32,
+    /// Most waiting subscriptions one account may keep
+    pub max_subscriptions: i64,
+    /// Key pair push messages are signed with; None disables web push
+    pub vapid: Option<VapidConfig>,
+}
+
+/// VAPID (RFC 8292) application server identity
+#[derive(Debug, Clone)]
+pub struct VapidConfig {
+    /// Uncompressed P-256 public key (65 bytes)
+    pub public_key: Vec<u8>,
+    /// P-256 private scalar (32 bytes)
+    pub private_key: Vec<u8>,
+    /// Contact for push services, a mailto: or https: URL
+    pub subject: String,
+}
+
+/// ClickHouse that analytics events are forwarded to (HTTP interface)
+#[derive(Debug, Clone)]
+pub struct ClickHouseConfig {
+    /// e.g. http://clickhouse:8123
+    pub url: String,
+    /// Table the events are inserted into (JSONEachRow)
+    pub table: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+}
+
+/// Settings applied to every database connection (None: server default / off)
+#[derive(Debug, Clone)]
+pub struct DbSessionConfig {
+    /// DB_STATEMENT_TIMEOUT_MS: longest a single query may run
+    pub statement_timeout: Option<Duration>,
+    /// DB_LONG_STATEMENT_TIMEOUT_MS: the limit for migrations, view refreshes
+    /// and long admin jobs (see database::begin_long_running)
+    pub long_statement_timeout: Option<Duration>,
+    /// DB_LOG_STATEMENTS: have Postgres log every statement and its duration
+    /// (needs superuser)
+    pub log_statements: bool,
+    /// DB_LOG_MIN_DURATION_MS: have Postgres log statements slower than this
+    /// (needs superuser)
+    pub log_min_duration: Option<Duration>,
+}
+
+/// When clients are banned automatically (a threshold of 0 disables that trigger)
+#[derive(Debug, Clone)]
+pub struct AutoBanConfig {
+    /// Failed Turnstile verifications from one IP within `window`
+    pub turnstile_failures: u32,
+    /// 429 responses to one IP or API key within `window`
+    pub rate_limit_violations: u32,
+    pub window: Duration,
+    /// How long automatic bans last
+    pub duration: Duration,
+}
+
+/// Every setting that was missing or invalid
+#[derive(Debug, thiserror::Error)]
+#[error("invalid configuration:\n  - {}", .problems.join("\n  - "))]
+pub struct ConfigError {
+    pub problems: Vec<String>,
+}
+
+/// Reads variables, remembering what was wrong instead of stopping at the first problem
+struct Env {
+    problems: Vec<String>,
+}
+
+impl Env {
+    /// The variable's trimmed value; empty counts as unset
+    fn string(&mut self, name: &str) -> Option<String> {
+        match std::env::var(name) {
+            Ok(value) if !value.trim().is_empty() => Some(value.trim().to_string()),
+            Ok(_) => None,
+            Err(std::env::VarError::NotPresent) => None,
+            Err(std::env::VarError::NotUnicode(_)) => {
+                self.problems.push(format!("{} is not valid UTF-8", name));
+                None
+            }
+        }
+    }
+
+    fn required(&mut self, name: &str) -> String {
+        self.string(name).unwrap_or_else(|| {
+            self.problems.push(format!("{} must be set", name));
+            String::new()
+        })
+    }
+
+    fn parse<T: FromStr>(&mut self, name: &str, expected: &str) -> Option<T> {
+        let value = self.string(name)?;
+        match value.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(_) => {
+                self.problems
+                    .push(format!("{} must be {} (got '{}')", name, expected, value));
+                None
+            }
+        }
+    }
+
+    fn number<T: FromStr>(&mut self, name: &str, default: T) -> T {
+        self.parse(name, "a number").unwrap_or(default)
+    }
+
+    fn positive<T: FromStr + PartialOrd + Default>(&mut self, name: &str, default: T) -> T {
+        match self.parse::<T>(name, "a number") {
+            Some(value) if value > T::default() => value,
+            Some(_) => {
+                self.problems.push(format!("{} must be greater than 0", name));
+                default
+            }
+            None => default,
+        }
+    }
+
+    fn seconds(&mut self, name: &str, default_secs: u64) -> Duration {
+        Duration::from_secs(self.positive(name, default_secs))
+    }
+
+    /// A duration in milliseconds where 0 means none
+    fn optional_millis(&mut self, name: &str, default_ms: u64) -> Option<Duration> {
+        Some(self.number(name, default_ms))
+            .filter(|&ms| ms > 0)
+            .map(Duration::from_millis)
+    }
+
+    /// Comma-separated IP addresses or CIDR ranges; bare addresses are
+    /// single-host networks
+    fn ip_ranges(&mut self, name: &str) -> Vec<ipnet::IpNet> {
+        let Some(value) = self.string(name) else {
+            return Vec::new();
+        };
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                crate::bans::parse_ip_range(entry).or_else(|| {
+                    self.problems.push(format!(
+                        "{} entry '{}' is not an IP address or CIDR range",
+                        name, entry
+                    ));
+                    None
+                })
+            })
+            .collect()
+    }
+
+    fn flag(&mut self, name: &str) -> bool {
+        match self.string(name).map(|v| v.to_lowercase()).as_deref() {
+            None | Some("false" | "0" | "no" | "off") => false,
+            Some("true" | "1" | "yes" | "on") => true,
+            Some(other) => {
+                self.problems
+                    .push(format!("{} must be true or false (got '{}')", name, other));
+                false
+            }
+        }
+    }
+}
+
+impl Config {
+    pub fn from_env() -> Result<Config, ConfigError> {
+        let mut env = Env { problems: Vec::new() };
+
+        let mock_mode = env.flag("MOCK_MODE");
+        let database_url = if mock_mode {
+            env.string("DATABASE_URL")
+                .unwrap_or_else(|| MOCK_DATABASE_URL.to_string())
+        } else {
+            env.required("DATABASE_URL")
+        };
+
+        let replica_database_urls = env
+            .string("REPLICA_DATABASE_URLS")
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty())
+                    .enumerate()
+                    .filter_map(|(index, url)| {
+                        // Not echoed, since it may contain a password
+                        match sqlx::postgres::PgConnectOptions::from_str(url) {
+                            Ok(_) => Some(url.to_string()),
+                            Err(_) => {
+                                env.problems.push(format!(
+                                    "REPLICA_DATABASE_URLS entry {} is not a valid Postgres URL",
+                                    index + 1
+                                ));
+                                None
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let db_session = DbSessionConfig {
+            statement_timeout: env.optional_millis("DB_STATEMENT_TIMEOUT_MS", 30_000),
+            long_statement_timeout: env.optional_millis("DB_LONG_STATEMENT_TIMEOUT_MS", 600_000),
+            log_statements: env.flag("DB_LOG_STATEMENTS"),
+            log_min_duration: env.optional_millis("DB_LOG_MIN_DURATION_MS", 0),
+        };
+
+        let unix_socket_mode = env.string("UNIX_SOCKET_MODE").and_then(|value| {
+            u32::from_str_radix(&value, 8)
+                .ok()
+                .filter(|&mode| mode <= 0o777)
+                .or_else(|| {
+                    env.problems.push(format!(
+                        "UNIX_SOCKET_MODE must be an octal mode like 660 (got '{}')",
+                        value
+                    ));
+                    None
+                })
+        });
+
+        let allowed_origins = match env.string("ALLOWED_ORIGINS") {
+            Some(value) => value
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .filter_map(|origin| match url::Url::parse(origin) {
+                    Ok(url) if matches!(url.scheme(), "http" | "https") => Some(origin.to_string()),
+                    _ => {
+                        env.problems.push(format!(
+                            "ALLOWED_ORIGINS entry '{}' is not an http(s) origin",
+                            origin
+                        ));
+                        None
+                    }
+                })
+                .collect(),
+            None => DEFAULT_ALLOWED_ORIGINS.iter().map(|o| o.to_string()).collect(),
+        };
+
+
+        let turnstile = TurnstileConfig {
+            secret_key: env.string("TURNSTILE_SECRET_KEY"),
+            bypass: env.flag("TURNSTILE_BYPASS"),
+            timeout: Duration::from_millis(env.positive("TURNSTILE_TIMEOUT_MS", 3000)),
+            max_retries: env.number("TURNSTILE_MAX_RETRIES", 2),
+            breaker_threshold: env.positive("TURNSTILE_BREAKER_THRESHOLD", 5),
+            breaker_cooldown_secs: env.number("TURNSTILE_BREAKER_COOLDOWN_SECS", 30),
+            fail_open: env.flag("TURNSTILE_FAIL_OPEN"),
+        };
+
+        let auto_ban = AutoBanConfig {
+            turnstile_failures: env.number("AUTO_BAN_TURNSTILE_FAILURES", 20),
+            rate_limit_violations: env.number("AUTO_BAN_RATE_LIMIT_VIOLATIONS", 100),
+            window: env.seconds("AUTO_BAN_WINDOW_SECS", 600),
+            duration: env.seconds("AUTO_BAN_DURATION_SECS", 3600),
+        };
+
+        let graphql = GraphqlConfig {
+            max_depth: env.positive("GRAPHQL_MAX_DEPTH", 8),
+            max_complexity: env.positive("GRAPHQL_MAX_COMPLEXITY", 2000),
+        };
+
+        let discord = match (
+            env.string("DISCORD_CLIENT_ID"),
+            env.string("DISCORD_CLIENT_SECRET"),
+            env.string("DISCORD_REDIRECT_URI"),
+        ) {
+            (Some(client_id), Some(client_secret), Some(redirect_uri)) => Some(DiscordOAuthConfig {
+                client_id,
+                client_secret,
+                redirect_uri,
+            }),
+            (None, None, None) => None,
+            _ => {
+                env.problems.push(
+                    "DISCORD_CLIENT_ID, DISCORD_CLIENT_SECRET and DISCORD_REDIRECT_URI must be set together"
+                        .to_string(),
+                );
+                None
+            }
+        };
+        let users = UserConfig {
+            token_secret: env.string("USER_TOKEN_SECRET"),
+            token_ttl: Duration::from_secs(env.positive("USER_TOKEN_TTL_DAYS", 30) * 86400),
+            discord,
+            max_bookmarks: env.positive("USER_MAX_BOOKMARKS", 500),
+        };
+
+        let vapid = match (
+            env.string("VAPID_PUBLIC_KEY"),
+            env.string("VAPID_PRIVATE_KEY"),
+            env.string("VAPID_SUBJECT"),
+        ) {
+            (Some(public_key), Some(private_key), Some(subject)) => {
+                let decode = |value: &str| URL_SAFE_NO_PAD.decode(value.trim_end_matches('='));
+                match (decode(&public_key), decode(&private_key)) {
+                    (Ok(public_key), Ok(private_key))
+                        if crate::notifications::web_push::is_vapid_key_pair(&public_key, &private_key) =>
+                    {
+                        if !(subject.starts_with("mailto:") || subject.starts_with("https://")) {
+                            env.problems
+                                .push("VAPID_SUBJECT must be a mailto: or https:// URL".to_string());
+                        }
+                        Some(VapidConfig {
+                            public_key,
+                            private_key,
+                            subject,
+                        })
+                    }
+                    _ => {
+                        env.problems.push(
+                            "VAPID_PUBLIC_KEY and VAPID_PRIVATE_KEY must be a base64url P-256 key pair (65-byte uncompressed public key, 32-byte private key)"
+                                .to_string(),
+                        );
+                        None
+                    }
+                }
+            }
+            (None, None, None) => None,
+            _ => {
+                env.problems.push(
+                    "VAPID_PUBLIC_KEY, VAPID_PRIVATE_KEY and VAPID_SUBJECT must be set together"
+                        .to_string(),
+                );
+                None
+            }
+        };
+        let notifications = NotificationConfig {
+            dispatch_interval: env.seconds("NOTIFICATION_DISPATCH_INTERVAL_SECS", 30),
+            max_attempts: env.positive("NOTIFICATION_MAX_ATTEMPTS", 5),
+            max_subscriptions: env.positive("USER_MAX_SUBSCRIPTIONS", 50),
+            vapid,
+        };
+
+        let clickhouse = env.string("CLICKHOUSE_URL").map(|url| {
+            let table = env
+                .string("CLICKHOUSE_TABLE")
+                .unwrap_or_else(|| "analytics_events".to_string());
+            if !table
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+            {
+                env.problems.push(format!(
+                    "CLICKHOUSE_TABLE must be a table name like db.events (got '{}')",
+                    table
+                ));
+            }
+            ClickHouseConfig {
+                url: url.trim_end_matches('/').to_string(),
+                table,
+                user: env.string("CLICKHOUSE_USER"),
+                password: env.string("CLICKHOUSE_PASSWORD"),
+            }
+        });
+
+        let view_refresh_intervals = crate::views::MATERIALIZED_VIEWS
+            .iter()
+            .map(|view| (view.name, env.seconds(view.interval_env, view.default_interval_secs)))
+            .collect();
+
+        let submission_journal_path = match std::env::var("SUBMISSION_JOURNAL_PATH") {
+            Ok(_) => env.string("SUBMISSION_JOURNAL_PATH"),
+            Err(_) => Some(DEFAULT_JOURNAL_PATH.to_string()),
+        };
+
+        let competition_offset = env
+            .parse("COMPETITION_TIMEZONE", "a UTC offset like +09:00")
+            .unwrap_or_else(|| {
+                FixedOffset::east_opt(DEFAULT_COMPETITION_OFFSET_SECS).expect("valid offset")
+            });
+
+        let affinity_version = match env.parse::<u32>("AFFINITY_VERSION", "a number") {
+            Some(version) if crate::affinity::resolve(Some(version)).is_err() => {
+                env.problems
+                    .push(format!("AFFINITY_VERSION {} is not a known formula version", version));
+                crate::affinity::LATEST_VERSION
+            }
+            Some(version) => version,
+            None => crate::affinity::LATEST_VERSION,
+        };
+
+        let spark_encoding = match env.string("SPARK_ENCODING").as_deref() {
+            None | Some("v1") => SparkEncoding::V1,
+            Some("v2") => SparkEncoding::V2,
+            Some(other) => {
+                env.problems
+                    .push(format!("SPARK_ENCODING must be v1 or v2 (got '{}')", other));
+                SparkEncoding::V1
+            }
+        };
+
+        let config = Config {
+            debug_mode: env.flag("DEBUG_MODE"),
+            json_logs: env
+                .string("LOG_FORMAT")
+                .is_some_and(|v| v.eq_ignore_ascii_case("json")),
+            mock_mode,
+            database_url,
+            replica_database_urls,
+            skip_migrations: env.flag("SKIP_MIGRATIONS"),
+            db_session,
+            host: env.string("HOST").unwrap_or_else(|| "127.0.0.1".to_string()),
+            port: env.parse("PORT", "a port number").unwrap_or(3001),
+            unix_socket_path: env.string("UNIX_SOCKET_PATH").map(PathBuf::from),
+            unix_socket_mode,
+            allowed_origins,
+            max_request_body_bytes: env.positive("MAX_REQUEST_BODY_BYTES", 2 * 1024 * 1024),
+            shutdown_timeout: Duration::from_secs(env.number("SHUTDOWN_TIMEOUT_SECS", 30)),
+            admin_token: env.string("ADMIN_TOKEN"),
+            worker_signing_secret: env.string("WORKER_SIGNING_SECRET"),
+            worker_allowed_ips: env.ip_ranges("WORKER_ALLOWED_IPS"),
+            trusted_proxies: env.ip_ranges("TRUSTED_PROXIES"),
+            worker_signature_max_age: env.seconds("WORKER_SIGNATURE_MAX_AGE_SECS", 300),
+            grpc_port: env.parse("GRPC_PORT", "a port number"),
+            grpc_task_poll_interval: Duration::from_millis(
+                env.positive("GRPC_TASK_POLL_INTERVAL_MS", 1000),
+            ),
+            visitor_hash_salt: env.string("VISITOR_HASH_SALT"),
+            turnstile,
+            auto_ban,
+            graphql,
+            users,
+            notifications,
+            clickhouse,
+            task_lease_secs: env.positive("TASK_LEASE_SECS", 900.0),
+            task_max_attempts: env.positive("TASK_MAX_ATTEMPTS", 5),
+            task_archive_after_hours: env.number("TASK_ARCHIVE_AFTER_HOURS", 24),
+            task_reaper_interval: env.seconds("TASK_REAPER_INTERVAL_SECS", 60),
+            trainer_retention_months: Some(env.number("TRAINER_RETENTION_MONTHS", 0))
+                .filter(|&months| months > 0),
+            circle_fans_retention_months: Some(env.number("CIRCLE_FANS_RETENTION_MONTHS", 0))
+                .filter(|&months| months > 0),
+            cache_cleanup_interval: env.seconds("CACHE_CLEANUP_INTERVAL_SECS", 600),
+            token_cleanup_interval: env.seconds("TOKEN_CLEANUP_INTERVAL_SECS", 300),
+            view_refresh_intervals,
+            game_data_dir: env.string("GAME_DATA_DIR").map(PathBuf::from),
+            submission_journal_path,
+            moderation_wordlist: env.string("MODERATION_WORDLIST").map(PathBuf::from),
+            notable_feed_url: env
+                .string("NOTABLE_FEED_URL")
+                .unwrap_or_else(|| DEFAULT_FEED_URL.to_string()),
+            websub_hub_url: env.string("WEBSUB_HUB_URL"),
+            new_inheritances_feed_min_rank: env.number("NEW_INHERITANCES_FEED_MIN_RANK", 19),
+            friendlist_report_threshold: env.positive("FRIENDLIST_REPORT_THRESHOLD", 3),
+            community_score_half_life: Duration::from_secs(
+                env.positive::<u64>("COMMUNITY_SCORE_HALF_LIFE_DAYS", 14) * 86400,
+            ),
+            matchmaking_reservation: env.seconds("MATCHMAKING_RESERVATION_SECS", 180),
+            competition_offset,
+            affinity_version,
+            spark_encoding,
+        };
+
+        if env.problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError {
+                problems: env.problems,
+            })
+        }
+    }
+}
+
+/// Make the configuration available through `get()`; called once at startup
+pub fn init(config: Config) -> Arc<Config> {
+    CONFIG.get_or_init(|| Arc::new(config)).clone()
+}
+
+/// The configuration loaded at startup
+pub fn get() -> &'static Config {
+    CONFIG
+        .get()
+        .expect("configuration is loaded at startup before it is read")
+}
+
+/// Configuration for unit tests: mock mode, with 10.0.0.0/8 as trusted proxies
+#[cfg(test)]
+pub(crate) fn init_for_tests() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        std::env::set_var("MOCK_MODE", "true");
+        std::env::set_var("TRUSTED_PROXIES", "10.0.0.0/8");
+        std::env::set_var("WORKER_SIGNING_SECRET", "test-secret");
+        Arc::new(Config::from_env().expect("test configuration is valid"))
+    })
+}
@@ -1,29 +1,245 @@
 use sqlx::{
     postgres::{PgConnectOptions, PgPoolOptions},
-    Executor, PgPool,
+    Executor, PgConnection, PgPool, Postgres, Transaction,
 };
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tracing::{info, warn};
 
-pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
+use crate::config::DbSessionConfig;
+
+/// Migrations embedded at build time; also what /readyz compares the database against
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
+
+/// Longest a replica health check waits before counting the replica as down
+const REPLICA_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Set once a logging SET was refused, so the warning isn't repeated per connection
+static LOGGING_REFUSED: AtomicBool = AtomicBool::new(false);
+
+pub async fn create_pool(
+    database_url: &str,
+    session: &DbSessionConfig,
+) -> Result<PgPool, sqlx::Error> {
     let options = PgConnectOptions::from_str(database_url)?
         .application_name("honsemoe-backend")
         .statement_cache_capacity(500); // Increase statement cache
 
+    pool_options(session)
+        .connect_with(options)
+        .await
+}
+
+/// Pool for a read replica; connects on first use so a replica that is down
+/// at startup doesn't keep the server from starting
+pub fn create_replica_pool(
+    database_url: &str,
+    session: &DbSessionConfig,
+) -> Result<PgPool, sqlx::Error> {
+    let options = PgConnectOptions::from_str(database_url)?
+        .application_name("honsemoe-backend-replica")
+        .statement_cache_capacity(500);
+
+    Ok(pool_options(session).min_connections(0).connect_lazy_with(options))
+}
+
+fn pool_options(session: &DbSessionConfig) -> PgPoolOptions {
+    let session = session.clone();
     PgPoolOptions::new()
         .max_connections(32) // Increase from default 10
         .min_connections(8) // Keep minimum connections ready
         .acquire_timeout(std::time::Duration::from_secs(2))
         .idle_timeout(std::time::Duration::from_secs(10))
         .test_before_acquire(false) // Disable if you trust connection stability
-        .after_connect(|conn, _meta| {
+        .after_connect(move |conn, _meta| {
+            let session = session.clone();
             Box::pin(async move {
-                // Enable query logging at PostgreSQL level
-                conn.execute("SET log_statement = 'all'").await?;
-                conn.execute("SET log_duration = on").await?;
-                conn.execute("SET log_min_duration_statement = 500").await?; // Log queries slower than 500ms
+                set_statement_timeout(conn, session.statement_timeout, false).await?;
+
+                // Read by the competition month functions in SQL
+                sqlx::query("SELECT set_config('umamoe.competition_offset', $1, false)")
+                    .bind(crate::config::get().competition_offset.to_string())
+                    .execute(&mut *conn)
+                    .await?;
+
+                // Postgres-side query logging; these need superuser, so a refusal
+                // is only reported rather than failing the connection
+                let mut logging = Vec::new();
+                if session.log_statements {
+                    logging.push("SET log_statement = 'all'".to_string());
+                    logging.push("SET log_duration = on".to_string());
+                }
+                if let Some(min_duration) = session.log_min_duration {
+                    logging.push(format!(
+                        "SET log_min_duration_statement = {}",
+                        min_duration.as_millis()
+                    ));
+                }
+                for statement in logging {
+                    if let Err(e) = conn.execute(statement.as_str()).await {
+                        if !LOGGING_REFUSED.swap(true, Ordering::Relaxed) {
+                            warn!("⚠️ Postgres query logging not enabled ({}): {}", statement, e);
+                        }
+                    }
+                }
                 Ok(())
             })
         })
-        .connect_with(options)
-        .await
+}
+
+/// Set statement_timeout on a connection, for the session or (`local`) the
+/// current transaction only; None lifts the limit
+async fn set_statement_timeout(
+    conn: &mut PgConnection,
+    timeout: Option<Duration>,
+    local: bool,
+) -> Result<(), sqlx::Error> {
+    let millis = timeout.map(|t| t.as_millis()).unwrap_or(0);
+    sqlx::query("SELECT set_config('statement_timeout', $1, $2)")
+        .bind(millis.to_string())
+        .bind(local)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Start a transaction whose statements may run up to DB_LONG_STATEMENT_TIMEOUT_MS
+/// instead of DB_STATEMENT_TIMEOUT_MS, for maintenance work such as view
+/// refreshes, award computation and batch rollbacks
+pub async fn begin_long_running(pool: &PgPool) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let timeout = crate::config::get().db_session.long_statement_timeout;
+    set_statement_timeout(&mut tx, timeout, true).await?;
+    Ok(tx)
+}
+
+/// A connection of its own with the long statement timeout, for migrations;
+/// it's closed when dropped instead of going back to the pool
+pub async fn acquire_long_running(pool: &PgPool) -> Result<PgConnection, sqlx::Error> {
+    let mut conn = pool.acquire().await?.detach();
+    let timeout = crate::config::get().db_session.long_statement_timeout;
+    set_statement_timeout(&mut conn, timeout, false).await?;
+    Ok(conn)
+}
+
+/// Pool that doesn't connect until first use, for MOCK_MODE where no database runs
+pub fn create_lazy_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
+    PgPoolOptions::new()
+        .acquire_timeout(std::time::Duration::from_secs(2))
+        .connect_lazy(database_url)
+}
+
+/// A read replica and whether its last health check passed
+struct Replica {
+    /// host:port/database, without credentials, for logs
+    name: String,
+    pool: PgPool,
+    healthy: AtomicBool,
+}
+
+/// The primary pool plus optional read replicas
+///
+/// Writes (and reads that must see them right away) go to `primary`. Read-only
+/// queries use `read()`, which spreads them over the replicas that passed their
+/// last health check and falls back to the primary when none did.
+pub struct DbPools {
+    pub primary: PgPool,
+    replicas: Vec<Replica>,
+    next_replica: AtomicUsize,
+}
+
+impl DbPools {
+    pub fn new(primary: PgPool) -> Self {
+        Self {
+            primary,
+            replicas: Vec::new(),
+            next_replica: AtomicUsize::new(0),
+        }
+    }
+
+    /// Add replicas, given as (name, pool); they take reads once a health check passes
+    pub fn with_replicas(mut self, replicas: Vec<(String, PgPool)>) -> Self {
+        self.replicas = replicas
+            .into_iter()
+            .map(|(name, pool)| Replica {
+                name,
+                pool,
+                healthy: AtomicBool::new(false),
+            })
+            .collect();
+        self
+    }
+
+    pub fn has_replicas(&self) -> bool {
+        !self.replicas.is_empty()
+    }
+
+    /// Pool for a read-only query: the next healthy replica, else the primary
+    pub fn read(&self) -> &PgPool {
+        if self.replicas.is_empty() {
+            return &self.primary;
+        }
+
+        let start = self.next_replica.fetch_add(1, Ordering::Relaxed);
+        (0..self.replicas.len())
+            .map(|offset| &self.replicas[(start + offset) % self.replicas.len()])
+            .find(|replica| replica.healthy.load(Ordering::Relaxed))
+            .map(|replica| &replica.pool)
+            .unwrap_or(&self.primary)
+    }
+
+    /// Run `SELECT 1` against every replica and take failing ones out of rotation
+    pub async fn check_replicas(&self) {
+        for replica in &self.replicas {
+            let result = tokio::time::timeout(
+                REPLICA_CHECK_TIMEOUT,
+                sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(&replica.pool),
+            )
+            .await;
+            let healthy = matches!(result, Ok(Ok(_)));
+
+            let was_healthy = replica.healthy.swap(healthy, Ordering::Relaxed);
+            match (was_healthy, healthy) {
+                (false, true) => info!("✅ Read replica {} is healthy, routing reads to it", replica.name),
+                (true, false) => warn!(
+                    "⚠️ Read replica {} failed its health check, routing reads elsewhere: {}",
+                    replica.name,
+                    match result {
+                        Ok(Err(e)) => e.to_string(),
+                        _ => "timed out".to_string(),
+                    }
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    /// (name, healthy) for every replica
+    pub fn replica_statuses(&self) -> Vec<(String, bool)> {
+        self.replicas
+            .iter()
+            .map(|replica| (replica.name.clone(), replica.healthy.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    pub async fn close(&self) {
+        self.primary.close().await;
+        for replica in &self.replicas {
+            replica.pool.close().await;
+        }
+    }
+}
+
+/// host:port/database of a connection URL, for logs (never the password)
+pub fn display_name(database_url: &str) -> String {
+    match PgConnectOptions::from_str(database_url) {
+        Ok(options) => format!(
+            "{}:{}/{}",
+            options.get_host(),
+            options.get_port(),
+            options.get_database().unwrap_or_default()
+        ),
+        Err(_) => "<invalid url>".to_string(),
+    }
 }
@@ -1,10 +1,32 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use serde::Serialize;
 use serde_json::json;
 
+/// One invalid request field and why it was rejected
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        FieldError {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Errors returned by handlers and middleware
+///
+/// Rendered as RFC 7807 `application/problem+json` with a stable `code` (see
+/// `AppError::code`) clients can branch on; `detail` is for humans and may
+/// change.
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
     #[error("Database error: {0}")]
@@ -13,34 +35,303 @@ pub enum AppError {
     #[error("Database error: {0}")]
     DatabaseError(String),
 
+    #[error("Internal error: {0}")]
+    Internal(String),
+
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    /// Request fields failed to parse or validate, sorted by field
+    #[error("Invalid fields: {}", describe_fields(.0))]
+    Validation(Vec<FieldError>),
+
     #[error("Not found: {0}")]
     NotFound(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    /// Turnstile token missing or rejected
+    #[error("Captcha failed: {0}")]
+    Captcha(String),
+
+    /// The request clashes with the current state (e.g. a duplicate)
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    /// Too many requests; `retry_after` seconds go out as Retry-After
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<u64>,
+    },
+
+    /// The work took longer than allowed (e.g. a statement timeout)
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+}
+
+impl AppError {
+    /// A single invalid field
+    pub fn invalid_field(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        AppError::Validation(vec![FieldError::new(field, reason)])
+    }
+
+    pub fn rate_limited(message: impl Into<String>) -> Self {
+        AppError::RateLimited {
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+
+    /// Postgres error class of a failed query, for the ones with their own code
+    fn database_code(err: &sqlx::Error) -> Option<&'static str> {
+        let sqlx::Error::Database(db) = err else {
+            return None;
+        };
+        match db.code().as_deref() {
+            // query_canceled: statement_timeout / lock_timeout
+            Some("57014") | Some("55P03") => Some("timeout"),
+            Some("23505") => Some("conflict"),
+            _ => None,
+        }
+    }
+
+    /// Stable, machine-readable error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(err) if is_connection_error(err) => "database_unavailable",
+            AppError::Database(err) => Self::database_code(err).unwrap_or("database_error"),
+            AppError::DatabaseError(_) => "database_error",
+            AppError::Internal(_) => "internal_error",
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Validation(_) => "validation_failed",
+            AppError::NotFound(_) => "not_found",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::Captcha(_) => "captcha_failed",
+            AppError::Conflict(_) => "conflict",
+            AppError::PayloadTooLarge(_) => "payload_too_large",
+            AppError::RateLimited { .. } => "rate_limited",
+            AppError::Timeout(_) => "timeout",
+            AppError::ServiceUnavailable(_) => "service_unavailable",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self.code() {
+            "database_unavailable" | "service_unavailable" => StatusCode::SERVICE_UNAVAILABLE,
+            "database_error" | "internal_error" => StatusCode::INTERNAL_SERVER_ERROR,
+            "bad_request" | "validation_failed" => StatusCode::BAD_REQUEST,
+            "not_found" => StatusCode::NOT_FOUND,
+            "unauthorized" => StatusCode::UNAUTHORIZED,
+            "forbidden" | "captcha_failed" => StatusCode::FORBIDDEN,
+            "conflict" => StatusCode::CONFLICT,
+            "payload_too_large" => StatusCode::PAYLOAD_TOO_LARGE,
+            "rate_limited" => StatusCode::TOO_MANY_REQUESTS,
+            "timeout" => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Message shown to the client; database internals are never exposed
+    pub fn detail(&self) -> String {
+        match self {
+            AppError::Database(_) => match self.code() {
+                "database_unavailable" => "Database unavailable".to_string(),
+                "timeout" => "The database took too long to answer".to_string(),
+                "conflict" => "The record already exists".to_string(),
+                _ => "Database error occurred".to_string(),
+            },
+            AppError::DatabaseError(msg)
+            | AppError::Internal(msg)
+            | AppError::BadRequest(msg)
+            | AppError::NotFound(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::Captcha(msg)
+            | AppError::Conflict(msg)
+            | AppError::PayloadTooLarge(msg)
+            | AppError::RateLimited { message: msg, .. }
+            | AppError::Timeout(msg)
+            | AppError::ServiceUnavailable(msg) => msg.clone(),
+            AppError::Validation(errors) => describe_fields(errors),
+        }
+    }
+
+    /// Log server-side failures; client errors aren't worth a log line
+    pub fn log(&self) {
+        match self {
+            AppError::Database(err) if is_connection_error(err) => {
+                tracing::error!("Database unavailable: {}", err)
+            }
+            AppError::Database(err) => tracing::error!("Database error: {:?}", err),
+            AppError::DatabaseError(msg) => tracing::error!("Database error: {}", msg),
+            AppError::Internal(msg) => tracing::error!("Internal error: {}", msg),
+            _ => {}
+        }
+    }
+}
+
+fn describe_fields(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|error| format!("{}: {}", error.field, error.reason))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Why a validator check failed: its message if it has one, otherwise the
+/// check and its parameters, e.g. "range check failed (min: 1)"
+fn describe_validation_error(error: &validator::ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+    let mut params: Vec<String> = error
+        .params
+        .iter()
+        .filter(|(name, _)| *name != "value")
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect();
+    params.sort();
+    if params.is_empty() {
+        format!("{} check failed", error.code)
+    } else {
+        format!("{} check failed ({})", error.code, params.join(", "))
+    }
+}
+
+/// Every failed check of every field, nested structs and lists as
+/// "parent.field" and "list[index].field"
+fn collect_field_errors(
+    prefix: &str,
+    errors: &validator::ValidationErrors,
+    out: &mut Vec<FieldError>,
+) {
+    for (field, kind) in errors.errors() {
+        let field = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+        match kind {
+            validator::ValidationErrorsKind::Field(failed) => out.extend(
+                failed
+                    .iter()
+                    .map(|error| FieldError::new(field.clone(), describe_validation_error(error))),
+            ),
+            validator::ValidationErrorsKind::Struct(nested) => {
+                collect_field_errors(&field, nested, out)
+            }
+            validator::ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_field_errors(&format!("{}[{}]", field, index), nested, out);
+                }
+            }
+        }
+    }
+}
+
+/// Every failed check of every field
+pub fn field_errors(errors: &validator::ValidationErrors) -> Vec<FieldError> {
+    let mut fields = Vec::new();
+    collect_field_errors("", errors, &mut fields);
+    fields
+}
+
+impl From<validator::ValidationErrors> for AppError {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        AppError::from(field_errors(&errors))
+    }
+}
+
+/// Sorted by field, so the same request always reports the same way
+impl From<Vec<FieldError>> for AppError {
+    fn from(mut errors: Vec<FieldError>) -> Self {
+        errors.sort_by(|a, b| a.field.cmp(&b.field));
+        AppError::Validation(errors)
+    }
+}
+
+/// Seconds clients are asked to wait (Retry-After) while the database is unreachable
+const DATABASE_UNAVAILABLE_RETRY_AFTER_SECS: u64 = 30;
+
+/// Response extension marking a 503 caused by an unreachable database, so
+/// routes with a fallback copy can answer in degraded mode instead
+#[derive(Debug, Clone, Copy)]
+pub struct DatabaseUnavailable;
+
+/// Whether `err` means no connection could be used at all, as opposed to a
+/// failing query
+pub fn is_connection_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_) => true,
+        // Class 08 (connection exception) and 57P01-57P03 (shutdown, cannot connect now)
+        sqlx::Error::Database(db) => db
+            .code()
+            .is_some_and(|code| code.starts_with("08") || matches!(&*code, "57P01" | "57P02" | "57P03")),
+        _ => false,
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match &self {
-            AppError::Database(err) => {
-                tracing::error!("Database error: {:?}", err);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Database error occurred")
+        self.log();
+        let status = self.status();
+
+        let mut body = json!({
+            "type": "about:blank",
+            "title": status.canonical_reason().unwrap_or("Error"),
+            "status": status.as_u16(),
+            "code": self.code(),
+            "detail": self.detail(),
+            // Pre-problem+json clients read the message from `error`
+            "error": self.detail(),
+        });
+        let retry_after = match &self {
+            AppError::Validation(errors) => {
+                // `field` is the first one, for clients that only show one
+                body["field"] = json!(errors.first().map(|error| &error.field));
+                body["errors"] = json!(errors);
+                None
             }
-            AppError::DatabaseError(msg) => {
-                tracing::error!("Database error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, msg.as_str())
+            AppError::RateLimited { retry_after, .. } => *retry_after,
+            AppError::Database(err) if is_connection_error(err) => {
+                Some(DATABASE_UNAVAILABLE_RETRY_AFTER_SECS)
             }
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.as_str()),
+            _ => None,
         };
+        if let Some(retry_after) = retry_after {
+            body["retry_after"] = json!(retry_after);
+        }
 
-        let body = Json(json!({
-            "error": error_message,
-            "status": status.as_u16()
-        }));
-
-        (status, body).into_response()
+        let mut response = (
+            status,
+            [(header::CONTENT_TYPE, "application/problem+json")],
+            Json(body),
+        )
+            .into_response();
+        if let Some(retry_after) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, retry_after.into());
+        }
+        if self.code() == "database_unavailable" {
+            response.extensions_mut().insert(DatabaseUnavailable);
+        }
+        response
     }
 }
 
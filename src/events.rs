@@ -0,0 +1,29 @@
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+
+/// Buffered events per subscriber before the slowest one starts lagging
+const EVENT_BUS_CAPACITY: usize = 256;
+
+/// In-process domain events, published by handlers and consumed by background listeners
+#[derive(Debug, Clone)]
+pub enum DomainEvent {
+    /// Stored inheritance/support card data changed (a scrape task completed, or an admin fix)
+    TrainerDataChanged { account_ids: Vec<String> },
+}
+
+static EVENT_BUS: OnceLock<broadcast::Sender<DomainEvent>> = OnceLock::new();
+
+fn get_bus() -> &'static broadcast::Sender<DomainEvent> {
+    EVENT_BUS.get_or_init(|| broadcast::channel(EVENT_BUS_CAPACITY).0)
+}
+
+/// Publish an event to all current subscribers
+pub fn publish(event: DomainEvent) {
+    // Sending only fails when nobody is subscribed, which is fine
+    let _ = get_bus().send(event);
+}
+
+/// Subscribe to events published from now on
+pub fn subscribe() -> broadcast::Receiver<DomainEvent> {
+    get_bus().subscribe()
+}
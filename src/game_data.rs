@@ -0,0 +1,278 @@
+//! Imports game master data (character names, support card metadata) from
+//! JSON or CSV into the character_names and support_card_meta tables.
+
+use serde::de::DeserializeOwned;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::errors::AppError;
+use crate::models::{CharacterNameEntry, GameDataImportResult, SupportCardMeta};
+
+const CARD_TYPES: &[&str] = &["speed", "stamina", "power", "guts", "wit", "friend", "group"];
+
+#[derive(Debug, Clone, Copy)]
+pub enum GameDataKind {
+    Characters,
+    SupportCards,
+}
+
+impl GameDataKind {
+    pub const ALL: [GameDataKind; 2] = [GameDataKind::Characters, GameDataKind::SupportCards];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "characters" => Some(Self::Characters),
+            "support-cards" => Some(Self::SupportCards),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Characters => "characters",
+            Self::SupportCards => "support-cards",
+        }
+    }
+
+    /// File name (without extension) looked for in GAME_DATA_DIR
+    fn file_stem(&self) -> &'static str {
+        match self {
+            Self::Characters => "characters",
+            Self::SupportCards => "support_cards",
+        }
+    }
+
+    /// CSV columns holding integers (empty cells become null)
+    fn numeric_columns(&self) -> &'static [&'static str] {
+        match self {
+            Self::Characters => &["chara_id"],
+            Self::SupportCards => &["support_card_id", "rarity", "chara_id"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameDataFormat {
+    /// An array of objects
+    Json,
+    /// A header row naming the fields, then one row per entry
+    Csv,
+}
+
+/// Import one kind of master data, replacing existing rows with the same ID,
+/// and reload the in-memory lookups. Returns the number of rows written.
+pub async fn import(
+    pool: &PgPool,
+    kind: GameDataKind,
+    body: &str,
+    format: GameDataFormat,
+) -> Result<GameDataImportResult, AppError> {
+    let imported = match kind {
+        GameDataKind::Characters => {
+            let entries: Vec<CharacterNameEntry> = parse_rows(body, format, kind)?;
+            import_characters(pool, entries).await?
+        }
+        GameDataKind::SupportCards => {
+            let cards: Vec<SupportCardMeta> = parse_rows(body, format, kind)?;
+            import_support_cards(pool, cards).await?
+        }
+    };
+
+    tracing::info!("📥 Imported {} {} rows from game data", imported, kind.as_str());
+
+    Ok(GameDataImportResult {
+        kind: kind.as_str().to_string(),
+        imported,
+    })
+}
+
+/// Import characters.{json,csv} and support_cards.{json,csv} from a directory,
+/// skipping files that don't exist
+pub async fn import_directory(pool: &PgPool, dir: &Path) {
+    for kind in GameDataKind::ALL {
+        for (extension, format) in [("json", GameDataFormat::Json), ("csv", GameDataFormat::Csv)] {
+            let path = dir.join(format!("{}.{}", kind.file_stem(), extension));
+            let body = match tokio::fs::read_to_string(&path).await {
+                Ok(body) => body,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    tracing::warn!("⚠️ Failed to read game data {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+
+            if let Err(e) = import(pool, kind, &body, format).await {
+                tracing::warn!("⚠️ Failed to import game data {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+async fn import_characters(
+    pool: &PgPool,
+    entries: Vec<CharacterNameEntry>,
+) -> Result<usize, AppError> {
+    // Later rows win; one upsert can't touch the same ID twice
+    let mut names = BTreeMap::new();
+    for entry in entries {
+        let name = entry.name.trim();
+        if entry.chara_id.0 <= 0 || name.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "Invalid character entry: {} '{}'",
+                entry.chara_id, entry.name
+            )));
+        }
+        names.insert(entry.chara_id.0, name.to_string());
+    }
+
+    let (ids, names): (Vec<i32>, Vec<String>) = names.into_iter().unzip();
+    let result = sqlx::query(
+        r#"
+        INSERT INTO character_names (chara_id, name, updated_at)
+        SELECT chara_id, name, CURRENT_TIMESTAMP
+        FROM UNNEST($1::int[], $2::text[]) AS t(chara_id, name)
+        ON CONFLICT (chara_id) DO UPDATE
+        SET name = EXCLUDED.name, updated_at = EXCLUDED.updated_at
+        WHERE character_names.name IS DISTINCT FROM EXCLUDED.name
+        "#,
+    )
+    .bind(&ids)
+    .bind(&names)
+    .execute(pool)
+    .await?;
+
+    crate::characters::load_character_names(pool).await;
+    Ok(result.rows_affected() as usize)
+}
+
+async fn import_support_cards(
+    pool: &PgPool,
+    cards: Vec<SupportCardMeta>,
+) -> Result<usize, AppError> {
+    let mut by_id = BTreeMap::new();
+    for mut card in cards {
+        card.name = card.name.trim().to_string();
+        card.card_type = card.card_type.trim().to_lowercase();
+        if card.support_card_id.0 <= 0
+            || card.name.is_empty()
+            || !(1..=3).contains(&card.rarity)
+            || !CARD_TYPES.contains(&card.card_type.as_str())
+        {
+            return Err(AppError::BadRequest(format!(
+                "Invalid support card entry: {} '{}' (rarity {}, type '{}')",
+                card.support_card_id, card.name, card.rarity, card.card_type
+            )));
+        }
+        by_id.insert(card.support_card_id.0, card);
+    }
+
+    let cards: Vec<SupportCardMeta> = by_id.into_values().collect();
+    let result = sqlx::query(
+        r#"
+        INSERT INTO support_card_meta (support_card_id, name, rarity, card_type, chara_id, updated_at)
+        SELECT support_card_id, name, rarity, card_type, chara_id, CURRENT_TIMESTAMP
+        FROM UNNEST($1::int[], $2::text[], $3::int[], $4::text[], $5::int[])
+            AS t(support_card_id, name, rarity, card_type, chara_id)
+        ON CONFLICT (support_card_id) DO UPDATE
+        SET name = EXCLUDED.name,
+            rarity = EXCLUDED.rarity,
+            card_type = EXCLUDED.card_type,
+            chara_id = EXCLUDED.chara_id,
+            updated_at = EXCLUDED.updated_at
+        WHERE (support_card_meta.name, support_card_meta.rarity, support_card_meta.card_type, support_card_meta.chara_id)
+            IS DISTINCT FROM (EXCLUDED.name, EXCLUDED.rarity, EXCLUDED.card_type, EXCLUDED.chara_id)
+        "#,
+    )
+    .bind(cards.iter().map(|c| c.support_card_id.0).collect::<Vec<_>>())
+    .bind(cards.iter().map(|c| c.name.clone()).collect::<Vec<_>>())
+    .bind(cards.iter().map(|c| c.rarity).collect::<Vec<_>>())
+    .bind(cards.iter().map(|c| c.card_type.clone()).collect::<Vec<_>>())
+    .bind(cards.iter().map(|c| c.chara_id.map(|id| id.0)).collect::<Vec<_>>())
+    .execute(pool)
+    .await?;
+
+    crate::support_cards::load_support_card_meta(pool).await;
+    Ok(result.rows_affected() as usize)
+}
+
+fn parse_rows<T: DeserializeOwned>(
+    body: &str,
+    format: GameDataFormat,
+    kind: GameDataKind,
+) -> Result<Vec<T>, AppError> {
+    let invalid = |e: String| AppError::BadRequest(format!("Invalid {} data: {}", kind.as_str(), e));
+
+    match format {
+        GameDataFormat::Json => serde_json::from_str(body).map_err(|e| invalid(e.to_string())),
+        GameDataFormat::Csv => {
+            let mut rows = parse_csv(body).map_err(invalid)?.into_iter();
+            let header = rows.next().unwrap_or_default();
+
+            rows.enumerate()
+                .map(|(i, row)| {
+                    let mut object = serde_json::Map::new();
+                    for (column, value) in header.iter().zip(row) {
+                        let column = column.trim();
+                        let value = if value.is_empty() {
+                            serde_json::Value::Null
+                        } else if kind.numeric_columns().contains(&column) {
+                            value
+                                .trim()
+                                .parse::<i64>()
+                                .map(serde_json::Value::from)
+                                .map_err(|_| {
+                                    invalid(format!("line {}: {} is not a number", i + 2, column))
+                                })?
+                        } else {
+                            serde_json::Value::String(value)
+                        };
+                        object.insert(column.to_string(), value);
+                    }
+                    serde_json::from_value(serde_json::Value::Object(object))
+                        .map_err(|e| invalid(format!("line {}: {}", i + 2, e)))
+                })
+                .collect()
+        }
+    }
+}
+
+/// Split CSV into rows of fields (RFC 4180 quoting, blank lines skipped)
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|f| !f.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            ('\r', false) => {}
+            (c, _) => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err("unterminated quoted field".to_string());
+    }
+    row.push(field);
+    if row.iter().any(|f| !f.is_empty()) {
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
@@ -0,0 +1,161 @@
+//! Batched lookups behind nested fields; one set per request, so results are
+//! only shared within a query
+
+use async_graphql::dataloader::Loader;
+use async_graphql::Error;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+use super::graphql_error;
+use crate::handlers::circles::CIRCLE_SELECT_SQL;
+use crate::handlers::sharing::INHERITANCE_SHARE_SELECT;
+use crate::models::{AccountId, Circle, CircleId, Inheritance, SupportCard, ViewerId};
+
+/// Columns of a trainer row
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TrainerRow {
+    pub account_id: AccountId,
+    pub name: String,
+    pub follower_num: Option<i32>,
+    pub last_updated: Option<chrono::NaiveDateTime>,
+    pub stale: bool,
+}
+
+fn account_ids(keys: &[AccountId]) -> Vec<&str> {
+    keys.iter().map(AccountId::as_str).collect()
+}
+
+/// Trainers by account ID
+pub struct TrainerLoader(pub PgPool);
+
+impl Loader<AccountId> for TrainerLoader {
+    type Value = TrainerRow;
+    type Error = Error;
+
+    async fn load(&self, keys: &[AccountId]) -> Result<HashMap<AccountId, TrainerRow>, Error> {
+        let rows = sqlx::query_as::<_, TrainerRow>(
+            r#"
+            SELECT account_id, COALESCE(display_name_override, name) AS name, follower_num, last_updated, stale
+            FROM trainer
+            WHERE account_id = ANY($1)
+            "#,
+        )
+        .bind(account_ids(keys))
+        .fetch_all(&self.0)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+
+        Ok(rows.into_iter().map(|row| (row.account_id.clone(), row)).collect())
+    }
+}
+
+/// Inheritance records of a trainer, newest first
+pub struct InheritanceLoader(pub PgPool);
+
+impl Loader<AccountId> for InheritanceLoader {
+    type Value = Vec<Inheritance>;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        keys: &[AccountId],
+    ) -> Result<HashMap<AccountId, Vec<Inheritance>>, Error> {
+        let records = sqlx::query_as::<_, Inheritance>(&format!(
+            "{} WHERE t.account_id = ANY($1) ORDER BY i.inheritance_id DESC",
+            INHERITANCE_SHARE_SELECT
+        ))
+        .bind(account_ids(keys))
+        .fetch_all(&self.0)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+
+        let mut by_account: HashMap<AccountId, Vec<Inheritance>> = HashMap::new();
+        for record in records {
+            by_account.entry(record.account_id.clone()).or_default().push(record);
+        }
+        Ok(by_account)
+    }
+}
+
+/// Support cards of a trainer, highest experience first (as on share pages)
+pub struct SupportCardLoader(pub PgPool);
+
+impl Loader<AccountId> for SupportCardLoader {
+    type Value = Vec<SupportCard>;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        keys: &[AccountId],
+    ) -> Result<HashMap<AccountId, Vec<SupportCard>>, Error> {
+        let cards = sqlx::query_as::<_, SupportCard>(
+            r#"
+            SELECT account_id, support_card_id, limit_break_count, experience
+            FROM support_card
+            WHERE account_id = ANY($1)
+            ORDER BY experience DESC, support_card_id ASC
+            "#,
+        )
+        .bind(account_ids(keys))
+        .fetch_all(&self.0)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+
+        let mut by_account: HashMap<AccountId, Vec<SupportCard>> = HashMap::new();
+        for card in cards {
+            by_account.entry(card.account_id.clone()).or_default().push(card);
+        }
+        Ok(by_account)
+    }
+}
+
+/// Circle a viewer was last seen in (latest month first, as find_viewer_circle)
+pub struct ViewerCircleLoader(pub PgPool);
+
+impl Loader<ViewerId> for ViewerCircleLoader {
+    type Value = CircleId;
+    type Error = Error;
+
+    async fn load(&self, keys: &[ViewerId]) -> Result<HashMap<ViewerId, CircleId>, Error> {
+        let viewer_ids: Vec<i64> = keys.iter().map(|id| id.0).collect();
+        let rows = sqlx::query_as::<_, (ViewerId, CircleId)>(
+            r#"
+            SELECT DISTINCT ON (viewer_id) viewer_id, circle_id
+            FROM circle_member_fans_monthly
+            WHERE viewer_id = ANY($1)
+            ORDER BY viewer_id, year DESC, month DESC
+            "#,
+        )
+        .bind(viewer_ids)
+        .fetch_all(&self.0)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+
+        Ok(rows.into_iter().collect())
+    }
+}
+
+/// Circles by ID, moderated
+pub struct CircleLoader(pub PgPool);
+
+impl Loader<CircleId> for CircleLoader {
+    type Value = Circle;
+    type Error = Error;
+
+    async fn load(&self, keys: &[CircleId]) -> Result<HashMap<CircleId, Circle>, Error> {
+        let circle_ids: Vec<i64> = keys.iter().map(|id| id.0).collect();
+        let circles = sqlx::query_as::<_, Circle>(&format!(
+            "{} WHERE c.circle_id = ANY($1)",
+            CIRCLE_SELECT_SQL
+        ))
+        .bind(circle_ids)
+        .fetch_all(&self.0)
+        .await
+        .map_err(|e| graphql_error(e.into()))?;
+
+        Ok(circles
+            .into_iter()
+            .map(|circle| (circle.circle_id, crate::moderation::mask_circle(circle)))
+            .collect())
+    }
+}
@@ -0,0 +1,197 @@
+//! GraphQL schema served at POST /api/graphql.
+//!
+//! Resolvers go through the same storage and queries as the REST endpoints.
+//! Relations (a trainer's records and cards, a member's trainer, a trainer's
+//! circle) are batched by per-request dataloaders, so a list of N trainers
+//! costs one query per relation instead of N. Depth and complexity are
+//! limited by GRAPHQL_MAX_DEPTH and GRAPHQL_MAX_COMPLEXITY before anything
+//! runs.
+
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Error, ErrorExtensions, Object, Result, Schema, ID,
+};
+use std::sync::OnceLock;
+
+use crate::errors::AppError;
+use crate::handlers::search::{parse_search_params, validate_search_type};
+use crate::handlers::tasks::is_valid_trainer_id;
+use crate::models::{AccountId, CircleId, CircleListParams};
+use crate::AppState;
+
+mod loaders;
+mod types;
+
+use loaders::{CircleLoader, InheritanceLoader, SupportCardLoader, TrainerLoader, ViewerCircleLoader};
+use types::{load_trainer, Circle, SearchPage, Trainer};
+
+/// Most trainers `trainers` looks up at once
+const MAX_TRAINERS: usize = 100;
+/// Most results per page of `search` and `circles`
+const MAX_PAGE_SIZE: i64 = 100;
+
+pub type AppSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+static SCHEMA: OnceLock<AppSchema> = OnceLock::new();
+
+/// The schema, built on first use with the configured limits
+pub fn schema() -> &'static AppSchema {
+    SCHEMA.get_or_init(|| {
+        let limits = &crate::config::get().graphql;
+        Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .limit_depth(limits.max_depth)
+            .limit_complexity(limits.max_complexity)
+            .finish()
+    })
+}
+
+/// Attach the state and a fresh set of dataloaders (reading from a replica
+/// when there is one) to a request
+pub fn prepare_request(request: async_graphql::Request, state: &AppState) -> async_graphql::Request {
+    let pool = state.read_db().clone();
+    request
+        .data(state.clone())
+        .data(DataLoader::new(TrainerLoader(pool.clone()), tokio::spawn))
+        .data(DataLoader::new(InheritanceLoader(pool.clone()), tokio::spawn))
+        .data(DataLoader::new(SupportCardLoader(pool.clone()), tokio::spawn))
+        .data(DataLoader::new(ViewerCircleLoader(pool.clone()), tokio::spawn))
+        .data(DataLoader::new(CircleLoader(pool), tokio::spawn))
+}
+
+/// GraphQL error for an AppError, with the same messages as the REST
+/// responses, the HTTP status name as `extensions.code` and the problem code
+/// (e.g. "validation_failed") as `extensions.error_code`
+pub(crate) fn graphql_error(err: AppError) -> Error {
+    err.log();
+    let status = err.status();
+    let status_name = status
+        .canonical_reason()
+        .unwrap_or("Internal Server Error")
+        .to_uppercase()
+        .replace([' ', '-'], "_");
+    let error_code = err.code();
+    Error::new(err.detail()).extend_with(|_, extensions| {
+        extensions.set("code", status_name);
+        extensions.set("error_code", error_code);
+    })
+}
+
+fn check_account_id(account_id: &str) -> Result<AccountId> {
+    let account_id = account_id.trim();
+    if !is_valid_trainer_id(account_id) {
+        return Err(graphql_error(AppError::BadRequest(format!(
+            "Invalid trainer ID '{}'. Must be 9-12 digits.",
+            account_id
+        ))));
+    }
+    Ok(AccountId::from(account_id))
+}
+
+fn page_size(limit: i64) -> i64 {
+    limit.clamp(1, MAX_PAGE_SIZE)
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// A trainer by account ID
+    async fn trainer(&self, ctx: &Context<'_>, account_id: String) -> Result<Option<Trainer>> {
+        load_trainer(ctx, check_account_id(&account_id)?).await
+    }
+
+    /// Several trainers by account ID (at most 100), in the order asked for;
+    /// unknown IDs are left out
+    #[graphql(complexity = "account_ids.len() * child_complexity")]
+    async fn trainers(&self, ctx: &Context<'_>, account_ids: Vec<String>) -> Result<Vec<Trainer>> {
+        if account_ids.len() > MAX_TRAINERS {
+            return Err(graphql_error(AppError::BadRequest(format!(
+                "At most {} trainers can be requested at once",
+                MAX_TRAINERS
+            ))));
+        }
+        let account_ids = account_ids
+            .iter()
+            .map(|account_id| check_account_id(account_id))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut found = ctx
+            .data_unchecked::<DataLoader<TrainerLoader>>()
+            .load_many(account_ids.iter().cloned())
+            .await?;
+        Ok(account_ids
+            .iter()
+            .filter_map(|account_id| found.remove(account_id))
+            .map(Trainer)
+            .collect())
+    }
+
+    /// Inheritance and support card search; `filter` takes the query string
+    /// of GET /api/v3/search (e.g. "main_parent_id=1007&parent_rank=3"),
+    /// paging is set with `page` and `limit` instead
+    #[graphql(complexity = "page_size(limit) as usize * child_complexity")]
+    async fn search(
+        &self,
+        #[graphql(default)] filter: String,
+        #[graphql(default = 0)] page: i64,
+        #[graphql(default = 20)] limit: i64,
+    ) -> Result<SearchPage> {
+        let params = parse_search_params(filter.trim_start_matches('?')).map_err(graphql_error)?;
+        validate_search_type(&params).map_err(graphql_error)?;
+        crate::affinity::resolve(params.affinity_version).map_err(graphql_error)?;
+        crate::live_stats::SEARCHES.record(1);
+
+        Ok(SearchPage {
+            params,
+            page: page.max(0),
+            limit: page_size(limit),
+        })
+    }
+
+    /// A circle by ID
+    async fn circle(&self, ctx: &Context<'_>, circle_id: ID) -> Result<Option<Circle>> {
+        let circle_id = circle_id.parse::<i64>().map(CircleId).map_err(|_| {
+            graphql_error(AppError::BadRequest(format!("Invalid circle ID '{}'", *circle_id)))
+        })?;
+        match ctx.data_unchecked::<AppState>().storage.circle(circle_id).await {
+            Ok(circle) => Ok(Some(Circle(circle))),
+            Err(AppError::NotFound(_)) => Ok(None),
+            Err(err) => Err(graphql_error(err)),
+        }
+    }
+
+    /// One page of the circle list, as GET /api/v4/circles/list
+    #[graphql(complexity = "page_size(limit) as usize * child_complexity")]
+    #[allow(clippy::too_many_arguments)]
+    async fn circles(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Circle ID/name, leader ID/name or member ID/name")] query: Option<String>,
+        #[graphql(desc = "name, member_count, monthly_rank or monthly_point")] sort_by: Option<String>,
+        #[graphql(desc = "asc or desc")] sort_dir: Option<String>,
+        #[graphql(desc = "Standings of a past month (with month)")] year: Option<i32>,
+        #[graphql(desc = "Standings of a past month (1-12, with year)")] month: Option<u32>,
+        #[graphql(default = 0)] page: i64,
+        #[graphql(default = 100)] limit: i64,
+    ) -> Result<Vec<Circle>> {
+        let params = CircleListParams {
+            query,
+            sort_by,
+            sort_dir,
+            year,
+            month,
+            ..Default::default()
+        };
+        let limit = page_size(limit);
+        let (_, circles) = ctx
+            .data_unchecked::<AppState>()
+            .storage
+            .list_circles(&params, limit, page.max(0) * limit)
+            .await
+            .map_err(graphql_error)?;
+        Ok(circles
+            .into_iter()
+            .map(|circle| Circle(crate::moderation::mask_circle(circle)))
+            .collect())
+    }
+}
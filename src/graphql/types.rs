@@ -0,0 +1,490 @@
+//! Object types of the schema; thin wrappers around the REST models that
+//! resolve relations through the request's dataloaders
+
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{Context, Object, Result, ID};
+use chrono::NaiveDateTime;
+
+use super::graphql_error;
+use super::loaders::{
+    CircleLoader, InheritanceLoader, SupportCardLoader, TrainerLoader, TrainerRow,
+    ViewerCircleLoader,
+};
+use crate::models::{
+    self, AccountId, CircleMemberFansMonthly, CircleQueryParams, Inheritance, SupportCard,
+    UnifiedAccountRecord, UnifiedSearchParams, ViewerId,
+};
+use crate::AppState;
+
+/// Most support cards a trainer field returns
+pub(super) const MAX_SUPPORT_CARDS: i32 = 50;
+/// Most members a circle field returns per page
+pub(super) const MAX_CIRCLE_MEMBERS: i32 = 100;
+/// Assumed inheritance records per trainer, for query complexity
+const INHERITANCE_RECORDS_COMPLEXITY: usize = 5;
+/// Assumed circle size when members aren't paged, for query complexity
+const CIRCLE_SIZE_COMPLEXITY: usize = 30;
+
+pub(super) async fn load_trainer(ctx: &Context<'_>, account_id: AccountId) -> Result<Option<Trainer>> {
+    Ok(ctx
+        .data_unchecked::<DataLoader<TrainerLoader>>()
+        .load_one(account_id)
+        .await?
+        .map(Trainer))
+}
+
+async fn load_circle(ctx: &Context<'_>, circle_id: models::CircleId) -> Result<Option<Circle>> {
+    Ok(ctx
+        .data_unchecked::<DataLoader<CircleLoader>>()
+        .load_one(circle_id)
+        .await?
+        .map(Circle))
+}
+
+async fn load_inheritance(ctx: &Context<'_>, account_id: &AccountId) -> Result<Vec<Inheritance>> {
+    Ok(ctx
+        .data_unchecked::<DataLoader<InheritanceLoader>>()
+        .load_one(account_id.clone())
+        .await?
+        .unwrap_or_default())
+}
+
+pub struct Trainer(pub(super) TrainerRow);
+
+#[Object]
+impl Trainer {
+    async fn account_id(&self) -> &str {
+        self.0.account_id.as_str()
+    }
+
+    /// In-game name, with blocked words masked
+    async fn name(&self) -> String {
+        crate::moderation::mask_text(&self.0.name)
+    }
+
+    async fn follower_num(&self) -> Option<i32> {
+        self.0.follower_num
+    }
+
+    async fn last_updated(&self) -> Option<NaiveDateTime> {
+        self.0.last_updated
+    }
+
+    /// Not updated within the retention window (left out of search by default)
+    async fn stale(&self) -> bool {
+        self.0.stale
+    }
+
+    /// Newest inheritance record
+    async fn inheritance(&self, ctx: &Context<'_>) -> Result<Option<InheritanceRecord>> {
+        Ok(load_inheritance(ctx, &self.0.account_id)
+            .await?
+            .into_iter()
+            .next()
+            .map(InheritanceRecord))
+    }
+
+    /// All inheritance records, newest first
+    #[graphql(complexity = "INHERITANCE_RECORDS_COMPLEXITY * child_complexity")]
+    async fn inheritance_records(&self, ctx: &Context<'_>) -> Result<Vec<InheritanceRecord>> {
+        Ok(load_inheritance(ctx, &self.0.account_id)
+            .await?
+            .into_iter()
+            .map(InheritanceRecord)
+            .collect())
+    }
+
+    /// Support cards, highest experience first
+    #[graphql(complexity = "limit.clamp(1, MAX_SUPPORT_CARDS) as usize * child_complexity")]
+    async fn support_cards(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10)] limit: i32,
+    ) -> Result<Vec<SupportCardRecord>> {
+        Ok(ctx
+            .data_unchecked::<DataLoader<SupportCardLoader>>()
+            .load_one(self.0.account_id.clone())
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .take(limit.clamp(1, MAX_SUPPORT_CARDS) as usize)
+            .map(SupportCardRecord)
+            .collect())
+    }
+
+    /// Circle the trainer was last seen in
+    async fn circle(&self, ctx: &Context<'_>) -> Result<Option<Circle>> {
+        let Some(viewer_id) = self.0.account_id.to_viewer_id() else {
+            return Ok(None);
+        };
+        match ctx
+            .data_unchecked::<DataLoader<ViewerCircleLoader>>()
+            .load_one(viewer_id)
+            .await?
+        {
+            Some(circle_id) => load_circle(ctx, circle_id).await,
+            None => Ok(None),
+        }
+    }
+}
+
+pub struct InheritanceRecord(Inheritance);
+
+#[Object]
+impl InheritanceRecord {
+    async fn inheritance_id(&self) -> i32 {
+        self.0.inheritance_id
+    }
+
+    async fn account_id(&self) -> &str {
+        self.0.account_id.as_str()
+    }
+
+    async fn main_parent_id(&self) -> i32 {
+        self.0.main_parent_id.0
+    }
+
+    async fn main_parent_name(&self) -> String {
+        crate::characters::character_name(self.0.main_parent_id.base())
+    }
+
+    async fn parent_left_id(&self) -> i32 {
+        self.0.parent_left_id.0
+    }
+
+    async fn parent_right_id(&self) -> i32 {
+        self.0.parent_right_id.0
+    }
+
+    async fn parent_rank(&self) -> i32 {
+        self.0.parent_rank
+    }
+
+    async fn parent_rarity(&self) -> i32 {
+        self.0.parent_rarity
+    }
+
+    /// Sparks in the v1 encoding, as in the REST responses
+    async fn blue_sparks(&self) -> &[i32] {
+        &self.0.blue_sparks
+    }
+
+    async fn pink_sparks(&self) -> &[i32] {
+        &self.0.pink_sparks
+    }
+
+    async fn green_sparks(&self) -> &[i32] {
+        &self.0.green_sparks
+    }
+
+    async fn white_sparks(&self) -> &[i32] {
+        &self.0.white_sparks
+    }
+
+    async fn win_count(&self) -> i32 {
+        self.0.win_count
+    }
+
+    async fn white_count(&self) -> i32 {
+        self.0.white_count
+    }
+
+    async fn main_blue_factors(&self) -> i32 {
+        self.0.main_blue_factors
+    }
+
+    async fn main_pink_factors(&self) -> i32 {
+        self.0.main_pink_factors
+    }
+
+    async fn main_green_factors(&self) -> i32 {
+        self.0.main_green_factors
+    }
+
+    async fn main_white_factors(&self) -> &[i32] {
+        &self.0.main_white_factors
+    }
+
+    async fn main_white_count(&self) -> i32 {
+        self.0.main_white_count
+    }
+
+    /// Affinity with the search's player character (search results only)
+    async fn affinity_score(&self) -> Option<i32> {
+        self.0.affinity_score
+    }
+}
+
+pub struct SupportCardRecord(SupportCard);
+
+#[Object]
+impl SupportCardRecord {
+    async fn support_card_id(&self) -> i32 {
+        self.0.support_card_id.0
+    }
+
+    async fn limit_break_count(&self) -> Option<i32> {
+        self.0.limit_break_count
+    }
+
+    async fn experience(&self) -> i32 {
+        self.0.experience
+    }
+
+    /// Card name, if the master data has been imported
+    async fn name(&self) -> Option<String> {
+        crate::support_cards::support_card_meta(self.0.support_card_id).map(|meta| meta.name)
+    }
+
+    /// R, SR or SSR, if the master data has been imported
+    async fn rarity(&self) -> Option<String> {
+        crate::support_cards::support_card_meta(self.0.support_card_id)
+            .map(|meta| crate::support_cards::rarity_label(meta.rarity))
+    }
+
+    /// speed, stamina, power, guts, wit, friend or group
+    async fn card_type(&self) -> Option<String> {
+        crate::support_cards::support_card_meta(self.0.support_card_id).map(|meta| meta.card_type)
+    }
+}
+
+pub struct Circle(pub(super) models::Circle);
+
+#[Object]
+impl Circle {
+    async fn circle_id(&self) -> ID {
+        ID(self.0.circle_id.to_string())
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn comment(&self) -> Option<&str> {
+        self.0.comment.as_deref()
+    }
+
+    async fn leader_name(&self) -> Option<&str> {
+        self.0.leader_name.as_deref()
+    }
+
+    async fn leader(&self, ctx: &Context<'_>) -> Result<Option<Trainer>> {
+        match self.0.leader_viewer_id {
+            Some(viewer_id) => load_trainer(ctx, viewer_id.to_account_id()).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn member_count(&self) -> Option<i32> {
+        self.0.member_count
+    }
+
+    async fn join_style(&self) -> Option<i32> {
+        self.0.join_style
+    }
+
+    async fn policy(&self) -> Option<i32> {
+        self.0.policy
+    }
+
+    async fn created_at(&self) -> Option<NaiveDateTime> {
+        self.0.created_at
+    }
+
+    async fn last_updated(&self) -> Option<NaiveDateTime> {
+        self.0.last_updated
+    }
+
+    async fn monthly_rank(&self) -> Option<i32> {
+        self.0.monthly_rank
+    }
+
+    async fn monthly_point(&self) -> Option<i64> {
+        self.0.monthly_point
+    }
+
+    async fn last_month_rank(&self) -> Option<i32> {
+        self.0.last_month_rank
+    }
+
+    async fn last_month_point(&self) -> Option<i64> {
+        self.0.last_month_point
+    }
+
+    async fn archived(&self) -> Option<bool> {
+        self.0.archived
+    }
+
+    async fn yesterday_rank(&self) -> Option<i32> {
+        self.0.yesterday_rank
+    }
+
+    async fn yesterday_points(&self) -> Option<i64> {
+        self.0.yesterday_points
+    }
+
+    /// Estimated final monthly points from the last 7 days' trend (current month only)
+    async fn projected_point(&self) -> Option<i64> {
+        self.0.projected_point
+    }
+
+    /// Percent of this month's tracked circles the circle out-scores
+    async fn percentile(&self, ctx: &Context<'_>) -> Result<Option<f64>> {
+        ctx.data_unchecked::<AppState>()
+            .storage
+            .circle_percentile(self.0.circle_id)
+            .await
+            .map_err(graphql_error)
+    }
+
+    /// Members and their fan counts for a month (default: the current competition month)
+    #[graphql(
+        complexity = "limit.map_or(CIRCLE_SIZE_COMPLEXITY, |limit| limit.clamp(1, MAX_CIRCLE_MEMBERS) as usize) * child_complexity"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn members(
+        &self,
+        ctx: &Context<'_>,
+        year: Option<i32>,
+        month: Option<i32>,
+        #[graphql(desc = "total_fans, today_fans or name (default: viewer ID)")] sort_by: Option<String>,
+        #[graphql(desc = "asc or desc")] sort_dir: Option<String>,
+        #[graphql(default = 0)] page: i64,
+        #[graphql(desc = "Members per page (default: all, max: 100)")] limit: Option<i32>,
+    ) -> Result<Vec<CircleMember>> {
+        let params = CircleQueryParams {
+            viewer_id: None,
+            circle_id: Some(self.0.circle_id),
+            month,
+            year,
+            sort_by,
+            sort_dir,
+            page: Some(page),
+            limit: limit.map(i64::from),
+            include: None,
+        };
+        let (members, _) = ctx
+            .data_unchecked::<AppState>()
+            .storage
+            .circle_members(self.0.circle_id, &params)
+            .await
+            .map_err(graphql_error)?;
+        Ok(members.into_iter().map(CircleMember).collect())
+    }
+}
+
+pub struct CircleMember(CircleMemberFansMonthly);
+
+#[Object]
+impl CircleMember {
+    async fn viewer_id(&self) -> ID {
+        ID(self.0.viewer_id.to_string())
+    }
+
+    async fn trainer_name(&self) -> Option<&str> {
+        self.0.trainer_name.as_deref()
+    }
+
+    async fn year(&self) -> i32 {
+        self.0.year
+    }
+
+    async fn month(&self) -> i32 {
+        self.0.month
+    }
+
+    /// Cumulative fan count per day of the month
+    async fn daily_fans(&self) -> &[i32] {
+        &self.0.daily_fans
+    }
+
+    /// Latest cumulative fan count this month
+    async fn total_fans(&self) -> i64 {
+        self.0.total_fans
+    }
+
+    /// Fans gained since the previous recorded day
+    async fn today_fans(&self) -> i64 {
+        self.0.today_fans
+    }
+
+    async fn last_updated(&self) -> Option<NaiveDateTime> {
+        self.0.last_updated
+    }
+
+    async fn trainer(&self, ctx: &Context<'_>) -> Result<Option<Trainer>> {
+        load_trainer(ctx, ViewerId::to_account_id(self.0.viewer_id)).await
+    }
+}
+
+/// One page of /api/v3/search results; the count only runs when `total` is selected
+pub struct SearchPage {
+    pub(super) params: UnifiedSearchParams,
+    pub(super) page: i64,
+    pub(super) limit: i64,
+}
+
+#[Object]
+impl SearchPage {
+    async fn page(&self) -> i64 {
+        self.page
+    }
+
+    async fn limit(&self) -> i64 {
+        self.limit
+    }
+
+    /// Number of matching records
+    async fn total(&self, ctx: &Context<'_>) -> Result<i64> {
+        ctx.data_unchecked::<AppState>()
+            .storage
+            .search_count(&self.params)
+            .await
+            .map_err(graphql_error)
+    }
+
+    async fn items(&self, ctx: &Context<'_>) -> Result<Vec<SearchRecord>> {
+        let records = ctx
+            .data_unchecked::<AppState>()
+            .storage
+            .search(&self.params, self.limit, self.page * self.limit)
+            .await
+            .map_err(graphql_error)?;
+        Ok(records.into_iter().map(SearchRecord).collect())
+    }
+}
+
+pub struct SearchRecord(UnifiedAccountRecord);
+
+#[Object]
+impl SearchRecord {
+    async fn account_id(&self) -> &str {
+        self.0.account_id.as_str()
+    }
+
+    async fn trainer_name(&self) -> &str {
+        &self.0.trainer_name
+    }
+
+    async fn follower_num(&self) -> Option<i32> {
+        self.0.follower_num
+    }
+
+    async fn last_updated(&self) -> Option<NaiveDateTime> {
+        self.0.last_updated
+    }
+
+    /// The matching inheritance record
+    async fn inheritance(&self) -> Option<InheritanceRecord> {
+        self.0.inheritance.clone().map(InheritanceRecord)
+    }
+
+    /// The matching (or best) support card
+    async fn support_card(&self) -> Option<SupportCardRecord> {
+        self.0.support_card.clone().map(SupportCardRecord)
+    }
+
+    async fn trainer(&self, ctx: &Context<'_>) -> Result<Option<Trainer>> {
+        load_trainer(ctx, self.0.account_id.clone()).await
+    }
+}
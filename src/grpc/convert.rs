@@ -0,0 +1,161 @@
+//! Conversions between the protobuf messages and the API models the worker
+//! endpoints use
+
+use tonic::Status;
+
+use super::proto;
+use crate::models::{
+    AccountId, BulkCircleMemberRow, BulkIngestResponse, CardId, CharaId, CircleId, CircleIngest,
+    CircleMemberIngest, IngestResponse, IngestRowError, IngestSource, InheritanceIngest,
+    SupportCardIngest, Task, TrainerIngest, ViewerId,
+};
+
+/// The request's source, which every ingest call must carry
+pub fn source(source: Option<proto::IngestSource>) -> Result<IngestSource, Status> {
+    let source = source.ok_or_else(|| Status::invalid_argument("source is required"))?;
+    Ok(IngestSource {
+        worker_id: source.worker_id,
+        server: source.server,
+        batch_id: source.batch_id,
+    })
+}
+
+impl From<Task> for proto::Task {
+    fn from(task: Task) -> Self {
+        proto::Task {
+            id: task.id,
+            task_type: task.task_type,
+            task_data_json: task.task_data.to_string(),
+            priority: task.priority,
+            status: task.status,
+            created_at: task.created_at.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+            account_id: task.account_id.map(|account_id| account_id.0),
+        }
+    }
+}
+
+impl From<proto::Trainer> for TrainerIngest {
+    fn from(trainer: proto::Trainer) -> Self {
+        TrainerIngest {
+            account_id: AccountId(trainer.account_id),
+            name: trainer.name,
+            follower_num: trainer.follower_num,
+            status: trainer.status,
+            comment: trainer.comment,
+        }
+    }
+}
+
+impl From<proto::InheritanceRecord> for InheritanceIngest {
+    fn from(record: proto::InheritanceRecord) -> Self {
+        InheritanceIngest {
+            account_id: AccountId(record.account_id),
+            main_parent_id: CharaId(record.main_parent_id),
+            parent_left_id: CharaId(record.parent_left_id),
+            parent_right_id: CharaId(record.parent_right_id),
+            parent_rank: record.parent_rank,
+            parent_rarity: record.parent_rarity,
+            blue_sparks: record.blue_sparks,
+            pink_sparks: record.pink_sparks,
+            green_sparks: record.green_sparks,
+            white_sparks: record.white_sparks,
+            win_count: record.win_count,
+            white_count: record.white_count,
+            main_blue_factors: record.main_blue_factors,
+            main_pink_factors: record.main_pink_factors,
+            main_green_factors: record.main_green_factors,
+            main_white_factors: record.main_white_factors,
+            main_white_count: record.main_white_count,
+            blue_stars_sum: record.blue_stars_sum,
+            pink_stars_sum: record.pink_stars_sum,
+            green_stars_sum: record.green_stars_sum,
+            white_stars_sum: record.white_stars_sum,
+            // Repeated fields can't be absent; empty means not computed, as a missing JSON field
+            affinity_scores: Some(record.affinity_scores).filter(|scores| !scores.is_empty()),
+            base_affinity: record.base_affinity,
+            race_affinity: record.race_affinity,
+        }
+    }
+}
+
+impl From<proto::SupportCard> for SupportCardIngest {
+    fn from(card: proto::SupportCard) -> Self {
+        SupportCardIngest {
+            account_id: AccountId(card.account_id),
+            support_card_id: CardId(card.support_card_id),
+            limit_break_count: card.limit_break_count,
+            experience: card.experience,
+        }
+    }
+}
+
+impl From<proto::Circle> for CircleIngest {
+    fn from(circle: proto::Circle) -> Self {
+        CircleIngest {
+            circle_id: CircleId(circle.circle_id),
+            name: circle.name,
+            comment: circle.comment,
+            leader_viewer_id: circle.leader_viewer_id.map(ViewerId),
+            member_count: circle.member_count,
+            join_style: circle.join_style,
+            policy: circle.policy,
+            monthly_rank: circle.monthly_rank,
+            monthly_point: circle.monthly_point,
+            last_month_rank: circle.last_month_rank,
+            last_month_point: circle.last_month_point,
+        }
+    }
+}
+
+impl From<proto::CircleMember> for CircleMemberIngest {
+    fn from(member: proto::CircleMember) -> Self {
+        CircleMemberIngest {
+            viewer_id: ViewerId(member.viewer_id),
+            year: member.year,
+            month: member.month,
+            daily_fans: member.daily_fans,
+        }
+    }
+}
+
+impl From<proto::BulkCircleMember> for BulkCircleMemberRow {
+    fn from(member: proto::BulkCircleMember) -> Self {
+        BulkCircleMemberRow {
+            circle_id: CircleId(member.circle_id),
+            viewer_id: ViewerId(member.viewer_id),
+            year: member.year,
+            month: member.month,
+            daily_fans: member.daily_fans,
+        }
+    }
+}
+
+impl From<IngestResponse> for proto::IngestResponse {
+    fn from(response: IngestResponse) -> Self {
+        proto::IngestResponse {
+            inserted: response.inserted,
+            updated: response.updated,
+            skipped: response.skipped,
+        }
+    }
+}
+
+impl From<IngestRowError> for proto::RowError {
+    fn from(error: IngestRowError) -> Self {
+        proto::RowError {
+            index: error.index as u64,
+            error: error.error,
+        }
+    }
+}
+
+impl From<BulkIngestResponse> for proto::BulkIngestResponse {
+    fn from(response: BulkIngestResponse) -> Self {
+        proto::BulkIngestResponse {
+            inserted: response.inserted,
+            updated: response.updated,
+            failed: response.failed as u64,
+            errors: response.errors.into_iter().map(Into::into).collect(),
+        }
+    }
+}
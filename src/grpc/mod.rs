@@ -0,0 +1,488 @@
+//! gRPC worker service (proto/worker.proto), served on GRPC_PORT.
+//!
+//! Same operations as /api/workers and /api/ingest, going through the same
+//! functions, so claims, validation and ingest transactions behave alike on
+//! both transports. StreamTasks additionally pushes tasks to a connected
+//! worker as slots free up instead of having it poll ClaimTasks.
+//!
+//! Calls are authorized like the HTTP worker API: WORKER_ALLOWED_IPS and a
+//! one-time HMAC signature (see `authorize`).
+
+// tonic::Status is the error type of every service method
+#![allow(clippy::result_large_err)]
+
+use futures_util::Stream;
+use sha2::{Digest, Sha256};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tonic::transport::server::TcpIncoming;
+use tonic::{Request, Response, Status};
+use tracing::{error, info, warn};
+use validator::Validate;
+
+use crate::errors::AppError;
+use crate::handlers::{ingest, workers};
+use crate::middleware::worker_auth::{is_allowed_worker_ip, signing_payload, verify_signature};
+use crate::models::{
+    BulkCircleMembersRequest, CircleIngestRequest, ClaimTasksRequest, CompleteTaskRequest,
+    InheritanceIngestRequest, SupportCardIngestRequest, TaskHeartbeatRequest, TrainerIngestRequest,
+};
+use crate::{shutdown, AppState};
+
+mod convert;
+
+pub mod proto {
+    tonic::include_proto!("umamoe.worker.v1");
+}
+
+use proto::worker_service_server::{WorkerService, WorkerServiceServer};
+
+/// Unix seconds the call was signed at
+const TIMESTAMP_METADATA: &str = "x-worker-timestamp";
+/// Random string, unique per call
+const NONCE_METADATA: &str = "x-worker-nonce";
+/// Hex HMAC-SHA256 of "{timestamp}\nPOST\n/{service}/{method}\n{nonce}\n{digest}", where
+/// digest is the hex SHA-256 of the request message's protobuf encoding
+const SIGNATURE_METADATA: &str = "x-worker-signature";
+
+/// Serve the worker service on `listener` until shutdown
+pub async fn serve(listener: TcpListener, state: AppState) {
+    let incoming = match TcpIncoming::from_listener(listener, true, None) {
+        Ok(incoming) => incoming,
+        Err(e) => {
+            error!("❌ gRPC worker service failed to start: {}", e);
+            return;
+        }
+    };
+
+    let result = tonic::transport::Server::builder()
+        .add_service(WorkerServiceServer::new(WorkerGrpc { state }))
+        .serve_with_incoming_shutdown(incoming, shutdown::requested())
+        .await;
+    if let Err(e) = result {
+        error!("❌ gRPC worker service stopped: {}", e);
+    }
+}
+
+/// gRPC status for an AppError, with the same messages as the HTTP responses
+fn status(err: AppError) -> Status {
+    err.log();
+    let message = err.detail();
+    match err.code() {
+        "bad_request" | "validation_failed" | "payload_too_large" => {
+            Status::invalid_argument(message)
+        }
+        "not_found" => Status::not_found(message),
+        "unauthorized" => Status::unauthenticated(message),
+        "forbidden" | "captcha_failed" => Status::permission_denied(message),
+        "conflict" => Status::already_exists(message),
+        "rate_limited" => Status::resource_exhausted(message),
+        "timeout" => Status::deadline_exceeded(message),
+        "service_unavailable" | "database_unavailable" => Status::unavailable(message),
+        _ => Status::internal(message),
+    }
+}
+
+/// Check a call to `method` (e.g. "ClaimTasks") against WORKER_ALLOWED_IPS
+/// and its signature
+///
+/// The signature covers a digest of the message as re-encoded here, which
+/// matches what the worker sent as long as it encodes fields in field-number
+/// order and sends none this server doesn't know (what the generated clients do).
+fn authorize<T: prost::Message>(request: &Request<T>, method: &str) -> Result<(), Status> {
+    let client_ip = request
+        .remote_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_default();
+    if !is_allowed_worker_ip(&client_ip) {
+        warn!("Rejected gRPC worker call from non-allowed IP: {}", client_ip);
+        return Err(Status::permission_denied("Not allowed"));
+    }
+
+    let Some(secret) = crate::config::get().worker_signing_secret.as_deref() else {
+        error!("WORKER_SIGNING_SECRET not set - worker endpoints disabled");
+        return Err(Status::unavailable("Worker API disabled"));
+    };
+
+    let metadata = |name: &str| {
+        request
+            .metadata()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+    };
+    let (Some(timestamp), Some(nonce), Some(signature)) = (
+        metadata(TIMESTAMP_METADATA),
+        metadata(NONCE_METADATA),
+        metadata(SIGNATURE_METADATA),
+    ) else {
+        return Err(Status::unauthenticated("Missing worker signature"));
+    };
+    if nonce.is_empty() {
+        return Err(Status::unauthenticated("Missing worker signature"));
+    }
+
+    let method = format!("/{}/{}", proto::worker_service_server::SERVICE_NAME, method);
+    let digest = hex::encode(Sha256::digest(request.get_ref().encode_to_vec()));
+    let body = format!("{}\n{}", nonce, digest);
+
+    verify_signature(
+        secret,
+        timestamp,
+        signature,
+        &signing_payload(timestamp, "POST", &method, body.as_bytes()),
+        &client_ip,
+    )
+    .map_err(status)
+}
+
+fn task_types(task_types: Vec<String>) -> Option<Vec<String>> {
+    Some(task_types).filter(|types| !types.is_empty())
+}
+
+struct WorkerGrpc {
+    state: AppState,
+}
+
+type TaskStream = Pin<Box<dyn Stream<Item = Result<proto::Task, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl WorkerService for WorkerGrpc {
+    async fn claim_tasks(
+        &self,
+        request: Request<proto::ClaimTasksRequest>,
+    ) -> Result<Response<proto::ClaimTasksResponse>, Status> {
+        authorize(&request, "ClaimTasks")?;
+        let request = request.into_inner();
+        let tasks = workers::claim(
+            &self.state,
+            &ClaimTasksRequest {
+                worker_id: request.worker_id,
+                task_types: task_types(request.task_types),
+                limit: request.limit,
+            },
+        )
+        .await
+        .map_err(status)?;
+
+        Ok(Response::new(proto::ClaimTasksResponse {
+            tasks: tasks.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    type StreamTasksStream = TaskStream;
+
+    async fn stream_tasks(
+        &self,
+        request: Request<proto::StreamTasksRequest>,
+    ) -> Result<Response<TaskStream>, Status> {
+        authorize(&request, "StreamTasks")?;
+        let request = request.into_inner();
+        let claim = ClaimTasksRequest {
+            worker_id: request.worker_id,
+            task_types: task_types(request.task_types),
+            limit: Some(i64::from(request.max_in_flight.max(1))),
+        };
+        claim.validate().map_err(|e| status(e.into()))?;
+        if shutdown::is_requested() {
+            return Err(Status::unavailable("Server is shutting down"));
+        }
+
+        let (sender, receiver) = mpsc::channel(1);
+        tokio::spawn(stream_tasks(self.state.clone(), claim, sender));
+
+        let stream = futures_util::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|item| (item, receiver))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn complete_task(
+        &self,
+        request: Request<proto::CompleteTaskRequest>,
+    ) -> Result<Response<proto::CompleteTaskResponse>, Status> {
+        authorize(&request, "CompleteTask")?;
+        let request = request.into_inner();
+        let status = workers::complete(
+            &self.state,
+            request.task_id,
+            &CompleteTaskRequest {
+                worker_id: request.worker_id,
+                success: request.success,
+                error_message: request.error_message,
+            },
+        )
+        .await
+        .map_err(status)?;
+
+        Ok(Response::new(proto::CompleteTaskResponse {
+            task_id: request.task_id,
+            status: status.to_string(),
+        }))
+    }
+
+    async fn heartbeat(
+        &self,
+        request: Request<proto::HeartbeatRequest>,
+    ) -> Result<Response<proto::HeartbeatResponse>, Status> {
+        authorize(&request, "Heartbeat")?;
+        let request = request.into_inner();
+        workers::heartbeat(
+            &self.state,
+            request.task_id,
+            &TaskHeartbeatRequest {
+                worker_id: request.worker_id,
+            },
+        )
+        .await
+        .map_err(status)?;
+
+        Ok(Response::new(proto::HeartbeatResponse {
+            task_id: request.task_id,
+        }))
+    }
+
+    async fn ingest_trainers(
+        &self,
+        request: Request<proto::IngestTrainersRequest>,
+    ) -> Result<Response<proto::IngestResponse>, Status> {
+        authorize(&request, "IngestTrainers")?;
+        let request = request.into_inner();
+        let payload = TrainerIngestRequest {
+            source: convert::source(request.source)?,
+            trainers: request.trainers.into_iter().map(Into::into).collect(),
+        };
+        let response = ingest::store_trainers(&self.state, &payload)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(response.into()))
+    }
+
+    async fn ingest_inheritance(
+        &self,
+        request: Request<proto::IngestInheritanceRequest>,
+    ) -> Result<Response<proto::IngestResponse>, Status> {
+        authorize(&request, "IngestInheritance")?;
+        let request = request.into_inner();
+        let payload = InheritanceIngestRequest {
+            source: convert::source(request.source)?,
+            records: request.records.into_iter().map(Into::into).collect(),
+        };
+        let response = ingest::store_inheritance(&self.state, &payload)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(response.into()))
+    }
+
+    async fn ingest_support_cards(
+        &self,
+        request: Request<proto::IngestSupportCardsRequest>,
+    ) -> Result<Response<proto::IngestResponse>, Status> {
+        authorize(&request, "IngestSupportCards")?;
+        let request = request.into_inner();
+        let payload = SupportCardIngestRequest {
+            source: convert::source(request.source)?,
+            cards: request.cards.into_iter().map(Into::into).collect(),
+        };
+        let response = ingest::store_support_cards(&self.state, &payload)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(response.into()))
+    }
+
+    async fn ingest_circle(
+        &self,
+        request: Request<proto::IngestCircleRequest>,
+    ) -> Result<Response<proto::IngestResponse>, Status> {
+        authorize(&request, "IngestCircle")?;
+        let request = request.into_inner();
+        let circle = request
+            .circle
+            .ok_or_else(|| Status::invalid_argument("circle is required"))?;
+        let payload = CircleIngestRequest {
+            source: convert::source(request.source)?,
+            circle: circle.into(),
+            members: request.members.into_iter().map(Into::into).collect(),
+        };
+        let response = ingest::store_circle(&self.state, &payload)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(response.into()))
+    }
+
+    async fn ingest_circle_members(
+        &self,
+        request: Request<proto::IngestCircleMembersRequest>,
+    ) -> Result<Response<proto::BulkIngestResponse>, Status> {
+        authorize(&request, "IngestCircleMembers")?;
+        let request = request.into_inner();
+        let payload = BulkCircleMembersRequest {
+            source: convert::source(request.source)?,
+            members: request.members.into_iter().map(Into::into).collect(),
+        };
+        let response = ingest::store_circle_members_bulk(&self.state, &payload)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(response.into()))
+    }
+}
+
+/// Push tasks to a StreamTasks caller until it disconnects or the server stops
+///
+/// Every GRPC_TASK_POLL_INTERVAL_MS the worker's claimed/processing tasks are
+/// counted (including ones claimed over HTTP) and the free slots up to
+/// `claim.limit` are claimed for it. A task that can no longer be delivered
+/// goes back to pending right away instead of waiting for the lease to expire.
+async fn stream_tasks(
+    state: AppState,
+    mut claim: ClaimTasksRequest,
+    sender: mpsc::Sender<Result<proto::Task, Status>>,
+) {
+    let max_in_flight = claim.limit.unwrap_or(1);
+    let mut interval = tokio::time::interval(crate::config::get().grpc_task_poll_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    info!("👷 Worker {} connected to the task stream", claim.worker_id);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = sender.closed() => break,
+            _ = shutdown::requested() => break,
+        }
+
+        let held = match sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM tasks WHERE worker_id = $1 AND status IN ('claimed', 'processing')",
+        )
+        .bind(&claim.worker_id)
+        .fetch_one(&state.db)
+        .await
+        {
+            Ok(held) => held,
+            Err(e) => {
+                let _ = sender.send(Err(status(e.into()))).await;
+                break;
+            }
+        };
+        if held >= max_in_flight {
+            continue;
+        }
+
+        claim.limit = Some(max_in_flight - held);
+        let tasks = match workers::claim(&state, &claim).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                let _ = sender.send(Err(status(e))).await;
+                break;
+            }
+        };
+
+        let mut tasks = tasks.into_iter();
+        while let Some(task) = tasks.next() {
+            let task_id = task.id;
+            if sender.send(Ok(task.into())).await.is_err() {
+                let undelivered = std::iter::once(task_id)
+                    .chain(tasks.map(|task| task.id))
+                    .collect();
+                release_tasks(&state, &claim.worker_id, undelivered).await;
+                break;
+            }
+        }
+    }
+
+    info!("👷 Worker {} left the task stream", claim.worker_id);
+}
+
+/// Put tasks claimed for a worker that never received them back to pending
+async fn release_tasks(state: &AppState, worker_id: &str, task_ids: Vec<i32>) {
+    let result = sqlx::query(
+        r#"
+        UPDATE tasks
+        SET status = 'pending', worker_id = NULL, claimed_at = NULL, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ANY($1) AND worker_id = $2 AND status = 'claimed'
+        "#,
+    )
+    .bind(&task_ids)
+    .bind(worker_id)
+    .execute(&state.db)
+    .await;
+
+    match result {
+        Ok(result) => warn!(
+            "👷 Released {} task(s) worker {} disconnected before receiving",
+            result.rows_affected(),
+            worker_id
+        ),
+        // The lease runs out eventually and the reaper requeues them
+        Err(e) => error!("Failed to release tasks {:?} of worker {}: {}", task_ids, worker_id, e),
+    }
+}
+
+/// Bind GRPC_PORT on HOST, failing startup like the HTTP listener when it's taken
+pub async fn bind(host: &str, port: u16) -> anyhow::Result<TcpListener> {
+    let listener = TcpListener::bind((host, port))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind gRPC {}:{}: {}", host, port, e))?;
+    let addr: SocketAddr = listener.local_addr()?;
+    info!("🛰️ gRPC worker service starting on {}", addr);
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hmac::{Hmac, Mac};
+    use prost::Message;
+
+    fn signed(message: &proto::ClaimTasksRequest, nonce: &str) -> Request<proto::ClaimTasksRequest> {
+        crate::config::init_for_tests();
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        let digest = hex::encode(Sha256::digest(message.encode_to_vec()));
+        let payload = signing_payload(
+            &timestamp,
+            "POST",
+            "/umamoe.worker.v1.WorkerService/ClaimTasks",
+            format!("{}\n{}", nonce, digest).as_bytes(),
+        );
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"test-secret").unwrap();
+        mac.update(&payload);
+
+        let mut request = Request::new(message.clone());
+        let metadata = request.metadata_mut();
+        metadata.insert(TIMESTAMP_METADATA, timestamp.parse().unwrap());
+        metadata.insert(NONCE_METADATA, nonce.parse().unwrap());
+        metadata.insert(
+            SIGNATURE_METADATA,
+            hex::encode(mac.finalize().into_bytes()).parse().unwrap(),
+        );
+        request
+    }
+
+    fn claim(worker_id: &str) -> proto::ClaimTasksRequest {
+        proto::ClaimTasksRequest {
+            worker_id: worker_id.to_string(),
+            task_types: vec!["trainer".to_string()],
+            limit: Some(5),
+        }
+    }
+
+    #[test]
+    fn accepts_signed_message() {
+        let request = signed(&claim("worker-1"), "nonce-accept");
+        assert!(authorize(&request, "ClaimTasks").is_ok());
+    }
+
+    #[test]
+    fn rejects_altered_message() {
+        let mut request = signed(&claim("worker-1"), "nonce-altered");
+        request.get_mut().limit = Some(50);
+        assert!(authorize(&request, "ClaimTasks").is_err());
+    }
+
+    #[test]
+    fn rejects_signature_for_another_method() {
+        let request = signed(&claim("worker-1"), "nonce-method");
+        assert!(authorize(&request, "Heartbeat").is_err());
+    }
+}
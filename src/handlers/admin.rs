@@ -0,0 +1,1907 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap},
+    response::Json,
+    routing::{delete, get, post, put},
+    Router,
+};
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    errors::AppError,
+    middleware::api_key::{self, ApiKeyScope},
+    models::{
+        AccountId, AdminAuditEntry, AdminAuditLogParams, Announcement, AnnouncementRequest, ApiKey,
+        ApiKeyCreateRequest, ApiKeyCreateResponse, BanCreateRequest, BanListParams, BannedClient,
+        BatchProvenanceSummary, CacheInvalidateParams, CacheInvalidateResponse, CacheKeyStats,
+        CacheKeysParams, CacheStatsResponse, CharacterNameReport, CircleAwardsParams, CircleId,
+        CircleModerationOverride, CircleModerationRequest, ConnectionStats, ContentReportKind,
+        DbStatsResponse, GameDataImportResult, IndexStats, IngestRollbackRequest,
+        IngestRollbackResponse, ModerationAction, ModerationQueueItem, ModerationQueueParams,
+        ModerationResolveRequest, NotabilityRule, NotabilityRuleRequest, RetentionRunResult,
+        RetentionStatus, SlowQueryStats, SparkBackfillRequest, SparkBackfillResponse,
+        SparkCompareRequest, SparkCompareResponse, SparkEncodingRequest, SparkEncodingStatus,
+        SparkEncodingStatusParams, TableStats, TaskArchiveResult, TaskReapResult,
+        TaskRequeueRequest, TaskRequeueResponse, TaskTypePolicy, TaskTypePolicyRequest,
+        TrainerModerationOverride, TrainerModerationRequest, TrainerProvenance, TurnstileStats,
+        ViewRefreshParams, ViewRefreshResponse, WorkerFleetSettings, WorkerFleetSettingsRequest,
+    },
+    sparks::SparkEncoding,
+    AppState,
+};
+
+// Tasks re-queued per UPDATE so a large requeue doesn't hold row locks for long
+const REQUEUE_BATCH_SIZE: i64 = 100;
+
+// Inheritance rows whose v2 spark columns still need to be filled from v1
+const SPARK_V2_PENDING_FILTER: &str = r#"
+    (blue_sparks_v2 IS NULL AND blue_sparks IS NOT NULL)
+    OR (pink_sparks_v2 IS NULL AND pink_sparks IS NOT NULL)
+    OR (green_sparks_v2 IS NULL AND green_sparks IS NOT NULL)
+    OR (white_sparks_v2 IS NULL AND white_sparks IS NOT NULL)
+    OR (main_white_factors_v2 IS NULL AND main_white_factors IS NOT NULL)
+    OR (main_blue_factors_v2 IS NULL AND main_blue_factors IS NOT NULL)
+    OR (main_pink_factors_v2 IS NULL AND main_pink_factors IS NOT NULL)
+    OR (main_green_factors_v2 IS NULL AND main_green_factors IS NOT NULL)
+"#;
+
+// Rows whose v2 spark columns disagree with their v1 columns
+const SPARK_V2_MISMATCH_FILTER: &str = r#"
+    blue_sparks_v2 IS DISTINCT FROM spark_v1_to_v2(blue_sparks)
+    OR pink_sparks_v2 IS DISTINCT FROM spark_v1_to_v2(pink_sparks)
+    OR green_sparks_v2 IS DISTINCT FROM spark_v1_to_v2(green_sparks)
+    OR white_sparks_v2 IS DISTINCT FROM spark_v1_to_v2(white_sparks)
+    OR main_white_factors_v2 IS DISTINCT FROM spark_v1_to_v2(main_white_factors)
+    OR main_blue_factors_v2 IS DISTINCT FROM spark_v1_to_v2(main_blue_factors)
+    OR main_pink_factors_v2 IS DISTINCT FROM spark_v1_to_v2(main_pink_factors)
+    OR main_green_factors_v2 IS DISTINCT FROM spark_v1_to_v2(main_green_factors)
+"#;
+
+/// Admin routes - mounted under /api/admin behind the admin token middleware;
+/// every non-GET request is also recorded by the audit middleware (see src/server.rs)
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/circles/:circle_id/moderation",
+            put(set_circle_moderation)
+                .get(get_circle_moderation)
+                .delete(delete_circle_moderation),
+        )
+        .route(
+            "/trainers/:account_id/moderation",
+            put(set_trainer_moderation)
+                .get(get_trainer_moderation)
+                .delete(delete_trainer_moderation),
+        )
+        .route("/moderation/queue", get(get_moderation_queue))
+        .route("/moderation/resolve", post(resolve_moderation_reports))
+        .route("/moderation/reload", post(reload_moderation_wordlist))
+        .route("/circle-awards/compute", post(compute_circle_awards))
+        .route(
+            "/support-card-leaderboard/compute",
+            post(compute_support_card_leaderboard),
+        )
+        .route("/leaderboards/compute", post(compute_leaderboards))
+        .route("/character-names", get(get_character_name_report))
+        .route("/character-names/reload", post(reload_character_names))
+        .route("/game-data/:kind", post(import_game_data))
+        .route("/tasks/reap", post(reap_stale_tasks))
+        .route("/refresh-views", post(refresh_views))
+        .route("/db-stats", get(get_db_stats))
+        .route("/cache", get(get_cache_stats).delete(invalidate_cache))
+        .route("/audit-log", get(get_audit_log))
+        .route("/turnstile", get(get_turnstile_stats))
+        .route("/api-keys", get(list_api_keys).post(create_api_key))
+        .route("/api-keys/:key_id", delete(revoke_api_key))
+        .route("/bans", get(list_bans).post(create_ban))
+        .route("/bans/:ban_id", delete(remove_ban))
+        .route("/tasks/archive", post(archive_completed_tasks))
+        .route("/retention", get(get_retention_status))
+        .route("/retention/run", post(run_retention))
+        .route("/tasks/requeue", post(requeue_failed_tasks))
+        .route("/workers/settings", put(update_worker_settings))
+        .route("/trainers/:account_id/provenance", get(get_trainer_provenance))
+        .route("/provenance/batches/:batch_id", get(get_batch_provenance))
+        .route("/ingest/rollback", post(rollback_ingest_batch))
+        .route(
+            "/task-policies",
+            get(list_task_policies).put(upsert_task_policy),
+        )
+        .route("/sparks/status", get(get_spark_encoding_status))
+        .route("/sparks/backfill", post(backfill_spark_encoding))
+        .route("/sparks/compare", post(compare_spark_encodings))
+        .route("/sparks/encoding", put(set_spark_encoding))
+        .route(
+            "/notability-rules",
+            get(list_notability_rules).put(upsert_notability_rule),
+        )
+        .route(
+            "/announcements",
+            get(list_all_announcements).post(create_announcement),
+        )
+        .route(
+            "/announcements/:announcement_id",
+            put(update_announcement).delete(delete_announcement),
+        )
+        .layer(axum::middleware::from_fn(
+            crate::middleware::admin_auth_middleware,
+        ))
+}
+
+/// Get the moderation override for a circle
+async fn get_circle_moderation(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+) -> Result<Json<CircleModerationOverride>, AppError> {
+    let record = sqlx::query_as::<_, CircleModerationOverride>(
+        r#"
+        SELECT circle_id, name_override, comment_override, hide_comment, updated_at
+        FROM circle_moderation_overrides
+        WHERE circle_id = $1
+        "#,
+    )
+    .bind(circle_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("No moderation override for circle {}", circle_id)))?;
+
+    Ok(Json(record))
+}
+
+/// Create or replace the moderation override for a circle
+async fn set_circle_moderation(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    Json(payload): Json<CircleModerationRequest>,
+) -> Result<Json<CircleModerationOverride>, AppError> {
+    if payload.name_override.is_none() && payload.comment_override.is_none() && !payload.hide_comment {
+        return Err(AppError::BadRequest(
+            "Provide name_override, comment_override or hide_comment".to_string(),
+        ));
+    }
+
+    let record = sqlx::query_as::<_, CircleModerationOverride>(
+        r#"
+        INSERT INTO circle_moderation_overrides (circle_id, name_override, comment_override, hide_comment, updated_at)
+        VALUES ($1, $2, $3, $4, CURRENT_TIMESTAMP)
+        ON CONFLICT (circle_id) DO UPDATE SET
+            name_override = EXCLUDED.name_override,
+            comment_override = EXCLUDED.comment_override,
+            hide_comment = EXCLUDED.hide_comment,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING circle_id, name_override, comment_override, hide_comment, updated_at
+        "#,
+    )
+    .bind(circle_id)
+    .bind(payload.name_override)
+    .bind(payload.comment_override)
+    .bind(payload.hide_comment)
+    .fetch_one(&state.db)
+    .await?;
+
+    crate::moderation::invalidate_displayed_text();
+
+    tracing::warn!("🛡️ Admin set moderation override for circle {}", circle_id);
+
+    Ok(Json(record))
+}
+
+/// Remove the moderation override for a circle
+async fn delete_circle_moderation(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let result = sqlx::query("DELETE FROM circle_moderation_overrides WHERE circle_id = $1")
+        .bind(circle_id)
+        .execute(&state.db)
+        .await?;
+
+    crate::moderation::invalidate_displayed_text();
+
+    tracing::warn!("🛡️ Admin removed moderation override for circle {}", circle_id);
+
+    Ok(Json(json!({
+        "success": true,
+        "deleted": result.rows_affected() > 0
+    })))
+}
+
+/// Get a trainer's name and its display override
+async fn get_trainer_moderation(
+    State(state): State<AppState>,
+    Path(account_id): Path<String>,
+) -> Result<Json<TrainerModerationOverride>, AppError> {
+    let record = sqlx::query_as::<_, TrainerModerationOverride>(
+        "SELECT account_id, name, display_name_override FROM trainer WHERE account_id = $1",
+    )
+    .bind(&account_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("No trainer with ID {}", account_id)))?;
+
+    Ok(Json(record))
+}
+
+/// Set the name shown for a trainer instead of their in-game name
+async fn set_trainer_moderation(
+    State(state): State<AppState>,
+    Path(account_id): Path<String>,
+    Json(payload): Json<TrainerModerationRequest>,
+) -> Result<Json<TrainerModerationOverride>, AppError> {
+    payload.validate()?;
+
+    let record = sqlx::query_as::<_, TrainerModerationOverride>(
+        r#"
+        UPDATE trainer SET display_name_override = $2
+        WHERE account_id = $1
+        RETURNING account_id, name, display_name_override
+        "#,
+    )
+    .bind(&account_id)
+    .bind(&payload.display_name_override)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("No trainer with ID {}", account_id)))?;
+    crate::moderation::invalidate_displayed_text();
+
+    tracing::warn!("🛡️ Admin set display name override for trainer {}", account_id);
+
+    Ok(Json(record))
+}
+
+/// Show a trainer's in-game name again
+async fn delete_trainer_moderation(
+    State(state): State<AppState>,
+    Path(account_id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let result = sqlx::query(
+        r#"
+        UPDATE trainer SET display_name_override = NULL
+        WHERE account_id = $1 AND display_name_override IS NOT NULL
+        "#,
+    )
+    .bind(&account_id)
+    .execute(&state.db)
+    .await?;
+    crate::moderation::invalidate_displayed_text();
+
+    tracing::warn!("🛡️ Admin removed display name override for trainer {}", account_id);
+
+    Ok(Json(json!({
+        "success": true,
+        "deleted": result.rows_affected() > 0
+    })))
+}
+
+/// Reported texts, most reported first
+async fn get_moderation_queue(
+    State(state): State<AppState>,
+    Query(params): Query<ModerationQueueParams>,
+) -> Result<Json<Vec<ModerationQueueItem>>, AppError> {
+    let status = params.status.as_deref().unwrap_or("open");
+    if !matches!(status, "open" | "actioned" | "dismissed") {
+        return Err(AppError::BadRequest(format!(
+            "Invalid status '{}' (expected open, actioned or dismissed)",
+            status
+        )));
+    }
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let offset = params.page.unwrap_or(0).max(0) * limit;
+
+    let items = sqlx::query_as::<_, ModerationQueueItem>(
+        r#"
+        SELECT
+            r.target_kind,
+            r.target_id,
+            CASE r.target_kind
+                WHEN 'trainer_name' THEN t.name
+                WHEN 'circle_name' THEN c.name
+                ELSE c.comment
+            END AS current_text,
+            CASE r.target_kind
+                WHEN 'trainer_name' THEN t.display_name_override
+                WHEN 'circle_name' THEN mo.name_override
+                ELSE CASE WHEN mo.hide_comment THEN '' ELSE mo.comment_override END
+            END AS override_text,
+            COUNT(*) AS reports,
+            ARRAY_REMOVE(ARRAY_AGG(r.reason ORDER BY r.created_at DESC), NULL) AS reasons,
+            MIN(r.created_at) AS first_reported_at,
+            MAX(r.created_at) AS last_reported_at
+        FROM content_reports r
+        LEFT JOIN trainer t
+            ON r.target_kind = 'trainer_name' AND t.account_id = r.target_id
+        LEFT JOIN circles c
+            ON r.target_kind <> 'trainer_name' AND c.circle_id::text = r.target_id
+        LEFT JOIN circle_moderation_overrides mo ON mo.circle_id = c.circle_id
+        WHERE r.status = $1
+        GROUP BY r.target_kind, r.target_id, t.name, t.display_name_override, c.name, c.comment,
+                 mo.name_override, mo.comment_override, mo.hide_comment
+        ORDER BY COUNT(*) DESC, MIN(r.created_at), r.target_kind, r.target_id
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(status)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(items))
+}
+
+/// Close the open reports on a target, masking the text or leaving it as is
+///
+/// Masking a trainer name sets its display_name_override, a circle name or
+/// comment the circle's moderation override. Without a replacement names are
+/// shown as asterisks and comments are hidden.
+async fn resolve_moderation_reports(
+    State(state): State<AppState>,
+    Json(payload): Json<ModerationResolveRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    payload.validate()?;
+    let target_id = payload.target_id.trim();
+    let not_found = || {
+        AppError::NotFound(format!(
+            "No {} to mask for {}",
+            payload.target_kind.as_str(),
+            target_id
+        ))
+    };
+
+    let mut tx = state.db.begin().await?;
+    if payload.action == ModerationAction::Mask {
+        let masked = match payload.target_kind {
+            ContentReportKind::TrainerName => sqlx::query(
+                r#"
+                UPDATE trainer SET display_name_override = COALESCE($2, repeat('*', char_length(name)))
+                WHERE account_id = $1
+                "#,
+            )
+            .bind(target_id)
+            .bind(&payload.replacement)
+            .execute(&mut *tx)
+            .await?,
+            ContentReportKind::CircleName => sqlx::query(
+                r#"
+                INSERT INTO circle_moderation_overrides (circle_id, name_override, updated_at)
+                SELECT circle_id, COALESCE($2, repeat('*', char_length(name))), CURRENT_TIMESTAMP
+                FROM circles
+                WHERE circle_id::text = $1
+                ON CONFLICT (circle_id) DO UPDATE SET
+                    name_override = EXCLUDED.name_override,
+                    updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(target_id)
+            .bind(&payload.replacement)
+            .execute(&mut *tx)
+            .await?,
+            ContentReportKind::CircleComment => sqlx::query(
+                r#"
+                INSERT INTO circle_moderation_overrides (circle_id, comment_override, hide_comment, updated_at)
+                SELECT circle_id, $2, $2::text IS NULL, CURRENT_TIMESTAMP
+                FROM circles
+                WHERE circle_id::text = $1
+                ON CONFLICT (circle_id) DO UPDATE SET
+                    comment_override = EXCLUDED.comment_override,
+                    hide_comment = EXCLUDED.hide_comment,
+                    updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(target_id)
+            .bind(&payload.replacement)
+            .execute(&mut *tx)
+            .await?,
+        };
+        if masked.rows_affected() == 0 {
+            return Err(not_found());
+        }
+    }
+
+    let resolved = sqlx::query(
+        r#"
+        UPDATE content_reports SET status = $3, resolved_at = CURRENT_TIMESTAMP
+        WHERE target_kind = $1 AND target_id = $2 AND status = 'open'
+        "#,
+    )
+    .bind(payload.target_kind.as_str())
+    .bind(target_id)
+    .bind(match payload.action {
+        ModerationAction::Mask => "actioned",
+        ModerationAction::Dismiss => "dismissed",
+    })
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+
+    if payload.action == ModerationAction::Mask {
+        crate::moderation::invalidate_displayed_text();
+    }
+    tracing::warn!(
+        "🛡️ Admin resolved {} reports on {} {} ({:?})",
+        resolved,
+        payload.target_kind.as_str(),
+        target_id,
+        payload.action
+    );
+
+    Ok(Json(json!({
+        "success": true,
+        "resolved": resolved
+    })))
+}
+
+/// Re-read the moderation wordlist file
+async fn reload_moderation_wordlist() -> Json<serde_json::Value> {
+    let word_count = crate::moderation::load_wordlist();
+
+    Json(json!({
+        "success": true,
+        "word_count": word_count
+    }))
+}
+
+/// Recompute circle awards for a month (default: last month), e.g. after late member data
+async fn compute_circle_awards(
+    State(state): State<AppState>,
+    Query(params): Query<CircleAwardsParams>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (year, month) = match (params.year, params.month) {
+        (Some(year), Some(month)) if (1..=12).contains(&month) => (year, month),
+        (None, None) => crate::competition::previous_month(),
+        _ => {
+            return Err(AppError::BadRequest(
+                "year and month (1-12) must be given together".to_string(),
+            ))
+        }
+    };
+
+    let awards = crate::handlers::circles::compute_circle_awards(&state.db, year, month).await?;
+
+    tracing::warn!("🏆 Admin recomputed {} circle awards for {}-{:02}", awards, year, month);
+
+    Ok(Json(json!({
+        "success": true,
+        "year": year,
+        "month": month,
+        "awards": awards
+    })))
+}
+
+/// Retake this week's support card leaderboard snapshot, e.g. after a large scrape
+async fn compute_support_card_leaderboard(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let week_start = crate::competition::current_week_start();
+    let cards =
+        crate::handlers::leaderboards::compute_support_card_leaderboard(&state.db, week_start)
+            .await?;
+
+    tracing::warn!(
+        "🃏 Admin recomputed the support card leaderboard for the week of {} ({} cards)",
+        week_start, cards
+    );
+
+    Ok(Json(json!({
+        "success": true,
+        "week_start": week_start,
+        "cards": cards
+    })))
+}
+
+/// Retake today's global leaderboard snapshots
+async fn compute_leaderboards(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let today = crate::competition::now().date_naive();
+    let entries = crate::handlers::leaderboards::compute_leaderboards(&state.db, today).await?;
+
+    tracing::warn!("🥇 Admin recomputed the leaderboards for {} ({} entries)", today, entries);
+
+    Ok(Json(json!({
+        "success": true,
+        "snapshot_date": today,
+        "entries": entries
+    })))
+}
+
+/// Character name sources and IDs that fell through to the placeholder name
+async fn get_character_name_report() -> Json<CharacterNameReport> {
+    Json(crate::characters::report())
+}
+
+/// Re-read the character_names table (e.g. after adding characters from a game update)
+async fn reload_character_names(State(state): State<AppState>) -> Json<CharacterNameReport> {
+    crate::characters::load_character_names(&state.db).await;
+
+    Json(crate::characters::report())
+}
+
+/// Import game master data (kind: characters or support-cards)
+///
+/// The body is a JSON array, or CSV with a header row when sent as text/csv.
+/// Characters: chara_id, name. Support cards: support_card_id, name, rarity
+/// (1-3), card_type, chara_id (optional). Rows with an existing ID are replaced.
+async fn import_game_data(
+    State(state): State<AppState>,
+    Path(kind): Path<String>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<GameDataImportResult>, AppError> {
+    let kind = crate::game_data::GameDataKind::parse(&kind).ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Unknown game data kind '{}' (expected characters or support-cards)",
+            kind
+        ))
+    })?;
+
+    let is_csv = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/csv"));
+    let format = if is_csv {
+        crate::game_data::GameDataFormat::Csv
+    } else {
+        crate::game_data::GameDataFormat::Json
+    };
+
+    let result = crate::game_data::import(&state.db, kind, &body, format).await?;
+    tracing::warn!(
+        "📥 Admin imported {} {} rows from game data",
+        result.imported,
+        result.kind
+    );
+
+    Ok(Json(result))
+}
+
+/// Trainer retention policy, how many trainers are flagged stale and what the job has done
+async fn get_retention_status(
+    State(state): State<AppState>,
+) -> Result<Json<RetentionStatus>, AppError> {
+    Ok(Json(crate::retention::status(&state.db).await?))
+}
+
+/// Apply the trainer retention policy now instead of waiting for the background job
+async fn run_retention(State(state): State<AppState>) -> Result<Json<RetentionRunResult>, AppError> {
+    let result = crate::retention::run(&state.db).await?;
+
+    tracing::warn!(
+        "🧊 Admin retention run: {} flagged stale, {} restored",
+        result.flagged,
+        result.restored
+    );
+
+    Ok(Json(result))
+}
+
+/// Run the stale task sweep now instead of waiting for the background job
+async fn reap_stale_tasks(State(state): State<AppState>) -> Result<Json<TaskReapResult>, AppError> {
+    let result = crate::handlers::tasks::reap_stale_tasks(&state.db).await?;
+
+    tracing::warn!(
+        "🧟 Admin stale task sweep: {} requeued, {} failed",
+        result.requeued,
+        result.failed
+    );
+
+    Ok(Json(result))
+}
+
+/// Refresh materialized views now instead of waiting for the scheduler
+///
+/// Parameters:
+/// - view: Only refresh this view (default: all). Failures are reported per view.
+async fn refresh_views(
+    State(state): State<AppState>,
+    Query(params): Query<ViewRefreshParams>,
+) -> Result<Json<ViewRefreshResponse>, AppError> {
+    let targets: Vec<_> = match params.view.as_deref() {
+        Some(name) => vec![crate::views::find(name)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown materialized view: {}", name)))?],
+        None => crate::views::MATERIALIZED_VIEWS.iter().collect(),
+    };
+
+    let mut views = Vec::with_capacity(targets.len());
+    for view in targets {
+        let started = std::time::Instant::now();
+        match view.refresh(&state.db).await {
+            Ok(()) => tracing::warn!(
+                "🔄 Admin refreshed {} in {}ms",
+                view.name,
+                started.elapsed().as_millis()
+            ),
+            Err(e) => tracing::warn!("⚠️ Admin refresh of {} failed: {}", view.name, e),
+        }
+        views.push(view.status());
+    }
+
+    Ok(Json(ViewRefreshResponse { views }))
+}
+
+/// Entry count, size, hit/miss counters and the largest keys of the in-process cache
+///
+/// Parameters:
+/// - limit: Number of top keys to return (default 20)
+async fn get_cache_stats(
+    Query(params): Query<CacheKeysParams>,
+) -> Result<Json<CacheStatsResponse>, AppError> {
+    params.validate()?;
+
+    let stats = crate::cache::stats();
+    let served = stats.hits + stats.stale_hits;
+    let lookups = served + stats.misses;
+    let top_keys = crate::cache::largest_entries(params.limit.unwrap_or(20))
+        .into_iter()
+        .map(|(key, size_bytes, expires_in)| CacheKeyStats {
+            key,
+            size_bytes,
+            expires_in_secs: expires_in.as_secs(),
+        })
+        .collect();
+
+    Ok(Json(CacheStatsResponse {
+        entry_count: stats.entry_count,
+        total_size_bytes: stats.total_size_bytes,
+        expired_count: stats.expired_count,
+        hits: stats.hits,
+        misses: stats.misses,
+        sets: stats.sets,
+        hit_rate: if lookups > 0 {
+            served as f64 / lookups as f64
+        } else {
+            0.0
+        },
+        stale_hits: stats.stale_hits,
+        revalidations: stats.revalidations,
+        coalesced: stats.coalesced,
+        cleanup_runs: stats.cleanup_runs,
+        expired_removed: stats.expired_removed,
+        top_keys,
+    }))
+}
+
+/// Remove cache entries, e.g. after fixing data by hand
+///
+/// Parameters:
+/// - namespace: Only remove keys in this namespace (search, count, share, stats, feeds, http, fallback)
+/// - prefix: Only remove keys starting with this, within the namespace if one is given
+///   (default: clear the whole cache)
+async fn invalidate_cache(
+    Query(params): Query<CacheInvalidateParams>,
+) -> Result<Json<CacheInvalidateResponse>, AppError> {
+    let prefix = match params.namespace.as_deref() {
+        Some(name) => {
+            let namespace = crate::cache::Namespace::parse(name).ok_or_else(|| {
+                AppError::BadRequest(format!("Unknown cache namespace: {}", name))
+            })?;
+            Some(namespace.key(params.prefix.as_deref().unwrap_or("")))
+        }
+        None => params.prefix.clone(),
+    };
+
+    let removed = match prefix.as_deref() {
+        Some(prefix) => crate::cache::invalidate_prefix(prefix),
+        None => {
+            let removed = crate::cache::stats().entry_count;
+            crate::cache::clear_all();
+            removed
+        }
+    };
+
+    tracing::warn!(
+        "🗑️ Admin invalidated {} cache entries (prefix: {})",
+        removed,
+        prefix.as_deref().unwrap_or("*")
+    );
+
+    Ok(Json(CacheInvalidateResponse { removed }))
+}
+
+/// Table/index sizes, connection usage and the slowest statements
+///
+/// Row counts come from pg_stat_user_tables, so this stays cheap even for
+/// inheritance and circle_member_fans_monthly.
+async fn get_db_stats(State(state): State<AppState>) -> Result<Json<DbStatsResponse>, AppError> {
+    use sqlx::Row;
+
+    let database_bytes: i64 = sqlx::query_scalar("SELECT pg_database_size(current_database())")
+        .fetch_one(&state.db)
+        .await?;
+
+    let tables = sqlx::query_as::<_, TableStats>(
+        r#"
+        SELECT
+            relname::text AS table_name,
+            n_live_tup AS live_rows,
+            n_dead_tup AS dead_rows,
+            CASE WHEN n_live_tup + n_dead_tup > 0
+                THEN n_dead_tup::float8 / (n_live_tup + n_dead_tup)
+                ELSE 0
+            END AS dead_ratio,
+            pg_table_size(relid) AS table_bytes,
+            pg_indexes_size(relid) AS index_bytes,
+            pg_total_relation_size(relid) AS total_bytes,
+            last_autovacuum,
+            last_autoanalyze
+        FROM pg_stat_user_tables
+        ORDER BY pg_total_relation_size(relid) DESC
+        LIMIT 50
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let indexes = sqlx::query_as::<_, IndexStats>(
+        r#"
+        SELECT
+            indexrelname::text AS index_name,
+            relname::text AS table_name,
+            pg_relation_size(indexrelid) AS index_bytes,
+            idx_scan AS scans
+        FROM pg_stat_user_indexes
+        ORDER BY pg_relation_size(indexrelid) DESC
+        LIMIT 50
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let backends = sqlx::query(
+        r#"
+        SELECT COALESCE(state, 'unknown') AS state, COUNT(*) AS connections
+        FROM pg_stat_activity
+        WHERE datname = current_database()
+        GROUP BY 1
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .map(|row| (row.get("state"), row.get("connections")))
+    .collect();
+
+    let max_connections: i64 =
+        sqlx::query_scalar("SELECT current_setting('max_connections')::bigint")
+            .fetch_one(&state.db)
+            .await?;
+
+    let has_pg_stat_statements: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'pg_stat_statements')",
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    let slow_queries = if has_pg_stat_statements {
+        // Still fails if the module isn't in shared_preload_libraries
+        match sqlx::query_as::<_, SlowQueryStats>(
+            r#"
+            SELECT
+                left(query, 500) AS query,
+                calls,
+                total_exec_time AS total_exec_ms,
+                mean_exec_time AS mean_exec_ms,
+                rows
+            FROM pg_stat_statements
+            WHERE dbid = (SELECT oid FROM pg_database WHERE datname = current_database())
+            ORDER BY mean_exec_time DESC
+            LIMIT 20
+            "#,
+        )
+        .fetch_all(&state.db)
+        .await
+        {
+            Ok(queries) => Some(queries),
+            Err(e) => {
+                tracing::warn!("⚠️ Could not read pg_stat_statements: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    Ok(Json(DbStatsResponse {
+        database_bytes,
+        tables,
+        indexes,
+        connections: ConnectionStats {
+            pool_size: state.db.size(),
+            pool_idle: state.db.num_idle() as u32,
+            pool_max: state.db.options().get_max_connections(),
+            backends,
+            max_connections,
+        },
+        slow_queries,
+    }))
+}
+
+/// Move completed tasks to the archive now instead of waiting for the background job
+async fn archive_completed_tasks(
+    State(state): State<AppState>,
+) -> Result<Json<TaskArchiveResult>, AppError> {
+    let result = crate::handlers::tasks::archive_completed_tasks(&state.db).await?;
+
+    tracing::warn!("🗄️ Admin task archival: {} archived", result.archived);
+
+    Ok(Json(result))
+}
+
+/// List the claim policy of every configured task type
+async fn list_task_policies(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<TaskTypePolicy>>, AppError> {
+    let policies = sqlx::query_as::<_, TaskTypePolicy>(
+        r#"
+        SELECT task_type, lane_priority, max_in_flight, paused, updated_at
+        FROM task_type_policies
+        ORDER BY lane_priority, task_type
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(policies))
+}
+
+/// Create or replace the claim policy for a task type
+async fn upsert_task_policy(
+    State(state): State<AppState>,
+    Json(payload): Json<TaskTypePolicyRequest>,
+) -> Result<Json<TaskTypePolicy>, AppError> {
+    payload.validate()?;
+
+    let policy = sqlx::query_as::<_, TaskTypePolicy>(
+        r#"
+        INSERT INTO task_type_policies (task_type, lane_priority, max_in_flight, paused, updated_at)
+        VALUES ($1, $2, $3, $4, CURRENT_TIMESTAMP)
+        ON CONFLICT (task_type) DO UPDATE SET
+            lane_priority = EXCLUDED.lane_priority,
+            max_in_flight = EXCLUDED.max_in_flight,
+            paused = EXCLUDED.paused,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING task_type, lane_priority, max_in_flight, paused, updated_at
+        "#,
+    )
+    .bind(&payload.task_type)
+    .bind(payload.lane_priority)
+    .bind(payload.max_in_flight)
+    .bind(payload.paused)
+    .fetch_one(&state.db)
+    .await?;
+
+    tracing::info!(
+        "🚦 Task policy for {} set: lane={}, max_in_flight={:?}, paused={}",
+        policy.task_type,
+        policy.lane_priority,
+        policy.max_in_flight,
+        policy.paused
+    );
+
+    Ok(Json(policy))
+}
+
+/// GET /api/admin/turnstile - Turnstile verification latency, outcomes and breaker state
+async fn get_turnstile_stats() -> Json<TurnstileStats> {
+    Json(crate::middleware::turnstile::stats())
+}
+
+/// GET /api/admin/audit-log - Recent admin audit entries, newest first
+///
+/// Parameters:
+/// - action: Only entries with this action
+/// - before_id: Page back from an entry ID
+/// - limit: Entries to return (default 100, max 500)
+async fn get_audit_log(
+    State(state): State<AppState>,
+    Query(params): Query<AdminAuditLogParams>,
+) -> Result<Json<Vec<AdminAuditEntry>>, AppError> {
+    params.validate()?;
+
+    let entries = sqlx::query_as::<_, AdminAuditEntry>(
+        r#"
+        SELECT id, action, details, created_at
+        FROM admin_audit_log
+        WHERE ($1::text IS NULL OR action = $1)
+          AND ($2::bigint IS NULL OR id < $2)
+        ORDER BY id DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(&params.action)
+    .bind(params.before_id)
+    .bind(params.limit.unwrap_or(100))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(entries))
+}
+
+/// List API keys, newest first (revoked keys included)
+async fn list_api_keys(State(state): State<AppState>) -> Result<Json<Vec<ApiKey>>, AppError> {
+    let keys = sqlx::query_as::<_, ApiKey>(
+        r#"
+        SELECT key_id, name, key_prefix, scopes, rate_limit_per_minute,
+               created_at, last_used_at, revoked_at
+        FROM api_keys
+        ORDER BY key_id DESC
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(keys))
+}
+
+/// Mint an API key for a trusted integration; the key is only returned here
+async fn create_api_key(
+    State(state): State<AppState>,
+    Json(payload): Json<ApiKeyCreateRequest>,
+) -> Result<Json<ApiKeyCreateResponse>, AppError> {
+    payload.validate()?;
+
+    let mut scopes = Vec::new();
+    for scope in &payload.scopes {
+        let scope = ApiKeyScope::parse(scope.trim()).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Unknown scope '{}' (expected search, tasks, circles or admin)",
+                scope
+            ))
+        })?;
+        if !scopes.contains(&scope.as_str()) {
+            scopes.push(scope.as_str());
+        }
+    }
+
+    let secret = api_key::generate_key();
+    let key = sqlx::query_as::<_, ApiKey>(
+        r#"
+        INSERT INTO api_keys (name, key_prefix, key_hash, scopes, rate_limit_per_minute)
+        VALUES ($1, $2, $3, $4, COALESCE($5, 120))
+        RETURNING key_id, name, key_prefix, scopes, rate_limit_per_minute,
+                  created_at, last_used_at, revoked_at
+        "#,
+    )
+    .bind(payload.name.trim())
+    .bind(&secret[..12])
+    .bind(api_key::hash_key(&secret))
+    .bind(&scopes)
+    .bind(payload.rate_limit_per_minute)
+    .fetch_one(&state.db)
+    .await?;
+
+    sqlx::query("INSERT INTO admin_audit_log (action, details) VALUES ($1, $2)")
+        .bind("api_key.create")
+        .bind(json!({
+            "key_id": key.key_id,
+            "name": &key.name,
+            "scopes": &key.scopes,
+            "rate_limit_per_minute": key.rate_limit_per_minute
+        }))
+        .execute(&state.db)
+        .await?;
+
+    tracing::warn!(
+        "🔑 Admin created API key {} ({}) with scopes {:?}",
+        key.key_id,
+        key.name,
+        key.scopes
+    );
+
+    Ok(Json(ApiKeyCreateResponse {
+        key,
+        api_key: secret,
+    }))
+}
+
+/// Revoke an API key; other instances stop accepting it within a minute
+async fn revoke_api_key(
+    State(state): State<AppState>,
+    Path(key_id): Path<i32>,
+) -> Result<Json<ApiKey>, AppError> {
+    let key = sqlx::query_as::<_, ApiKey>(
+        r#"
+        UPDATE api_keys
+        SET revoked_at = COALESCE(revoked_at, CURRENT_TIMESTAMP)
+        WHERE key_id = $1
+        RETURNING key_id, name, key_prefix, scopes, rate_limit_per_minute,
+                  created_at, last_used_at, revoked_at
+        "#,
+    )
+    .bind(key_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("API key {} not found", key_id)))?;
+
+    api_key::forget_cached_keys();
+
+    sqlx::query("INSERT INTO admin_audit_log (action, details) VALUES ($1, $2)")
+        .bind("api_key.revoke")
+        .bind(json!({ "key_id": key.key_id, "name": &key.name }))
+        .execute(&state.db)
+        .await?;
+
+    tracing::warn!("🔑 Admin revoked API key {} ({})", key.key_id, key.name);
+
+    Ok(Json(key))
+}
+
+/// GET /api/admin/bans - Bans, newest first
+///
+/// Parameters:
+/// - include_expired: Also list bans that have run out (default false)
+async fn list_bans(
+    State(state): State<AppState>,
+    Query(params): Query<BanListParams>,
+) -> Result<Json<Vec<BannedClient>>, AppError> {
+    let bans = sqlx::query_as::<_, BannedClient>(
+        r#"
+        SELECT ban_id, ip_range::text AS ip_range, api_key_id, reason, source,
+               created_at, expires_at
+        FROM banned_clients
+        WHERE $1 OR expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP
+        ORDER BY ban_id DESC
+        "#,
+    )
+    .bind(params.include_expired.unwrap_or(false))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(bans))
+}
+
+/// POST /api/admin/bans - Ban an IP/CIDR range and/or an API key
+///
+/// Applies on this instance immediately and on others within a minute.
+async fn create_ban(
+    State(state): State<AppState>,
+    Json(payload): Json<BanCreateRequest>,
+) -> Result<Json<BannedClient>, AppError> {
+    payload.validate()?;
+
+    let ip_range = match payload.ip.as_deref().map(str::trim).filter(|ip| !ip.is_empty()) {
+        Some(ip) => Some(crate::bans::parse_ip_range(ip).ok_or_else(|| {
+            AppError::BadRequest(format!("'{}' is not an IP address or CIDR range", ip))
+        })?),
+        None => None,
+    };
+    if ip_range.is_none() && payload.api_key_id.is_none() {
+        return Err(AppError::BadRequest(
+            "A ban needs an ip or an api_key_id".to_string(),
+        ));
+    }
+    if let Some(key_id) = payload.api_key_id {
+        let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM api_keys WHERE key_id = $1)")
+            .bind(key_id)
+            .fetch_one(&state.db)
+            .await?;
+        if !exists {
+            return Err(AppError::NotFound(format!("API key {} not found", key_id)));
+        }
+    }
+
+    let duration = payload
+        .duration_secs
+        .map(|secs| std::time::Duration::from_secs(secs as u64));
+    let ban = crate::bans::ban(
+        &state.db,
+        ip_range,
+        payload.api_key_id,
+        payload.reason.trim(),
+        "admin",
+        duration,
+    )
+    .await?;
+
+    sqlx::query("INSERT INTO admin_audit_log (action, details) VALUES ($1, $2)")
+        .bind("ban.create")
+        .bind(json!({
+            "ban_id": ban.ban_id,
+            "ip_range": &ban.ip_range,
+            "api_key_id": ban.api_key_id,
+            "reason": &ban.reason,
+            "duration_secs": payload.duration_secs
+        }))
+        .execute(&state.db)
+        .await?;
+
+    tracing::warn!(
+        "🚫 Admin banned {:?} / API key {:?}: {}",
+        ban.ip_range,
+        ban.api_key_id,
+        ban.reason
+    );
+
+    Ok(Json(ban))
+}
+
+/// DELETE /api/admin/bans/:ban_id - Lift a ban
+async fn remove_ban(
+    State(state): State<AppState>,
+    Path(ban_id): Path<i32>,
+) -> Result<Json<BannedClient>, AppError> {
+    let ban = crate::bans::lift(&state.db, ban_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ban {} not found", ban_id)))?;
+
+    sqlx::query("INSERT INTO admin_audit_log (action, details) VALUES ($1, $2)")
+        .bind("ban.remove")
+        .bind(json!({
+            "ban_id": ban.ban_id,
+            "ip_range": &ban.ip_range,
+            "api_key_id": ban.api_key_id
+        }))
+        .execute(&state.db)
+        .await?;
+
+    tracing::warn!("🚫 Admin lifted ban {}", ban.ban_id);
+
+    Ok(Json(ban))
+}
+
+/// List the rules deciding which records appear in the notable feed
+async fn list_notability_rules(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<NotabilityRule>>, AppError> {
+    let rules = sqlx::query_as::<_, NotabilityRule>(
+        r#"
+        SELECT rule_id, label, min_blue_stars, min_pink_stars, min_green_stars,
+               min_white_stars, min_win_count, enabled, updated_at
+        FROM notability_rules
+        ORDER BY rule_id
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(rules))
+}
+
+/// Create or replace a notability rule
+async fn upsert_notability_rule(
+    State(state): State<AppState>,
+    Json(payload): Json<NotabilityRuleRequest>,
+) -> Result<Json<NotabilityRule>, AppError> {
+    payload.validate()?;
+
+    let rule = sqlx::query_as::<_, NotabilityRule>(
+        r#"
+        INSERT INTO notability_rules (
+            rule_id, label, min_blue_stars, min_pink_stars, min_green_stars,
+            min_white_stars, min_win_count, enabled, updated_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, CURRENT_TIMESTAMP)
+        ON CONFLICT (rule_id) DO UPDATE SET
+            label = EXCLUDED.label,
+            min_blue_stars = EXCLUDED.min_blue_stars,
+            min_pink_stars = EXCLUDED.min_pink_stars,
+            min_green_stars = EXCLUDED.min_green_stars,
+            min_white_stars = EXCLUDED.min_white_stars,
+            min_win_count = EXCLUDED.min_win_count,
+            enabled = EXCLUDED.enabled,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING rule_id, label, min_blue_stars, min_pink_stars, min_green_stars,
+                  min_white_stars, min_win_count, enabled, updated_at
+        "#,
+    )
+    .bind(&payload.rule_id)
+    .bind(&payload.label)
+    .bind(payload.min_blue_stars)
+    .bind(payload.min_pink_stars)
+    .bind(payload.min_green_stars)
+    .bind(payload.min_white_stars)
+    .bind(payload.min_win_count)
+    .bind(payload.enabled)
+    .fetch_one(&state.db)
+    .await?;
+
+    // The feed should reflect the new rule right away
+    crate::cache::invalidate("feeds:notable");
+
+    tracing::info!("⭐ Notability rule {} set (enabled={})", rule.rule_id, rule.enabled);
+
+    Ok(Json(rule))
+}
+
+/// List every announcement, including drafts, scheduled and expired ones
+async fn list_all_announcements(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Announcement>>, AppError> {
+    let announcements = sqlx::query_as::<_, Announcement>(&format!(
+        "SELECT {} FROM announcements ORDER BY published_at DESC, announcement_id DESC",
+        crate::handlers::announcements::ANNOUNCEMENT_COLUMNS
+    ))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(announcements))
+}
+
+fn validate_announcement(payload: &AnnouncementRequest) -> Result<(), AppError> {
+    payload.validate()?;
+
+    // The frontend renders it as a link, so nothing like javascript: URLs
+    if let Some(link_url) = &payload.link_url {
+        if !url::Url::parse(link_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) {
+            return Err(AppError::BadRequest("link_url must be an http(s) URL".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// expires_at not after published_at (which may be the stored or current time)
+/// violates the table's check
+fn announcement_error(e: sqlx::Error) -> AppError {
+    match &e {
+        sqlx::Error::Database(db) if db.is_check_violation() => {
+            AppError::BadRequest("expires_at must be after published_at".to_string())
+        }
+        _ => AppError::from(e),
+    }
+}
+
+/// The public announcement list is cached; drop it after changes
+fn invalidate_announcements() {
+    crate::cache::invalidate_prefix(&crate::cache::Namespace::Http.key("/api/announcements"));
+}
+
+/// Create an announcement (published right away unless scheduled or a draft)
+async fn create_announcement(
+    State(state): State<AppState>,
+    Json(payload): Json<AnnouncementRequest>,
+) -> Result<Json<Announcement>, AppError> {
+    validate_announcement(&payload)?;
+
+    let announcement = sqlx::query_as::<_, Announcement>(&format!(
+        r#"
+        INSERT INTO announcements (kind, title, body, link_url, published, published_at, expires_at)
+        VALUES ($1, $2, $3, $4, $5, COALESCE($6, CURRENT_TIMESTAMP), $7)
+        RETURNING {}
+        "#,
+        crate::handlers::announcements::ANNOUNCEMENT_COLUMNS
+    ))
+    .bind(payload.kind.as_str())
+    .bind(payload.title.trim())
+    .bind(payload.body.trim())
+    .bind(&payload.link_url)
+    .bind(payload.published)
+    .bind(payload.published_at)
+    .bind(payload.expires_at)
+    .fetch_one(&state.db)
+    .await
+    .map_err(announcement_error)?;
+
+    invalidate_announcements();
+    tracing::info!(
+        "📣 Announcement {} created ({})",
+        announcement.announcement_id,
+        announcement.kind.as_str()
+    );
+
+    Ok(Json(announcement))
+}
+
+/// Replace an announcement; published_at is kept unless given
+async fn update_announcement(
+    State(state): State<AppState>,
+    Path(announcement_id): Path<i32>,
+    Json(payload): Json<AnnouncementRequest>,
+) -> Result<Json<Announcement>, AppError> {
+    validate_announcement(&payload)?;
+
+    let announcement = sqlx::query_as::<_, Announcement>(&format!(
+        r#"
+        UPDATE announcements SET
+            kind = $2,
+            title = $3,
+            body = $4,
+            link_url = $5,
+            published = $6,
+            published_at = COALESCE($7, published_at),
+            expires_at = $8,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE announcement_id = $1
+        RETURNING {}
+        "#,
+        crate::handlers::announcements::ANNOUNCEMENT_COLUMNS
+    ))
+    .bind(announcement_id)
+    .bind(payload.kind.as_str())
+    .bind(payload.title.trim())
+    .bind(payload.body.trim())
+    .bind(&payload.link_url)
+    .bind(payload.published)
+    .bind(payload.published_at)
+    .bind(payload.expires_at)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(announcement_error)?
+    .ok_or_else(|| AppError::NotFound(format!("Announcement {} not found", announcement_id)))?;
+
+    invalidate_announcements();
+    tracing::info!("📣 Announcement {} updated", announcement_id);
+
+    Ok(Json(announcement))
+}
+
+/// Delete an announcement
+async fn delete_announcement(
+    State(state): State<AppState>,
+    Path(announcement_id): Path<i32>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let removed = sqlx::query("DELETE FROM announcements WHERE announcement_id = $1")
+        .bind(announcement_id)
+        .execute(&state.db)
+        .await?
+        .rows_affected();
+    if removed == 0 {
+        return Err(AppError::NotFound(format!(
+            "Announcement {} not found",
+            announcement_id
+        )));
+    }
+
+    invalidate_announcements();
+    tracing::info!("📣 Announcement {} deleted", announcement_id);
+
+    Ok(Json(json!({
+        "success": true,
+        "announcement_id": announcement_id
+    })))
+}
+
+/// Reset failed/dead tasks matching the filters back to pending
+///
+/// Works in batches of REQUEUE_BATCH_SIZE until max_tasks is reached or no
+/// matching tasks remain, then records the operation in the admin audit log.
+async fn requeue_failed_tasks(
+    State(state): State<AppState>,
+    Json(payload): Json<TaskRequeueRequest>,
+) -> Result<Json<TaskRequeueResponse>, AppError> {
+    payload.validate()?;
+
+    let max_tasks = payload.max_tasks.unwrap_or(500);
+    let error_pattern = payload.error_class.as_ref().map(|e| format!("%{}%", e));
+
+    const FILTER: &str = r#"
+        status IN ('failed', 'dead')
+          AND ($1::text IS NULL OR task_type = $1)
+          AND ($2::text IS NULL OR error_message ILIKE $2)
+          AND ($3::bigint IS NULL OR COALESCE(updated_at, created_at) <= (CURRENT_TIMESTAMP - make_interval(mins => $3::int))::timestamp)
+          AND ($4::bigint IS NULL OR COALESCE(updated_at, created_at) >= (CURRENT_TIMESTAMP - make_interval(mins => $4::int))::timestamp)
+    "#;
+
+    if payload.dry_run {
+        let matching: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM tasks WHERE {}", FILTER))
+            .bind(&payload.task_type)
+            .bind(&error_pattern)
+            .bind(payload.min_age_minutes)
+            .bind(payload.max_age_minutes)
+            .fetch_one(&state.db)
+            .await?;
+
+        return Ok(Json(TaskRequeueResponse {
+            dry_run: true,
+            requeued: matching.min(max_tasks),
+            batches: 0,
+        }));
+    }
+
+    let update_sql = format!(
+        r#"
+        UPDATE tasks SET
+            status = 'pending',
+            attempts = 0,
+            worker_id = NULL,
+            claimed_at = NULL,
+            error_message = NULL,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id IN (
+            SELECT id FROM tasks
+            WHERE {}
+            ORDER BY id
+            LIMIT $5
+            FOR UPDATE SKIP LOCKED
+        )
+        "#,
+        FILTER
+    );
+
+    let mut requeued: i64 = 0;
+    let mut batches: u32 = 0;
+    while requeued < max_tasks {
+        let batch_limit = REQUEUE_BATCH_SIZE.min(max_tasks - requeued);
+        let mut tx = state.db.begin().await?;
+        crate::handlers::tasks::set_task_actor(&mut tx, "admin").await?;
+        let result = sqlx::query(&update_sql)
+            .bind(&payload.task_type)
+            .bind(&error_pattern)
+            .bind(payload.min_age_minutes)
+            .bind(payload.max_age_minutes)
+            .bind(batch_limit)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        let affected = result.rows_affected() as i64;
+        if affected == 0 {
+            break;
+        }
+        requeued += affected;
+        batches += 1;
+    }
+
+    sqlx::query("INSERT INTO admin_audit_log (action, details) VALUES ($1, $2)")
+        .bind("tasks.requeue")
+        .bind(json!({
+            "filters": &payload,
+            "requeued": requeued,
+            "batches": batches
+        }))
+        .execute(&state.db)
+        .await?;
+
+    tracing::warn!("♻️ Admin re-queued {} failed task(s) in {} batch(es)", requeued, batches);
+
+    Ok(Json(TaskRequeueResponse {
+        dry_run: false,
+        requeued,
+        batches,
+    }))
+}
+
+/// Replace the worker fleet settings advertised via /api/workers/config
+async fn update_worker_settings(
+    State(state): State<AppState>,
+    Json(payload): Json<WorkerFleetSettingsRequest>,
+) -> Result<Json<WorkerFleetSettings>, AppError> {
+    payload.validate()?;
+
+    if payload
+        .maintenance_windows
+        .iter()
+        .any(|w| w.ends_at <= w.starts_at)
+    {
+        return Err(AppError::BadRequest(
+            "Maintenance windows must end after they start".to_string(),
+        ));
+    }
+
+    let settings = sqlx::query_as::<_, WorkerFleetSettings>(
+        r#"
+        UPDATE worker_fleet_settings SET
+            paused = $1,
+            claim_endpoints = $2,
+            poll_interval_secs = $3,
+            idle_poll_interval_secs = $4,
+            maintenance_windows = $5,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING paused, claim_endpoints, poll_interval_secs, idle_poll_interval_secs,
+                  maintenance_windows, updated_at
+        "#,
+    )
+    .bind(payload.paused)
+    .bind(&payload.claim_endpoints)
+    .bind(payload.poll_interval_secs)
+    .bind(payload.idle_poll_interval_secs)
+    .bind(sqlx::types::Json(&payload.maintenance_windows))
+    .fetch_one(&state.db)
+    .await?;
+
+    tracing::warn!(
+        "🛠️ Admin updated worker fleet settings: paused={}, {} maintenance window(s)",
+        settings.paused,
+        settings.maintenance_windows.len()
+    );
+
+    Ok(Json(settings))
+}
+
+/// Source/ingestion metadata for a trainer's inheritance and support cards
+async fn get_trainer_provenance(
+    State(state): State<AppState>,
+    Path(account_id): Path<AccountId>,
+) -> Result<Json<TrainerProvenance>, AppError> {
+    let provenance = crate::handlers::tasks::fetch_trainer_provenance(&state.db, &account_id).await?;
+
+    if provenance.inheritance.is_none() && provenance.support_cards.is_empty() {
+        return Err(AppError::NotFound(format!("No records for trainer {}", account_id)));
+    }
+
+    Ok(Json(provenance))
+}
+
+/// How many rows a scrape batch wrote and when
+async fn get_batch_provenance(
+    State(state): State<AppState>,
+    Path(batch_id): Path<String>,
+) -> Result<Json<BatchProvenanceSummary>, AppError> {
+    let summary = sqlx::query_as::<_, BatchProvenanceSummary>(
+        r#"
+        WITH rows AS (
+            SELECT 'inheritance' AS kind, ingested_at FROM inheritance WHERE source->>'batch_id' = $1
+            UNION ALL
+            SELECT 'support_card' AS kind, ingested_at FROM support_card WHERE source->>'batch_id' = $1
+        )
+        SELECT
+            $1 AS batch_id,
+            COUNT(*) FILTER (WHERE kind = 'inheritance') AS inheritance_rows,
+            COUNT(*) FILTER (WHERE kind = 'support_card') AS support_card_rows,
+            MIN(ingested_at) AS first_ingested_at,
+            MAX(ingested_at) AS last_ingested_at
+        FROM rows
+        "#,
+    )
+    .bind(&batch_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    if summary.inheritance_rows == 0 && summary.support_card_rows == 0 {
+        return Err(AppError::NotFound(format!("No records from batch {}", batch_id)));
+    }
+
+    Ok(Json(summary))
+}
+
+/// Roll back every inheritance/support_card row currently attributed to a batch
+///
+/// Rows the batch overwrote are restored to the version it replaced; rows it
+/// created are deleted (and kept in record_versions). Runs in one transaction,
+/// is recorded in the admin audit log, and invalidates cached search pages.
+async fn rollback_ingest_batch(
+    State(state): State<AppState>,
+    Json(payload): Json<IngestRollbackRequest>,
+) -> Result<Json<IngestRollbackResponse>, AppError> {
+    payload.validate()?;
+
+    let batch_id = payload.batch_id.clone();
+    let mut response = IngestRollbackResponse {
+        batch_id: batch_id.clone(),
+        dry_run: payload.dry_run,
+        ..Default::default()
+    };
+
+    let mut tx = crate::database::begin_long_running(&state.db).await?;
+    sqlx::query("SELECT set_config('umamoe.restoring', 'on', true), set_config('umamoe.version_reason', $1, true)")
+        .bind(format!("rollback:{}", batch_id))
+        .execute(&mut *tx)
+        .await?;
+
+    // (table, key expression) for each versioned table
+    let tables = [
+        ("inheritance", "account_id"),
+        ("support_card", "account_id || ':' || support_card_id"),
+    ];
+
+    let mut affected_trainers = std::collections::HashSet::new();
+
+    for (table, key_expr) in tables {
+        let rows: Vec<(String, String)> = sqlx::query_as(&format!(
+            "SELECT {key_expr}, account_id FROM {table} WHERE source->>'batch_id' = $1"
+        ))
+        .bind(&batch_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if rows.is_empty() {
+            continue;
+        }
+
+        let keys: Vec<String> = rows.iter().map(|(key, _)| key.clone()).collect();
+        affected_trainers.extend(rows.into_iter().map(|(_, account_id)| account_id));
+
+        // Earliest version each row had before the batch touched it
+        let restorable: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(DISTINCT record_key) FROM record_versions
+            WHERE replaced_by_batch = $1 AND table_name = $2 AND record_key = ANY($3)
+            "#,
+        )
+        .bind(&batch_id)
+        .bind(table)
+        .bind(&keys)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let restored = restorable as u64;
+        let removed = keys.len() as u64 - restored;
+
+        if !payload.dry_run {
+            sqlx::query(&format!(
+                "DELETE FROM {table} WHERE source->>'batch_id' = $1 AND {key_expr} = ANY($2)"
+            ))
+            .bind(&batch_id)
+            .bind(&keys)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(&format!(
+                r#"
+                INSERT INTO {table}
+                SELECT (jsonb_populate_record(NULL::{table}, v.data)).*
+                FROM (
+                    SELECT DISTINCT ON (record_key) data
+                    FROM record_versions
+                    WHERE replaced_by_batch = $1 AND table_name = $2 AND record_key = ANY($3)
+                    ORDER BY record_key, id
+                ) v
+                "#
+            ))
+            .bind(&batch_id)
+            .bind(table)
+            .bind(&keys)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if table == "inheritance" {
+            response.inheritance_restored = restored;
+            response.inheritance_removed = removed;
+        } else {
+            response.support_cards_restored = restored;
+            response.support_cards_removed = removed;
+        }
+    }
+
+    response.affected_trainers = affected_trainers.len();
+
+    if payload.dry_run {
+        tx.rollback().await?;
+        return Ok(Json(response));
+    }
+
+    sqlx::query("INSERT INTO admin_audit_log (action, details) VALUES ($1, $2)")
+        .bind("ingest.rollback")
+        .bind(json!(&response))
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    crate::events::publish(crate::events::DomainEvent::TrainerDataChanged {
+        account_ids: affected_trainers.into_iter().collect(),
+    });
+
+    tracing::warn!(
+        "⏪ Admin rolled back batch {}: inheritance {} restored/{} removed, support cards {} restored/{} removed",
+        batch_id,
+        response.inheritance_restored,
+        response.inheritance_removed,
+        response.support_cards_restored,
+        response.support_cards_removed
+    );
+
+    Ok(Json(response))
+}
+
+async fn count_pending_spark_backfill(pool: &sqlx::PgPool) -> Result<i64, AppError> {
+    let pending = sqlx::query_scalar::<_, i64>(&format!(
+        "SELECT COUNT(*) FROM inheritance WHERE {}",
+        SPARK_V2_PENDING_FILTER
+    ))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(pending)
+}
+
+/// Backfill progress and v1/v2 consistency of the most recent backfilled rows
+async fn get_spark_encoding_status(
+    State(state): State<AppState>,
+    Query(params): Query<SparkEncodingStatusParams>,
+) -> Result<Json<SparkEncodingStatus>, AppError> {
+    params.validate()?;
+
+    let sample = params.sample.unwrap_or(10_000);
+
+    let total_rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM inheritance")
+        .fetch_one(&state.db)
+        .await?;
+    let pending_backfill = count_pending_spark_backfill(&state.db).await?;
+
+    let checked = sqlx::query(&format!(
+        r#"
+        WITH checked AS (
+            SELECT *
+            FROM inheritance
+            WHERE NOT ({})
+            ORDER BY inheritance_id DESC
+            LIMIT $1
+        )
+        SELECT
+            (SELECT COUNT(*) FROM checked) AS checked_rows,
+            (SELECT COUNT(*) FROM checked WHERE {}) AS mismatched_rows,
+            (SELECT COALESCE(array_agg(inheritance_id), '{{}}') FROM (
+                SELECT inheritance_id FROM checked WHERE {} ORDER BY inheritance_id DESC LIMIT 20
+            ) m) AS mismatched_ids
+        "#,
+        SPARK_V2_PENDING_FILTER, SPARK_V2_MISMATCH_FILTER, SPARK_V2_MISMATCH_FILTER
+    ))
+    .bind(sample)
+    .fetch_one(&state.db)
+    .await?;
+
+    use sqlx::Row;
+    Ok(Json(SparkEncodingStatus {
+        active_encoding: SparkEncoding::active().as_str().to_string(),
+        total_rows,
+        pending_backfill,
+        checked_rows: checked.get("checked_rows"),
+        mismatched_rows: checked.get("mismatched_rows"),
+        mismatched_ids: checked.get("mismatched_ids"),
+    }))
+}
+
+/// Fill the v2 spark columns of existing rows from v1, in small batches
+///
+/// Runs with umamoe.maintenance on so the backfill neither restamps provenance
+/// nor creates record versions.
+async fn backfill_spark_encoding(
+    State(state): State<AppState>,
+    Json(payload): Json<SparkBackfillRequest>,
+) -> Result<Json<SparkBackfillResponse>, AppError> {
+    payload.validate()?;
+
+    let batch_size = payload.batch_size.unwrap_or(1000);
+    let max_rows = payload.max_rows.unwrap_or(100_000);
+
+    let update_sql = format!(
+        r#"
+        UPDATE inheritance SET
+            blue_sparks_v2 = spark_v1_to_v2(blue_sparks),
+            pink_sparks_v2 = spark_v1_to_v2(pink_sparks),
+            green_sparks_v2 = spark_v1_to_v2(green_sparks),
+            white_sparks_v2 = spark_v1_to_v2(white_sparks),
+            main_white_factors_v2 = spark_v1_to_v2(main_white_factors),
+            main_blue_factors_v2 = spark_v1_to_v2(main_blue_factors),
+            main_pink_factors_v2 = spark_v1_to_v2(main_pink_factors),
+            main_green_factors_v2 = spark_v1_to_v2(main_green_factors)
+        WHERE inheritance_id IN (
+            SELECT inheritance_id FROM inheritance
+            WHERE {}
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        "#,
+        SPARK_V2_PENDING_FILTER
+    );
+
+    let mut updated: i64 = 0;
+    let mut batches: u32 = 0;
+    while updated < max_rows {
+        let batch_limit = batch_size.min(max_rows - updated);
+        let mut tx = crate::database::begin_long_running(&state.db).await?;
+        sqlx::query("SELECT set_config('umamoe.maintenance', 'on', true)")
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query(&update_sql)
+            .bind(batch_limit)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        let affected = result.rows_affected() as i64;
+        if affected == 0 {
+            break;
+        }
+        updated += affected;
+        batches += 1;
+    }
+
+    let remaining = count_pending_spark_backfill(&state.db).await?;
+
+    sqlx::query("INSERT INTO admin_audit_log (action, details) VALUES ($1, $2)")
+        .bind("sparks.backfill")
+        .bind(json!({
+            "updated": updated,
+            "batches": batches,
+            "remaining": remaining
+        }))
+        .execute(&state.db)
+        .await?;
+
+    tracing::info!(
+        "✨ Backfilled v2 sparks for {} row(s) in {} batch(es), {} remaining",
+        updated,
+        batches,
+        remaining
+    );
+
+    Ok(Json(SparkBackfillResponse {
+        updated,
+        batches,
+        remaining,
+    }))
+}
+
+/// Run the same search count against both encodings
+async fn compare_spark_encodings(
+    State(state): State<AppState>,
+    Json(payload): Json<SparkCompareRequest>,
+) -> Result<Json<SparkCompareResponse>, AppError> {
+    payload.validate()?;
+
+    let query = payload.query.trim_start_matches('?').to_string();
+    let params = crate::handlers::search::parse_search_params(&query)?;
+
+    let v1_count =
+        crate::handlers::search::execute_count_query(&state.db, &params, SparkEncoding::V1).await?;
+    let v2_count =
+        crate::handlers::search::execute_count_query(&state.db, &params, SparkEncoding::V2).await?;
+
+    if v1_count != v2_count {
+        tracing::warn!(
+            "⚠️ Spark encoding mismatch for '{}': v1={} v2={}",
+            query,
+            v1_count,
+            v2_count
+        );
+    }
+
+    Ok(Json(SparkCompareResponse {
+        query,
+        v1_count,
+        v2_count,
+        matches: v1_count == v2_count,
+    }))
+}
+
+/// Switch searches between spark encodings at runtime
+///
+/// Only affects this process; set SPARK_ENCODING to make the choice stick.
+async fn set_spark_encoding(
+    State(state): State<AppState>,
+    Json(payload): Json<SparkEncodingRequest>,
+) -> Result<Json<SparkEncodingStatus>, AppError> {
+    let encoding = match payload.encoding.as_str() {
+        "v1" => SparkEncoding::V1,
+        "v2" => SparkEncoding::V2,
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "Unknown spark encoding '{}' (expected v1 or v2)",
+                other
+            )))
+        }
+    };
+
+    let pending_backfill = count_pending_spark_backfill(&state.db).await?;
+    if encoding == SparkEncoding::V2 && pending_backfill > 0 && !payload.force {
+        return Err(AppError::Conflict(format!(
+            "{} row(s) still need the v2 backfill; pass force=true to switch anyway",
+            pending_backfill
+        )));
+    }
+
+    let previous = SparkEncoding::active();
+    encoding.activate();
+
+    // Cached pages and counts were computed against the previous encoding
+    let removed = crate::cache::invalidate_namespaces(crate::cache::Namespace::TRAINER_DATA);
+
+    sqlx::query("INSERT INTO admin_audit_log (action, details) VALUES ($1, $2)")
+        .bind("sparks.encoding")
+        .bind(json!({
+            "from": previous.as_str(),
+            "to": encoding.as_str(),
+            "force": payload.force,
+            "pending_backfill": pending_backfill
+        }))
+        .execute(&state.db)
+        .await?;
+
+    tracing::warn!(
+        "🔀 Spark encoding switched {} -> {} ({} cached entries dropped)",
+        previous.as_str(),
+        encoding.as_str(),
+        removed
+    );
+
+    get_spark_encoding_status(
+        State(state),
+        Query(SparkEncodingStatusParams { sample: Some(1000) }),
+    )
+    .await
+}
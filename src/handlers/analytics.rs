@@ -0,0 +1,86 @@
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap},
+    response::Json,
+    routing::post,
+    Router,
+};
+use std::net::SocketAddr;
+use validator::Validate;
+
+use crate::analytics::{self, ValidEvent};
+use crate::errors::AppError;
+use crate::models::{AnalyticsEventBatch, AnalyticsEventResponse, IngestRowError};
+use crate::AppState;
+
+/// Oldest client timestamp accepted (events queued by an offline browser)
+const MAX_EVENT_AGE_DAYS: i64 = 7;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", post(ingest_events))
+}
+
+/// POST /api/events - Record a batch of frontend analytics events
+///
+/// Each event's properties are checked against the schema of its type; events
+/// that don't match are reported back and the rest are stored. Posting events
+/// also counts the caller as today's visitor, as /api/stats/daily-visit does.
+///
+/// Body:
+/// - session_id: Random per-session ID chosen by the frontend (optional)
+/// - events: 1-50 of {type, occurred_at, properties}
+pub async fn ingest_events(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(batch): Json<AnalyticsEventBatch>,
+) -> Result<Json<AnalyticsEventResponse>, AppError> {
+    batch.validate()?;
+    if batch.session_id.as_deref().is_some_and(|id| {
+        !id.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }) {
+        return Err(AppError::BadRequest(
+            "session_id may only contain letters, digits, '-' and '_'".to_string(),
+        ));
+    }
+
+    let now = chrono::Utc::now();
+    let oldest = now - chrono::Duration::days(MAX_EVENT_AGE_DAYS);
+    let mut response = AnalyticsEventResponse::default();
+    let mut events = Vec::with_capacity(batch.events.len());
+    for (index, event) in batch.events.into_iter().enumerate() {
+        // Clients with a fast clock are clamped to now
+        let occurred_at = event.occurred_at.unwrap_or(now).min(now);
+        let checked = if occurred_at < oldest {
+            Err(format!("occurred_at is more than {} days ago", MAX_EVENT_AGE_DAYS))
+        } else {
+            analytics::validate_properties(event.event_type, &event.properties)
+        };
+        match checked {
+            Ok(()) => events.push(ValidEvent {
+                event_type: event.event_type,
+                occurred_at: occurred_at.naive_utc(),
+                properties: event.properties,
+            }),
+            Err(error) => response.errors.push(IngestRowError { index, error }),
+        }
+    }
+
+    if !events.is_empty() {
+        analytics::store(&state.db, batch.session_id.as_deref(), &events).await?;
+    }
+    response.accepted = events.len();
+    response.rejected = response.errors.len();
+
+    let client_ip = crate::middleware::turnstile::extract_client_ip(&headers, addr);
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if let Err(e) = crate::visitors::record_visit(&state.db, &client_ip, user_agent).await {
+        tracing::warn!("⚠️ Failed to record daily visit: {}", e);
+    }
+
+    Ok(Json(response))
+}
@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+    routing::get,
+    Router,
+};
+use std::time::Duration;
+
+use crate::errors::AppError;
+use crate::models::{Announcement, AnnouncementListParams};
+use crate::AppState;
+
+/// Short, since admin changes also drop the cached responses
+const ANNOUNCEMENTS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Columns of Announcement
+pub(crate) const ANNOUNCEMENT_COLUMNS: &str = r#"
+    announcement_id, kind, title, body, link_url, published, published_at,
+    expires_at, created_at, updated_at
+"#;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route(
+        "/",
+        get(list_announcements).layer(axum::middleware::from_fn_with_state(
+            ANNOUNCEMENTS_CACHE_TTL,
+            crate::middleware::response_cache_middleware,
+        )),
+    )
+}
+
+/// GET /api/announcements - Current announcements, newest first
+///
+/// Lists published announcements whose time has come and that haven't expired.
+/// Admins manage them under /api/admin/announcements.
+///
+/// Parameters:
+/// - since: Only announcements published after this time (e.g. the newest
+///   published_at already shown)
+/// - include_expired: Also list expired announcements, for a changelog
+/// - limit: Announcements to return (1-100, default 20)
+pub async fn list_announcements(
+    State(state): State<AppState>,
+    Query(params): Query<AnnouncementListParams>,
+) -> Result<Json<Vec<Announcement>>, AppError> {
+    let limit = params.limit.unwrap_or(20);
+    if !(1..=100).contains(&limit) {
+        return Err(AppError::BadRequest("limit must be between 1 and 100".to_string()));
+    }
+
+    let announcements = sqlx::query_as::<_, Announcement>(&format!(
+        r#"
+        SELECT {}
+        FROM announcements
+        WHERE published
+          AND published_at <= CURRENT_TIMESTAMP
+          AND ($1 OR expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+          AND ($2::timestamp IS NULL OR published_at > $2)
+        ORDER BY published_at DESC, announcement_id DESC
+        LIMIT $3
+        "#,
+        ANNOUNCEMENT_COLUMNS
+    ))
+    .bind(params.include_expired.unwrap_or(false))
+    .bind(params.since)
+    .bind(limit)
+    .fetch_all(state.read_db())
+    .await?;
+
+    Ok(Json(announcements))
+}
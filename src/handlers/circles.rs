@@ -1,77 +1,128 @@
 use axum::{
-    extract::{Query, State},
-    routing::get,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
     Json, Router,
 };
-use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
 use crate::{
+    competition,
     errors::AppError,
-    models::{Circle, CircleMemberFansMonthly},
+    handlers::tasks::{dry_run_response, find_pending_task},
+    models::{
+        Circle, CircleAward, CircleAwardsParams, CircleAwardsResponse, CircleBulkParams, CircleBulkResponse, CircleHistoryParams, CircleHistoryResponse, CircleId, CircleListParams,
+        CircleListResponse, CircleMemberEvent, CircleMemberEventsParams,
+        CircleMemberEventsResponse, CircleMemberFansMonthly, CircleQueryParams,
+        CircleContributor, CircleRankSnapshot, CircleRefreshParams, CircleResponse, CircleStats,
+        CircleStatsParams, CircleWatchResponse, CircleWebhook, CircleWebhookRequest, CircleWithRank, MemberFanDay, MemberFanHistoryParams,
+        MemberFanHistoryResponse, TaskOutcome, ViewerId, WatchedCircle, WatchedCirclesResponse,
+    },
     AppState,
 };
 
-#[derive(Debug, Deserialize)]
-pub struct CircleQueryParams {
-    /// Query by viewer ID - will find their circle
-    pub viewer_id: Option<i64>,
-    /// Query by circle ID directly
-    pub circle_id: Option<i64>,
-    /// Filter members by month (1-12)
-    pub month: Option<i32>,
-    /// Filter members by year
-    pub year: Option<i32>,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct CircleListParams {
-    /// Page number (0-indexed)
-    #[serde(default)]
-    pub page: Option<i64>,
-    /// Results per page
-    #[serde(default)]
-    pub limit: Option<i64>,
-    /// Search by circle name (partial match)
-    pub name: Option<String>,
-    /// Minimum member count
-    pub min_members: Option<i32>,
-    /// Minimum monthly rank (lower is better)
-    pub max_rank: Option<i32>,
-    /// Sort by field (name, member_count, monthly_rank, monthly_point)
-    pub sort_by: Option<String>,
-    /// Sort direction (asc, desc)
-    pub sort_dir: Option<String>,
-    /// General search query (circle ID/name, leader ID/name, member ID/name)
-    pub query: Option<String>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct CircleResponse {
-    pub circle: Circle,
-    pub members: Vec<CircleMemberFansMonthly>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct CircleWithRank {
-    #[serde(flatten)]
-    pub circle: Circle,
-}
-
-#[derive(Debug, Serialize)]
-pub struct CircleListResponse {
-    pub circles: Vec<CircleWithRank>,
-    pub total: i64,
-    pub page: i64,
-    pub limit: i64,
-    pub total_pages: i64,
+// Circles whose member data was updated this recently are not re-fetched
+const CIRCLE_REFRESH_COOLDOWN_SECS: f64 = 3600.0;
+
+// Longest date range served by the rank history endpoint
+const MAX_HISTORY_DAYS: i64 = 366;
+
+// Circles per bulk lookup
+const MAX_BULK_CIRCLES: usize = 50;
+
+// Circles a single watch token can follow
+const MAX_WATCHED_CIRCLES: i64 = 50;
+
+// Circles kept in the leaderboard snapshot (deeper pages query the database)
+const LEADERBOARD_SNAPSHOT_SIZE: i64 = 10_000;
+// Snapshots older than this are ignored, e.g. while the database is unreachable
+const LEADERBOARD_SNAPSHOT_MAX_AGE: Duration = Duration::from_secs(300);
+
+// How long a circle's points percentile is cached
+const CIRCLE_PERCENTILE_TTL: Duration = Duration::from_secs(600);
+
+// How long viewer -> circle lookups and circle responses are cached
+const CIRCLE_CACHE_TTL: Duration = Duration::from_secs(600);
+
+// How long whole circle list pages are cached
+const CIRCLE_LIST_CACHE_TTL: Duration = Duration::from_secs(60);
+
+// Rank thresholds a circle webhook can watch
+const MAX_WEBHOOK_THRESHOLDS: usize = 5;
+const DEFAULT_WEBHOOK_THRESHOLDS: [i32; 2] = [100, 500];
+pub(crate) const DISCORD_WEBHOOK_PREFIXES: [&str; 2] = [
+    "https://discord.com/api/webhooks/",
+    "https://discordapp.com/api/webhooks/",
+];
+
+// Per-member fan gains for one month of a circle ($1 = circle_id, $2 = year, $3 = month).
+// daily_fans is cumulative (0 = no data for that day), so a day's gain is the
+// difference to the member's previous recorded day.
+const CIRCLE_MEMBER_DAYS_CTE: &str = r#"
+    member_days AS (
+        SELECT
+            cm.viewer_id,
+            x.d,
+            x.f - lag(x.f) OVER (PARTITION BY cm.viewer_id ORDER BY x.d) AS gain
+        FROM circle_member_fans_monthly cm
+        CROSS JOIN LATERAL unnest(cm.daily_fans) WITH ORDINALITY AS x(f, d)
+        WHERE cm.circle_id = $1 AND cm.year = $2 AND cm.month = $3 AND x.f > 0
+    ),
+    members AS (
+        SELECT
+            viewer_id,
+            COALESCE(SUM(gain), 0)::bigint AS month_fans,
+            COALESCE(SUM(gain)::float8 / NULLIF(MAX(d) - MIN(d), 0), 0) AS avg_daily_fans
+        FROM member_days
+        GROUP BY viewer_id
+    )
+"#;
+
+/// Which month's standings the circle list shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CircleListPeriod {
+    /// Live standings for the running month
+    Current,
+    /// Final standings of the previous month, from the stored last_month_* columns
+    PreviousMonth,
+    /// Older months, from the last circle_rank_history snapshot of the month
+    History { year: i32, month: u32 },
 }
 
 /// Create the circles router
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", get(get_circle))
-        .route("/list", get(list_circles))
+        .route(
+            "/list",
+            get(list_circles)
+                .layer(axum::middleware::from_fn_with_state(
+                    CIRCLE_LIST_CACHE_TTL,
+                    crate::middleware::response_cache_middleware,
+                ))
+                .layer(axum::middleware::from_fn_with_state(
+                    crate::middleware::DegradedFallback::ALL,
+                    crate::middleware::degraded_middleware,
+                )),
+        )
+        .route("/bulk", get(get_circles_bulk))
+        .route("/refresh", post(refresh_circle))
+        .route("/watched", get(get_watched_circles))
+        .route("/:circle_id/watch", post(watch_circle).delete(unwatch_circle))
+        .route(
+            "/:circle_id/webhook",
+            get(get_circle_webhook).put(put_circle_webhook).delete(delete_circle_webhook),
+        )
+        .route("/:circle_id/webhook/verify", post(verify_circle_webhook))
+        .route("/:circle_id/history", get(get_circle_history))
+        .route("/:circle_id/member-events", get(get_circle_member_events))
+        .route("/:circle_id/stats", get(get_circle_stats))
+        .route("/:circle_id/awards", get(get_circle_awards))
+        .route("/members/:viewer_id/history", get(get_member_fan_history))
 }
 
 /// GET /api/circles - Get circle information and member fan counts
@@ -79,8 +130,14 @@ pub fn router() -> Router<AppState> {
 /// Parameters:
 /// - viewer_id: Get circle for a specific viewer (will add to tasks if not found)
 /// - circle_id: Get circle by ID directly
+/// - year, month: Month of member fan data (default: current competition month)
+/// - sort_by: Member sort field (total_fans, today_fans, name; default: viewer_id)
+/// - sort_dir: Sort direction (asc, desc; default: desc for fan counts, asc otherwise)
+/// - page, limit: Member pagination (default: all members)
+/// - include: "records" adds each member's best inheritance record
 ///
-/// Returns circle info with all member fan count data
+/// Returns circle info with member fan count data and the circle's points percentile.
+/// Responses are cached (10 minutes) until the circle or its members are updated.
 pub async fn get_circle(
     Query(params): Query<CircleQueryParams>,
     State(state): State<AppState>,
@@ -92,29 +149,13 @@ pub async fn get_circle(
         ));
     }
 
-    let circle = if let Some(viewer_id) = params.viewer_id {
+    let circle_id = if let Some(viewer_id) = params.viewer_id {
         // Query by viewer_id - first check if viewer exists in circle_member_fans_monthly
-        let member_record = sqlx::query!(
-            r#"
-            SELECT circle_id 
-            FROM circle_member_fans_monthly 
-            WHERE viewer_id = $1 
-            LIMIT 1
-            "#,
-            viewer_id
-        )
-        .fetch_optional(&state.db)
-        .await?;
-
-        match member_record {
-            Some(record) => {
-                // Viewer found, get their circle
-                let circle_id = record.circle_id;
-                fetch_circle_by_id(&state.db, circle_id).await?
-            }
+        match state.storage.circle_of_viewer(viewer_id).await? {
+            Some(circle_id) => circle_id,
             None => {
                 // Viewer not found - add to tasks for later fetching
-                add_viewer_to_tasks(&state.db, viewer_id).await?;
+                state.storage.queue_circle_fetch(viewer_id).await?;
 
                 return Err(AppError::NotFound(format!(
                     "Viewer {} not found in any circle. Added to task queue for fetching.",
@@ -124,15 +165,120 @@ pub async fn get_circle(
         }
     } else if let Some(circle_id) = params.circle_id {
         // Query by circle_id directly
-        fetch_circle_by_id(&state.db, circle_id).await?
+        circle_id
     } else {
         unreachable!("Already validated at least one param exists");
     };
 
+    // Serve the cached response while neither the circle nor its members changed
+    let cache_key = state
+        .storage
+        .circle_version(circle_id, &params)
+        .await?
+        .map(|version| circle_response_cache_key(circle_id, &params, &version));
+    if let Some(cached) = cache_key
+        .as_deref()
+        .and_then(crate::cache::get::<CircleResponse>)
+    {
+        return Ok(Json(cached));
+    }
+
+    let circle = state.storage.circle(circle_id).await?;
+
     // Get all members and their fan counts for this circle
-    let members = fetch_circle_members(&state.db, circle.circle_id, params.year, params.month).await?;
+    let (members, total_members) = state.storage.circle_members(circle.circle_id, &params).await?;
+    let percentile = state.storage.circle_percentile(circle.circle_id).await?;
+
+    let response = CircleResponse {
+        circle,
+        percentile,
+        members,
+        total_members,
+    };
+    if let Some(cache_key) = cache_key {
+        let _ = crate::cache::set(&cache_key, &response, CIRCLE_CACHE_TTL);
+    }
+
+    Ok(Json(response))
+}
 
-    Ok(Json(CircleResponse { circle, members }))
+fn circle_response_cache_key(circle_id: CircleId, params: &CircleQueryParams, version: &str) -> String {
+    // Every parameter that shapes the member list must be part of the key
+    format!(
+        "circle:{}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{:?}:{}",
+        circle_id,
+        params.year,
+        params.month,
+        params.sort_by,
+        params.sort_dir,
+        params.page,
+        params.limit,
+        params.include,
+        version
+    )
+}
+
+/// POST /api/circles/refresh - Queue a re-fetch of a viewer's circle
+///
+/// Parameters:
+/// - viewer_id: Viewer whose circle should be fetched
+/// - dry_run: Only report what would happen (task_created / duplicate / throttled)
+///
+/// Requests for viewers whose member data was updated within the last hour are throttled.
+pub async fn refresh_circle(
+    Query(params): Query<CircleRefreshParams>,
+    State(state): State<AppState>,
+) -> Result<Response, AppError> {
+    let recently_updated = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM circle_member_fans_monthly
+            WHERE viewer_id = $1
+              AND last_updated >= (CURRENT_TIMESTAMP - make_interval(secs => $2))::timestamp
+        )
+        "#,
+    )
+    .bind(params.viewer_id)
+    .bind(CIRCLE_REFRESH_COOLDOWN_SECS)
+    .fetch_one(&state.db)
+    .await?;
+
+    let existing_task_id = find_pending_task(
+        &state.db,
+        "fetch_circle",
+        "viewer_id",
+        &params.viewer_id.to_string(),
+    )
+    .await?;
+
+    let outcome = if recently_updated {
+        TaskOutcome::Throttled
+    } else if existing_task_id.is_some() {
+        TaskOutcome::Duplicate
+    } else {
+        TaskOutcome::TaskCreated
+    };
+
+    if params.dry_run {
+        return Ok(dry_run_response(outcome, "fetch_circle", existing_task_id));
+    }
+
+    if outcome == TaskOutcome::Throttled {
+        return Err(AppError::rate_limited(format!(
+            "Circle data for viewer {} was updated recently",
+            params.viewer_id
+        )));
+    }
+
+    let outcome = add_viewer_to_tasks(&state.db, params.viewer_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "outcome": outcome,
+        "task_created": outcome == TaskOutcome::TaskCreated
+    }))
+    .into_response())
 }
 
 /// GET /api/circles/list - List all circles with pagination and filtering
@@ -140,361 +286,1870 @@ pub async fn get_circle(
 /// Parameters:
 /// - page: Page number (0-indexed, default: 0)
 /// - limit: Results per page (default: 100, max: 100)
-/// - name: Filter by circle name (partial match, case-insensitive)
+/// - name: Filter by circle name (partial match, ignoring case and full/half-width differences)
 /// - min_members: Minimum member count
 /// - max_rank: Maximum monthly rank (lower is better, e.g., rank 1 is best)
 /// - sort_by: Field to sort by (name, member_count, monthly_rank, monthly_point)
 /// - sort_dir: Sort direction (asc, desc)
+/// - sort_locale: Collation used when sorting by name (ja, root; default: ja)
+/// - year, month: Show a past month's final standings instead of the live ones (competition months)
 ///
-/// Returns paginated list of circles
+/// Returns paginated list of circles, cached per query for a minute. The response carries
+/// an ETag over its content; requests with a matching If-None-Match get 304 Not Modified
+/// without a body.
 pub async fn list_circles(
     Query(params): Query<CircleListParams>,
     State(state): State<AppState>,
-) -> Result<Json<CircleListResponse>, AppError> {
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
     let page = params.page.unwrap_or(0);
     let limit = params.limit.unwrap_or(100).min(100);
     let offset = page * limit;
 
-    // Only calculate live ranks if we are NOT searching (or if explicitly requested)
-    // For search queries, we can rely on stored monthly_rank to avoid expensive window functions
-    // Use materialized view for live ranks (much faster than computing on every query)
-    let use_live_ranks = params.query.is_none();
+    let (total, circles) = state.storage.list_circles(&params, limit, offset).await?;
+
+    let circles_with_rank: Vec<CircleWithRank> = circles
+        .into_iter()
+        .map(|circle| CircleWithRank {
+            circle: crate::moderation::mask_circle(circle),
+        })
+        .collect();
 
-    let mut with_parts = Vec::new();
+    let total_pages = if limit > 0 {
+        ((total as f64) / (limit as f64)).ceil() as i64
+    } else {
+        0
+    };
 
-    // Note: We now use the circle_live_ranks materialized view instead of computing ranks
-    // The view should be refreshed periodically (e.g., every 5 minutes)
+    let body = serde_json::to_vec(&CircleListResponse {
+        circles: circles_with_rank,
+        total,
+        page,
+        limit,
+        total_pages,
+    })
+    .map_err(|e| AppError::DatabaseError(format!("Failed to serialize circle list: {}", e)))?;
 
-    // If search query is present, add MatchingCircles CTE to optimize search
-    let mut join_matching_circles = String::new();
-    
-    if let Some(query) = &params.query {
-        // Skip very short queries that would match too many results
-        let query_trimmed = query.trim();
-        if query_trimmed.len() >= 2 {
-            let search_pattern = format!("%{}%", query_trimmed.replace("'", "''"));
-            let search_exact = query_trimmed.replace("'", "''");
-            let is_number = query_trimmed.parse::<i64>().is_ok();
+    let etag = content_etag(&body);
+    // no-cache: clients may keep the list but must revalidate it on every use
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, "no-cache".to_string()),
+    ];
 
-        let mut union_parts = Vec::new();
+    if etag_matches(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
 
-        // 1. Search by Circle Name
-        union_parts.push(format!("SELECT circle_id FROM circles WHERE name ILIKE '{}'", search_pattern));
+    Ok((
+        cache_headers,
+        [(header::CONTENT_TYPE, "application/json")],
+        body,
+    )
+        .into_response())
+}
 
-        // 2. Search by Leader Name
-        union_parts.push(format!(
-            "SELECT c.circle_id FROM circles c JOIN trainer t ON c.leader_viewer_id::text = t.account_id WHERE t.name ILIKE '{}'", 
-            search_pattern
-        ));
+/// Strong ETag derived from a response body
+pub(crate) fn content_etag(body: &[u8]) -> String {
+    format!("\"{}\"", &hex::encode(Sha256::digest(body))[..32])
+}
 
-        // 3. Search by Member Name
-        union_parts.push(format!(
-            r#"
-            SELECT cm.circle_id 
-            FROM circle_member_fans_monthly cm 
-            JOIN trainer tm ON cm.viewer_id::text = tm.account_id 
-            WHERE cm.year = extract(year from CURRENT_TIMESTAMP AT TIME ZONE 'Asia/Tokyo')::int 
-              AND cm.month = extract(month from CURRENT_TIMESTAMP AT TIME ZONE 'Asia/Tokyo')::int 
-              AND tm.name ILIKE '{}'
-            "#,
-            search_pattern
-        ));
+/// Whether If-None-Match lists `etag` (weak comparison, as RFC 9110 requires for GET)
+pub(crate) fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let Some(if_none_match) = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return false;
+    };
 
-        if is_number {
-            // 4. Search by Circle ID
-            union_parts.push(format!("SELECT circle_id FROM circles WHERE circle_id = {}", search_exact));
-            
-            // 5. Search by Leader ID
-            union_parts.push(format!("SELECT circle_id FROM circles WHERE leader_viewer_id = {}", search_exact));
-            
-            // 6. Search by Member ID
-            union_parts.push(format!(
-                r#"
-                SELECT circle_id 
-                FROM circle_member_fans_monthly 
-                WHERE viewer_id = {} 
-                  AND year = extract(year from CURRENT_TIMESTAMP AT TIME ZONE 'Asia/Tokyo')::int 
-                  AND month = extract(month from CURRENT_TIMESTAMP AT TIME ZONE 'Asia/Tokyo')::int
-                "#,
-                search_exact
-            ));
-        }
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.trim_start_matches("W/") == etag
+    })
+}
+
+/// Total matches and one page of the circle list
+///
+/// Unfiltered requests in the default order are served from the leaderboard
+/// snapshot while it is fresh; everything else queries the database.
+pub(crate) async fn query_circle_list(
+    pool: &PgPool,
+    params: &CircleListParams,
+    limit: i64,
+    offset: i64,
+) -> Result<(i64, Vec<Circle>), AppError> {
+    if let Some(page) = leaderboard_page(params, limit, offset) {
+        return Ok(page);
+    }
+    query_live_circle_list(pool, params, limit, offset).await
+}
+
+/// Unfiltered circle list in default order, rebuilt by the leaderboard snapshot task
+struct LeaderboardSnapshot {
+    total: i64,
+    /// The first LEADERBOARD_SNAPSHOT_SIZE circles
+    circles: Vec<Circle>,
+    built_at: Instant,
+}
+
+static LEADERBOARD: OnceLock<RwLock<Option<Arc<LeaderboardSnapshot>>>> = OnceLock::new();
+
+fn get_leaderboard() -> &'static RwLock<Option<Arc<LeaderboardSnapshot>>> {
+    LEADERBOARD.get_or_init(|| RwLock::new(None))
+}
 
-        with_parts.push(format!("MatchingCircles AS ({})", union_parts.join(" UNION ")));
+/// Rebuild the leaderboard snapshot, returning the number of circles it holds
+pub(crate) async fn refresh_leaderboard_snapshot(pool: &PgPool) -> Result<usize, AppError> {
+    let (total, circles) = query_live_circle_list(
+        pool,
+        &CircleListParams::default(),
+        LEADERBOARD_SNAPSHOT_SIZE,
+        0,
+    )
+    .await?;
+    let size = circles.len();
 
-        join_matching_circles = "INNER JOIN MatchingCircles mc ON c.circle_id = mc.circle_id".to_string();
-        } // end of query_trimmed.len() >= 2 check
+    if let Ok(mut leaderboard) = get_leaderboard().write() {
+        *leaderboard = Some(Arc::new(LeaderboardSnapshot {
+            total,
+            circles,
+            built_at: Instant::now(),
+        }));
     }
+    Ok(size)
+}
 
-    let with_clause = if with_parts.is_empty() {
-        String::new()
-    } else {
-        format!("WITH {}", with_parts.join(", "))
-    };
+/// A page from the leaderboard snapshot, if the request is unfiltered and the snapshot covers it
+fn leaderboard_page(params: &CircleListParams, limit: i64, offset: i64) -> Option<(i64, Vec<Circle>)> {
+    let default_order = matches!(params.sort_by.as_deref(), None | Some("rank") | Some("monthly_rank"))
+        && params
+            .sort_dir
+            .as_deref()
+            .is_none_or(|dir| !dir.eq_ignore_ascii_case("desc"));
+    let unfiltered = params.name.is_none()
+        && params.query.is_none()
+        && params.min_members.is_none()
+        && params.max_rank.is_none()
+        && params.year.is_none()
+        && params.month.is_none();
+    if !default_order || !unfiltered || limit < 0 || offset < 0 {
+        return None;
+    }
 
-    // Use materialized view instead of CTE for live ranks
-    let join_global_ranks = if use_live_ranks {
-        "LEFT JOIN circle_live_ranks gr ON c.circle_id = gr.circle_id"
-    } else {
-        ""
-    };
+    let snapshot = get_leaderboard().read().ok()?.clone()?;
+    if snapshot.built_at.elapsed() > LEADERBOARD_SNAPSHOT_MAX_AGE {
+        return None;
+    }
 
-    let rank_column = if use_live_ranks {
-        "COALESCE(gr.live_rank::integer, c.monthly_rank)"
-    } else {
-        "c.monthly_rank"
+    let held = snapshot.circles.len() as i64;
+    if offset.saturating_add(limit) > held && held < snapshot.total {
+        return None;
+    }
+
+    let circles = snapshot
+        .circles
+        .iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .cloned()
+        .collect();
+    Some((snapshot.total, circles))
+}
+
+async fn query_live_circle_list(
+    pool: &PgPool,
+    params: &CircleListParams,
+    limit: i64,
+    offset: i64,
+) -> Result<(i64, Vec<Circle>), AppError> {
+    let period = resolve_list_period(params.year, params.month)?;
+
+    // Only calculate live ranks if we are NOT searching (or if explicitly requested)
+    // For search queries, we can rely on stored monthly_rank to avoid expensive window functions
+    // Use materialized view for live ranks (much faster than computing on every query)
+    // Past months have no live ranks
+    let use_live_ranks = params.query.is_none() && period == CircleListPeriod::Current;
+
+    // Skip very short queries that would match too many results
+    let search_query = params
+        .query
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| q.len() >= 2);
+
+    let (rank_column, point_column, yesterday_rank_column) = match period {
+        CircleListPeriod::Current if use_live_ranks => (
+            "COALESCE(gr.live_rank::integer, c.monthly_rank)".to_string(),
+            "c.monthly_point".to_string(),
+            "COALESCE(gr.live_yesterday_rank::integer, c.yesterday_rank)".to_string(),
+        ),
+        CircleListPeriod::Current => (
+            "c.monthly_rank".to_string(),
+            "c.monthly_point".to_string(),
+            "c.yesterday_rank".to_string(),
+        ),
+        // Circles updated since the reset carry last month in last_month_*,
+        // circles last updated before it still hold it in monthly_*
+        CircleListPeriod::PreviousMonth => (
+            format!(
+                "CASE WHEN c.last_updated >= {} THEN c.last_month_rank ELSE c.monthly_rank END",
+                competition::month_start_sql(0)
+            ),
+            format!(
+                "CASE WHEN c.last_updated >= {} THEN c.last_month_point ELSE c.monthly_point END",
+                competition::month_start_sql(0)
+            ),
+            "NULL::integer".to_string(),
+        ),
+        CircleListPeriod::History { .. } => (
+            "hr.monthly_rank".to_string(),
+            "hr.monthly_point".to_string(),
+            "NULL::integer".to_string(),
+        ),
     };
 
-    let yesterday_rank_column = if use_live_ranks {
-        "COALESCE(gr.live_yesterday_rank::integer, c.yesterday_rank)"
+    // Projections only make sense while the month is still running
+    let projected_point_column = if period == CircleListPeriod::Current {
+        "circle_projected_point(c.circle_id, c.monthly_point)"
     } else {
-        "c.yesterday_rank"
+        "NULL::bigint"
     };
 
-    // Build dynamic query
-    let mut count_query = format!(
-        "{} SELECT COUNT(*) FROM circles c {} LEFT JOIN trainer t ON c.leader_viewer_id::text = t.account_id {} WHERE 1=1", 
-        with_clause, 
-        join_global_ranks,
-        join_matching_circles
-    );
-    
-    let mut select_query = format!(
+    // Get total count
+    let mut count_query: QueryBuilder<Postgres> = QueryBuilder::new("");
+    push_circle_list_ctes(&mut count_query, search_query, period);
+    count_query.push(" SELECT COUNT(*) FROM circles c");
+    push_circle_list_joins(&mut count_query, use_live_ranks, search_query.is_some(), period);
+    push_circle_list_conditions(&mut count_query, params, period, &rank_column, &point_column);
+
+    let total: i64 = count_query
+        .build_query_scalar()
+        .fetch_one(pool)
+        .await?;
+
+    let mut select_query: QueryBuilder<Postgres> = QueryBuilder::new("");
+    push_circle_list_ctes(&mut select_query, search_query, period);
+    select_query.push(format!(
         r#"
-        {}
         SELECT 
             c.circle_id,
-            c.name,
-            c.comment,
+            COALESCE(mo.name_override, c.name) as name,
+            CASE WHEN mo.hide_comment THEN NULL ELSE COALESCE(mo.comment_override, c.comment) END as comment,
             c.leader_viewer_id,
-            t.name as leader_name,
+            COALESCE(t.display_name_override, t.name) as leader_name,
             c.member_count,
             c.join_style,
             c.policy,
             c.created_at,
             c.last_updated,
             {} as monthly_rank,
-            c.monthly_point,
+            {} as monthly_point,
             c.last_month_rank,
             c.last_month_point,
             c.archived,
             c.yesterday_updated,
             c.yesterday_points,
-            {} as yesterday_rank
+            {} as yesterday_rank,
+            {} as projected_point
         FROM circles c
-        {}
-        LEFT JOIN trainer t ON c.leader_viewer_id::text = t.account_id
-        {}
-        WHERE 1=1
         "#,
-        with_clause,
-        rank_column,
-        yesterday_rank_column,
-        join_global_ranks,
-        join_matching_circles
-    );
-
-    let mut conditions = Vec::new();
-
-    // Only show circles updated this month to ensure points are current
-    conditions.push("c.last_updated >= ((date_trunc('month', CURRENT_TIMESTAMP AT TIME ZONE 'Asia/Tokyo') + interval '12 hours') AT TIME ZONE 'Asia/Tokyo') AT TIME ZONE 'Europe/Berlin'".to_string());
-    conditions.push("c.last_updated < ((date_trunc('month', CURRENT_TIMESTAMP AT TIME ZONE 'Asia/Tokyo') + interval '1 month' + interval '12 hours') AT TIME ZONE 'Asia/Tokyo') AT TIME ZONE 'Europe/Berlin'".to_string());
-    // Exclude archived circles
-    conditions.push("(c.archived IS NULL OR c.archived = false)".to_string());
-
-    // Name filter
-    if let Some(name) = &params.name {
-        conditions.push(format!("c.name ILIKE '%{}%'", name.replace("'", "''")));
-    }
-
-    // General Search Query - handled by CTE now, no extra conditions needed here
-    // But we keep the parameter check to avoid unused variable warning if we removed it completely
-    // (Actually we used it above to build CTE)
-
-    // Min members filter
-    if let Some(min_members) = params.min_members {
-        conditions.push(format!("c.member_count >= {}", min_members));
-    }
-
-    // Max rank filter (lower rank number is better)
-    if let Some(max_rank) = params.max_rank {
-        // Use the calculated rank for filtering
-        conditions.push(format!("COALESCE(gr.live_rank, c.monthly_rank) <= {}", max_rank));
-    }
-
-    // Add conditions to queries
-    for condition in &conditions {
-        count_query.push_str(&format!(" AND {}", condition));
-        select_query.push_str(&format!(" AND {}", condition));
-    }
-
-    // Get total count
-    let total: i64 = sqlx::query_scalar(&count_query)
-        .fetch_one(&state.db)
-        .await?;
+        rank_column, point_column, yesterday_rank_column, projected_point_column
+    ));
+    push_circle_list_joins(&mut select_query, use_live_ranks, search_query.is_some(), period);
+    select_query.push(" LEFT JOIN circle_moderation_overrides mo ON mo.circle_id = c.circle_id");
+    push_circle_list_conditions(&mut select_query, params, period, &rank_column, &point_column);
 
     // Add sorting
     let sort_by = params.sort_by.as_deref().unwrap_or("rank");
-    let sort_dir = params.sort_dir.as_deref().unwrap_or("asc");
+    let sort_dir = if params
+        .sort_dir
+        .as_deref()
+        .unwrap_or("asc")
+        .eq_ignore_ascii_case("desc")
+    {
+        "DESC"
+    } else {
+        "ASC"
+    };
+
+    // ICU collations from the normalized names migration
+    let name_collation = match params.sort_locale.as_deref() {
+        Some("root") => "name_root",
+        _ => "name_ja",
+    };
 
     let order_clause = match sort_by {
-        "name" => format!(" ORDER BY c.name {}, c.circle_id ASC", sort_dir.to_uppercase()),
+        "name" => format!(
+            " ORDER BY c.name COLLATE {} {}, c.circle_id ASC",
+            name_collation, sort_dir
+        ),
         "member_count" => format!(
             " ORDER BY c.member_count {} NULLS LAST, c.circle_id ASC",
-            sort_dir.to_uppercase()
+            sort_dir
         ),
         "rank" | "monthly_rank" => format!(
             " ORDER BY monthly_rank {} NULLS LAST, c.circle_id ASC",
-            sort_dir.to_uppercase()
+            sort_dir
         ),
         "monthly_point" => format!(
-            " ORDER BY c.monthly_point {} NULLS LAST, c.circle_id ASC",
-            sort_dir.to_uppercase()
+            " ORDER BY monthly_point {} NULLS LAST, c.circle_id ASC",
+            sort_dir
         ),
         _ => " ORDER BY monthly_rank ASC NULLS LAST, c.circle_id ASC".to_string(),
     };
 
-    select_query.push_str(&order_clause);
-    select_query.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
+    select_query.push(order_clause);
+    select_query.push(" LIMIT ");
+    select_query.push_bind(limit);
+    select_query.push(" OFFSET ");
+    select_query.push_bind(offset);
 
     // Execute query
-    let circles = sqlx::query_as::<_, Circle>(&select_query)
-        .fetch_all(&state.db)
+    let circles = select_query
+        .build_query_as::<Circle>()
+        .fetch_all(pool)
         .await?;
 
-    let circles_with_rank: Vec<CircleWithRank> = circles
-        .into_iter()
-        .map(|circle| CircleWithRank { circle })
-        .collect();
+    Ok((total, circles))
+}
 
-    let total_pages = if limit > 0 {
-        ((total as f64) / (limit as f64)).ceil() as i64
-    } else {
-        0
+/// Pick the standings period for the requested year/month (competition timezone)
+fn resolve_list_period(year: Option<i32>, month: Option<u32>) -> Result<CircleListPeriod, AppError> {
+    let (year, month) = match (year, month) {
+        (None, None) => return Ok(CircleListPeriod::Current),
+        (Some(year), Some(month)) if (1..=12).contains(&month) => (year, month),
+        (Some(_), Some(_)) => {
+            return Err(AppError::BadRequest("month must be between 1 and 12".to_string()))
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "year and month must be provided together".to_string(),
+            ))
+        }
     };
 
-    Ok(Json(CircleListResponse {
-        circles: circles_with_rank,
-        total,
-        page,
-        limit,
-        total_pages,
-    }))
+    let (current_year, current_month) = competition::current_month();
+    let requested = year * 12 + month as i32;
+    let current = current_year * 12 + current_month;
+
+    match current - requested {
+        0 => Ok(CircleListPeriod::Current),
+        1 => Ok(CircleListPeriod::PreviousMonth),
+        n if n > 1 => Ok(CircleListPeriod::History { year, month }),
+        _ => Err(AppError::BadRequest(format!(
+            "No standings for future month {}-{:02}",
+            year, month
+        ))),
+    }
 }
 
-/// Fetch circle by ID
-async fn fetch_circle_by_id(pool: &PgPool, circle_id: i64) -> Result<Circle, AppError> {
-    let circle = sqlx::query_as::<_, Circle>(
-        r#"
-        SELECT 
-            c.circle_id,
-            c.name,
-            c.comment,
-            c.leader_viewer_id,
-            t.name as leader_name,
-            c.member_count,
-            c.join_style,
-            c.policy,
-            c.created_at,
-            c.last_updated,
-            c.monthly_rank,
-            c.monthly_point,
-            c.last_month_rank,
-            c.last_month_point,
-            c.archived,
-            c.yesterday_updated,
-            c.yesterday_points,
-            c.yesterday_rank
-        FROM circles c
-        LEFT JOIN trainer t ON c.leader_viewer_id::text = t.account_id
-        WHERE c.circle_id = $1
-        "#,
-    )
-    .bind(circle_id)
-    .fetch_optional(pool)
-    .await?
-    .ok_or_else(|| AppError::NotFound(format!("Circle {} not found", circle_id)))?;
+/// CTEs for the circle list: MatchingCircles for the general search query and
+/// HistoryRanks for months served from circle_rank_history
+fn push_circle_list_ctes(
+    query_builder: &mut QueryBuilder<'_, Postgres>,
+    search_query: Option<&str>,
+    period: CircleListPeriod,
+) {
+    let mut has_cte = false;
+
+    if let CircleListPeriod::History { year, month } = period {
+        // Last snapshot of the month per circle
+        query_builder.push(
+            r#"WITH HistoryRanks AS (
+                SELECT DISTINCT ON (circle_id) circle_id, monthly_rank, monthly_point
+                FROM circle_rank_history
+                WHERE snapshot_date >= make_date("#,
+        );
+        query_builder.push_bind(year);
+        query_builder.push(", ");
+        query_builder.push_bind(month as i32);
+        query_builder.push(
+            r#", 1)
+                  AND snapshot_date < make_date("#,
+        );
+        query_builder.push_bind(year);
+        query_builder.push(", ");
+        query_builder.push_bind(month as i32);
+        query_builder.push(
+            r#", 1) + interval '1 month'
+                ORDER BY circle_id, snapshot_date DESC
+            )"#,
+        );
+        has_cte = true;
+    }
 
-    Ok(circle)
+    if let Some(search_query) = search_query {
+        query_builder.push(if has_cte { ", " } else { "WITH " });
+        push_matching_circles_cte(query_builder, search_query);
+    }
 }
 
-/// Fetch all members and their fan counts for a circle
-async fn fetch_circle_members(
-    pool: &PgPool,
-    circle_id: i64,
-    year: Option<i32>,
-    month: Option<i32>,
-) -> Result<Vec<CircleMemberFansMonthly>, AppError> {
-    use chrono::{Datelike, FixedOffset, Utc};
-    
-    // Default to current date (JST) if not provided
-    let (target_year, target_month) = if year.is_none() || month.is_none() {
-        let jst_offset = FixedOffset::east_opt(9 * 3600).unwrap();
-        let now = Utc::now().with_timezone(&jst_offset);
-        (
-            year.unwrap_or(now.year()),
-            month.unwrap_or(now.month() as i32)
-        )
-    } else {
-        (year.unwrap(), month.unwrap())
-    };
+/// MatchingCircles CTE for the general search query (circle ID/name, leader ID/name, member ID/name)
+fn push_matching_circles_cte(query_builder: &mut QueryBuilder<'_, Postgres>, search_query: &str) {
+    let search_pattern = format!("%{}%", search_query);
+
+    query_builder.push("MatchingCircles AS (");
+
+    // 1. Search by Circle Name
+    query_builder.push("SELECT circle_id FROM circles WHERE name_normalized LIKE lower(normalize(");
+    query_builder.push_bind(search_pattern.clone());
+    query_builder.push(", NFKC))");
 
-    // PostgreSQL returns integer arrays as Vec<i32>, but query_as! infers Vec<i64>
-    // We need to handle the conversion manually
-    let records = sqlx::query!(
+    // 2. Search by Leader Name
+    query_builder.push(
+        " UNION SELECT c.circle_id FROM circles c JOIN trainer t ON c.leader_viewer_id::text = t.account_id WHERE t.name_normalized LIKE lower(normalize(",
+    );
+    query_builder.push_bind(search_pattern.clone());
+    query_builder.push(", NFKC))");
+
+    // 3. Search by Member Name
+    let (current_year, current_month) = competition::current_month();
+    query_builder.push(
         r#"
-        SELECT 
-            cm.id,
-            cm.circle_id,
-            cm.viewer_id,
-            t.name as "trainer_name?",
-            cm.year,
-            cm.month,
-            cm.daily_fans,
-            cm.last_updated
-        FROM circle_member_fans_monthly cm
-        LEFT JOIN trainer t ON cm.viewer_id::text = t.account_id
-        WHERE cm.circle_id = $1 AND cm.year = $2 AND cm.month = $3
-        ORDER BY cm.viewer_id
-        "#,
-        circle_id,
-        target_year,
-        target_month
-    )
-    .fetch_all(pool)
-    .await?;
+        UNION
+        SELECT cm.circle_id 
+        FROM circle_member_fans_monthly cm 
+        JOIN trainer tm ON cm.viewer_id::text = tm.account_id 
+        WHERE cm.year = "#,
+    );
+    query_builder.push_bind(current_year);
+    query_builder.push(" AND cm.month = ");
+    query_builder.push_bind(current_month);
+    query_builder.push(" AND tm.name_normalized LIKE lower(normalize(");
+    query_builder.push_bind(search_pattern);
+    query_builder.push(", NFKC))");
 
-    let members = records
-        .into_iter()
-        .map(|rec| CircleMemberFansMonthly {
-            id: rec.id,
-            circle_id: rec.circle_id,
-            viewer_id: rec.viewer_id,
-            trainer_name: rec.trainer_name,
-            year: rec.year,
-            month: rec.month,
-            daily_fans: rec.daily_fans.into_iter().map(|v| v as i32).collect(),
-            last_updated: rec.last_updated,
-        })
-        .collect();
+    if let Ok(search_id) = search_query.parse::<i64>() {
+        // 4. Search by Circle ID
+        query_builder.push(" UNION SELECT circle_id FROM circles WHERE circle_id = ");
+        query_builder.push_bind(search_id);
 
-    Ok(members)
+        // 5. Search by Leader ID
+        query_builder.push(" UNION SELECT circle_id FROM circles WHERE leader_viewer_id = ");
+        query_builder.push_bind(search_id);
+
+        // 6. Search by Member ID
+        query_builder.push(
+            r#"
+            UNION
+            SELECT circle_id 
+            FROM circle_member_fans_monthly 
+            WHERE year = "#,
+        );
+        query_builder.push_bind(current_year);
+        query_builder.push(" AND month = ");
+        query_builder.push_bind(current_month);
+        query_builder.push(" AND viewer_id = ");
+        query_builder.push_bind(search_id);
+    }
+
+    query_builder.push(")");
 }
 
-/// Add a viewer to the tasks queue for later fetching
-async fn add_viewer_to_tasks(pool: &PgPool, viewer_id: i64) -> Result<(), AppError> {
-    // Insert into tasks table with viewer_id in task_data
-    // account_id is for the worker that processes the task, so we leave it NULL
-    sqlx::query!(
-        r#"
-        INSERT INTO tasks (task_type, task_data, status, created_at, updated_at)
-        VALUES ('fetch_circle', $1, 'pending', NOW(), NOW())
-        ON CONFLICT DO NOTHING
-        "#,
-        serde_json::json!({ "viewer_id": viewer_id })
-    )
-    .execute(pool)
-    .await?;
+/// Joins shared by the circle list count and select queries
+fn push_circle_list_joins(
+    query_builder: &mut QueryBuilder<'_, Postgres>,
+    use_live_ranks: bool,
+    has_search_query: bool,
+    period: CircleListPeriod,
+) {
+    // Use materialized view instead of CTE for live ranks
+    if use_live_ranks {
+        query_builder.push(" LEFT JOIN circle_live_ranks gr ON c.circle_id = gr.circle_id");
+    }
+    query_builder.push(" LEFT JOIN trainer t ON c.leader_viewer_id::text = t.account_id");
+    if has_search_query {
+        query_builder.push(" INNER JOIN MatchingCircles mc ON c.circle_id = mc.circle_id");
+    }
+    if matches!(period, CircleListPeriod::History { .. }) {
+        query_builder.push(" INNER JOIN HistoryRanks hr ON c.circle_id = hr.circle_id");
+    }
+}
 
-    Ok(())
+/// WHERE clause shared by the circle list count and select queries
+fn push_circle_list_conditions(
+    query_builder: &mut QueryBuilder<'_, Postgres>,
+    params: &CircleListParams,
+    period: CircleListPeriod,
+    rank_column: &str,
+    point_column: &str,
+) {
+    // Exclude archived circles
+    query_builder.push(" WHERE (c.archived IS NULL OR c.archived = false)");
+
+    match period {
+        CircleListPeriod::Current => {
+            // Only show circles updated this month to ensure points are current
+            query_builder.push(format!(" AND c.last_updated >= {}", competition::month_start_sql(0)));
+            query_builder.push(format!(" AND c.last_updated < {}", competition::month_start_sql(1)));
+        }
+        CircleListPeriod::PreviousMonth => {
+            query_builder.push(format!(" AND c.last_updated >= {}", competition::month_start_sql(-1)));
+            query_builder.push(format!(" AND {} IS NOT NULL", point_column));
+        }
+        // HistoryRanks join already limits to circles with a snapshot that month
+        CircleListPeriod::History { .. } => {}
+    }
+
+    // Name filter
+    if let Some(name) = &params.name {
+        query_builder.push(" AND c.name_normalized LIKE lower(normalize(");
+        query_builder.push_bind(format!("%{}%", name));
+        query_builder.push(", NFKC))");
+    }
+
+    // Min members filter
+    if let Some(min_members) = params.min_members {
+        query_builder.push(" AND c.member_count >= ");
+        query_builder.push_bind(min_members);
+    }
+
+    // Max rank filter (lower rank number is better)
+    if let Some(max_rank) = params.max_rank {
+        query_builder.push(format!(" AND {} <= ", rank_column));
+        query_builder.push_bind(max_rank);
+    }
+}
+
+/// GET /api/v4/circles/{circle_id}/history - Daily monthly_point/monthly_rank time series
+/// Parameters:
+/// - from: First day to include (YYYY-MM-DD), defaults to 30 days before `to`
+/// - to: Last day to include (YYYY-MM-DD), defaults to today
+pub async fn get_circle_history(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    Query(params): Query<CircleHistoryParams>,
+) -> Result<Json<CircleHistoryResponse>, AppError> {
+    let to = params.to.unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let from = params.from.unwrap_or(to - chrono::Duration::days(30));
+
+    if from > to {
+        return Err(AppError::BadRequest("from must not be after to".to_string()));
+    }
+    if (to - from).num_days() > MAX_HISTORY_DAYS {
+        return Err(AppError::BadRequest(format!(
+            "Date range must not exceed {} days",
+            MAX_HISTORY_DAYS
+        )));
+    }
+
+    let history = sqlx::query_as::<_, CircleRankSnapshot>(
+        r#"
+        SELECT snapshot_date, monthly_rank, monthly_point, member_count
+        FROM circle_rank_history
+        WHERE circle_id = $1 AND snapshot_date BETWEEN $2 AND $3
+        ORDER BY snapshot_date
+        "#,
+    )
+    .bind(circle_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(state.read_db())
+    .await?;
+
+    Ok(Json(CircleHistoryResponse {
+        circle_id,
+        from,
+        to,
+        history,
+    }))
+}
+
+/// GET /api/v4/circles/{circle_id}/member-events - Member joins and leaves, newest first
+/// Parameters:
+/// - page: Page number (0-indexed)
+/// - limit: Results per page (default 50, max 200)
+pub async fn get_circle_member_events(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    Query(params): Query<CircleMemberEventsParams>,
+) -> Result<Json<CircleMemberEventsResponse>, AppError> {
+    let page = params.page.unwrap_or(0).max(0);
+    let limit = params.limit.unwrap_or(50).clamp(1, 200);
+
+    let events = sqlx::query_as::<_, CircleMemberEvent>(
+        r#"
+        SELECT e.viewer_id, COALESCE(t.display_name_override, t.name) as trainer_name,
+               e.event_type, e.detected_at
+        FROM circle_member_events e
+        LEFT JOIN trainer t ON t.account_id = e.viewer_id::text
+        WHERE e.circle_id = $1
+        ORDER BY e.detected_at DESC, e.id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(circle_id)
+    .bind(limit)
+    .bind(page * limit)
+    .fetch_all(state.read_db())
+    .await?;
+
+    Ok(Json(CircleMemberEventsResponse {
+        circle_id,
+        events,
+        page,
+        limit,
+    }))
+}
+
+/// GET /api/v4/circles/{circle_id}/stats - Member fan statistics for a month
+///
+/// Parameters:
+/// - year, month: Month of member fan data (default: current competition month)
+pub async fn get_circle_stats(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    Query(params): Query<CircleStatsParams>,
+) -> Result<Json<CircleStats>, AppError> {
+    use sqlx::Row;
+
+    ensure_circle_exists(state.read_db(), circle_id).await?;
+    let (year, month) = resolve_member_month(params.year, params.month);
+
+    let summary = sqlx::query(&format!(
+        r#"
+        WITH {},
+        latest AS (
+            SELECT MAX(d) AS d FROM member_days WHERE gain IS NOT NULL
+        ),
+        day_totals AS (
+            SELECT d, SUM(gain)::bigint AS fans
+            FROM member_days
+            WHERE gain IS NOT NULL
+            GROUP BY d
+        )
+        SELECT
+            (SELECT COUNT(*) FROM circle_member_fans_monthly
+             WHERE circle_id = $1 AND year = $2 AND month = $3) AS member_count,
+            (SELECT COUNT(*) FROM member_days md, latest
+             WHERE md.d = latest.d AND md.gain > 0) AS active_members,
+            (SELECT COALESCE(AVG(avg_daily_fans), 0) FROM members) AS avg_daily_fans,
+            (SELECT COALESCE(percentile_cont(0.5) WITHIN GROUP (ORDER BY avg_daily_fans), 0)
+             FROM members) AS median_daily_fans,
+            (SELECT COALESCE(SUM(fans), 0)::bigint FROM day_totals, latest
+             WHERE day_totals.d = latest.d) AS today_fans,
+            (SELECT fans FROM day_totals, latest
+             WHERE day_totals.d < latest.d
+             ORDER BY day_totals.d DESC
+             LIMIT 1) AS yesterday_fans
+        "#,
+        CIRCLE_MEMBER_DAYS_CTE
+    ))
+    .bind(circle_id)
+    .bind(year)
+    .bind(month)
+    .fetch_one(state.read_db())
+    .await?;
+
+    // Circles have at most 30 members, so rank them all and take both ends
+    let contributors = sqlx::query_as::<_, CircleContributor>(&format!(
+        r#"
+        WITH {}
+        SELECT m.viewer_id, COALESCE(t.display_name_override, t.name) AS trainer_name, m.month_fans
+        FROM members m
+        LEFT JOIN trainer t ON m.viewer_id::text = t.account_id
+        ORDER BY m.month_fans DESC, m.viewer_id ASC
+        "#,
+        CIRCLE_MEMBER_DAYS_CTE
+    ))
+    .bind(circle_id)
+    .bind(year)
+    .bind(month)
+    .fetch_all(state.read_db())
+    .await?;
+
+    let mut contributors: Vec<CircleContributor> = contributors
+        .into_iter()
+        .map(|mut contributor| {
+            contributor.trainer_name = contributor
+                .trainer_name
+                .as_deref()
+                .map(crate::moderation::mask_text);
+            contributor
+        })
+        .collect();
+    let bottom_contributor = if contributors.len() > 1 {
+        contributors.pop()
+    } else {
+        None
+    };
+    let top_contributor = contributors.into_iter().next();
+
+    let today_fans: i64 = summary.get("today_fans");
+    let yesterday_fans: Option<i64> = summary.get("yesterday_fans");
+    let day_over_day_growth = yesterday_fans
+        .filter(|&yesterday| yesterday > 0)
+        .map(|yesterday| (today_fans - yesterday) as f64 / yesterday as f64 * 100.0);
+
+    Ok(Json(CircleStats {
+        circle_id,
+        year,
+        month,
+        member_count: summary.get("member_count"),
+        active_members: summary.get("active_members"),
+        avg_daily_fans: summary.get("avg_daily_fans"),
+        median_daily_fans: summary.get("median_daily_fans"),
+        top_contributor,
+        bottom_contributor,
+        today_fans,
+        yesterday_fans: yesterday_fans.unwrap_or(0),
+        day_over_day_growth,
+    }))
+}
+
+/// GET /api/v4/circles/{circle_id}/awards - Member awards of a finished month
+///
+/// Awards are computed by the monthly rollup job once a month is over.
+/// Parameters:
+/// - month: Month (1-12, default: last competition month)
+/// - year: Year
+pub async fn get_circle_awards(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    Query(params): Query<CircleAwardsParams>,
+) -> Result<Json<CircleAwardsResponse>, AppError> {
+    let (year, month) = match (params.year, params.month) {
+        (Some(year), Some(month)) => (year, month),
+        (None, None) => competition::previous_month(),
+        _ => {
+            return Err(AppError::BadRequest(
+                "year and month must be given together".to_string(),
+            ))
+        }
+    };
+    if !(1..=12).contains(&month) {
+        return Err(AppError::BadRequest("month must be between 1 and 12".to_string()));
+    }
+
+    ensure_circle_exists(state.read_db(), circle_id).await?;
+
+    let awards = sqlx::query_as::<_, CircleAward>(
+        r#"
+        SELECT a.award, a.viewer_id, COALESCE(t.display_name_override, t.name) AS trainer_name,
+               a.value, a.award_date, a.computed_at
+        FROM circle_awards a
+        LEFT JOIN trainer t ON a.viewer_id::text = t.account_id
+        WHERE a.circle_id = $1 AND a.year = $2 AND a.month = $3
+        ORDER BY a.award
+        "#,
+    )
+    .bind(circle_id)
+    .bind(year)
+    .bind(month)
+    .fetch_all(state.read_db())
+    .await?
+    .into_iter()
+    .map(|mut award| {
+        award.trainer_name = award.trainer_name.as_deref().map(crate::moderation::mask_text);
+        award
+    })
+    .collect();
+
+    Ok(Json(CircleAwardsResponse {
+        circle_id,
+        year,
+        month,
+        awards,
+    }))
+}
+
+/// Compute (or recompute) the member awards of every circle for one month.
+/// Returns the number of awards stored.
+pub(crate) async fn compute_circle_awards(
+    pool: &PgPool,
+    year: i32,
+    month: i32,
+) -> Result<u64, AppError> {
+    let mut tx = crate::database::begin_long_running(pool).await?;
+
+    // Replace the whole month so awards that no longer qualify don't linger
+    sqlx::query("DELETE FROM circle_awards WHERE year = $1 AND month = $2")
+        .bind(year)
+        .bind(month)
+        .execute(&mut *tx)
+        .await?;
+
+    // Same gain calculation as CIRCLE_MEMBER_DAYS_CTE, across all circles
+    let result = sqlx::query(
+        r#"
+        WITH member_days AS (
+            SELECT
+                cm.circle_id,
+                cm.viewer_id,
+                x.d,
+                x.f - lag(x.f) OVER (PARTITION BY cm.circle_id, cm.viewer_id ORDER BY x.d) AS gain,
+                x.d - lag(x.d) OVER (PARTITION BY cm.circle_id, cm.viewer_id ORDER BY x.d) AS gap
+            FROM circle_member_fans_monthly cm
+            CROSS JOIN LATERAL unnest(cm.daily_fans) WITH ORDINALITY AS x(f, d)
+            WHERE cm.year = $1 AND cm.month = $2 AND x.f > 0
+        ),
+        members AS (
+            SELECT
+                circle_id,
+                viewer_id,
+                SUM(gain)::bigint AS month_fans,
+                COUNT(*) FILTER (WHERE gain > 0) AS active_days,
+                COALESCE(stddev_pop(gain), 0) AS gain_stddev
+            FROM member_days
+            WHERE gain IS NOT NULL
+            GROUP BY circle_id, viewer_id
+        ),
+        awards AS (
+            (SELECT DISTINCT ON (circle_id)
+                circle_id, 'most_fans' AS award, viewer_id, month_fans AS value, NULL::date AS award_date
+            FROM members
+            WHERE month_fans > 0
+            ORDER BY circle_id, month_fans DESC, viewer_id)
+            UNION ALL
+            (SELECT DISTINCT ON (circle_id)
+                circle_id, 'most_consistent', viewer_id, active_days, NULL::date
+            FROM members
+            WHERE active_days > 0
+            ORDER BY circle_id, active_days DESC, gain_stddev ASC, viewer_id)
+            UNION ALL
+            (SELECT DISTINCT ON (circle_id)
+                circle_id, 'biggest_day', viewer_id, gain::bigint, make_date($1, $2, 1) + (d - 1)::int
+            FROM member_days
+            -- Gains spanning days without data aren't a single day's gain
+            WHERE gain > 0 AND gap = 1
+            ORDER BY circle_id, gain DESC, d, viewer_id)
+        )
+        INSERT INTO circle_awards (circle_id, year, month, award, viewer_id, value, award_date, computed_at)
+        SELECT a.circle_id, $1, $2, a.award, a.viewer_id, a.value, a.award_date, CURRENT_TIMESTAMP
+        FROM awards a
+        JOIN circles c ON c.circle_id = a.circle_id
+        "#,
+    )
+    .bind(year)
+    .bind(month)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected())
+}
+
+/// GET /api/v4/circles/members/{viewer_id}/history - A member's daily fans across months
+///
+/// Stitches the member's daily_fans rows from every stored month (and circle)
+/// into one continuous series; days without data have null counts.
+/// Parameters:
+/// - from, to: Date range, limited to the recorded days (default: everything recorded)
+pub async fn get_member_fan_history(
+    State(state): State<AppState>,
+    Path(viewer_id): Path<ViewerId>,
+    Query(params): Query<MemberFanHistoryParams>,
+) -> Result<Json<MemberFanHistoryResponse>, AppError> {
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from > to {
+            return Err(AppError::BadRequest("from must not be after to".to_string()));
+        }
+    }
+
+    // daily_fans[d] is the cumulative count on day d of the month (0 = no data).
+    // A member who switched circles mid-month has two rows; keep the higher count.
+    let days = sqlx::query_as::<_, MemberFanDay>(
+        r#"
+        WITH recorded AS (
+            SELECT DISTINCT ON (day) day, circle_id, fans
+            FROM (
+                SELECT
+                    make_date(cm.year, cm.month, 1) + (x.d - 1)::int AS day,
+                    cm.circle_id,
+                    x.f::bigint AS fans
+                FROM circle_member_fans_monthly cm
+                CROSS JOIN LATERAL unnest(cm.daily_fans) WITH ORDINALITY AS x(f, d)
+                WHERE cm.viewer_id = $1
+                  AND x.f > 0
+                  AND x.d <= extract(day FROM make_date(cm.year, cm.month, 1) + interval '1 month - 1 day')
+            ) member_days
+            ORDER BY day, fans DESC
+        ),
+        gains AS (
+            SELECT day, circle_id, fans, fans - lag(fans) OVER (ORDER BY day) AS gained
+            FROM recorded
+        ),
+        bounds AS (
+            -- Clamped to recorded data, so an open-ended range can't blow up the series
+            SELECT GREATEST($2, MIN(day)) AS first_day, LEAST($3, MAX(day)) AS last_day
+            FROM recorded
+            HAVING COUNT(*) > 0
+        )
+        SELECT s.day::date AS date, g.circle_id, g.fans, g.gained
+        FROM bounds
+        CROSS JOIN generate_series(bounds.first_day, bounds.last_day, interval '1 day') AS s(day)
+        LEFT JOIN gains g ON g.day = s.day::date
+        ORDER BY s.day
+        "#,
+    )
+    .bind(viewer_id)
+    .bind(params.from)
+    .bind(params.to)
+    .fetch_all(state.read_db())
+    .await?;
+
+    if days.iter().all(|day| day.fans.is_none()) {
+        return Err(AppError::NotFound(format!(
+            "No fan history found for viewer {}",
+            viewer_id
+        )));
+    }
+
+    let trainer_name = sqlx::query_scalar::<_, String>(
+        "SELECT COALESCE(display_name_override, name) FROM trainer WHERE account_id = $1::text",
+    )
+    .bind(viewer_id)
+    .fetch_optional(state.read_db())
+    .await?
+    .map(|name| crate::moderation::mask_text(&name));
+
+    Ok(Json(MemberFanHistoryResponse {
+        viewer_id,
+        trainer_name,
+        days,
+    }))
+}
+
+async fn ensure_circle_exists(pool: &PgPool, circle_id: CircleId) -> Result<(), AppError> {
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM circles WHERE circle_id = $1)",
+    )
+    .bind(circle_id)
+    .fetch_one(pool)
+    .await?;
+
+    if !exists {
+        return Err(AppError::NotFound("Circle not found".to_string()));
+    }
+    Ok(())
+}
+
+/// SHA-256 of the client's watch token from the `X-Watch-Token` header
+fn watch_token_hash(headers: &HeaderMap) -> Result<String, AppError> {
+    client_token_hash(headers, "X-Watch-Token", "Watch token")
+}
+
+/// SHA-256 of a client-chosen token (16-128 characters) from `header`
+fn client_token_hash(headers: &HeaderMap, header: &str, label: &str) -> Result<String, AppError> {
+    let token = headers
+        .get(header)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| AppError::Unauthorized(format!("Missing {}", label.to_lowercase())))?;
+
+    if token.len() < 16 || token.len() > 128 {
+        return Err(AppError::BadRequest(format!(
+            "{} must be 16-128 characters",
+            label
+        )));
+    }
+
+    Ok(hex::encode(Sha256::digest(token.as_bytes())))
+}
+
+async fn count_watched_circles(pool: &PgPool, token_hash: &str) -> Result<i64, AppError> {
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM circle_watchlist WHERE watch_token_hash = $1",
+    )
+    .bind(token_hash)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
+
+/// POST /api/v4/circles/{circle_id}/watch - Add a circle to the caller's watchlist
+///
+/// The watchlist is keyed by a client-generated token sent in the
+/// `X-Watch-Token` header. Watching an already watched circle is a no-op.
+pub async fn watch_circle(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    headers: HeaderMap,
+) -> Result<Json<CircleWatchResponse>, AppError> {
+    let token_hash = watch_token_hash(&headers)?;
+    ensure_circle_exists(&state.db, circle_id).await?;
+
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO circle_watchlist (watch_token_hash, circle_id)
+        SELECT $1, $2
+        WHERE (SELECT COUNT(*) FROM circle_watchlist WHERE watch_token_hash = $1) < $3
+        ON CONFLICT (watch_token_hash, circle_id) DO NOTHING
+        "#,
+    )
+    .bind(&token_hash)
+    .bind(circle_id)
+    .bind(MAX_WATCHED_CIRCLES)
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    let watched_count = count_watched_circles(&state.db, &token_hash).await?;
+
+    if inserted == 0 {
+        // Either already watched or the list is full
+        let already_watched = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM circle_watchlist WHERE watch_token_hash = $1 AND circle_id = $2)",
+        )
+        .bind(&token_hash)
+        .bind(circle_id)
+        .fetch_one(&state.db)
+        .await?;
+
+        if !already_watched {
+            return Err(AppError::BadRequest(format!(
+                "A watchlist can hold at most {} circles",
+                MAX_WATCHED_CIRCLES
+            )));
+        }
+    }
+
+    Ok(Json(CircleWatchResponse {
+        circle_id,
+        watching: true,
+        watched_count,
+    }))
+}
+
+/// DELETE /api/v4/circles/{circle_id}/watch - Remove a circle from the caller's watchlist
+pub async fn unwatch_circle(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    headers: HeaderMap,
+) -> Result<Json<CircleWatchResponse>, AppError> {
+    let token_hash = watch_token_hash(&headers)?;
+
+    sqlx::query("DELETE FROM circle_watchlist WHERE watch_token_hash = $1 AND circle_id = $2")
+        .bind(&token_hash)
+        .bind(circle_id)
+        .execute(&state.db)
+        .await?;
+
+    let watched_count = count_watched_circles(&state.db, &token_hash).await?;
+
+    Ok(Json(CircleWatchResponse {
+        circle_id,
+        watching: false,
+        watched_count,
+    }))
+}
+
+/// GET /api/v4/circles/watched - Current rank and change since yesterday for watched circles
+///
+/// Uses the live ranks like the circle list, ordered by rank.
+pub async fn get_watched_circles(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<WatchedCirclesResponse>, AppError> {
+    let token_hash = watch_token_hash(&headers)?;
+
+    let circles: Vec<WatchedCircle> = sqlx::query_as::<_, WatchedCircle>(
+        r#"
+        SELECT
+            circle_id,
+            name,
+            member_count,
+            monthly_rank,
+            monthly_point,
+            yesterday_rank,
+            yesterday_rank - monthly_rank as rank_delta,
+            monthly_point - yesterday_points as point_delta,
+            last_updated,
+            watched_since
+        FROM (
+            SELECT
+                c.circle_id,
+                COALESCE(mo.name_override, c.name) as name,
+                c.member_count,
+                COALESCE(gr.live_rank::integer, c.monthly_rank) as monthly_rank,
+                c.monthly_point,
+                COALESCE(gr.live_yesterday_rank::integer, c.yesterday_rank) as yesterday_rank,
+                c.yesterday_points,
+                c.last_updated,
+                w.created_at as watched_since
+            FROM circle_watchlist w
+            INNER JOIN circles c ON c.circle_id = w.circle_id
+            LEFT JOIN circle_live_ranks gr ON gr.circle_id = c.circle_id
+            LEFT JOIN circle_moderation_overrides mo ON mo.circle_id = c.circle_id
+            WHERE w.watch_token_hash = $1
+        ) watched
+        ORDER BY monthly_rank ASC NULLS LAST, circle_id ASC
+        "#,
+    )
+    .bind(&token_hash)
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .map(|mut circle| {
+        circle.name = crate::moderation::mask_text(&circle.name);
+        circle
+    })
+    .collect();
+
+    Ok(Json(WatchedCirclesResponse { circles }))
+}
+
+// Columns of a CircleWebhook; the code is only shown until the webhook is verified
+const CIRCLE_WEBHOOK_RETURNING: &str = r#"
+    circle_id,
+    verified_at IS NOT NULL AS verified,
+    CASE WHEN verified_at IS NULL THEN verification_code END AS verification_code,
+    rank_thresholds AS thresholds,
+    rival_circle_id
+"#;
+
+/// GET /api/v4/circles/{circle_id}/webhook - The circle's rank-change webhook
+///
+/// Requires the `X-Webhook-Token` header the webhook was registered with.
+pub async fn get_circle_webhook(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    headers: HeaderMap,
+) -> Result<Json<CircleWebhook>, AppError> {
+    let token_hash = webhook_token_hash(&headers)?;
+
+    let webhook = sqlx::query_as::<_, CircleWebhook>(&format!(
+        "SELECT {} FROM circle_webhooks WHERE circle_id = $1 AND manage_token_hash = $2",
+        CIRCLE_WEBHOOK_RETURNING
+    ))
+    .bind(circle_id)
+    .bind(&token_hash)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("No webhook registered with this token".to_string()))?;
+
+    Ok(Json(webhook))
+}
+
+/// PUT /api/v4/circles/{circle_id}/webhook - Register or update a Discord webhook for rank changes
+///
+/// The caller picks a manage token (`X-Webhook-Token` header, 16-128 characters).
+/// New webhooks return a verification code that the circle leader puts into the
+/// circle comment before calling /verify. A verified webhook can only be changed
+/// with its token; an unverified one is replaced by the next registration.
+///
+/// Body:
+/// - webhook_url: Discord webhook URL
+/// - thresholds: Ranks to notify about when entered or left (default: 100, 500)
+/// - rival_circle_id: Notify when this circle and the rival trade places
+pub async fn put_circle_webhook(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    headers: HeaderMap,
+    Json(payload): Json<CircleWebhookRequest>,
+) -> Result<Json<CircleWebhook>, AppError> {
+    let token_hash = webhook_token_hash(&headers)?;
+
+    let webhook_url = payload.webhook_url.trim();
+    if !DISCORD_WEBHOOK_PREFIXES
+        .iter()
+        .any(|prefix| webhook_url.starts_with(prefix))
+    {
+        return Err(AppError::BadRequest(
+            "webhook_url must be a Discord webhook URL".to_string(),
+        ));
+    }
+
+    let mut thresholds = payload
+        .thresholds
+        .unwrap_or_else(|| DEFAULT_WEBHOOK_THRESHOLDS.to_vec());
+    thresholds.sort_unstable();
+    thresholds.dedup();
+    if thresholds.len() > MAX_WEBHOOK_THRESHOLDS || thresholds.iter().any(|&rank| rank < 1) {
+        return Err(AppError::BadRequest(format!(
+            "thresholds must be at most {} positive ranks",
+            MAX_WEBHOOK_THRESHOLDS
+        )));
+    }
+
+    ensure_circle_exists(&state.db, circle_id).await?;
+    if let Some(rival_circle_id) = payload.rival_circle_id {
+        if rival_circle_id == circle_id {
+            return Err(AppError::BadRequest(
+                "rival_circle_id must be a different circle".to_string(),
+            ));
+        }
+        ensure_circle_exists(&state.db, rival_circle_id).await?;
+    }
+
+    let verification_code = format!("honse-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+
+    // Same token keeps code and verification; another token may only take over unverified webhooks
+    let webhook = sqlx::query_as::<_, CircleWebhook>(&format!(
+        r#"
+        INSERT INTO circle_webhooks
+            (circle_id, webhook_url, manage_token_hash, verification_code, rank_thresholds, rival_circle_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (circle_id) DO UPDATE SET
+            webhook_url = EXCLUDED.webhook_url,
+            rank_thresholds = EXCLUDED.rank_thresholds,
+            rival_circle_id = EXCLUDED.rival_circle_id,
+            verification_code = CASE
+                WHEN circle_webhooks.manage_token_hash = EXCLUDED.manage_token_hash
+                THEN circle_webhooks.verification_code
+                ELSE EXCLUDED.verification_code
+            END,
+            verified_at = CASE
+                WHEN circle_webhooks.manage_token_hash = EXCLUDED.manage_token_hash
+                THEN circle_webhooks.verified_at
+            END,
+            manage_token_hash = EXCLUDED.manage_token_hash,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE circle_webhooks.verified_at IS NULL
+           OR circle_webhooks.manage_token_hash = EXCLUDED.manage_token_hash
+        RETURNING {}
+        "#,
+        CIRCLE_WEBHOOK_RETURNING
+    ))
+    .bind(circle_id)
+    .bind(webhook_url)
+    .bind(&token_hash)
+    .bind(&verification_code)
+    .bind(&thresholds)
+    .bind(payload.rival_circle_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Conflict("This circle already has a verified webhook".to_string()))?;
+
+    Ok(Json(webhook))
+}
+
+/// POST /api/v4/circles/{circle_id}/webhook/verify - Verify the webhook via the circle comment
+///
+/// Succeeds once the stored circle comment contains the verification code;
+/// refresh the circle after editing the comment in game.
+pub async fn verify_circle_webhook(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    headers: HeaderMap,
+) -> Result<Json<CircleWebhook>, AppError> {
+    let token_hash = webhook_token_hash(&headers)?;
+
+    let verified = sqlx::query_as::<_, CircleWebhook>(&format!(
+        r#"
+        UPDATE circle_webhooks w
+        SET verified_at = COALESCE(verified_at, CURRENT_TIMESTAMP), updated_at = CURRENT_TIMESTAMP
+        WHERE circle_id = $1
+          AND manage_token_hash = $2
+          AND (
+              verified_at IS NOT NULL
+              OR EXISTS (
+                  SELECT 1 FROM circles c
+                  WHERE c.circle_id = w.circle_id
+                    AND strpos(COALESCE(c.comment, ''), w.verification_code) > 0
+              )
+          )
+        RETURNING {}
+        "#,
+        CIRCLE_WEBHOOK_RETURNING
+    ))
+    .bind(circle_id)
+    .bind(&token_hash)
+    .fetch_optional(&state.db)
+    .await?;
+
+    if let Some(webhook) = verified {
+        return Ok(Json(webhook));
+    }
+
+    let registered = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM circle_webhooks WHERE circle_id = $1 AND manage_token_hash = $2)",
+    )
+    .bind(circle_id)
+    .bind(&token_hash)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !registered {
+        return Err(AppError::NotFound("No webhook registered with this token".to_string()));
+    }
+    Err(AppError::BadRequest(
+        "Verification code not found in the circle comment yet - refresh the circle after editing it".to_string(),
+    ))
+}
+
+/// DELETE /api/v4/circles/{circle_id}/webhook - Remove the circle's webhook
+pub async fn delete_circle_webhook(
+    State(state): State<AppState>,
+    Path(circle_id): Path<CircleId>,
+    headers: HeaderMap,
+) -> Result<StatusCode, AppError> {
+    let token_hash = webhook_token_hash(&headers)?;
+
+    let deleted = sqlx::query(
+        "DELETE FROM circle_webhooks WHERE circle_id = $1 AND manage_token_hash = $2",
+    )
+    .bind(circle_id)
+    .bind(&token_hash)
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    if deleted == 0 {
+        return Err(AppError::NotFound("No webhook registered with this token".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn webhook_token_hash(headers: &HeaderMap) -> Result<String, AppError> {
+    client_token_hash(headers, "X-Webhook-Token", "Webhook token")
+}
+
+/// Rank-change events for one circle since yesterday, as (key, message)
+fn rank_change_events(
+    thresholds: &[i32],
+    rank: i32,
+    yesterday_rank: Option<i32>,
+    rival: Option<(&str, Option<i32>, Option<i32>)>,
+) -> Vec<(String, String)> {
+    let mut events = Vec::new();
+
+    if let Some(yesterday_rank) = yesterday_rank {
+        for &threshold in thresholds {
+            if rank <= threshold && yesterday_rank > threshold {
+                events.push((
+                    format!("entered_top_{}", threshold),
+                    format!("📈 Entered the top {} (#{} → #{})", threshold, yesterday_rank, rank),
+                ));
+            } else if rank > threshold && yesterday_rank <= threshold {
+                events.push((
+                    format!("left_top_{}", threshold),
+                    format!("📉 Dropped out of the top {} (#{} → #{})", threshold, yesterday_rank, rank),
+                ));
+            }
+        }
+
+        if let Some((rival_name, Some(rival_rank), Some(rival_yesterday_rank))) = rival {
+            if yesterday_rank < rival_yesterday_rank && rank > rival_rank {
+                events.push((
+                    "overtaken_by_rival".to_string(),
+                    format!("⚔️ Overtaken by {} (#{} vs. #{})", rival_name, rival_rank, rank),
+                ));
+            } else if yesterday_rank > rival_yesterday_rank && rank < rival_rank {
+                events.push((
+                    "overtook_rival".to_string(),
+                    format!("🏁 Overtook {} (#{} vs. #{})", rival_name, rank, rival_rank),
+                ));
+            }
+        }
+    }
+
+    events
+}
+
+/// Post rank-change notifications for verified circle webhooks.
+///
+/// Compares each circle's live rank to yesterday's; every event is posted at
+/// most once per competition day. Returns the number of notifications sent.
+pub(crate) async fn notify_circle_rank_changes(
+    pool: &PgPool,
+    client: &reqwest::Client,
+) -> Result<u64, AppError> {
+    use sqlx::Row;
+
+    let today = competition::now().date_naive();
+
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            w.circle_id,
+            w.webhook_url,
+            w.rank_thresholds,
+            CASE WHEN w.notified_on = $1 THEN w.notified_events ELSE '{}' END AS notified_events,
+            COALESCE(mo.name_override, c.name) AS name,
+            COALESCE(gr.live_rank::integer, c.monthly_rank) AS monthly_rank,
+            COALESCE(gr.live_yesterday_rank::integer, c.yesterday_rank) AS yesterday_rank,
+            COALESCE(rmo.name_override, r.name) AS rival_name,
+            COALESCE(rgr.live_rank::integer, r.monthly_rank) AS rival_rank,
+            COALESCE(rgr.live_yesterday_rank::integer, r.yesterday_rank) AS rival_yesterday_rank
+        FROM circle_webhooks w
+        INNER JOIN circles c ON c.circle_id = w.circle_id
+        LEFT JOIN circle_live_ranks gr ON gr.circle_id = c.circle_id
+        LEFT JOIN circle_moderation_overrides mo ON mo.circle_id = c.circle_id
+        LEFT JOIN circles r ON r.circle_id = w.rival_circle_id
+        LEFT JOIN circle_live_ranks rgr ON rgr.circle_id = r.circle_id
+        LEFT JOIN circle_moderation_overrides rmo ON rmo.circle_id = r.circle_id
+        WHERE w.verified_at IS NOT NULL
+        "#,
+    )
+    .bind(today)
+    .fetch_all(pool)
+    .await?;
+
+    let mut sent = 0;
+    for row in rows {
+        let circle_id: CircleId = row.get("circle_id");
+        let Some(rank) = row.get::<Option<i32>, _>("monthly_rank") else {
+            continue;
+        };
+        let thresholds: Vec<i32> = row.get("rank_thresholds");
+        let mut notified_events: Vec<String> = row.get("notified_events");
+        let rival_name = row
+            .get::<Option<String>, _>("rival_name")
+            .map(|name| crate::moderation::mask_text(&name));
+
+        let events: Vec<(String, String)> = rank_change_events(
+            &thresholds,
+            rank,
+            row.get("yesterday_rank"),
+            rival_name
+                .as_deref()
+                .map(|name| (name, row.get("rival_rank"), row.get("rival_yesterday_rank"))),
+        )
+        .into_iter()
+        .filter(|(key, _)| !notified_events.contains(key))
+        .collect();
+
+        if events.is_empty() {
+            continue;
+        }
+
+        let name = crate::moderation::mask_text(&row.get::<String, _>("name"));
+        let content = format!(
+            "**{}** is now #{}\n{}",
+            name,
+            rank,
+            events
+                .iter()
+                .map(|(_, message)| message.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+
+        let webhook_url: String = row.get("webhook_url");
+        let delivered = client
+            .post(&webhook_url)
+            .json(&serde_json::json!({ "username": "honse.moe", "content": content }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        if let Err(e) = delivered {
+            tracing::warn!("⚠️ Rank webhook for circle {} failed: {}", circle_id, e);
+            continue;
+        }
+
+        notified_events.extend(events.into_iter().map(|(key, _)| key));
+        sqlx::query(
+            "UPDATE circle_webhooks SET notified_on = $2, notified_events = $3 WHERE circle_id = $1",
+        )
+        .bind(circle_id)
+        .bind(today)
+        .bind(&notified_events)
+        .execute(pool)
+        .await?;
+        sent += 1;
+    }
+
+    Ok(sent)
+}
+
+// Single-circle select with moderation overrides applied; callers add the WHERE clause
+pub(crate) const CIRCLE_SELECT_SQL: &str = r#"
+        SELECT 
+            c.circle_id,
+            COALESCE(mo.name_override, c.name) as name,
+            CASE WHEN mo.hide_comment THEN NULL ELSE COALESCE(mo.comment_override, c.comment) END as comment,
+            c.leader_viewer_id,
+            COALESCE(t.display_name_override, t.name) as leader_name,
+            c.member_count,
+            c.join_style,
+            c.policy,
+            c.created_at,
+            c.last_updated,
+            c.monthly_rank,
+            c.monthly_point,
+            c.last_month_rank,
+            c.last_month_point,
+            c.archived,
+            c.yesterday_updated,
+            c.yesterday_points,
+            c.yesterday_rank,
+            circle_projected_point(c.circle_id, c.monthly_point) as projected_point
+        FROM circles c
+        LEFT JOIN trainer t ON c.leader_viewer_id::text = t.account_id
+        LEFT JOIN circle_moderation_overrides mo ON mo.circle_id = c.circle_id
+"#;
+
+/// GET /api/v4/circles/bulk - Several circles (without members) in one request
+///
+/// Parameters:
+/// - ids: Comma-separated circle IDs (max 50)
+pub async fn get_circles_bulk(
+    State(state): State<AppState>,
+    Query(params): Query<CircleBulkParams>,
+) -> Result<Json<CircleBulkResponse>, AppError> {
+    let mut ids: Vec<CircleId> = Vec::new();
+    for id in params.ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+        let id = id
+            .parse::<i64>()
+            .map(CircleId)
+            .map_err(|_| AppError::BadRequest(format!("Invalid circle ID '{}'", id)))?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    if ids.is_empty() {
+        return Err(AppError::BadRequest("ids must list at least one circle ID".to_string()));
+    }
+    if ids.len() > MAX_BULK_CIRCLES {
+        return Err(AppError::BadRequest(format!(
+            "At most {} circles can be requested at once",
+            MAX_BULK_CIRCLES
+        )));
+    }
+
+    let raw_ids: Vec<i64> = ids.iter().map(|id| id.0).collect();
+    let found = sqlx::query_as::<_, Circle>(&format!(
+        "{} WHERE c.circle_id = ANY($1)",
+        CIRCLE_SELECT_SQL
+    ))
+    .bind(&raw_ids)
+    .fetch_all(state.read_db())
+    .await?;
+
+    let mut found: std::collections::HashMap<CircleId, Circle> = found
+        .into_iter()
+        .map(|circle| (circle.circle_id, circle))
+        .collect();
+
+    let mut circles = Vec::with_capacity(found.len());
+    let mut missing = Vec::new();
+    for id in ids {
+        match found.remove(&id) {
+            Some(circle) => circles.push(crate::moderation::mask_circle(circle)),
+            None => missing.push(id),
+        }
+    }
+
+    Ok(Json(CircleBulkResponse { circles, missing }))
+}
+
+/// Circle a viewer was last seen in, from their member fan rows
+pub(crate) async fn find_viewer_circle(
+    pool: &PgPool,
+    viewer_id: ViewerId,
+) -> Result<Option<CircleId>, AppError> {
+    let cache_key = format!("viewer_circle:{}", viewer_id);
+    if let Some(circle_id) = crate::cache::get::<Option<CircleId>>(&cache_key) {
+        return Ok(circle_id);
+    }
+
+    // Latest month first, so viewers who switched circles resolve to the current one
+    let circle_id = sqlx::query_scalar::<_, CircleId>(
+        r#"
+        SELECT circle_id 
+        FROM circle_member_fans_monthly 
+        WHERE viewer_id = $1 
+        ORDER BY year DESC, month DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(viewer_id)
+    .fetch_optional(pool)
+    .await?;
+
+    // Misses are only cached briefly so a queued fetch shows up soon after it lands
+    let ttl = if circle_id.is_some() {
+        CIRCLE_CACHE_TTL
+    } else {
+        crate::cache::NEGATIVE_TTL
+    };
+    let _ = crate::cache::set(&cache_key, &circle_id, ttl);
+    Ok(circle_id)
+}
+
+/// Fingerprint of a circle row, its moderation override and its members for the requested month
+pub(crate) async fn fetch_circle_version(
+    pool: &PgPool,
+    circle_id: CircleId,
+    params: &CircleQueryParams,
+) -> Result<Option<String>, AppError> {
+    let (year, month) = resolve_member_month(params.year, params.month);
+
+    let version = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT concat_ws('|', c.last_updated, c.yesterday_updated, mo.updated_at, m.updated, m.members)
+        FROM circles c
+        LEFT JOIN circle_moderation_overrides mo ON mo.circle_id = c.circle_id
+        CROSS JOIN LATERAL (
+            SELECT max(last_updated) AS updated, count(*) AS members
+            FROM circle_member_fans_monthly
+            WHERE circle_id = c.circle_id AND year = $2 AND month = $3
+        ) m
+        WHERE c.circle_id = $1
+        "#,
+    )
+    .bind(circle_id)
+    .bind(year)
+    .bind(month)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(version)
+}
+
+pub(crate) async fn fetch_circle_by_id(pool: &PgPool, circle_id: CircleId) -> Result<Circle, AppError> {
+    let circle = sqlx::query_as::<_, Circle>(&format!(
+        "{} WHERE c.circle_id = $1",
+        CIRCLE_SELECT_SQL
+    ))
+    .bind(circle_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Circle {} not found", circle_id)))?;
+
+    Ok(crate::moderation::mask_circle(circle))
+}
+
+/// Month of member fan data to show, defaulting to the current competition month
+fn resolve_member_month(year: Option<i32>, month: Option<i32>) -> (i32, i32) {
+    match (year, month) {
+        (Some(year), Some(month)) => (year, month),
+        _ => {
+            let (current_year, current_month) = competition::current_month();
+            (year.unwrap_or(current_year), month.unwrap_or(current_month))
+        }
+    }
+}
+
+/// Percent of this month's tracked circles with fewer points than `circle_id`
+///
+/// One percent_rank() pass over all circles, cached per circle for a few minutes.
+pub(crate) async fn fetch_circle_percentile(
+    pool: &PgPool,
+    circle_id: CircleId,
+) -> Result<Option<f64>, AppError> {
+    let cache_key = format!("circle_percentile:{}", circle_id);
+    if let Some(cached) = crate::cache::get::<Option<f64>>(&cache_key) {
+        return Ok(cached);
+    }
+
+    let percentile = sqlx::query_scalar::<_, f64>(&format!(
+        r#"
+        SELECT percentile FROM (
+            SELECT
+                circle_id,
+                round((percent_rank() OVER (ORDER BY monthly_point) * 100)::numeric, 1)::float8 AS percentile
+            FROM circles
+            WHERE monthly_point IS NOT NULL
+              AND NOT COALESCE(archived, FALSE)
+              AND last_updated >= {}
+        ) ranked
+        WHERE circle_id = $1
+        "#,
+        competition::month_start_sql(0)
+    ))
+    .bind(circle_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let _ = crate::cache::set(&cache_key, &percentile, CIRCLE_PERCENTILE_TTL);
+    Ok(percentile)
+}
+
+/// Fetch all members and their fan counts for a circle
+pub(crate) async fn fetch_circle_members(
+    pool: &PgPool,
+    circle_id: CircleId,
+    params: &CircleQueryParams,
+) -> Result<(Vec<CircleMemberFansMonthly>, i64), AppError> {
+    use sqlx::Row;
+
+    let (target_year, target_month) = resolve_member_month(params.year, params.month);
+
+    let sort_column = match params.sort_by.as_deref() {
+        None | Some("viewer_id") => "cm.viewer_id",
+        Some("total_fans") => "total_fans",
+        Some("today_fans") => "today_fans",
+        Some("name") => "t.name COLLATE name_ja",
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid sort_by '{}' (expected total_fans, today_fans or name)",
+                other
+            )))
+        }
+    };
+    let sort_dir = match params.sort_dir.as_deref().map(str::to_lowercase).as_deref() {
+        Some("asc") => "ASC",
+        Some("desc") => "DESC",
+        Some(_) => return Err(AppError::BadRequest("sort_dir must be asc or desc".to_string())),
+        None if sort_column.ends_with("_fans") => "DESC",
+        None => "ASC",
+    };
+
+    let include_records = includes_member_records(params.include.as_deref())?;
+
+    let total_members = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*)
+        FROM circle_member_fans_monthly
+        WHERE circle_id = $1 AND year = $2 AND month = $3
+        "#,
+    )
+    .bind(circle_id)
+    .bind(target_year)
+    .bind(target_month)
+    .fetch_one(pool)
+    .await?;
+
+    // daily_fans holds the member's cumulative fan count for each day of the month
+    // (0 = no data for that day): total_fans is the latest count, today_fans the
+    // gain over the previous recorded day
+    let mut query_builder = QueryBuilder::<Postgres>::new(
+        r#"
+        SELECT
+            cm.id,
+            cm.circle_id,
+            cm.viewer_id,
+            COALESCE(t.display_name_override, t.name) AS trainer_name,
+            cm.year,
+            cm.month,
+            cm.daily_fans,
+            cm.last_updated,
+            COALESCE(fans.counts[1], 0) AS total_fans,
+            COALESCE(fans.counts[1] - fans.counts[2], 0) AS today_fans"#,
+    );
+    if include_records {
+        query_builder.push(", to_jsonb(rec) AS inheritance");
+    }
+    query_builder.push(
+        r#"
+        FROM circle_member_fans_monthly cm
+        LEFT JOIN trainer t ON cm.viewer_id::text = t.account_id
+        CROSS JOIN LATERAL (
+            SELECT array_agg(f ORDER BY d DESC) AS counts
+            FROM unnest(cm.daily_fans) WITH ORDINALITY AS x(f, d)
+            WHERE f > 0
+        ) fans"#,
+    );
+    if include_records {
+        // Best record per member: highest parent rank, then default affinity
+        query_builder.push(
+            r#"
+        LEFT JOIN LATERAL (
+            SELECT
+                i.inheritance_id,
+                i.main_parent_id,
+                COALESCE(i.parent_left_id, 0) AS parent_left_id,
+                COALESCE(i.parent_right_id, 0) AS parent_right_id,
+                COALESCE(i.parent_rank, 0) AS parent_rank,
+                COALESCE(i.parent_rarity, 0) AS parent_rarity,
+                COALESCE(i.win_count, 0) AS win_count,
+                COALESCE(i.base_affinity, 0) + COALESCE(i.race_affinity, 0) AS affinity_score,
+                COALESCE(i.main_blue_factors, 0) AS main_blue_factors,
+                COALESCE(i.main_pink_factors, 0) AS main_pink_factors,
+                COALESCE(i.main_green_factors, 0) AS main_green_factors,
+                COALESCE(i.main_white_factors, '{}') AS main_white_factors
+            FROM inheritance i
+            WHERE i.account_id = cm.viewer_id::text
+            ORDER BY i.parent_rank DESC NULLS LAST, i.base_affinity + i.race_affinity DESC NULLS LAST, i.inheritance_id DESC
+            LIMIT 1
+        ) rec ON TRUE"#,
+        );
+    }
+    query_builder.push(" WHERE cm.circle_id = ");
+    query_builder.push_bind(circle_id);
+    query_builder.push(" AND cm.year = ");
+    query_builder.push_bind(target_year);
+    query_builder.push(" AND cm.month = ");
+    query_builder.push_bind(target_month);
+    query_builder.push(format!(
+        " ORDER BY {} {} NULLS LAST, cm.viewer_id ASC",
+        sort_column, sort_dir
+    ));
+    if let Some(limit) = params.limit {
+        let limit = limit.clamp(1, 100);
+        query_builder.push(" LIMIT ");
+        query_builder.push_bind(limit);
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(params.page.unwrap_or(0).max(0) * limit);
+    }
+
+    let rows = query_builder.build().fetch_all(pool).await?;
+
+    // PostgreSQL stores daily_fans as BIGINT[], the API exposes it as Vec<i32>
+    let members = rows
+        .into_iter()
+        .map(|row| {
+            let inheritance = if include_records {
+                row.get::<Option<serde_json::Value>, _>("inheritance")
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_err(|e| {
+                        AppError::DatabaseError(format!("Invalid member inheritance record: {}", e))
+                    })?
+            } else {
+                None
+            };
+            Ok(CircleMemberFansMonthly {
+                id: row.get("id"),
+                circle_id: row.get("circle_id"),
+                viewer_id: row.get("viewer_id"),
+                trainer_name: row.get("trainer_name"),
+                year: row.get("year"),
+                month: row.get("month"),
+                daily_fans: row
+                    .get::<Vec<i64>, _>("daily_fans")
+                    .into_iter()
+                    .map(|v| v as i32)
+                    .collect(),
+                last_updated: row.get("last_updated"),
+                total_fans: row.get("total_fans"),
+                today_fans: row.get("today_fans"),
+                inheritance,
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    Ok((members, total_members))
+}
+
+/// Whether the member `include` list asks for inheritance records
+pub(crate) fn includes_member_records(include: Option<&str>) -> Result<bool, AppError> {
+    let mut records = false;
+    for part in include.unwrap_or("").split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part {
+            "records" => records = true,
+            other => {
+                return Err(AppError::BadRequest(format!(
+                    "Invalid include '{}' (expected records)",
+                    other
+                )))
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Add a viewer to the tasks queue for later fetching
+///
+/// Skips the insert when a fetch for this viewer is already pending.
+pub(crate) async fn add_viewer_to_tasks(pool: &PgPool, viewer_id: ViewerId) -> Result<TaskOutcome, AppError> {
+    let viewer_key = viewer_id.to_string();
+    if find_pending_task(pool, "fetch_circle", "viewer_id", &viewer_key)
+        .await?
+        .is_some()
+    {
+        return Ok(TaskOutcome::Duplicate);
+    }
+
+    // Insert into tasks table with viewer_id in task_data
+    // account_id is for the worker that processes the task, so we leave it NULL
+    sqlx::query(
+        r#"
+        INSERT INTO tasks (task_type, task_data, status, created_at, updated_at)
+        VALUES ('fetch_circle', $1, 'pending', NOW(), NOW())
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(serde_json::json!({ "viewer_id": viewer_id }))
+    .execute(pool)
+    .await?;
+
+    Ok(TaskOutcome::TaskCreated)
 }
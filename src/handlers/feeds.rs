@@ -0,0 +1,385 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::time::Duration;
+
+use crate::{
+    cache::Namespace,
+    characters::character_name,
+    errors::AppError,
+    models::{NewInheritanceRecord, NotableRecord},
+    AppState,
+};
+
+const FEED_CACHE_KEY: &str = "feeds:notable";
+const FEED_ENTRY_LIMIT: i64 = 50;
+
+/// Public site the feed and sitemap links point to
+pub(crate) const SITE_URL: &str = "https://honse.moe";
+
+/// After this the new inheritances feed picks up records ingested since
+const NEW_INHERITANCES_REFRESH: Duration = Duration::from_secs(60);
+
+/// After this the feed is rebuilt from scratch, dropping records that were
+/// deleted or fell below the rank threshold
+const NEW_INHERITANCES_REBUILD: Duration = Duration::from_secs(3600);
+
+// High-rank inheritance records ingested from $1 on (GREATEST skips a NULL $1)
+// and within the last 7 days
+const NEW_INHERITANCES_SQL: &str = r#"
+    SELECT
+        i.inheritance_id,
+        i.account_id,
+        COALESCE(t.display_name_override, t.name) AS trainer_name,
+        i.main_parent_id,
+        COALESCE(i.parent_rank, 0) AS parent_rank,
+        COALESCE(i.win_count, 0) AS win_count,
+        COALESCE(i.white_count, 0) AS white_count,
+        i.ingested_at
+    FROM inheritance i
+    LEFT JOIN trainer t ON t.account_id = i.account_id
+    WHERE i.ingested_at >= GREATEST($1::timestamp, CURRENT_TIMESTAMP - INTERVAL '7 days')
+      AND i.parent_rank >= $2
+    ORDER BY i.ingested_at DESC, i.inheritance_id DESC
+    LIMIT $3
+"#;
+
+// Newly ingested inheritance records matching any enabled notability rule,
+// with the labels of every rule they matched
+const NOTABLE_RECORDS_SQL: &str = r#"
+    SELECT
+        i.account_id,
+        COALESCE(t.display_name_override, t.name) AS trainer_name,
+        i.main_parent_id,
+        COALESCE(i.win_count, 0) AS win_count,
+        COALESCE(i.blue_stars_sum, 0) AS blue_stars_sum,
+        COALESCE(i.pink_stars_sum, 0) AS pink_stars_sum,
+        m.matched_rules,
+        i.ingested_at
+    FROM inheritance i
+    LEFT JOIN trainer t ON t.account_id = i.account_id
+    CROSS JOIN LATERAL (
+        SELECT array_agg(r.label ORDER BY r.rule_id) AS matched_rules
+        FROM notability_rules r
+        WHERE r.enabled
+          AND (r.min_blue_stars IS NULL OR COALESCE(i.blue_stars_sum, 0) >= r.min_blue_stars)
+          AND (r.min_pink_stars IS NULL OR COALESCE(i.pink_stars_sum, 0) >= r.min_pink_stars)
+          AND (r.min_green_stars IS NULL OR COALESCE(i.green_stars_sum, 0) >= r.min_green_stars)
+          AND (r.min_white_stars IS NULL OR COALESCE(i.white_stars_sum, 0) >= r.min_white_stars)
+          AND (r.min_win_count IS NULL OR COALESCE(i.win_count, 0) >= r.min_win_count)
+    ) m
+    WHERE i.ingested_at >= CURRENT_TIMESTAMP - INTERVAL '7 days'
+      AND m.matched_rules IS NOT NULL
+    ORDER BY i.ingested_at DESC
+    LIMIT $1
+"#;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/notable.xml", get(notable_feed))
+        .route("/new-inheritances.xml", get(new_inheritances_feed))
+}
+
+/// Public URL of the notable feed, used as the feed's self link and for WebSub pings
+pub(crate) fn feed_url() -> String {
+    crate::config::get().notable_feed_url.clone()
+}
+
+/// WebSub hub advertised in the feed and pinged when new records arrive
+pub(crate) fn websub_hub_url() -> Option<String> {
+    crate::config::get().websub_hub_url.clone()
+}
+
+/// GET /feeds/notable.xml - Atom feed of newly ingested records passing the notability rules
+///
+/// Lists up to 50 records ingested in the last 7 days, newest first.
+pub async fn notable_feed(State(state): State<AppState>) -> Result<Response, AppError> {
+    let xml = match crate::cache::get::<String>(FEED_CACHE_KEY) {
+        Some(cached) => cached,
+        None => {
+            let records = fetch_notable_records(&state.db, FEED_ENTRY_LIMIT).await?;
+            let xml = render_atom_feed(&records);
+            let _ = crate::cache::set(FEED_CACHE_KEY, &xml, std::time::Duration::from_secs(300));
+            xml
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/atom+xml; charset=utf-8"),
+    );
+
+    Ok((headers, xml).into_response())
+}
+
+pub(crate) async fn fetch_notable_records(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<NotableRecord>, AppError> {
+    let records = sqlx::query_as::<_, NotableRecord>(NOTABLE_RECORDS_SQL)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(records)
+}
+
+/// Ingestion time of the newest notable record, if any
+pub(crate) async fn latest_notable_ingest(pool: &PgPool) -> Result<Option<NaiveDateTime>, AppError> {
+    Ok(fetch_notable_records(pool, 1)
+        .await?
+        .first()
+        .map(|record| record.ingested_at))
+}
+
+/// Tell the WebSub hub the feed has new content
+pub(crate) async fn ping_websub_hub(
+    client: &reqwest::Client,
+    hub_url: &str,
+    feed_url: &str,
+) -> Result<(), reqwest::Error> {
+    client
+        .post(hub_url)
+        .form(&[("hub.mode", "publish"), ("hub.url", feed_url)])
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Cached state of the new inheritances feed
+#[derive(Serialize, Deserialize)]
+struct NewInheritancesFeed {
+    /// Newest first
+    records: Vec<NewInheritanceRecord>,
+    rebuilt_at: NaiveDateTime,
+    xml: String,
+}
+
+/// GET /feeds/new-inheritances.xml - Atom feed of newly ingested high-rank records
+///
+/// Lists up to 50 records of at least NEW_INHERITANCES_FEED_MIN_RANK ingested
+/// in the last 7 days, newest first. The feed is cached and only records
+/// ingested since the last refresh are fetched, so frequent polling by feed
+/// readers stays cheap.
+pub async fn new_inheritances_feed(State(state): State<AppState>) -> Result<Response, AppError> {
+    let key = Namespace::Feeds.key("new-inheritances");
+    let pool = state.db.clone();
+    let feed = crate::cache::get_or_revalidate(
+        &key,
+        NEW_INHERITANCES_REFRESH,
+        NEW_INHERITANCES_REBUILD,
+        {
+            let key = key.clone();
+            move || async move {
+                let previous = crate::cache::get::<NewInheritancesFeed>(&key);
+                refresh_new_inheritances(&pool, previous).await
+            }
+        },
+    )
+    .await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/atom+xml; charset=utf-8"),
+    );
+
+    Ok((headers, feed.xml).into_response())
+}
+
+/// Add records ingested since `previous` was built to it, or build the feed
+/// from scratch when there is none or it is due for a rebuild
+async fn refresh_new_inheritances(
+    pool: &PgPool,
+    previous: Option<NewInheritancesFeed>,
+) -> Result<NewInheritancesFeed, AppError> {
+    let now = chrono::Utc::now().naive_utc();
+    let rebuild_before = now - chrono::Duration::from_std(NEW_INHERITANCES_REBUILD).unwrap_or_default();
+    let (mut records, rebuilt_at) = match previous {
+        Some(feed) if feed.rebuilt_at > rebuild_before => (feed.records, feed.rebuilt_at),
+        _ => (Vec::new(), now),
+    };
+
+    // Records sharing the newest timestamp are fetched again and deduplicated
+    let since = records.first().map(|record| record.ingested_at);
+    let fresh = sqlx::query_as::<_, NewInheritanceRecord>(NEW_INHERITANCES_SQL)
+        .bind(since)
+        .bind(crate::config::get().new_inheritances_feed_min_rank)
+        .bind(FEED_ENTRY_LIMIT)
+        .fetch_all(pool)
+        .await?;
+
+    // A trainer's new record replaces the old one
+    records.retain(|record| {
+        !fresh
+            .iter()
+            .any(|f| f.inheritance_id == record.inheritance_id || f.account_id == record.account_id)
+    });
+    let oldest = now - chrono::Duration::days(7);
+    let records: Vec<NewInheritanceRecord> = fresh
+        .into_iter()
+        .chain(records)
+        .filter(|record| record.ingested_at >= oldest)
+        .take(FEED_ENTRY_LIMIT as usize)
+        .collect();
+
+    let xml = render_new_inheritances_feed(&records);
+    Ok(NewInheritancesFeed {
+        records,
+        rebuilt_at,
+        xml,
+    })
+}
+
+fn render_new_inheritances_feed(records: &[NewInheritanceRecord]) -> String {
+    let self_url = format!("{}/feeds/new-inheritances.xml", SITE_URL);
+    let updated = records
+        .first()
+        .map(|record| record.ingested_at)
+        .unwrap_or_else(|| chrono::Utc::now().naive_utc());
+
+    let mut xml = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>honse.moe - New high-rank inheritance records</title>
+  <id>{}</id>
+  <link rel="self" href="{}"/>
+  <link rel="alternate" href="{}/"/>
+  <updated>{}</updated>
+"#,
+        xml_escape(&self_url),
+        xml_escape(&self_url),
+        SITE_URL,
+        atom_timestamp(updated)
+    );
+
+    for record in records {
+        let trainer_name = record
+            .trainer_name
+            .as_deref()
+            .map(crate::moderation::mask_text)
+            .unwrap_or_else(|| record.account_id.to_string());
+        let title = format!(
+            "{} by {} - Rank {}",
+            character_name(record.main_parent_id),
+            trainer_name,
+            record.parent_rank
+        );
+        let summary = format!(
+            "Wins {} • White sparks {}",
+            record.win_count, record.white_count
+        );
+
+        xml.push_str(&format!(
+            r#"  <entry>
+    <id>tag:honse.moe,2026:inheritance-record/{}</id>
+    <title>{}</title>
+    <link href="{}/s/inheritance/record/{}"/>
+    <updated>{}</updated>
+    <summary>{}</summary>
+  </entry>
+"#,
+            record.inheritance_id,
+            xml_escape(&title),
+            SITE_URL,
+            record.inheritance_id,
+            atom_timestamp(record.ingested_at),
+            xml_escape(&summary)
+        ));
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn render_atom_feed(records: &[NotableRecord]) -> String {
+    let self_url = feed_url();
+    let updated = records
+        .first()
+        .map(|record| record.ingested_at)
+        .unwrap_or_else(|| chrono::Utc::now().naive_utc());
+
+    let hub_link = websub_hub_url()
+        .map(|hub| format!("\n  <link rel=\"hub\" href=\"{}\"/>", xml_escape(&hub)))
+        .unwrap_or_default();
+
+    let mut xml = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>honse.moe - Notable inheritance records</title>
+  <id>{}</id>
+  <link rel="self" href="{}"/>{}
+  <link rel="alternate" href="https://honse.moe/"/>
+  <updated>{}</updated>
+"#,
+        xml_escape(&self_url),
+        xml_escape(&self_url),
+        hub_link,
+        atom_timestamp(updated)
+    );
+
+    for record in records {
+        let trainer_name = record
+            .trainer_name
+            .as_deref()
+            .map(crate::moderation::mask_text)
+            .unwrap_or_else(|| record.account_id.to_string());
+        let title = format!(
+            "{} by {} - {}",
+            character_name(record.main_parent_id),
+            trainer_name,
+            record.matched_rules.join(", ")
+        );
+        let summary = format!(
+            "Blue ★{} • Pink ★{} • Wins {}",
+            record.blue_stars_sum, record.pink_stars_sum, record.win_count
+        );
+        let categories: String = record
+            .matched_rules
+            .iter()
+            .map(|label| format!("\n    <category term=\"{}\"/>", xml_escape(label)))
+            .collect();
+
+        xml.push_str(&format!(
+            r#"  <entry>
+    <id>tag:honse.moe,2026:inheritance/{}/{}</id>
+    <title>{}</title>
+    <link href="https://honse.moe/s/inheritance/{}"/>
+    <updated>{}</updated>
+    <summary>{}</summary>{}
+  </entry>
+"#,
+            xml_escape(record.account_id.as_str()),
+            record.ingested_at.and_utc().timestamp(),
+            xml_escape(&title),
+            xml_escape(record.account_id.as_str()),
+            atom_timestamp(record.ingested_at),
+            xml_escape(&summary),
+            categories
+        ));
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+pub(crate) fn atom_timestamp(timestamp: NaiveDateTime) -> String {
+    timestamp.and_utc().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+pub(crate) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
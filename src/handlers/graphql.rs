@@ -0,0 +1,21 @@
+use axum::{extract::State, response::Json, routing::post, Router};
+
+use crate::graphql;
+use crate::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", post(execute_query))
+}
+
+/// POST /api/graphql - Run a GraphQL query against trainers, inheritance, support cards and circles
+///
+/// Takes the standard `{"query", "operationName", "variables"}` body. Errors
+/// (including queries over the depth/complexity limits) are reported in the
+/// response's `errors` with a 200 status, as GraphQL clients expect.
+async fn execute_query(
+    State(state): State<AppState>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let request = graphql::prepare_request(request, &state);
+    Json(graphql::schema().execute(request).await)
+}
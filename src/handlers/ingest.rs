@@ -0,0 +1,681 @@
+use axum::{extract::State, response::Json, routing::post, Router};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use sqlx::{postgres::PgArguments, query::Query, Postgres, Transaction};
+use validator::Validate;
+
+use crate::cache::{self, Namespace};
+use crate::errors::AppError;
+use crate::events::{self, DomainEvent};
+use crate::handlers::privacy::{deleted_accounts, verify_comments};
+use crate::handlers::tasks::{is_valid_trainer_id, verify_claim_comments};
+use crate::handlers::users::record_friend_slot_openings;
+use crate::member_fan_partitions;
+use crate::notifications;
+use crate::models::{
+    AccountId, BulkCircleMembersRequest, BulkIngestResponse, CircleIngestRequest, IngestResponse,
+    IngestRowError, IngestSource, InheritanceIngest, InheritanceIngestRequest,
+    SupportCardIngestRequest, TrainerIngestRequest,
+};
+use crate::AppState;
+
+/// Write path for scraped data - mounted under /api/ingest
+///
+/// Workers post what they scraped here instead of writing to Postgres
+/// themselves. Requests must be signed like the other worker endpoints (see
+/// worker_auth_middleware); each one is written in a single transaction, with
+/// `source` recorded as the rows' provenance.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/trainer", post(ingest_trainers))
+        .route("/inheritance", post(ingest_inheritance))
+        .route("/support-cards", post(ingest_support_cards))
+        .route("/circle", post(ingest_circle))
+        .route("/bulk/circle-members", post(bulk_ingest_circle_members))
+        .route_layer(axum::middleware::from_fn(
+            crate::middleware::worker_auth_middleware,
+        ))
+}
+
+fn validate<T: Validate>(payload: &T) -> Result<(), AppError> {
+    Ok(payload.validate()?)
+}
+
+fn check_account_ids<'a>(account_ids: impl Iterator<Item = &'a AccountId>) -> Result<(), AppError> {
+    for account_id in account_ids {
+        if !is_valid_trainer_id(account_id.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Invalid trainer ID: {}",
+                account_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Start the write transaction, with `source` as the session provenance the
+/// inheritance/support_card triggers stamp on every row written
+async fn begin_with_source(
+    state: &AppState,
+    source: &IngestSource,
+) -> Result<Transaction<'static, Postgres>, AppError> {
+    let mut provenance = json!({ "worker_id": source.worker_id });
+    if let Some(server) = &source.server {
+        provenance["server"] = json!(server);
+    }
+    if let Some(batch_id) = &source.batch_id {
+        provenance["batch_id"] = json!(batch_id);
+    }
+
+    let mut tx = state.db.begin().await?;
+    sqlx::query("SELECT set_config('umamoe.source', $1, true)")
+        .bind(provenance.to_string())
+        .execute(&mut *tx)
+        .await?;
+    Ok(tx)
+}
+
+/// Reject rows for trainers that haven't been ingested yet
+async fn require_known_trainers(
+    tx: &mut Transaction<'static, Postgres>,
+    account_ids: &[AccountId],
+) -> Result<(), AppError> {
+    let known = sqlx::query_scalar::<_, AccountId>(
+        "SELECT account_id FROM trainer WHERE account_id = ANY($1)",
+    )
+    .bind(account_ids)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let unknown: Vec<&str> = account_ids
+        .iter()
+        .filter(|account_id| !known.contains(account_id))
+        .map(AccountId::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Unknown trainers (ingest them first): {}",
+            unknown.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn distinct_accounts<'a>(account_ids: impl Iterator<Item = &'a AccountId>) -> Vec<AccountId> {
+    let mut account_ids: Vec<AccountId> = account_ids.cloned().collect();
+    account_ids.sort();
+    account_ids.dedup();
+    account_ids
+}
+
+/// Mark trainers as freshly scraped
+async fn touch_trainers(
+    tx: &mut Transaction<'static, Postgres>,
+    account_ids: &[AccountId],
+) -> Result<(), AppError> {
+    sqlx::query("UPDATE trainer SET last_updated = CURRENT_TIMESTAMP WHERE account_id = ANY($1)")
+        .bind(account_ids)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// POST /api/ingest/trainer - Create or update trainers
+///
+/// Sets name, follower count and status, and stamps last_updated. Trainers
+/// whose data was deleted on request are skipped; a `comment` is only matched
+/// against open deletion and claim requests (see handlers::privacy and
+/// handlers::tasks). Users who
+/// bookmarked a trainer whose friend list was full are notified when it no
+/// longer is (see handlers::users).
+async fn ingest_trainers(
+    State(state): State<AppState>,
+    Json(payload): Json<TrainerIngestRequest>,
+) -> Result<Json<IngestResponse>, AppError> {
+    Ok(Json(store_trainers(&state, &payload).await?))
+}
+
+/// Create or update trainers (see ingest_trainers)
+pub(crate) async fn store_trainers(
+    state: &AppState,
+    payload: &TrainerIngestRequest,
+) -> Result<IngestResponse, AppError> {
+    validate(payload)?;
+    check_account_ids(payload.trainers.iter().map(|trainer| &trainer.account_id))?;
+
+    let mut tx = begin_with_source(state, &payload.source).await?;
+    let deleted = deleted_accounts(
+        &mut *tx,
+        payload.trainers.iter().map(|trainer| trainer.account_id.0.clone()).collect(),
+    )
+    .await?;
+
+    let scraped: Vec<(String, String, Option<i32>)> = payload
+        .trainers
+        .iter()
+        .filter(|trainer| !deleted.contains(trainer.account_id.as_str()))
+        .map(|trainer| (trainer.account_id.0.clone(), trainer.name.clone(), trainer.follower_num))
+        .collect();
+    let notified = record_friend_slot_openings(&mut tx, &scraped).await?;
+    let triggered = notifications::trigger_availability(&mut tx, &scraped).await?;
+
+    let mut response = IngestResponse::default();
+    for trainer in &payload.trainers {
+        if deleted.contains(trainer.account_id.as_str()) {
+            response.skipped += 1;
+            continue;
+        }
+        let inserted = sqlx::query_scalar::<_, bool>(
+            r#"
+            INSERT INTO trainer (account_id, name, follower_num, status, last_updated)
+            VALUES ($1, $2, $3, $4, CURRENT_TIMESTAMP)
+            ON CONFLICT (account_id) DO UPDATE SET
+                name = EXCLUDED.name,
+                follower_num = EXCLUDED.follower_num,
+                status = EXCLUDED.status,
+                last_updated = CURRENT_TIMESTAMP
+            RETURNING (xmax = 0)
+            "#,
+        )
+        .bind(&trainer.account_id)
+        .bind(&trainer.name)
+        .bind(trainer.follower_num)
+        .bind(&trainer.status)
+        .fetch_one(&mut *tx)
+        .await?;
+        count_row(&mut response, inserted);
+    }
+
+    let comments: Vec<(String, String)> = payload
+        .trainers
+        .iter()
+        .filter_map(|trainer| Some((trainer.account_id.0.clone(), trainer.comment.clone()?)))
+        .collect();
+    let verified = verify_comments(&mut tx, &comments).await?;
+    let claims_verified = verify_claim_comments(&mut tx, &comments).await?;
+    tx.commit().await?;
+    if verified > 0 {
+        tracing::info!("🔏 Verified {} data deletion request(s) from trainer comments", verified);
+    }
+    if claims_verified > 0 {
+        tracing::info!("🔏 Verified {} trainer claim request(s) from trainer comments", claims_verified);
+    }
+    if notified > 0 {
+        tracing::info!("🔔 Notified {} bookmark(s) of trainers with open friend slots", notified);
+    }
+    if triggered > 0 {
+        tracing::info!("🔔 Triggered {} availability subscription(s)", triggered);
+    }
+
+    let account_ids = distinct_accounts(
+        payload
+            .trainers
+            .iter()
+            .map(|trainer| &trainer.account_id)
+            .filter(|account_id| !deleted.contains(account_id.as_str())),
+    );
+    finish(&payload.source, "trainers", account_ids, &response);
+    Ok(response)
+}
+
+/// Replace the trainer's latest inheritance record ($1 = account_id)
+const INHERITANCE_UPDATE: &str = r#"
+    UPDATE inheritance SET
+        main_parent_id = $2, parent_left_id = $3, parent_right_id = $4,
+        parent_rank = $5, parent_rarity = $6,
+        blue_sparks = $7, pink_sparks = $8, green_sparks = $9, white_sparks = $10,
+        win_count = $11, white_count = $12,
+        main_blue_factors = $13, main_pink_factors = $14, main_green_factors = $15,
+        main_white_factors = $16, main_white_count = $17,
+        blue_stars_sum = $18, pink_stars_sum = $19, green_stars_sum = $20, white_stars_sum = $21,
+        affinity_scores = $22, base_affinity = $23, race_affinity = $24
+    WHERE inheritance_id = (
+        SELECT inheritance_id FROM inheritance
+        WHERE account_id = $1
+        ORDER BY inheritance_id DESC
+        LIMIT 1
+    )
+"#;
+
+const INHERITANCE_INSERT: &str = r#"
+    INSERT INTO inheritance (
+        account_id, main_parent_id, parent_left_id, parent_right_id,
+        parent_rank, parent_rarity,
+        blue_sparks, pink_sparks, green_sparks, white_sparks,
+        win_count, white_count,
+        main_blue_factors, main_pink_factors, main_green_factors,
+        main_white_factors, main_white_count,
+        blue_stars_sum, pink_stars_sum, green_stars_sum, white_stars_sum,
+        affinity_scores, base_affinity, race_affinity
+    )
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
+            $17, $18, $19, $20, $21, $22, $23, $24)
+"#;
+
+/// Bind a record in the parameter order of INHERITANCE_UPDATE / INHERITANCE_INSERT
+fn bind_inheritance<'q>(
+    query: Query<'q, Postgres, PgArguments>,
+    record: &'q InheritanceIngest,
+) -> Query<'q, Postgres, PgArguments> {
+    query
+        .bind(&record.account_id)
+        .bind(record.main_parent_id)
+        .bind(record.parent_left_id)
+        .bind(record.parent_right_id)
+        .bind(record.parent_rank)
+        .bind(record.parent_rarity)
+        .bind(&record.blue_sparks)
+        .bind(&record.pink_sparks)
+        .bind(&record.green_sparks)
+        .bind(&record.white_sparks)
+        .bind(record.win_count)
+        .bind(record.white_count)
+        .bind(record.main_blue_factors)
+        .bind(record.main_pink_factors)
+        .bind(record.main_green_factors)
+        .bind(&record.main_white_factors)
+        .bind(record.main_white_count)
+        .bind(record.blue_stars_sum)
+        .bind(record.pink_stars_sum)
+        .bind(record.green_stars_sum)
+        .bind(record.white_stars_sum)
+        .bind(&record.affinity_scores)
+        .bind(record.base_affinity)
+        .bind(record.race_affinity)
+}
+
+/// POST /api/ingest/inheritance - Store trainers' inheritance records
+///
+/// Replaces each trainer's latest record (or adds the first one). The trainers
+/// must exist already, except those deleted on request, which are skipped.
+/// Sparks and factors are in the v1 encoding.
+async fn ingest_inheritance(
+    State(state): State<AppState>,
+    Json(payload): Json<InheritanceIngestRequest>,
+) -> Result<Json<IngestResponse>, AppError> {
+    Ok(Json(store_inheritance(&state, &payload).await?))
+}
+
+/// Store inheritance records (see ingest_inheritance)
+pub(crate) async fn store_inheritance(
+    state: &AppState,
+    payload: &InheritanceIngestRequest,
+) -> Result<IngestResponse, AppError> {
+    validate(payload)?;
+    check_account_ids(payload.records.iter().map(|record| &record.account_id))?;
+    let mut account_ids = distinct_accounts(payload.records.iter().map(|record| &record.account_id));
+
+    let mut tx = begin_with_source(state, &payload.source).await?;
+    let deleted = deleted_accounts(&mut *tx, account_ids.iter().map(|id| id.0.clone()).collect()).await?;
+    account_ids.retain(|account_id| !deleted.contains(account_id.as_str()));
+    require_known_trainers(&mut tx, &account_ids).await?;
+
+    let mut response = IngestResponse::default();
+    for record in &payload.records {
+        if deleted.contains(record.account_id.as_str()) {
+            response.skipped += 1;
+            continue;
+        }
+        // Concurrent ingests of the same trainer would otherwise both insert
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('ingest:inheritance'), hashtext($1))")
+            .bind(&record.account_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let updated = bind_inheritance(sqlx::query(INHERITANCE_UPDATE), record)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if updated == 0 {
+            bind_inheritance(sqlx::query(INHERITANCE_INSERT), record)
+                .execute(&mut *tx)
+                .await?;
+        }
+        count_row(&mut response, updated == 0);
+    }
+    touch_trainers(&mut tx, &account_ids).await?;
+    tx.commit().await?;
+
+    finish(&payload.source, "inheritance records", account_ids, &response);
+    Ok(response)
+}
+
+/// POST /api/ingest/support-cards - Store trainers' support cards
+///
+/// Upserts by (trainer, card). The trainers must exist already, except those
+/// deleted on request, which are skipped.
+async fn ingest_support_cards(
+    State(state): State<AppState>,
+    Json(payload): Json<SupportCardIngestRequest>,
+) -> Result<Json<IngestResponse>, AppError> {
+    Ok(Json(store_support_cards(&state, &payload).await?))
+}
+
+/// Store support cards (see ingest_support_cards)
+pub(crate) async fn store_support_cards(
+    state: &AppState,
+    payload: &SupportCardIngestRequest,
+) -> Result<IngestResponse, AppError> {
+    validate(payload)?;
+    check_account_ids(payload.cards.iter().map(|card| &card.account_id))?;
+    let mut account_ids = distinct_accounts(payload.cards.iter().map(|card| &card.account_id));
+
+    let mut tx = begin_with_source(state, &payload.source).await?;
+    let deleted = deleted_accounts(&mut *tx, account_ids.iter().map(|id| id.0.clone()).collect()).await?;
+    account_ids.retain(|account_id| !deleted.contains(account_id.as_str()));
+    require_known_trainers(&mut tx, &account_ids).await?;
+
+    let mut response = IngestResponse::default();
+    for card in &payload.cards {
+        if deleted.contains(card.account_id.as_str()) {
+            response.skipped += 1;
+            continue;
+        }
+        let inserted = sqlx::query_scalar::<_, bool>(
+            r#"
+            INSERT INTO support_card (account_id, support_card_id, limit_break_count, experience)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (account_id, support_card_id) DO UPDATE SET
+                limit_break_count = EXCLUDED.limit_break_count,
+                experience = EXCLUDED.experience
+            RETURNING (xmax = 0)
+            "#,
+        )
+        .bind(&card.account_id)
+        .bind(card.support_card_id)
+        .bind(card.limit_break_count)
+        .bind(card.experience)
+        .fetch_one(&mut *tx)
+        .await?;
+        count_row(&mut response, inserted);
+    }
+    touch_trainers(&mut tx, &account_ids).await?;
+    tx.commit().await?;
+
+    finish(&payload.source, "support cards", account_ids, &response);
+    Ok(response)
+}
+
+/// POST /api/ingest/circle - Store a circle and its members' monthly fan counts
+///
+/// Upserts the circle (stamping last_updated) and each member's row for the
+/// given month, skipping members whose data was deleted on request.
+async fn ingest_circle(
+    State(state): State<AppState>,
+    Json(payload): Json<CircleIngestRequest>,
+) -> Result<Json<IngestResponse>, AppError> {
+    Ok(Json(store_circle(&state, &payload).await?))
+}
+
+/// Store a circle and its members (see ingest_circle)
+pub(crate) async fn store_circle(
+    state: &AppState,
+    payload: &CircleIngestRequest,
+) -> Result<IngestResponse, AppError> {
+    validate(payload)?;
+    let circle = &payload.circle;
+
+    let mut tx = begin_with_source(state, &payload.source).await?;
+    let deleted = deleted_accounts(
+        &mut *tx,
+        payload.members.iter().map(|member| member.viewer_id.to_string()).collect(),
+    )
+    .await?;
+    let members: Vec<_> = payload
+        .members
+        .iter()
+        .filter(|member| !deleted.contains(&member.viewer_id.to_string()))
+        .collect();
+    member_fan_partitions::ensure(&mut tx, members.iter().map(|m| (m.year, m.month))).await?;
+
+    let mut response = IngestResponse::default();
+    let inserted = sqlx::query_scalar::<_, bool>(
+        r#"
+        INSERT INTO circles (
+            circle_id, name, comment, leader_viewer_id, member_count, join_style, policy,
+            monthly_rank, monthly_point, last_month_rank, last_month_point,
+            created_at, last_updated
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+        ON CONFLICT (circle_id) DO UPDATE SET
+            name = EXCLUDED.name,
+            comment = EXCLUDED.comment,
+            leader_viewer_id = EXCLUDED.leader_viewer_id,
+            member_count = EXCLUDED.member_count,
+            join_style = EXCLUDED.join_style,
+            policy = EXCLUDED.policy,
+            monthly_rank = EXCLUDED.monthly_rank,
+            monthly_point = EXCLUDED.monthly_point,
+            last_month_rank = COALESCE(EXCLUDED.last_month_rank, circles.last_month_rank),
+            last_month_point = COALESCE(EXCLUDED.last_month_point, circles.last_month_point),
+            last_updated = CURRENT_TIMESTAMP
+        RETURNING (xmax = 0)
+        "#,
+    )
+    .bind(circle.circle_id)
+    .bind(&circle.name)
+    .bind(&circle.comment)
+    .bind(circle.leader_viewer_id)
+    .bind(circle.member_count)
+    .bind(circle.join_style)
+    .bind(circle.policy)
+    .bind(circle.monthly_rank)
+    .bind(circle.monthly_point)
+    .bind(circle.last_month_rank)
+    .bind(circle.last_month_point)
+    .fetch_one(&mut *tx)
+    .await?;
+    count_row(&mut response, inserted);
+    response.skipped = (payload.members.len() - members.len()) as u64;
+
+    for member in &members {
+        // xmax can't tell inserts from updates on a partitioned table
+        let inserted = sqlx::query_scalar::<_, bool>(
+            r#"
+            WITH existing AS (
+                SELECT 1 FROM circle_member_fans_monthly
+                WHERE year = $3 AND month = $4 AND circle_id = $1 AND viewer_id = $2
+            )
+            INSERT INTO circle_member_fans_monthly
+                (circle_id, viewer_id, year, month, daily_fans, last_updated)
+            VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP)
+            ON CONFLICT (year, month, circle_id, viewer_id) DO UPDATE SET
+                daily_fans = EXCLUDED.daily_fans,
+                last_updated = EXCLUDED.last_updated
+            RETURNING NOT EXISTS (SELECT 1 FROM existing)
+            "#,
+        )
+        .bind(circle.circle_id)
+        .bind(member.viewer_id)
+        .bind(member.year)
+        .bind(member.month)
+        .bind(&member.daily_fans)
+        .fetch_one(&mut *tx)
+        .await?;
+        count_row(&mut response, inserted);
+    }
+    tx.commit().await?;
+
+    // Circle responses are keyed by last_updated already; lookups and lists are not
+    for member in &members {
+        cache::invalidate(&format!("viewer_circle:{}", member.viewer_id));
+    }
+    cache::invalidate_prefix(&Namespace::Share.key(&format!("circle:{}:", circle.circle_id)));
+    cache::invalidate_prefix(&Namespace::Http.key("/api/v4/circles"));
+
+    tracing::info!(
+        "📥 Worker {} ingested circle {} with {} member rows ({} inserted, {} updated)",
+        payload.source.worker_id,
+        circle.circle_id,
+        members.len(),
+        response.inserted,
+        response.updated
+    );
+    Ok(response)
+}
+
+/// POST /api/ingest/bulk/circle-members - Load many circles' member rows at once
+///
+/// For full scrape cycles: up to 20000 rows are upserted with one set-based
+/// statement in one transaction. Rows that are invalid, belong to an unknown
+/// circle or a trainer deleted on request, or repeat an earlier row's
+/// circle/member/month (the last one wins) are skipped and listed in `errors`; the rest replace the stored row for
+/// their month or are added.
+async fn bulk_ingest_circle_members(
+    State(state): State<AppState>,
+    Json(payload): Json<BulkCircleMembersRequest>,
+) -> Result<Json<BulkIngestResponse>, AppError> {
+    Ok(Json(store_circle_members_bulk(&state, &payload).await?))
+}
+
+/// Load many circles' member rows (see bulk_ingest_circle_members)
+pub(crate) async fn store_circle_members_bulk(
+    state: &AppState,
+    payload: &BulkCircleMembersRequest,
+) -> Result<BulkIngestResponse, AppError> {
+    validate(payload)?;
+
+    let mut errors = Vec::new();
+    let mut fail = |index: usize, error: String| errors.push(IngestRowError { index, error });
+
+    // Per-row checks; for repeated keys only the last row is kept
+    let mut latest_by_key: HashMap<(i64, i64, i32, i32), usize> = HashMap::new();
+    for (index, row) in payload.members.iter().enumerate() {
+        if !(2020..=2100).contains(&row.year) || !(1..=12).contains(&row.month) {
+            fail(index, format!("Invalid month {}-{}", row.year, row.month));
+        } else if row.daily_fans.len() > 31 {
+            fail(index, format!("{} daily fan counts (at most 31)", row.daily_fans.len()));
+        } else {
+            let key = (row.circle_id.0, row.viewer_id.0, row.year, row.month);
+            if let Some(previous) = latest_by_key.insert(key, index) {
+                fail(previous, format!("Superseded by row {}", index));
+            }
+        }
+    }
+
+    let mut circle_ids: Vec<i64> = latest_by_key.keys().map(|key| key.0).collect();
+    circle_ids.sort_unstable();
+    circle_ids.dedup();
+
+    let mut tx = begin_with_source(state, &payload.source).await?;
+    let deleted = deleted_accounts(
+        &mut *tx,
+        latest_by_key.keys().map(|key| key.1.to_string()).collect(),
+    )
+    .await?;
+    let known: HashSet<i64> =
+        sqlx::query_scalar::<_, i64>("SELECT circle_id FROM circles WHERE circle_id = ANY($1)")
+            .bind(&circle_ids)
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .collect();
+
+    let mut rows: Vec<usize> = Vec::with_capacity(latest_by_key.len());
+    for (key, index) in latest_by_key {
+        if deleted.contains(&key.1.to_string()) {
+            fail(index, format!("Data of trainer {} was deleted on request", key.1));
+        } else if known.contains(&key.0) {
+            rows.push(index);
+        } else {
+            fail(index, format!("Unknown circle {}", key.0));
+        }
+    }
+    rows.sort_unstable();
+
+    let members: Vec<_> = rows.iter().map(|&index| &payload.members[index]).collect();
+    member_fan_partitions::ensure(&mut tx, members.iter().map(|row| (row.year, row.month))).await?;
+    let circles: Vec<i64> = members.iter().map(|row| row.circle_id.0).collect();
+    let viewers: Vec<i64> = members.iter().map(|row| row.viewer_id.0).collect();
+    let years: Vec<i32> = members.iter().map(|row| row.year).collect();
+    let months: Vec<i32> = members.iter().map(|row| row.month).collect();
+    // Jagged fan arrays don't fit a 2-D array parameter, so each goes as a text literal
+    let fans: Vec<String> = members
+        .iter()
+        .map(|row| {
+            let fans: Vec<String> = row.daily_fans.iter().map(i64::to_string).collect();
+            format!("{{{}}}", fans.join(","))
+        })
+        .collect();
+
+    // Rows already stored are counted from the statement's snapshot, since xmax
+    // can't tell inserts from updates on a partitioned table
+    let (written, updated) = sqlx::query_as::<_, (i64, i64)>(
+        r#"
+        WITH v AS (
+            SELECT * FROM unnest($1::bigint[], $2::bigint[], $3::int[], $4::int[], $5::text[])
+                AS v(circle_id, viewer_id, year, month, daily_fans)
+        ),
+        existing AS (
+            SELECT COUNT(*) AS count
+            FROM circle_member_fans_monthly m
+            JOIN v USING (year, month, circle_id, viewer_id)
+        ),
+        written AS (
+            INSERT INTO circle_member_fans_monthly
+                (circle_id, viewer_id, year, month, daily_fans, last_updated)
+            SELECT circle_id, viewer_id, year, month, daily_fans::bigint[], CURRENT_TIMESTAMP
+            FROM v
+            ON CONFLICT (year, month, circle_id, viewer_id) DO UPDATE SET
+                daily_fans = EXCLUDED.daily_fans,
+                last_updated = EXCLUDED.last_updated
+            RETURNING 1
+        )
+        SELECT (SELECT COUNT(*) FROM written), (SELECT count FROM existing)
+        "#,
+    )
+    .bind(&circles)
+    .bind(&viewers)
+    .bind(&years)
+    .bind(&months)
+    .bind(&fans)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    for viewer_id in &viewers {
+        cache::invalidate(&format!("viewer_circle:{}", viewer_id));
+    }
+    cache::invalidate_prefix(&Namespace::Http.key("/api/v4/circles"));
+
+    errors.sort_by_key(|error| error.index);
+    let response = BulkIngestResponse {
+        inserted: (written - updated) as u64,
+        updated: updated as u64,
+        failed: errors.len(),
+        errors,
+    };
+    tracing::info!(
+        "📥 Worker {} bulk-loaded member rows of {} circle(s) ({} inserted, {} updated, {} failed)",
+        payload.source.worker_id,
+        known.len(),
+        response.inserted,
+        response.updated,
+        response.failed
+    );
+    Ok(response)
+}
+
+fn count_row(response: &mut IngestResponse, inserted: bool) {
+    if inserted {
+        response.inserted += 1;
+    } else {
+        response.updated += 1;
+    }
+}
+
+/// Log the write and drop cached search pages, counts and shares for the trainers
+fn finish(source: &IngestSource, what: &str, account_ids: Vec<AccountId>, response: &IngestResponse) {
+    tracing::info!(
+        "📥 Worker {} ingested {} for {} trainer(s) ({} inserted, {} updated)",
+        source.worker_id,
+        what,
+        account_ids.len(),
+        response.inserted,
+        response.updated
+    );
+    events::publish(DomainEvent::TrainerDataChanged {
+        account_ids: account_ids.into_iter().map(|account_id| account_id.0).collect(),
+    });
+}
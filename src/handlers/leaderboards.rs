@@ -0,0 +1,433 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+    routing::get,
+    Router,
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use sqlx::{PgConnection, PgPool};
+use std::time::Duration;
+
+use crate::errors::AppError;
+use crate::handlers::users::FRIEND_LIST_FULL;
+use crate::models::{
+    LeaderboardEntry, LeaderboardKind, LeaderboardParams, LeaderboardResponse,
+    SupportCardLeaderboardEntry, SupportCardLeaderboardParams, SupportCardLeaderboardResponse,
+};
+use crate::AppState;
+
+/// Leaderboards only change once a day (support cards once a week)
+const LEADERBOARD_CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// Cards lent by fewer trainers rank after all others by MLB rate, so a card
+/// two trainers own (both MLB) doesn't top the list
+const MLB_RANK_MIN_OWNERS: i32 = 10;
+
+/// Entries kept per global leaderboard snapshot
+const LEADERBOARD_SIZE: i64 = 100;
+
+/// Daily snapshots older than this are dropped
+const LEADERBOARD_HISTORY_DAYS: i64 = 180;
+
+/// Community leaderboards - mounted under /api/leaderboards
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/support-cards",
+            get(get_support_card_leaderboard).layer(axum::middleware::from_fn_with_state(
+                LEADERBOARD_CACHE_TTL,
+                crate::middleware::response_cache_middleware,
+            )),
+        )
+        .route(
+            "/:kind",
+            get(get_leaderboard).layer(axum::middleware::from_fn_with_state(
+                LEADERBOARD_CACHE_TTL,
+                crate::middleware::response_cache_middleware,
+            )),
+        )
+}
+
+/// ISO week label of a week's Monday, e.g. "2026-W42"
+fn week_label(week_start: NaiveDate) -> String {
+    let week = week_start.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+/// Monday of an ISO week given as "YYYY-Www"
+fn parse_week(week: &str) -> Option<NaiveDate> {
+    let (year, week) = week.trim().split_once("-W")?;
+    NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, chrono::Weekday::Mon)
+}
+
+/// GET /api/leaderboards/support-cards - Weekly usage and MLB-rate leaderboard
+/// of support cards, for the community tier list
+///
+/// Parameters:
+/// - week: ISO week, e.g. "2026-W42" (default: the latest computed week)
+/// - sort_by: usage (default) or mlb_rate
+///
+/// Usage is the share of active (non-stale) trainers lending a card; MLB rate
+/// is the share of those owners lending it max limit broken.
+pub async fn get_support_card_leaderboard(
+    State(state): State<AppState>,
+    Query(params): Query<SupportCardLeaderboardParams>,
+) -> Result<Json<SupportCardLeaderboardResponse>, AppError> {
+    let order_by = match params.sort_by.as_deref().unwrap_or("usage") {
+        "usage" => "l.usage_rank, l.support_card_id",
+        "mlb_rate" => "l.mlb_rank, l.support_card_id",
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid sort_by '{}' (expected usage or mlb_rate)",
+                other
+            )))
+        }
+    };
+
+    let week_start = match params.week.as_deref() {
+        Some(week) => parse_week(week).ok_or_else(|| {
+            AppError::BadRequest(format!("Invalid week '{}' (expected e.g. 2026-W42)", week))
+        })?,
+        None => sqlx::query_scalar::<_, Option<NaiveDate>>(
+            "SELECT MAX(week_start) FROM support_card_leaderboard",
+        )
+        .fetch_one(state.read_db())
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound("The support card leaderboard hasn't been computed yet".to_string())
+        })?,
+    };
+
+    let summary = sqlx::query_as::<_, (i32, NaiveDateTime)>(
+        "SELECT trainers, computed_at FROM support_card_leaderboard WHERE week_start = $1 LIMIT 1",
+    )
+    .bind(week_start)
+    .fetch_optional(state.read_db())
+    .await?;
+    let Some((trainers, computed_at)) = summary else {
+        return Err(AppError::NotFound(format!(
+            "No support card leaderboard for {}",
+            week_label(week_start)
+        )));
+    };
+
+    let cards = sqlx::query_as::<_, SupportCardLeaderboardEntry>(&format!(
+        r#"
+        SELECT l.support_card_id, m.name, m.rarity, m.card_type,
+               l.owners, l.usage_rate, l.usage_rank,
+               l.mlb_count, l.mlb_rate, l.mlb_rank, l.avg_limit_break
+        FROM support_card_leaderboard l
+        LEFT JOIN support_card_meta m ON m.support_card_id = l.support_card_id
+        WHERE l.week_start = $1
+        ORDER BY {}
+        "#,
+        order_by
+    ))
+    .bind(week_start)
+    .fetch_all(state.read_db())
+    .await?;
+
+    Ok(Json(SupportCardLeaderboardResponse {
+        week: week_label(week_start),
+        week_start,
+        trainers,
+        computed_at,
+        cards,
+    }))
+}
+
+/// Snapshot the support cards active trainers lend into the week's
+/// leaderboard, replacing any earlier snapshot of that week. Returns the
+/// number of cards ranked.
+pub(crate) async fn compute_support_card_leaderboard(
+    pool: &PgPool,
+    week_start: NaiveDate,
+) -> Result<u64, AppError> {
+    let mut tx = crate::database::begin_long_running(pool).await?;
+
+    sqlx::query("DELETE FROM support_card_leaderboard WHERE week_start = $1")
+        .bind(week_start)
+        .execute(&mut *tx)
+        .await?;
+
+    let result = sqlx::query(
+        r#"
+        WITH lent AS (
+            SELECT sc.account_id, sc.support_card_id, sc.limit_break_count
+            FROM support_card sc
+            JOIN trainer t ON t.account_id = sc.account_id
+            WHERE NOT t.stale
+        ),
+        total AS (
+            SELECT COUNT(DISTINCT account_id)::int AS trainers FROM lent
+        ),
+        cards AS (
+            SELECT
+                support_card_id,
+                COUNT(*)::int AS owners,
+                COUNT(*) FILTER (WHERE limit_break_count = 4)::int AS mlb_count,
+                AVG(limit_break_count)::float8 AS avg_limit_break
+            FROM lent
+            GROUP BY support_card_id
+        )
+        INSERT INTO support_card_leaderboard (
+            week_start, support_card_id, trainers, owners, usage_rate, usage_rank,
+            mlb_count, mlb_rate, mlb_rank, avg_limit_break, computed_at
+        )
+        SELECT
+            $1,
+            c.support_card_id,
+            total.trainers,
+            c.owners,
+            c.owners::float8 / total.trainers,
+            RANK() OVER (ORDER BY c.owners DESC),
+            c.mlb_count,
+            c.mlb_count::float8 / c.owners,
+            RANK() OVER (ORDER BY c.owners >= $2 DESC, c.mlb_count::float8 / c.owners DESC, c.owners DESC),
+            c.avg_limit_break,
+            CURRENT_TIMESTAMP
+        FROM cards c
+        CROSS JOIN total
+        "#,
+    )
+    .bind(week_start)
+    .bind(MLB_RANK_MIN_OWNERS)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected())
+}
+
+/// GET /api/leaderboards/:kind - Top 100 trainers of a daily leaderboard
+/// snapshot, with rank changes
+///
+/// Kinds: win_count, white_count, main_white_count (of the trainer's
+/// inheritance record) and availability_streak (days in a row with room on the
+/// friend list). Only active (non-stale) trainers are ranked; ties share a rank.
+///
+/// Parameters:
+/// - date: Snapshot day, YYYY-MM-DD (default: the latest snapshot)
+/// - compare_to: Day to compute rank and value changes against (default: the
+///   snapshot before), e.g. a week before for "this week" pages
+/// - limit: Entries to return (1-100, default 100)
+pub async fn get_leaderboard(
+    State(state): State<AppState>,
+    Path(kind): Path<String>,
+    Query(params): Query<LeaderboardParams>,
+) -> Result<Json<LeaderboardResponse>, AppError> {
+    let kind = LeaderboardKind::parse(&kind).ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Unknown leaderboard '{}' (expected win_count, white_count, main_white_count or availability_streak)",
+            kind
+        ))
+    })?;
+    let limit = params.limit.unwrap_or(LEADERBOARD_SIZE);
+    if !(1..=LEADERBOARD_SIZE).contains(&limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            LEADERBOARD_SIZE
+        )));
+    }
+
+    let snapshot = sqlx::query_as::<_, (NaiveDate, NaiveDateTime)>(
+        r#"
+        SELECT snapshot_date, computed_at FROM leaderboard_snapshots
+        WHERE kind = $1 AND ($2::date IS NULL OR snapshot_date = $2)
+        ORDER BY snapshot_date DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(params.date)
+    .fetch_optional(state.read_db())
+    .await?;
+    let Some((snapshot_date, computed_at)) = snapshot else {
+        return Err(AppError::NotFound(match params.date {
+            Some(date) => format!("No {} leaderboard for {}", kind.as_str(), date),
+            None => format!("The {} leaderboard hasn't been computed yet", kind.as_str()),
+        }));
+    };
+
+    // Comparing against the snapshot itself (or a later one) gives no changes
+    let compare_to = params.compare_to.unwrap_or(snapshot_date - chrono::Duration::days(1));
+    let compared_to = sqlx::query_scalar::<_, NaiveDate>(
+        r#"
+        SELECT snapshot_date FROM leaderboard_snapshots
+        WHERE kind = $1 AND snapshot_date <= $2 AND snapshot_date < $3
+        ORDER BY snapshot_date DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(compare_to)
+    .bind(snapshot_date)
+    .fetch_optional(state.read_db())
+    .await?;
+
+    let entries = sqlx::query_as::<_, LeaderboardEntry>(
+        r#"
+        SELECT e.rank, p.rank AS previous_rank, p.rank - e.rank AS rank_delta,
+               e.account_id, COALESCE(t.display_name_override, t.name) AS trainer_name,
+               t.follower_num,
+               e.inheritance_id, i.main_parent_id,
+               e.value, e.value - p.value AS value_delta
+        FROM leaderboard_entries e
+        LEFT JOIN leaderboard_entries p
+            ON p.kind = e.kind AND p.snapshot_date = $3 AND p.account_id = e.account_id
+        LEFT JOIN trainer t ON t.account_id = e.account_id
+        LEFT JOIN inheritance i ON i.inheritance_id = e.inheritance_id
+        WHERE e.kind = $1 AND e.snapshot_date = $2
+        ORDER BY e.rank, e.account_id
+        LIMIT $4
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(snapshot_date)
+    .bind(compared_to)
+    .bind(limit)
+    .fetch_all(state.read_db())
+    .await?
+    .into_iter()
+    .map(|mut entry| {
+        entry.trainer_name = entry.trainer_name.as_deref().map(crate::moderation::mask_text);
+        entry
+    })
+    .collect();
+
+    Ok(Json(LeaderboardResponse {
+        kind,
+        snapshot_date,
+        compared_to,
+        computed_at,
+        entries,
+    }))
+}
+
+/// The (account_id, inheritance_id, value) rows a leaderboard ranks
+fn leaderboard_source(kind: LeaderboardKind) -> &'static str {
+    match kind {
+        LeaderboardKind::WinCount => {
+            "SELECT i.account_id, i.inheritance_id, i.win_count::bigint AS value FROM inheritance i"
+        }
+        LeaderboardKind::WhiteCount => {
+            "SELECT i.account_id, i.inheritance_id, i.white_count::bigint AS value FROM inheritance i"
+        }
+        LeaderboardKind::MainWhiteCount => {
+            "SELECT i.account_id, i.inheritance_id, i.main_white_count::bigint AS value FROM inheritance i"
+        }
+        LeaderboardKind::AvailabilityStreak => {
+            r#"
+            SELECT s.account_id, NULL::int AS inheritance_id, ($1 - s.since + 1)::bigint AS value
+            FROM trainer_availability_streaks s
+            WHERE s.checked_on = $1
+            "#
+        }
+    }
+}
+
+/// Carry the availability streaks forward to `snapshot_date`: trainers with
+/// room on their friend list continue (or start) a streak, the others lose it.
+/// Running again for the same day changes nothing.
+async fn update_availability_streaks(
+    conn: &mut PgConnection,
+    snapshot_date: NaiveDate,
+) -> Result<(), AppError> {
+    // Every remaining row was available on the previous run, so existing rows
+    // keep their start day
+    sqlx::query(
+        r#"
+        INSERT INTO trainer_availability_streaks (account_id, since, checked_on)
+        SELECT account_id, $1, $1 FROM trainer
+        WHERE NOT stale AND follower_num < $2
+        ON CONFLICT (account_id) DO UPDATE SET checked_on = EXCLUDED.checked_on
+        WHERE trainer_availability_streaks.checked_on < EXCLUDED.checked_on
+        "#,
+    )
+    .bind(snapshot_date)
+    .bind(FRIEND_LIST_FULL)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("DELETE FROM trainer_availability_streaks WHERE checked_on < $1")
+        .bind(snapshot_date)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Take every global leaderboard's snapshot for `snapshot_date`, replacing
+/// any earlier snapshot of that day, and drop snapshots past
+/// LEADERBOARD_HISTORY_DAYS. Returns the number of entries written.
+pub(crate) async fn compute_leaderboards(
+    pool: &PgPool,
+    snapshot_date: NaiveDate,
+) -> Result<u64, AppError> {
+    let mut tx = crate::database::begin_long_running(pool).await?;
+
+    update_availability_streaks(&mut tx, snapshot_date).await?;
+
+    let mut entries = 0;
+    for kind in LeaderboardKind::ALL {
+        sqlx::query(
+            r#"
+            INSERT INTO leaderboard_snapshots (kind, snapshot_date, computed_at)
+            VALUES ($2, $1, CURRENT_TIMESTAMP)
+            ON CONFLICT (kind, snapshot_date) DO UPDATE SET computed_at = EXCLUDED.computed_at
+            "#,
+        )
+        .bind(snapshot_date)
+        .bind(kind.as_str())
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM leaderboard_entries WHERE kind = $1 AND snapshot_date = $2")
+            .bind(kind.as_str())
+            .bind(snapshot_date)
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query(&format!(
+            r#"
+            WITH source AS ({}),
+            -- One entry per trainer, for the best of their records
+            best AS (
+                SELECT DISTINCT ON (s.account_id) s.account_id, s.inheritance_id, s.value
+                FROM source s
+                JOIN trainer t ON t.account_id = s.account_id
+                WHERE NOT t.stale AND s.value > 0
+                ORDER BY s.account_id, s.value DESC, s.inheritance_id DESC
+            ),
+            ranked AS (
+                SELECT
+                    account_id,
+                    inheritance_id,
+                    value,
+                    RANK() OVER (ORDER BY value DESC) AS rank,
+                    ROW_NUMBER() OVER (ORDER BY value DESC, account_id) AS position
+                FROM best
+            )
+            INSERT INTO leaderboard_entries (kind, snapshot_date, account_id, inheritance_id, value, rank)
+            SELECT $2, $1, account_id, inheritance_id, value, rank
+            FROM ranked
+            WHERE position <= $3
+            "#,
+            leaderboard_source(kind)
+        ))
+        .bind(snapshot_date)
+        .bind(kind.as_str())
+        .bind(LEADERBOARD_SIZE)
+        .execute(&mut *tx)
+        .await?;
+        entries += result.rows_affected();
+    }
+
+    sqlx::query("DELETE FROM leaderboard_snapshots WHERE snapshot_date < $1")
+        .bind(snapshot_date - chrono::Duration::days(LEADERBOARD_HISTORY_DAYS))
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(entries)
+}
@@ -0,0 +1,258 @@
+use axum::{
+    extract::{ConnectInfo, Path, State},
+    http::HeaderMap,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde_json::json;
+use sqlx::PgConnection;
+use std::net::SocketAddr;
+use validator::Validate;
+
+use crate::errors::AppError;
+use crate::handlers::users::FRIEND_LIST_FULL;
+use crate::handlers::votes::client_hash;
+use crate::middleware::turnstile::require_turnstile;
+use crate::models::{
+    MatchmakingRequest, MatchmakingStatus, MatchmakingSuggestion, MatchmakingTicket,
+};
+use crate::AppState;
+
+/// Tickets nobody has looked at for this long are dropped, releasing their trainer
+const TICKET_TTL_MINUTES: i32 = 30;
+
+const TICKET_COLUMNS: &str = r#"
+    ticket_id, status, main_parent_id, min_parent_rank, min_win_count,
+    reserved_until, expires_at
+"#;
+
+/// Friend matchmaking queue - mounted under /api/matchmaking
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(open_ticket))
+        .route("/:ticket_id", get(get_ticket).delete(cancel_ticket))
+        .route("/:ticket_id/next", post(next_suggestion))
+}
+
+/// Serialize matching until the transaction ends, so two tickets are never
+/// handed the same trainer
+async fn lock_queue(conn: &mut PgConnection) -> Result<(), AppError> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('matchmaking'))")
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Reserve the best free trainer for a ticket, or leave it waiting if there
+/// is none. Trainers copied least recently come first, so demand spreads over
+/// everyone that fits; ties go to the better parent rank.
+async fn match_ticket(conn: &mut PgConnection, ticket_id: &str) -> Result<(), AppError> {
+    let account_id = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT t.account_id
+        FROM matchmaking_tickets mt
+        JOIN inheritance i ON CASE WHEN mt.main_parent_id > 100000
+            THEN i.main_parent_id = mt.main_parent_id
+            ELSE i.main_parent_id / 100 = mt.main_parent_id END
+        JOIN trainer t ON t.account_id = i.account_id
+        LEFT JOIN trainer_copies tc ON tc.trainer_id = t.account_id
+        WHERE mt.ticket_id = $1
+          AND NOT t.stale
+          AND t.follower_num < $2
+          AND (mt.min_parent_rank IS NULL OR i.parent_rank >= mt.min_parent_rank)
+          AND (mt.min_win_count IS NULL OR i.win_count >= mt.min_win_count)
+          AND t.account_id <> ALL(mt.skipped)
+          AND NOT EXISTS (
+              SELECT 1 FROM matchmaking_tickets r
+              WHERE r.status = 'matched' AND r.account_id = t.account_id
+                AND r.reserved_until > LOCALTIMESTAMP AND r.ticket_id <> mt.ticket_id
+          )
+        ORDER BY tc.last_copied ASC NULLS FIRST, i.parent_rank DESC, t.follower_num ASC, t.account_id
+        LIMIT 1
+        "#,
+    )
+    .bind(ticket_id)
+    .bind(FRIEND_LIST_FULL)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE matchmaking_tickets SET
+            status = CASE WHEN $2::text IS NULL THEN 'waiting' ELSE 'matched' END,
+            account_id = $2,
+            reserved_until = CASE WHEN $2::text IS NULL THEN NULL
+                ELSE LOCALTIMESTAMP + make_interval(secs => $3) END,
+            expires_at = LOCALTIMESTAMP + make_interval(mins => $4)
+        WHERE ticket_id = $1
+        "#,
+    )
+    .bind(ticket_id)
+    .bind(account_id)
+    .bind(crate::config::get().matchmaking_reservation.as_secs_f64())
+    .bind(TICKET_TTL_MINUTES)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+async fn load_ticket(
+    conn: &mut PgConnection,
+    ticket_id: &str,
+) -> Result<MatchmakingTicket, AppError> {
+    let mut ticket = sqlx::query_as::<_, MatchmakingTicket>(&format!(
+        "SELECT {} FROM matchmaking_tickets WHERE ticket_id = $1 AND expires_at > LOCALTIMESTAMP",
+        TICKET_COLUMNS
+    ))
+    .bind(ticket_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("No open matchmaking ticket {}", ticket_id)))?;
+
+    if ticket.status == MatchmakingStatus::Matched {
+        ticket.suggestion = sqlx::query_as::<_, MatchmakingSuggestion>(
+            r#"
+            SELECT t.account_id, COALESCE(t.display_name_override, t.name) AS trainer_name,
+                   t.follower_num, i.inheritance_id, i.main_parent_id, i.parent_rank, i.win_count, i.white_count
+            FROM matchmaking_tickets mt
+            JOIN trainer t ON t.account_id = mt.account_id
+            JOIN inheritance i ON i.account_id = t.account_id
+            WHERE mt.ticket_id = $1
+            ORDER BY i.parent_rank DESC, i.inheritance_id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(ticket_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|mut suggestion| {
+            suggestion.trainer_name = suggestion
+                .trainer_name
+                .as_deref()
+                .map(crate::moderation::mask_text);
+            suggestion
+        });
+    }
+    Ok(ticket)
+}
+
+/// POST /api/matchmaking - Open a ticket for a parent and get an available
+/// trainer with it, reserved for a few minutes
+///
+/// Requires a Turnstile token. A client holds one ticket; opening another
+/// drops the previous one and releases its trainer. If no trainer is free the
+/// ticket waits, and GET on it tries again.
+///
+/// Body:
+/// - main_parent_id: Base character (1007) or a specific card (100701)
+/// - min_parent_rank: Lowest parent rank to suggest (optional)
+/// - min_win_count: Lowest win count to suggest (optional)
+pub async fn open_ticket(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<MatchmakingRequest>,
+) -> Result<Json<MatchmakingTicket>, AppError> {
+    payload.validate()?;
+    require_turnstile(&state, &headers, addr).await?;
+    let requester_hash = client_hash(&headers, addr);
+    let ticket_id = uuid::Uuid::new_v4().simple().to_string();
+
+    let mut tx = state.db.begin().await?;
+    lock_queue(&mut tx).await?;
+    sqlx::query(
+        "DELETE FROM matchmaking_tickets WHERE requester_hash = $1 OR expires_at <= LOCALTIMESTAMP",
+    )
+    .bind(&requester_hash)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO matchmaking_tickets (
+            ticket_id, requester_hash, main_parent_id, min_parent_rank, min_win_count, expires_at
+        )
+        VALUES ($1, $2, $3, $4, $5, LOCALTIMESTAMP + make_interval(mins => $6))
+        "#,
+    )
+    .bind(&ticket_id)
+    .bind(&requester_hash)
+    .bind(payload.main_parent_id)
+    .bind(payload.min_parent_rank)
+    .bind(payload.min_win_count)
+    .bind(TICKET_TTL_MINUTES)
+    .execute(&mut *tx)
+    .await?;
+    match_ticket(&mut tx, &ticket_id).await?;
+    let ticket = load_ticket(&mut tx, &ticket_id).await?;
+    tx.commit().await?;
+
+    Ok(Json(ticket))
+}
+
+/// GET /api/matchmaking/:ticket_id - The ticket and its suggestion; a waiting
+/// ticket tries to match again
+pub async fn get_ticket(
+    State(state): State<AppState>,
+    Path(ticket_id): Path<String>,
+) -> Result<Json<MatchmakingTicket>, AppError> {
+    let mut tx = state.db.begin().await?;
+    let ticket = load_ticket(&mut tx, &ticket_id).await?;
+    if ticket.status == MatchmakingStatus::Matched {
+        return Ok(Json(ticket));
+    }
+
+    lock_queue(&mut tx).await?;
+    match_ticket(&mut tx, &ticket_id).await?;
+    let ticket = load_ticket(&mut tx, &ticket_id).await?;
+    tx.commit().await?;
+
+    Ok(Json(ticket))
+}
+
+/// POST /api/matchmaking/:ticket_id/next - Pass on the suggestion (e.g. the
+/// friend list turned out full) and reserve another trainer
+///
+/// Trainers passed on aren't suggested to the ticket again.
+pub async fn next_suggestion(
+    State(state): State<AppState>,
+    Path(ticket_id): Path<String>,
+) -> Result<Json<MatchmakingTicket>, AppError> {
+    let mut tx = state.db.begin().await?;
+    lock_queue(&mut tx).await?;
+    load_ticket(&mut tx, &ticket_id).await?;
+    sqlx::query(
+        r#"
+        UPDATE matchmaking_tickets
+        SET skipped = array_append(skipped, account_id)
+        WHERE ticket_id = $1 AND account_id IS NOT NULL
+        "#,
+    )
+    .bind(&ticket_id)
+    .execute(&mut *tx)
+    .await?;
+    match_ticket(&mut tx, &ticket_id).await?;
+    let ticket = load_ticket(&mut tx, &ticket_id).await?;
+    tx.commit().await?;
+
+    Ok(Json(ticket))
+}
+
+/// DELETE /api/matchmaking/:ticket_id - Drop the ticket, releasing its trainer
+pub async fn cancel_ticket(
+    State(state): State<AppState>,
+    Path(ticket_id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let deleted = sqlx::query("DELETE FROM matchmaking_tickets WHERE ticket_id = $1")
+        .bind(&ticket_id)
+        .execute(&state.db)
+        .await?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!(
+            "No open matchmaking ticket {}",
+            ticket_id
+        )));
+    }
+    Ok(Json(json!({ "success": true })))
+}
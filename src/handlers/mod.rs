@@ -1,5 +1,21 @@
+pub mod admin;
+pub mod analytics;
+pub mod announcements;
 pub mod circles;
+pub mod feeds;
+pub mod graphql;
+pub mod ingest;
+pub mod leaderboards;
+pub mod matchmaking;
+pub mod notifications;
+pub mod privacy;
+pub mod reports;
 pub mod search;
 pub mod sharing;
+pub mod sitemap;
 pub mod stats;
 pub mod tasks;
+pub mod team_stadium;
+pub mod users;
+pub mod votes;
+pub mod workers;
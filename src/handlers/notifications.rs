@@ -0,0 +1,274 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+    routing::{delete, get},
+    Extension, Router,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde_json::json;
+use validator::Validate;
+
+use crate::errors::AppError;
+use crate::handlers::circles::DISCORD_WEBHOOK_PREFIXES;
+use crate::handlers::tasks::is_valid_trainer_id;
+use crate::handlers::users::{signed_in, FRIEND_LIST_FULL};
+use crate::middleware::user_auth::UserIdentity;
+use crate::models::{
+    AvailabilitySubscription, AvailabilitySubscriptionRequest, NotificationChannel,
+    VapidKeyResponse,
+};
+use crate::notifications::web_push;
+use crate::AppState;
+
+/// Push services browsers hand out endpoints on; anything else is refused so
+/// subscriptions can't point the dispatcher at arbitrary hosts
+const PUSH_SERVICE_HOSTS: [&str; 3] = [
+    "fcm.googleapis.com",
+    "updates.push.services.mozilla.com",
+    "web.push.apple.com",
+];
+const PUSH_SERVICE_HOST_SUFFIXES: [&str; 2] = [".notify.windows.com", ".push.apple.com"];
+
+/// Columns of AvailabilitySubscription (s = availability_subscriptions, t = trainer)
+const SUBSCRIPTION_COLUMNS: &str = r#"
+    s.subscription_id, s.account_id,
+    COALESCE(t.display_name_override, t.name) AS trainer_name, t.follower_num,
+    s.channel, s.status, s.created_at, s.triggered_at, s.delivered_at,
+    s.attempts, s.last_error
+"#;
+
+/// Trainer availability subscriptions - merged into the /api/users router
+/// (behind user_auth_middleware)
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/push/vapid-key", get(get_vapid_key))
+        .route(
+            "/me/subscriptions",
+            get(list_subscriptions).post(create_subscription),
+        )
+        .route("/me/subscriptions/:subscription_id", delete(delete_subscription))
+}
+
+/// GET /api/users/push/vapid-key - The server's VAPID public key for
+/// `pushManager.subscribe()`; 503 when web push isn't configured
+async fn get_vapid_key() -> Result<Json<VapidKeyResponse>, AppError> {
+    let vapid = crate::config::get()
+        .notifications
+        .vapid
+        .as_ref()
+        .ok_or_else(|| AppError::ServiceUnavailable("Web push is not enabled".to_string()))?;
+    Ok(Json(VapidKeyResponse {
+        public_key: URL_SAFE_NO_PAD.encode(&vapid.public_key),
+    }))
+}
+
+/// GET /api/users/me/subscriptions - The account's availability subscriptions,
+/// newest first
+async fn list_subscriptions(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+) -> Result<Json<Vec<AvailabilitySubscription>>, AppError> {
+    let identity = signed_in(identity)?;
+    let subscriptions = sqlx::query_as::<_, AvailabilitySubscription>(&format!(
+        r#"
+        SELECT {}
+        FROM availability_subscriptions s
+        LEFT JOIN trainer t ON t.account_id = s.account_id
+        WHERE s.user_id = $1
+        ORDER BY s.created_at DESC, s.subscription_id DESC
+        "#,
+        SUBSCRIPTION_COLUMNS
+    ))
+    .bind(identity.user_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(
+        subscriptions
+            .into_iter()
+            .map(|mut subscription| {
+                subscription.trainer_name = subscription
+                    .trainer_name
+                    .as_deref()
+                    .map(crate::moderation::mask_text);
+                subscription
+            })
+            .collect(),
+    ))
+}
+
+fn is_push_service_host(host: &str) -> bool {
+    PUSH_SERVICE_HOSTS.contains(&host)
+        || PUSH_SERVICE_HOST_SUFFIXES
+            .iter()
+            .any(|suffix| host.ends_with(suffix))
+}
+
+/// Where a subscription delivers to: (endpoint, p256dh, auth)
+fn destination(
+    payload: &AvailabilitySubscriptionRequest,
+) -> Result<(String, Option<String>, Option<String>), AppError> {
+    match payload.channel {
+        NotificationChannel::Discord => {
+            let webhook_url = payload.webhook_url.as_deref().map(str::trim).unwrap_or("");
+            if !DISCORD_WEBHOOK_PREFIXES
+                .iter()
+                .any(|prefix| webhook_url.starts_with(prefix))
+            {
+                return Err(AppError::BadRequest(
+                    "webhook_url must be a Discord webhook URL".to_string(),
+                ));
+            }
+            Ok((webhook_url.to_string(), None, None))
+        }
+        NotificationChannel::WebPush => {
+            if crate::config::get().notifications.vapid.is_none() {
+                return Err(AppError::ServiceUnavailable("Web push is not enabled".to_string()));
+            }
+            let subscription = payload.push_subscription.as_ref().ok_or_else(|| {
+                AppError::BadRequest("push_subscription is required for web_push".to_string())
+            })?;
+            let endpoint = url::Url::parse(&subscription.endpoint)
+                .ok()
+                .filter(|url| url.scheme() == "https")
+                .filter(|url| url.host_str().is_some_and(is_push_service_host))
+                .ok_or_else(|| {
+                    AppError::BadRequest(
+                        "push_subscription.endpoint must be a browser push service URL".to_string(),
+                    )
+                })?;
+            let keys = &subscription.keys;
+            if !web_push::is_valid_subscription_keys(&keys.p256dh, &keys.auth) {
+                return Err(AppError::BadRequest(
+                    "push_subscription.keys must hold a P-256 p256dh key and a 16-byte auth secret"
+                        .to_string(),
+                ));
+            }
+            Ok((
+                endpoint.to_string(),
+                Some(keys.p256dh.clone()),
+                Some(keys.auth.clone()),
+            ))
+        }
+    }
+}
+
+/// POST /api/users/me/subscriptions - Get notified when a full trainer's
+/// friend list has room again
+///
+/// The trainer must currently be full (1000 followers). Once a scrape shows
+/// fewer, the notification is sent once to the Discord webhook or push
+/// subscription. Subscribing again to the same trainer and destination re-arms
+/// a delivered or failed subscription. Accounts hold at most
+/// USER_MAX_SUBSCRIPTIONS waiting subscriptions.
+///
+/// Body:
+/// - account_id: The trainer
+/// - channel: discord or web_push
+/// - webhook_url: Discord webhook URL (discord)
+/// - push_subscription: The browser's PushSubscription JSON (web_push)
+async fn create_subscription(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+    Json(payload): Json<AvailabilitySubscriptionRequest>,
+) -> Result<Json<AvailabilitySubscription>, AppError> {
+    let identity = signed_in(identity)?;
+    payload.validate()?;
+    let account_id = payload.account_id.as_str().trim();
+    if !is_valid_trainer_id(account_id) {
+        return Err(AppError::BadRequest(format!("Invalid trainer ID '{}'", account_id)));
+    }
+    let (endpoint, p256dh, auth) = destination(&payload)?;
+
+    let follower_num = sqlx::query_scalar::<_, Option<i32>>(
+        "SELECT follower_num FROM trainer WHERE account_id = $1",
+    )
+    .bind(account_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("No trainer with ID {}", account_id)))?;
+    if follower_num.is_none_or(|n| n < FRIEND_LIST_FULL) {
+        return Err(AppError::BadRequest(
+            "This trainer's friend list isn't full".to_string(),
+        ));
+    }
+
+    let max_subscriptions = crate::config::get().notifications.max_subscriptions;
+    // The count and upsert race only against the same user's parallel requests
+    let subscription_id = sqlx::query_scalar::<_, i64>(
+        r#"
+        WITH existing AS (
+            SELECT COUNT(*) AS n FROM availability_subscriptions
+            WHERE user_id = $1 AND status IN ('waiting', 'pending')
+              AND NOT (account_id = $2 AND channel = $3 AND endpoint = $4)
+        )
+        INSERT INTO availability_subscriptions (user_id, account_id, channel, endpoint, p256dh, auth)
+        SELECT $1, $2, $3, $4, $5, $6 FROM existing WHERE existing.n < $7
+        ON CONFLICT (user_id, account_id, channel, endpoint) DO UPDATE SET
+            p256dh = EXCLUDED.p256dh,
+            auth = EXCLUDED.auth,
+            status = 'waiting',
+            triggered_at = NULL,
+            attempts = 0,
+            next_attempt_at = NULL,
+            last_error = NULL,
+            delivered_at = NULL
+        RETURNING subscription_id
+        "#,
+    )
+    .bind(identity.user_id)
+    .bind(account_id)
+    .bind(payload.channel.as_str())
+    .bind(&endpoint)
+    .bind(&p256dh)
+    .bind(&auth)
+    .bind(max_subscriptions)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "Subscription limit reached ({}); remove some subscriptions first",
+            max_subscriptions
+        ))
+    })?;
+
+    let mut subscription = sqlx::query_as::<_, AvailabilitySubscription>(&format!(
+        r#"
+        SELECT {}
+        FROM availability_subscriptions s
+        LEFT JOIN trainer t ON t.account_id = s.account_id
+        WHERE s.subscription_id = $1
+        "#,
+        SUBSCRIPTION_COLUMNS
+    ))
+    .bind(subscription_id)
+    .fetch_one(&state.db)
+    .await?;
+    subscription.trainer_name = subscription
+        .trainer_name
+        .as_deref()
+        .map(crate::moderation::mask_text);
+    Ok(Json(subscription))
+}
+
+/// DELETE /api/users/me/subscriptions/:subscription_id - Unsubscribe
+async fn delete_subscription(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+    Path(subscription_id): Path<i64>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let identity = signed_in(identity)?;
+    let removed = sqlx::query(
+        "DELETE FROM availability_subscriptions WHERE subscription_id = $1 AND user_id = $2",
+    )
+    .bind(subscription_id)
+    .bind(identity.user_id)
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    Ok(Json(json!({
+        "success": true,
+        "removed": removed > 0
+    })))
+}
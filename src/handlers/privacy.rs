@@ -0,0 +1,365 @@
+use axum::{
+    extract::{ConnectInfo, Path, State},
+    http::HeaderMap,
+    response::Json,
+    routing::{delete, post},
+    Router,
+};
+use serde_json::json;
+use sqlx::{PgConnection, PgExecutor, Postgres, Transaction};
+use std::collections::{BTreeMap, HashSet};
+use std::net::SocketAddr;
+
+use crate::cache::{self, Namespace};
+use crate::errors::AppError;
+use crate::events::{self, DomainEvent};
+use crate::handlers::tasks::{find_pending_task, is_valid_trainer_id};
+use crate::middleware::turnstile::require_turnstile;
+use crate::models::{
+    AccountId, PrivacyDeletionRequest, PrivacyDeletionResponse, PrivacyVerificationResponse,
+    PRIORITY_FORCED_UPDATE,
+};
+use crate::AppState;
+
+/// How long a trainer has to put the code into their comment
+const VERIFICATION_TTL_HOURS: i64 = 24;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/trainer/:account_id", delete(delete_trainer_data))
+        .route("/trainer/:account_id/verification", post(request_verification))
+}
+
+/// Accounts among `account_ids` whose data was deleted on request
+pub(crate) async fn deleted_accounts<'e>(
+    executor: impl PgExecutor<'e>,
+    account_ids: Vec<String>,
+) -> Result<HashSet<String>, AppError> {
+    if account_ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let deleted = sqlx::query_scalar::<_, String>(
+        "SELECT account_id FROM privacy_deletions WHERE account_id = ANY($1)",
+    )
+    .bind(account_ids)
+    .fetch_all(executor)
+    .await?;
+    Ok(deleted.into_iter().collect())
+}
+
+/// Mark open deletion requests verified whose code appears in the trainer's
+/// freshly scraped comment, returning how many were
+pub(crate) async fn verify_comments(
+    conn: &mut PgConnection,
+    comments: &[(String, String)],
+) -> Result<u64, AppError> {
+    if comments.is_empty() {
+        return Ok(0);
+    }
+    let (account_ids, comments): (Vec<&str>, Vec<&str>) = comments
+        .iter()
+        .map(|(account_id, comment)| (account_id.as_str(), comment.as_str()))
+        .unzip();
+
+    let verified = sqlx::query(
+        r#"
+        UPDATE privacy_deletion_requests r
+        SET verified_at = CURRENT_TIMESTAMP
+        FROM unnest($1::text[], $2::text[]) AS c(account_id, comment)
+        WHERE r.account_id = c.account_id
+          AND r.verified_at IS NULL
+          AND r.expires_at > CURRENT_TIMESTAMP
+          AND strpos(upper(c.comment), r.code) > 0
+        "#,
+    )
+    .bind(account_ids)
+    .bind(comments)
+    .execute(conn)
+    .await?
+    .rows_affected();
+    Ok(verified)
+}
+
+fn check_account_id(account_id: &AccountId) -> Result<AccountId, AppError> {
+    let account_id = account_id.as_str().trim();
+    if !is_valid_trainer_id(account_id) {
+        return Err(AppError::BadRequest(
+            "Invalid trainer ID format. Must be 9-12 digits.".to_string(),
+        ));
+    }
+    Ok(AccountId::from(account_id))
+}
+
+/// POST /api/privacy/trainer/:account_id/verification - Start a data deletion request
+///
+/// Requires a Turnstile token. Returns a code to put into the trainer's
+/// in-game comment and schedules a refresh of the trainer's profile; once a
+/// worker has seen the code there, DELETE /api/privacy/trainer/:account_id
+/// with the code deletes the data. Asking again while a request is open
+/// returns the same code.
+async fn request_verification(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(account_id): Path<AccountId>,
+    headers: HeaderMap,
+) -> Result<Json<PrivacyVerificationResponse>, AppError> {
+    let account_id = check_account_id(&account_id)?;
+    require_turnstile(&state, &headers, addr).await?;
+
+    let code = format!(
+        "UMA-{}",
+        uuid::Uuid::new_v4().simple().to_string()[..8].to_uppercase()
+    );
+    // An open request keeps its code, so a reload doesn't invalidate the comment
+    let (code, expires_at, verified) = sqlx::query_as::<_, (String, chrono::NaiveDateTime, bool)>(
+        r#"
+        INSERT INTO privacy_deletion_requests (account_id, code, expires_at)
+        VALUES ($1, $2, CURRENT_TIMESTAMP + make_interval(hours => $3))
+        ON CONFLICT (account_id) DO UPDATE SET
+            code = CASE WHEN privacy_deletion_requests.expires_at > CURRENT_TIMESTAMP
+                        THEN privacy_deletion_requests.code ELSE EXCLUDED.code END,
+            verified_at = CASE WHEN privacy_deletion_requests.expires_at > CURRENT_TIMESTAMP
+                               THEN privacy_deletion_requests.verified_at END,
+            requested_at = CURRENT_TIMESTAMP,
+            expires_at = GREATEST(privacy_deletion_requests.expires_at, EXCLUDED.expires_at)
+        RETURNING code, expires_at, verified_at IS NOT NULL
+        "#,
+    )
+    .bind(&account_id)
+    .bind(&code)
+    .bind(VERIFICATION_TTL_HOURS as i32)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !verified && find_pending_task(&state.db, "friend/search", "id", account_id.as_str()).await?.is_none() {
+        sqlx::query(
+            r#"
+            INSERT INTO tasks (task_type, task_data, priority, status, created_at)
+            VALUES ('friend/search', $1, $2, 'pending', CURRENT_TIMESTAMP)
+            "#,
+        )
+        .bind(json!({
+            "id": account_id,
+            "action": "refresh",
+            "reason": "privacy_verification"
+        }))
+        .bind(PRIORITY_FORCED_UPDATE)
+        .execute(&state.db)
+        .await?;
+    }
+
+    let message = if verified {
+        "Ownership verified; send DELETE with this code to delete the data".to_string()
+    } else {
+        format!(
+            "Put {} into your in-game comment. Once your profile has been refreshed, send DELETE with the code to delete your data.",
+            code
+        )
+    };
+    Ok(Json(PrivacyVerificationResponse {
+        account_id,
+        code,
+        expires_at,
+        message,
+    }))
+}
+
+/// DELETE /api/privacy/trainer/:account_id - Delete a trainer's data
+///
+/// Requires a Turnstile token and the code from the verification request,
+/// after a worker has seen it in the trainer's comment. Removes the trainer,
+/// its inheritance and support cards (with their archived versions), copy
+/// counts, claims, friend list reports, circle member rows (archived months
+/// included), pending tasks and users' bookmarks of and notifications about
+/// the trainer, and records the account so it isn't collected again.
+async fn delete_trainer_data(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(account_id): Path<AccountId>,
+    headers: HeaderMap,
+    Json(payload): Json<PrivacyDeletionRequest>,
+) -> Result<Json<PrivacyDeletionResponse>, AppError> {
+    let account_id = check_account_id(&account_id)?;
+    require_turnstile(&state, &headers, addr).await?;
+
+    let mut tx = state.db.begin().await?;
+    let request = sqlx::query_as::<_, (String, bool, bool)>(
+        r#"
+        SELECT code, verified_at IS NOT NULL, expires_at > CURRENT_TIMESTAMP
+        FROM privacy_deletion_requests
+        WHERE account_id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(&account_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((code, verified, open)) = request else {
+        return Err(AppError::NotFound(
+            "No deletion request for this trainer; request a code first".to_string(),
+        ));
+    };
+    if !code.eq_ignore_ascii_case(payload.code.trim()) {
+        return Err(AppError::Unauthorized("Invalid deletion code".to_string()));
+    }
+    if !verified && !open {
+        return Err(AppError::BadRequest(
+            "The deletion code expired; request a new one".to_string(),
+        ));
+    }
+    if !verified {
+        return Err(AppError::Unauthorized(
+            "Ownership not verified yet: the code hasn't been seen in the trainer's comment".to_string(),
+        ));
+    }
+
+    let removed = delete_account_rows(&mut tx, &account_id).await?;
+    let deleted_at = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+        r#"
+        INSERT INTO privacy_deletions (account_id) VALUES ($1)
+        ON CONFLICT (account_id) DO UPDATE SET deleted_at = CURRENT_TIMESTAMP
+        RETURNING deleted_at
+        "#,
+    )
+    .bind(&account_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM privacy_deletion_requests WHERE account_id = $1")
+        .bind(&account_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    events::publish(DomainEvent::TrainerDataChanged {
+        account_ids: vec![account_id.to_string()],
+    });
+    cache::invalidate(&format!("viewer_circle:{}", account_id));
+    cache::invalidate_prefix(&Namespace::Http.key("/api/v4/circles"));
+    cache::invalidate_prefix(&Namespace::Http.key("/api/team-stadium"));
+    // Feeds and sitemaps may list the trainer
+    cache::invalidate_namespaces(&[Namespace::Feeds]);
+
+    tracing::warn!(
+        "🗑️ Deleted data of trainer {} on request ({} rows)",
+        account_id,
+        removed.values().sum::<u64>()
+    );
+    Ok(Json(PrivacyDeletionResponse {
+        account_id,
+        deleted_at,
+        removed,
+    }))
+}
+
+/// Delete every row about the account, returning the count per table
+async fn delete_account_rows(
+    tx: &mut Transaction<'static, Postgres>,
+    account_id: &AccountId,
+) -> Result<BTreeMap<String, u64>, AppError> {
+    let viewer_id = account_id
+        .to_viewer_id()
+        .ok_or_else(|| AppError::BadRequest("Invalid trainer ID".to_string()))?;
+    let mut removed = BTreeMap::new();
+
+    // Deleting inheritance/support cards archives them to record_versions,
+    // which is therefore cleared last
+    let by_account = [
+        (
+            "user_bookmarks",
+            r#"
+            DELETE FROM user_bookmarks
+            WHERE (target_type = 'trainer' AND target_id = $1)
+               OR (target_type = 'inheritance' AND target_id IN (
+                       SELECT inheritance_id::text FROM inheritance WHERE account_id = $1
+                   ))
+            "#,
+        ),
+        ("user_notifications", "DELETE FROM user_notifications WHERE account_id = $1"),
+        (
+            "availability_subscriptions",
+            "DELETE FROM availability_subscriptions WHERE account_id = $1",
+        ),
+        ("leaderboard_entries", "DELETE FROM leaderboard_entries WHERE account_id = $1"),
+        (
+            "trainer_availability_streaks",
+            "DELETE FROM trainer_availability_streaks WHERE account_id = $1",
+        ),
+        ("inheritance", "DELETE FROM inheritance WHERE account_id = $1"),
+        ("support_card", "DELETE FROM support_card WHERE account_id = $1"),
+        ("team_stadium", "DELETE FROM team_stadium WHERE account_id = $1"),
+        ("matchmaking_tickets", "DELETE FROM matchmaking_tickets WHERE account_id = $1"),
+        (
+            "content_reports",
+            "DELETE FROM content_reports WHERE target_kind = 'trainer_name' AND target_id = $1",
+        ),
+        ("trainer_copies", "DELETE FROM trainer_copies WHERE trainer_id = $1"),
+        ("trainer_claims", "DELETE FROM trainer_claims WHERE trainer_id = $1"),
+        (
+            "trainer_claim_requests",
+            "DELETE FROM trainer_claim_requests WHERE trainer_id = $1",
+        ),
+        ("friendlist_reports", "DELETE FROM friendlist_reports WHERE trainer_id = $1"),
+        (
+            "tasks",
+            "DELETE FROM tasks WHERE status = 'pending' AND (account_id = $1 OR task_data->>'id' = $1)",
+        ),
+        ("trainer", "DELETE FROM trainer WHERE account_id = $1"),
+        ("record_versions", "DELETE FROM record_versions WHERE account_id = $1"),
+    ];
+    for (table, sql) in by_account {
+        let rows = sqlx::query(sql)
+            .bind(account_id)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected();
+        removed.insert(table.to_string(), rows);
+    }
+
+    let by_viewer = [
+        (
+            "circle_member_fans_monthly",
+            "DELETE FROM circle_member_fans_monthly WHERE viewer_id = $1",
+        ),
+        ("circle_member_roster", "DELETE FROM circle_member_roster WHERE viewer_id = $1"),
+        ("circle_member_events", "DELETE FROM circle_member_events WHERE viewer_id = $1"),
+    ];
+    for (table, sql) in by_viewer {
+        let rows = sqlx::query(sql)
+            .bind(viewer_id)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected();
+        removed.insert(table.to_string(), rows);
+    }
+
+    // Months moved out by the partition archiver
+    let archived = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT format('archive.%I', tablename)
+        FROM pg_tables
+        WHERE schemaname = 'archive' AND tablename ~ '^circle_member_fans_monthly_[0-9]{4}_[0-9]{2}$'
+        "#,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+    let mut archived_rows = 0;
+    for table in archived {
+        archived_rows += sqlx::query(&format!("DELETE FROM {} WHERE viewer_id = $1", table))
+            .bind(viewer_id)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected();
+    }
+    removed.insert("circle_member_fans_monthly (archived)".to_string(), archived_rows);
+
+    // The circle stays; only the link to this trainer goes
+    let circles = sqlx::query("UPDATE circles SET leader_viewer_id = NULL WHERE leader_viewer_id = $1")
+        .bind(viewer_id)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+    removed.insert("circles.leader_viewer_id".to_string(), circles);
+
+    Ok(removed)
+}
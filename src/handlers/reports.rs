@@ -0,0 +1,93 @@
+use axum::{
+    extract::{ConnectInfo, State},
+    http::HeaderMap,
+    response::Json,
+    routing::post,
+    Router,
+};
+use serde_json::json;
+use std::net::SocketAddr;
+use validator::Validate;
+
+use crate::errors::AppError;
+use crate::handlers::votes::client_hash;
+use crate::middleware::turnstile::require_turnstile;
+use crate::models::{AccountId, CircleId, ContentReportKind, ContentReportRequest};
+use crate::AppState;
+
+/// Content reports - mounted under /api/reports
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", post(create_report))
+}
+
+/// POST /api/reports - Report an offensive trainer name, circle name or
+/// circle comment to the moderation queue
+///
+/// Requires a Turnstile token. Each client (by IP and user agent) has one open
+/// report per target; reporting again replaces the reason.
+///
+/// Body:
+/// - target_kind: trainer_name, circle_name or circle_comment
+/// - target_id: Trainer account ID or circle ID
+/// - reason: Why the text is offensive (optional)
+pub async fn create_report(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<ContentReportRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    payload.validate()?;
+    let target_id = payload.target_id.trim();
+    let exists = match payload.target_kind {
+        ContentReportKind::TrainerName => {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM trainer WHERE account_id = $1)",
+            )
+            .bind(AccountId::from(target_id))
+            .fetch_one(state.read_db())
+            .await?
+        }
+        ContentReportKind::CircleName | ContentReportKind::CircleComment => {
+            let circle_id = target_id
+                .parse()
+                .map(CircleId)
+                .map_err(|_| AppError::BadRequest(format!("Invalid circle ID '{}'", target_id)))?;
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM circles WHERE circle_id = $1)",
+            )
+            .bind(circle_id)
+            .fetch_one(state.read_db())
+            .await?
+        }
+    };
+    if !exists {
+        return Err(AppError::NotFound(format!(
+            "Nothing to report for {} {}",
+            payload.target_kind.as_str(),
+            target_id
+        )));
+    }
+    require_turnstile(&state, &headers, addr).await?;
+    let reason = payload
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty());
+
+    sqlx::query(
+        r#"
+        INSERT INTO content_reports (target_kind, target_id, reason, reporter_hash)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (target_kind, target_id, reporter_hash) WHERE status = 'open'
+        DO UPDATE SET reason = EXCLUDED.reason, created_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(payload.target_kind.as_str())
+    .bind(target_id)
+    .bind(reason)
+    .bind(client_hash(&headers, addr))
+    .execute(&state.db)
+    .await?;
+
+    Ok(Json(json!({ "success": true })))
+}
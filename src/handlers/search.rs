@@ -1,33 +1,25 @@
 use axum::{
-    extract::State,
-    response::Json,
-    routing::get,
+    extract::{Path, State},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
     Router,
 };
-use sqlx::{Postgres, QueryBuilder, Row};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+use validator::Validate;
 
 use crate::{
-    errors::Result,
-    models::{Inheritance, SearchResponse, SupportCard, UnifiedAccountRecord, UnifiedSearchParams},
+    errors::{field_errors, AppError, FieldError, Result},
+    models::{
+        AccountId, CardId, CharaId, Inheritance, SearchResponse, SearchSnapshot,
+        SearchSnapshotEntry, SupportCard, UnifiedAccountRecord, UnifiedSearchParams,
+    },
+    sparks::SparkEncoding,
     AppState,
 };
 
-fn get_affinity_expression(player_chara_id: Option<i32>) -> String {
-    match player_chara_id {
-        None => "(COALESCE(i.base_affinity, 0) + COALESCE(i.race_affinity, 0))".to_string(),
-        Some(p_val) => {
-            let chara_id = if p_val > 100000 { p_val / 100 } else { p_val };
-            let array_index = chara_id - 1000;
-            format!(
-                "(COALESCE(i.affinity_scores[{}], 0) + COALESCE(i.race_affinity, 0))",
-                array_index
-            )
-        }
-    }
-}
-
 fn add_main_parent_spark_conditions<'a>(
     query_builder: &mut QueryBuilder<'a, Postgres>,
+    encoding: SparkEncoding,
     column: &str,
     sparks: &[i32],
 ) {
@@ -67,7 +59,12 @@ fn add_main_parent_spark_conditions<'a>(
             query_builder.push(" OR ");
         }
         let min_wildcard = wildcard_levels.iter().min().unwrap();
-        query_builder.push(format!("({} % 10 >= {})", column, min_wildcard));
+        query_builder.push(format!(
+            "({} % {} >= {})",
+            column,
+            encoding.multiplier(),
+            min_wildcard
+        ));
     }
 
     query_builder.push(")");
@@ -75,6 +72,7 @@ fn add_main_parent_spark_conditions<'a>(
 
 fn add_spark_range_conditions<'a>(
     query_builder: &mut QueryBuilder<'a, Postgres>,
+    encoding: SparkEncoding,
     column: &str,
     sparks: &[i32],
 ) {
@@ -113,13 +111,7 @@ fn add_spark_range_conditions<'a>(
         if has_condition {
             query_builder.push(" OR ");
         }
-        let max_factor_id = 100;
-        let mut all_possible_sparks = Vec::new();
-        for factor_id in 1..=max_factor_id {
-            for &level in &wildcard_levels {
-                all_possible_sparks.push(factor_id * 10 + level);
-            }
-        }
+        let all_possible_sparks = encoding.expand_wildcard_levels(&wildcard_levels);
         query_builder.push(column);
         query_builder.push(" && ARRAY[");
         for (i, val) in all_possible_sparks.iter().enumerate() {
@@ -136,11 +128,12 @@ fn add_spark_range_conditions<'a>(
 
 fn add_9star_spark_conditions<'a>(
     query_builder: &mut QueryBuilder<'a, Postgres>,
+    encoding: SparkEncoding,
     column: &str,
     desired_star: i32,
 ) {
     let values: Vec<i32> = (1..=6)
-        .map(|stat_type| stat_type * 100 + desired_star)
+        .map(|stat_type| encoding.encode(stat_type * 10, desired_star))
         .collect();
 
     query_builder.push(" AND ");
@@ -156,15 +149,23 @@ fn add_9star_spark_conditions<'a>(
     query_builder.push("]::int[]");
 }
 
-fn process_spark_groups(groups: &[String]) -> Vec<Vec<i32>> {
-    groups.iter()
-        .map(|s| s.split(',').filter_map(|v| v.trim().parse::<i32>().ok()).collect::<Vec<i32>>())
+/// Parse comma-separated spark groups (v1 values from the client) into the given encoding
+fn process_spark_groups(groups: &[String], encoding: SparkEncoding) -> Vec<Vec<i32>> {
+    groups
+        .iter()
+        .map(|s| {
+            s.split(',')
+                .filter_map(|v| v.trim().parse::<i32>().ok())
+                .map(|v| encoding.translate_v1(v))
+                .collect::<Vec<i32>>()
+        })
         .filter(|v| !v.is_empty())
         .collect()
 }
 
 fn add_multi_group_spark_conditions<'a>(
     query_builder: &mut QueryBuilder<'a, Postgres>,
+    encoding: SparkEncoding,
     column: &str,
     groups: &[Vec<i32>],
 ) {
@@ -173,13 +174,13 @@ fn add_multi_group_spark_conditions<'a>(
     }
 
     if groups.len() == 1 {
-        add_spark_range_conditions(query_builder, column, &groups[0]);
+        add_spark_range_conditions(query_builder, encoding, column, &groups[0]);
         return;
     }
 
     let mut group_values: Vec<Vec<i32>> = Vec::new();
     for group in groups {
-        let values = expand_spark_group(group);
+        let values = expand_spark_group(encoding, group);
         group_values.push(values);
     }
 
@@ -296,7 +297,7 @@ fn add_multi_group_spark_conditions<'a>(
     }
 }
 
-fn expand_spark_group(sparks: &[i32]) -> Vec<i32> {
+fn expand_spark_group(encoding: SparkEncoding, sparks: &[i32]) -> Vec<i32> {
     let mut result = Vec::new();
     let mut wildcard_levels = Vec::new();
     
@@ -309,12 +310,7 @@ fn expand_spark_group(sparks: &[i32]) -> Vec<i32> {
     }
 
     if !wildcard_levels.is_empty() {
-        let max_factor_id = 100;
-        for factor_id in 1..=max_factor_id {
-            for &level in &wildcard_levels {
-                result.push(factor_id * 10 + level);
-            }
-        }
+        result.extend(encoding.expand_wildcard_levels(&wildcard_levels));
     }
     
     result.sort();
@@ -324,26 +320,53 @@ fn expand_spark_group(sparks: &[i32]) -> Vec<i32> {
 
 pub fn router() -> Router<AppState> {
     Router::new()
-        .route("/search", get(unified_search))
+        .route(
+            "/search",
+            get(unified_search).layer(axum::middleware::from_fn_with_state(
+                crate::middleware::DegradedFallback::when(is_blank_search),
+                crate::middleware::degraded_middleware,
+            )),
+        )
         .route("/count", get(get_unified_count))
+        .route("/search/snapshot", post(create_search_snapshot))
+        .route("/search/snapshot/:snapshot_id", get(get_search_snapshot))
 }
 
-fn parse_search_params(query: &str) -> UnifiedSearchParams {
-    let mut params_map: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+/// Parse and validate a search query string
+///
+/// Values that don't parse (limit=abc) or fail validation (limit=-1, a bad
+/// spark ID, a malformed trainer_id) are reported together as field errors.
+/// Empty values count as not given.
+pub(crate) fn parse_search_params(query: &str) -> Result<UnifiedSearchParams> {
+    let mut params_map: std::collections::HashMap<String, Vec<String>> =
+        std::collections::HashMap::new();
     for (k, v) in url::form_urlencoded::parse(query.as_bytes()) {
         params_map.entry(k.to_string()).or_default().push(v.to_string());
     }
 
-    let get_i32 = |key: &str| -> Option<i32> {
-        params_map.get(key).and_then(|v| v.last()).and_then(|s| s.parse().ok())
+    let errors = std::cell::RefCell::new(Vec::new());
+    let get_value = |key: &str| -> Option<&str> {
+        Some(params_map.get(key)?.last()?.trim()).filter(|value| !value.is_empty())
     };
-    
+    let invalid = |key: &str, expected: &str, value: &str| {
+        errors
+            .borrow_mut()
+            .push(FieldError::new(key, format!("expected {}, got '{}'", expected, value)));
+    };
+
     let get_i64 = |key: &str| -> Option<i64> {
-        params_map.get(key).and_then(|v| v.last()).and_then(|s| s.parse().ok())
+        let value = get_value(key)?;
+        value.parse().map_err(|_| invalid(key, "an integer", value)).ok()
+    };
+
+    let get_i32 = |key: &str| -> Option<i32> {
+        let value = get_value(key)?;
+        value.parse().map_err(|_| invalid(key, "a 32-bit integer", value)).ok()
     };
 
     let get_bool = |key: &str| -> Option<bool> {
-        params_map.get(key).and_then(|v| v.last()).and_then(|s| s.parse().ok())
+        let value = get_value(key)?;
+        value.parse().map_err(|_| invalid(key, "true or false", value)).ok()
     };
 
     let get_string = |key: &str| -> Option<String> {
@@ -354,13 +377,13 @@ fn parse_search_params(query: &str) -> UnifiedSearchParams {
         params_map.get(key).cloned().unwrap_or_default()
     };
 
-    UnifiedSearchParams {
+    let params = UnifiedSearchParams {
         page: get_i64("page"),
         limit: get_i64("limit"),
         search_type: get_string("search_type"),
-        main_parent_id: get_i32("main_parent_id"),
-        parent_left_id: get_i32("parent_left_id"),
-        parent_right_id: get_i32("parent_right_id"),
+        main_parent_id: get_i32("main_parent_id").map(CharaId),
+        parent_left_id: get_i32("parent_left_id").map(CharaId),
+        parent_right_id: get_i32("parent_right_id").map(CharaId),
         parent_rank: get_i32("parent_rank"),
         parent_rarity: get_i32("parent_rarity"),
         blue_sparks: get_vec("blue_sparks"),
@@ -398,38 +421,61 @@ fn parse_search_params(query: &str) -> UnifiedSearchParams {
                 v
             }
         },
-        support_card_id: get_i32("support_card_id"),
+        support_card_id: get_i32("support_card_id").map(CardId),
         min_limit_break: get_i32("min_limit_break"),
         max_limit_break: get_i32("max_limit_break"),
         min_experience: get_i32("min_experience"),
-        trainer_id: get_string("trainer_id"),
+        trainer_id: get_value("trainer_id").map(AccountId::from),
         trainer_name: get_string("trainer_name"),
         max_follower_num: get_i32("max_follower_num"),
+        include_stale: get_bool("include_stale").unwrap_or(false),
         sort_by: get_string("sort_by"),
         sort_order: get_string("sort_order"),
-        player_chara_id: get_i32("player_chara_id"),
-        player_chara_id_2: get_i32("player_chara_id_2"),
-        desired_main_chara_id: get_i32("desired_main_chara_id"),
+        player_chara_id: get_i32("player_chara_id").map(CharaId),
+        player_chara_id_2: get_i32("player_chara_id_2").map(CharaId),
+        desired_main_chara_id: get_i32("desired_main_chara_id").map(CharaId),
+        affinity_version: get_value("affinity_version").and_then(|value| {
+            value.parse().map_err(|_| invalid("affinity_version", "a version number", value)).ok()
+        }),
+    };
+
+    let mut errors = errors.into_inner();
+    if let Err(e) = params.validate() {
+        errors.extend(field_errors(&e));
+    }
+    if errors.is_empty() {
+        Ok(params)
+    } else {
+        Err(errors.into())
     }
 }
 
-pub async fn unified_search(
-    State(state): State<AppState>,
-    request: axum::extract::Request,
-) -> Result<Json<SearchResponse<UnifiedAccountRecord>>> {
-    let query_string = request.uri().query().unwrap_or("");
-    let params = parse_search_params(query_string);
+/// search_type=combined: only accounts that have both the requested support
+/// card and the requested main parent, returned with both sides populated
+fn is_combined_search(params: &UnifiedSearchParams) -> bool {
+    params.search_type.as_deref() == Some("combined")
+}
 
-    tracing::info!("🔍 SEARCH REQUEST: page={:?}, limit={:?}, search_type={:?}, sort_by={:?}, player_chara_id={:?}, filters={:?}", 
-        params.page, params.limit, params.search_type, params.sort_by, params.player_chara_id,
-        format!("{:?}", params).chars().take(200).collect::<String>());
+/// Trainers flagged stale by the retention job are left out unless asked for
+/// or looked up by ID
+fn includes_stale(params: &UnifiedSearchParams) -> bool {
+    params.include_stale || params.trainer_id.is_some()
+}
 
-    let page = params.page.unwrap_or(0);
-    let limit = params.limit.unwrap_or(20).min(100);
-    let offset = page * limit;
+pub(crate) fn validate_search_type(params: &UnifiedSearchParams) -> Result<()> {
+    if is_combined_search(params)
+        && (params.support_card_id.is_none() || params.main_parent_id.is_none())
+    {
+        return Err(AppError::BadRequest(
+            "search_type=combined requires both support_card_id and main_parent_id".to_string(),
+        ));
+    }
+    Ok(())
+}
 
-    // Check if this is a blank/default query (no filters applied except search_type and sort)
-    let is_blank_query = params.trainer_id.is_none()
+/// Whether the request applies no filters besides search_type, sort and paging
+pub(crate) fn is_blank_query(params: &UnifiedSearchParams) -> bool {
+    params.trainer_id.is_none()
         && params.trainer_name.is_none()
         && params.main_parent_id.is_none()
         && params.parent_left_id.is_none()
@@ -470,18 +516,46 @@ pub async fn unified_search(
         && (params.min_main_white_count.is_none() || params.min_main_white_count == Some(0))
         && params.desired_main_chara_id.is_none()
         && params.player_chara_id.is_none()
-        && (params.max_follower_num.is_none() || params.max_follower_num == Some(1000) || params.max_follower_num == Some(999));
+        && (params.max_follower_num.is_none() || params.max_follower_num == Some(1000) || params.max_follower_num == Some(999))
+}
+
+/// Blank search pages are what most visitors load, so they are kept for degraded mode
+fn is_blank_search(uri: &axum::http::Uri) -> bool {
+    parse_search_params(uri.query().unwrap_or("")).is_ok_and(|params| is_blank_query(&params))
+}
+
+pub async fn unified_search(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+) -> Result<Response> {
+    let query_string = request.uri().query().unwrap_or("");
+    let params = parse_search_params(query_string)?;
+    validate_search_type(&params)?;
+    crate::live_stats::SEARCHES.record(1);
+
+    tracing::info!("🔍 SEARCH REQUEST: page={:?}, limit={:?}, search_type={:?}, sort_by={:?}, player_chara_id={:?}, filters={:?}", 
+        params.page, params.limit, params.search_type, params.sort_by, params.player_chara_id,
+        format!("{:?}", params).chars().take(200).collect::<String>());
+
+    let page = params.page.unwrap_or(0);
+    let limit = params.limit.unwrap_or(20).min(100);
+    let offset = page * limit;
+    let affinity_version = crate::affinity::resolve(params.affinity_version)?.version();
+
+    // Check if this is a blank/default query (no filters applied except search_type and sort)
+    let is_blank_query = is_blank_query(&params);
 
     // Build a comprehensive search cache key for all queries (not just blank)
     // This caches search results for common filter combinations
     // IMPORTANT: Must include ALL filter parameters to avoid returning wrong cached results
-    let search_cache_key = format!(
-        "search:p{}:l{}:sort={}:order={}:player={}:follower={}:type={}:main={}:left={}:right={}:rank={}:rarity={}:blue={}:pink={}:green={}:white={}:blue9={}:pink9={}:green9={}:mpb={}:mpp={}:mpg={}:mpw={}:win={}:wh={}:mmb={}:mmp={}:mmg={}:mwf={}:mwh={}:owh={}:omwf={}:bsum={}:psum={}:gsum={}:wsum={}:sc={}:lb={}:exp={}:trainer={}:desired={}",
+    let search_cache_key = crate::cache::Namespace::Search.key(&format!(
+        "p{}:l{}:sort={}:order={}:player={}:follower={}:stale={}:type={}:main={}:left={}:right={}:rank={}:rarity={}:blue={}:pink={}:green={}:white={}:blue9={}:pink9={}:green9={}:mpb={}:mpp={}:mpg={}:mpw={}:win={}:wh={}:mmb={}:mmp={}:mmg={}:mwf={}:mwh={}:owh={}:omwf={}:bsum={:?}-{:?}:psum={:?}-{:?}:gsum={:?}-{:?}:wsum={:?}-{:?}:sc={}:lb={:?}-{:?}:exp={}:trainer={}:desired={}:aff=v{}",
         page, limit,
         params.sort_by.as_deref().unwrap_or("default"),
         params.sort_order.as_deref().unwrap_or("desc"),
         params.player_chara_id.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
         params.max_follower_num.map(|v| v.to_string()).unwrap_or_else(|| "def".to_string()),
+        includes_stale(&params),
         params.search_type.as_deref().unwrap_or("all"),
         params.main_parent_id.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
         params.parent_left_id.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
@@ -508,68 +582,65 @@ pub async fn unified_search(
         params.min_main_white_count.unwrap_or(0),
         if params.optional_white_sparks.is_empty() { "any".to_string() } else { format!("{:?}", params.optional_white_sparks) },
         if params.optional_main_white_factors.is_empty() { "any".to_string() } else { format!("{:?}", params.optional_main_white_factors) },
-        format!("{:?}-{:?}", params.min_blue_stars_sum, params.max_blue_stars_sum),
-        format!("{:?}-{:?}", params.min_pink_stars_sum, params.max_pink_stars_sum),
-        format!("{:?}-{:?}", params.min_green_stars_sum, params.max_green_stars_sum),
-        format!("{:?}-{:?}", params.min_white_stars_sum, params.max_white_stars_sum),
+        params.min_blue_stars_sum, params.max_blue_stars_sum,
+        params.min_pink_stars_sum, params.max_pink_stars_sum,
+        params.min_green_stars_sum, params.max_green_stars_sum,
+        params.min_white_stars_sum, params.max_white_stars_sum,
         params.support_card_id.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
-        format!("{:?}-{:?}", params.min_limit_break, params.max_limit_break),
+        params.min_limit_break, params.max_limit_break,
         params.min_experience.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
-        params.trainer_id.as_deref().unwrap_or("any"),
-        params.desired_main_chara_id.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string())
-    );
-
-    // Try cache for all queries (not just blank ones)
-    if let Some(cached) = crate::cache::get::<SearchResponse<UnifiedAccountRecord>>(&search_cache_key) {
-        tracing::info!("🎯 CACHE HIT: search results");
-        return Ok(Json(cached));
-    }
-
-    let query_start = std::time::Instant::now();
-    let total_count = execute_count_query(&state, &params).await?;
-    let count_duration = query_start.elapsed();
-    tracing::info!("⏱️  COUNT QUERY: {}ms", count_duration.as_millis());
-
-    let search_start = std::time::Instant::now();
-    let records = execute_search_query(&state, &params, limit, offset).await?;
-    let search_duration = search_start.elapsed();
-    tracing::info!(
-        "⏱️  SEARCH QUERY: {}ms (returned {} records) - player_chara_id={:?}",
-        search_duration.as_millis(),
-        records.len(),
-        params.player_chara_id
-    );
-
-    let total_pages = if limit > 0 {
-        ((total_count as f64) / (limit as f64)).ceil() as i64
-    } else {
-        0
-    };
+        params.trainer_id.as_ref().map(AccountId::as_str).unwrap_or("any"),
+        params.desired_main_chara_id.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
+        affinity_version
+    ));
 
-    let total_display = if !is_blank_query && total_count > 10000 {
-        "over 10000".to_string()
+    // Cache all search results - blank queries are refreshed in the background after
+    // an hour (served stale for up to 6 hours meanwhile), filtered ones expire after 5 minutes
+    let (soft_ttl, hard_ttl) = if is_blank_query {
+        (std::time::Duration::from_secs(3600), std::time::Duration::from_secs(6 * 3600))
     } else {
-        total_count.to_string()
+        (std::time::Duration::from_secs(300), std::time::Duration::from_secs(300))
     };
 
-    let response = SearchResponse {
-        items: records,
-        total: total_display,
-        page,
-        limit,
-        total_pages,
-    };
+    let storage = state.storage.clone();
+    let response = crate::cache::get_or_revalidate(&search_cache_key, soft_ttl, hard_ttl, move || async move {
+        let query_start = std::time::Instant::now();
+        let total_count = storage.search_count(&params).await?;
+        let count_duration = query_start.elapsed();
+        tracing::info!("⏱️  COUNT QUERY: {}ms", count_duration.as_millis());
+
+        let search_start = std::time::Instant::now();
+        let records = storage.search(&params, limit, offset).await?;
+        let search_duration = search_start.elapsed();
+        tracing::info!(
+            "⏱️  SEARCH QUERY: {}ms (returned {} records) - player_chara_id={:?}",
+            search_duration.as_millis(),
+            records.len(),
+            params.player_chara_id
+        );
+
+        let total_pages = if limit > 0 {
+            ((total_count as f64) / (limit as f64)).ceil() as i64
+        } else {
+            0
+        };
 
-    // Cache all search results - blank queries for 1 hour, filtered for 5 minutes
-    let cache_ttl = if is_blank_query {
-        std::time::Duration::from_secs(3600) // 1 hour for blank queries
-    } else {
-        std::time::Duration::from_secs(300) // 5 minutes for filtered queries
-    };
-    
-    if crate::cache::set(&search_cache_key, &response, cache_ttl).is_ok() {
-        tracing::info!("💾 CACHE SET: search results (ttl={}s)", cache_ttl.as_secs());
-    }
+        let total_display = if !is_blank_query && total_count > 10000 {
+            "over 10000".to_string()
+        } else {
+            total_count.to_string()
+        };
+
+        tracing::info!("💾 CACHE SET: search results (ttl={}s)", soft_ttl.as_secs());
+        Ok::<_, AppError>(SearchResponse {
+            items: records,
+            total: total_display,
+            page,
+            limit,
+            total_pages,
+        })
+    })
+    .await?;
 
     tracing::info!(
         "✅ SEARCH COMPLETE: returned {} items, total={}, page={}, total_pages={}",
@@ -579,11 +650,92 @@ pub async fn unified_search(
         response.total_pages
     );
 
-    Ok(Json(response))
+    Ok(search_page_response(response))
+}
+
+/// Large pages are streamed to keep peak memory per request down
+fn search_page_response(response: SearchResponse<UnifiedAccountRecord>) -> Response {
+    if response.limit >= crate::streaming::STREAMING_THRESHOLD {
+        crate::streaming::stream_search_response(response)
+    } else {
+        Json(response).into_response()
+    }
+}
+
+/// POST /api/v3/search/snapshot - Run a search and freeze the result page under a permalink
+/// Accepts the same query parameters as GET /api/v3/search. Snapshots bypass the
+/// search cache so the frozen page reflects the data at the time of the request.
+pub async fn create_search_snapshot(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+) -> Result<Json<SearchSnapshot>> {
+    let query_string = request.uri().query().unwrap_or("").to_string();
+    let params = parse_search_params(&query_string)?;
+    validate_search_type(&params)?;
+
+    let page = params.page.unwrap_or(0);
+    let limit = params.limit.unwrap_or(20).min(100);
+    let offset = page * limit;
+
+    let total_count = state.storage.search_count(&params).await?;
+    let records = state.storage.search(&params, limit, offset).await?;
+
+    let entries: Vec<SearchSnapshotEntry> = records
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| SearchSnapshotEntry {
+            rank: offset + i as i64 + 1,
+            account_id: record.account_id,
+            trainer_name: record.trainer_name,
+            inheritance_id: record.inheritance.as_ref().map(|inh| inh.inheritance_id),
+            affinity_score: record.inheritance.as_ref().and_then(|inh| inh.affinity_score),
+        })
+        .collect();
+
+    let snapshot_id = uuid::Uuid::new_v4().simple().to_string()[..12].to_string();
+
+    let snapshot = sqlx::query_as::<_, SearchSnapshot>(
+        r#"
+        INSERT INTO search_snapshots (snapshot_id, query, total, entries)
+        VALUES ($1, $2, $3, $4)
+        RETURNING snapshot_id, query, total, entries, created_at
+        "#,
+    )
+    .bind(&snapshot_id)
+    .bind(&query_string)
+    .bind(total_count.to_string())
+    .bind(sqlx::types::Json(&entries))
+    .fetch_one(&state.db)
+    .await?;
+
+    tracing::info!(
+        "📸 SEARCH SNAPSHOT {}: froze {} entries (total={})",
+        snapshot.snapshot_id,
+        snapshot.entries.len(),
+        snapshot.total
+    );
+
+    Ok(Json(snapshot))
+}
+
+/// GET /api/v3/search/snapshot/{snapshot_id} - Retrieve a frozen search result page
+pub async fn get_search_snapshot(
+    State(state): State<AppState>,
+    Path(snapshot_id): Path<String>,
+) -> Result<Json<SearchSnapshot>> {
+    let snapshot = sqlx::query_as::<_, SearchSnapshot>(
+        "SELECT snapshot_id, query, total, entries, created_at FROM search_snapshots WHERE snapshot_id = $1",
+    )
+    .bind(&snapshot_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Search snapshot {} not found", snapshot_id)))?;
+
+    Ok(Json(snapshot))
 }
 
-async fn execute_search_query(
-    state: &AppState,
+pub(crate) async fn execute_search_query(
+    pool: &PgPool,
     params: &UnifiedSearchParams,
     limit: i64,
     offset: i64,
@@ -594,19 +746,22 @@ async fn execute_search_query(
     // );
     // tracing::info!("🔍 UNIFIED SEARCH: Inheritance-first with support card join");
 
+    let spark_encoding = SparkEncoding::active();
+
     // Build unified query: always start from inheritance, join support card
     let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new("");
+    let affinity_formula = crate::affinity::resolve(params.affinity_version)?;
     
     // Use desired_main_chara_id for affinity calculation if provided, otherwise use player_chara_id
     // This allows filtering by main character AND calculating affinity for that character
     let affinity_player_id = params.desired_main_chara_id.or(params.player_chara_id);
-    let affinity_expr = get_affinity_expression(affinity_player_id);
+    let affinity_expr = affinity_formula.sql_expression(affinity_player_id);
 
     query_builder.push(
         r#"
         SELECT
             i.account_id,
-            t.name as trainer_name,
+            COALESCE(t.display_name_override, t.name) as trainer_name,
             t.follower_num,
             t.last_updated,
             -- Inheritance fields
@@ -658,15 +813,33 @@ async fn execute_search_query(
 
     // Construct scoring expressions for use in SELECT and ORDER BY
     let white_sparks_score_expr = if !optional_white_sparks_ids.is_empty() {
-        let ids_str = optional_white_sparks_ids.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",");
-        format!("calculate_sparks_score(i.white_sparks, ARRAY[{}]::int[])", ids_str)
+        let ids_str = optional_white_sparks_ids
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "calculate_sparks_score({}, ARRAY[{}]::int[], {})",
+            spark_encoding.column("i.white_sparks"),
+            ids_str,
+            spark_encoding.multiplier()
+        )
     } else {
         "0".to_string()
     };
 
     let main_white_factors_score_expr = if !optional_main_white_factors_ids.is_empty() {
-        let ids_str = optional_main_white_factors_ids.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",");
-        format!("calculate_sparks_score(i.main_white_factors, ARRAY[{}]::int[])", ids_str)
+        let ids_str = optional_main_white_factors_ids
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "calculate_sparks_score({}, ARRAY[{}]::int[], {})",
+            spark_encoding.column("i.main_white_factors"),
+            ids_str,
+            spark_encoding.multiplier()
+        )
     } else {
         "0".to_string()
     };
@@ -681,6 +854,18 @@ async fn execute_search_query(
     query_builder.push(&main_white_factors_score_expr);
     query_builder.push(" AS main_white_factors_score");
 
+    // Community votes are only joined when they decide the order
+    let sort_by_community = params.sort_by.as_deref() == Some("community_score");
+    let community_score_expr = format!(
+        "COALESCE(decayed_community_score(cs.score, cs.scored_at, {}), 0)",
+        crate::config::get().community_score_half_life.as_secs_f64()
+    );
+    if sort_by_community {
+        query_builder.push(", ");
+        query_builder.push(&community_score_expr);
+        query_builder.push(" AS community_score");
+    }
+
     query_builder.push(
         r#",
             -- Support card fields (best one per account)
@@ -689,10 +874,20 @@ async fn execute_search_query(
             sc.experience
         FROM inheritance i
         INNER JOIN trainer t ON i.account_id = t.account_id
-        LEFT JOIN support_card sc ON i.account_id = sc.account_id
-        WHERE 1=1
     "#,
     );
+    if sort_by_community {
+        query_builder
+            .push(" LEFT JOIN inheritance_community_scores cs ON cs.inheritance_id = i.inheritance_id");
+    }
+
+    // Combined mode must never return a row with only one side populated
+    if is_combined_search(params) {
+        query_builder.push(" INNER JOIN support_card sc ON i.account_id = sc.account_id");
+    } else {
+        query_builder.push(" LEFT JOIN support_card sc ON i.account_id = sc.account_id");
+    }
+    query_builder.push(" WHERE 1=1");
 
     // Support card filters
     if let Some(support_card_id) = params.support_card_id {
@@ -724,12 +919,16 @@ async fn execute_search_query(
         query_builder.push(" AND (t.follower_num IS NULL OR t.follower_num < 1000)");
     }
 
+    if !includes_stale(params) {
+        query_builder.push(" AND NOT t.stale");
+    }
+
     // Player exclusion - don't show inheritances where player is the main character
     // Use the same player ID as affinity calculation (desired_main_chara_id takes precedence)
     // Convert to base character ID format (player_chara_id 100701 -> 1007)
     let affinity_player_id = params.desired_main_chara_id.or(params.player_chara_id);
     if let Some(player_id) = affinity_player_id {
-        let base_chara_id = if player_id > 100000 { player_id / 100 } else { player_id };
+        let base_chara_id = player_id.base();
         query_builder.push(" AND i.main_chara_id != ");
         query_builder.push_bind(base_chara_id);
     }
@@ -741,8 +940,9 @@ async fn execute_search_query(
     }
 
     if let Some(trainer_name) = &params.trainer_name {
-        query_builder.push(" AND t.name ILIKE ");
+        query_builder.push(" AND t.name_normalized LIKE lower(normalize(");
         query_builder.push_bind(format!("%{}%", trainer_name));
+        query_builder.push(", NFKC))");
     }
 
     if let Some(main_parent_id) = params.main_parent_id {
@@ -779,53 +979,115 @@ async fn execute_search_query(
     }
 
     // Add spark filters (multi-group AND logic)
-    let blue_sparks_groups = process_spark_groups(&params.blue_sparks);
-    add_multi_group_spark_conditions(&mut query_builder, "i.blue_sparks", &blue_sparks_groups);
+    let blue_sparks_groups = process_spark_groups(&params.blue_sparks, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.blue_sparks"),
+        &blue_sparks_groups,
+    );
 
-    let pink_sparks_groups = process_spark_groups(&params.pink_sparks);
-    add_multi_group_spark_conditions(&mut query_builder, "i.pink_sparks", &pink_sparks_groups);
+    let pink_sparks_groups = process_spark_groups(&params.pink_sparks, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.pink_sparks"),
+        &pink_sparks_groups,
+    );
 
-    let green_sparks_groups = process_spark_groups(&params.green_sparks);
-    add_multi_group_spark_conditions(&mut query_builder, "i.green_sparks", &green_sparks_groups);
+    let green_sparks_groups = process_spark_groups(&params.green_sparks, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.green_sparks"),
+        &green_sparks_groups,
+    );
 
-    let white_sparks_groups = process_spark_groups(&params.white_sparks);
-    add_multi_group_spark_conditions(&mut query_builder, "i.white_sparks", &white_sparks_groups);
+    let white_sparks_groups = process_spark_groups(&params.white_sparks, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.white_sparks"),
+        &white_sparks_groups,
+    );
 
     // Add 9-star spark filters (search across all stat types)
     if let Some(true) = params.blue_sparks_9star {
-        add_9star_spark_conditions(&mut query_builder, "i.blue_sparks", 9);
+        add_9star_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.blue_sparks"),
+            9,
+        );
     }
 
     if let Some(true) = params.pink_sparks_9star {
-        add_9star_spark_conditions(&mut query_builder, "i.pink_sparks", 9);
+        add_9star_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.pink_sparks"),
+            9,
+        );
     }
 
     if let Some(true) = params.green_sparks_9star {
-        add_9star_spark_conditions(&mut query_builder, "i.green_sparks", 9);
+        add_9star_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.green_sparks"),
+            9,
+        );
     }
 
     // Add main parent spark filters
-    let main_parent_blue_groups = process_spark_groups(&params.main_parent_blue_sparks);
+    let main_parent_blue_groups =
+        process_spark_groups(&params.main_parent_blue_sparks, spark_encoding);
     for group in main_parent_blue_groups {
-        add_main_parent_spark_conditions(&mut query_builder, "i.main_blue_factors", &group);
+        add_main_parent_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.main_blue_factors"),
+            &group,
+        );
     }
 
-    let main_parent_pink_groups = process_spark_groups(&params.main_parent_pink_sparks);
+    let main_parent_pink_groups =
+        process_spark_groups(&params.main_parent_pink_sparks, spark_encoding);
     for group in main_parent_pink_groups {
-        add_main_parent_spark_conditions(&mut query_builder, "i.main_pink_factors", &group);
+        add_main_parent_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.main_pink_factors"),
+            &group,
+        );
     }
 
-    let main_parent_green_groups = process_spark_groups(&params.main_parent_green_sparks);
+    let main_parent_green_groups =
+        process_spark_groups(&params.main_parent_green_sparks, spark_encoding);
     for group in main_parent_green_groups {
-        add_main_parent_spark_conditions(&mut query_builder, "i.main_green_factors", &group);
+        add_main_parent_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.main_green_factors"),
+            &group,
+        );
     }
 
     // main_parent_white_sparks - REQUIRED filter for main parent's white factors
-    let main_parent_white_groups = process_spark_groups(&params.main_parent_white_sparks);
+    let main_parent_white_groups =
+        process_spark_groups(&params.main_parent_white_sparks, spark_encoding);
     if !main_parent_white_groups.is_empty() {
-        tracing::info!("🔍 MAIN_PARENT_WHITE_SPARKS filter (SEARCH): {:?}", main_parent_white_groups);
-    }
-    add_multi_group_spark_conditions(&mut query_builder, "i.main_white_factors", &main_parent_white_groups);
+        tracing::info!(
+            "🔍 MAIN_PARENT_WHITE_SPARKS filter (SEARCH): {:?}",
+            main_parent_white_groups
+        );
+    }
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.main_white_factors"),
+        &main_parent_white_groups,
+    );
 
     if let Some(min_win_count) = params.min_win_count {
         query_builder.push(" AND i.win_count >= ");
@@ -876,23 +1138,38 @@ async fn execute_search_query(
 
     // Main inherit filtering
     if let Some(min_main_blue) = params.min_main_blue_factors {
-        query_builder.push(" AND i.main_blue_factors >= ");
-        query_builder.push_bind(min_main_blue);
+        query_builder.push(format!(
+            " AND {} >= ",
+            spark_encoding.column("i.main_blue_factors")
+        ));
+        query_builder.push_bind(spark_encoding.translate_v1(min_main_blue));
     }
 
     if let Some(min_main_pink) = params.min_main_pink_factors {
-        query_builder.push(" AND i.main_pink_factors >= ");
-        query_builder.push_bind(min_main_pink);
+        query_builder.push(format!(
+            " AND {} >= ",
+            spark_encoding.column("i.main_pink_factors")
+        ));
+        query_builder.push_bind(spark_encoding.translate_v1(min_main_pink));
     }
 
     if let Some(min_main_green) = params.min_main_green_factors {
-        query_builder.push(" AND i.main_green_factors >= ");
-        query_builder.push_bind(min_main_green);
+        query_builder.push(format!(
+            " AND {} >= ",
+            spark_encoding.column("i.main_green_factors")
+        ));
+        query_builder.push_bind(spark_encoding.translate_v1(min_main_green));
     }
 
     // main_white_factors - REQUIRED filter for specific white factors on main parent (SEARCH)
-    let main_white_factors_groups = process_spark_groups(&params.main_white_factors);
-    add_multi_group_spark_conditions(&mut query_builder, "i.main_white_factors", &main_white_factors_groups);
+    let main_white_factors_groups =
+        process_spark_groups(&params.main_white_factors, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.main_white_factors"),
+        &main_white_factors_groups,
+    );
 
     if let Some(min_main_white_count) = params.min_main_white_count {
         query_builder.push(" AND i.main_white_count >= ");
@@ -901,11 +1178,13 @@ async fn execute_search_query(
 
     // GIN-optimized filter: include rows that have at least one matching optional spark
     // Filter each column separately based on what the user actually requested
-    let white_sparks_expanded: Vec<i32> = optional_white_sparks_ids.iter()
-        .flat_map(|&factor_id| (1..=9).map(move |level| factor_id * 10 + level))
+    let white_sparks_expanded: Vec<i32> = optional_white_sparks_ids
+        .iter()
+        .flat_map(|&factor_id| (1..=9).map(move |level| spark_encoding.encode(factor_id, level)))
         .collect();
-    let main_white_factors_expanded: Vec<i32> = optional_main_white_factors_ids.iter()
-        .flat_map(|&factor_id| (1..=9).map(move |level| factor_id * 10 + level))
+    let main_white_factors_expanded: Vec<i32> = optional_main_white_factors_ids
+        .iter()
+        .flat_map(|&factor_id| (1..=9).map(move |level| spark_encoding.encode(factor_id, level)))
         .collect();
 
     let has_white_sparks_filter = !white_sparks_expanded.is_empty();
@@ -913,12 +1192,18 @@ async fn execute_search_query(
 
     if has_white_sparks_filter && has_main_white_factors_filter {
         // Both specified: must match at least one in EITHER column (combined filter)
-        query_builder.push(" AND (i.white_sparks && ARRAY[");
+        query_builder.push(format!(
+            " AND ({} && ARRAY[",
+            spark_encoding.column("i.white_sparks")
+        ));
         for (i, val) in white_sparks_expanded.iter().enumerate() {
             if i > 0 { query_builder.push(","); }
             query_builder.push_bind(*val);
         }
-        query_builder.push("]::int[] OR i.main_white_factors && ARRAY[");
+        query_builder.push(format!(
+            "]::int[] OR {} && ARRAY[",
+            spark_encoding.column("i.main_white_factors")
+        ));
         for (i, val) in main_white_factors_expanded.iter().enumerate() {
             if i > 0 { query_builder.push(","); }
             query_builder.push_bind(*val);
@@ -926,7 +1211,10 @@ async fn execute_search_query(
         query_builder.push("]::int[])");
     } else if has_white_sparks_filter {
         // Only white_sparks specified: filter only on white_sparks
-        query_builder.push(" AND i.white_sparks && ARRAY[");
+        query_builder.push(format!(
+            " AND {} && ARRAY[",
+            spark_encoding.column("i.white_sparks")
+        ));
         for (i, val) in white_sparks_expanded.iter().enumerate() {
             if i > 0 { query_builder.push(","); }
             query_builder.push_bind(*val);
@@ -934,7 +1222,10 @@ async fn execute_search_query(
         query_builder.push("]::int[]");
     } else if has_main_white_factors_filter {
         // Only main_white_factors specified: filter only on main_white_factors
-        query_builder.push(" AND i.main_white_factors && ARRAY[");
+        query_builder.push(format!(
+            " AND {} && ARRAY[",
+            spark_encoding.column("i.main_white_factors")
+        ));
         for (i, val) in main_white_factors_expanded.iter().enumerate() {
             if i > 0 { query_builder.push(","); }
             query_builder.push_bind(*val);
@@ -982,7 +1273,7 @@ async fn execute_search_query(
             // Affinity-based sorting - uses expression index
             // Use desired_main_chara_id for affinity if provided
             let affinity_player_id = params.desired_main_chara_id.or(params.player_chara_id);
-            let affinity_expr = get_affinity_expression(affinity_player_id);
+            let affinity_expr = affinity_formula.sql_expression(affinity_player_id);
             if has_optional_scoring {
                 // Optional scoring takes priority, then affinity as tiebreaker
                 format!(" ORDER BY {} DESC, {} {}", total_score_expr, affinity_expr, sort_dir)
@@ -1067,6 +1358,13 @@ async fn execute_search_query(
                 format!(" ORDER BY COALESCE(t.follower_num, 999999) {}, t.account_id ASC", follower_sort_dir)
             }
         }
+        Some("community_score") => {
+            if has_optional_scoring {
+                format!(" ORDER BY {} DESC, {} {}, t.account_id ASC", total_score_expr, community_score_expr, sort_dir)
+            } else {
+                format!(" ORDER BY {} {}, t.account_id ASC", community_score_expr, sort_dir)
+            }
+        }
         Some("white_sparks_score") => {
             // Sort primarily by combined optional sparks score
             format!(" ORDER BY {} {}, t.account_id ASC", total_score_expr, sort_dir)
@@ -1079,7 +1377,7 @@ async fn execute_search_query(
             // Default: use affinity ordering for best results
             // Use desired_main_chara_id for affinity if provided
             let affinity_player_id = params.desired_main_chara_id.or(params.player_chara_id);
-            let affinity_expr = get_affinity_expression(affinity_player_id);
+            let affinity_expr = affinity_formula.sql_expression(affinity_player_id);
             if has_optional_scoring {
                 format!(" ORDER BY {} DESC, {} {}", total_score_expr, affinity_expr, sort_dir)
             } else {
@@ -1115,7 +1413,7 @@ async fn execute_search_query(
     // );
 
     let query_start = std::time::Instant::now();
-    let rows = query.fetch_all(&state.db).await?;
+    let rows = query.fetch_all(pool).await?;
     let _query_duration = query_start.elapsed();
     // eprintln!(
     //     "⏱️  SQL EXECUTION TIME: {}ms (returned {} rows)",
@@ -1130,7 +1428,7 @@ async fn execute_search_query(
 
     let mut records = Vec::new();
     for row in rows {
-        let account_id: String = row.get("account_id");
+        let account_id: AccountId = row.get("account_id");
 
         // Build support card directly from row (no JSON parsing needed)
         let support_card: Option<SupportCard> =
@@ -1172,6 +1470,7 @@ async fn execute_search_query(
                     green_stars_sum: row.get("green_stars_sum"),
                     white_stars_sum: row.get("white_stars_sum"),
                     affinity_score: row.try_get("affinity_score").ok(),
+                    community_score: row.try_get("community_score").ok().flatten(),
                 })
             } else {
                 None
@@ -1190,57 +1489,20 @@ async fn execute_search_query(
     Ok(records)
 }
 
-async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) -> Result<i64> {
+pub(crate) async fn execute_count_query(
+    pool: &PgPool,
+    params: &UnifiedSearchParams,
+    spark_encoding: SparkEncoding,
+) -> Result<i64> {
     // For blank queries with no filters, use approximate count from stats table
-    let is_blank_query = params.trainer_id.is_none()
-        && params.trainer_name.is_none()
-        && params.main_parent_id.is_none()
-        && params.parent_left_id.is_none()
-        && params.parent_right_id.is_none()
-        && (params.parent_rank.is_none() || params.parent_rank == Some(1))
-        && params.parent_rarity.is_none()
-        && params.blue_sparks.is_empty()
-        && params.pink_sparks.is_empty()
-        && params.green_sparks.is_empty()
-        && params.white_sparks.is_empty()
-        && params.blue_sparks_9star.is_none()
-        && params.pink_sparks_9star.is_none()
-        && params.green_sparks_9star.is_none()
-        && params.main_parent_blue_sparks.is_empty()
-        && params.main_parent_pink_sparks.is_empty()
-        && params.main_parent_green_sparks.is_empty()
-        && params.main_parent_white_sparks.is_empty()
-        && params.support_card_id.is_none()
-        && params.min_limit_break.is_none()
-        && params.max_limit_break.is_none()
-        && params.min_experience.is_none()
-        && (params.min_win_count.is_none() || params.min_win_count == Some(0))
-        && (params.min_white_count.is_none() || params.min_white_count == Some(0))
-        && params.min_blue_stars_sum.is_none()
-        && params.max_blue_stars_sum.is_none()
-        && params.min_pink_stars_sum.is_none()
-        && params.max_pink_stars_sum.is_none()
-        && params.min_green_stars_sum.is_none()
-        && params.max_green_stars_sum.is_none()
-        && params.min_white_stars_sum.is_none()
-        && params.max_white_stars_sum.is_none()
-        && params.min_main_blue_factors.is_none()
-        && params.min_main_pink_factors.is_none()
-        && params.min_main_green_factors.is_none()
-        && params.main_white_factors.is_empty()
-        && params.optional_white_sparks.is_empty()
-        && params.optional_main_white_factors.is_empty()
-        && (params.min_main_white_count.is_none() || params.min_main_white_count == Some(0))
-        && params.desired_main_chara_id.is_none()
-        && params.player_chara_id.is_none()
-        && (params.max_follower_num.is_none() || params.max_follower_num == Some(1000) || params.max_follower_num == Some(999));
+    let is_blank_query = is_blank_query(params);
 
     if is_blank_query {
         tracing::info!("📊 COUNT: Using stats_counts table (instant)");
         // Use materialized view for instant count (no actual counting!)
         let count: i64 =
             sqlx::query_scalar("SELECT COALESCE(trainer_count, 0) FROM stats_counts LIMIT 1")
-                .fetch_one(&state.db)
+                .fetch_one(pool)
                 .await?;
 
         return Ok(count);
@@ -1249,11 +1511,13 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
     // Cache counts for common filter combinations (they change infrequently)
     // Build comprehensive cache key based on ALL filters to avoid returning wrong counts
     // NOTE: player_chara_id and max_follower_num affect the query and MUST be included
-    let cache_key = format!(
-        "count:type={}:player={}:follower={}:sc_id={}:lb_min={}:lb_max={}:exp_min={}:main_parent={}:p_left={}:p_right={}:p_rank={}:p_rarity={}:blue={}:pink={}:green={}:white={}:blue9={}:pink9={}:green9={}:mp_blue={}:mp_pink={}:mp_green={}:mp_white={}:win={}:wh_cnt={}:trainer={}:trainer_name={}:desired_main={}:b_sum_min={}:b_sum_max={}:p_sum_min={}:p_sum_max={}:g_sum_min={}:g_sum_max={}:w_sum_min={}:w_sum_max={}:mm_blue={}:mm_pink={}:mm_green={}:m_white={}:mm_wh_cnt={}:opt_wh={}:opt_m_wh={}",
+    let cache_key = crate::cache::Namespace::Count.key(&format!(
+        "enc={}:type={}:player={}:follower={}:stale={}:sc_id={}:lb_min={}:lb_max={}:exp_min={}:main_parent={}:p_left={}:p_right={}:p_rank={}:p_rarity={}:blue={}:pink={}:green={}:white={}:blue9={}:pink9={}:green9={}:mp_blue={}:mp_pink={}:mp_green={}:mp_white={}:win={}:wh_cnt={}:trainer={}:trainer_name={}:desired_main={}:b_sum_min={}:b_sum_max={}:p_sum_min={}:p_sum_max={}:g_sum_min={}:g_sum_max={}:w_sum_min={}:w_sum_max={}:mm_blue={}:mm_pink={}:mm_green={}:m_white={}:mm_wh_cnt={}:opt_wh={}:opt_m_wh={}",
+        spark_encoding.as_str(),
         params.search_type.as_deref().unwrap_or("all"),
         params.player_chara_id.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
         params.max_follower_num.map(|v| v.to_string()).unwrap_or_else(|| "default".to_string()),
+        includes_stale(params),
         params.support_card_id.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
         params.min_limit_break.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
         params.max_limit_break.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
@@ -1276,7 +1540,7 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
         if params.main_parent_white_sparks.is_empty() { "any".to_string() } else { format!("{:?}", params.main_parent_white_sparks) },
         params.min_win_count.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
         params.min_white_count.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
-        params.trainer_id.as_ref().unwrap_or(&"any".to_string()),
+        params.trainer_id.as_ref().map(AccountId::as_str).unwrap_or("any"),
         params.trainer_name.as_ref().unwrap_or(&"any".to_string()),
         params.desired_main_chara_id.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
         params.min_blue_stars_sum.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
@@ -1294,7 +1558,7 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
         params.min_main_white_count.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
         if params.optional_white_sparks.is_empty() { "any".to_string() } else { format!("{:?}", params.optional_white_sparks) },
         if params.optional_main_white_factors.is_empty() { "any".to_string() } else { format!("{:?}", params.optional_main_white_factors) }
-    );
+    ));
 
     // Try to get cached count (cache for 5 minutes)
     if let Some(cached_count) = crate::cache::get::<i64>(&cache_key) {
@@ -1303,6 +1567,12 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
     }
     tracing::info!("❌ CACHE MISS: count query");
 
+    // Identical counts requested at the same time run once; the rest wait and read the cache
+    let _loading = crate::cache::lock_load(&cache_key).await;
+    if let Some(cached_count) = crate::cache::get::<i64>(&cache_key) {
+        return Ok(cached_count);
+    }
+
     // Unified count query: always start from inheritance
     // OPTIMIZATION: Wrap in subquery with LIMIT to prevent slow full table scans
     let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
@@ -1324,12 +1594,16 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
         query_builder.push(" AND (t.follower_num IS NULL OR t.follower_num < 1000)");
     }
 
+    if !includes_stale(params) {
+        query_builder.push(" AND NOT t.stale");
+    }
+
     // Player exclusion - don't show inheritances where player is the main character
     // Use the same player ID as affinity calculation (desired_main_chara_id takes precedence)
     // Convert to base character ID format (player_chara_id 100701 -> 1007)
     let affinity_player_id = params.desired_main_chara_id.or(params.player_chara_id);
     if let Some(player_id) = affinity_player_id {
-        let base_chara_id = if player_id > 100000 { player_id / 100 } else { player_id };
+        let base_chara_id = player_id.base();
         query_builder.push(" AND i.main_chara_id != ");
         query_builder.push_bind(base_chara_id);
     }
@@ -1388,8 +1662,9 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
     // Note: trainer_id already applied above, skipping duplicate
 
     if let Some(trainer_name) = &params.trainer_name {
-        query_builder.push(" AND t.name ILIKE ");
+        query_builder.push(" AND t.name_normalized LIKE lower(normalize(");
         query_builder.push_bind(format!("%{}%", trainer_name));
+        query_builder.push(", NFKC))");
     }
 
     if let Some(main_parent_id) = params.main_parent_id {
@@ -1421,50 +1696,109 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
     }
 
     // Add spark filters (multi-group AND logic)
-    let blue_sparks_groups = process_spark_groups(&params.blue_sparks);
-    add_multi_group_spark_conditions(&mut query_builder, "i.blue_sparks", &blue_sparks_groups);
+    let blue_sparks_groups = process_spark_groups(&params.blue_sparks, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.blue_sparks"),
+        &blue_sparks_groups,
+    );
 
-    let pink_sparks_groups = process_spark_groups(&params.pink_sparks);
-    add_multi_group_spark_conditions(&mut query_builder, "i.pink_sparks", &pink_sparks_groups);
+    let pink_sparks_groups = process_spark_groups(&params.pink_sparks, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.pink_sparks"),
+        &pink_sparks_groups,
+    );
 
-    let green_sparks_groups = process_spark_groups(&params.green_sparks);
-    add_multi_group_spark_conditions(&mut query_builder, "i.green_sparks", &green_sparks_groups);
+    let green_sparks_groups = process_spark_groups(&params.green_sparks, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.green_sparks"),
+        &green_sparks_groups,
+    );
 
-    let white_sparks_groups = process_spark_groups(&params.white_sparks);
-    add_multi_group_spark_conditions(&mut query_builder, "i.white_sparks", &white_sparks_groups);
+    let white_sparks_groups = process_spark_groups(&params.white_sparks, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.white_sparks"),
+        &white_sparks_groups,
+    );
 
     // Add 9-star spark filters (search across all stat types)
     if let Some(true) = params.blue_sparks_9star {
-        add_9star_spark_conditions(&mut query_builder, "i.blue_sparks", 9);
+        add_9star_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.blue_sparks"),
+            9,
+        );
     }
 
     if let Some(true) = params.pink_sparks_9star {
-        add_9star_spark_conditions(&mut query_builder, "i.pink_sparks", 9);
+        add_9star_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.pink_sparks"),
+            9,
+        );
     }
 
     if let Some(true) = params.green_sparks_9star {
-        add_9star_spark_conditions(&mut query_builder, "i.green_sparks", 9);
+        add_9star_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.green_sparks"),
+            9,
+        );
     }
 
     // Add main parent spark filters
-    let main_parent_blue_groups = process_spark_groups(&params.main_parent_blue_sparks);
+    let main_parent_blue_groups =
+        process_spark_groups(&params.main_parent_blue_sparks, spark_encoding);
     for group in main_parent_blue_groups {
-        add_main_parent_spark_conditions(&mut query_builder, "i.main_blue_factors", &group);
+        add_main_parent_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.main_blue_factors"),
+            &group,
+        );
     }
 
-    let main_parent_pink_groups = process_spark_groups(&params.main_parent_pink_sparks);
+    let main_parent_pink_groups =
+        process_spark_groups(&params.main_parent_pink_sparks, spark_encoding);
     for group in main_parent_pink_groups {
-        add_main_parent_spark_conditions(&mut query_builder, "i.main_pink_factors", &group);
+        add_main_parent_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.main_pink_factors"),
+            &group,
+        );
     }
 
-    let main_parent_green_groups = process_spark_groups(&params.main_parent_green_sparks);
+    let main_parent_green_groups =
+        process_spark_groups(&params.main_parent_green_sparks, spark_encoding);
     for group in main_parent_green_groups {
-        add_main_parent_spark_conditions(&mut query_builder, "i.main_green_factors", &group);
+        add_main_parent_spark_conditions(
+            &mut query_builder,
+            spark_encoding,
+            &spark_encoding.column("i.main_green_factors"),
+            &group,
+        );
     }
 
     // main_parent_white_sparks - REQUIRED filter for main parent's white factors (COUNT)
-    let main_parent_white_groups = process_spark_groups(&params.main_parent_white_sparks);
-    add_multi_group_spark_conditions(&mut query_builder, "i.main_white_factors", &main_parent_white_groups);
+    let main_parent_white_groups =
+        process_spark_groups(&params.main_parent_white_sparks, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.main_white_factors"),
+        &main_parent_white_groups,
+    );
 
     if let Some(min_win_count) = params.min_win_count {
         query_builder.push(" AND i.win_count >= ");
@@ -1515,23 +1849,38 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
 
     // Main inherit filtering
     if let Some(min_main_blue) = params.min_main_blue_factors {
-        query_builder.push(" AND i.main_blue_factors >= ");
-        query_builder.push_bind(min_main_blue);
+        query_builder.push(format!(
+            " AND {} >= ",
+            spark_encoding.column("i.main_blue_factors")
+        ));
+        query_builder.push_bind(spark_encoding.translate_v1(min_main_blue));
     }
 
     if let Some(min_main_pink) = params.min_main_pink_factors {
-        query_builder.push(" AND i.main_pink_factors >= ");
-        query_builder.push_bind(min_main_pink);
+        query_builder.push(format!(
+            " AND {} >= ",
+            spark_encoding.column("i.main_pink_factors")
+        ));
+        query_builder.push_bind(spark_encoding.translate_v1(min_main_pink));
     }
 
     if let Some(min_main_green) = params.min_main_green_factors {
-        query_builder.push(" AND i.main_green_factors >= ");
-        query_builder.push_bind(min_main_green);
+        query_builder.push(format!(
+            " AND {} >= ",
+            spark_encoding.column("i.main_green_factors")
+        ));
+        query_builder.push_bind(spark_encoding.translate_v1(min_main_green));
     }
 
     // main_white_factors - REQUIRED filter for specific white factors on main parent (COUNT)
-    let main_white_factors_groups = process_spark_groups(&params.main_white_factors);
-    add_multi_group_spark_conditions(&mut query_builder, "i.main_white_factors", &main_white_factors_groups);
+    let main_white_factors_groups =
+        process_spark_groups(&params.main_white_factors, spark_encoding);
+    add_multi_group_spark_conditions(
+        &mut query_builder,
+        spark_encoding,
+        &spark_encoding.column("i.main_white_factors"),
+        &main_white_factors_groups,
+    );
 
     if let Some(min_main_white_count) = params.min_main_white_count {
         query_builder.push(" AND i.main_white_count >= ");
@@ -1552,11 +1901,13 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
 
     // GIN-optimized filter: include rows that have at least one matching optional spark
     // Filter each column separately based on what the user actually requested
-    let white_sparks_expanded: Vec<i32> = optional_white_sparks_ids.iter()
-        .flat_map(|&factor_id| (1..=9).map(move |level| factor_id * 10 + level))
+    let white_sparks_expanded: Vec<i32> = optional_white_sparks_ids
+        .iter()
+        .flat_map(|&factor_id| (1..=9).map(move |level| spark_encoding.encode(factor_id, level)))
         .collect();
-    let main_white_factors_expanded: Vec<i32> = optional_main_white_factors_ids.iter()
-        .flat_map(|&factor_id| (1..=9).map(move |level| factor_id * 10 + level))
+    let main_white_factors_expanded: Vec<i32> = optional_main_white_factors_ids
+        .iter()
+        .flat_map(|&factor_id| (1..=9).map(move |level| spark_encoding.encode(factor_id, level)))
         .collect();
 
     let has_white_sparks_filter = !white_sparks_expanded.is_empty();
@@ -1564,12 +1915,18 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
 
     if has_white_sparks_filter && has_main_white_factors_filter {
         // Both specified: must match at least one in EITHER column (combined filter)
-        query_builder.push(" AND (i.white_sparks && ARRAY[");
+        query_builder.push(format!(
+            " AND ({} && ARRAY[",
+            spark_encoding.column("i.white_sparks")
+        ));
         for (i, val) in white_sparks_expanded.iter().enumerate() {
             if i > 0 { query_builder.push(","); }
             query_builder.push_bind(*val);
         }
-        query_builder.push("]::int[] OR i.main_white_factors && ARRAY[");
+        query_builder.push(format!(
+            "]::int[] OR {} && ARRAY[",
+            spark_encoding.column("i.main_white_factors")
+        ));
         for (i, val) in main_white_factors_expanded.iter().enumerate() {
             if i > 0 { query_builder.push(","); }
             query_builder.push_bind(*val);
@@ -1577,7 +1934,10 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
         query_builder.push("]::int[])");
     } else if has_white_sparks_filter {
         // Only white_sparks specified: filter only on white_sparks
-        query_builder.push(" AND i.white_sparks && ARRAY[");
+        query_builder.push(format!(
+            " AND {} && ARRAY[",
+            spark_encoding.column("i.white_sparks")
+        ));
         for (i, val) in white_sparks_expanded.iter().enumerate() {
             if i > 0 { query_builder.push(","); }
             query_builder.push_bind(*val);
@@ -1585,7 +1945,10 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
         query_builder.push("]::int[]");
     } else if has_main_white_factors_filter {
         // Only main_white_factors specified: filter only on main_white_factors
-        query_builder.push(" AND i.main_white_factors && ARRAY[");
+        query_builder.push(format!(
+            " AND {} && ARRAY[",
+            spark_encoding.column("i.main_white_factors")
+        ));
         for (i, val) in main_white_factors_expanded.iter().enumerate() {
             if i > 0 { query_builder.push(","); }
             query_builder.push_bind(*val);
@@ -1624,7 +1987,7 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
     let query = query_builder.build();
 
     let query_start = std::time::Instant::now();
-    let row = query.fetch_one(&state.db).await?;
+    let row = query.fetch_one(pool).await?;
     let count: i64 = row.get::<i64, _>(0);
     let query_duration = query_start.elapsed();
     tracing::info!(
@@ -1643,13 +2006,13 @@ async fn execute_count_query(state: &AppState, params: &UnifiedSearchParams) ->
 
 pub async fn get_unified_count(State(state): State<AppState>) -> Result<Json<serde_json::Value>> {
     let total_inheritance_count = sqlx::query("SELECT COUNT(*) FROM inheritance")
-        .fetch_one(&state.db)
+        .fetch_one(state.read_db())
         .await?
         .get::<i64, _>(0);
 
     let total_support_card_accounts =
         sqlx::query("SELECT COUNT(DISTINCT account_id) FROM support_card")
-            .fetch_one(&state.db)
+            .fetch_one(state.read_db())
             .await?
             .get::<i64, _>(0);
 
@@ -1661,7 +2024,7 @@ pub async fn get_unified_count(State(state): State<AppState>) -> Result<Json<ser
         WHERE (t.follower_num IS NULL OR t.follower_num < 1000)
         "#,
     )
-    .fetch_one(&state.db)
+    .fetch_one(state.read_db())
     .await?
     .get::<i64, _>(0);
 
@@ -1673,7 +2036,7 @@ pub async fn get_unified_count(State(state): State<AppState>) -> Result<Json<ser
         WHERE (t.follower_num IS NULL OR t.follower_num < 1000)
         "#,
     )
-    .fetch_one(&state.db)
+    .fetch_one(state.read_db())
     .await?
     .get::<i64, _>(0);
 
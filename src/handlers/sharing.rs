@@ -1,29 +1,111 @@
 use axum::{
-    extract::{Path, State},
-    http::{HeaderMap, HeaderValue},
-    response::{Html, IntoResponse, Response},
-    routing::get,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Json, Redirect, Response},
+    routing::{get, post},
     Router,
 };
-use sqlx::Row;
+use sqlx::{FromRow, PgPool, Row};
+use std::time::Duration;
 
 use crate::{
-    errors::Result,
-    models::{InheritanceShareData, SharePathParams, SupportCardShareData},
+    characters,
+    errors::{AppError, Result},
+    handlers::circles::{content_etag, etag_matches},
+    handlers::feeds::xml_escape,
+    share_links::{self, SHARE_HOSTS},
+    sparks::SparkEncoding,
+    models::{
+        AccountId, CardId, CircleId, CircleShareData, Inheritance, InheritanceShareData,
+        OEmbedParams, OEmbedResponse, ShareLinkResponse, SharePathParams, ShortenShareLinkRequest,
+        SupportCard, SupportCardShareData,
+    },
     AppState,
 };
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/s/:share_type/:account_id", get(share_page))
+    Router::new()
+        .route("/s/:share_type/:account_id", get(share_page))
+        .route("/s/inheritance/:account_id/og.png", get(inheritance_og_image))
+        .route("/s/inheritance/record/:inheritance_id", get(inheritance_record_share))
+        .route(
+            "/s/inheritance/record/:inheritance_id/og.png",
+            get(inheritance_record_og_image),
+        )
+        .route("/oembed", get(oembed))
+        .route("/s/x/:token", get(short_link))
+        .route("/api/share/shorten", post(shorten_share_link))
 }
 
+/// How long rendered share pages are served from the in-process cache
+const SHARE_PAGE_TTL: Duration = Duration::from_secs(300);
+
+/// Strip a ".json" suffix from the last path segment; JSON is also served
+/// when the client asks for it via Accept
+fn split_json_suffix<'a>(segment: &'a str, headers: &HeaderMap) -> (&'a str, bool) {
+    if let Some(id) = segment.strip_suffix(".json") {
+        return (id, true);
+    }
+
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    (
+        segment,
+        accept.contains("application/json") && !accept.contains("text/html"),
+    )
+}
+
+/// GET /s/{share_type}/{id}[.json] - Share page (HTML for embeds, or JSON)
+///
+/// The ID is an account ID for inheritance and support-card shares, a circle ID for circles.
 pub async fn share_page(
     State(state): State<AppState>,
     Path(params): Path<SharePathParams>,
+    headers: HeaderMap,
 ) -> Result<Response> {
+    let (account_id, json) = split_json_suffix(params.account_id.as_str(), &headers);
+    let account_id = AccountId(account_id.to_string());
+
     match params.share_type.as_str() {
-        "inheritance" => inheritance_share(&state, &params.account_id).await,
-        "support-card" => support_card_share(&state, &params.account_id).await,
+        "inheritance" => {
+            serve_share_page(
+                &format!("share:inheritance:{}", account_id),
+                json,
+                &headers,
+                inheritance_share_data_for_account(&state, &account_id),
+                generate_inheritance_html,
+                inheritance_not_found,
+            )
+            .await
+        }
+        "support-card" => {
+            serve_share_page(
+                &format!("share:support-card:{}", account_id),
+                json,
+                &headers,
+                support_card_share_data(&state, &account_id),
+                generate_support_card_html,
+                support_card_not_found,
+            )
+            .await
+        }
+        "circle" => match account_id.as_str().parse() {
+            Ok(circle_id) => {
+                serve_share_page(
+                    &format!("share:circle:{}", circle_id),
+                    json,
+                    &headers,
+                    circle_share_data(&state, CircleId(circle_id)),
+                    generate_circle_html,
+                    circle_not_found,
+                )
+                .await
+            }
+            Err(_) => Ok(circle_not_found(json)),
+        },
+        _ if json => Err(AppError::NotFound("Unknown share type".to_string())),
         _ => {
             // Return a 404 for unknown share types
             let html = generate_error_html(
@@ -35,73 +117,168 @@ pub async fn share_page(
     }
 }
 
-async fn inheritance_share(state: &AppState, account_id: &str) -> Result<Response> {
-    // Query to get inheritance data with character names
-    let query = r#"
-        SELECT 
-            t.account_id,
-            t.name as trainer_name,
-            t.follower_num,
-            i.inheritance_id,
-            i.main_parent_id,
-            i.parent_left_id,
-            i.parent_right_id,
-            i.parent_rank,
-            i.parent_rarity,
-            i.blue_sparks,
-            i.pink_sparks,
-            i.green_sparks,
-            i.white_sparks,
-            i.win_count,
-            i.white_count,
-            i.main_blue_factors,
-            i.main_pink_factors,
-            i.main_green_factors,
-            i.main_white_factors,
-            i.main_white_count
-        FROM trainer t
-        INNER JOIN inheritance i ON t.account_id = i.account_id
-        WHERE t.account_id = $1
-    "#;
+// Trainer name and inheritance columns of a share page, filtered by the caller
+pub(crate) const INHERITANCE_SHARE_SELECT: &str = r#"
+    SELECT 
+        t.account_id,
+        COALESCE(t.display_name_override, t.name) as trainer_name,
+        t.follower_num,
+        i.inheritance_id,
+        i.main_parent_id,
+        i.parent_left_id,
+        i.parent_right_id,
+        i.parent_rank,
+        i.parent_rarity,
+        i.blue_sparks,
+        i.pink_sparks,
+        i.green_sparks,
+        i.white_sparks,
+        i.win_count,
+        i.white_count,
+        i.main_blue_factors,
+        i.main_pink_factors,
+        i.main_green_factors,
+        i.main_white_factors,
+        i.main_white_count
+    FROM trainer t
+    INNER JOIN inheritance i ON t.account_id = i.account_id
+"#;
 
-    let row = match sqlx::query(query)
+/// Trainer name and inheritance record behind an inheritance share page
+///
+/// Accounts with several records share their newest one; links to a specific
+/// record use fetch_inheritance_share_by_id.
+pub(crate) async fn fetch_inheritance_share(
+    pool: &PgPool,
+    account_id: &AccountId,
+) -> Result<Option<(String, Inheritance)>> {
+    let query = format!(
+        "{} WHERE t.account_id = $1 ORDER BY i.inheritance_id DESC LIMIT 1",
+        INHERITANCE_SHARE_SELECT
+    );
+
+    let row = sqlx::query(&query)
         .bind(account_id)
-        .fetch_optional(&state.db)
+        .fetch_optional(pool)
+        .await?;
+
+    match row {
+        Some(row) => Ok(Some((row.get("trainer_name"), Inheritance::from_row(&row)?))),
+        None => Ok(None),
+    }
+}
+
+/// Trainer name and a specific inheritance record, for /s/inheritance/record/{id}
+pub(crate) async fn fetch_inheritance_share_by_id(
+    pool: &PgPool,
+    inheritance_id: i32,
+) -> Result<Option<(String, Inheritance)>> {
+    let query = format!("{} WHERE i.inheritance_id = $1", INHERITANCE_SHARE_SELECT);
+
+    let row = sqlx::query(&query)
+        .bind(inheritance_id)
+        .fetch_optional(pool)
+        .await?;
+
+    match row {
+        Some(row) => Ok(Some((row.get("trainer_name"), Inheritance::from_row(&row)?))),
+        None => Ok(None),
+    }
+}
+
+async fn inheritance_share_data_for_account(
+    state: &AppState,
+    account_id: &AccountId,
+) -> Result<Option<InheritanceShareData>> {
+    let share_url = format!("https://honse.moe/s/inheritance/{}", account_id);
+    Ok(state
+        .storage
+        .inheritance_share(account_id)
         .await?
-    {
-        Some(row) => row,
-        None => {
-            let html = generate_error_html(
-                "Inheritance Not Found",
-                "The requested inheritance record could not be found.",
-            );
-            return Ok(Html(html).into_response());
-        }
-    };
+        .map(|(trainer_name, inheritance)| {
+            inheritance_share_data(&trainer_name, inheritance, share_url)
+        }))
+}
+
+async fn inheritance_share_data_for_record(
+    state: &AppState,
+    inheritance_id: i32,
+) -> Result<Option<InheritanceShareData>> {
+    let share_url = format!("https://honse.moe/s/inheritance/record/{}", inheritance_id);
+    Ok(state
+        .storage
+        .inheritance_share_by_id(inheritance_id)
+        .await?
+        .map(|(trainer_name, inheritance)| {
+            inheritance_share_data(&trainer_name, inheritance, share_url)
+        }))
+}
+
+/// GET /s/inheritance/record/{inheritance_id}[.json] - Share page for one specific record
+async fn inheritance_record_share(
+    State(state): State<AppState>,
+    Path(inheritance_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let (inheritance_id, json) = split_json_suffix(&inheritance_id, &headers);
+    let inheritance_id: i32 = inheritance_id
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid inheritance ID".to_string()))?;
+
+    serve_share_page(
+        &format!("share:inheritance-record:{}", inheritance_id),
+        json,
+        &headers,
+        inheritance_share_data_for_record(&state, inheritance_id),
+        generate_inheritance_html,
+        inheritance_not_found,
+    )
+    .await
+}
+
+fn inheritance_not_found(json: bool) -> Response {
+    if json {
+        return AppError::NotFound("Inheritance not found".to_string()).into_response();
+    }
+    let html = generate_error_html(
+        "Inheritance Not Found",
+        "The requested inheritance record could not be found.",
+    );
+    Html(html).into_response()
+}
+
+/// Display data for an inheritance share page and its image
+fn inheritance_share_data(
+    trainer_name: &str,
+    inheritance: Inheritance,
+    share_url: String,
+) -> InheritanceShareData {
+    let trainer_name = crate::moderation::mask_text(trainer_name);
+    let Inheritance {
+        inheritance_id,
+        account_id,
+        main_parent_id,
+        parent_left_id,
+        parent_right_id,
+        parent_rank,
+        parent_rarity,
+        win_count,
+        white_count,
+        blue_sparks,
+        pink_sparks,
+        green_sparks,
+        white_sparks,
+        main_blue_factors,
+        main_pink_factors,
+        main_green_factors,
+        main_white_factors,
+        main_white_count,
+        ..
+    } = inheritance;
 
-    // Extract data from the row
-    let trainer_name: String = row.get("trainer_name");
-    let main_parent_id: i32 = row.get("main_parent_id");
-    let parent_left_id: i32 = row.get("parent_left_id");
-    let parent_right_id: i32 = row.get("parent_right_id");
-    let parent_rank: i32 = row.get("parent_rank");
-    let parent_rarity: i32 = row.get("parent_rarity");
-    let win_count: i32 = row.get("win_count");
-    let white_count: i32 = row.get("white_count");
-    let blue_sparks: Vec<i32> = row.get("blue_sparks");
-    let pink_sparks: Vec<i32> = row.get("pink_sparks");
-    let green_sparks: Vec<i32> = row.get("green_sparks");
-    let white_sparks: Vec<i32> = row.get("white_sparks");
-    let main_blue_factors: i32 = row.get("main_blue_factors");
-    let main_pink_factors: i32 = row.get("main_pink_factors");
-    let main_green_factors: i32 = row.get("main_green_factors");
-    let main_white_factors: Vec<i32> = row.get("main_white_factors");
-    let main_white_count: i32 = row.get("main_white_count");
-
-    // Get character names (you'll need to create this mapping)
-    let character_name = get_character_name(main_parent_id);
-    let parent_left_name = get_character_name(parent_left_id);
-    let parent_right_name = get_character_name(parent_right_id);
+    let character_name = characters::character_name(main_parent_id);
+    let parent_left_name = characters::character_name(parent_left_id);
+    let parent_right_name = characters::character_name(parent_right_id);
 
     // Generate summaries
     let blue_factors_summary = format_sparks_summary(&blue_sparks, "blue");
@@ -117,8 +294,10 @@ async fn inheritance_share(state: &AppState, account_id: &str) -> Result<Respons
         main_white_count
     );
 
-    let share_data = InheritanceShareData {
-        account_id: account_id.to_string(),
+    InheritanceShareData {
+        account_id,
+        inheritance_id,
+        share_url,
         trainer_name,
         character_name,
         parent_left_name,
@@ -132,26 +311,75 @@ async fn inheritance_share(state: &AppState, account_id: &str) -> Result<Respons
         green_factors_summary,
         white_factors_summary,
         main_factors_summary,
-    };
+    }
+}
 
-    let html = generate_inheritance_html(&share_data);
+/// GET /s/inheritance/{account_id}/og.png - Share card image of an account's shared record
+async fn inheritance_og_image(
+    State(state): State<AppState>,
+    Path(account_id): Path<AccountId>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let share = inheritance_share_data_for_account(&state, &account_id).await?;
+    og_image_response(share, &headers).await
+}
 
-    // Set proper headers for HTML response
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        "content-type",
-        HeaderValue::from_static("text/html; charset=utf-8"),
-    );
+/// GET /s/inheritance/record/{inheritance_id}/og.png - Share card image referenced by og:image
+async fn inheritance_record_og_image(
+    State(state): State<AppState>,
+    Path(inheritance_id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let share = inheritance_share_data_for_record(&state, inheritance_id).await?;
+    og_image_response(share, &headers).await
+}
+
+/// Share card PNG, rendered from the same data as the share page
+///
+/// Identical cards are served from memory, and the ETag lets crawlers
+/// revalidate cheaply.
+async fn og_image_response(
+    share: Option<InheritanceShareData>,
+    headers: &HeaderMap,
+) -> Result<Response> {
+    let share_data = share.ok_or_else(|| AppError::NotFound("Inheritance not found".to_string()))?;
+
+    let inheritance_id = share_data.inheritance_id;
+    let svg = generate_inheritance_svg(&share_data);
+    let etag = format!("\"{}\"", crate::og_image::svg_hash(&svg));
+
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, "public, max-age=3600".to_string()),
+    ];
+
+    if etag_matches(headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
 
-    Ok((headers, Html(html)).into_response())
+    let png = crate::og_image::render_png(svg).await.map_err(|e| {
+        tracing::error!("❌ Failed to render share image for record {}: {}", inheritance_id, e);
+        AppError::DatabaseError("Failed to render share image".to_string())
+    })?;
+
+    Ok((
+        cache_headers,
+        [(header::CONTENT_TYPE, "image/png".to_string())],
+        png,
+    )
+        .into_response())
 }
 
-async fn support_card_share(state: &AppState, account_id: &str) -> Result<Response> {
+/// Trainer name and best support card behind a support card share page
+pub(crate) async fn fetch_support_card_share(
+    pool: &PgPool,
+    account_id: &AccountId,
+) -> Result<Option<(String, SupportCard)>> {
     // Query to get the best support card for this account
     let query = r#"
         SELECT 
             t.account_id,
-            t.name as trainer_name,
+            COALESCE(t.display_name_override, t.name) as trainer_name,
             sc.support_card_id,
             sc.limit_break_count,
             sc.experience
@@ -162,49 +390,273 @@ async fn support_card_share(state: &AppState, account_id: &str) -> Result<Respon
         LIMIT 1
     "#;
 
-    let row = match sqlx::query(query)
+    let row = sqlx::query(query)
         .bind(account_id)
-        .fetch_optional(&state.db)
-        .await?
-    {
-        Some(row) => row,
-        None => {
-            let html = generate_error_html(
-                "Support Card Not Found",
-                "The requested support card record could not be found.",
-            );
-            return Ok(Html(html).into_response());
-        }
+        .fetch_optional(pool)
+        .await?;
+
+    match row {
+        Some(row) => Ok(Some((row.get("trainer_name"), SupportCard::from_row(&row)?))),
+        None => Ok(None),
+    }
+}
+
+async fn support_card_share_data(
+    state: &AppState,
+    account_id: &AccountId,
+) -> Result<Option<SupportCardShareData>> {
+    let (trainer_name, support_card) = match state.storage.support_card_share(account_id).await? {
+        Some(share) => share,
+        None => return Ok(None),
     };
 
-    let trainer_name: String = row.get("trainer_name");
-    let support_card_id: i32 = row.get("support_card_id");
-    let limit_break_count: Option<i32> = row.get("limit_break_count");
-    let experience: i32 = row.get("experience");
+    let trainer_name = crate::moderation::mask_text(&trainer_name);
+    let SupportCard {
+        support_card_id,
+        limit_break_count,
+        experience,
+        ..
+    } = support_card;
 
-    // Get card details (you'll need to create this mapping)
     let (card_name, card_rarity, card_type) = get_support_card_details(support_card_id);
 
-    let share_data = SupportCardShareData {
-        account_id: account_id.to_string(),
+    Ok(Some(SupportCardShareData {
+        account_id: account_id.clone(),
         trainer_name,
         card_name,
         card_rarity,
         limit_break_count,
         experience,
         card_type,
+    }))
+}
+
+fn support_card_not_found(json: bool) -> Response {
+    if json {
+        return AppError::NotFound("Support card not found".to_string()).into_response();
+    }
+    let html = generate_error_html(
+        "Support Card Not Found",
+        "The requested support card record could not be found.",
+    );
+    Html(html).into_response()
+}
+
+async fn circle_share_data(
+    state: &AppState,
+    circle_id: CircleId,
+) -> Result<Option<CircleShareData>> {
+    let circle = match state.storage.circle(circle_id).await {
+        Ok(circle) => crate::moderation::mask_circle(circle),
+        Err(AppError::NotFound(_)) => return Ok(None),
+        Err(e) => return Err(e),
     };
 
-    let html = generate_support_card_html(&share_data);
+    Ok(Some(CircleShareData {
+        circle_id,
+        share_url: format!("https://honse.moe/s/circle/{}", circle_id),
+        name: circle.name,
+        leader_name: circle.leader_name,
+        member_count: circle.member_count,
+        monthly_rank: circle.monthly_rank,
+        monthly_point: circle.monthly_point,
+    }))
+}
 
-    // Set proper headers for HTML response
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        "content-type",
-        HeaderValue::from_static("text/html; charset=utf-8"),
+fn circle_not_found(json: bool) -> Response {
+    if json {
+        return AppError::NotFound("Circle not found".to_string()).into_response();
+    }
+    let html = generate_error_html(
+        "Circle Not Found",
+        "The requested circle could not be found.",
     );
+    Html(html).into_response()
+}
+
+/// Serve a share page from the in-process cache, rendering it on a miss
+///
+/// Embed scrapers refetch the same pages constantly, so rendered bodies are
+/// kept for SHARE_PAGE_TTL under `cache_key` and revalidated via ETag.
+/// Missing records aren't cached so new uploads show up right away.
+async fn serve_share_page<T, F>(
+    cache_key: &str,
+    json: bool,
+    headers: &HeaderMap,
+    load: F,
+    render_html: fn(&T) -> String,
+    not_found: fn(bool) -> Response,
+) -> Result<Response>
+where
+    T: serde::Serialize,
+    F: std::future::Future<Output = Result<Option<T>>>,
+{
+    let cache_key = format!("{}:{}", cache_key, if json { "json" } else { "html" });
+
+    let body = match crate::cache::get::<String>(&cache_key) {
+        Some(body) => body,
+        None => {
+            let Some(share_data) = load.await? else {
+                return Ok(not_found(json));
+            };
+            let body = if json {
+                serde_json::to_string(&share_data).map_err(|e| {
+                    AppError::DatabaseError(format!("Failed to serialize share data: {}", e))
+                })?
+            } else {
+                render_html(&share_data)
+            };
+            let _ = crate::cache::set(&cache_key, &body, SHARE_PAGE_TTL);
+            body
+        }
+    };
+
+    let etag = content_etag(body.as_bytes());
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, "public, max-age=300".to_string()),
+    ];
+    if etag_matches(headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    let content_type = if json {
+        "application/json"
+    } else {
+        "text/html; charset=utf-8"
+    };
+    Ok((cache_headers, [(header::CONTENT_TYPE, content_type)], body).into_response())
+}
+
+/// POST /api/share/shorten - Create (or reuse) a short link for a share URL
+///
+/// Body: { "url": "https://honse.moe/..." } or a path such as "/s/inheritance/123?tab=sparks"
+async fn shorten_share_link(
+    State(state): State<AppState>,
+    Json(request): Json<ShortenShareLinkRequest>,
+) -> Result<Json<ShareLinkResponse>> {
+    let target = share_links::normalize_target(&request.url)?;
+    let token = share_links::shorten(&state.db, &target).await?;
+
+    Ok(Json(ShareLinkResponse {
+        short_url: format!("https://honse.moe/s/x/{}", token),
+        token,
+        target,
+    }))
+}
+
+/// GET /s/x/{token} - Redirect a short link to its share page
+async fn short_link(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Response> {
+    match share_links::resolve(&state.db, &token).await? {
+        Some(target) => Ok(Redirect::temporary(&target).into_response()),
+        None => {
+            let html = generate_error_html(
+                "Link Not Found",
+                "This short link doesn't exist or has been removed.",
+            );
+            Ok((StatusCode::NOT_FOUND, Html(html)).into_response())
+        }
+    }
+}
+
+/// GET /oembed - oEmbed data for a share page URL (https://oembed.com)
+///
+/// Parameters:
+/// - url: A honse.moe share page URL
+/// - format: Only "json" is supported
+/// - maxwidth / maxheight: The thumbnail is left out when it doesn't fit
+async fn oembed(
+    State(state): State<AppState>,
+    Query(params): Query<OEmbedParams>,
+) -> Result<Response> {
+    if params.format.as_deref().is_some_and(|format| format != "json") {
+        return Ok((StatusCode::NOT_IMPLEMENTED, "Only JSON oEmbed is supported").into_response());
+    }
+
+    let not_found = || AppError::NotFound("Not a share page URL".to_string());
+    let url = url::Url::parse(&params.url).map_err(|_| not_found())?;
+    if !url.host_str().is_some_and(|host| SHARE_HOSTS.contains(&host)) {
+        return Err(not_found());
+    }
+    let segments: Vec<&str> = url.path_segments().ok_or_else(not_found)?.collect();
+
+    let mut response = OEmbedResponse {
+        version: "1.0".to_string(),
+        oembed_type: "link".to_string(),
+        title: String::new(),
+        author_name: String::new(),
+        provider_name: "honse.moe".to_string(),
+        provider_url: "https://honse.moe".to_string(),
+        cache_age: 3600,
+        thumbnail_url: None,
+        thumbnail_width: None,
+        thumbnail_height: None,
+    };
+
+    match segments.as_slice() {
+        ["s", "inheritance", rest @ ..] => {
+            let share = match rest {
+                ["record", inheritance_id] => {
+                    let inheritance_id = inheritance_id.parse().map_err(|_| not_found())?;
+                    inheritance_share_data_for_record(&state, inheritance_id).await?
+                }
+                [account_id] => {
+                    let account_id = AccountId(account_id.to_string());
+                    inheritance_share_data_for_account(&state, &account_id).await?
+                }
+                _ => return Err(not_found()),
+            };
+            let data = share.ok_or_else(|| AppError::NotFound("Inheritance not found".to_string()))?;
+
+            response.title = format!("{}'s {} Inheritance", data.trainer_name, data.character_name);
+            response.author_name = data.trainer_name;
+
+            let fits = params.maxwidth.is_none_or(|w| w >= crate::og_image::WIDTH)
+                && params.maxheight.is_none_or(|h| h >= crate::og_image::HEIGHT);
+            if fits {
+                response.thumbnail_url = Some(format!(
+                    "https://honse.moe/s/inheritance/record/{}/og.png",
+                    data.inheritance_id
+                ));
+                response.thumbnail_width = Some(crate::og_image::WIDTH);
+                response.thumbnail_height = Some(crate::og_image::HEIGHT);
+            }
+        }
+        ["s", "support-card", account_id] => {
+            let account_id = AccountId(account_id.to_string());
+            let data = support_card_share_data(&state, &account_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Support card not found".to_string()))?;
+
+            response.title = format!("{}'s {} Support Card", data.trainer_name, data.card_name);
+            response.author_name = data.trainer_name;
+        }
+        ["s", "circle", circle_id] => {
+            let circle_id = CircleId(circle_id.parse().map_err(|_| not_found())?);
+            let data = circle_share_data(&state, circle_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Circle not found".to_string()))?;
+
+            response.title = format!("Circle: {}", data.name);
+            response.author_name = data.leader_name.unwrap_or(data.name);
+        }
+        _ => return Err(not_found()),
+    }
+
+    Ok(Json(response).into_response())
+}
 
-    Ok((headers, Html(html)).into_response())
+/// <link> tags pointing crawlers at the JSON and oEmbed variants of a share page
+fn alternate_links(share_url: &str) -> String {
+    let encoded: String = url::form_urlencoded::byte_serialize(share_url.as_bytes()).collect();
+    format!(
+        "<link rel=\"alternate\" type=\"application/json\" href=\"{}.json\">
+    <link rel=\"alternate\" type=\"application/json+oembed\" href=\"https://honse.moe/oembed?url={}&amp;format=json\">",
+        share_url, encoded
+    )
 }
 
 fn generate_inheritance_html(data: &InheritanceShareData) -> String {
@@ -235,20 +687,25 @@ fn generate_inheritance_html(data: &InheritanceShareData) -> String {
     <meta property=\"og:type\" content=\"website\">
     <meta property=\"og:title\" content=\"{}\">
     <meta property=\"og:description\" content=\"{}\">
-    <meta property=\"og:url\" content=\"https://honse.moe/s/inheritance/{}\">
+    <meta property=\"og:url\" content=\"{}\">
     <meta property=\"og:site_name\" content=\"Honse.moe - Uma Musume Database\">
     <meta property=\"og:color\" content=\"#FF6B9D\">
+    <meta property=\"og:image\" content=\"https://honse.moe/s/inheritance/record/{}/og.png\">
+    <meta property=\"og:image:width\" content=\"1200\">
+    <meta property=\"og:image:height\" content=\"630\">
     
     <!-- Twitter Card -->
-    <meta name=\"twitter:card\" content=\"summary\">
+    <meta name=\"twitter:card\" content=\"summary_large_image\">
     <meta name=\"twitter:title\" content=\"{}\">
     <meta name=\"twitter:description\" content=\"{}\">
+    <meta name=\"twitter:image\" content=\"https://honse.moe/s/inheritance/record/{}/og.png\">
+    {alternate_links}
     
     <!-- Redirect to main app -->
     <script>
         // Redirect to the main app after a short delay to allow Discord to scrape
         setTimeout(function() {{
-            window.location.href = 'https://honse.moe/inheritance?trainer_id={}';
+            window.location.href = 'https://honse.moe/inheritance?trainer_id={}&inheritance_id={}';
         }}, 2000);
     </script>
     
@@ -353,10 +810,13 @@ fn generate_inheritance_html(data: &InheritanceShareData) -> String {
         title,
         title,
         description,
-        data.account_id,
+        data.share_url,
+        data.inheritance_id,
         title,
         description,
+        data.inheritance_id,
         data.account_id,
+        data.inheritance_id,
         data.character_name,
         data.trainer_name,
         data.parent_left_name,
@@ -369,11 +829,98 @@ fn generate_inheritance_html(data: &InheritanceShareData) -> String {
         data.pink_factors_summary,
         data.green_factors_summary,
         data.white_factors_summary,
-        data.main_factors_summary
+        data.main_factors_summary,
+        alternate_links = alternate_links(&data.share_url)
     );
     html
 }
 
+/// Shorten text to `max_chars`, ending with an ellipsis when cut
+fn truncate_text(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut truncated: String = text.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+/// 1200x630 share card for an inheritance record (rendered to PNG for og:image)
+fn generate_inheritance_svg(data: &InheritanceShareData) -> String {
+    // Placeholder art until character portraits are available: the name's initial
+    let initial = data
+        .character_name
+        .chars()
+        .next()
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_default();
+
+    let spark_rows = [
+        ("Blue", "#4A90E2", &data.blue_factors_summary),
+        ("Pink", "#FF6B9D", &data.pink_factors_summary),
+        ("Green", "#7ED321", &data.green_factors_summary),
+        ("White", "#D8D8E0", &data.white_factors_summary),
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, (label, color, summary))| {
+        let y = 360 + i as i32 * 52;
+        format!(
+            "<circle cx=\"492\" cy=\"{}\" r=\"10\" fill=\"{}\"/>\
+             <text x=\"514\" y=\"{}\" font-size=\"26\" fill=\"#B8B8C8\">{}</text>\
+             <text x=\"610\" y=\"{}\" font-size=\"26\" fill=\"#FFFFFF\">{}</text>",
+            y - 9,
+            color,
+            y,
+            label,
+            y,
+            xml_escape(&truncate_text(summary, 42))
+        )
+    })
+    .collect::<String>();
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">
+    <defs>
+        <linearGradient id=\"bg\" x1=\"0\" y1=\"0\" x2=\"1\" y2=\"1\">
+            <stop offset=\"0\" stop-color=\"#1E1B2E\"/>
+            <stop offset=\"1\" stop-color=\"#3B2A4D\"/>
+        </linearGradient>
+    </defs>
+    <rect width=\"{width}\" height=\"{height}\" fill=\"url(#bg)\"/>
+    <rect x=\"0\" y=\"0\" width=\"{width}\" height=\"8\" fill=\"#FF6B9D\"/>
+    <g font-family=\"DejaVu Sans, Arial, sans-serif\">
+        <circle cx=\"240\" cy=\"315\" r=\"170\" fill=\"#FF6B9D\" fill-opacity=\"0.18\" stroke=\"#FF6B9D\" stroke-width=\"6\"/>
+        <text x=\"240\" y=\"365\" font-size=\"150\" font-weight=\"bold\" fill=\"#FF6B9D\" text-anchor=\"middle\">{initial}</text>
+        <text x=\"470\" y=\"120\" font-size=\"54\" font-weight=\"bold\" fill=\"#FFFFFF\">{character}</text>
+        <text x=\"470\" y=\"172\" font-size=\"30\" fill=\"#B8B8C8\">{trainer}</text>
+        <text x=\"470\" y=\"232\" font-size=\"26\" fill=\"#B8B8C8\">Parents: <tspan fill=\"#FFFFFF\">{parents}</tspan></text>
+        <text x=\"470\" y=\"280\" font-size=\"26\" fill=\"#B8B8C8\">Wins: <tspan fill=\"#FFFFFF\">{wins}</tspan><tspan dx=\"36\">White skills: </tspan><tspan fill=\"#FFFFFF\">{white_count}</tspan></text>
+        {spark_rows}
+        <rect x=\"1000\" y=\"40\" width=\"160\" height=\"110\" rx=\"18\" fill=\"#FF6B9D\"/>
+        <text x=\"1080\" y=\"108\" font-size=\"52\" font-weight=\"bold\" fill=\"#FFFFFF\" text-anchor=\"middle\">{rank}</text>
+        <text x=\"1080\" y=\"190\" font-size=\"30\" fill=\"#FFD166\" text-anchor=\"middle\">{rarity}</text>
+        <text x=\"1160\" y=\"600\" font-size=\"24\" fill=\"#8A8AA0\" text-anchor=\"end\">honse.moe</text>
+    </g>
+</svg>",
+        width = crate::og_image::WIDTH,
+        height = crate::og_image::HEIGHT,
+        initial = xml_escape(&initial),
+        character = xml_escape(&truncate_text(&data.character_name, 24)),
+        trainer = xml_escape(&truncate_text(&data.trainer_name, 36)),
+        parents = xml_escape(&truncate_text(
+            &format!("{} × {}", data.parent_left_name, data.parent_right_name),
+            40
+        )),
+        wins = data.win_count,
+        white_count = data.white_count,
+        spark_rows = spark_rows,
+        rank = xml_escape(&get_rank_display(data.parent_rank)),
+        rarity = xml_escape(&get_rarity_display(data.parent_rarity)),
+    )
+}
+
 fn generate_support_card_html(data: &SupportCardShareData) -> String {
     let title = format!("{}'s {} Support Card", data.trainer_name, data.card_name);
     let limit_break_display = match data.limit_break_count {
@@ -405,6 +952,7 @@ fn generate_support_card_html(data: &SupportCardShareData) -> String {
     <meta name=\"twitter:card\" content=\"summary\">
     <meta name=\"twitter:title\" content=\"{}\">
     <meta name=\"twitter:description\" content=\"{}\">
+    {alternate_links}
     
     <!-- Redirect to main app -->
     <script>
@@ -504,11 +1052,162 @@ fn generate_support_card_html(data: &SupportCardShareData) -> String {
         data.card_rarity,
         limit_break_display,
         data.experience,
-        data.card_type
+        data.card_type,
+        alternate_links =
+            alternate_links(&format!("https://honse.moe/s/support-card/{}", data.account_id))
+    );
+    html
+}
+
+fn generate_circle_html(data: &CircleShareData) -> String {
+    let title = format!("Circle: {}", data.name);
+    let rank_display = data
+        .monthly_rank
+        .map(|rank| format!("#{}", rank))
+        .unwrap_or_else(|| "Unranked".to_string());
+    let points_display = data
+        .monthly_point
+        .map(format_points)
+        .unwrap_or_else(|| "0".to_string());
+    let members_display = data
+        .member_count
+        .map(|count| format!("{}/30", count))
+        .unwrap_or_else(|| "?".to_string());
+    let description = format!(
+        "Rank: {} • Monthly Fans: {} • Members: {}{}",
+        rank_display,
+        points_display,
+        members_display,
+        data.leader_name
+            .as_ref()
+            .map(|leader| format!(" • Leader: {}", leader))
+            .unwrap_or_default()
+    );
+
+    let html = format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+    <meta charset=\"UTF-8\">
+    <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">
+    <title>{title}</title>
+    
+    <!-- Discord Embed Meta Tags -->
+    <meta property=\"og:type\" content=\"website\">
+    <meta property=\"og:title\" content=\"{title}\">
+    <meta property=\"og:description\" content=\"{description}\">
+    <meta property=\"og:url\" content=\"{share_url}\">
+    <meta property=\"og:site_name\" content=\"Honse.moe - Uma Musume Database\">
+    <meta property=\"og:color\" content=\"#FF9800\">
+    
+    <!-- Twitter Card -->
+    <meta name=\"twitter:card\" content=\"summary\">
+    <meta name=\"twitter:title\" content=\"{title}\">
+    <meta name=\"twitter:description\" content=\"{description}\">
+    {alternate_links}
+    
+    <!-- Redirect to main app -->
+    <script>
+        // Redirect to the main app after a short delay to allow Discord to scrape
+        setTimeout(function() {{
+            window.location.href = 'https://honse.moe/circles/{circle_id}';
+        }}, 2000);
+    </script>
+    
+    <style>
+        body {{
+            font-family: Arial, sans-serif;
+            max-width: 800px;
+            margin: 0 auto;
+            padding: 20px;
+            background-color: #f5f5f5;
+        }}
+        .card {{
+            background: white;
+            border-radius: 10px;
+            padding: 20px;
+            box-shadow: 0 2px 10px rgba(0,0,0,0.1);
+            margin-bottom: 20px;
+        }}
+        .circle-name {{
+            font-size: 24px;
+            font-weight: bold;
+            color: #FF9800;
+            margin-bottom: 10px;
+        }}
+        .stats {{
+            display: grid;
+            grid-template-columns: repeat(auto-fit, minmax(150px, 1fr));
+            gap: 10px;
+            margin-bottom: 15px;
+        }}
+        .stat {{
+            background: #f8f9fa;
+            padding: 10px;
+            border-radius: 5px;
+            text-align: center;
+        }}
+        .redirect-notice {{
+            background: #e3f2fd;
+            border: 1px solid #2196F3;
+            border-radius: 5px;
+            padding: 15px;
+            text-align: center;
+            color: #1976D2;
+        }}
+    </style>
+</head>
+<body>
+    <div class=\"card\">
+        <div class=\"circle-name\">{name}</div>
+        
+        <div class=\"stats\">
+            <div class=\"stat\">
+                <strong>Monthly Rank</strong><br>
+                {rank_display}
+            </div>
+            <div class=\"stat\">
+                <strong>Monthly Fans</strong><br>
+                {points_display}
+            </div>
+            <div class=\"stat\">
+                <strong>Members</strong><br>
+                {members_display}
+            </div>
+        </div>
+    </div>
+    
+    <div class=\"redirect-notice\">
+        Redirecting to the circle page in a moment...
+    </div>
+</body>
+</html>",
+        title = xml_escape(&title),
+        description = xml_escape(&description),
+        share_url = data.share_url,
+        circle_id = data.circle_id,
+        name = xml_escape(&data.name),
+        alternate_links = alternate_links(&data.share_url),
     );
     html
 }
 
+/// Fan count with thousands separators (12,345,678)
+fn format_points(points: i64) -> String {
+    let digits = points.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    if points < 0 {
+        grouped.insert(0, '-');
+    }
+    grouped
+}
+
 fn generate_error_html(title: &str, message: &str) -> String {
     format!(
         "<!DOCTYPE html>
@@ -573,117 +1272,25 @@ fn generate_error_html(title: &str, message: &str) -> String {
     )
 }
 
-// Helper functions for mapping IDs to names (you'll need to implement these)
-fn get_character_name(character_id: i32) -> String {
-    // This is a simplified mapping - you should load this from your data files
-    match character_id {
-        1 => "Special Week".to_string(),
-        2 => "Silence Suzuka".to_string(),
-        3 => "Tokai Teio".to_string(),
-        4 => "Vodka".to_string(),
-        5 => "Daiwa Scarlet".to_string(),
-        6 => "Gold Ship".to_string(),
-        7 => "Mejiro McQueen".to_string(),
-        8 => "Emperor".to_string(),
-        9 => "Fuji Kiseki".to_string(),
-        10 => "Orfevre".to_string(),
-        11 => "Agnes Tachyon".to_string(),
-        12 => "Agnes Digital".to_string(),
-        13 => "Haru Urara".to_string(),
-        14 => "El Condor Pasa".to_string(),
-        15 => "Grass Wonder".to_string(),
-        16 => "Air Groove".to_string(),
-        17 => "Mayano Top Gun".to_string(),
-        18 => "Manhattan Cafe".to_string(),
-        19 => "Mihono Bourbon".to_string(),
-        20 => "Mejiro Ryan".to_string(),
-        21 => "Hishi Amazon".to_string(),
-        22 => "Yukino Bijin".to_string(),
-        23 => "Rice Shower".to_string(),
-        24 => "King Halo".to_string(),
-        25 => "Matikanetannhauser".to_string(),
-        26 => "Ikuno Dictus".to_string(),
-        27 => "Tamamo Cross".to_string(),
-        28 => "Fine Motion".to_string(),
-        29 => "Biwa Hayahide".to_string(),
-        30 => "Narita Taishin".to_string(),
-        31 => "Winning Ticket".to_string(),
-        32 => "Air Shakur".to_string(),
-        33 => "Eishin Flash".to_string(),
-        34 => "Copano Rickey".to_string(),
-        35 => "Sinister Minister".to_string(),
-        36 => "Mejiro Dober".to_string(),
-        37 => "Twin Turbo".to_string(),
-        38 => "Marvelous Sunday".to_string(),
-        39 => "Seeking the Pearl".to_string(),
-        40 => "Shinko Windy".to_string(),
-        41 => "Sweep Tosho".to_string(),
-        42 => "Super Creek".to_string(),
-        43 => "Smart Falcon".to_string(),
-        44 => "Zen-no-Rob Roy".to_string(),
-        45 => "T.M. Opera O".to_string(),
-        46 => "Narita Brian".to_string(),
-        47 => "Symboli Rudolf".to_string(),
-        48 => "Aiming for the Top".to_string(),
-        49 => "Admire Vega".to_string(),
-        50 => "Inari One".to_string(),
-        51 => "Winning Ticket".to_string(),
-        52 => "Nice Nature".to_string(),
-        53 => "Tosen Jordan".to_string(),
-        54 => "Mejiro Bright".to_string(),
-        55 => "Satono Diamond".to_string(),
-        56 => "Kitasan Black".to_string(),
-        57 => "Sakura Bakushin O".to_string(),
-        58 => "Sirius Symboli".to_string(),
-        59 => "Mejiro Ardan".to_string(),
-        60 => "Yaeno Muteki".to_string(),
-        61 => "Nishino Flower".to_string(),
-        62 => "Hokko Tarumae".to_string(),
-        63 => "Wonder Acute".to_string(),
-        64 => "Nakayama Festa".to_string(),
-        65 => "Tap Dance City".to_string(),
-        66 => "Curren Chan".to_string(),
-        67 => "Gold City".to_string(),
-        68 => "Sakura Chiyono O".to_string(),
-        69 => "Meisho Doto".to_string(),
-        70 => "Yamanin Zephyr".to_string(),
-        71 => "K.S. Miracle".to_string(),
-        72 => "Dantsu Flame".to_string(),
-        73 => "Sound of Earth".to_string(),
-        74 => "Duramente".to_string(),
-        75 => "Daiichi Ruby".to_string(),
-        76 => "Zenno Rob Roy".to_string(),
-        77 => "Tagano Diamond".to_string(),
-        78 => "Kawakami Princess".to_string(),
-        79 => "Mejiro Palmer".to_string(),
-        80 => "Neo Universe".to_string(),
-        81 => "Symboli Kris S".to_string(),
-        82 => "Narita Top Road".to_string(),
-        83 => "Jungle Pocket".to_string(),
-        84 => "Daiwa Major".to_string(),
-        85 => "Yukikaze".to_string(),
-        86 => "Cheval Grand".to_string(),
-        87 => "Gossamer".to_string(),
-        88 => "Meiner Liebe".to_string(),
-        89 => "Agnes World".to_string(),
-        90 => "World End".to_string(),
-        91 => "Lovely Derby".to_string(),
-        92 => "Bamboo Memory".to_string(),
-        93 => "Hello Unique".to_string(),
-        94 => "Zenith".to_string(),
-        _ => format!("Character {}", character_id),
-    }
-}
-
-fn get_support_card_details(support_card_id: i32) -> (String, String, String) {
-    // This is a simplified mapping - you should load this from your data files
-    // Return (name, rarity, type)
-    match support_card_id {
-        // This is just an example - you'll need to populate with actual support card data
-        _ => (
+// Helper functions for mapping IDs to names (character names: crate::characters)
+fn get_support_card_details(support_card_id: CardId) -> (String, String, String) {
+    // Return (name, rarity, type) from the imported game data
+    match crate::support_cards::support_card_meta(support_card_id) {
+        Some(meta) => {
+            let mut card_type = meta.card_type;
+            if let Some(first) = card_type.get_mut(0..1) {
+                first.make_ascii_uppercase();
+            }
+            (
+                meta.name,
+                crate::support_cards::rarity_label(meta.rarity),
+                card_type,
+            )
+        }
+        None => (
             format!("Support Card {}", support_card_id),
-            "★★★".to_string(),
-            "Speed".to_string(),
+            "Unknown".to_string(),
+            "Unknown".to_string(),
         ),
     }
 }
@@ -718,13 +1325,12 @@ fn format_sparks_summary(sparks: &[i32], _spark_type: &str) -> String {
         return "None".to_string();
     }
 
-    // Group sparks by factor type and count levels
-    use std::collections::HashMap;
-    let mut factor_counts: HashMap<i32, Vec<i32>> = HashMap::new();
+    // Group sparks by factor type and count levels (ordered, so summaries are stable)
+    use std::collections::BTreeMap;
+    let mut factor_counts: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
 
     for &spark in sparks {
-        let factor_id = spark / 10;
-        let level = spark % 10;
+        let (factor_id, level) = SparkEncoding::V1.decode(spark);
         factor_counts.entry(factor_id).or_default().push(level);
     }
 
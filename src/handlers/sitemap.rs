@@ -0,0 +1,245 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::time::Duration;
+
+use crate::cache::Namespace;
+use crate::errors::AppError;
+use crate::handlers::feeds::{xml_escape, SITE_URL};
+use crate::AppState;
+
+/// URLs per sitemap page (the protocol allows up to 50000)
+const SITEMAP_PAGE_SIZE: i64 = 10000;
+
+/// Sitemaps are served from cache for this long, then refreshed in the background
+const SITEMAP_REFRESH: Duration = Duration::from_secs(3600);
+const SITEMAP_MAX_AGE: Duration = Duration::from_secs(6 * 3600);
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/sitemap.xml", get(sitemap_index))
+        .route("/sitemaps/:file", get(sitemap_page))
+}
+
+/// What a sitemap page lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SitemapKind {
+    /// Trainer share pages (/s/inheritance/:account_id)
+    Trainers,
+    /// Circle share pages (/s/circle/:circle_id)
+    Circles,
+}
+
+impl SitemapKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            SitemapKind::Trainers => "trainers",
+            SitemapKind::Circles => "circles",
+        }
+    }
+
+    /// Parse "trainers-3.xml" into (kind, page)
+    fn parse_file(file: &str) -> Option<(Self, usize)> {
+        let (kind, page) = file.strip_suffix(".xml")?.rsplit_once('-')?;
+        let kind = match kind {
+            "trainers" => SitemapKind::Trainers,
+            "circles" => SitemapKind::Circles,
+            _ => return None,
+        };
+        Some((kind, page.parse().ok().filter(|&page| page > 0)?))
+    }
+}
+
+/// First key of each sitemap page, so pages are read with a range scan
+/// instead of an OFFSET
+#[derive(Serialize, Deserialize)]
+struct SitemapPages {
+    trainers: Vec<String>,
+    circles: Vec<i64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SitemapUrl {
+    path: String,
+    last_updated: Option<NaiveDateTime>,
+}
+
+fn xml_response(xml: String) -> Response {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/xml; charset=utf-8"),
+    );
+    (headers, xml).into_response()
+}
+
+async fn sitemap_pages(pool: &PgPool) -> Result<SitemapPages, AppError> {
+    let pool = pool.clone();
+    crate::cache::get_or_revalidate(
+        &Namespace::Feeds.key("sitemap:pages"),
+        SITEMAP_REFRESH,
+        SITEMAP_MAX_AGE,
+        move || async move { load_sitemap_pages(&pool).await },
+    )
+    .await
+}
+
+async fn load_sitemap_pages(pool: &PgPool) -> Result<SitemapPages, AppError> {
+    let trainers = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT account_id FROM (
+            SELECT t.account_id, ROW_NUMBER() OVER (ORDER BY t.account_id) AS n
+            FROM trainer t
+            WHERE NOT t.stale
+              AND EXISTS (SELECT 1 FROM inheritance i WHERE i.account_id = t.account_id)
+        ) numbered
+        WHERE n % $1 = 1
+        ORDER BY account_id
+        "#,
+    )
+    .bind(SITEMAP_PAGE_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let circles = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT circle_id FROM (
+            SELECT circle_id, ROW_NUMBER() OVER (ORDER BY circle_id) AS n
+            FROM circles
+            WHERE archived IS NOT TRUE
+        ) numbered
+        WHERE n % $1 = 1
+        ORDER BY circle_id
+        "#,
+    )
+    .bind(SITEMAP_PAGE_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(SitemapPages { trainers, circles })
+}
+
+/// GET /sitemap.xml - Sitemap index listing the trainer and circle sitemap pages
+pub async fn sitemap_index(State(state): State<AppState>) -> Result<Response, AppError> {
+    let pages = sitemap_pages(&state.db).await?;
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for (kind, count) in [
+        (SitemapKind::Trainers, pages.trainers.len()),
+        (SitemapKind::Circles, pages.circles.len()),
+    ] {
+        for page in 1..=count {
+            xml.push_str(&format!(
+                "  <sitemap><loc>{}/sitemaps/{}-{}.xml</loc></sitemap>\n",
+                SITE_URL,
+                kind.as_str(),
+                page
+            ));
+        }
+    }
+    xml.push_str("</sitemapindex>\n");
+
+    Ok(xml_response(xml))
+}
+
+/// GET /sitemaps/{trainers|circles}-{page}.xml - One page of share URLs
+///
+/// Trainers are listed if they have an inheritance record and aren't stale,
+/// circles unless archived. Each page holds up to 10000 URLs.
+pub async fn sitemap_page(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+) -> Result<Response, AppError> {
+    let not_found = || AppError::NotFound(format!("No sitemap {}", file));
+    let (kind, page) = SitemapKind::parse_file(&file).ok_or_else(not_found)?;
+
+    let pool = state.db.clone();
+    let xml = crate::cache::get_or_revalidate(
+        &Namespace::Feeds.key(&format!("sitemap:{}-{}", kind.as_str(), page)),
+        SITEMAP_REFRESH,
+        SITEMAP_MAX_AGE,
+        move || async move { render_sitemap_page(&pool, kind, page).await },
+    )
+    .await?;
+
+    xml.map(xml_response).ok_or_else(not_found)
+}
+
+/// The page's urlset, or None if there is no such page
+async fn render_sitemap_page(
+    pool: &PgPool,
+    kind: SitemapKind,
+    page: usize,
+) -> Result<Option<String>, AppError> {
+    let pages = sitemap_pages(pool).await?;
+
+    let urls = match kind {
+        SitemapKind::Trainers => {
+            let Some(start) = pages.trainers.get(page - 1) else {
+                return Ok(None);
+            };
+            sqlx::query_as::<_, SitemapUrl>(
+                r#"
+                SELECT '/s/inheritance/' || t.account_id AS path, t.last_updated
+                FROM trainer t
+                WHERE t.account_id >= $1 AND ($2::text IS NULL OR t.account_id < $2)
+                  AND NOT t.stale
+                  AND EXISTS (SELECT 1 FROM inheritance i WHERE i.account_id = t.account_id)
+                ORDER BY t.account_id
+                LIMIT 50000
+                "#,
+            )
+            .bind(start)
+            .bind(pages.trainers.get(page))
+            .fetch_all(pool)
+            .await?
+        }
+        SitemapKind::Circles => {
+            let Some(start) = pages.circles.get(page - 1) else {
+                return Ok(None);
+            };
+            sqlx::query_as::<_, SitemapUrl>(
+                r#"
+                SELECT '/s/circle/' || circle_id AS path, last_updated
+                FROM circles
+                WHERE circle_id >= $1 AND ($2::bigint IS NULL OR circle_id < $2)
+                  AND archived IS NOT TRUE
+                ORDER BY circle_id
+                LIMIT 50000
+                "#,
+            )
+            .bind(start)
+            .bind(pages.circles.get(page))
+            .fetch_all(pool)
+            .await?
+        }
+    };
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for url in urls {
+        let lastmod = url
+            .last_updated
+            .map(|at| format!("<lastmod>{}</lastmod>", at.format("%Y-%m-%d")))
+            .unwrap_or_default();
+        xml.push_str(&format!(
+            "  <url><loc>{}{}</loc>{}</url>\n",
+            SITE_URL,
+            xml_escape(&url.path),
+            lastmod
+        ));
+    }
+    xml.push_str("</urlset>\n");
+
+    Ok(Some(xml))
+}
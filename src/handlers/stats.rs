@@ -1,66 +1,121 @@
 use axum::{
-    extract::{ConnectInfo, Path, Query, State},
-    response::Json,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Path, Query, State,
+    },
+    http::{header, HeaderMap},
+    response::{Json, Response},
     routing::{get, post},
     Router,
 };
 use serde_json::{json, Value};
-use sqlx::Row;
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use validator::Validate;
+use std::collections::{BTreeMap, HashMap};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use crate::errors::AppError;
+use crate::handlers::tasks::{find_pending_task, is_valid_trainer_id};
+use crate::middleware::DegradedFallback;
 use crate::models::{
-    DailyStatsResponse, DailyVisitRequest, FriendlistReportResponse, RollingStats, StatsResponse,
-    TodayStats, TotalStats,
+    CharaId, CharacterCardStats, CharacterStats, CharacterStatsResponse, DailyStatsResponse,
+    FriendlistReportResponse, FriendlistReportStats, RollingStats,
+    StatsResponse, TeamStadiumBreakdown, TeamStadiumCharacterStats, TeamStadiumStatsParams,
+    TeamStadiumStatsResponse, TodayStats, TotalStats, UploadBucket, UploadStatsParams, UploadStatsResponse,
+    PRIORITY_RECHECK,
 };
+use crate::storage::StatsCounts;
 use crate::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/daily-visit", post(track_daily_visit))
-        .route("/", get(get_stats))
-        .route("/daily", get(get_daily_stats))
-        .route("/today", get(get_today_stats_endpoint))
+        .route(
+            "/",
+            get(get_stats).layer(axum::middleware::from_fn_with_state(
+                DegradedFallback::ALL,
+                crate::middleware::degraded_middleware,
+            )),
+        )
+        .route(
+            "/daily",
+            get(get_daily_stats).layer(axum::middleware::from_fn_with_state(
+                DegradedFallback::ALL,
+                crate::middleware::degraded_middleware,
+            )),
+        )
+        .route(
+            "/today",
+            get(get_today_stats_endpoint).layer(axum::middleware::from_fn_with_state(
+                DegradedFallback::ALL,
+                crate::middleware::degraded_middleware,
+            )),
+        )
+        .route(
+            "/characters",
+            get(get_character_stats)
+                .layer(axum::middleware::from_fn_with_state(
+                    Duration::from_secs(3600),
+                    crate::middleware::response_cache_middleware,
+                ))
+                .layer(axum::middleware::from_fn_with_state(
+                    DegradedFallback::ALL,
+                    crate::middleware::degraded_middleware,
+                )),
+        )
+        .route(
+            "/uploads",
+            get(get_upload_stats)
+                .layer(axum::middleware::from_fn_with_state(
+                    Duration::from_secs(600),
+                    crate::middleware::response_cache_middleware,
+                ))
+                .layer(axum::middleware::from_fn_with_state(
+                    DegradedFallback::ALL,
+                    crate::middleware::degraded_middleware,
+                )),
+        )
+        .route(
+            "/team-stadium",
+            get(get_team_stadium_stats)
+                .layer(axum::middleware::from_fn_with_state(
+                    Duration::from_secs(3600),
+                    crate::middleware::response_cache_middleware,
+                ))
+                .layer(axum::middleware::from_fn_with_state(
+                    DegradedFallback::ALL,
+                    crate::middleware::degraded_middleware,
+                )),
+        )
+        .route("/live", get(live_stats))
         .route("/friendlist/:id", post(report_friendlist_full))
+        .route("/friendlist-reports/:trainer_id", get(get_friendlist_reports))
 }
 
-// New efficient daily visit tracking (only increments counter once per day per user)
+/// POST /api/stats/daily-visit - Count the caller as today's visitor
+///
+/// The visitor is identified server-side (salted hash of IP + user agent) and
+/// added to today's HyperLogLog sketch; any request body is ignored.
 pub async fn track_daily_visit(
     State(state): State<AppState>,
-    Json(payload): Json<DailyVisitRequest>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
 ) -> Result<Json<Value>, AppError> {
-    // Parse the date
-    let target_date = match chrono::NaiveDate::parse_from_str(&payload.date, "%Y-%m-%d") {
-        Ok(date) => date,
-        Err(_) => {
-            return Ok(Json(json!({
-                "success": false,
-                "error": "Invalid date format"
-            })));
-        }
-    };
+    let client_ip = crate::middleware::turnstile::extract_client_ip(&headers, addr);
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
 
-    // Call the database function to increment the daily counter
-    let result = sqlx::query_scalar::<_, i32>("SELECT increment_daily_visitor_count($1)")
-        .bind(target_date)
-        .fetch_one(&state.db)
-        .await;
-
-    match result {
-        Ok(count) => Ok(Json(json!({
-            "success": true,
-            "daily_count": count
-        }))),
-        Err(e) => {
-            eprintln!("Database error in track_daily_visit: {}", e);
-            // Gracefully handle database errors
-            Ok(Json(json!({
-                "success": true,
-                "daily_count": 1
-            })))
-        }
+    if let Err(e) = crate::visitors::record_visit(&state.db, &client_ip, user_agent).await {
+        // Visitor counting must never break page loads
+        tracing::warn!("⚠️ Failed to record daily visit: {}", e);
     }
+
+    Ok(Json(json!({ "success": true })))
 }
 
 pub async fn get_stats(
@@ -74,35 +129,25 @@ pub async fn get_stats(
 
     // Check cache first - cache for 1 hour
     let cache_key = "stats:main";
-    if let Some(cached) = crate::cache::get::<StatsResponse>(cache_key) {
+    if let Some(mut cached) = crate::cache::get::<StatsResponse>(cache_key) {
+        // Refresh times move independently of the cached counts
+        cached.views = crate::views::statuses();
         return Ok(Json(cached));
     }
 
-    // Use materialized view for instant results (no counting needed!)
-    // This query returns in <1ms instead of 1+ seconds
-    let stats_row = sqlx::query(
-        r#"
-        SELECT 
-            COALESCE(trainer_count, 0) as total_accounts_tracked,
-            COALESCE(circles_count, 0) as total_circles_tracked,
-            COALESCE(team_stadium_count, 0) as total_characters,
-            COALESCE(unique_visitors_7_day, 0) as unique_visitors_7_day
-        FROM stats_counts
-        LIMIT 1
-        "#,
-    )
-    .fetch_one(&state.db)
-    .await?;
-
-    let unique_visitors_7_day = stats_row.get::<f64, _>("unique_visitors_7_day");
-    let total_accounts_tracked = stats_row.get::<i64, _>("total_accounts_tracked");
-    let total_circles_tracked = stats_row.get::<i64, _>("total_circles_tracked");
-    let total_characters = stats_row.get::<i64, _>("total_characters");
+    let StatsCounts {
+        unique_visitors_today,
+        unique_visitors_7_day,
+        unique_visitors_30_day,
+        total_accounts_tracked,
+        total_circles_tracked,
+        total_characters,
+    } = state.storage.stats_counts().await?;
 
     // Fixed values for everything else
     let today_stats = TodayStats {
         total_visitors: 0,
-        unique_visitors: 0,
+        unique_visitors: unique_visitors_today as i32,
         inheritance_uploads: 0,
         total_inheritance_records: 0,
         total_support_card_records: 0,
@@ -112,7 +157,7 @@ pub async fn get_stats(
         visitors_7_day: 0.0,
         visitors_30_day: 0.0,
         unique_visitors_7_day,
-        unique_visitors_30_day: 0.0,
+        unique_visitors_30_day,
         uploads_7_day: 0.0,
         uploads_30_day: 0.0,
     };
@@ -135,14 +180,358 @@ pub async fn get_stats(
         rolling_averages,
         daily_data,
         totals: total_stats,
+        views: crate::views::statuses(),
+    };
+
+    Ok(Json(response))
+}
+
+/// Headline counters from the stats_counts materialized view and the visitor sketches
+pub(crate) async fn fetch_stats_counts(pool: &PgPool) -> Result<StatsCounts, AppError> {
+    // Use materialized view for instant results (no counting needed!)
+    // This query returns in <1ms instead of 1+ seconds
+    let stats_row = sqlx::query(
+        r#"
+        SELECT 
+            COALESCE(trainer_count, 0) as total_accounts_tracked,
+            COALESCE(circles_count, 0) as total_circles_tracked,
+            COALESCE(team_stadium_count, 0) as total_characters
+        FROM stats_counts
+        LIMIT 1
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(StatsCounts {
+        unique_visitors_today: crate::visitors::unique_visitors(pool, 1).await? as i64,
+        unique_visitors_7_day: crate::visitors::unique_visitors(pool, 7).await?,
+        unique_visitors_30_day: crate::visitors::unique_visitors(pool, 30).await?,
+        total_accounts_tracked: stats_row.get::<i64, _>("total_accounts_tracked"),
+        total_circles_tracked: stats_row.get::<i64, _>("total_circles_tracked"),
+        total_characters: stats_row.get::<i64, _>("total_characters"),
+    })
+}
+
+/// GET /api/stats/characters - How often each character is used in inheritance records
+///
+/// Counts main parent (per character and per card variant) and grandparent
+/// appearances plus the average parent_rank, over all inheritance records.
+/// Cached for 1 hour.
+pub async fn get_character_stats(
+    State(state): State<AppState>,
+) -> Result<Json<CharacterStatsResponse>, AppError> {
+    let total_records = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM inheritance")
+        .fetch_one(state.read_db())
+        .await?;
+
+    let main_rows = sqlx::query(
+        r#"
+        SELECT
+            main_parent_id,
+            COUNT(*) AS main_count,
+            SUM(parent_rank)::float8 AS rank_sum,
+            COUNT(parent_rank) AS ranked
+        FROM inheritance
+        WHERE main_parent_id > 0
+        GROUP BY main_parent_id
+        "#,
+    )
+    .fetch_all(state.read_db())
+    .await?;
+
+    let grandparent_rows = sqlx::query(
+        r#"
+        SELECT g.parent_id / 100 AS chara_id, COUNT(*) AS grandparent_count
+        FROM inheritance i
+        CROSS JOIN LATERAL (VALUES (i.parent_left_id), (i.parent_right_id)) AS g(parent_id)
+        WHERE g.parent_id > 0
+        GROUP BY 1
+        "#,
+    )
+    .fetch_all(state.read_db())
+    .await?;
+
+    // Per base character: card stats plus the rank sum/count for the overall average
+    let mut characters: BTreeMap<i32, (Vec<CharacterCardStats>, f64, i64)> = BTreeMap::new();
+    for row in main_rows {
+        let main_parent_id = CharaId(row.get("main_parent_id"));
+        let main_count: i64 = row.get("main_count");
+        let rank_sum: Option<f64> = row.get("rank_sum");
+        let ranked: i64 = row.get("ranked");
+
+        let entry = characters.entry(main_parent_id.base().0).or_default();
+        entry.0.push(CharacterCardStats {
+            main_parent_id,
+            main_count,
+            avg_parent_rank: rank_sum.filter(|_| ranked > 0).map(|sum| sum / ranked as f64),
+        });
+        entry.1 += rank_sum.unwrap_or(0.0);
+        entry.2 += ranked;
+    }
+
+    let mut grandparent_counts: HashMap<i32, i64> = HashMap::new();
+    for row in grandparent_rows {
+        let chara_id: i32 = row.get("chara_id");
+        grandparent_counts.insert(chara_id, row.get("grandparent_count"));
+        characters.entry(chara_id).or_default();
+    }
+
+    let mut characters: Vec<CharacterStats> = characters
+        .into_iter()
+        .map(|(chara_id, (mut cards, rank_sum, ranked))| {
+            cards.sort_by(|a, b| {
+                b.main_count
+                    .cmp(&a.main_count)
+                    .then(a.main_parent_id.cmp(&b.main_parent_id))
+            });
+            CharacterStats {
+                chara_id: CharaId(chara_id),
+                name: crate::characters::character_name(CharaId(chara_id)),
+                main_count: cards.iter().map(|card| card.main_count).sum(),
+                grandparent_count: grandparent_counts.get(&chara_id).copied().unwrap_or(0),
+                avg_parent_rank: (ranked > 0).then(|| rank_sum / ranked as f64),
+                cards,
+            }
+        })
+        .collect();
+    characters.sort_by(|a, b| {
+        b.main_count
+            .cmp(&a.main_count)
+            .then(b.grandparent_count.cmp(&a.grandparent_count))
+            .then(a.chara_id.cmp(&b.chara_id))
+    });
+
+    let response = CharacterStatsResponse {
+        total_records,
+        characters,
+    };
+
+    Ok(Json(response))
+}
+
+/// Team stadium members matching the stats filters: $1 team class, $2 character
+/// or card ID, $3 whether $2 is a base character
+const TEAM_STADIUM_STATS_MEMBERS: &str = r#"
+    WITH members AS (
+        SELECT card_id, distance_type, team_class, rank_score
+        FROM team_stadium
+        WHERE ($1::int IS NULL OR team_class = $1)
+          AND ($2::int IS NULL OR CASE WHEN $3 THEN card_id / 100 ELSE card_id END = $2)
+    )
+"#;
+
+/// GET /api/stats/team-stadium - Character usage in trainers' team stadium teams
+///
+/// Parameters:
+/// - team_class: Only teams of this class (1-6)
+/// - chara_id: Base character (1007) or a specific card (100701)
+///
+/// Returns usage counts and average rank scores per character, per distance
+/// and per team class. Cached for 1 hour.
+pub async fn get_team_stadium_stats(
+    State(state): State<AppState>,
+    Query(params): Query<TeamStadiumStatsParams>,
+) -> Result<Json<TeamStadiumStatsResponse>, AppError> {
+    params.validate()?;
+
+    let chara_id = params.chara_id.map(|chara_id| chara_id.0);
+    let is_base = params
+        .chara_id
+        .is_some_and(|chara_id| chara_id.base() == chara_id);
+
+    let mut characters = sqlx::query_as::<_, TeamStadiumCharacterStats>(&format!(
+        r#"{}
+        SELECT
+            card_id / 100 AS chara_id,
+            COUNT(*) AS usage_count,
+            AVG(rank_score)::float8 AS avg_rank_score
+        FROM members
+        GROUP BY 1
+        ORDER BY usage_count DESC, chara_id
+        "#,
+        TEAM_STADIUM_STATS_MEMBERS
+    ))
+    .bind(params.team_class)
+    .bind(chara_id)
+    .bind(is_base)
+    .fetch_all(state.read_db())
+    .await?;
+    for character in &mut characters {
+        character.name = crate::characters::character_name(character.chara_id);
+    }
+
+    let distances = sqlx::query_as::<_, TeamStadiumBreakdown>(&format!(
+        r#"{}
+        SELECT
+            distance_type AS key,
+            COUNT(*) AS usage_count,
+            AVG(rank_score)::float8 AS avg_rank_score
+        FROM members
+        GROUP BY 1
+        ORDER BY 1
+        "#,
+        TEAM_STADIUM_STATS_MEMBERS
+    ))
+    .bind(params.team_class)
+    .bind(chara_id)
+    .bind(is_base)
+    .fetch_all(state.read_db())
+    .await?;
+
+    let classes = sqlx::query_as::<_, TeamStadiumBreakdown>(&format!(
+        r#"{}
+        SELECT
+            team_class AS key,
+            COUNT(*) AS usage_count,
+            AVG(rank_score)::float8 AS avg_rank_score
+        FROM members
+        WHERE team_class IS NOT NULL
+        GROUP BY 1
+        ORDER BY 1
+        "#,
+        TEAM_STADIUM_STATS_MEMBERS
+    ))
+    .bind(params.team_class)
+    .bind(chara_id)
+    .bind(is_base)
+    .fetch_all(state.read_db())
+    .await?;
+
+    Ok(Json(TeamStadiumStatsResponse {
+        total_members: distances.iter().map(|distance| distance.usage_count).sum(),
+        characters,
+        distances,
+        classes,
+    }))
+}
+
+/// GET /api/stats/uploads - Newly tracked inheritance and support card rows over time
+///
+/// Parameters:
+/// - granularity: "day" (default) or "week"
+/// - buckets: Number of buckets to return, ending with the current one
+///   (default 30 days / 12 weeks, max 365)
+///
+/// Rows tracked before created_at existed have no creation time and are not counted.
+/// Cached for 10 minutes.
+pub async fn get_upload_stats(
+    State(state): State<AppState>,
+    Query(params): Query<UploadStatsParams>,
+) -> Result<Json<UploadStatsResponse>, AppError> {
+    params.validate()?;
+
+    let (granularity, default_buckets) = match params.granularity.as_deref() {
+        None | Some("day") => ("day", 30),
+        Some("week") => ("week", 12),
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid granularity '{}' (expected day or week)",
+                other
+            )))
+        }
     };
+    let bucket_count = params.buckets.unwrap_or(default_buckets);
 
-    // Cache for 1 hour
-    let _ = crate::cache::set(cache_key, &response, std::time::Duration::from_secs(3600));
+    // $1 is the date_trunc field and doubles as the interval unit
+    let buckets = sqlx::query_as::<_, UploadBucket>(
+        r#"
+        WITH series AS (
+            SELECT generate_series(
+                date_trunc($1, LOCALTIMESTAMP) - ($2 - 1) * ('1 ' || $1)::interval,
+                date_trunc($1, LOCALTIMESTAMP),
+                ('1 ' || $1)::interval
+            ) AS bucket_start
+        ),
+        inheritance_counts AS (
+            SELECT date_trunc($1, created_at) AS bucket_start, COUNT(*) AS uploads
+            FROM inheritance
+            WHERE created_at >= (SELECT MIN(bucket_start) FROM series)
+            GROUP BY 1
+        ),
+        support_card_counts AS (
+            SELECT date_trunc($1, created_at) AS bucket_start, COUNT(*) AS uploads
+            FROM support_card
+            WHERE created_at >= (SELECT MIN(bucket_start) FROM series)
+            GROUP BY 1
+        )
+        SELECT
+            s.bucket_start::date AS bucket_start,
+            COALESCE(i.uploads, 0) AS inheritance,
+            COALESCE(sc.uploads, 0) AS support_cards
+        FROM series s
+        LEFT JOIN inheritance_counts i ON i.bucket_start = s.bucket_start
+        LEFT JOIN support_card_counts sc ON sc.bucket_start = s.bucket_start
+        ORDER BY s.bucket_start
+        "#,
+    )
+    .bind(granularity)
+    .bind(bucket_count)
+    .fetch_all(state.read_db())
+    .await?;
+
+    let response = UploadStatsResponse {
+        granularity: granularity.to_string(),
+        buckets,
+    };
 
     Ok(Json(response))
 }
 
+/// How often the live stats socket pushes a snapshot
+const LIVE_STATS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Open live stats sockets per server, so idle dashboards can't pile up
+const LIVE_STATS_MAX_CONNECTIONS: usize = 500;
+
+static LIVE_STATS_CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// GET /api/stats/live - WebSocket pushing rolling per-minute counters
+///
+/// Sends a LiveStats JSON message right away and then every 5 seconds.
+/// Counters are per server instance and not persisted.
+pub async fn live_stats(ws: WebSocketUpgrade) -> Result<Response, AppError> {
+    if LIVE_STATS_CONNECTIONS.fetch_add(1, Ordering::AcqRel) >= LIVE_STATS_MAX_CONNECTIONS {
+        LIVE_STATS_CONNECTIONS.fetch_sub(1, Ordering::AcqRel);
+        return Err(AppError::rate_limited("Too many live stats connections"));
+    }
+
+    Ok(ws.on_upgrade(|socket| async {
+        push_live_stats(socket).await;
+        LIVE_STATS_CONNECTIONS.fetch_sub(1, Ordering::AcqRel);
+    }))
+}
+
+async fn push_live_stats(mut socket: WebSocket) {
+    let mut interval = tokio::time::interval(LIVE_STATS_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let message = match serde_json::to_string(&crate::live_stats::snapshot()) {
+                    Ok(json) => Message::Text(json),
+                    Err(e) => {
+                        tracing::error!("❌ Failed to serialize live stats: {}", e);
+                        return;
+                    }
+                };
+                if socket.send(message).await.is_err() {
+                    return;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                // Clients only listen; anything but a close is ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+            _ = crate::shutdown::requested() => {
+                let _ = socket.send(Message::Close(None)).await;
+                return;
+            }
+        }
+    }
+}
+
 pub async fn get_daily_stats(
     State(_state): State<AppState>,
     Query(_params): Query<HashMap<String, String>>,
@@ -164,16 +553,125 @@ pub async fn get_today_stats_endpoint(
     }))
 }
 
+// Reports newer than the trainer's last refresh; older ones were already handled
+const REPORTS_SINCE_UPDATE_SQL: &str = r#"
+    SELECT COUNT(*)
+    FROM friendlist_reports r
+    LEFT JOIN trainer t ON t.account_id = r.trainer_id
+    WHERE r.trainer_id = $1
+      AND (t.last_updated IS NULL OR r.reported_at > t.last_updated)
+"#;
+
+/// POST /api/stats/friendlist/{trainer_id} - Report a trainer's friend list as full
+///
+/// Reports are deduplicated per client IP (a repeat counts again after 24 hours).
+/// Once enough distinct reports arrived since the trainer was last refreshed,
+/// a friend/recheck task is queued unless a lookup is already pending.
 pub async fn report_friendlist_full(
-    State(_state): State<AppState>,
-    Path(_record_id): Path<String>,
+    State(state): State<AppState>,
+    Path(trainer_id): Path<String>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
 ) -> Result<Json<FriendlistReportResponse>, AppError> {
-    let _ip_address = addr.ip();
+    let trainer_id = trainer_id.trim();
+    if !is_valid_trainer_id(trainer_id) {
+        return Err(AppError::BadRequest("Invalid trainer ID format".to_string()));
+    }
+
+    let client_ip = crate::middleware::turnstile::extract_client_ip(&headers, addr);
+    let reporter_hash = hex::encode(Sha256::digest(client_ip.as_bytes()));
+
+    let recorded = sqlx::query(
+        r#"
+        INSERT INTO friendlist_reports (trainer_id, reporter_hash)
+        VALUES ($1, $2)
+        ON CONFLICT (trainer_id, reporter_hash) DO UPDATE SET reported_at = CURRENT_TIMESTAMP
+        WHERE friendlist_reports.reported_at < CURRENT_TIMESTAMP - interval '24 hours'
+        "#,
+    )
+    .bind(trainer_id)
+    .bind(&reporter_hash)
+    .execute(&state.db)
+    .await?
+    .rows_affected()
+        > 0;
+
+    let report_count = sqlx::query_scalar::<_, i64>(REPORTS_SINCE_UPDATE_SQL)
+        .bind(trainer_id)
+        .fetch_one(&state.db)
+        .await?;
+
+    // Only the report that reaches the threshold queues the recheck
+    let mut recheck_queued = false;
+    if recorded && report_count == state.config.friendlist_report_threshold {
+        let pending = match find_pending_task(&state.db, "friend/recheck", "id", trainer_id).await? {
+            Some(task_id) => Some(task_id),
+            None => find_pending_task(&state.db, "friend/search", "id", trainer_id).await?,
+        };
+
+        if pending.is_none() {
+            sqlx::query(
+                r#"
+                INSERT INTO tasks (task_type, task_data, priority, status, created_at)
+                VALUES ($1, $2, $3, 'pending', CURRENT_TIMESTAMP)
+                "#,
+            )
+            .bind("friend/recheck")
+            .bind(json!({
+                "id": trainer_id,
+                "action": "recheck",
+                "reason": "friendlist_reports"
+            }))
+            .bind(PRIORITY_RECHECK)
+            .execute(&state.db)
+            .await?;
+
+            tracing::info!(
+                "📋 Queued recheck for trainer {} after {} friendlist-full reports",
+                trainer_id,
+                report_count
+            );
+            recheck_queued = true;
+        }
+    }
 
-    // For now, just return success without database operations
     Ok(Json(FriendlistReportResponse {
         success: true,
-        message: "Report submitted successfully".to_string(),
+        message: if recorded {
+            "Report submitted successfully".to_string()
+        } else {
+            "Already reported recently".to_string()
+        },
+        report_count,
+        recheck_queued,
     }))
 }
+
+/// GET /api/stats/friendlist-reports/{trainer_id} - Friendlist-full report counts for a trainer
+pub async fn get_friendlist_reports(
+    State(state): State<AppState>,
+    Path(trainer_id): Path<String>,
+) -> Result<Json<FriendlistReportStats>, AppError> {
+    let trainer_id = trainer_id.trim();
+    if !is_valid_trainer_id(trainer_id) {
+        return Err(AppError::BadRequest("Invalid trainer ID format".to_string()));
+    }
+
+    let stats = sqlx::query_as::<_, FriendlistReportStats>(&format!(
+        r#"
+        SELECT
+            $1 AS trainer_id,
+            (SELECT COUNT(*) FROM friendlist_reports WHERE trainer_id = $1) AS total_reports,
+            ({}) AS reports_since_update,
+            (SELECT MAX(reported_at) FROM friendlist_reports WHERE trainer_id = $1) AS last_reported_at,
+            $2 AS recheck_threshold
+        "#,
+        REPORTS_SINCE_UPDATE_SQL
+    ))
+    .bind(trainer_id)
+    .bind(state.config.friendlist_report_threshold)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(stats))
+}
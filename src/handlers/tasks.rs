@@ -1,51 +1,337 @@
 use axum::{
-    extract::{Path, State},
-    response::Json,
+    extract::{ConnectInfo, Path, Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::net::SocketAddr;
 use validator::Validate;
 
-use crate::errors::AppError;
-use crate::models::{CreateTaskRequest, TaskResponse, TrainerSubmissionRequest};
+use crate::errors::{AppError, FieldError};
+use crate::handlers::privacy::deleted_accounts;
+use crate::journal::{self, JournalTask};
+use crate::middleware::turnstile::require_turnstile;
+use crate::models::{
+    AccountId, BatchSubmissionResponse, BatchSubmissionResult, BatchSubmissionStatus, CreateTaskRequest,
+    DryRunParams, DryRunResponse, RecordProvenance, SupportCardProvenance, TaskArchiveResult, TaskEvent, TaskOutcome,
+    TaskReapResult, TaskResponse, TrainerClaimRequest, TrainerClaimResponse, TrainerClaimVerificationResponse,
+    TrainerBatchSubmissionRequest, TrainerProvenance, TrainerSubmissionRequest, PRIORITY_BATCH_SUBMISSION,
+    PRIORITY_CLAIMED_REFRESH, PRIORITY_FORCED_UPDATE, PRIORITY_RECHECK, PRIORITY_SUBMISSION,
+};
 use crate::AppState;
 
+// Minimum time between two claimed refreshes of the same trainer
+const CLAIMED_REFRESH_COOLDOWN_SECS: f64 = 300.0;
+
+/// How long a trainer has to put the claim code into their comment
+const CLAIM_VERIFICATION_TTL_HOURS: i32 = 24;
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/submit", post(submit_trainer_id))
+        .route("/submit-batch", post(submit_trainer_batch))
         .route("/task", post(create_task))
         .route(
             "/report-unavailable/:trainer_id",
             post(report_trainer_unavailable),
         )
+        .route("/claims/:trainer_id", post(issue_claim))
+        .route("/claims/:trainer_id/verification", post(request_claim_verification))
+        .route("/claimed-refresh/:trainer_id", post(claimed_refresh))
         .route("/track-copy/:trainer_id", post(track_trainer_copy))
         .route("/trainer/:trainer_id/status", get(get_trainer_status))
+        .route("/:task_id", get(get_task))
+        .route("/:task_id/history", get(get_task_history))
+}
+
+/// Find a pending task of `task_type` whose `task_data->>key` equals `value`
+pub(crate) async fn find_pending_task(
+    pool: &PgPool,
+    task_type: &str,
+    key: &str,
+    value: &str,
+) -> Result<Option<i32>, AppError> {
+    let task_id = sqlx::query_scalar::<_, i32>(
+        r#"
+        SELECT id FROM tasks
+        WHERE task_type = $1 AND status = 'pending' AND task_data->>$2 = $3
+        ORDER BY priority ASC, created_at ASC
+        LIMIT 1
+        "#,
+    )
+    .bind(task_type)
+    .bind(key)
+    .bind(value)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(task_id)
+}
+
+/// Attribute task status changes made in this transaction to `actor` in task_events
+pub(crate) async fn set_task_actor(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    actor: &str,
+) -> Result<(), AppError> {
+    sqlx::query("SELECT set_config('umamoe.actor', $1, true)")
+        .bind(actor)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Return tasks stuck in claimed/processing past their lease to pending
+///
+/// Each reaped task has its attempts incremented; tasks that reach
+/// TASK_MAX_ATTEMPTS (default 5) are marked failed instead of re-queued.
+/// The lease duration comes from TASK_LEASE_SECS (default 900).
+pub(crate) async fn reap_stale_tasks(pool: &PgPool) -> Result<TaskReapResult, AppError> {
+    let lease_secs = crate::config::get().task_lease_secs;
+    let max_attempts = crate::config::get().task_max_attempts;
+
+    let mut tx = pool.begin().await?;
+    set_task_actor(&mut tx, "reaper").await?;
+
+    let statuses = sqlx::query_scalar::<_, String>(
+        r#"
+        WITH stale AS (
+            SELECT id FROM tasks
+            WHERE status IN ('claimed', 'processing')
+              AND COALESCE(claimed_at, updated_at, created_at)
+                  < (CURRENT_TIMESTAMP - make_interval(secs => $1))::timestamp
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE tasks t SET
+            attempts = t.attempts + 1,
+            status = CASE WHEN t.attempts + 1 >= $2 THEN 'failed' ELSE 'pending' END,
+            error_message = CASE
+                WHEN t.attempts + 1 >= $2 THEN 'Lease expired after ' || (t.attempts + 1) || ' attempts'
+                ELSE t.error_message
+            END,
+            worker_id = NULL,
+            claimed_at = NULL,
+            updated_at = CURRENT_TIMESTAMP
+        FROM stale
+        WHERE t.id = stale.id
+        RETURNING t.status
+        "#,
+    )
+    .bind(lease_secs)
+    .bind(max_attempts)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let mut result = TaskReapResult::default();
+    for status in statuses {
+        if status == "failed" {
+            result.failed += 1;
+        } else {
+            result.requeued += 1;
+        }
+    }
+
+    Ok(result)
+}
+
+// Completed tasks moved to the archive per statement
+const ARCHIVE_BATCH_SIZE: i64 = 5000;
+
+/// Move completed tasks to tasks_archive so the tasks table only holds live rows
+///
+/// Tasks completed more than TASK_ARCHIVE_AFTER_HOURS (default 24) ago are moved
+/// in batches. Their task_events stay where they are.
+pub(crate) async fn archive_completed_tasks(pool: &PgPool) -> Result<TaskArchiveResult, AppError> {
+    let after_hours = crate::config::get().task_archive_after_hours;
+
+    let mut result = TaskArchiveResult::default();
+    loop {
+        let moved = sqlx::query(
+            r#"
+            WITH done AS (
+                SELECT id FROM tasks
+                WHERE status = 'completed'
+                  AND COALESCE(updated_at, created_at)
+                      < (CURRENT_TIMESTAMP - make_interval(hours => $1))::timestamp
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            ),
+            moved AS (
+                DELETE FROM tasks t
+                USING done
+                WHERE t.id = done.id
+                RETURNING t.id, t.task_type, t.task_data, t.priority, t.status, t.created_at,
+                          t.updated_at, t.worker_id, t.error_message, t.account_id, t.attempts
+            )
+            INSERT INTO tasks_archive (id, task_type, task_data, priority, status, created_at,
+                                       updated_at, worker_id, error_message, account_id, attempts)
+            SELECT * FROM moved
+            "#,
+        )
+        .bind(after_hours)
+        .bind(ARCHIVE_BATCH_SIZE)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+        result.archived += moved;
+        if moved < ARCHIVE_BATCH_SIZE as u64 {
+            break;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Check task_data against the registered schema for its task type
+///
+/// Rejects task types missing from (or disabled in) the task_types catalog.
+async fn validate_task_data(
+    pool: &PgPool,
+    task_type: &str,
+    task_data: &serde_json::Value,
+) -> Result<(), AppError> {
+    let schema = sqlx::query_scalar::<_, serde_json::Value>(
+        "SELECT payload_schema FROM task_types WHERE task_type = $1 AND enabled",
+    )
+    .bind(task_type)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::invalid_field("task_type", format!("unknown task type '{}'", task_type))
+    })?;
+
+    let validator = jsonschema::validator_for(&schema).map_err(|e| {
+        tracing::error!("Invalid payload schema for task type {}: {}", task_type, e);
+        AppError::DatabaseError(format!("Invalid payload schema for task type {}", task_type))
+    })?;
+
+    // Each failure is reported against its JSON pointer, e.g. "task_data/id"
+    let errors: Vec<FieldError> = validator
+        .iter_errors(task_data)
+        .take(5)
+        .map(|e| FieldError::new(format!("task_data{}", e.instance_path), e.to_string()))
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(errors.into());
+    }
+
+    Ok(())
+}
+
+/// Trainer IDs are 9-12 ASCII digits
+pub(crate) fn is_valid_trainer_id(trainer_id: &str) -> bool {
+    (9..=12).contains(&trainer_id.len()) && trainer_id.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The trimmed trainer ID from a path, or a trainer_id field error
+fn check_trainer_id(trainer_id: &str) -> Result<&str, AppError> {
+    let trainer_id = trainer_id.trim();
+    if !is_valid_trainer_id(trainer_id) {
+        return Err(AppError::invalid_field("trainer_id", "must be 9-12 digits"));
+    }
+    Ok(trainer_id)
+}
+
+/// Build the dry-run report for a task-creating endpoint
+pub(crate) fn dry_run_response(
+    outcome: TaskOutcome,
+    task_type: &str,
+    existing_task_id: Option<i32>,
+) -> Response {
+    let message = match outcome {
+        TaskOutcome::TaskCreated => "A new task would be created",
+        TaskOutcome::Duplicate => "A matching task is already pending",
+        TaskOutcome::Throttled => "This request is on cooldown and would be rejected",
+    };
+
+    Json(DryRunResponse {
+        dry_run: true,
+        outcome,
+        task_type: task_type.to_string(),
+        existing_task_id,
+        message: message.to_string(),
+    })
+    .into_response()
 }
 
 /// Submit a trainer ID for friend search task
+///
+/// If a search for this trainer is already pending, the existing task is returned
+/// instead of creating a new one.
 async fn submit_trainer_id(
     State(state): State<AppState>,
+    Query(dry_run): Query<DryRunParams>,
     Json(payload): Json<TrainerSubmissionRequest>,
-) -> Result<Json<TaskResponse>, AppError> {
-    // Validate trainer ID format (9-12 digits)
-    let trainer_id = payload.trainer_id.trim();
-    if trainer_id.is_empty()
-        || !trainer_id.chars().all(|c| c.is_ascii_digit())
-        || trainer_id.len() < 9
-        || trainer_id.len() > 12
-    {
+) -> Result<Response, AppError> {
+    payload.validate()?;
+    let trainer_id = payload.trainer_id.as_str().trim();
+
+    if !deleted_accounts(&state.db, vec![trainer_id.to_string()]).await?.is_empty() {
         return Err(AppError::BadRequest(
-            "Invalid trainer ID format. Must be 9-12 digits.".to_string(),
+            "This trainer's data was deleted on request and isn't collected anymore".to_string(),
         ));
     }
 
+    let existing_task_id = find_pending_task(&state.db, "friend/search", "id", trainer_id).await?;
+
+    if dry_run.dry_run {
+        let outcome = if existing_task_id.is_some() {
+            TaskOutcome::Duplicate
+        } else {
+            TaskOutcome::TaskCreated
+        };
+        return Ok(dry_run_response(outcome, "friend/search", existing_task_id));
+    }
+
+    if let Some(task_id) = existing_task_id {
+        let task = sqlx::query_as::<_, crate::models::Task>(
+            r#"
+            SELECT id, task_type, task_data, priority, status, created_at, updated_at, worker_id, error_message, account_id
+            FROM tasks
+            WHERE id = $1 AND status = 'pending'
+            "#,
+        )
+        .bind(task_id)
+        .fetch_optional(&state.db)
+        .await?;
+
+        // The task may have been claimed in the meantime - fall through and create a new one
+        if let Some(task) = task {
+            return Ok(Json(TaskResponse {
+                id: task.id,
+                task_type: task.task_type,
+                task_data: task.task_data,
+                priority: task.priority,
+                status: task.status,
+                account_id: task.account_id,
+                created_at: task.created_at,
+                updated_at: task.updated_at,
+            })
+            .into_response());
+        }
+    }
+
     // Create task data
     let task_data = json!({
         "id": trainer_id,
         "action": "search"
     });
 
+    let journal_entry = journal::begin(vec![JournalTask {
+        task_type: "friend/search".to_string(),
+        task_data: task_data.clone(),
+        priority: PRIORITY_SUBMISSION,
+        account_id: None,
+    }])
+    .await;
+
     // Insert task into database
     let task = sqlx::query_as::<_, crate::models::Task>(
         r#"
@@ -56,7 +342,7 @@ async fn submit_trainer_id(
     )
     .bind("friend/search")
     .bind(task_data)
-    .bind(1i32)
+    .bind(PRIORITY_SUBMISSION)
     .bind(None::<String>)
     .fetch_one(&state.db)
     .await
@@ -65,6 +351,9 @@ async fn submit_trainer_id(
         AppError::DatabaseError("Failed to create task".to_string())
     })?;
 
+    journal_entry.commit();
+    crate::live_stats::NEW_TRAINERS.record(1);
+
     Ok(Json(TaskResponse {
         id: task.id,
         task_type: task.task_type,
@@ -74,6 +363,139 @@ async fn submit_trainer_id(
         account_id: task.account_id,
         created_at: task.created_at,
         updated_at: task.updated_at,
+    })
+    .into_response())
+}
+
+/// POST /api/tasks/submit-batch - Submit many trainer IDs for friend search at once
+///
+/// Each ID is validated and checked against known trainers and pending searches;
+/// only new IDs get a task. Returns a status for every submitted ID, in order.
+async fn submit_trainer_batch(
+    State(state): State<AppState>,
+    Query(dry_run): Query<DryRunParams>,
+    Json(payload): Json<TrainerBatchSubmissionRequest>,
+) -> Result<Json<BatchSubmissionResponse>, AppError> {
+    payload.validate()?;
+
+    let mut seen = std::collections::HashSet::new();
+    let mut results: Vec<BatchSubmissionResult> = payload
+        .trainer_ids
+        .iter()
+        .map(|raw| {
+            let trainer_id = raw.as_str().trim().to_string();
+            let status = if !is_valid_trainer_id(&trainer_id) {
+                BatchSubmissionStatus::Invalid
+            } else if !seen.insert(trainer_id.clone()) {
+                BatchSubmissionStatus::DuplicateInBatch
+            } else {
+                BatchSubmissionStatus::Queued
+            };
+            BatchSubmissionResult {
+                trainer_id: AccountId(trainer_id),
+                status,
+                task_id: None,
+            }
+        })
+        .collect();
+
+    let candidates: Vec<String> = seen.into_iter().collect();
+
+    let deleted = deleted_accounts(&state.db, candidates.clone()).await?;
+
+    let known: std::collections::HashSet<String> = sqlx::query_scalar::<_, String>(
+        "SELECT account_id FROM trainer WHERE account_id = ANY($1)",
+    )
+    .bind(&candidates)
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .collect();
+
+    let pending: std::collections::HashMap<String, i32> = sqlx::query_as::<_, (String, i32)>(
+        r#"
+        SELECT DISTINCT ON (task_data->>'id') task_data->>'id', id
+        FROM tasks
+        WHERE task_type = 'friend/search' AND status = 'pending' AND task_data->>'id' = ANY($1)
+        ORDER BY task_data->>'id', id
+        "#,
+    )
+    .bind(&candidates)
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .collect();
+
+    for result in results.iter_mut() {
+        if result.status != BatchSubmissionStatus::Queued {
+            continue;
+        }
+        let trainer_id = result.trainer_id.as_str();
+        if deleted.contains(trainer_id) {
+            result.status = BatchSubmissionStatus::Deleted;
+        } else if let Some(task_id) = pending.get(trainer_id) {
+            result.status = BatchSubmissionStatus::AlreadyPending;
+            result.task_id = Some(*task_id);
+        } else if known.contains(trainer_id) {
+            result.status = BatchSubmissionStatus::AlreadyKnown;
+        }
+    }
+
+    let to_queue: Vec<String> = results
+        .iter()
+        .filter(|r| r.status == BatchSubmissionStatus::Queued)
+        .map(|r| r.trainer_id.0.clone())
+        .collect();
+
+    if !dry_run.dry_run && !to_queue.is_empty() {
+        let journal_entry = journal::begin(
+            to_queue
+                .iter()
+                .map(|trainer_id| JournalTask {
+                    task_type: "friend/search".to_string(),
+                    task_data: json!({ "id": trainer_id, "action": "search" }),
+                    priority: PRIORITY_BATCH_SUBMISSION,
+                    account_id: None,
+                })
+                .collect(),
+        )
+        .await;
+
+        let created: std::collections::HashMap<String, i32> = sqlx::query_as::<_, (String, i32)>(
+            r#"
+            INSERT INTO tasks (task_type, task_data, priority, status, created_at)
+            SELECT 'friend/search', jsonb_build_object('id', trainer_id, 'action', 'search'), $2, 'pending', CURRENT_TIMESTAMP
+            FROM UNNEST($1::text[]) AS trainer_id
+            RETURNING task_data->>'id', id
+            "#,
+        )
+        .bind(&to_queue)
+        .bind(PRIORITY_BATCH_SUBMISSION)
+        .fetch_all(&state.db)
+        .await?
+        .into_iter()
+        .collect();
+
+        journal_entry.commit();
+        crate::live_stats::NEW_TRAINERS.record(created.len() as u64);
+
+        for result in results.iter_mut() {
+            if result.status == BatchSubmissionStatus::Queued {
+                result.task_id = created.get(result.trainer_id.as_str()).copied();
+            }
+        }
+
+        tracing::info!(
+            "📦 Batch submission: queued {} of {} trainer IDs",
+            created.len(),
+            results.len()
+        );
+    }
+
+    Ok(Json(BatchSubmissionResponse {
+        dry_run: dry_run.dry_run,
+        queued: to_queue.len(),
+        results,
     }))
 }
 
@@ -83,12 +505,20 @@ async fn create_task(
     Json(payload): Json<CreateTaskRequest>,
 ) -> Result<Json<TaskResponse>, AppError> {
     // Validate the request
-    payload
-        .validate()
-        .map_err(|e| AppError::BadRequest(format!("Validation error: {}", e)))?;
+    payload.validate()?;
+
+    validate_task_data(&state.db, &payload.task_type, &payload.task_data).await?;
 
     let priority = payload.priority.unwrap_or(0);
 
+    let journal_entry = journal::begin(vec![JournalTask {
+        task_type: payload.task_type.clone(),
+        task_data: payload.task_data.clone(),
+        priority,
+        account_id: payload.account_id.clone(),
+    }])
+    .await;
+
     // Insert task into database
     let task = sqlx::query_as::<_, crate::models::Task>(
         r#"
@@ -108,6 +538,8 @@ async fn create_task(
         AppError::DatabaseError("Failed to create task".to_string())
     })?;
 
+    journal_entry.commit();
+
     Ok(Json(TaskResponse {
         id: task.id,
         task_type: task.task_type,
@@ -120,28 +552,67 @@ async fn create_task(
     }))
 }
 
+
 /// Report a trainer as unavailable (friend list full) - triggers immediate update
+///
+/// If a search for this trainer is already pending it is bumped to the forced
+/// update priority instead of queueing a second one.
 async fn report_trainer_unavailable(
     State(state): State<AppState>,
     Path(trainer_id): Path<String>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    // Validate trainer ID
-    let trainer_id = trainer_id.trim();
-    if trainer_id.is_empty()
-        || !trainer_id.chars().all(|c| c.is_ascii_digit())
-        || trainer_id.len() < 9
-        || trainer_id.len() > 12
-    {
+    Query(dry_run): Query<DryRunParams>,
+) -> Result<Response, AppError> {
+    let trainer_id = check_trainer_id(&trainer_id)?;
+
+    if !deleted_accounts(&state.db, vec![trainer_id.to_string()]).await?.is_empty() {
         return Err(AppError::BadRequest(
-            "Invalid trainer ID format".to_string(),
+            "This trainer's data was deleted on request and isn't collected anymore".to_string(),
         ));
     }
 
+    let existing_task_id = find_pending_task(&state.db, "friend/search", "id", trainer_id).await?;
+
+    if dry_run.dry_run {
+        let outcome = if existing_task_id.is_some() {
+            TaskOutcome::Duplicate
+        } else {
+            TaskOutcome::TaskCreated
+        };
+        return Ok(dry_run_response(outcome, "friend/search", existing_task_id));
+    }
+
+    if let Some(task_id) = existing_task_id {
+        let bumped = sqlx::query(
+            "UPDATE tasks SET priority = LEAST(priority, $2), updated_at = CURRENT_TIMESTAMP WHERE id = $1 AND status = 'pending'",
+        )
+        .bind(task_id)
+        .bind(PRIORITY_FORCED_UPDATE)
+        .execute(&state.db)
+        .await?;
+
+        if bumped.rows_affected() > 0 {
+            return Ok(Json(json!({
+                "success": true,
+                "task_created": false,
+                "message": "Trainer scheduled for immediate update"
+            }))
+            .into_response());
+        }
+    }
+
     // Create task data for friend search (force update)
     let task_data = json!({
         "id": trainer_id
     });
 
+    let journal_entry = journal::begin(vec![JournalTask {
+        task_type: "friend/search".to_string(),
+        task_data: task_data.clone(),
+        priority: PRIORITY_FORCED_UPDATE,
+        account_id: None,
+    }])
+    .await;
+
     // Create high-priority friend search task
     sqlx::query(
         r#"
@@ -151,7 +622,7 @@ async fn report_trainer_unavailable(
     )
     .bind("friend/search")
     .bind(&task_data)
-    .bind(0i32) // High priority for forced updates
+    .bind(PRIORITY_FORCED_UPDATE)
     .bind(None::<String>)
     .execute(&state.db)
     .await
@@ -160,11 +631,330 @@ async fn report_trainer_unavailable(
         AppError::DatabaseError("Failed to create task".to_string())
     })?;
 
+    journal_entry.commit();
+
     Ok(Json(json!({
         "success": true,
         "task_created": true,
         "message": "Trainer scheduled for immediate update"
-    })))
+    }))
+    .into_response())
+}
+
+/// Mark open claim requests verified whose code appears in the trainer's
+/// freshly scraped comment, returning how many were
+pub(crate) async fn verify_claim_comments(
+    conn: &mut sqlx::PgConnection,
+    comments: &[(String, String)],
+) -> Result<u64, AppError> {
+    if comments.is_empty() {
+        return Ok(0);
+    }
+    let (trainer_ids, comments): (Vec<&str>, Vec<&str>) = comments
+        .iter()
+        .map(|(trainer_id, comment)| (trainer_id.as_str(), comment.as_str()))
+        .unzip();
+
+    let verified = sqlx::query(
+        r#"
+        UPDATE trainer_claim_requests r
+        SET verified_at = CURRENT_TIMESTAMP
+        FROM unnest($1::text[], $2::text[]) AS c(trainer_id, comment)
+        WHERE r.trainer_id = c.trainer_id
+          AND r.verified_at IS NULL
+          AND r.expires_at > CURRENT_TIMESTAMP
+          AND strpos(upper(c.comment), r.code) > 0
+        "#,
+    )
+    .bind(trainer_ids)
+    .bind(comments)
+    .execute(conn)
+    .await?
+    .rows_affected();
+    Ok(verified)
+}
+
+/// POST /api/tasks/claims/:trainer_id/verification - Start claiming a trainer
+///
+/// Requires a Turnstile token. Returns a code to put into the trainer's
+/// in-game comment and schedules a refresh of the trainer's profile; once a
+/// worker has seen the code there, POST /api/tasks/claims/:trainer_id with
+/// the code issues the claim token. Asking again while a request is open
+/// returns the same code.
+async fn request_claim_verification(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(trainer_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<TrainerClaimVerificationResponse>, AppError> {
+    let trainer_id = check_trainer_id(&trainer_id)?;
+    require_turnstile(&state, &headers, addr).await?;
+
+    if !deleted_accounts(&state.db, vec![trainer_id.to_string()]).await?.is_empty() {
+        return Err(AppError::BadRequest(
+            "This trainer's data was deleted on request and isn't collected anymore".to_string(),
+        ));
+    }
+
+    let code = format!(
+        "UMA-{}",
+        uuid::Uuid::new_v4().simple().to_string()[..8].to_uppercase()
+    );
+    // An open request keeps its code, so a reload doesn't invalidate the comment
+    let (code, expires_at, verified) = sqlx::query_as::<_, (String, chrono::NaiveDateTime, bool)>(
+        r#"
+        INSERT INTO trainer_claim_requests (trainer_id, code, expires_at)
+        VALUES ($1, $2, CURRENT_TIMESTAMP + make_interval(hours => $3))
+        ON CONFLICT (trainer_id) DO UPDATE SET
+            code = CASE WHEN trainer_claim_requests.expires_at > CURRENT_TIMESTAMP
+                        THEN trainer_claim_requests.code ELSE EXCLUDED.code END,
+            verified_at = CASE WHEN trainer_claim_requests.expires_at > CURRENT_TIMESTAMP
+                               THEN trainer_claim_requests.verified_at END,
+            requested_at = CURRENT_TIMESTAMP,
+            expires_at = GREATEST(trainer_claim_requests.expires_at, EXCLUDED.expires_at)
+        RETURNING code, expires_at, verified_at IS NOT NULL
+        "#,
+    )
+    .bind(trainer_id)
+    .bind(&code)
+    .bind(CLAIM_VERIFICATION_TTL_HOURS)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !verified && find_pending_task(&state.db, "friend/search", "id", trainer_id).await?.is_none() {
+        sqlx::query(
+            r#"
+            INSERT INTO tasks (task_type, task_data, priority, status, created_at)
+            VALUES ('friend/search', $1, $2, 'pending', CURRENT_TIMESTAMP)
+            "#,
+        )
+        .bind(json!({
+            "id": trainer_id,
+            "action": "refresh",
+            "reason": "claim_verification"
+        }))
+        .bind(PRIORITY_FORCED_UPDATE)
+        .execute(&state.db)
+        .await?;
+    }
+
+    let message = if verified {
+        "Ownership verified; send the code to POST /api/tasks/claims/:trainer_id to get the claim token".to_string()
+    } else {
+        format!(
+            "Put {} into your in-game comment. Once your profile has been refreshed, send the code to get your claim token.",
+            code
+        )
+    };
+    Ok(Json(TrainerClaimVerificationResponse {
+        trainer_id: AccountId::from(trainer_id),
+        code,
+        expires_at,
+        message,
+    }))
+}
+
+/// POST /api/tasks/claims/:trainer_id - Issue a claim token for a verified trainer
+///
+/// Requires a Turnstile token and the code from the verification request,
+/// after a worker has seen it in the trainer's comment. The token is returned
+/// once and only its hash is stored; claiming again replaces the previous
+/// token.
+async fn issue_claim(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(trainer_id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<TrainerClaimRequest>,
+) -> Result<Json<TrainerClaimResponse>, AppError> {
+    let trainer_id = check_trainer_id(&trainer_id)?;
+    require_turnstile(&state, &headers, addr).await?;
+
+    let mut tx = state.db.begin().await?;
+    let request = sqlx::query_as::<_, (String, bool, bool)>(
+        r#"
+        SELECT code, verified_at IS NOT NULL, expires_at > CURRENT_TIMESTAMP
+        FROM trainer_claim_requests
+        WHERE trainer_id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(trainer_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((code, verified, open)) = request else {
+        return Err(AppError::NotFound(
+            "No claim request for this trainer; request a code first".to_string(),
+        ));
+    };
+    if !code.eq_ignore_ascii_case(payload.code.trim()) {
+        return Err(AppError::Unauthorized("Invalid claim code".to_string()));
+    }
+    if !verified && !open {
+        return Err(AppError::BadRequest(
+            "The claim code expired; request a new one".to_string(),
+        ));
+    }
+    if !verified {
+        return Err(AppError::Unauthorized(
+            "Ownership not verified yet: the code hasn't been seen in the trainer's comment".to_string(),
+        ));
+    }
+
+    let claim_token = format!("umc_{}", uuid::Uuid::new_v4().simple());
+    let token_hash = hex::encode(Sha256::digest(claim_token.as_bytes()));
+    let verified_at = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
+        r#"
+        INSERT INTO trainer_claims (trainer_id, claim_token_hash)
+        VALUES ($1, $2)
+        ON CONFLICT (trainer_id) DO UPDATE SET
+            claim_token_hash = EXCLUDED.claim_token_hash,
+            verified_at = CURRENT_TIMESTAMP
+        RETURNING verified_at
+        "#,
+    )
+    .bind(trainer_id)
+    .bind(&token_hash)
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM trainer_claim_requests WHERE trainer_id = $1")
+        .bind(trainer_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    tracing::info!("🔑 Issued claim for trainer {}", trainer_id);
+    Ok(Json(TrainerClaimResponse {
+        trainer_id: AccountId::from(trainer_id),
+        claim_token,
+        verified_at,
+    }))
+}
+
+/// Refresh requested by the verified owner of a trainer ("my list just opened")
+///
+/// Requires the claim token in the `X-Claim-Token` header. These tasks use the
+/// claimed refresh priority so they are picked up before anonymous rechecks,
+/// but each trainer can only trigger one every few minutes.
+async fn claimed_refresh(
+    State(state): State<AppState>,
+    Path(trainer_id): Path<String>,
+    Query(dry_run): Query<DryRunParams>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let trainer_id = check_trainer_id(&trainer_id)?;
+
+    let claim_token = headers
+        .get("X-Claim-Token")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| AppError::Unauthorized("Missing claim token".to_string()))?;
+    let token_hash = hex::encode(Sha256::digest(claim_token.as_bytes()));
+
+    if dry_run.dry_run {
+        let on_cooldown = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT last_refresh_at IS NOT NULL
+               AND last_refresh_at >= CURRENT_TIMESTAMP - make_interval(secs => $3)
+            FROM trainer_claims
+            WHERE trainer_id = $1 AND claim_token_hash = $2
+            "#,
+        )
+        .bind(trainer_id)
+        .bind(&token_hash)
+        .bind(CLAIMED_REFRESH_COOLDOWN_SECS)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid claim token for this trainer".to_string()))?;
+
+        let outcome = if on_cooldown {
+            TaskOutcome::Throttled
+        } else {
+            TaskOutcome::TaskCreated
+        };
+        return Ok(dry_run_response(outcome, "friend/search", None));
+    }
+
+    let task_data = json!({
+        "id": trainer_id,
+        "action": "refresh",
+        "reason": "claimed_refresh"
+    });
+
+    // Verify the claim and take the cooldown slot in one statement so
+    // concurrent requests can't both get through. The slot and the task are
+    // committed together, so a crash in between doesn't use up the slot.
+    let mut tx = state.db.begin().await?;
+    let accepted = sqlx::query_scalar::<_, String>(
+        r#"
+        UPDATE trainer_claims
+        SET last_refresh_at = CURRENT_TIMESTAMP
+        WHERE trainer_id = $1
+          AND claim_token_hash = $2
+          AND (last_refresh_at IS NULL
+               OR last_refresh_at < CURRENT_TIMESTAMP - make_interval(secs => $3))
+        RETURNING trainer_id
+        "#,
+    )
+    .bind(trainer_id)
+    .bind(&token_hash)
+    .bind(CLAIMED_REFRESH_COOLDOWN_SECS)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if accepted.is_none() {
+        drop(tx);
+        // Work out why the update didn't match
+        let claimed = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM trainer_claims WHERE trainer_id = $1 AND claim_token_hash = $2)",
+        )
+        .bind(trainer_id)
+        .bind(&token_hash)
+        .fetch_one(&state.db)
+        .await?;
+
+        return Err(if claimed {
+            AppError::rate_limited("A refresh for this trainer was requested recently")
+        } else {
+            AppError::Unauthorized("Invalid claim token for this trainer".to_string())
+        });
+    }
+
+    let journal_entry = journal::begin(vec![JournalTask {
+        task_type: "friend/search".to_string(),
+        task_data: task_data.clone(),
+        priority: PRIORITY_CLAIMED_REFRESH,
+        account_id: None,
+    }])
+    .await;
+
+    sqlx::query(
+        r#"
+        INSERT INTO tasks (task_type, task_data, priority, status, created_at)
+        VALUES ($1, $2, $3, 'pending', CURRENT_TIMESTAMP)
+        "#,
+    )
+    .bind("friend/search")
+    .bind(&task_data)
+    .bind(PRIORITY_CLAIMED_REFRESH)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to create task: {}", e);
+        AppError::DatabaseError("Failed to create task".to_string())
+    })?;
+    tx.commit().await?;
+
+    journal_entry.commit();
+
+    Ok(Json(json!({
+        "success": true,
+        "task_created": true,
+        "message": "Trainer scheduled for priority refresh"
+    }))
+    .into_response())
 }
 
 /// Track when a trainer ID is copied (for automatic re-checking)
@@ -172,16 +962,15 @@ async fn track_trainer_copy(
     State(state): State<AppState>,
     Path(trainer_id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    // Validate trainer ID
-    let trainer_id = trainer_id.trim();
-    if trainer_id.is_empty()
-        || !trainer_id.chars().all(|c| c.is_ascii_digit())
-        || trainer_id.len() < 9
-        || trainer_id.len() > 12
-    {
-        return Err(AppError::BadRequest(
-            "Invalid trainer ID format".to_string(),
-        ));
+    let trainer_id = check_trainer_id(&trainer_id)?;
+
+    // Deleted trainers aren't counted, so copies can't queue rechecks for them
+    if !deleted_accounts(&state.db, vec![trainer_id.to_string()]).await?.is_empty() {
+        return Ok(Json(json!({
+            "success": true,
+            "copy_count": 0,
+            "task_created": false
+        })));
     }
 
     // Increment copy count
@@ -224,6 +1013,14 @@ async fn track_trainer_copy(
                 "copy_count": copy_count
             });
 
+            let journal_entry = journal::begin(vec![JournalTask {
+                task_type: "friend/recheck".to_string(),
+                task_data: task_data.clone(),
+                priority: PRIORITY_RECHECK,
+                account_id: None,
+            }])
+            .await;
+
             // Create medium-priority recheck task
             sqlx::query(
                 r#"
@@ -233,9 +1030,11 @@ async fn track_trainer_copy(
             )
             .bind("friend/recheck")
             .bind(&task_data)
-            .bind(5i32) // Medium priority for rechecks
+            .bind(PRIORITY_RECHECK)
             .execute(&state.db)
             .await?;
+
+            journal_entry.commit();
         }
     }
 
@@ -267,12 +1066,18 @@ async fn get_trainer_status(
     .await?;
 
     if let Some((follower_num, status, copy_count)) = status {
+        let provenance = fetch_trainer_provenance(&state.db, &AccountId::from(trainer_id.as_str())).await?;
+
         Ok(Json(json!({
             "trainer_id": trainer_id,
             "available": follower_num.unwrap_or(0) <= 1000,
             "follower_num": follower_num,
             "status": status,
-            "copy_count": copy_count.unwrap_or(0)
+            "copy_count": copy_count.unwrap_or(0),
+            "provenance": {
+                "inheritance": provenance.inheritance,
+                "support_cards": provenance.support_cards
+            }
         })))
     } else {
         Ok(Json(json!({
@@ -283,3 +1088,83 @@ async fn get_trainer_status(
         })))
     }
 }
+
+/// Source and ingestion time of a trainer's stored inheritance and support cards
+pub(crate) async fn fetch_trainer_provenance(
+    pool: &PgPool,
+    account_id: &AccountId,
+) -> Result<TrainerProvenance, AppError> {
+    let inheritance = sqlx::query_as::<_, RecordProvenance>(
+        "SELECT source, ingested_at FROM inheritance WHERE account_id = $1",
+    )
+    .bind(account_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let support_cards = sqlx::query_as::<_, SupportCardProvenance>(
+        r#"
+        SELECT support_card_id, source, ingested_at
+        FROM support_card
+        WHERE account_id = $1
+        ORDER BY support_card_id
+        "#,
+    )
+    .bind(account_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(TrainerProvenance {
+        account_id: account_id.clone(),
+        inheritance,
+        support_cards,
+    })
+}
+
+/// GET /api/tasks/{task_id} - Current state of a task, including archived ones
+async fn get_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<i32>,
+) -> Result<Json<TaskResponse>, AppError> {
+    let task = sqlx::query_as::<_, TaskResponse>(
+        r#"
+        SELECT id, task_type, task_data, priority, status, account_id, created_at, updated_at
+        FROM tasks
+        WHERE id = $1
+        UNION ALL
+        SELECT id, task_type, task_data, priority, status, account_id, created_at, updated_at
+        FROM tasks_archive
+        WHERE id = $1
+        LIMIT 1
+        "#,
+    )
+    .bind(task_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Task {} not found", task_id)))?;
+
+    Ok(Json(task))
+}
+
+/// GET /api/tasks/{task_id}/history - Status transitions of a task, oldest first
+async fn get_task_history(
+    State(state): State<AppState>,
+    Path(task_id): Path<i32>,
+) -> Result<Json<Vec<TaskEvent>>, AppError> {
+    let events = sqlx::query_as::<_, TaskEvent>(
+        r#"
+        SELECT id, task_id, from_status, to_status, worker_id, actor, error_message, created_at
+        FROM task_events
+        WHERE task_id = $1
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(task_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    if events.is_empty() {
+        return Err(AppError::NotFound(format!("No history for task {}", task_id)));
+    }
+
+    Ok(Json(events))
+}
@@ -0,0 +1,242 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+    routing::get,
+    Router,
+};
+use sqlx::{Postgres, QueryBuilder};
+use std::time::Duration;
+
+use crate::errors::AppError;
+use crate::models::{
+    AccountId, SearchResponse, TeamStadiumMember, TeamStadiumSearchParams, TeamStadiumTeam,
+    TrainerTeamsResponse,
+};
+use crate::AppState;
+
+/// Team stadium data only changes when a trainer is scraped again
+const TEAM_STADIUM_CACHE_TTL: Duration = Duration::from_secs(300);
+
+const TEAM_STADIUM_MEMBER_COLUMNS: &str = r#"
+    ts.account_id, COALESCE(t.display_name_override, t.name) AS trainer_name,
+    ts.distance_type, ts.member_id,
+    ts.trained_chara_id, ts.card_id, ts.running_style,
+    ts.speed, ts.stamina, ts.power, ts.guts, ts.wiz,
+    ts.rank_score, ts.skills, ts.updated_at
+"#;
+
+/// Team stadium teams - mounted under /api/team-stadium
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/search",
+            get(search_team_stadium).layer(axum::middleware::from_fn_with_state(
+                TEAM_STADIUM_CACHE_TTL,
+                crate::middleware::response_cache_middleware,
+            )),
+        )
+        .route(
+            "/trainers/:account_id",
+            get(get_trainer_teams).layer(axum::middleware::from_fn_with_state(
+                TEAM_STADIUM_CACHE_TTL,
+                crate::middleware::response_cache_middleware,
+            )),
+        )
+}
+
+fn mask_trainer_name(mut member: TeamStadiumMember) -> TeamStadiumMember {
+    member.trainer_name = member.trainer_name.as_deref().map(crate::moderation::mask_text);
+    member
+}
+
+fn push_search_conditions(
+    query_builder: &mut QueryBuilder<'_, Postgres>,
+    params: &TeamStadiumSearchParams,
+) {
+    query_builder.push(" WHERE TRUE");
+
+    // Stale trainers are left out unless one is asked for directly
+    match &params.trainer_id {
+        Some(trainer_id) => {
+            query_builder.push(" AND ts.account_id = ");
+            query_builder.push_bind(trainer_id.0.clone());
+        }
+        None => {
+            query_builder.push(" AND t.stale IS NOT TRUE");
+        }
+    }
+    if let Some(chara_id) = params.chara_id {
+        // A card variant matches that card, a base ID every card of the character
+        if chara_id.base() == chara_id {
+            query_builder.push(" AND ts.card_id / 100 = ");
+        } else {
+            query_builder.push(" AND ts.card_id = ");
+        }
+        query_builder.push_bind(chara_id.0);
+    }
+    if let Some(distance_type) = params.distance_type {
+        query_builder.push(" AND ts.distance_type = ");
+        query_builder.push_bind(distance_type);
+    }
+    if let Some(running_style) = params.running_style {
+        query_builder.push(" AND ts.running_style = ");
+        query_builder.push_bind(running_style);
+    }
+    if let Some(min_score) = params.min_score {
+        query_builder.push(" AND ts.rank_score >= ");
+        query_builder.push_bind(min_score);
+    }
+    if let Some(max_score) = params.max_score {
+        query_builder.push(" AND ts.rank_score <= ");
+        query_builder.push_bind(max_score);
+    }
+}
+
+/// GET /api/team-stadium/search - Search trained characters in trainers'
+/// team stadium teams
+///
+/// Parameters:
+/// - page: Page number (0-based, default: 0)
+/// - limit: Results per page (max 100, default: 20)
+/// - chara_id: Base character (1007) or a specific card (100701)
+/// - distance_type: 1 = sprint, 2 = mile, 3 = medium, 4 = long, 5 = dirt
+/// - running_style: Running style of the member
+/// - min_score / max_score: Rank score range
+/// - trainer_id: Only this trainer's members (stale trainers are otherwise left out)
+/// - sort_by: rank_score (default) or updated_at
+/// - sort_dir: asc or desc (default: desc)
+pub async fn search_team_stadium(
+    State(state): State<AppState>,
+    Query(params): Query<TeamStadiumSearchParams>,
+) -> Result<Json<SearchResponse<TeamStadiumMember>>, AppError> {
+    let page = params.page.unwrap_or(0).max(0);
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = page * limit;
+
+    if let Some(distance_type) = params.distance_type {
+        if !(1..=5).contains(&distance_type) {
+            return Err(AppError::BadRequest(
+                "distance_type must be between 1 and 5".to_string(),
+            ));
+        }
+    }
+    if let (Some(min_score), Some(max_score)) = (params.min_score, params.max_score) {
+        if min_score > max_score {
+            return Err(AppError::BadRequest(
+                "min_score must not be greater than max_score".to_string(),
+            ));
+        }
+    }
+    let sort_column = match params.sort_by.as_deref().unwrap_or("rank_score") {
+        "rank_score" => "ts.rank_score",
+        "updated_at" => "ts.updated_at",
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid sort_by '{}' (expected rank_score or updated_at)",
+                other
+            )))
+        }
+    };
+    let sort_dir = match params.sort_dir.as_deref().unwrap_or("desc") {
+        "asc" => "ASC",
+        "desc" => "DESC",
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid sort_dir '{}' (expected asc or desc)",
+                other
+            )))
+        }
+    };
+
+    let mut count_query: QueryBuilder<Postgres> = QueryBuilder::new(
+        "SELECT COUNT(*) FROM team_stadium ts LEFT JOIN trainer t ON t.account_id = ts.account_id",
+    );
+    push_search_conditions(&mut count_query, &params);
+    let total: i64 = count_query
+        .build_query_scalar()
+        .fetch_one(state.read_db())
+        .await?;
+
+    let mut select_query: QueryBuilder<Postgres> = QueryBuilder::new(format!(
+        "SELECT {} FROM team_stadium ts LEFT JOIN trainer t ON t.account_id = ts.account_id",
+        TEAM_STADIUM_MEMBER_COLUMNS
+    ));
+    push_search_conditions(&mut select_query, &params);
+    select_query.push(format!(
+        " ORDER BY {} {} NULLS LAST, ts.account_id, ts.distance_type, ts.member_id LIMIT ",
+        sort_column, sort_dir
+    ));
+    select_query.push_bind(limit);
+    select_query.push(" OFFSET ");
+    select_query.push_bind(offset);
+
+    let items = select_query
+        .build_query_as::<TeamStadiumMember>()
+        .fetch_all(state.read_db())
+        .await?
+        .into_iter()
+        .map(mask_trainer_name)
+        .collect();
+
+    Ok(Json(SearchResponse {
+        items,
+        total: total.to_string(),
+        page,
+        limit,
+        total_pages: (total + limit - 1) / limit,
+    }))
+}
+
+/// GET /api/team-stadium/trainers/:account_id - A trainer's team stadium
+/// teams, grouped by distance
+pub async fn get_trainer_teams(
+    State(state): State<AppState>,
+    Path(account_id): Path<String>,
+) -> Result<Json<TrainerTeamsResponse>, AppError> {
+    let trainer_name = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT COALESCE(display_name_override, name) FROM trainer WHERE account_id = $1",
+    )
+    .bind(&account_id)
+    .fetch_optional(state.read_db())
+    .await?;
+
+    let members = sqlx::query_as::<_, TeamStadiumMember>(&format!(
+        r#"
+        SELECT {}
+        FROM team_stadium ts
+        LEFT JOIN trainer t ON t.account_id = ts.account_id
+        WHERE ts.account_id = $1
+        ORDER BY ts.distance_type, ts.member_id
+        "#,
+        TEAM_STADIUM_MEMBER_COLUMNS
+    ))
+    .bind(&account_id)
+    .fetch_all(state.read_db())
+    .await?;
+
+    if trainer_name.is_none() && members.is_empty() {
+        return Err(AppError::NotFound(format!("No trainer with ID {}", account_id)));
+    }
+
+    let mut teams: Vec<TeamStadiumTeam> = Vec::new();
+    for member in members.into_iter().map(mask_trainer_name) {
+        let score = member.rank_score.unwrap_or(0) as i64;
+        match teams.last_mut() {
+            Some(team) if team.distance_type == member.distance_type => {
+                team.total_score += score;
+                team.members.push(member);
+            }
+            _ => teams.push(TeamStadiumTeam {
+                distance_type: member.distance_type,
+                total_score: score,
+                members: vec![member],
+            }),
+        }
+    }
+
+    Ok(Json(TrainerTeamsResponse {
+        account_id: AccountId(account_id),
+        trainer_name: trainer_name.flatten().as_deref().map(crate::moderation::mask_text),
+        teams,
+    }))
+}
@@ -0,0 +1,545 @@
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+    routing::{get, post, put},
+    Extension, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgConnection;
+use std::net::SocketAddr;
+use std::sync::OnceLock;
+use std::time::Duration;
+use validator::Validate;
+
+use crate::errors::AppError;
+use crate::handlers::tasks::is_valid_trainer_id;
+use crate::middleware::turnstile::require_turnstile;
+use crate::middleware::user_auth::{issue_token, token_secret, UserIdentity};
+use crate::models::{
+    Bookmark, BookmarkListParams, BookmarkType, DiscordLoginRequest, MarkNotificationsReadRequest,
+    NotificationListParams, User, UserNotification, UserSession,
+};
+use crate::AppState;
+
+/// Follower count at which a trainer's friend list is full (as in search)
+pub(crate) const FRIEND_LIST_FULL: i32 = 1000;
+
+/// Columns of User
+const USER_COLUMNS: &str = "user_id, display_name, discord_id IS NOT NULL AS discord_linked, created_at";
+
+/// Columns of SessionUser
+const SESSION_COLUMNS: &str =
+    "user_id, display_name, discord_id IS NOT NULL AS discord_linked, created_at, token_version";
+
+/// An account with the token version new tokens are issued for
+#[derive(sqlx::FromRow)]
+struct SessionUser {
+    #[sqlx(flatten)]
+    user: User,
+    token_version: i32,
+}
+
+/// User accounts - mounted under /api/users behind user_auth_middleware,
+/// which attaches the account of the request's bearer token
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/anonymous", post(create_anonymous_user))
+        .route("/auth/discord", post(discord_login))
+        .route("/me", get(get_me).delete(delete_me))
+        .route("/me/revoke-tokens", post(revoke_tokens))
+        .route("/me/bookmarks", get(list_bookmarks))
+        .route(
+            "/me/bookmarks/:target_type/:target_id",
+            put(add_bookmark).delete(remove_bookmark),
+        )
+        .route("/me/notifications", get(list_notifications))
+        .route("/me/notifications/read", post(mark_notifications_read))
+}
+
+/// The signed-in account, for endpoints that need one
+pub(crate) fn signed_in(identity: Option<Extension<UserIdentity>>) -> Result<UserIdentity, AppError> {
+    identity
+        .map(|Extension(identity)| identity)
+        .ok_or_else(|| AppError::Unauthorized("Sign in required".to_string()))
+}
+
+fn session(account: SessionUser) -> Result<Json<UserSession>, AppError> {
+    let (token, expires_at) = issue_token(account.user.user_id, account.token_version)?;
+    Ok(Json(UserSession {
+        token,
+        expires_at,
+        user: account.user,
+    }))
+}
+
+/// POST /api/users/anonymous - Create an anonymous account
+///
+/// Requires a Turnstile token. The returned token is the only way back into
+/// the account, unless it is later linked to Discord.
+async fn create_anonymous_user(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Result<Json<UserSession>, AppError> {
+    // Fail before creating an account nobody could sign into
+    token_secret()?;
+    require_turnstile(&state, &headers, addr).await?;
+
+    let user = sqlx::query_as::<_, SessionUser>(&format!(
+        "INSERT INTO users DEFAULT VALUES RETURNING {}",
+        SESSION_COLUMNS
+    ))
+    .fetch_one(&state.db)
+    .await?;
+    session(user)
+}
+
+fn discord_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+#[derive(Deserialize)]
+struct DiscordToken {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct DiscordUser {
+    id: String,
+    username: String,
+    global_name: Option<String>,
+}
+
+/// Exchange an authorization code for the Discord user it was issued to
+async fn fetch_discord_user(code: &str) -> Result<DiscordUser, AppError> {
+    let Some(discord) = crate::config::get().users.discord.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Discord sign-in is not configured".to_string(),
+        ));
+    };
+    let failed = |e: reqwest::Error| {
+        tracing::warn!("Discord sign-in failed: {}", e);
+        AppError::Unauthorized("Discord sign-in failed".to_string())
+    };
+
+    let token = discord_client()
+        .post("https://discord.com/api/oauth2/token")
+        .form(&[
+            ("client_id", discord.client_id.as_str()),
+            ("client_secret", discord.client_secret.as_str()),
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", discord.redirect_uri.as_str()),
+        ])
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(failed)?
+        .json::<DiscordToken>()
+        .await
+        .map_err(failed)?;
+
+    discord_client()
+        .get("https://discord.com/api/users/@me")
+        .bearer_auth(&token.access_token)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(failed)?
+        .json::<DiscordUser>()
+        .await
+        .map_err(failed)
+}
+
+/// POST /api/users/auth/discord - Sign in with Discord
+///
+/// Takes the `code` from Discord's OAuth2 redirect (identify scope, redirected
+/// to DISCORD_REDIRECT_URI). Signing in with an anonymous account's token
+/// links that account to Discord, keeping its bookmarks, unless the Discord
+/// user already has an account; that account is signed into instead.
+async fn discord_login(
+    State(state): State<AppState>,
+    current: Option<Extension<UserIdentity>>,
+    Json(payload): Json<DiscordLoginRequest>,
+) -> Result<Json<UserSession>, AppError> {
+    payload.validate()?;
+    let discord_user = fetch_discord_user(&payload.code).await?;
+    let display_name = discord_user.global_name.unwrap_or(discord_user.username);
+
+    let mut tx = state.db.begin().await?;
+    let existing = sqlx::query_scalar::<_, i64>("SELECT user_id FROM users WHERE discord_id = $1")
+        .bind(&discord_user.id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let user = match (existing, current) {
+        (None, Some(Extension(current))) => sqlx::query_as::<_, SessionUser>(&format!(
+            r#"
+            UPDATE users SET discord_id = $2, display_name = $3
+            WHERE user_id = $1 AND discord_id IS NULL
+            RETURNING {}
+            "#,
+            SESSION_COLUMNS
+        ))
+        .bind(current.user_id)
+        .bind(&discord_user.id)
+        .bind(&display_name)
+        .fetch_optional(&mut *tx)
+        .await?,
+        _ => None,
+    };
+    // Not linked: the Discord user's own account, created on first sign-in
+    let user = match user {
+        Some(user) => user,
+        None => {
+            sqlx::query_as::<_, SessionUser>(&format!(
+                r#"
+                INSERT INTO users (discord_id, display_name) VALUES ($1, $2)
+                ON CONFLICT (discord_id) DO UPDATE SET
+                    display_name = EXCLUDED.display_name,
+                    last_seen_at = CURRENT_TIMESTAMP
+                RETURNING {}
+                "#,
+                SESSION_COLUMNS
+            ))
+            .bind(&discord_user.id)
+            .bind(&display_name)
+            .fetch_one(&mut *tx)
+            .await?
+        }
+    };
+    tx.commit().await?;
+    session(user)
+}
+
+/// GET /api/users/me - The signed-in account
+async fn get_me(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+) -> Result<Json<User>, AppError> {
+    let identity = signed_in(identity)?;
+    let user = sqlx::query_as::<_, User>(&format!(
+        "SELECT {} FROM users WHERE user_id = $1",
+        USER_COLUMNS
+    ))
+    .bind(identity.user_id)
+    .fetch_one(&state.db)
+    .await?;
+    Ok(Json(user))
+}
+
+/// DELETE /api/users/me - Delete the account with its bookmarks and notifications
+async fn delete_me(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let identity = signed_in(identity)?;
+    sqlx::query("DELETE FROM users WHERE user_id = $1")
+        .bind(identity.user_id)
+        .execute(&state.db)
+        .await?;
+    Ok(Json(json!({ "success": true })))
+}
+
+/// POST /api/users/me/revoke-tokens - Sign the account out everywhere
+///
+/// Bumps the account's token version, so every token issued so far (including
+/// the one used here) stops working, and returns a new session.
+async fn revoke_tokens(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+) -> Result<Json<UserSession>, AppError> {
+    let identity = signed_in(identity)?;
+    let account = sqlx::query_as::<_, SessionUser>(&format!(
+        "UPDATE users SET token_version = token_version + 1 WHERE user_id = $1 RETURNING {}",
+        SESSION_COLUMNS
+    ))
+    .bind(identity.user_id)
+    .fetch_one(&state.db)
+    .await?;
+    session(account)
+}
+
+/// GET /api/users/me/bookmarks - Bookmarks, newest first (`type` to filter)
+async fn list_bookmarks(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+    Query(params): Query<BookmarkListParams>,
+) -> Result<Json<Vec<Bookmark>>, AppError> {
+    let identity = signed_in(identity)?;
+    // Every target_id is numeric (checked when bookmarked)
+    let bookmarks = sqlx::query_as::<_, Bookmark>(
+        r#"
+        SELECT b.target_type, b.target_id, b.created_at,
+               COALESCE(t.display_name_override, t.name, c.name) AS name,
+               t.follower_num
+        FROM user_bookmarks b
+        LEFT JOIN inheritance i
+            ON b.target_type = 'inheritance' AND i.inheritance_id = b.target_id::bigint
+        LEFT JOIN trainer t
+            ON t.account_id = CASE b.target_type
+                                  WHEN 'trainer' THEN b.target_id
+                                  WHEN 'inheritance' THEN i.account_id
+                              END
+        LEFT JOIN circles c
+            ON b.target_type = 'circle' AND c.circle_id = b.target_id::bigint
+        WHERE b.user_id = $1 AND ($2::text IS NULL OR b.target_type = $2)
+        ORDER BY b.created_at DESC, b.target_id
+        "#,
+    )
+    .bind(identity.user_id)
+    .bind(params.target_type.map(|target_type| target_type.as_str()))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(
+        bookmarks
+            .into_iter()
+            .map(|mut bookmark| {
+                if bookmark.target_type == BookmarkType::Circle {
+                    bookmark.name = bookmark.name.as_deref().map(crate::moderation::mask_text);
+                }
+                bookmark
+            })
+            .collect(),
+    ))
+}
+
+/// Check that a bookmark target exists, returning its normalized ID
+async fn check_target(
+    state: &AppState,
+    target_type: BookmarkType,
+    target_id: &str,
+) -> Result<String, AppError> {
+    let target_id = target_id.trim();
+    let (valid, sql) = match target_type {
+        BookmarkType::Trainer => (
+            is_valid_trainer_id(target_id),
+            "SELECT EXISTS (SELECT 1 FROM trainer WHERE account_id = $1)",
+        ),
+        BookmarkType::Inheritance => (
+            target_id.parse::<i32>().is_ok_and(|id| id > 0),
+            "SELECT EXISTS (SELECT 1 FROM inheritance WHERE inheritance_id = $1::integer)",
+        ),
+        BookmarkType::Circle => (
+            target_id.parse::<i64>().is_ok_and(|id| id > 0),
+            "SELECT EXISTS (SELECT 1 FROM circles WHERE circle_id = $1::bigint)",
+        ),
+    };
+    if !valid {
+        return Err(AppError::BadRequest(format!(
+            "Invalid {} ID '{}'",
+            target_type.as_str(),
+            target_id
+        )));
+    }
+
+    let exists = sqlx::query_scalar::<_, bool>(sql)
+        .bind(target_id)
+        .fetch_one(&state.db)
+        .await?;
+    if !exists {
+        return Err(AppError::NotFound(format!(
+            "No {} with ID {}",
+            target_type.as_str(),
+            target_id
+        )));
+    }
+    Ok(target_id.to_string())
+}
+
+/// PUT /api/users/me/bookmarks/:target_type/:target_id - Bookmark a trainer,
+/// inheritance record or circle
+///
+/// Bookmarking again keeps the original bookmark. Accounts hold at most
+/// USER_MAX_BOOKMARKS bookmarks.
+async fn add_bookmark(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+    Path((target_type, target_id)): Path<(BookmarkType, String)>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let identity = signed_in(identity)?;
+    let target_id = check_target(&state, target_type, &target_id).await?;
+    let max_bookmarks = crate::config::get().users.max_bookmarks;
+
+    // The count and insert race only against the same user's parallel requests
+    let added = sqlx::query_scalar::<_, bool>(
+        r#"
+        WITH existing AS (
+            SELECT COUNT(*) AS n FROM user_bookmarks WHERE user_id = $1
+        )
+        INSERT INTO user_bookmarks (user_id, target_type, target_id)
+        SELECT $1, $2, $3 FROM existing WHERE existing.n < $4
+        ON CONFLICT (user_id, target_type, target_id) DO NOTHING
+        RETURNING TRUE
+        "#,
+    )
+    .bind(identity.user_id)
+    .bind(target_type.as_str())
+    .bind(&target_id)
+    .bind(max_bookmarks)
+    .fetch_optional(&state.db)
+    .await?
+    .is_some();
+
+    if !added {
+        let bookmarked = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM user_bookmarks
+                WHERE user_id = $1 AND target_type = $2 AND target_id = $3
+            )
+            "#,
+        )
+        .bind(identity.user_id)
+        .bind(target_type.as_str())
+        .bind(&target_id)
+        .fetch_one(&state.db)
+        .await?;
+        if !bookmarked {
+            return Err(AppError::BadRequest(format!(
+                "Bookmark limit reached ({}); remove some bookmarks first",
+                max_bookmarks
+            )));
+        }
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "target_type": target_type,
+        "target_id": target_id,
+        "added": added
+    })))
+}
+
+/// DELETE /api/users/me/bookmarks/:target_type/:target_id - Remove a bookmark
+async fn remove_bookmark(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+    Path((target_type, target_id)): Path<(BookmarkType, String)>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let identity = signed_in(identity)?;
+    let removed = sqlx::query(
+        "DELETE FROM user_bookmarks WHERE user_id = $1 AND target_type = $2 AND target_id = $3",
+    )
+    .bind(identity.user_id)
+    .bind(target_type.as_str())
+    .bind(target_id.trim())
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    Ok(Json(json!({
+        "success": true,
+        "removed": removed > 0
+    })))
+}
+
+/// GET /api/users/me/notifications - Notifications about bookmarked trainers,
+/// newest first
+async fn list_notifications(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+    Query(params): Query<NotificationListParams>,
+) -> Result<Json<Vec<UserNotification>>, AppError> {
+    let identity = signed_in(identity)?;
+    let notifications = sqlx::query_as::<_, UserNotification>(
+        r#"
+        SELECT notification_id, kind, account_id, data, created_at, read_at
+        FROM user_notifications
+        WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL)
+        ORDER BY notification_id DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(identity.user_id)
+    .bind(params.unread)
+    .bind(params.limit.unwrap_or(50).clamp(1, 100))
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(notifications))
+}
+
+/// POST /api/users/me/notifications/read - Mark notifications read (all, or
+/// up to `up_to`)
+async fn mark_notifications_read(
+    State(state): State<AppState>,
+    identity: Option<Extension<UserIdentity>>,
+    payload: Option<Json<MarkNotificationsReadRequest>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let identity = signed_in(identity)?;
+    let up_to = payload.and_then(|Json(payload)| payload.up_to);
+    let marked = sqlx::query(
+        r#"
+        UPDATE user_notifications SET read_at = CURRENT_TIMESTAMP
+        WHERE user_id = $1 AND read_at IS NULL AND ($2::bigint IS NULL OR notification_id <= $2)
+        "#,
+    )
+    .bind(identity.user_id)
+    .bind(up_to)
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    Ok(Json(json!({
+        "success": true,
+        "marked": marked
+    })))
+}
+
+/// Notify users who bookmarked a trainer (or one of its inheritance records)
+/// whose friend list was full and, going by the fresh scrape, isn't anymore
+///
+/// Runs in the ingest transaction before the trainers are updated, while the
+/// stored follower counts are still the previous ones. Returns how many
+/// notifications were created.
+pub(crate) async fn record_friend_slot_openings(
+    conn: &mut PgConnection,
+    trainers: &[(String, String, Option<i32>)],
+) -> Result<u64, AppError> {
+    let mut account_ids = Vec::with_capacity(trainers.len());
+    let mut names = Vec::with_capacity(trainers.len());
+    let mut follower_nums = Vec::with_capacity(trainers.len());
+    for (account_id, name, follower_num) in trainers {
+        account_ids.push(account_id.as_str());
+        names.push(name.as_str());
+        follower_nums.push(*follower_num);
+    }
+
+    let created = sqlx::query(
+        r#"
+        INSERT INTO user_notifications (user_id, kind, account_id, data)
+        SELECT DISTINCT ON (b.user_id, t.account_id)
+               b.user_id, 'friend_slots_open', t.account_id,
+               jsonb_build_object(
+                   'trainer_name', n.name,
+                   'follower_num', n.follower_num,
+                   'previous_follower_num', t.follower_num
+               )
+        FROM unnest($1::text[], $2::text[], $3::int[]) AS n(account_id, name, follower_num)
+        JOIN trainer t ON t.account_id = n.account_id
+        JOIN user_bookmarks b
+            ON (b.target_type = 'trainer' AND b.target_id = t.account_id)
+            OR (b.target_type = 'inheritance' AND b.target_id IN (
+                    SELECT i.inheritance_id::text FROM inheritance i WHERE i.account_id = t.account_id
+                ))
+        WHERE t.follower_num >= $4 AND n.follower_num < $4
+        "#,
+    )
+    .bind(account_ids)
+    .bind(names)
+    .bind(follower_nums)
+    .bind(FRIEND_LIST_FULL)
+    .execute(conn)
+    .await?
+    .rows_affected();
+    Ok(created)
+}
@@ -0,0 +1,219 @@
+use axum::{
+    extract::{ConnectInfo, Path, State},
+    http::{header, HeaderMap},
+    response::Json,
+    routing::get,
+    Router,
+};
+use sqlx::PgConnection;
+use std::net::SocketAddr;
+use validator::Validate;
+
+use crate::errors::AppError;
+use crate::middleware::turnstile::require_single_use_turnstile;
+use crate::models::{InheritanceVoteKind, InheritanceVoteRequest, InheritanceVoteSummary};
+use crate::AppState;
+
+/// How much a report counts against a record, in upvotes
+const REPORT_WEIGHT: f64 = 2.0;
+
+/// Community votes on inheritance records - mounted under /api/v3/inheritance
+pub fn router() -> Router<AppState> {
+    Router::new().route(
+        "/:inheritance_id/votes",
+        get(get_votes).post(vote).delete(withdraw_vote),
+    )
+}
+
+/// The caller's client ID: salted hash of IP (resolved through
+/// TRUSTED_PROXIES, so it can't be forged per request) and user agent
+pub(crate) fn client_hash(headers: &HeaderMap, addr: SocketAddr) -> String {
+    let client_ip = crate::middleware::turnstile::client_ip(headers, addr).to_string();
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    crate::visitors::fingerprint(&client_ip, user_agent)
+}
+
+/// Serialize vote changes on a record until the transaction ends, so each
+/// aggregate is computed from every committed vote
+async fn lock_record(conn: &mut PgConnection, inheritance_id: i32) -> Result<(), AppError> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('inheritance_votes'), $1)")
+        .bind(inheritance_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Recompute a record's aggregate from its votes, each decayed by its age
+async fn refresh_score(conn: &mut PgConnection, inheritance_id: i32) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO inheritance_community_scores (inheritance_id, upvotes, reports, score, scored_at)
+        SELECT $1,
+               COUNT(*) FILTER (WHERE kind = 'up'),
+               COUNT(*) FILTER (WHERE kind = 'report'),
+               COALESCE(SUM(decayed_community_score(
+                   CASE kind WHEN 'up' THEN 1 ELSE -$2::double precision END,
+                   voted_at,
+                   $3
+               )), 0),
+               LOCALTIMESTAMP
+        FROM inheritance_votes
+        WHERE inheritance_id = $1
+        ON CONFLICT (inheritance_id) DO UPDATE SET
+            upvotes = EXCLUDED.upvotes,
+            reports = EXCLUDED.reports,
+            score = EXCLUDED.score,
+            scored_at = EXCLUDED.scored_at
+        "#,
+    )
+    .bind(inheritance_id)
+    .bind(REPORT_WEIGHT)
+    .bind(crate::config::get().community_score_half_life.as_secs_f64())
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+async fn summary(
+    conn: &mut PgConnection,
+    inheritance_id: i32,
+    voter_hash: &str,
+) -> Result<InheritanceVoteSummary, AppError> {
+    let summary = sqlx::query_as::<_, InheritanceVoteSummary>(
+        r#"
+        SELECT i.inheritance_id,
+               COALESCE(cs.upvotes, 0) AS upvotes,
+               COALESCE(cs.reports, 0) AS reports,
+               COALESCE(decayed_community_score(cs.score, cs.scored_at, $3), 0) AS community_score,
+               v.kind AS your_vote
+        FROM inheritance i
+        LEFT JOIN inheritance_community_scores cs ON cs.inheritance_id = i.inheritance_id
+        LEFT JOIN inheritance_votes v ON v.inheritance_id = i.inheritance_id AND v.voter_hash = $2
+        WHERE i.inheritance_id = $1
+        "#,
+    )
+    .bind(inheritance_id)
+    .bind(voter_hash)
+    .bind(crate::config::get().community_score_half_life.as_secs_f64())
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!("No inheritance record with ID {}", inheritance_id))
+    })?;
+    Ok(summary)
+}
+
+/// GET /api/v3/inheritance/:inheritance_id/votes - Vote counts, community score
+/// and the caller's vote
+pub async fn get_votes(
+    State(state): State<AppState>,
+    Path(inheritance_id): Path<i32>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Result<Json<InheritanceVoteSummary>, AppError> {
+    let mut conn = state.db.acquire().await?;
+    Ok(Json(
+        summary(&mut conn, inheritance_id, &client_hash(&headers, addr)).await?,
+    ))
+}
+
+/// POST /api/v3/inheritance/:inheritance_id/votes - Upvote or report a record
+///
+/// Requires a fresh Turnstile token for every vote. Each client (by IP and
+/// user agent) has one vote per record; voting again replaces it, and
+/// repeating the same vote changes nothing.
+///
+/// Body:
+/// - kind: up or report
+/// - reason: Why the record is reported (optional)
+pub async fn vote(
+    State(state): State<AppState>,
+    Path(inheritance_id): Path<i32>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<InheritanceVoteRequest>,
+) -> Result<Json<InheritanceVoteSummary>, AppError> {
+    payload.validate()?;
+    require_single_use_turnstile(&state, &headers, addr).await?;
+    let voter_hash = client_hash(&headers, addr);
+    let reason = payload
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty() && payload.kind == InheritanceVoteKind::Report);
+
+    let mut tx = state.db.begin().await?;
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM inheritance WHERE inheritance_id = $1)",
+    )
+    .bind(inheritance_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if !exists {
+        return Err(AppError::NotFound(format!(
+            "No inheritance record with ID {}",
+            inheritance_id
+        )));
+    }
+    lock_record(&mut tx, inheritance_id).await?;
+
+    // Repeating a vote keeps its original time, so it can't be refreshed against decay
+    let changed = sqlx::query(
+        r#"
+        INSERT INTO inheritance_votes (inheritance_id, voter_hash, kind, reason)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (inheritance_id, voter_hash) DO UPDATE SET
+            kind = EXCLUDED.kind,
+            reason = EXCLUDED.reason,
+            voted_at = LOCALTIMESTAMP
+        WHERE inheritance_votes.kind <> EXCLUDED.kind
+        "#,
+    )
+    .bind(inheritance_id)
+    .bind(&voter_hash)
+    .bind(payload.kind.as_str())
+    .bind(reason)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if changed {
+        refresh_score(&mut tx, inheritance_id).await?;
+    }
+    let summary = summary(&mut tx, inheritance_id, &voter_hash).await?;
+    tx.commit().await?;
+
+    Ok(Json(summary))
+}
+
+/// DELETE /api/v3/inheritance/:inheritance_id/votes - Withdraw the caller's vote
+pub async fn withdraw_vote(
+    State(state): State<AppState>,
+    Path(inheritance_id): Path<i32>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Result<Json<InheritanceVoteSummary>, AppError> {
+    let voter_hash = client_hash(&headers, addr);
+
+    let mut tx = state.db.begin().await?;
+    lock_record(&mut tx, inheritance_id).await?;
+    let removed = sqlx::query(
+        "DELETE FROM inheritance_votes WHERE inheritance_id = $1 AND voter_hash = $2",
+    )
+    .bind(inheritance_id)
+    .bind(&voter_hash)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if removed {
+        refresh_score(&mut tx, inheritance_id).await?;
+    }
+    let summary = summary(&mut tx, inheritance_id, &voter_hash).await?;
+    tx.commit().await?;
+
+    Ok(Json(summary))
+}
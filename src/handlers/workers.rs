@@ -0,0 +1,276 @@
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+use validator::Validate;
+
+use crate::errors::AppError;
+use crate::models::{
+    ClaimTasksRequest, CompleteTaskRequest, Task, TaskHeartbeatRequest, WorkerConfig,
+    WorkerFleetSettings,
+};
+use crate::AppState;
+
+// How long workers may act on a fetched config before re-fetching it
+const WORKER_CONFIG_TTL_SECS: i64 = 300;
+
+/// Worker-facing task queue routes - mounted under /api/workers
+///
+/// Claim, complete and heartbeat only accept signed requests from allowed
+/// worker IPs (see worker_auth_middleware); the config is signed instead.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/claim", post(claim_tasks))
+        .route("/tasks/:task_id/complete", post(complete_task))
+        .route("/tasks/:task_id/heartbeat", post(heartbeat_task))
+        .route_layer(axum::middleware::from_fn(
+            crate::middleware::worker_auth_middleware,
+        ))
+        .route("/config", get(get_worker_config))
+}
+
+/// GET /api/workers/config - Central configuration for the scraper fleet
+///
+/// The body is signed with HMAC-SHA256 using WORKER_SIGNING_SECRET; the hex
+/// digest is returned in the `X-Signature` header so workers can verify the
+/// config came from this server before applying it.
+async fn get_worker_config(State(state): State<AppState>) -> Result<Response, AppError> {
+    let Some(secret) = state.config.worker_signing_secret.as_deref() else {
+        tracing::error!("WORKER_SIGNING_SECRET not set - cannot serve signed worker config");
+        return Err(AppError::ServiceUnavailable("Worker config is unavailable".to_string()));
+    };
+
+    let settings = sqlx::query_as::<_, WorkerFleetSettings>(
+        r#"
+        SELECT paused, claim_endpoints, poll_interval_secs, idle_poll_interval_secs,
+               maintenance_windows, updated_at
+        FROM worker_fleet_settings
+        "#,
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    let paused_task_types = sqlx::query_scalar::<_, String>(
+        "SELECT task_type FROM task_type_policies WHERE paused ORDER BY task_type",
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let maintenance_windows: Vec<_> = settings
+        .maintenance_windows
+        .into_iter()
+        .filter(|w| w.ends_at > now)
+        .collect();
+    let in_maintenance = maintenance_windows
+        .iter()
+        .any(|w| w.starts_at <= now && now < w.ends_at);
+
+    let config = WorkerConfig {
+        paused: settings.paused || in_maintenance,
+        claim_endpoints: settings.claim_endpoints,
+        poll_interval_secs: settings.poll_interval_secs,
+        idle_poll_interval_secs: settings.idle_poll_interval_secs,
+        paused_task_types,
+        maintenance_windows,
+        issued_at: now,
+        expires_at: now + chrono::Duration::seconds(WORKER_CONFIG_TTL_SECS),
+    };
+
+    let body = serde_json::to_vec(&config)
+        .map_err(|e| AppError::DatabaseError(format!("Failed to serialize worker config: {}", e)))?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(&body);
+    let signature = hex::encode(mac.finalize().into_bytes());
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::HeaderName::from_static("x-signature"), signature),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// POST /api/workers/claim - Claim pending tasks for a worker
+///
+/// Tasks are ordered by their task type's lane (see task_type_policies), then by
+/// task priority and age. Paused task types are skipped, and types with a
+/// max_in_flight cap are skipped once that many tasks are claimed/processing.
+/// The cap is checked per claim, so one batch may overshoot it by up to `limit - 1`.
+async fn claim_tasks(
+    State(state): State<AppState>,
+    Json(payload): Json<ClaimTasksRequest>,
+) -> Result<Json<Vec<Task>>, AppError> {
+    Ok(Json(claim(&state, &payload).await?))
+}
+
+/// Claim up to `payload.limit` pending tasks for a worker (see claim_tasks)
+pub(crate) async fn claim(state: &AppState, payload: &ClaimTasksRequest) -> Result<Vec<Task>, AppError> {
+    payload.validate()?;
+
+    // Workers retry elsewhere; claimed tasks would otherwise sit on a stopping instance
+    if crate::shutdown::is_requested() {
+        return Err(AppError::ServiceUnavailable(
+            "Server is shutting down".to_string(),
+        ));
+    }
+
+    let limit = payload.limit.unwrap_or(1);
+
+    let tasks = sqlx::query_as::<_, Task>(
+        r#"
+        WITH in_flight AS (
+            SELECT task_type, COUNT(*) AS n
+            FROM tasks
+            WHERE status IN ('claimed', 'processing')
+            GROUP BY task_type
+        ),
+        candidates AS (
+            SELECT t.id
+            FROM tasks t
+            LEFT JOIN task_type_policies p ON p.task_type = t.task_type
+            LEFT JOIN in_flight f ON f.task_type = t.task_type
+            WHERE t.status = 'pending'
+              AND ($2::text[] IS NULL OR t.task_type = ANY($2))
+              AND NOT COALESCE(p.paused, FALSE)
+              AND (p.max_in_flight IS NULL OR COALESCE(f.n, 0) < p.max_in_flight)
+            ORDER BY COALESCE(p.lane_priority, 100), t.priority, t.created_at
+            LIMIT $3
+            FOR UPDATE OF t SKIP LOCKED
+        )
+        UPDATE tasks t SET
+            status = 'claimed',
+            worker_id = $1,
+            claimed_at = CURRENT_TIMESTAMP,
+            updated_at = CURRENT_TIMESTAMP
+        FROM candidates c
+        WHERE t.id = c.id
+        RETURNING t.id, t.task_type, t.task_data, t.priority, t.status, t.created_at,
+                  t.updated_at, t.worker_id, t.error_message, t.account_id
+        "#,
+    )
+    .bind(&payload.worker_id)
+    .bind(payload.task_types.as_deref())
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await?;
+
+    if !tasks.is_empty() {
+        tracing::info!("👷 Worker {} claimed {} task(s)", payload.worker_id, tasks.len());
+    }
+
+    Ok(tasks)
+}
+
+/// POST /api/workers/tasks/{task_id}/complete - Mark a claimed task as completed or failed
+async fn complete_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<i32>,
+    Json(payload): Json<CompleteTaskRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let status = complete(&state, task_id, &payload).await?;
+    Ok(Json(json!({
+        "success": true,
+        "task_id": task_id,
+        "status": status
+    })))
+}
+
+/// Mark a task held by the worker completed or failed, returning the new status
+pub(crate) async fn complete(
+    state: &AppState,
+    task_id: i32,
+    payload: &CompleteTaskRequest,
+) -> Result<&'static str, AppError> {
+    payload.validate()?;
+
+    let status = if payload.success { "completed" } else { "failed" };
+
+    let completed = sqlx::query_scalar::<_, Option<String>>(
+        r#"
+        UPDATE tasks
+        SET status = $3, error_message = $4, claimed_at = NULL, updated_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND worker_id = $2 AND status IN ('claimed', 'processing')
+        RETURNING account_id
+        "#,
+    )
+    .bind(task_id)
+    .bind(&payload.worker_id)
+    .bind(status)
+    .bind(&payload.error_message)
+    .fetch_optional(&state.db)
+    .await?;
+
+    let Some(account_id) = completed else {
+        return Err(AppError::NotFound(format!(
+            "No in-flight task {} held by worker {}",
+            task_id, payload.worker_id
+        )));
+    };
+
+    if payload.success {
+        crate::live_stats::TASKS_COMPLETED.record(1);
+
+        // Workers store the trainer's scraped data before completing its task
+        if let Some(account_id) = account_id {
+            crate::events::publish(crate::events::DomainEvent::TrainerDataChanged {
+                account_ids: vec![account_id],
+            });
+        }
+    }
+
+    Ok(status)
+}
+
+/// POST /api/workers/tasks/{task_id}/heartbeat - Extend the lease on a claimed task
+async fn heartbeat_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<i32>,
+    Json(payload): Json<TaskHeartbeatRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    heartbeat(&state, task_id, &payload).await?;
+    Ok(Json(json!({
+        "success": true,
+        "task_id": task_id
+    })))
+}
+
+/// Extend the worker's lease on a task it holds
+pub(crate) async fn heartbeat(
+    state: &AppState,
+    task_id: i32,
+    payload: &TaskHeartbeatRequest,
+) -> Result<(), AppError> {
+    payload.validate()?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE tasks
+        SET status = 'processing', claimed_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND worker_id = $2 AND status IN ('claimed', 'processing')
+        "#,
+    )
+    .bind(task_id)
+    .bind(&payload.worker_id)
+    .execute(&state.db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!(
+            "No in-flight task {} held by worker {}",
+            task_id, payload.worker_id
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,412 @@
+//! Write-ahead journal for task submissions.
+//!
+//! Each submission is appended (and fsynced) before its task INSERT and marked
+//! committed afterwards. Submissions still open at startup - the process died
+//! in between - are replayed. Replay skips tasks that are already pending or
+//! were created after the journal entry (for instance by the client retrying
+//! against another instance), so together with the submit dedup a submission
+//! takes effect exactly once.
+//!
+//! A submission whose INSERT failed is aborted instead: the client got an
+//! error and may retry, and replaying it as well would create the task twice.
+//! The same goes for a request dropped mid-way because the client went away.
+//!
+//! Records go through a single writer task that appends whatever has queued up
+//! while the previous write was in flight and fsyncs once per batch, so
+//! concurrent submissions share an fsync instead of waiting for one each.
+//!
+//! Journaled are the endpoints that create tasks on a user's behalf (submit,
+//! batch submit, generic task creation, unavailable reports, claimed refreshes
+//! and copy rechecks). Tasks queued as a side effect of another write (friend
+//! list report rechecks, circle fetches, refreshes for ownership verification)
+//! are not: the request that triggers them is repeated until it has an effect.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::models::AccountId;
+
+// The file is rewritten with only the open entries once it grows past this
+const COMPACT_THRESHOLD_BYTES: u64 = 1024 * 1024;
+
+// Records appended (and fsynced) together at most
+const MAX_BATCH_RECORDS: usize = 256;
+
+/// A task the submission is about to insert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalTask {
+    pub task_type: String,
+    pub task_data: serde_json::Value,
+    pub priority: i32,
+    pub account_id: Option<AccountId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum JournalRecord {
+    Begin {
+        id: String,
+        at: DateTime<Utc>,
+        tasks: Vec<JournalTask>,
+    },
+    Commit {
+        id: String,
+    },
+    /// The INSERT failed (and the client was told), so the entry isn't replayed
+    Abort {
+        id: String,
+    },
+}
+
+/// A serialized record on its way to the writer task
+enum Op {
+    Begin {
+        id: String,
+        line: Vec<u8>,
+        durable: oneshot::Sender<bool>,
+    },
+    Finish {
+        id: String,
+        line: Vec<u8>,
+    },
+}
+
+struct Journal {
+    path: String,
+    file: tokio::fs::File,
+    /// Begin records written but not committed or aborted yet
+    open: HashMap<String, Vec<u8>>,
+    bytes: u64,
+}
+
+static WRITER: OnceLock<mpsc::UnboundedSender<Op>> = OnceLock::new();
+
+async fn open_append(path: &str) -> std::io::Result<tokio::fs::File> {
+    tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+}
+
+/// Replay submissions left open by the previous run, then start journaling.
+/// The journal file comes from SUBMISSION_JOURNAL_PATH (empty disables it).
+pub async fn init(pool: &PgPool) {
+    let Some(path) = crate::config::get().submission_journal_path.clone() else {
+        tracing::warn!("⚠️ Submission journal disabled (SUBMISSION_JOURNAL_PATH is empty)");
+        return;
+    };
+
+    let pending = match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => open_entries(&contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => {
+            tracing::error!("❌ Failed to read submission journal {}: {}", path, e);
+            return;
+        }
+    };
+
+    // Keep the file untouched if replay fails so the next start retries
+    let mut replay_failed = false;
+    if !pending.is_empty() {
+        match replay(pool, &pending).await {
+            Ok(created) => tracing::warn!(
+                "📒 Replayed {} open submission(s) from the journal, {} task(s) created",
+                pending.len(),
+                created
+            ),
+            Err(e) => {
+                tracing::error!("❌ Failed to replay submission journal: {}", e);
+                replay_failed = true;
+            }
+        }
+    }
+
+    let mut open = HashMap::new();
+    if replay_failed {
+        open.extend(pending.into_iter().map(|(id, at, tasks)| {
+            let line = record_line(&JournalRecord::Begin {
+                id: id.clone(),
+                at,
+                tasks,
+            });
+            (id, line)
+        }));
+    } else if let Err(e) = tokio::fs::write(&path, b"").await {
+        tracing::error!("❌ Failed to truncate submission journal {}: {}", path, e);
+        return;
+    }
+
+    match open_append(&path).await {
+        Ok(file) => {
+            let bytes = file.metadata().await.map(|m| m.len()).unwrap_or(0);
+            let (sender, receiver) = mpsc::unbounded_channel();
+            if WRITER.set(sender).is_ok() {
+                tokio::spawn(run_writer(
+                    Journal {
+                        path,
+                        file,
+                        open,
+                        bytes,
+                    },
+                    receiver,
+                ));
+            }
+        }
+        Err(e) => tracing::error!("❌ Failed to open submission journal {}: {}", path, e),
+    }
+}
+
+/// Begin entries without a matching commit or abort, in file order
+fn open_entries(contents: &str) -> Vec<(String, DateTime<Utc>, Vec<JournalTask>)> {
+    let mut begun = Vec::new();
+    let mut finished = HashSet::new();
+
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        // A torn last line from a crash mid-write is expected; skip it
+        match serde_json::from_str::<JournalRecord>(line) {
+            Ok(JournalRecord::Begin { id, at, tasks }) => begun.push((id, at, tasks)),
+            Ok(JournalRecord::Commit { id } | JournalRecord::Abort { id }) => {
+                finished.insert(id);
+            }
+            Err(e) => tracing::warn!("⚠️ Skipping unreadable journal line: {}", e),
+        }
+    }
+
+    begun
+        .into_iter()
+        .filter(|(id, _, _)| !finished.contains(id))
+        .collect()
+}
+
+async fn replay(
+    pool: &PgPool,
+    pending: &[(String, DateTime<Utc>, Vec<JournalTask>)],
+) -> Result<u64, sqlx::Error> {
+    let mut created = 0;
+    for (_, at, tasks) in pending {
+        // Small margin for clock skew between this host and the database
+        let since = *at - chrono::Duration::minutes(1);
+        for task in tasks {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO tasks (task_type, task_data, priority, status, created_at, account_id)
+                SELECT $1, $2, $3, 'pending', CURRENT_TIMESTAMP, $4
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM tasks
+                    WHERE task_type = $1 AND task_data = $2
+                      AND (status = 'pending' OR created_at::timestamptz >= $5)
+                )
+                "#,
+            )
+            .bind(&task.task_type)
+            .bind(&task.task_data)
+            .bind(task.priority)
+            .bind(&task.account_id)
+            .bind(since)
+            .execute(pool)
+            .await?;
+            created += result.rows_affected();
+        }
+    }
+    Ok(created)
+}
+
+fn record_line(record: &JournalRecord) -> Vec<u8> {
+    let mut line = serde_json::to_vec(record).expect("journal records serialize");
+    line.push(b'\n');
+    line
+}
+
+/// Append queued records, fsyncing once per batch that contains Begin records
+async fn run_writer(mut journal: Journal, mut receiver: mpsc::UnboundedReceiver<Op>) {
+    let mut ops = Vec::new();
+    while receiver.recv_many(&mut ops, MAX_BATCH_RECORDS).await > 0 {
+        let mut buffer = Vec::new();
+        let mut begun = Vec::new();
+        for op in ops.drain(..) {
+            match op {
+                Op::Begin { id, line, durable } => {
+                    buffer.extend_from_slice(&line);
+                    begun.push((id, line, durable));
+                }
+                Op::Finish { id, line } => {
+                    buffer.extend_from_slice(&line);
+                    journal.open.remove(&id);
+                }
+            }
+        }
+
+        let result = async {
+            journal.file.write_all(&buffer).await?;
+            // Commits and aborts aren't synced: losing one only means a replay,
+            // which skips tasks that exist already
+            if !begun.is_empty() {
+                journal.file.sync_data().await?;
+            }
+            Ok::<_, std::io::Error>(())
+        }
+        .await;
+        let written = match result {
+            Ok(()) => {
+                journal.bytes += buffer.len() as u64;
+                true
+            }
+            Err(e) => {
+                tracing::error!("❌ Failed to write submission journal: {}", e);
+                false
+            }
+        };
+
+        for (id, line, durable) in begun {
+            if written {
+                journal.open.insert(id, line);
+            }
+            let _ = durable.send(written);
+        }
+
+        if journal.bytes > COMPACT_THRESHOLD_BYTES {
+            // Entries open for long (a failed replay) don't make it rewrite every batch
+            let open_bytes: u64 = journal.open.values().map(|line| line.len() as u64).sum();
+            if journal.bytes > 2 * open_bytes {
+                compact(&mut journal).await;
+            }
+        }
+    }
+}
+
+/// A journaled submission; dropping it without `commit` aborts it
+pub struct JournalEntry {
+    id: Option<String>,
+}
+
+impl JournalEntry {
+    /// Mark the submission as done once its INSERT succeeded
+    pub fn commit(mut self) {
+        if let Some(id) = self.id.take() {
+            finish(id, true);
+        }
+    }
+}
+
+impl Drop for JournalEntry {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            finish(id, false);
+        }
+    }
+}
+
+fn finish(id: String, committed: bool) {
+    let Some(writer) = WRITER.get() else {
+        return;
+    };
+    let record = if committed {
+        JournalRecord::Commit { id: id.clone() }
+    } else {
+        JournalRecord::Abort { id: id.clone() }
+    };
+    let _ = writer.send(Op::Finish {
+        line: record_line(&record),
+        id,
+    });
+}
+
+/// Record tasks about to be inserted, returning once the entry is durable.
+/// Journaling is skipped (with an error logged) when it is off or the write failed.
+pub async fn begin(tasks: Vec<JournalTask>) -> JournalEntry {
+    let Some(writer) = WRITER.get() else {
+        return JournalEntry { id: None };
+    };
+
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let line = record_line(&JournalRecord::Begin {
+        id: id.clone(),
+        at: Utc::now(),
+        tasks,
+    });
+
+    let (durable, written) = oneshot::channel();
+    let sent = writer
+        .send(Op::Begin {
+            id: id.clone(),
+            line,
+            durable,
+        })
+        .is_ok();
+    // Durable before the INSERT, otherwise a crash could still lose it
+    let written = sent && written.await.unwrap_or(false);
+    JournalEntry {
+        id: written.then_some(id),
+    }
+}
+
+/// Replace the file with one holding only the open entries
+async fn compact(journal: &mut Journal) {
+    let compacted_path = format!("{}.compact", journal.path);
+    let contents: Vec<u8> = journal.open.values().flatten().copied().collect();
+    let result = async {
+        let mut file = tokio::fs::File::create(&compacted_path).await?;
+        file.write_all(&contents).await?;
+        file.sync_data().await?;
+        tokio::fs::rename(&compacted_path, &journal.path).await?;
+        open_append(&journal.path).await
+    }
+    .await;
+
+    match result {
+        Ok(file) => {
+            journal.file = file;
+            journal.bytes = contents.len() as u64;
+        }
+        Err(e) => tracing::error!("❌ Failed to compact submission journal: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn begin_line(id: &str) -> String {
+        let record = JournalRecord::Begin {
+            id: id.to_string(),
+            at: Utc::now(),
+            tasks: vec![JournalTask {
+                task_type: "friend/search".to_string(),
+                task_data: serde_json::json!({ "id": "123456789" }),
+                priority: 1,
+                account_id: None,
+            }],
+        };
+        String::from_utf8(record_line(&record)).unwrap()
+    }
+
+    #[test]
+    fn committed_and_aborted_entries_are_not_replayed() {
+        let contents = [
+            begin_line("a"),
+            begin_line("b"),
+            begin_line("c"),
+            "{\"op\":\"commit\",\"id\":\"a\"}\n".to_string(),
+            "{\"op\":\"abort\",\"id\":\"b\"}\n".to_string(),
+        ]
+        .concat();
+
+        let open: Vec<String> = open_entries(&contents).into_iter().map(|(id, _, _)| id).collect();
+        assert_eq!(open, ["c"]);
+    }
+
+    #[test]
+    fn torn_last_line_is_skipped() {
+        let mut contents = begin_line("a");
+        contents.push_str("{\"op\":\"commit\",\"id\":");
+
+        let open: Vec<String> = open_entries(&contents).into_iter().map(|(id, _, _)| id).collect();
+        assert_eq!(open, ["a"]);
+    }
+}
@@ -0,0 +1,63 @@
+//! uma.moe backend: the API server (`server::run`) and the parts of it the
+//! maintenance CLI (`umamoe-admin`) shares
+
+use sqlx::PgPool;
+use std::sync::Arc;
+
+mod affinity;
+mod analytics;
+mod bans;
+mod cache;
+pub mod config;
+mod characters;
+pub mod competition;
+pub mod database;
+mod errors;
+mod events;
+mod game_data;
+mod graphql;
+mod grpc;
+mod handlers;
+mod journal;
+mod live_stats;
+mod member_fan_partitions;
+mod middleware;
+mod models;
+mod moderation;
+mod notifications;
+mod og_image;
+mod retention;
+pub mod server;
+mod share_links;
+mod shutdown;
+mod sparks;
+mod storage;
+mod streaming;
+mod support_cards;
+#[cfg(unix)]
+mod unix_socket;
+pub mod views;
+mod visitors;
+
+use config::Config;
+use database::DbPools;
+use storage::Storage;
+
+#[derive(Clone)]
+pub struct AppState {
+    /// Primary database, for writes and reads that must see them
+    pub db: PgPool,
+    /// The primary plus read replicas (REPLICA_DATABASE_URLS); see read_db
+    pub db_pools: Arc<DbPools>,
+    /// Reads behind search, circles, stats and shares (Postgres or mock fixtures)
+    pub storage: Arc<dyn Storage>,
+    /// Settings loaded from the environment at startup
+    pub config: Arc<Config>,
+}
+
+impl AppState {
+    /// Pool for read-only queries: a healthy read replica, else the primary
+    pub fn read_db(&self) -> &PgPool {
+        self.db_pools.read()
+    }
+}
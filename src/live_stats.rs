@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::models::LiveStats;
+
+/// Length of the rolling window in seconds (one slot per second)
+const WINDOW_SECS: usize = 60;
+
+/// Events per second over the last minute, lock-free
+///
+/// Slots are reused once their second is older than the window. Two threads
+/// racing on a slot rollover can lose a count, which is fine for a dashboard.
+pub struct RollingCounter {
+    counts: [AtomicU64; WINDOW_SECS],
+    seconds: [AtomicU64; WINDOW_SECS],
+}
+
+impl RollingCounter {
+    const fn new() -> Self {
+        Self {
+            counts: [const { AtomicU64::new(0) }; WINDOW_SECS],
+            seconds: [const { AtomicU64::new(0) }; WINDOW_SECS],
+        }
+    }
+
+    pub fn record(&self, events: u64) {
+        let now = unix_secs();
+        let slot = now as usize % WINDOW_SECS;
+
+        let second = self.seconds[slot].load(Ordering::Acquire);
+        if second != now
+            && self.seconds[slot]
+                .compare_exchange(second, now, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            self.counts[slot].store(events, Ordering::Release);
+        } else {
+            self.counts[slot].fetch_add(events, Ordering::AcqRel);
+        }
+    }
+
+    /// Events in the last minute
+    pub fn per_minute(&self) -> u64 {
+        let now = unix_secs();
+        (0..WINDOW_SECS)
+            .filter(|&slot| {
+                now.saturating_sub(self.seconds[slot].load(Ordering::Acquire)) < WINDOW_SECS as u64
+            })
+            .map(|slot| self.counts[slot].load(Ordering::Acquire))
+            .sum()
+    }
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Search requests served
+pub static SEARCHES: RollingCounter = RollingCounter::new();
+/// Tasks reported as successfully completed by workers
+pub static TASKS_COMPLETED: RollingCounter = RollingCounter::new();
+/// Trainer IDs queued for their first lookup
+pub static NEW_TRAINERS: RollingCounter = RollingCounter::new();
+
+/// Current per-minute rates of all live counters
+pub fn snapshot() -> LiveStats {
+    LiveStats {
+        searches_per_min: SEARCHES.per_minute(),
+        tasks_completed_per_min: TASKS_COMPLETED.per_minute(),
+        new_trainers_per_min: NEW_TRAINERS.per_minute(),
+        timestamp: chrono::Utc::now(),
+    }
+}
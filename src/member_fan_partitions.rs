@@ -0,0 +1,82 @@
+//! Monthly partitions of circle_member_fans_monthly.
+//!
+//! Each (year, month) lives in its own partition. The maintenance task creates
+//! the current and next month's partitions ahead of time (writers outside this
+//! server insert directly) and, when CIRCLE_FANS_RETENTION_MONTHS is set,
+//! detaches older months into the archive schema.
+
+use sqlx::{PgConnection, PgPool};
+use std::collections::BTreeSet;
+use tracing::{info, warn};
+
+use crate::errors::AppError;
+
+/// Create the partitions of these (year, month)s where missing
+pub async fn ensure(
+    conn: &mut PgConnection,
+    months: impl IntoIterator<Item = (i32, i32)>,
+) -> Result<(), AppError> {
+    let distinct: BTreeSet<(i32, i32)> = months.into_iter().collect();
+    let (years, months): (Vec<i32>, Vec<i32>) = distinct.into_iter().unzip();
+    sqlx::query(
+        "SELECT ensure_circle_member_fans_partition(y, m) FROM unnest($1::int[], $2::int[]) AS t(y, m)",
+    )
+    .bind(&years)
+    .bind(&months)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Move the partitions of months more than `keep_months` before the current
+/// one to the archive schema, returning their names
+pub async fn archive_older_than(pool: &PgPool, keep_months: u32) -> Result<Vec<String>, AppError> {
+    let (year, month) = crate::competition::current_month();
+    let (before_year, before_month) = crate::competition::add_months(year, month, -(keep_months as i32));
+
+    let mut tx = crate::database::begin_long_running(pool).await?;
+    let archived = sqlx::query_scalar::<_, String>(
+        "SELECT archive_circle_member_fans_partitions($1, $2)",
+    )
+    .bind(before_year)
+    .bind(before_month)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(archived)
+}
+
+/// Background task keeping upcoming partitions in place and archiving old ones
+pub async fn maintenance_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600)); // 1 hour
+    let retention_months = crate::config::get().circle_fans_retention_months;
+
+    info!("🗂️ Starting circle member fans partition maintenance (runs every hour)");
+
+    loop {
+        if crate::shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        let (year, month) = crate::competition::current_month();
+        let upcoming = [(year, month), crate::competition::add_months(year, month, 1)];
+        let ensured = async {
+            let mut conn = pool.acquire().await?;
+            ensure(&mut conn, upcoming).await
+        }
+        .await;
+        if let Err(e) = ensured {
+            warn!("⚠️ Failed to create circle member fans partitions: {}", e);
+        }
+
+        if let Some(keep_months) = retention_months {
+            match archive_older_than(&pool, keep_months).await {
+                Ok(archived) if !archived.is_empty() => {
+                    info!("🗂️ Archived circle member fans partitions: {}", archived.join(", "))
+                }
+                Ok(_) => {}
+                Err(e) => warn!("⚠️ Failed to archive circle member fans partitions: {}", e),
+            }
+        }
+    }
+}
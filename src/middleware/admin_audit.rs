@@ -0,0 +1,65 @@
+use axum::{
+    extract::{OriginalUri, State},
+    http::{header, Method},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::json;
+use tracing::warn;
+
+use super::api_key::ApiKeyIdentity;
+use crate::AppState;
+
+/// Record every admin request that changes something in admin_audit_log
+///
+/// Runs outside the admin auth check, so rejected attempts are logged with
+/// their 401/403 status as well. Handlers still write their own entries with
+/// operation details; this guarantees nothing is missed.
+pub async fn admin_audit_middleware(
+    State(state): State<AppState>,
+    request: axum::http::Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    if matches!(method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(request).await;
+    }
+
+    // The nested router sees paths without the /api/admin prefix
+    let uri = match request.extensions().get::<OriginalUri>() {
+        Some(OriginalUri(uri)) => uri.clone(),
+        None => request.uri().clone(),
+    };
+    let path = uri.path().to_string();
+    let query = uri.query().map(str::to_string);
+    let actor = match request.extensions().get::<ApiKeyIdentity>() {
+        Some(key) => format!("api_key:{} ({})", key.key_id, key.name),
+        None if request.headers().contains_key(header::AUTHORIZATION) => "admin_token".to_string(),
+        None => "anonymous".to_string(),
+    };
+
+    let response = next.run(request).await;
+    let status = response.status().as_u16();
+
+    // Written in the background so a slow insert doesn't hold up the response
+    let pool = state.db.clone();
+    tokio::spawn(async move {
+        let result = sqlx::query("INSERT INTO admin_audit_log (action, details) VALUES ($1, $2)")
+            .bind("admin.request")
+            .bind(json!({
+                "method": method.as_str(),
+                "path": path,
+                "query": query,
+                "status": status,
+                "actor": actor
+            }))
+            .execute(&pool)
+            .await;
+
+        if let Err(e) = result {
+            warn!("⚠️ Failed to write admin audit entry for {} {}: {}", method, path, e);
+        }
+    });
+
+    response
+}
@@ -0,0 +1,51 @@
+use axum::{
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use tracing::{error, warn};
+
+use super::api_key::{ApiKeyIdentity, ApiKeyScope};
+use crate::errors::AppError;
+
+/// Require `Authorization: Bearer <ADMIN_TOKEN>` (or an API key with the admin scope) on admin routes.
+/// If ADMIN_TOKEN is not configured, token access to the admin API is disabled.
+pub async fn admin_auth_middleware(
+    headers: HeaderMap,
+    request: axum::http::Request<axum::body::Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let api_key = request.extensions().get::<ApiKeyIdentity>();
+    if api_key.is_some_and(|key| key.has_scope(ApiKeyScope::Admin)) {
+        return Ok(next.run(request).await);
+    }
+
+    let Some(admin_token) = crate::config::get().admin_token.as_deref() else {
+        error!("ADMIN_TOKEN environment variable not set - admin API disabled");
+        return Err(AppError::ServiceUnavailable("Admin API disabled".to_string()));
+    };
+
+    let provided = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim());
+
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), admin_token.as_bytes()) => {
+            Ok(next.run(request).await)
+        }
+        Some(_) => {
+            warn!("Rejected admin request with invalid token: {}", request.uri().path());
+            Err(AppError::Forbidden("Invalid admin token".to_string()))
+        }
+        None => Err(AppError::Unauthorized("Missing admin token".to_string())),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,253 @@
+use axum::{
+    extract::State,
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use sha2::{Digest, Sha256};
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{error, warn};
+
+use crate::errors::AppError;
+use crate::AppState;
+
+/// Header trusted integrations send their key in
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// How long a looked-up key is trusted before it is read again, so a
+/// revoked key stops working on other instances within this time
+const KEY_CACHE_DURATION: Duration = Duration::from_secs(60);
+
+/// Cap on cached lookups, so requests with random keys can't grow the cache unbounded
+const MAX_CACHED_KEYS: usize = 10_000;
+
+/// Looked-up keys by hash; None for keys that don't exist or were revoked
+static KEY_CACHE: OnceLock<DashMap<String, (Option<ApiKeyIdentity>, Instant)>> = OnceLock::new();
+
+/// Requests per key in the current minute: key_id -> (minute, count)
+static RATE_WINDOWS: OnceLock<DashMap<i32, (u64, u32)>> = OnceLock::new();
+
+fn get_key_cache() -> &'static DashMap<String, (Option<ApiKeyIdentity>, Instant)> {
+    KEY_CACHE.get_or_init(DashMap::new)
+}
+
+fn get_rate_windows() -> &'static DashMap<i32, (u64, u32)> {
+    RATE_WINDOWS.get_or_init(DashMap::new)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKeyScope {
+    Search,
+    Tasks,
+    Circles,
+    Admin,
+}
+
+impl ApiKeyScope {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "search" => Some(Self::Search),
+            "tasks" => Some(Self::Tasks),
+            "circles" => Some(Self::Circles),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Search => "search",
+            Self::Tasks => "tasks",
+            Self::Circles => "circles",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// Scope a key needs for a path; None if any valid key may call it
+    fn required_for(path: &str) -> Option<Self> {
+        if path.starts_with("/api/admin") {
+            Some(Self::Admin)
+        } else if path.starts_with("/api/tasks") || path.starts_with("/api/v3/tasks") {
+            Some(Self::Tasks)
+        } else if path.starts_with("/api/v4/circles") {
+            Some(Self::Circles)
+        } else if path.starts_with("/api/v3/search") || path.starts_with("/api/v3/count") {
+            Some(Self::Search)
+        } else {
+            None
+        }
+    }
+}
+
+/// A verified API key, added to the request extensions
+#[derive(Debug, Clone)]
+pub struct ApiKeyIdentity {
+    pub key_id: i32,
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub rate_limit_per_minute: u32,
+}
+
+impl ApiKeyIdentity {
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// A new random key ("umk_" + 32 hex characters)
+pub fn generate_key() -> String {
+    format!("umk_{}", uuid::Uuid::new_v4().simple())
+}
+
+/// What is stored instead of the key itself
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Drop all looked-up keys, e.g. after a key was revoked
+pub fn forget_cached_keys() {
+    get_key_cache().clear();
+}
+
+/// Drop lookups and rate windows that have run out, returning how many lookups were removed
+pub fn cleanup_expired_keys() -> usize {
+    let cache = get_key_cache();
+    let before_count = cache.len();
+    cache.retain(|_, (_, looked_up)| looked_up.elapsed() < KEY_CACHE_DURATION);
+
+    let minute = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 60)
+        .unwrap_or(0);
+    get_rate_windows().retain(|_, (window_minute, _)| *window_minute >= minute);
+
+    before_count.saturating_sub(cache.len())
+}
+
+/// Authenticate requests carrying `X-Api-Key`
+///
+/// Requests without the header pass through unchanged. A valid key that has the
+/// scope the path needs is attached as ApiKeyIdentity, which lets the request skip
+/// Turnstile (and the admin token, for admin-scoped keys), and counts against the
+/// key's per-minute rate limit.
+pub async fn api_key_middleware(
+    State(state): State<AppState>,
+    mut request: axum::http::Request<axum::body::Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(provided) = request.headers().get(API_KEY_HEADER) else {
+        return Ok(next.run(request).await);
+    };
+    let provided = provided
+        .to_str()
+        .map_err(|_| AppError::BadRequest(format!("Invalid {} header", API_KEY_HEADER)))?
+        .trim();
+
+    let Some(identity) = lookup_key(&state, provided).await? else {
+        warn!("Rejected request with unknown API key: {}", request.uri().path());
+        return Err(AppError::Unauthorized("Unknown API key".to_string()));
+    };
+
+    if let Some(scope) = ApiKeyScope::required_for(request.uri().path()) {
+        if !identity.has_scope(scope) {
+            warn!(
+                "API key {} ({}) lacks the {} scope for {}",
+                identity.key_id,
+                identity.name,
+                scope.as_str(),
+                request.uri().path()
+            );
+            return Err(AppError::Forbidden(format!(
+                "API key lacks the {} scope",
+                scope.as_str()
+            )));
+        }
+    }
+
+    let (used, retry_after) = count_request(identity.key_id);
+    let limit = identity.rate_limit_per_minute;
+    if used > limit {
+        let mut response = AppError::RateLimited {
+            message: format!("API key rate limit of {} requests per minute exceeded", limit),
+            retry_after: Some(retry_after),
+        }
+        .into_response();
+        let headers = response.headers_mut();
+        headers.insert("x-ratelimit-limit", HeaderValue::from(limit));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(0));
+        return Ok(response);
+    }
+
+    request.extensions_mut().insert(identity);
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    headers.insert("x-ratelimit-limit", HeaderValue::from(limit));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(limit - used));
+    Ok(response)
+}
+
+/// The key's identity if it exists and isn't revoked
+async fn lookup_key(state: &AppState, key: &str) -> Result<Option<ApiKeyIdentity>, AppError> {
+    let key_hash = hash_key(key);
+    let cache = get_key_cache();
+    if let Some(entry) = cache.get(&key_hash) {
+        if entry.1.elapsed() < KEY_CACHE_DURATION {
+            return Ok(entry.0.clone());
+        }
+    }
+
+    // Reading the key also records that it's in use (at most once per cache period)
+    let row = sqlx::query_as::<_, (i32, String, Vec<String>, i32)>(
+        r#"
+        UPDATE api_keys
+        SET last_used_at = CURRENT_TIMESTAMP
+        WHERE key_hash = $1 AND revoked_at IS NULL
+        RETURNING key_id, name, scopes, rate_limit_per_minute
+        "#,
+    )
+    .bind(&key_hash)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| {
+        error!("Failed to look up API key: {}", e);
+        AppError::ServiceUnavailable("API keys can't be checked right now".to_string())
+    })?;
+
+    let identity = row.map(|(key_id, name, scopes, rate_limit_per_minute)| ApiKeyIdentity {
+        key_id,
+        name,
+        scopes: scopes.iter().filter_map(|s| ApiKeyScope::parse(s)).collect(),
+        rate_limit_per_minute: rate_limit_per_minute.max(1) as u32,
+    });
+
+    if cache.len() >= MAX_CACHED_KEYS {
+        cache.retain(|_, (_, looked_up)| looked_up.elapsed() < KEY_CACHE_DURATION);
+    }
+    if cache.len() < MAX_CACHED_KEYS || identity.is_some() {
+        cache.insert(key_hash, (identity.clone(), Instant::now()));
+    }
+    Ok(identity)
+}
+
+/// Count a request in the key's current one-minute window.
+/// Returns the requests made in the window so far and the seconds until it resets.
+fn count_request(key_id: i32) -> (u32, u64) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let minute = now / 60;
+
+    let mut window = get_rate_windows().entry(key_id).or_insert((minute, 0));
+    if window.0 != minute {
+        *window = (minute, 0);
+    }
+    window.1 = window.1.saturating_add(1);
+
+    (window.1, 60 - now % 60)
+}
@@ -0,0 +1,44 @@
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::net::SocketAddr;
+use tracing::debug;
+
+use super::api_key::{hash_key, API_KEY_HEADER};
+use super::turnstile::client_ip;
+use crate::errors::AppError;
+use crate::{bans, AppState};
+
+/// Refuse banned IPs and API keys before anything else handles the request
+///
+/// Also counts 429 responses towards an automatic ban of the client.
+pub async fn ban_middleware(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let client_ip = client_ip(request.headers(), addr);
+    let key_hash = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|key| hash_key(key.trim()));
+
+    if !bans::is_empty() {
+        if let Some(ban_id) = bans::find_ban(Some(client_ip), key_hash.as_deref()) {
+            debug!("Refused request from banned client {} (ban {})", client_ip, ban_id);
+            return AppError::Forbidden("This client is banned".to_string()).into_response();
+        }
+    }
+
+    let response = next.run(request).await;
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        bans::record_rate_limit_violation(&state.db, client_ip, key_hash);
+    }
+    response
+}
@@ -0,0 +1,117 @@
+use axum::{
+    body::Body,
+    extract::{OriginalUri, State},
+    http::{header, HeaderValue, Method, Request, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use super::response_cache::{CachedResponse, MAX_CACHED_BODY_BYTES};
+use crate::cache::Namespace;
+use crate::errors::{AppError, DatabaseUnavailable};
+
+/// Set to "true" on responses served from a fallback copy
+pub const DEGRADED_HEADER: &str = "X-Degraded";
+
+/// How long the last good response of a request is kept for degraded mode
+const FALLBACK_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// Which GET requests of a route keep a fallback copy for degraded mode
+#[derive(Clone, Copy)]
+pub struct DegradedFallback {
+    eligible: fn(&Uri) -> bool,
+}
+
+impl DegradedFallback {
+    /// Every GET request of the route
+    pub const ALL: Self = Self { eligible: |_| true };
+
+    /// Only GET requests for which `eligible` holds (e.g. unfiltered pages)
+    pub const fn when(eligible: fn(&Uri) -> bool) -> Self {
+        Self { eligible }
+    }
+}
+
+/// Answer from the last good response while the database is unreachable
+///
+/// Apply per route with `from_fn_with_state(DegradedFallback::ALL, degraded_middleware)`,
+/// outside any response cache layer. Successful responses of eligible requests
+/// are kept for a day; when the handler fails with an unreachable database
+/// (a 503 marked with `DatabaseUnavailable`), the kept copy is served with
+/// `"degraded": true` added to its JSON body and an `X-Degraded` header.
+/// Without a copy the 503 and its Retry-After pass through unchanged.
+pub async fn degraded_middleware(
+    State(fallback): State<DegradedFallback>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+
+    let uri = match request.extensions().get::<OriginalUri>() {
+        Some(OriginalUri(uri)) => uri.clone(),
+        None => request.uri().clone(),
+    };
+    if !(fallback.eligible)(&uri) {
+        return next.run(request).await;
+    }
+    let cache_key = Namespace::Fallback.key(uri.path_and_query().map(|p| p.as_str()).unwrap_or("/"));
+
+    let response = next.run(request).await;
+
+    if response.extensions().get::<DatabaseUnavailable>().is_some() {
+        return match crate::cache::get::<CachedResponse>(&cache_key).and_then(into_degraded) {
+            Some(degraded) => {
+                info!("🩹 Serving {} in degraded mode", cache_key);
+                degraded
+            }
+            None => response,
+        };
+    }
+
+    if response.status() != StatusCode::OK || response.headers().contains_key(header::SET_COOKIE) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("⚠️ Failed to buffer response for {}: {}", cache_key, e);
+            return AppError::Internal(format!("Failed to buffer response: {}", e))
+                .into_response();
+        }
+    };
+    if let Ok(body) = std::str::from_utf8(&bytes) {
+        if bytes.len() <= MAX_CACHED_BODY_BYTES {
+            let cached = CachedResponse::new(&parts.headers, body.to_string());
+            let _ = crate::cache::set(&cache_key, &cached, FALLBACK_TTL);
+        }
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// The kept response with the degraded flag added; None unless its body is a JSON object
+fn into_degraded(mut cached: CachedResponse) -> Option<Response> {
+    let mut body: serde_json::Value = serde_json::from_str(&cached.body).ok()?;
+    body.as_object_mut()?
+        .insert("degraded".to_string(), serde_json::Value::Bool(true));
+    cached.body = body.to_string();
+
+    // The body no longer matches the validators or length of the original
+    cached.headers.retain(|(name, _)| {
+        name != header::ETAG.as_str()
+            && name != header::LAST_MODIFIED.as_str()
+            && name != header::CONTENT_LENGTH.as_str()
+            && name != header::CACHE_CONTROL.as_str()
+    });
+
+    let mut response = cached.into_response("STALE");
+    let headers = response.headers_mut();
+    headers.insert(DEGRADED_HEADER, HeaderValue::from_static("true"));
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    Some(response)
+}
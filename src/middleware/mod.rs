@@ -1,4 +1,22 @@
+pub mod admin_audit;
+pub mod admin_auth;
+pub mod api_key;
+pub mod ban;
+pub mod degraded;
+pub mod request_id;
+pub mod response_cache;
 pub mod turnstile;
+pub mod user_auth;
+pub mod worker_auth;
+
+pub use admin_audit::admin_audit_middleware;
+pub use admin_auth::admin_auth_middleware;
+pub use api_key::api_key_middleware;
+pub use ban::ban_middleware;
+pub use degraded::{degraded_middleware, DegradedFallback};
+pub use response_cache::response_cache_middleware;
+pub use user_auth::user_auth_middleware;
+pub use worker_auth::worker_auth_middleware;
 
 // Re-export when turnstile verification is enabled
 // pub use turnstile::*;
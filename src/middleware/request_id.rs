@@ -0,0 +1,24 @@
+use axum::{body::Body, http::Request};
+use tracing::Span;
+
+/// Header carrying the request ID, set by SetRequestIdLayer and echoed in responses
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Span wrapping each request, so every log line it emits carries the request ID
+///
+/// Created at ERROR level only so it stays enabled when the filter is WARN;
+/// the span itself is never printed as an event.
+pub fn request_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-");
+
+    tracing::error_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id = %request_id,
+    )
+}
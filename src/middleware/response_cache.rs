@@ -0,0 +1,127 @@
+use axum::{
+    body::Body,
+    extract::{OriginalUri, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::cache::Namespace;
+use crate::errors::AppError;
+use crate::handlers::circles::etag_matches;
+
+/// Whether a response came from the response cache (HIT) or the handler (MISS)
+pub const CACHE_STATUS_HEADER: &str = "X-Cache";
+
+/// Largest response body kept in the cache
+pub(crate) const MAX_CACHED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// A successful response as stored in the cache
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CachedResponse {
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) body: String,
+}
+
+impl CachedResponse {
+    pub(crate) fn new(headers: &HeaderMap, body: String) -> Self {
+        Self {
+            headers: headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    value
+                        .to_str()
+                        .ok()
+                        .map(|value| (name.as_str().to_string(), value.to_string()))
+                })
+                .collect(),
+            body,
+        }
+    }
+
+    fn header(&self, name: &HeaderName) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name.as_str())
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn into_response(self, cache_status: &'static str) -> Response {
+        let mut response = Response::new(Body::from(self.body));
+        let headers = response.headers_mut();
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) = (name.parse::<HeaderName>(), HeaderValue::from_str(&value)) {
+                headers.append(name, value);
+            }
+        }
+        headers.insert(CACHE_STATUS_HEADER, HeaderValue::from_static(cache_status));
+        response
+    }
+}
+
+/// Cache whole GET responses of a route for `ttl`, keyed by path and query
+///
+/// Apply per route with `from_fn_with_state(ttl, response_cache_middleware)`.
+/// Only 200 responses with a UTF-8 body (up to 2 MB) and no Set-Cookie are
+/// stored. Cached responses carrying an ETag still answer a matching
+/// If-None-Match with 304.
+pub async fn response_cache_middleware(
+    State(ttl): State<Duration>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+
+    // Nested routers see paths without their prefix
+    let uri = match request.extensions().get::<OriginalUri>() {
+        Some(OriginalUri(uri)) => uri.clone(),
+        None => request.uri().clone(),
+    };
+    let cache_key = Namespace::Http.key(uri.path_and_query().map(|p| p.as_str()).unwrap_or("/"));
+
+    if let Some(cached) = crate::cache::get::<CachedResponse>(&cache_key) {
+        let not_modified = cached
+            .header(&header::ETAG)
+            .is_some_and(|etag| etag_matches(request.headers(), etag));
+        if not_modified {
+            let etag = cached.header(&header::ETAG).unwrap_or_default().to_string();
+            return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+        }
+        return cached.into_response("HIT");
+    }
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK || response.headers().contains_key(header::SET_COOKIE) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            // The body has been consumed, so the response can't be passed on either
+            warn!("⚠️ Failed to buffer response for {}: {}", cache_key, e);
+            return AppError::Internal(format!("Failed to buffer response: {}", e))
+                .into_response();
+        }
+    };
+    let body = match std::str::from_utf8(&bytes) {
+        Ok(body) if bytes.len() <= MAX_CACHED_BODY_BYTES => body.to_string(),
+        _ => {
+            let mut response = Response::from_parts(parts, Body::from(bytes));
+            response
+                .headers_mut()
+                .insert(CACHE_STATUS_HEADER, HeaderValue::from_static("MISS"));
+            return response;
+        }
+    };
+
+    let cached = CachedResponse::new(&parts.headers, body);
+    let _ = crate::cache::set(&cache_key, &cached, ttl);
+    cached.into_response("MISS")
+}
@@ -1,20 +1,28 @@
 #![allow(dead_code)]
 
 use axum::{
-    extract::ConnectInfo,
-    http::{HeaderMap, Method, StatusCode},
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, Method},
     middleware::Next,
     response::Response,
 };
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::{
-    net::SocketAddr,
-    sync::OnceLock,
-    time::{Duration, Instant},
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tracing::{error, warn};
 
+use crate::config::TurnstileConfig;
+use crate::errors::AppError;
+use crate::models::TurnstileStats;
+use crate::AppState;
+
 // Global token cache to allow reuse of validated tokens
 // Using OnceLock for thread-safe lazy initialization
 static TOKEN_CACHE: OnceLock<DashMap<String, Instant>> = OnceLock::new();
@@ -23,7 +31,7 @@ static TOKEN_CACHE: OnceLock<DashMap<String, Instant>> = OnceLock::new();
 const TOKEN_CACHE_DURATION: Duration = Duration::from_secs(300);
 
 fn get_token_cache() -> &'static DashMap<String, Instant> {
-    TOKEN_CACHE.get_or_init(|| DashMap::new())
+    TOKEN_CACHE.get_or_init(DashMap::new)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -44,13 +52,125 @@ struct TurnstileVerifyResponse {
 
 const TURNSTILE_VERIFY_URL: &str = "https://challenges.cloudflare.com/turnstile/v0/siteverify";
 
+// Clients that passed verification recently; may be let through while Cloudflare is down.
+// Keyed on `client_ip`, so a forged X-Forwarded-For can't claim someone else's standing.
+static KNOWN_GOOD_IPS: OnceLock<DashMap<IpAddr, Instant>> = OnceLock::new();
+
+// How long a client stays known-good after a successful verification
+const KNOWN_GOOD_DURATION: Duration = Duration::from_secs(24 * 3600);
+
+fn get_known_good_ips() -> &'static DashMap<IpAddr, Instant> {
+    KNOWN_GOOD_IPS.get_or_init(DashMap::new)
+}
+
+fn config() -> &'static TurnstileConfig {
+    &crate::config::get().turnstile
+}
+
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+fn http_client() -> &'static reqwest::Client {
+    HTTP_CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(config().timeout)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+// Circuit breaker: consecutive failed verifications, and when it closes again (unix seconds, 0 = closed)
+static CONSECUTIVE_FAILURES: AtomicU32 = AtomicU32::new(0);
+static BREAKER_OPEN_UNTIL: AtomicU64 = AtomicU64::new(0);
+
+// Verification counters since startup
+static VERIFIED: AtomicU64 = AtomicU64::new(0);
+static REJECTED: AtomicU64 = AtomicU64::new(0);
+static ERRORS: AtomicU64 = AtomicU64::new(0);
+static FAIL_OPEN_ALLOWED: AtomicU64 = AtomicU64::new(0);
+static BREAKER_TRIPS: AtomicU64 = AtomicU64::new(0);
+static LATENCY_TOTAL_MS: AtomicU64 = AtomicU64::new(0);
+static LATENCY_COUNT: AtomicU64 = AtomicU64::new(0);
+static LATENCY_MAX_MS: AtomicU64 = AtomicU64::new(0);
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn breaker_open() -> bool {
+    unix_secs() < BREAKER_OPEN_UNTIL.load(Ordering::Acquire)
+}
+
+fn record_success() {
+    CONSECUTIVE_FAILURES.store(0, Ordering::Release);
+    BREAKER_OPEN_UNTIL.store(0, Ordering::Release);
+}
+
+// Once the threshold is reached, every further failure (e.g. the first call
+// after the cooldown) opens the breaker again
+fn record_failure() {
+    let config = config();
+    let failures = CONSECUTIVE_FAILURES.fetch_add(1, Ordering::AcqRel) + 1;
+    if failures < config.breaker_threshold {
+        return;
+    }
+
+    let now = unix_secs();
+    let previous = BREAKER_OPEN_UNTIL.swap(now + config.breaker_cooldown_secs, Ordering::AcqRel);
+    if previous <= now {
+        BREAKER_TRIPS.fetch_add(1, Ordering::Relaxed);
+        error!(
+            "Turnstile circuit breaker opened after {} consecutive failures (cooldown {}s)",
+            failures, config.breaker_cooldown_secs
+        );
+    }
+}
+
+fn record_latency(elapsed: Duration) {
+    let ms = elapsed.as_millis() as u64;
+    LATENCY_TOTAL_MS.fetch_add(ms, Ordering::Relaxed);
+    LATENCY_COUNT.fetch_add(1, Ordering::Relaxed);
+    LATENCY_MAX_MS.fetch_max(ms, Ordering::Relaxed);
+}
+
+/// Verification outcomes, latency and circuit breaker state since startup
+pub fn stats() -> TurnstileStats {
+    let open_until = BREAKER_OPEN_UNTIL.load(Ordering::Acquire);
+    let calls = LATENCY_COUNT.load(Ordering::Relaxed);
+
+    TurnstileStats {
+        breaker_open: breaker_open(),
+        breaker_open_until: (unix_secs() < open_until)
+            .then(|| chrono::DateTime::from_timestamp(open_until as i64, 0))
+            .flatten(),
+        consecutive_failures: CONSECUTIVE_FAILURES.load(Ordering::Acquire),
+        breaker_trips: BREAKER_TRIPS.load(Ordering::Relaxed),
+        fail_open: config().fail_open,
+        verified: VERIFIED.load(Ordering::Relaxed),
+        rejected: REJECTED.load(Ordering::Relaxed),
+        errors: ERRORS.load(Ordering::Relaxed),
+        fail_open_allowed: FAIL_OPEN_ALLOWED.load(Ordering::Relaxed),
+        calls,
+        avg_latency_ms: if calls > 0 {
+            LATENCY_TOTAL_MS.load(Ordering::Relaxed) as f64 / calls as f64
+        } else {
+            0.0
+        },
+        max_latency_ms: LATENCY_MAX_MS.load(Ordering::Relaxed),
+        known_good_ips: get_known_good_ips().len(),
+    }
+}
+
 pub async fn turnstile_verification_middleware(
+    State(state): State<AppState>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
     method: Method,
     request: axum::http::Request<axum::body::Body>,
     next: Next,
-) -> Result<Response, StatusCode> {
+) -> Result<Response, AppError> {
     // Only verify POST requests
     if method != Method::POST {
         return Ok(next.run(request).await);
@@ -65,47 +185,71 @@ pub async fn turnstile_verification_middleware(
         return Ok(next.run(request).await);
     }
 
-    // Skip Turnstile verification in development mode
-    if std::env::var("TURNSTILE_BYPASS").unwrap_or_default() == "true" {
-        tracing::info!("Turnstile verification bypassed for development");
+    // Trusted integrations authenticate with an API key instead
+    if request.extensions().get::<super::api_key::ApiKeyIdentity>().is_some() {
         return Ok(next.run(request).await);
     }
 
-    // Get secret key from environment
-    let secret_key = std::env::var("TURNSTILE_SECRET_KEY").unwrap_or_else(|_| {
-        error!("TURNSTILE_SECRET_KEY environment variable not set");
-        String::new()
-    });
+    verify_request(&state, &headers, client_ip(&headers, addr), TokenUse::Reusable).await?;
+    Ok(next.run(request).await)
+}
 
-    if secret_key.is_empty() {
-        error!("Turnstile secret key is empty - consider setting TURNSTILE_BYPASS=true for development");
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+/// Whether a verified token may be used again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenUse {
+    /// Accepted again for TOKEN_CACHE_DURATION, and known-good clients are
+    /// let through while Cloudflare is unreachable (if TURNSTILE_FAIL_OPEN)
+    Reusable,
+    /// Always checked with Cloudflare, which accepts each token once; for
+    /// writes limited per client, such as votes
+    SingleUse,
+}
+
+/// Check the request's CF-Turnstile-Token, for handlers that need Turnstile
+/// on methods the middleware doesn't cover
+///
+/// Fails with bad_request for a malformed header, captcha_failed for a missing
+/// or rejected token and service_unavailable if Cloudflare can't be reached.
+pub(crate) async fn verify_request(
+    state: &AppState,
+    headers: &HeaderMap,
+    client_ip: IpAddr,
+    token_use: TokenUse,
+) -> Result<(), AppError> {
+    // Skip Turnstile verification in development mode
+    if config().bypass {
+        tracing::info!("Turnstile verification bypassed for development");
+        return Ok(());
     }
 
+    // Get secret key from environment
+    let Some(secret_key) = config().secret_key.as_deref() else {
+        error!("TURNSTILE_SECRET_KEY not set - consider setting TURNSTILE_BYPASS=true for development");
+        return Err(AppError::Internal("Turnstile is not configured".to_string()));
+    };
+
     // Extract Turnstile token from headers
     let turnstile_token = match headers.get("CF-Turnstile-Token") {
         Some(token_header) => match token_header.to_str() {
             Ok(token) => token,
             Err(_) => {
                 warn!("Invalid Turnstile token header format");
-                return Err(StatusCode::BAD_REQUEST);
+                return Err(AppError::BadRequest("Invalid Turnstile token header".to_string()));
             }
         },
         None => {
-            warn!("Missing Turnstile token in POST request");
-            return Err(StatusCode::FORBIDDEN);
+            warn!("Missing Turnstile token");
+            return Err(AppError::Captcha("Missing Turnstile token".to_string()));
         }
     };
 
-    // Get client IP for verification
-    let client_ip = extract_client_ip(&headers, addr);
-
     // Check if token is cached and still valid
     let now = Instant::now();
     let token_cache = get_token_cache();
-    if let Some(cached_time) = token_cache.get(turnstile_token) {
-        if now.duration_since(*cached_time) < TOKEN_CACHE_DURATION {
-            return Ok(next.run(request).await);
+    let cached_time = token_cache.get(turnstile_token).map(|entry| *entry);
+    if let Some(cached_time) = cached_time.filter(|_| token_use == TokenUse::Reusable) {
+        if now.duration_since(cached_time) < TOKEN_CACHE_DURATION {
+            return Ok(());
         } else {
             // Token expired, remove from cache
             token_cache.remove(turnstile_token);
@@ -113,29 +257,129 @@ pub async fn turnstile_verification_middleware(
     }
 
     // Verify token with Cloudflare
-    match verify_turnstile_token(turnstile_token, &client_ip, &secret_key).await {
-        Ok(true) => {
-            // Cache the successful token
+    match verify_with_retries(turnstile_token, &client_ip.to_string(), secret_key).await {
+        Verification::Valid => {
+            // Cache the successful token and remember the client as known-good
             token_cache.insert(turnstile_token.to_string(), now);
-            Ok(next.run(request).await)
+            get_known_good_ips().insert(client_ip, now);
+            Ok(())
         }
-        Ok(false) => {
+        Verification::Invalid => {
             warn!("Turnstile verification failed for IP: {}", client_ip);
-            Err(StatusCode::FORBIDDEN)
+            crate::bans::record_turnstile_failure(&state.db, client_ip);
+            Err(AppError::Captcha("Turnstile verification failed".to_string()))
         }
-        Err(e) => {
-            error!("Turnstile verification error: {}", e);
-            Err(StatusCode::SERVICE_UNAVAILABLE)
+        Verification::Unavailable(reason) => {
+            // Fail-open trusts the client's address, so it needs TRUSTED_PROXIES (or
+            // the socket mode proxy) to resolve one. A request that reached us without
+            // a forwarded address shows up as one of our proxies and vouches for nobody.
+            let app_config = crate::config::get();
+            let resolves_clients =
+                !app_config.trusted_proxies.is_empty() || app_config.unix_socket_path.is_some();
+            let is_proxy = app_config
+                .trusted_proxies
+                .iter()
+                .any(|net| net.contains(&client_ip));
+            let known_good = resolves_clients
+                && !is_proxy
+                && get_known_good_ips()
+                    .get(&client_ip)
+                    .is_some_and(|verified| now.duration_since(*verified) < KNOWN_GOOD_DURATION);
+
+            if config().fail_open && known_good && token_use == TokenUse::Reusable {
+                FAIL_OPEN_ALLOWED.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "Turnstile unavailable ({}), letting known-good IP through: {}",
+                    reason, client_ip
+                );
+                Ok(())
+            } else {
+                error!("Turnstile verification error: {}", reason);
+                Err(AppError::ServiceUnavailable(
+                    "Turnstile verification is unavailable, try again later".to_string(),
+                ))
+            }
         }
     }
 }
 
+/// Verify the request's Turnstile token for handlers outside the Turnstile middleware
+pub(crate) async fn require_turnstile(
+    state: &AppState,
+    headers: &HeaderMap,
+    addr: SocketAddr,
+) -> Result<(), AppError> {
+    let client_ip = client_ip(headers, addr);
+    verify_request(state, headers, client_ip, TokenUse::Reusable).await
+}
+
+/// Like `require_turnstile`, but the token counts once: no cached
+/// verification and no fail-open
+pub(crate) async fn require_single_use_turnstile(
+    state: &AppState,
+    headers: &HeaderMap,
+    addr: SocketAddr,
+) -> Result<(), AppError> {
+    let client_ip = client_ip(headers, addr);
+    verify_request(state, headers, client_ip, TokenUse::SingleUse).await
+}
+
+enum Verification {
+    Valid,
+    Invalid,
+    /// Cloudflare couldn't be reached (or the circuit breaker is open)
+    Unavailable(String),
+}
+
+/// Verify a token, retrying transient errors with a short backoff
+///
+/// Consecutive failed verifications open the circuit breaker, after which
+/// Cloudflare isn't called until the cooldown has passed.
+async fn verify_with_retries(token: &str, client_ip: &str, secret_key: &str) -> Verification {
+    let config = config();
+    if breaker_open() {
+        return Verification::Unavailable("circuit breaker open".to_string());
+    }
+
+    let mut last_error = String::new();
+    for attempt in 0..=config.max_retries {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_millis(200 * attempt as u64)).await;
+        }
+
+        let started = Instant::now();
+        let result = verify_turnstile_token(token, client_ip, secret_key).await;
+        record_latency(started.elapsed());
+
+        match result {
+            Ok(valid) => {
+                record_success();
+                return if valid {
+                    VERIFIED.fetch_add(1, Ordering::Relaxed);
+                    Verification::Valid
+                } else {
+                    REJECTED.fetch_add(1, Ordering::Relaxed);
+                    Verification::Invalid
+                };
+            }
+            Err(e) => {
+                warn!("Turnstile verification attempt {} failed: {}", attempt + 1, e);
+                last_error = e.to_string();
+            }
+        }
+    }
+
+    ERRORS.fetch_add(1, Ordering::Relaxed);
+    record_failure();
+    Verification::Unavailable(last_error)
+}
+
 async fn verify_turnstile_token(
     token: &str,
     client_ip: &str,
     secret_key: &str,
 ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-    let client = reqwest::Client::new();
+    let client = http_client();
 
     let verify_request = TurnstileVerifyRequest {
         secret: secret_key.to_string(),
@@ -168,45 +412,150 @@ async fn verify_turnstile_token(
     Ok(true)
 }
 
-fn extract_client_ip(headers: &HeaderMap, addr: SocketAddr) -> String {
-    // Check for common proxy headers
-    if let Some(forwarded_for) = headers.get("X-Forwarded-For") {
-        if let Ok(forwarded_str) = forwarded_for.to_str() {
-            // Take the first IP in the chain
-            if let Some(first_ip) = forwarded_str.split(',').next() {
-                return first_ip.trim().to_string();
+/// The client's IP address
+///
+/// Forwarding headers are only believed when the peer is one of
+/// TRUSTED_PROXIES (or connected over UNIX_SOCKET_PATH, which only the proxy
+/// can reach), since anyone can send them. X-Forwarded-For is read from the
+/// right, skipping trusted proxies, so a client can't get a forged address in
+/// front of the one its proxy appended.
+pub(crate) fn client_ip(headers: &HeaderMap, addr: SocketAddr) -> IpAddr {
+    let config = crate::config::get();
+    let peer = addr.ip().to_canonical();
+    let is_trusted = |ip: &IpAddr| config.trusted_proxies.iter().any(|net| net.contains(ip));
+    if config.unix_socket_path.is_none() && !is_trusted(&peer) {
+        return peer;
+    }
+
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+    // The last hop not added by one of our proxies; if they all were, the
+    // leftmost one that parses
+    let rightmost_untrusted = |hops: Vec<&str>| {
+        let mut client = None;
+        for hop in hops.into_iter().rev() {
+            let Some(ip) = parse_forwarded_ip(hop) else {
+                break;
+            };
+            client = Some(ip);
+            if !is_trusted(&ip) {
+                break;
             }
         }
-    }
+        client
+    };
 
-    if let Some(real_ip) = headers.get("X-Real-IP") {
-        if let Ok(real_ip_str) = real_ip.to_str() {
-            return real_ip_str.to_string();
+    if let Some(forwarded_for) = header("X-Forwarded-For") {
+        if let Some(ip) = rightmost_untrusted(forwarded_for.split(',').collect()) {
+            return ip;
         }
     }
 
-    if let Some(forwarded) = headers.get("Forwarded") {
-        if let Ok(forwarded_str) = forwarded.to_str() {
-            // Parse RFC 7239 Forwarded header
-            for pair in forwarded_str.split(';') {
-                if let Some((key, value)) = pair.split_once('=') {
-                    if key.trim().eq_ignore_ascii_case("for") {
-                        return value.trim().trim_matches('"').to_string();
-                    }
-                }
-            }
+    if let Some(ip) = header("X-Real-IP").and_then(parse_forwarded_ip) {
+        return ip;
+    }
+
+    if let Some(forwarded) = header("Forwarded") {
+        // RFC 7239: comma-separated elements of ;-separated pairs
+        let hops = forwarded
+            .split(',')
+            .filter_map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (key, value) = pair.split_once('=')?;
+                    key.trim().eq_ignore_ascii_case("for").then_some(value)
+                })
+            })
+            .collect();
+        if let Some(ip) = rightmost_untrusted(hops) {
+            return ip;
         }
     }
 
-    // Fall back to direct connection IP
-    addr.ip().to_string()
+    peer
+}
+
+pub(crate) fn extract_client_ip(headers: &HeaderMap, addr: SocketAddr) -> String {
+    client_ip(headers, addr).to_string()
+}
+
+/// An address from a forwarding header: "203.0.113.7", "203.0.113.7:4711"
+/// or "[2001:db8::1]:4711", optionally in double quotes
+fn parse_forwarded_ip(value: &str) -> Option<IpAddr> {
+    let value = value.trim().trim_matches('"');
+    if let Ok(ip) = value.parse::<IpAddr>() {
+        return Some(ip.to_canonical());
+    }
+    if let Ok(addr) = value.parse::<SocketAddr>() {
+        return Some(addr.ip().to_canonical());
+    }
+    value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+        .and_then(|value| value.parse::<IpAddr>().ok())
+        .map(|ip| ip.to_canonical())
 }
 
-// Cleanup function to remove expired tokens from cache
-// This should be called periodically to prevent memory leaks
-#[allow(dead_code)]
-pub fn cleanup_expired_tokens() {
+// Cleanup function to remove expired tokens from cache, returning how many were removed
+// Called periodically by the token cleanup task to prevent memory leaks
+pub fn cleanup_expired_tokens() -> usize {
     let now = Instant::now();
     let token_cache = get_token_cache();
+    let before_count = token_cache.len();
     token_cache.retain(|_, cached_time| now.duration_since(*cached_time) < TOKEN_CACHE_DURATION);
+    get_known_good_ips().retain(|_, verified| now.duration_since(*verified) < KNOWN_GOOD_DURATION);
+    before_count.saturating_sub(token_cache.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(headers: &[(&str, &str)], peer: &str) -> String {
+        crate::config::init_for_tests();
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.insert(
+                axum::http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+        }
+        extract_client_ip(&map, peer.parse().unwrap())
+    }
+
+    #[test]
+    fn ignores_forwarding_headers_from_untrusted_peers() {
+        let headers = [
+            ("X-Forwarded-For", "198.51.100.7"),
+            ("X-Real-IP", "198.51.100.8"),
+            ("Forwarded", "for=198.51.100.9"),
+        ];
+        assert_eq!(ip(&headers, "203.0.113.5:4000"), "203.0.113.5");
+        assert_eq!(ip(&[], "[::ffff:203.0.113.5]:4000"), "203.0.113.5");
+    }
+
+    #[test]
+    fn takes_rightmost_untrusted_forwarded_hop() {
+        // The client prepended a forged hop; the proxy appended the real one
+        let headers = [("X-Forwarded-For", "198.51.100.7, 203.0.113.9")];
+        assert_eq!(ip(&headers, "10.0.0.2:4000"), "203.0.113.9");
+
+        // Hops added by our own proxies are skipped
+        let headers = [("X-Forwarded-For", "203.0.113.9, 10.0.0.3")];
+        assert_eq!(ip(&headers, "10.0.0.2:4000"), "203.0.113.9");
+
+        // Garbage stops the walk at the last address that parsed
+        let headers = [("X-Forwarded-For", "garbage, 10.0.0.3")];
+        assert_eq!(ip(&headers, "10.0.0.2:4000"), "10.0.0.3");
+    }
+
+    #[test]
+    fn reads_other_forwarding_headers_from_trusted_peers() {
+        let headers = [("X-Real-IP", "203.0.113.9")];
+        assert_eq!(ip(&headers, "10.0.0.2:4000"), "203.0.113.9");
+
+        let headers = [("Forwarded", "for=198.51.100.7, for=\"[2001:db8::1]:4711\";proto=https")];
+        assert_eq!(ip(&headers, "10.0.0.2:4000"), "2001:db8::1");
+
+        assert_eq!(ip(&[], "10.0.0.2:4000"), "10.0.0.2");
+    }
 }
@@ -0,0 +1,171 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, SubsecRound, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::errors::AppError;
+use crate::AppState;
+
+/// The signed-in user, attached to requests by user_auth_middleware
+#[derive(Debug, Clone, Copy)]
+pub struct UserIdentity {
+    pub user_id: i64,
+}
+
+/// USER_TOKEN_SECRET, or 503 when accounts are disabled
+pub(crate) fn token_secret() -> Result<&'static str, AppError> {
+    crate::config::get()
+        .users
+        .token_secret
+        .as_deref()
+        .ok_or_else(|| AppError::ServiceUnavailable("User accounts are not enabled".to_string()))
+}
+
+fn token_mac(secret: &str, user_id: i64, version: i32, expires_at: i64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(format!("user:{}:{}:{}", user_id, version, expires_at).as_bytes());
+    mac
+}
+
+/// Token for a user: "{user_id}.{version}.{expires_at}.{hex HMAC-SHA256 of
+/// "user:{user_id}:{version}:{expires_at}"}", with expires_at in unix seconds
+///
+/// Tokens expire after USER_TOKEN_TTL_DAYS, and stop working when the account's
+/// token_version is bumped, the account is deleted or USER_TOKEN_SECRET changes.
+pub fn issue_token(user_id: i64, version: i32) -> Result<(String, DateTime<Utc>), AppError> {
+    let secret = token_secret()?;
+    let ttl = chrono::Duration::from_std(crate::config::get().users.token_ttl)
+        .map_err(|e| AppError::Internal(format!("Invalid user token lifetime: {}", e)))?;
+    // Whole seconds, as in the token
+    let expires_at = (Utc::now() + ttl).trunc_subsecs(0);
+    let signature = token_mac(secret, user_id, version, expires_at.timestamp())
+        .finalize()
+        .into_bytes();
+    let token = format!(
+        "{}.{}.{}.{}",
+        user_id,
+        version,
+        expires_at.timestamp(),
+        hex::encode(signature)
+    );
+    Ok((token, expires_at))
+}
+
+/// The (user_id, token version) of a correctly signed token that hasn't expired at `now`
+fn verify_token(secret: &str, token: &str, now: i64) -> Result<(i64, i32), AppError> {
+    let invalid = || AppError::Unauthorized("Invalid user token".to_string());
+    let mut parts = token.splitn(4, '.');
+    let (Some(user_id), Some(version), Some(expires_at), Some(signature)) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    let user_id: i64 = user_id.parse().map_err(|_| invalid())?;
+    let version: i32 = version.parse().map_err(|_| invalid())?;
+    let expires_at: i64 = expires_at.parse().map_err(|_| invalid())?;
+    let signature = hex::decode(signature).map_err(|_| invalid())?;
+    token_mac(secret, user_id, version, expires_at)
+        .verify_slice(&signature)
+        .map_err(|_| invalid())?;
+
+    if expires_at <= now {
+        return Err(AppError::Unauthorized("User token expired".to_string()));
+    }
+    Ok((user_id, version))
+}
+
+/// The user a bearer token belongs to, if the request carries one
+///
+/// A token that is malformed, wrongly signed, expired, revoked or belongs to a
+/// deleted account is rejected rather than treated as absent.
+async fn authenticate(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Option<UserIdentity>, AppError> {
+    let Some(token) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+    else {
+        return Ok(None);
+    };
+    let (user_id, version) = verify_token(token_secret()?, token, Utc::now().timestamp())?;
+
+    // A bumped token_version (or a deleted account) revokes the token
+    let current = sqlx::query(
+        "UPDATE users SET last_seen_at = CURRENT_TIMESTAMP WHERE user_id = $1 AND token_version = $2",
+    )
+    .bind(user_id)
+    .bind(version)
+    .execute(&state.db)
+    .await?
+    .rows_affected()
+        > 0;
+    if !current {
+        return Err(AppError::Unauthorized("Invalid user token".to_string()));
+    }
+    Ok(Some(UserIdentity { user_id }))
+}
+
+/// Attach the user of an `Authorization: Bearer <user token>` (see
+/// issue_token) as UserIdentity
+///
+/// Requests without a token pass through unchanged; handlers that need an
+/// account check for the identity. Invalid tokens are rejected with 401.
+pub async fn user_auth_middleware(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut request: axum::http::Request<axum::body::Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let Some(identity) = authenticate(&state, &headers).await? {
+        request.extensions_mut().insert(identity);
+    }
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+
+    fn token(user_id: i64, version: i32, expires_at: i64) -> String {
+        let signature = token_mac(SECRET, user_id, version, expires_at).finalize().into_bytes();
+        format!("{}.{}.{}.{}", user_id, version, expires_at, hex::encode(signature))
+    }
+
+    #[test]
+    fn accepts_unexpired_token() {
+        let token = token(42, 3, 2_000);
+        assert_eq!(verify_token(SECRET, &token, 1_000).unwrap(), (42, 3));
+    }
+
+    #[test]
+    fn rejects_expired_token() {
+        let token = token(42, 3, 2_000);
+        assert!(verify_token(SECRET, &token, 2_000).is_err());
+    }
+
+    #[test]
+    fn rejects_altered_claims() {
+        let token = token(42, 3, 2_000);
+        let signature = token.rsplit('.').next().unwrap();
+
+        // Another user, an older version or a later expiry under the same signature
+        for altered in ["43.3.2000", "42.2.2000", "42.3.9000"] {
+            let altered = format!("{}.{}", altered, signature);
+            assert!(verify_token(SECRET, &altered, 1_000).is_err());
+        }
+        assert!(verify_token("other-secret", &token, 1_000).is_err());
+        // The unversioned format tokens used to have
+        assert!(verify_token(SECRET, "42.abcdef", 1_000).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, OriginalUri},
+    http::{HeaderMap, Request},
+    middleware::Next,
+    response::Response,
+};
+use dashmap::DashMap;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::{error, warn};
+
+use super::turnstile::extract_client_ip;
+use crate::errors::AppError;
+
+/// Unix seconds the request was signed at
+pub const WORKER_TIMESTAMP_HEADER: &str = "X-Worker-Timestamp";
+/// Hex HMAC-SHA256 of the signing string (see `signing_payload`)
+pub const WORKER_SIGNATURE_HEADER: &str = "X-Worker-Signature";
+
+/// Signatures already accepted, with their timestamp, so a captured request
+/// can't be replayed while its timestamp is still within the allowed age
+static SEEN_SIGNATURES: OnceLock<DashMap<String, u64>> = OnceLock::new();
+
+fn get_seen_signatures() -> &'static DashMap<String, u64> {
+    SEEN_SIGNATURES.get_or_init(DashMap::new)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// What workers sign: "{timestamp}\n{METHOD}\n{path?query}\n" followed by the raw body
+pub(crate) fn signing_payload(timestamp: &str, method: &str, path_and_query: &str, body: &[u8]) -> Vec<u8> {
+    let mut payload = format!("{}\n{}\n{}\n", timestamp, method, path_and_query).into_bytes();
+    payload.extend_from_slice(body);
+    payload
+}
+
+/// Drop remembered signatures too old to be accepted again, returning how many were removed
+pub fn cleanup_seen_signatures() -> usize {
+    let max_age = crate::config::get().worker_signature_max_age.as_secs();
+    let oldest = now_secs().saturating_sub(max_age);
+    let seen = get_seen_signatures();
+    let before_count = seen.len();
+    seen.retain(|_, timestamp| *timestamp >= oldest);
+    before_count.saturating_sub(seen.len())
+}
+
+/// Whether the client may call worker endpoints (WORKER_ALLOWED_IPS, when set)
+pub(crate) fn is_allowed_worker_ip(client_ip: &str) -> bool {
+    let allowed_ips = &crate::config::get().worker_allowed_ips;
+    allowed_ips.is_empty()
+        || client_ip
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| allowed_ips.iter().any(|net| net.contains(&ip)))
+}
+
+/// Check a worker signature: the timestamp must be fresh, the signature a
+/// valid HMAC of `payload` under `secret`, and not seen before
+pub(crate) fn verify_signature(
+    secret: &str,
+    timestamp: &str,
+    signature: &str,
+    payload: &[u8],
+    client_ip: &str,
+) -> Result<(), AppError> {
+    let invalid = || AppError::Unauthorized("Invalid worker signature".to_string());
+    let signed_at = timestamp.parse::<u64>().map_err(|_| invalid())?;
+    if now_secs().abs_diff(signed_at) > crate::config::get().worker_signature_max_age.as_secs() {
+        warn!("Rejected worker request with stale timestamp {} from {}", signed_at, client_ip);
+        return Err(invalid());
+    }
+    let signature_bytes = hex::decode(signature).map_err(|_| invalid())?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    if mac.verify_slice(&signature_bytes).is_err() {
+        warn!("Rejected worker request with invalid signature from {}", client_ip);
+        return Err(invalid());
+    }
+
+    if get_seen_signatures()
+        .insert(signature.to_ascii_lowercase(), signed_at)
+        .is_some()
+    {
+        warn!("Rejected replayed worker request from {}", client_ip);
+        return Err(invalid());
+    }
+
+    Ok(())
+}
+
+/// Restrict worker endpoints to known workers
+///
+/// The client IP (the peer address, or what a TRUSTED_PROXIES proxy forwarded)
+/// must be in WORKER_ALLOWED_IPS (when set), and the request must
+/// carry a fresh timestamp and an HMAC-SHA256 signature made with
+/// WORKER_SIGNING_SECRET over the timestamp, method, path and body. Each
+/// signature is accepted once, so requests can't be replayed.
+pub async fn worker_auth_middleware(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    request: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let config = crate::config::get();
+
+    let client_ip = extract_client_ip(&headers, addr);
+    if !is_allowed_worker_ip(&client_ip) {
+        warn!("Rejected worker request from non-allowed IP: {}", client_ip);
+        return Err(AppError::Forbidden("Not allowed".to_string()));
+    }
+
+    let Some(secret) = config.worker_signing_secret.as_deref() else {
+        error!("WORKER_SIGNING_SECRET not set - worker endpoints disabled");
+        return Err(AppError::ServiceUnavailable("Worker API disabled".to_string()));
+    };
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+    let (Some(timestamp), Some(signature)) =
+        (header(WORKER_TIMESTAMP_HEADER), header(WORKER_SIGNATURE_HEADER))
+    else {
+        return Err(AppError::Unauthorized("Missing worker signature".to_string()));
+    };
+
+    // The nested router sees paths without the /api/workers prefix
+    let uri = match request.extensions().get::<OriginalUri>() {
+        Some(OriginalUri(uri)) => uri.clone(),
+        None => request.uri().clone(),
+    };
+    let path_and_query = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, config.max_request_body_bytes)
+        .await
+        .map_err(|_| AppError::PayloadTooLarge("Request body too large".to_string()))?;
+
+    verify_signature(
+        secret,
+        timestamp,
+        signature,
+        &signing_payload(timestamp, parts.method.as_str(), path_and_query, &body),
+        &client_ip,
+    )?;
+
+    Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+
+    fn sign(payload: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(payload);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn check(timestamp: &str, signature: &str, payload: &[u8]) -> Result<(), AppError> {
+        crate::config::init_for_tests();
+        verify_signature(SECRET, timestamp, signature, payload, "192.0.2.1")
+    }
+
+    #[test]
+    fn accepts_fresh_signature_once() {
+        let timestamp = now_secs().to_string();
+        let payload = signing_payload(&timestamp, "POST", "/api/workers/claim", b"{\"once\":1}");
+        let signature = sign(&payload);
+
+        assert!(check(&timestamp, &signature, &payload).is_ok());
+        // The same signature again is a replay
+        assert!(check(&timestamp, &signature, &payload).is_err());
+    }
+
+    #[test]
+    fn rejects_stale_timestamp() {
+        let timestamp = (now_secs() - 3600).to_string();
+        let payload = signing_payload(&timestamp, "POST", "/api/workers/claim", b"{}");
+        assert!(check(&timestamp, &sign(&payload), &payload).is_err());
+
+        let future = (now_secs() + 3600).to_string();
+        let payload = signing_payload(&future, "POST", "/api/workers/claim", b"{}");
+        assert!(check(&future, &sign(&payload), &payload).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_mac() {
+        let timestamp = now_secs().to_string();
+        let payload = signing_payload(&timestamp, "POST", "/api/workers/claim", b"{\"a\":1}");
+        let signature = sign(&payload);
+
+        // Signed for a different body
+        let altered = signing_payload(&timestamp, "POST", "/api/workers/claim", b"{\"a\":2}");
+        assert!(check(&timestamp, &signature, &altered).is_err());
+        // Signed with a different key
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"other-secret").unwrap();
+        mac.update(&payload);
+        let forged = hex::encode(mac.finalize().into_bytes());
+        assert!(check(&timestamp, &forged, &payload).is_err());
+        // Not hex, or not a number
+        assert!(check(&timestamp, "not-hex", &payload).is_err());
+        assert!(check("yesterday", &signature, &payload).is_err());
+    }
+}
@@ -1,18 +1,6 @@
-// Re-export all model types from submodules
-mod circles;
-mod common;
-mod inheritance;
-mod search;
+// API request/response types live in the umamoe-api-types crate so other Rust
+// consumers can share them; only server-internal models are defined here
 mod sharing;
-mod stats;
-mod support_cards;
-mod tasks;
 
-// Re-export everything from each module except common (items from common are imported directly where needed)
-pub use circles::*;
-pub use inheritance::*;
-pub use search::*;
 pub use sharing::*;
-pub use stats::*;
-pub use support_cards::*;
-pub use tasks::*;
+pub use umamoe_api_types::*;
@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
 
+use umamoe_api_types::{AccountId, CircleId};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InheritanceShareData {
-    pub account_id: String,
+    pub account_id: AccountId,
+    pub inheritance_id: i32,
+    /// Canonical URL of the share page (og:url)
+    pub share_url: String,
     pub trainer_name: String,
     pub character_name: String,
     pub parent_left_name: String,
@@ -20,7 +25,7 @@ pub struct InheritanceShareData {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SupportCardShareData {
-    pub account_id: String,
+    pub account_id: AccountId,
     pub trainer_name: String,
     pub card_name: String,
     pub card_rarity: String,
@@ -29,8 +34,61 @@ pub struct SupportCardShareData {
     pub card_type: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleShareData {
+    pub circle_id: CircleId,
+    /// Canonical URL of the share page (og:url)
+    pub share_url: String,
+    pub name: String,
+    pub leader_name: Option<String>,
+    pub member_count: Option<i32>,
+    pub monthly_rank: Option<i32>,
+    pub monthly_point: Option<i64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SharePathParams {
     pub share_type: String,
-    pub account_id: String,
+    pub account_id: AccountId,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShortenShareLinkRequest {
+    /// honse.moe URL or path to shorten
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareLinkResponse {
+    pub token: String,
+    pub short_url: String,
+    /// Normalized path the token redirects to
+    pub target: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OEmbedParams {
+    pub url: String,
+    pub format: Option<String>,
+    pub maxwidth: Option<u32>,
+    pub maxheight: Option<u32>,
+}
+
+/// oEmbed 1.0 response for a share page
+#[derive(Debug, Serialize)]
+pub struct OEmbedResponse {
+    pub version: String,
+    #[serde(rename = "type")]
+    pub oembed_type: String,
+    pub title: String,
+    pub author_name: String,
+    pub provider_name: String,
+    pub provider_url: String,
+    pub cache_age: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_height: Option<u32>,
 }
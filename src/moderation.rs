@@ -0,0 +1,98 @@
+use crate::cache::Namespace;
+use crate::models::Circle;
+use std::sync::{OnceLock, RwLock};
+
+/// Words that must be masked in game-sourced text (circle names, comments, trainer names).
+/// Each word is stored pre-folded to lowercase chars for case-insensitive matching.
+static WORDLIST: OnceLock<RwLock<Vec<Vec<char>>>> = OnceLock::new();
+
+fn get_wordlist() -> &'static RwLock<Vec<Vec<char>>> {
+    WORDLIST.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Load (or reload) the wordlist from the file named by MODERATION_WORDLIST.
+/// One word per line, blank lines and lines starting with '#' are ignored.
+/// Returns the number of words loaded.
+pub fn load_wordlist() -> usize {
+    let Some(path) = crate::config::get().moderation_wordlist.as_deref() else {
+        return 0;
+    };
+
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            tracing::warn!("⚠️ Failed to read moderation wordlist {}: {}", path.display(), e);
+            return 0;
+        }
+    };
+
+    let words: Vec<Vec<char>> = contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|word| word.chars().map(fold).collect())
+        .collect();
+
+    let count = words.len();
+    if let Ok(mut wordlist) = get_wordlist().write() {
+        *wordlist = words;
+    }
+
+    tracing::info!("🛡️ Loaded {} moderation words from {}", count, path.display());
+    count
+}
+
+/// Replace every wordlist match in `text` with asterisks (case-insensitive)
+pub fn mask_text(text: &str) -> String {
+    let wordlist = match get_wordlist().read() {
+        Ok(wordlist) => wordlist,
+        Err(_) => return text.to_string(),
+    };
+
+    if wordlist.is_empty() || text.is_empty() {
+        return text.to_string();
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let folded: Vec<char> = chars.iter().map(|c| fold(*c)).collect();
+    let mut masked = vec![false; chars.len()];
+
+    for word in wordlist.iter() {
+        if word.is_empty() || word.len() > folded.len() {
+            continue;
+        }
+        for start in 0..=(folded.len() - word.len()) {
+            if folded[start..start + word.len()] == word[..] {
+                masked[start..start + word.len()].fill(true);
+            }
+        }
+    }
+
+    chars
+        .iter()
+        .zip(masked)
+        .map(|(c, is_masked)| if is_masked { '*' } else { *c })
+        .collect()
+}
+
+/// Mask the game-sourced text of a circle before it is returned
+pub fn mask_circle(mut circle: Circle) -> Circle {
+    circle.name = mask_text(&circle.name);
+    circle.comment = circle.comment.as_deref().map(mask_text);
+    circle
+}
+
+/// Drop cached responses that may show a name or comment an admin just
+/// overrode, so the change shows everywhere at once
+pub fn invalidate_displayed_text() {
+    crate::cache::invalidate_namespaces(&[
+        Namespace::Search,
+        Namespace::Share,
+        Namespace::Feeds,
+        Namespace::Http,
+    ]);
+}
@@ -0,0 +1,299 @@
+//! Trainer availability notifications: users subscribe to a trainer whose
+//! friend list is full (see handlers::notifications), ingest marks the
+//! subscriptions pending once a scrape shows room again, and dispatch_task
+//! delivers them to a Discord webhook or a browser push subscription.
+//!
+//! Failed deliveries are retried with exponential backoff up to
+//! NOTIFICATION_MAX_ATTEMPTS; webhooks and push subscriptions that are gone
+//! (404/410) fail right away.
+
+use sqlx::{PgConnection, PgPool};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::errors::AppError;
+use crate::handlers::users::FRIEND_LIST_FULL;
+use crate::models::NotificationChannel;
+use crate::shutdown;
+
+pub mod web_push;
+
+/// Deliveries claimed per round
+const DISPATCH_BATCH_SIZE: i64 = 50;
+
+/// How long a claimed delivery is held before another instance may retry it
+const CLAIM_LEASE_SECS: i64 = 300;
+
+/// Backoff after the first failed attempt, doubling per attempt up to RETRY_MAX_DELAY
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(60);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(3600);
+
+/// Mark waiting subscriptions pending for trainers that, going by the fresh
+/// scrape, have room on their friend list
+///
+/// Runs in the ingest transaction; `trainers` is (account_id, name,
+/// follower_num) as scraped. Returns how many subscriptions were triggered.
+pub(crate) async fn trigger_availability(
+    conn: &mut PgConnection,
+    trainers: &[(String, String, Option<i32>)],
+) -> Result<u64, AppError> {
+    let (account_ids, follower_nums): (Vec<&str>, Vec<Option<i32>>) = trainers
+        .iter()
+        .map(|(account_id, _, follower_num)| (account_id.as_str(), *follower_num))
+        .unzip();
+
+    let triggered = sqlx::query(
+        r#"
+        UPDATE availability_subscriptions s
+        SET status = 'pending',
+            triggered_at = CURRENT_TIMESTAMP,
+            next_attempt_at = CURRENT_TIMESTAMP,
+            attempts = 0,
+            last_error = NULL
+        FROM unnest($1::text[], $2::int[]) AS n(account_id, follower_num)
+        WHERE s.account_id = n.account_id
+          AND s.status = 'waiting'
+          AND n.follower_num < $3
+        "#,
+    )
+    .bind(account_ids)
+    .bind(follower_nums)
+    .bind(FRIEND_LIST_FULL)
+    .execute(conn)
+    .await?
+    .rows_affected();
+    Ok(triggered)
+}
+
+#[derive(sqlx::FromRow)]
+struct Delivery {
+    subscription_id: i64,
+    account_id: String,
+    channel: NotificationChannel,
+    endpoint: String,
+    p256dh: Option<String>,
+    auth: Option<String>,
+    attempts: i32,
+    trainer_name: Option<String>,
+    follower_num: Option<i32>,
+}
+
+enum Outcome {
+    Delivered,
+    /// Worth trying again later (network error, rate limit, server error)
+    Retry(String),
+    /// The destination rejected the notification or no longer exists
+    Failed(String),
+}
+
+/// Background task delivering triggered notifications every
+/// NOTIFICATION_DISPATCH_INTERVAL_SECS
+pub async fn dispatch_task(pool: PgPool) {
+    let config = &crate::config::get().notifications;
+    let mut interval = tokio::time::interval(config.dispatch_interval);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap_or_default();
+
+    info!(
+        "🔔 Starting availability notification dispatcher (runs every {}s)",
+        config.dispatch_interval.as_secs()
+    );
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        match dispatch_due(&pool, &client).await {
+            Ok(0) => {}
+            Ok(delivered) => info!("🔔 Delivered {} availability notification(s)", delivered),
+            Err(e) => warn!("⚠️ Availability notification dispatch failed: {}", e),
+        }
+    }
+}
+
+/// Deliver the pending notifications that are due, returning how many were
+/// delivered
+async fn dispatch_due(pool: &PgPool, client: &reqwest::Client) -> Result<u64, AppError> {
+    let max_attempts = crate::config::get().notifications.max_attempts;
+    let mut delivered = 0;
+
+    loop {
+        // Claiming pushes next_attempt_at past the lease, so a crash mid-delivery
+        // only delays the notification and parallel instances skip these rows
+        let batch = sqlx::query_as::<_, Delivery>(
+            r#"
+            WITH claimed AS (
+                UPDATE availability_subscriptions
+                SET next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => $2)
+                WHERE subscription_id IN (
+                    SELECT subscription_id FROM availability_subscriptions
+                    WHERE status = 'pending' AND next_attempt_at <= CURRENT_TIMESTAMP
+                    ORDER BY next_attempt_at
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING subscription_id, account_id, channel, endpoint, p256dh, auth, attempts
+            )
+            SELECT c.*, COALESCE(t.display_name_override, t.name) AS trainer_name,
+                   t.follower_num
+            FROM claimed c
+            LEFT JOIN trainer t ON t.account_id = c.account_id
+            "#,
+        )
+        .bind(DISPATCH_BATCH_SIZE)
+        .bind(CLAIM_LEASE_SECS as f64)
+        .fetch_all(pool)
+        .await?;
+        let batch_len = batch.len() as i64;
+
+        for delivery in batch {
+            // Filled up again before we got to it: wait for the next opening
+            if delivery.follower_num.is_some_and(|n| n >= FRIEND_LIST_FULL) {
+                sqlx::query(
+                    r#"
+                    UPDATE availability_subscriptions
+                    SET status = 'waiting', next_attempt_at = NULL, attempts = 0
+                    WHERE subscription_id = $1
+                    "#,
+                )
+                .bind(delivery.subscription_id)
+                .execute(pool)
+                .await?;
+                continue;
+            }
+
+            let outcome = deliver(client, &delivery).await;
+            let attempts = delivery.attempts + 1;
+            match outcome {
+                Outcome::Delivered => {
+                    sqlx::query(
+                        r#"
+                        UPDATE availability_subscriptions
+                        SET status = 'delivered', delivered_at = CURRENT_TIMESTAMP,
+                            attempts = $2, next_attempt_at = NULL, last_error = NULL
+                        WHERE subscription_id = $1
+                        "#,
+                    )
+                    .bind(delivery.subscription_id)
+                    .bind(attempts)
+                    .execute(pool)
+                    .await?;
+                    delivered += 1;
+                }
+                Outcome::Retry(error) if attempts < max_attempts => {
+                    let delay = RETRY_BASE_DELAY
+                        .saturating_mul(1 << (attempts - 1).min(16))
+                        .min(RETRY_MAX_DELAY);
+                    sqlx::query(
+                        r#"
+                        UPDATE availability_subscriptions
+                        SET attempts = $2, last_error = $3,
+                            next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => $4)
+                        WHERE subscription_id = $1
+                        "#,
+                    )
+                    .bind(delivery.subscription_id)
+                    .bind(attempts)
+                    .bind(&error)
+                    .bind(delay.as_secs_f64())
+                    .execute(pool)
+                    .await?;
+                }
+                Outcome::Retry(error) | Outcome::Failed(error) => {
+                    warn!(
+                        "⚠️ Giving up on availability notification {} after {} attempt(s): {}",
+                        delivery.subscription_id, attempts, error
+                    );
+                    sqlx::query(
+                        r#"
+                        UPDATE availability_subscriptions
+                        SET status = 'failed', attempts = $2, last_error = $3, next_attempt_at = NULL
+                        WHERE subscription_id = $1
+                        "#,
+                    )
+                    .bind(delivery.subscription_id)
+                    .bind(attempts)
+                    .bind(&error)
+                    .execute(pool)
+                    .await?;
+                }
+            }
+        }
+
+        if batch_len < DISPATCH_BATCH_SIZE || shutdown::is_requested() {
+            return Ok(delivered);
+        }
+    }
+}
+
+/// Classify a destination's response
+fn outcome(status: reqwest::StatusCode) -> Outcome {
+    if status.is_success() {
+        Outcome::Delivered
+    } else if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        Outcome::Retry(format!("HTTP {}", status))
+    } else {
+        Outcome::Failed(format!("HTTP {}", status))
+    }
+}
+
+async fn deliver(client: &reqwest::Client, delivery: &Delivery) -> Outcome {
+    let name = crate::moderation::mask_text(
+        delivery
+            .trainer_name
+            .as_deref()
+            .unwrap_or(&delivery.account_id),
+    );
+
+    let request = match delivery.channel {
+        NotificationChannel::Discord => {
+            let content = match delivery.follower_num {
+                Some(follower_num) => format!(
+                    "**{}** ({}) has room on their friend list again ({}/{} followers)",
+                    name, delivery.account_id, follower_num, FRIEND_LIST_FULL
+                ),
+                None => format!(
+                    "**{}** ({}) has room on their friend list again",
+                    name, delivery.account_id
+                ),
+            };
+            client
+                .post(&delivery.endpoint)
+                .json(&serde_json::json!({ "username": "honse.moe", "content": content }))
+        }
+        NotificationChannel::WebPush => {
+            let Some(vapid) = &crate::config::get().notifications.vapid else {
+                return Outcome::Failed("web push is not configured".to_string());
+            };
+            let (Some(p256dh), Some(auth)) = (&delivery.p256dh, &delivery.auth) else {
+                return Outcome::Failed("push subscription keys missing".to_string());
+            };
+            let payload = serde_json::json!({
+                "type": "trainer_available",
+                "account_id": delivery.account_id,
+                "trainer_name": name,
+                "follower_num": delivery.follower_num,
+            });
+            match web_push::request(
+                client,
+                vapid,
+                &delivery.endpoint,
+                p256dh,
+                auth,
+                payload.to_string().as_bytes(),
+            ) {
+                Ok(request) => request,
+                Err(e) => return Outcome::Failed(e),
+            }
+        }
+    };
+
+    match request.send().await {
+        Ok(response) => outcome(response.status()),
+        Err(e) => Outcome::Retry(e.without_url().to_string()),
+    }
+}
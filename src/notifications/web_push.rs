@@ -0,0 +1,164 @@
+//! Web push delivery: messages encrypted for the browser (RFC 8291,
+//! aes128gcm) and signed with the server's VAPID key (RFC 8292)
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use ring::{aead, agreement, hkdf, rand::SecureRandom, rand::SystemRandom, signature};
+use std::time::Duration;
+
+use crate::config::VapidConfig;
+
+/// Record size advertised in the message header; the payload fits in one record
+const RECORD_SIZE: u32 = 4096;
+
+/// How long push services keep a message for an offline browser
+const MESSAGE_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// Lifetime of the VAPID token (push services reject more than 24 hours)
+const VAPID_TOKEN_LIFETIME_SECS: i64 = 12 * 3600;
+
+/// Whether the keys form a P-256 key pair VAPID tokens can be signed with
+pub fn is_vapid_key_pair(public_key: &[u8], private_key: &[u8]) -> bool {
+    signature::EcdsaKeyPair::from_private_key_and_public_key(
+        &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+        private_key,
+        public_key,
+        &SystemRandom::new(),
+    )
+    .is_ok()
+}
+
+/// Whether the browser's keys have the shape RFC 8291 requires
+/// (uncompressed P-256 point, 16-byte auth secret)
+pub fn is_valid_subscription_keys(p256dh: &str, auth: &str) -> bool {
+    matches!(
+        (decode(p256dh), decode(auth)),
+        (Some(p256dh), Some(auth)) if p256dh.len() == 65 && p256dh[0] == 0x04 && auth.len() == 16
+    )
+}
+
+fn decode(value: &str) -> Option<Vec<u8>> {
+    URL_SAFE_NO_PAD.decode(value.trim_end_matches('=')).ok()
+}
+
+/// HKDF output length, as ring wants it
+struct Len(usize);
+
+impl hkdf::KeyType for Len {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+fn hkdf_expand(prk: &hkdf::Prk, info: &[u8], len: usize) -> Result<Vec<u8>, String> {
+    let mut out = vec![0; len];
+    prk.expand(&[info], Len(len))
+        .and_then(|okm| okm.fill(&mut out))
+        .map_err(|_| "HKDF expansion failed".to_string())?;
+    Ok(out)
+}
+
+/// Encrypt a payload for a push subscription as one aes128gcm record
+fn encrypt(payload: &[u8], p256dh: &str, auth: &str) -> Result<Vec<u8>, String> {
+    let rng = SystemRandom::new();
+    let ua_public = decode(p256dh).ok_or("invalid p256dh key")?;
+    let auth_secret = decode(auth).ok_or("invalid auth secret")?;
+
+    let as_private = agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng)
+        .map_err(|_| "failed to generate key")?;
+    let as_public = as_private
+        .compute_public_key()
+        .map_err(|_| "failed to compute public key")?;
+    let as_public = as_public.as_ref();
+
+    // PRK_key = HKDF(auth_secret, ecdh_secret); IKM = expand(PRK_key, key_info, 32)
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(&ua_public);
+    key_info.extend_from_slice(as_public);
+    let ikm = agreement::agree_ephemeral(
+        as_private,
+        &agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, &ua_public),
+        |ecdh_secret| {
+            let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, &auth_secret).extract(ecdh_secret);
+            hkdf_expand(&prk, &key_info, 32)
+        },
+    )
+    .map_err(|_| "invalid p256dh key".to_string())??;
+
+    let mut salt = [0u8; 16];
+    rng.fill(&mut salt).map_err(|_| "failed to generate salt")?;
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, &salt).extract(&ikm);
+    let cek = hkdf_expand(&prk, b"Content-Encoding: aes128gcm\0", 16)?;
+    let nonce = hkdf_expand(&prk, b"Content-Encoding: nonce\0", 12)?;
+
+    let key = aead::LessSafeKey::new(
+        aead::UnboundKey::new(&aead::AES_128_GCM, &cek).map_err(|_| "invalid content key")?,
+    );
+    let nonce = aead::Nonce::try_assume_unique_for_key(&nonce).map_err(|_| "invalid nonce")?;
+    // A single (so last) record: the payload followed by the 0x02 delimiter
+    let mut record = payload.to_vec();
+    record.push(0x02);
+    key.seal_in_place_append_tag(nonce, aead::Aad::empty(), &mut record)
+        .map_err(|_| "encryption failed")?;
+
+    let mut body = Vec::with_capacity(16 + 4 + 1 + as_public.len() + record.len());
+    body.extend_from_slice(&salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(as_public.len() as u8);
+    body.extend_from_slice(as_public);
+    body.extend_from_slice(&record);
+    Ok(body)
+}
+
+/// `Authorization: vapid t=<JWT>, k=<public key>` for a push service
+fn vapid_authorization(vapid: &VapidConfig, endpoint: &url::Url) -> Result<String, String> {
+    let audience = endpoint.origin().ascii_serialization();
+    let header = URL_SAFE_NO_PAD.encode(br#"{"typ":"JWT","alg":"ES256"}"#);
+    let claims = URL_SAFE_NO_PAD.encode(
+        serde_json::json!({
+            "aud": audience,
+            "exp": chrono::Utc::now().timestamp() + VAPID_TOKEN_LIFETIME_SECS,
+            "sub": vapid.subject,
+        })
+        .to_string(),
+    );
+    let signing_input = format!("{}.{}", header, claims);
+
+    let rng = SystemRandom::new();
+    let key_pair = signature::EcdsaKeyPair::from_private_key_and_public_key(
+        &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+        &vapid.private_key,
+        &vapid.public_key,
+        &rng,
+    )
+    .map_err(|_| "invalid VAPID key pair")?;
+    let signature = key_pair
+        .sign(&rng, signing_input.as_bytes())
+        .map_err(|_| "VAPID signing failed")?;
+
+    Ok(format!(
+        "vapid t={}.{}, k={}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(signature.as_ref()),
+        URL_SAFE_NO_PAD.encode(&vapid.public_key)
+    ))
+}
+
+/// The request delivering `payload` to a push subscription
+pub fn request(
+    client: &reqwest::Client,
+    vapid: &VapidConfig,
+    endpoint: &str,
+    p256dh: &str,
+    auth: &str,
+    payload: &[u8],
+) -> Result<reqwest::RequestBuilder, String> {
+    let url = url::Url::parse(endpoint).map_err(|e| format!("invalid endpoint: {}", e))?;
+    let authorization = vapid_authorization(vapid, &url)?;
+    Ok(client
+        .post(url)
+        .header(reqwest::header::CONTENT_ENCODING, "aes128gcm")
+        .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+        .header("TTL", MESSAGE_TTL.as_secs().to_string())
+        .header(reqwest::header::AUTHORIZATION, authorization)
+        .body(encrypt(payload, p256dh, auth)?))
+}
@@ -0,0 +1,83 @@
+use axum::body::Bytes;
+use dashmap::DashMap;
+use resvg::{tiny_skia, usvg};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// OpenGraph image size recommended by Discord/Twitter
+pub const WIDTH: u32 = 1200;
+pub const HEIGHT: u32 = 630;
+
+/// Rendered images kept in memory before the least recently used are dropped
+const MAX_CACHED_IMAGES: usize = 500;
+
+/// Rendered PNGs keyed by the hash of their SVG source
+static RENDERED: OnceLock<DashMap<String, (Bytes, Instant)>> = OnceLock::new();
+
+/// Parser options with the system fonts loaded (scanning fonts takes a while, so only once)
+static OPTIONS: OnceLock<usvg::Options<'static>> = OnceLock::new();
+
+fn options() -> &'static usvg::Options<'static> {
+    OPTIONS.get_or_init(|| {
+        let mut options = usvg::Options::default();
+        options.fontdb_mut().load_system_fonts();
+        if options.fontdb.is_empty() {
+            tracing::warn!("⚠️ No system fonts found, share images will render without text");
+        }
+        options
+    })
+}
+
+fn rendered() -> &'static DashMap<String, (Bytes, Instant)> {
+    RENDERED.get_or_init(DashMap::new)
+}
+
+/// Content hash of an SVG, used as cache key and ETag
+pub fn svg_hash(svg: &str) -> String {
+    hex::encode(Sha256::digest(svg.as_bytes()))[..32].to_string()
+}
+
+/// Render an SVG to PNG, reusing the cached image for identical input
+///
+/// Rendering is CPU-bound, so this runs on the blocking pool.
+pub async fn render_png(svg: String) -> Result<Bytes, String> {
+    let hash = svg_hash(&svg);
+    if let Some(mut entry) = rendered().get_mut(&hash) {
+        entry.1 = Instant::now();
+        return Ok(entry.0.clone());
+    }
+
+    let png = tokio::task::spawn_blocking(move || rasterize(&svg))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let cache = rendered();
+    if cache.len() >= MAX_CACHED_IMAGES {
+        evict_oldest(cache);
+    }
+    cache.insert(hash, (png.clone(), Instant::now()));
+
+    Ok(png)
+}
+
+fn rasterize(svg: &str) -> Result<Bytes, String> {
+    let tree = usvg::Tree::from_str(svg, options()).map_err(|e| e.to_string())?;
+    let mut pixmap =
+        tiny_skia::Pixmap::new(WIDTH, HEIGHT).ok_or_else(|| "invalid image size".to_string())?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map(Bytes::from).map_err(|e| e.to_string())
+}
+
+/// Drop the least recently served quarter of the cache
+fn evict_oldest(cache: &DashMap<String, (Bytes, Instant)>) {
+    let mut entries: Vec<(String, Instant)> = cache
+        .iter()
+        .map(|entry| (entry.key().clone(), entry.value().1))
+        .collect();
+    entries.sort_by_key(|(_, last_used)| *last_used);
+
+    for (key, _) in entries.iter().take(entries.len() / 4) {
+        cache.remove(key);
+    }
+}
@@ -0,0 +1,150 @@
+//! Trainer retention: trainers whose data hasn't been refreshed for
+//! TRAINER_RETENTION_MONTHS are flagged stale and left out of searches by
+//! default (see search::includes_stale).
+//!
+//! Nothing is deleted. A trigger clears the flag as soon as a trainer is
+//! updated again, and each run also restores stale trainers that are back
+//! within the window (e.g. after the setting was raised or turned off).
+
+use chrono::Utc;
+use sqlx::PgPool;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::errors::AppError;
+use crate::models::{RetentionRunResult, RetentionStatus};
+
+/// Trainers flagged per transaction, so a large first run doesn't hold locks for long
+const FLAG_BATCH_SIZE: i64 = 5000;
+
+static STATUS: OnceLock<Mutex<RetentionStatus>> = OnceLock::new();
+
+fn get_status() -> &'static Mutex<RetentionStatus> {
+    STATUS.get_or_init(|| Mutex::new(RetentionStatus::default()))
+}
+
+/// Flag trainers past the retention window and restore those within it,
+/// recording the outcome for `status()`
+pub async fn run(pool: &PgPool) -> Result<RetentionRunResult, AppError> {
+    let started = Instant::now();
+    let result = apply(pool).await.map(|run| RetentionRunResult {
+        duration_ms: started.elapsed().as_millis() as u64,
+        ..run
+    });
+
+    if let Ok(mut status) = get_status().lock() {
+        status.last_run_at = Some(Utc::now().naive_utc());
+        match &result {
+            Ok(run) => {
+                status.total_flagged += run.flagged;
+                status.total_restored += run.restored;
+                status.last_run = Some(run.clone());
+                status.last_error = None;
+            }
+            Err(e) => status.last_error = Some(e.to_string()),
+        }
+    }
+
+    // Cached search pages and counts may include or omit the affected trainers
+    if let Ok(run) = &result {
+        if run.flagged > 0 || run.restored > 0 {
+            crate::cache::invalidate_namespaces(crate::cache::Namespace::TRAINER_DATA);
+        }
+    }
+    result
+}
+
+async fn apply(pool: &PgPool) -> Result<RetentionRunResult, AppError> {
+    let months = crate::config::get().trainer_retention_months.map(|m| m as i32);
+    let mut result = RetentionRunResult::default();
+
+    // Without a policy every trainer is within the window
+    let mut tx = crate::database::begin_long_running(pool).await?;
+    result.restored = sqlx::query(
+        r#"
+        UPDATE trainer SET stale = false
+        WHERE stale
+          AND ($1::int IS NULL
+               OR last_updated >= (CURRENT_TIMESTAMP - make_interval(months => $1))::timestamp)
+        "#,
+    )
+    .bind(months)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+
+    let Some(months) = months else {
+        return Ok(result);
+    };
+
+    loop {
+        let mut tx = crate::database::begin_long_running(pool).await?;
+        let flagged = sqlx::query(
+            r#"
+            UPDATE trainer SET stale = true
+            WHERE account_id IN (
+                SELECT account_id FROM trainer
+                WHERE NOT stale
+                  AND last_updated < (CURRENT_TIMESTAMP - make_interval(months => $1))::timestamp
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            "#,
+        )
+        .bind(months)
+        .bind(FLAG_BATCH_SIZE)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        tx.commit().await?;
+
+        result.flagged += flagged;
+        if (flagged as i64) < FLAG_BATCH_SIZE {
+            break;
+        }
+    }
+
+    Ok(result)
+}
+
+/// The retention policy, stale trainer count and job counters
+pub async fn status(pool: &PgPool) -> Result<RetentionStatus, AppError> {
+    let stale_trainers =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM trainer WHERE stale")
+            .fetch_one(pool)
+            .await?;
+
+    let mut status = get_status()
+        .lock()
+        .map(|status| status.clone())
+        .unwrap_or_default();
+    status.retention_months = crate::config::get().trainer_retention_months;
+    status.stale_trainers = stale_trainers;
+    Ok(status)
+}
+
+/// Background task applying the retention policy every 6 hours (first run right away)
+pub async fn retention_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(Duration::from_secs(6 * 3600));
+
+    info!("🧊 Starting trainer retention task (runs every 6 hours)");
+
+    loop {
+        if crate::shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        match run(&pool).await {
+            Ok(run) if run.flagged > 0 || run.restored > 0 => {
+                info!(
+                    "🧊 Trainer retention: {} flagged stale, {} restored in {}ms",
+                    run.flagged, run.restored, run.duration_ms
+                );
+            }
+            Ok(_) => {}
+            Err(e) => warn!("⚠️ Trainer retention run failed: {}", e),
+        }
+    }
+}
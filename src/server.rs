@@ -0,0 +1,1011 @@
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    http::StatusCode,
+    response::Json,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tower::ServiceBuilder;
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{Any, CorsLayer},
+    limit::RequestBodyLimitLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
+use tracing::{error, info, warn, Level};
+use tracing_subscriber::EnvFilter;
+
+#[cfg(unix)]
+use crate::unix_socket;
+use crate::{
+    bans, cache, characters, competition, events, game_data, grpc, journal,
+    member_fan_partitions, middleware, moderation, retention, shutdown, support_cards, views,
+};
+use crate::config::{self, Config};
+use crate::database::{self, DbPools};
+use crate::errors::AppError;
+use crate::handlers::{
+    self, admin, analytics, announcements, circles, feeds, graphql, ingest, leaderboards,
+    matchmaking, notifications, privacy, reports, search, sharing, sitemap, stats, tasks,
+    team_stadium, users, votes, workers,
+};
+use crate::storage::{MockStorage, PgStorage, Storage};
+use crate::AppState;
+
+/// Load the configuration, connect to the database and serve the API until shutdown
+pub async fn run() -> anyhow::Result<()> {
+    // Load environment variables (first, so DEBUG_MODE/LOG_FORMAT can come from .env),
+    // then check all settings, refusing to start with a list of what is missing or invalid
+    dotenvy::dotenv().ok();
+    let config = config::init(Config::from_env()?);
+
+    // Initialize tracing - production uses WARN/ERROR only, development uses INFO
+    let is_development = config.debug_mode;
+    // LOG_FORMAT=json emits one JSON object per line (with the request span) for Loki/ELK
+    let json_logs = config.json_logs;
+
+    let subscriber = if is_development {
+        tracing_subscriber::fmt()
+            .with_max_level(Level::INFO)
+            .with_env_filter(EnvFilter::new("honsemoe_backend=info,sqlx=info,info"))
+    } else {
+        tracing_subscriber::fmt()
+            .with_max_level(Level::WARN)
+            .with_env_filter(EnvFilter::new("honsemoe_backend=warn,sqlx=warn,warn"))
+    };
+    if json_logs {
+        subscriber.json().init();
+    } else {
+        subscriber.init();
+    }
+    if is_development {
+        info!("🔧 Development mode: INFO logging enabled with SQL query logging");
+    }
+
+    // MOCK_MODE serves search, circles, stats and shares from bundled fixtures
+    // so the frontend can be developed without a database
+    let mock_mode = config.mock_mode;
+
+    let (db_pools, storage): (Arc<DbPools>, Arc<dyn Storage>) = if mock_mode {
+        warn!("🧪 MOCK_MODE enabled: serving bundled fixtures, database-backed endpoints will fail");
+        let pool = database::create_lazy_pool(&config.database_url).expect("Invalid DATABASE_URL");
+        let fixtures = MockStorage::load().expect("Invalid bundled mock fixtures");
+        (Arc::new(DbPools::new(pool)), Arc::new(fixtures))
+    } else {
+        // Database connection
+        let pool = database::create_pool(&config.database_url, &config.db_session)
+            .await
+            .expect("Failed to connect to PostgreSQL");
+
+        run_migrations(&pool).await?;
+
+        // Re-create tasks from submissions the previous run journaled but never inserted
+        journal::init(&pool).await;
+
+        // Read replicas take read-only queries once they pass a health check
+        let replicas = config
+            .replica_database_urls
+            .iter()
+            .map(|url| Ok((database::display_name(url), database::create_replica_pool(url, &config.db_session)?)))
+            .collect::<Result<Vec<_>, sqlx::Error>>()
+            .expect("Invalid REPLICA_DATABASE_URLS");
+        let db_pools = Arc::new(DbPools::new(pool).with_replicas(replicas));
+        db_pools.check_replicas().await;
+
+        (db_pools.clone(), Arc::new(PgStorage::new(db_pools)))
+    };
+    let pool = db_pools.primary.clone();
+
+    // Load the moderation wordlist used to mask game-sourced names/comments
+    moderation::load_wordlist();
+
+    // Game master data from GAME_DATA_DIR, then character names (table, then the
+    // bundled list) and support card metadata
+    if !mock_mode {
+        if let Some(dir) = &config.game_data_dir {
+            game_data::import_directory(&pool, dir).await;
+        }
+        characters::load_character_names(&pool).await;
+        support_cards::load_support_card_meta(&pool).await;
+        shutdown::spawn_job(characters::report_unknown_characters(pool.clone()));
+    }
+
+    let state = AppState {
+        db: pool.clone(),
+        db_pools: db_pools.clone(),
+        storage,
+        config: config.clone(),
+    };
+
+    // Database maintenance jobs (there is no database in mock mode)
+    if !mock_mode {
+        // Start background tasks refreshing the materialized views (stats_counts, circle_live_ranks)
+        for view in views::MATERIALIZED_VIEWS {
+            shutdown::spawn_job(views::refresh_task(pool.clone(), view));
+        }
+
+        // Start background task to precompute the unfiltered circle leaderboard
+        shutdown::spawn_job(circle_leaderboard_task(pool.clone()));
+
+        // Start background task to snapshot circle monthly ranks into circle_rank_history
+        shutdown::spawn_job(snapshot_circle_ranks_task(pool.clone()));
+
+        // Start background task to compute circle awards once a month is over
+        shutdown::spawn_job(circle_awards_rollup_task(pool.clone()));
+
+        // Start background task to snapshot the weekly support card leaderboard
+        shutdown::spawn_job(support_card_leaderboard_task(pool.clone()));
+
+        // Start background task to snapshot the global leaderboards once a day
+        shutdown::spawn_job(leaderboards_task(pool.clone()));
+
+        // Start background task to post rank changes to circle webhooks
+        shutdown::spawn_job(circle_webhook_task(pool.clone()));
+
+        // Start background task to deliver trainer availability notifications
+        shutdown::spawn_job(crate::notifications::dispatch_task(pool.clone()));
+
+        // Start background task to create upcoming member fan partitions and archive old ones
+        shutdown::spawn_job(member_fan_partitions::maintenance_task(pool.clone()));
+
+        // Start background task to flag trainers past the retention window as stale
+        shutdown::spawn_job(retention::retention_task(pool.clone()));
+
+        // Start background task to detect members that left their circle
+        shutdown::spawn_job(detect_circle_departures_task(pool.clone()));
+
+        // Start background task to load the ban list (first run right away) and keep it current
+        shutdown::spawn_job(ban_refresh_task(pool.clone()));
+
+        // Start background task taking unhealthy read replicas out of rotation
+        if db_pools.has_replicas() {
+            shutdown::spawn_job(replica_health_task(db_pools.clone()));
+        }
+
+        // Start background task to return tasks stranded by crashed workers to the queue
+        shutdown::spawn_job(stale_task_reaper_task(pool.clone()));
+
+        // Start background task to move completed tasks to tasks_archive
+        shutdown::spawn_job(task_archiver_task(pool.clone()));
+
+        // Start background task to notify the WebSub hub about new notable records (if configured)
+        if let Some(hub_url) = feeds::websub_hub_url() {
+            shutdown::spawn_job(websub_ping_task(pool.clone(), hub_url));
+        }
+    }
+
+    // Start listener that drops cached responses when stored data changes
+    shutdown::spawn_job(cache_invalidation_task());
+
+    // Start background tasks to clean up expired cache entries and cached tokens
+    shutdown::spawn_job(cache_cleanup_task());
+    shutdown::spawn_job(token_cleanup_task());
+
+    // gRPC worker service on its own port; it stops with the HTTP server and is
+    // drained like the background jobs
+    if let Some(grpc_port) = config.grpc_port {
+        let listener = grpc::bind(&config.host, grpc_port).await?;
+        shutdown::spawn_job(grpc::serve(listener, state.clone()));
+    }
+
+    // Configure CORS - more permissive for development, strict for production
+    let cors = if is_development {
+        info!("🔓 Development mode: Using permissive CORS");
+        CorsLayer::new()
+            .allow_origin(Any)
+            .allow_credentials(false) // Can't use credentials with allow_origin(Any)
+    } else {
+        // Entries were checked to be http(s) origins when the config was loaded
+        let origins: Vec<axum::http::HeaderValue> = config
+            .allowed_origins
+            .iter()
+            .filter_map(|origin| origin.parse().ok())
+            .collect();
+        info!("🔒 Production mode: CORS configured for origins: {}", config.allowed_origins.join(","));
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_credentials(true)
+    }
+    .allow_methods([
+        axum::http::Method::GET,
+        axum::http::Method::POST,
+        axum::http::Method::PUT,
+        axum::http::Method::DELETE,
+        axum::http::Method::OPTIONS,
+    ])
+    .allow_headers([
+        axum::http::header::CONTENT_TYPE,
+        axum::http::header::AUTHORIZATION,
+        axum::http::header::ACCEPT,
+        axum::http::header::USER_AGENT,
+        axum::http::header::REFERER,
+        axum::http::header::ORIGIN,
+        "CF-Turnstile-Token".parse().unwrap(),
+        "X-Claim-Token".parse().unwrap(),
+        middleware::api_key::API_KEY_HEADER.parse().unwrap(),
+    ]);
+
+    // Build the application with proper routing and middleware
+    // Public endpoints (no Turnstile, permissive CORS)
+    let public_routes = Router::new()
+        .route("/healthz", get(liveness_check))
+        .route("/readyz", get(readiness_check))
+        .nest("/api/v4/circles", circles::router())
+        .nest("/feeds", feeds::router())
+        .merge(sitemap::router())
+        .layer(
+            ServiceBuilder::new()
+                .layer(
+                    TraceLayer::new_for_http()
+                        .make_span_with(middleware::request_id::request_span),
+                )
+                .layer(CorsLayer::permissive()) // Allow all origins for public API
+                .layer(axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    middleware::api_key_middleware,
+                )),
+        )
+        .with_state(state.clone());
+
+    // Protected endpoints (Turnstile + restricted CORS)
+    let protected_routes = Router::new()
+        .route("/api/health", get(health_check))
+        .nest("/api/stats", stats::router())
+        .nest("/api/events", analytics::router())
+        .nest("/api/announcements", announcements::router())
+        .nest("/api/leaderboards", leaderboards::router())
+        .nest("/api/matchmaking", matchmaking::router())
+        .nest("/api/reports", reports::router())
+        .nest("/api/team-stadium", team_stadium::router())
+        .nest("/api/tasks", tasks::router())
+        .nest("/api/v3/tasks", tasks::router())
+        .nest("/api/workers", workers::router())
+        .nest("/api/ingest", ingest::router())
+        .nest("/api/privacy", privacy::router())
+        .nest(
+            "/api/users",
+            users::router()
+                .merge(notifications::router())
+                .layer(axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    middleware::user_auth_middleware,
+                )),
+        )
+        .nest(
+            "/api/admin",
+            admin::router().layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                middleware::admin_audit_middleware,
+            )),
+        )
+        .nest("/api/v3", search::router())
+        .nest("/api/v3/inheritance", votes::router())
+        .nest("/api/graphql", graphql::router())
+        .nest("/", sharing::router())
+        .layer(
+            ServiceBuilder::new()
+                .layer(
+                    TraceLayer::new_for_http()
+                        .make_span_with(middleware::request_id::request_span),
+                )
+                .layer(cors)
+                // X-Api-Key is checked before Turnstile so keyed requests can skip it
+                .layer(axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    middleware::api_key_middleware,
+                )),
+                //.layer(axum::middleware::from_fn_with_state(state.clone(), middleware::turnstile_verification_middleware)),
+        )
+        .with_state(state.clone());
+
+    // Merge public and protected routes; the request ID is assigned (or taken from
+    // an upstream X-Request-Id) before the per-router trace spans are created
+    let request_id_header =
+        axum::http::HeaderName::from_static(middleware::request_id::REQUEST_ID_HEADER);
+    // Bodies over MAX_REQUEST_BODY_BYTES are refused with 413 from Content-Length
+    // (or once the limit is read) instead of being buffered; this replaces axum's
+    // 2 MB extractor default. Large JSON responses are gzip/br compressed.
+    let max_body_bytes = config.max_request_body_bytes;
+    // Banned clients are refused right after getting a request ID, before anything else runs
+    let app = public_routes.merge(protected_routes).layer(
+        ServiceBuilder::new()
+            .layer(SetRequestIdLayer::new(request_id_header.clone(), MakeRequestUuid))
+            .layer(PropagateRequestIdLayer::new(request_id_header))
+            .layer(axum::middleware::from_fn_with_state(
+                state,
+                middleware::ban_middleware,
+            ))
+            .layer(CompressionLayer::new())
+            .layer(DefaultBodyLimit::disable())
+            .layer(RequestBodyLimitLayer::new(max_body_bytes)),
+    );
+
+    // Server configuration: HOST may be an IP (0.0.0.0 / :: for containers) or a
+    // hostname; UNIX_SOCKET_PATH serves on a Unix domain socket instead of TCP
+    let (host, port) = (config.host.as_str(), config.port);
+    let unix_socket_path = config.unix_socket_path.clone();
+
+    // Start the server using Axum 0.7 syntax; on SIGTERM/Ctrl-C it stops accepting
+    // connections and waits for in-flight requests, up to SHUTDOWN_TIMEOUT_SECS
+    let shutdown_timeout = config.shutdown_timeout;
+    let server: std::pin::Pin<Box<dyn std::future::Future<Output = std::io::Result<()>> + Send>> =
+        match &unix_socket_path {
+            #[cfg(unix)]
+            Some(path) => {
+                // UNIX_SOCKET_MODE (e.g. 660) lets the proxy's group connect
+                let listener = unix_socket::bind(path, config.unix_socket_mode).map_err(|e| {
+                    anyhow::anyhow!("Failed to bind Unix socket {}: {}", path.display(), e)
+                })?;
+
+                info!("🚀 Server starting on unix:{}", path.display());
+                tokio::spawn(shutdown::signal());
+                Box::pin(unix_socket::serve(listener, app))
+            }
+            #[cfg(not(unix))]
+            Some(_) => anyhow::bail!("UNIX_SOCKET_PATH is only supported on Unix"),
+            None => {
+                let listener = tokio::net::TcpListener::bind((host, port))
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to bind {}:{}: {}", host, port, e))?;
+
+                info!("🚀 Server starting on http://{}", listener.local_addr()?);
+                Box::pin(std::future::IntoFuture::into_future(
+                    axum::serve(
+                        listener,
+                        app.into_make_service_with_connect_info::<SocketAddr>(),
+                    )
+                    .with_graceful_shutdown(shutdown::signal()),
+                ))
+            }
+        };
+
+    tokio::select! {
+        result = server => result?,
+        _ = async {
+            shutdown::requested().await;
+            tokio::time::sleep(shutdown_timeout).await;
+        } => warn!(
+            "⚠️ Requests still in flight after {}s, closing their connections",
+            shutdown_timeout.as_secs()
+        ),
+    }
+    info!("🛑 Server stopped accepting requests, waiting for background jobs");
+    #[cfg(unix)]
+    if let Some(path) = &unix_socket_path {
+        let _ = std::fs::remove_file(path);
+    }
+
+    // Background jobs stop at their next tick; give running iterations the same deadline
+    shutdown::drain_jobs(shutdown_timeout).await;
+
+    db_pools.close().await;
+    info!("👋 Database pool closed, shutdown complete");
+
+    Ok(())
+}
+
+// Run migrations with better error handling (can be disabled via env var)
+async fn run_migrations(pool: &PgPool) -> anyhow::Result<()> {
+    if config::get().skip_migrations {
+        warn!("⚠️ Skipping migrations due to SKIP_MIGRATIONS=true");
+    } else {
+        info!("🔄 Running database migrations...");
+        // Index builds may outlast the per-query statement timeout
+        let mut conn = database::acquire_long_running(pool).await?;
+        match database::MIGRATOR.run(&mut conn).await {
+            Ok(_) => info!("✅ Migrations completed successfully"),
+            Err(sqlx::migrate::MigrateError::VersionMismatch(version)) => {
+                error!("⚠️  Migration version mismatch: {}", version);
+                error!("Database has different migration state than expected");
+                error!("Consider resetting migrations: DROP TABLE _sqlx_migrations;");
+                return Err(anyhow::anyhow!("Migration version mismatch"));
+            }
+            Err(e) => {
+                error!("❌ Failed to run migrations: {}", e);
+                error!("Migration error details: {:?}", e);
+                return Err(anyhow::anyhow!("Migration failed: {}", e));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn health_check() -> Result<Json<serde_json::Value>, AppError> {
+    // Tell load balancers to stop routing here while connections drain
+    if shutdown::is_requested() {
+        return Err(AppError::ServiceUnavailable("Shutting down".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "status": "healthy",
+        "service": "honsemoe-backend",
+        "timestamp": chrono::Utc::now(),
+        "version": "1.0.0",
+        "endpoints": {
+            "search": "/api/v3/search",
+            "stats": "/api/stats",
+            "tasks": "/api/tasks",
+            "circles": "/api/v4/circles",
+            "health": "/api/health"
+        }
+    })))
+}
+
+/// GET /healthz - Liveness probe
+///
+/// Only says the process is up and serving; it checks no dependencies, so a
+/// database outage doesn't get the container restarted in a loop.
+async fn liveness_check() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "alive" }))
+}
+
+/// GET /readyz - Readiness probe
+///
+/// 200 when the database answers, every embedded migration is applied and the
+/// cache works; 503 otherwise (and while shutting down), with each check's result.
+async fn readiness_check(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let (database, migrations) = if state.config.mock_mode {
+        let skipped = serde_json::json!({ "ok": true, "skipped": "MOCK_MODE" });
+        (skipped.clone(), skipped)
+    } else {
+        (check_database(&state.db).await, check_migrations(&state.db).await)
+    };
+    let cache = check_cache();
+    // Informational only: reads fall back to the primary while replicas are down
+    let replicas: Vec<serde_json::Value> = state
+        .db_pools
+        .replica_statuses()
+        .into_iter()
+        .map(|(name, healthy)| serde_json::json!({ "name": name, "ok": healthy }))
+        .collect();
+    let shutting_down = shutdown::is_requested();
+
+    let ready = !shutting_down
+        && [&database, &migrations, &cache]
+            .iter()
+            .all(|check| check["ok"].as_bool() == Some(true));
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "status": if ready { "ready" } else { "not_ready" },
+            "shutting_down": shutting_down,
+            "checks": {
+                "database": database,
+                "migrations": migrations,
+                "cache": cache,
+                "replicas": replicas
+            }
+        })),
+    )
+}
+
+/// Longest the readiness probe waits on the database before reporting it down
+const READINESS_DB_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+async fn check_database(pool: &PgPool) -> serde_json::Value {
+    let started = std::time::Instant::now();
+    let result = tokio::time::timeout(
+        READINESS_DB_TIMEOUT,
+        sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(pool),
+    )
+    .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(Ok(_)) => serde_json::json!({ "ok": true, "latency_ms": latency_ms }),
+        Ok(Err(e)) => serde_json::json!({ "ok": false, "latency_ms": latency_ms, "error": e.to_string() }),
+        Err(_) => serde_json::json!({ "ok": false, "latency_ms": latency_ms, "error": "timed out" }),
+    }
+}
+
+/// Compare the database's applied migrations with the embedded ones.
+/// A failed migration is never ready; pending ones are tolerated with
+/// SKIP_MIGRATIONS, where the schema is managed outside the app.
+async fn check_migrations(pool: &PgPool) -> serde_json::Value {
+    let expected: Vec<i64> = database::MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .collect();
+    let latest = expected.iter().max().copied();
+    let skipped = config::get().skip_migrations;
+
+    let applied = tokio::time::timeout(
+        READINESS_DB_TIMEOUT,
+        sqlx::query_as::<_, (i64, bool)>("SELECT version, success FROM _sqlx_migrations")
+            .fetch_all(pool),
+    )
+    .await;
+    let applied = match applied {
+        Ok(Ok(rows)) => rows,
+        // No migration table: fine if migrations are run by hand
+        Ok(Err(sqlx::Error::Database(e))) if e.code().as_deref() == Some("42P01") => {
+            return serde_json::json!({
+                "ok": skipped,
+                "expected": expected.len(),
+                "latest": latest,
+                "error": "_sqlx_migrations table does not exist"
+            });
+        }
+        Ok(Err(e)) => return serde_json::json!({ "ok": false, "error": e.to_string() }),
+        Err(_) => return serde_json::json!({ "ok": false, "error": "timed out" }),
+    };
+
+    let failed: Vec<i64> = applied
+        .iter()
+        .filter(|(_, success)| !success)
+        .map(|(version, _)| *version)
+        .collect();
+    let pending: Vec<i64> = expected
+        .iter()
+        .filter(|version| !applied.iter().any(|(v, success)| v == *version && *success))
+        .filter(|version| !failed.contains(version))
+        .copied()
+        .collect();
+
+    serde_json::json!({
+        "ok": failed.is_empty() && (pending.is_empty() || skipped),
+        "expected": expected.len(),
+        "applied": applied.len() - failed.len(),
+        "latest": latest,
+        "pending": pending,
+        "failed": failed
+    })
+}
+
+/// Round-trip a value through the in-memory cache
+fn check_cache() -> serde_json::Value {
+    let probe = chrono::Utc::now().timestamp_micros();
+    let key = "readyz:probe";
+    let roundtrip = cache::set(key, &probe, std::time::Duration::from_secs(5)).is_ok()
+        && cache::get::<i64>(key) == Some(probe);
+    cache::invalidate(key);
+
+    serde_json::json!({
+        "ok": roundtrip,
+        "entries": cache::stats().entry_count
+    })
+}
+
+// Background task to rebuild the circle leaderboard snapshot served to unfiltered list requests
+async fn circle_leaderboard_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60)); // 1 minute
+
+    info!("🏁 Starting circle leaderboard snapshot task (runs every minute)");
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        if let Err(e) = handlers::circles::refresh_leaderboard_snapshot(&pool).await {
+            warn!("⚠️ Failed to rebuild circle leaderboard snapshot: {}", e);
+        }
+    }
+}
+
+// Background task to record each circle's monthly point/rank for the day
+// Runs hourly and upserts the ranking day's row, so the snapshot ends up holding the last value
+// of the day. Ranking days roll over with the months, so a month's last snapshot is its final one.
+async fn snapshot_circle_ranks_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600)); // 1 hour
+
+    info!("📈 Starting circle rank history snapshot task (runs every hour)");
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        match sqlx::query(
+            r#"
+            INSERT INTO circle_rank_history (circle_id, snapshot_date, monthly_rank, monthly_point, member_count, recorded_at)
+            SELECT circle_id, competition_date(), monthly_rank, monthly_point, member_count, CURRENT_TIMESTAMP
+            FROM circles
+            WHERE monthly_point IS NOT NULL AND NOT COALESCE(archived, FALSE)
+            ON CONFLICT (circle_id, snapshot_date) DO UPDATE SET
+                monthly_rank = EXCLUDED.monthly_rank,
+                monthly_point = EXCLUDED.monthly_point,
+                member_count = EXCLUDED.member_count,
+                recorded_at = EXCLUDED.recorded_at
+            "#,
+        )
+        .execute(&pool)
+        .await
+        {
+            Ok(result) => info!("📈 Recorded rank history for {} circles", result.rows_affected()),
+            Err(e) => warn!("⚠️ Failed to snapshot circle ranks: {}", e),
+        }
+    }
+}
+
+// Background task to record members missing from their circle's latest scrape as having left
+// Joins are recorded by a trigger on circle_member_fans_monthly; this only handles leaves.
+// Circles are only checked once their latest scrape has been quiet for 10 minutes.
+async fn detect_circle_departures_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(900)); // 15 minutes
+
+    info!("🚪 Starting circle departure detection task (runs every 15 minutes)");
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        match sqlx::query(
+            r#"
+            WITH latest AS (
+                SELECT circle_id, MAX(last_seen_at) AS latest_scrape
+                FROM circle_member_roster
+                GROUP BY circle_id
+            ),
+            departed AS (
+                DELETE FROM circle_member_roster r
+                USING latest l
+                WHERE r.circle_id = l.circle_id
+                  AND l.latest_scrape < CURRENT_TIMESTAMP - INTERVAL '10 minutes'
+                  AND r.last_seen_at < l.latest_scrape - INTERVAL '10 minutes'
+                RETURNING r.circle_id, r.viewer_id
+            )
+            INSERT INTO circle_member_events (circle_id, viewer_id, event_type)
+            SELECT circle_id, viewer_id, 'left' FROM departed
+            "#,
+        )
+        .execute(&pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() > 0 => {
+                info!("🚪 Recorded {} circle departures", result.rows_affected())
+            }
+            Ok(_) => {}
+            Err(e) => warn!("⚠️ Failed to detect circle departures: {}", e),
+        }
+    }
+}
+
+// Background task to post rank-change notifications to verified circle webhooks
+async fn circle_webhook_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600)); // 1 hour
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+
+    info!("🔔 Starting circle webhook notifier (runs every hour)");
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        match handlers::circles::notify_circle_rank_changes(&pool, &client).await {
+            Ok(sent) if sent > 0 => info!("🔔 Sent {} circle rank notifications", sent),
+            Ok(_) => {}
+            Err(e) => warn!("⚠️ Circle webhook notifications failed: {}", e),
+        }
+    }
+}
+
+// Background task to compute last month's circle awards once the month is over
+async fn circle_awards_rollup_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600)); // 1 hour
+
+    info!("🏆 Starting circle awards rollup task (runs every hour)");
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        let (year, month) = competition::previous_month();
+        let computed = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM circle_awards WHERE year = $1 AND month = $2)",
+        )
+        .bind(year)
+        .bind(month)
+        .fetch_one(&pool)
+        .await;
+
+        match computed {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(e) => {
+                warn!("⚠️ Failed to check circle awards: {}", e);
+                continue;
+            }
+        }
+
+        match handlers::circles::compute_circle_awards(&pool, year, month).await {
+            Ok(count) => info!("🏆 Computed {} circle awards for {}-{:02}", count, year, month),
+            Err(e) => warn!("⚠️ Failed to compute circle awards: {}", e),
+        }
+    }
+}
+
+// Background task to snapshot the support card leaderboard once per competition week
+async fn support_card_leaderboard_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600)); // 1 hour
+
+    info!("🃏 Starting support card leaderboard task (runs every hour)");
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        let week_start = competition::current_week_start();
+        let computed = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM support_card_leaderboard WHERE week_start = $1)",
+        )
+        .bind(week_start)
+        .fetch_one(&pool)
+        .await;
+
+        match computed {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(e) => {
+                warn!("⚠️ Failed to check the support card leaderboard: {}", e);
+                continue;
+            }
+        }
+
+        match handlers::leaderboards::compute_support_card_leaderboard(&pool, week_start).await {
+            Ok(count) => info!(
+                "🃏 Computed the support card leaderboard for the week of {} ({} cards)",
+                week_start, count
+            ),
+            Err(e) => warn!("⚠️ Failed to compute the support card leaderboard: {}", e),
+        }
+    }
+}
+
+// Background task to snapshot the global leaderboards once per competition day
+async fn leaderboards_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600)); // 1 hour
+
+    info!("🥇 Starting leaderboard snapshot task (runs every hour)");
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        let today = competition::now().date_naive();
+        let computed = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM leaderboard_snapshots WHERE snapshot_date = $1)",
+        )
+        .bind(today)
+        .fetch_one(&pool)
+        .await;
+
+        match computed {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(e) => {
+                warn!("⚠️ Failed to check leaderboard snapshots: {}", e);
+                continue;
+            }
+        }
+
+        match handlers::leaderboards::compute_leaderboards(&pool, today).await {
+            Ok(count) => info!("🥇 Computed leaderboards for {} ({} entries)", today, count),
+            Err(e) => warn!("⚠️ Failed to compute leaderboards: {}", e),
+        }
+    }
+}
+
+// Background task to pick up bans made on other instances and drop expired ones
+async fn ban_refresh_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60)); // 1 minute
+
+    info!("🚫 Starting ban list refresh task (runs every minute)");
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        if let Err(e) = bans::reload(&pool).await {
+            warn!("⚠️ Failed to reload ban list: {}", e);
+        }
+    }
+}
+
+// Background task checking read replicas, so reads skip the ones that are down
+async fn replica_health_task(db_pools: Arc<DbPools>) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(10));
+
+    info!("🩺 Starting read replica health check task (runs every 10 seconds)");
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        db_pools.check_replicas().await;
+    }
+}
+
+// Background task to re-queue tasks whose worker lease has expired
+async fn stale_task_reaper_task(pool: PgPool) {
+    let interval_secs = config::get().task_reaper_interval.as_secs();
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
+
+    info!("🧟 Starting stale task reaper (runs every {} seconds)", interval_secs);
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        match handlers::tasks::reap_stale_tasks(&pool).await {
+            Ok(result) if result.requeued > 0 || result.failed > 0 => warn!(
+                "🧟 Reaped stale tasks: {} requeued, {} failed",
+                result.requeued, result.failed
+            ),
+            Ok(_) => {}
+            Err(e) => warn!("⚠️ Stale task sweep failed: {}", e),
+        }
+    }
+}
+
+// Background task to keep completed tasks out of the hot tasks table
+async fn task_archiver_task(pool: PgPool) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(900)); // 15 minutes
+
+    info!("🗄️ Starting completed task archiver (runs every 15 minutes)");
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        match handlers::tasks::archive_completed_tasks(&pool).await {
+            Ok(result) if result.archived > 0 => {
+                info!("🗄️ Archived {} completed tasks", result.archived)
+            }
+            Ok(_) => {}
+            Err(e) => warn!("⚠️ Task archival failed: {}", e),
+        }
+    }
+}
+
+// Background task to ping the WebSub hub whenever the notable feed gains entries
+async fn websub_ping_task(pool: PgPool, hub_url: String) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300)); // 5 minutes
+    let client = reqwest::Client::new();
+    let feed_url = feeds::feed_url();
+    let mut last_published = None;
+
+    info!("📣 Starting WebSub ping task for {} (runs every 5 minutes)", hub_url);
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        let latest = match feeds::latest_notable_ingest(&pool).await {
+            Ok(latest) => latest,
+            Err(e) => {
+                warn!("⚠️ Failed to check notable feed for new records: {}", e);
+                continue;
+            }
+        };
+
+        if latest.is_none() || latest == last_published {
+            continue;
+        }
+        // The first check only records the current state
+        if last_published.is_none() {
+            last_published = latest;
+            continue;
+        }
+
+        cache::invalidate("feeds:notable");
+        match feeds::ping_websub_hub(&client, &hub_url, &feed_url).await {
+            Ok(()) => {
+                info!("📣 Notified WebSub hub about new notable records");
+                last_published = latest;
+            }
+            Err(e) => warn!("⚠️ WebSub hub ping failed: {}", e),
+        }
+    }
+}
+
+// Background task to invalidate cached responses affected by domain events
+async fn cache_invalidation_task() {
+    let mut events = events::subscribe();
+
+    loop {
+        let event = tokio::select! {
+            _ = shutdown::requested() => break,
+            event = events.recv() => event,
+        };
+
+        match event {
+            Ok(events::DomainEvent::TrainerDataChanged { account_ids }) => {
+                // Search pages and counts can include any trainer, so drop them all
+                let removed = cache::invalidate_namespaces(cache::Namespace::TRAINER_DATA);
+                for account_id in &account_ids {
+                    let share = cache::Namespace::Share;
+                    cache::invalidate_prefix(&share.key(&format!("inheritance:{}:", account_id)));
+                    cache::invalidate_prefix(&share.key(&format!("support-card:{}:", account_id)));
+                }
+                info!(
+                    "🧹 Trainer data changed for {} account(s), invalidated {} cached search pages/counts",
+                    account_ids.len(),
+                    removed
+                );
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("⚠️ Cache invalidation lagged by {} events, clearing cache", skipped);
+                cache::clear_all();
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+// Background task to clean up expired cache entries
+async fn cache_cleanup_task() {
+    let interval_secs = config::get().cache_cleanup_interval.as_secs();
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
+
+    info!("🧹 Starting cache cleanup background task (runs every {} seconds)", interval_secs);
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        // Clean up expired entries
+        let removed = cache::cleanup_expired();
+
+        // Log cache stats
+        let stats = cache::stats();
+        info!(
+            "📊 Cache stats: {} entries, {:.2} MB total, {} expired removed ({} since startup)",
+            stats.entry_count,
+            stats.total_size_bytes as f64 / 1_048_576.0,
+            removed,
+            stats.expired_removed
+        );
+    }
+}
+
+// Background task to drop expired Turnstile tokens, API key lookups, worker signatures and auto-ban strikes
+async fn token_cleanup_task() {
+    let interval_secs = config::get().token_cleanup_interval.as_secs();
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
+
+    info!("🧹 Starting token cleanup background task (runs every {} seconds)", interval_secs);
+
+    loop {
+        if shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        let tokens = middleware::turnstile::cleanup_expired_tokens();
+        let keys = middleware::api_key::cleanup_expired_keys();
+        let signatures = middleware::worker_auth::cleanup_seen_signatures();
+        let strikes = bans::cleanup_strikes();
+        if tokens > 0 || keys > 0 || signatures > 0 || strikes > 0 {
+            info!(
+                "🧹 Removed {} expired Turnstile tokens, {} API key lookups, {} worker signatures and {} auto-ban strike windows",
+                tokens, keys, signatures, strikes
+            );
+        }
+    }
+}
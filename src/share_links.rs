@@ -0,0 +1,121 @@
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+
+/// Characters in a token; eight of them give ~2^47 tokens
+const TOKEN_ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const TOKEN_LENGTH: usize = 8;
+
+/// Longest target accepted, so one link can't bloat the table
+const MAX_TARGET_LENGTH: usize = 2048;
+
+/// Hosts share URLs are accepted from (short link targets, /oembed)
+pub const SHARE_HOSTS: &[&str] = &["honse.moe", "www.honse.moe", "uma.moe", "www.uma.moe"];
+
+fn random_token() -> String {
+    let mut value = uuid::Uuid::new_v4().as_u128();
+    let base = TOKEN_ALPHABET.len() as u128;
+    (0..TOKEN_LENGTH)
+        .map(|_| {
+            let c = TOKEN_ALPHABET[(value % base) as usize] as char;
+            value /= base;
+            c
+        })
+        .collect()
+}
+
+fn is_token(value: &str) -> bool {
+    value.len() == TOKEN_LENGTH && value.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+/// Path, query and fragment of a honse.moe URL or absolute path
+///
+/// Anything pointing at another host is rejected so short links can't be
+/// used as an open redirect.
+pub fn normalize_target(url: &str) -> Result<String, AppError> {
+    let invalid = || AppError::BadRequest("URL must be a honse.moe link".to_string());
+
+    let url = url.trim();
+    if url.is_empty() || url.len() > MAX_TARGET_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "URL must be between 1 and {} characters",
+            MAX_TARGET_LENGTH
+        )));
+    }
+
+    let base = url::Url::parse("https://honse.moe/").expect("valid base URL");
+    let parsed = if url.starts_with('/') {
+        base.join(url).map_err(|_| invalid())?
+    } else {
+        url::Url::parse(url).map_err(|_| invalid())?
+    };
+    if !matches!(parsed.scheme(), "http" | "https")
+        || !parsed.host_str().is_some_and(|host| SHARE_HOSTS.contains(&host))
+    {
+        return Err(invalid());
+    }
+
+    let mut target = parsed.path().to_string();
+    if target.starts_with("/s/x/") {
+        return Err(AppError::BadRequest("URL is already a short link".to_string()));
+    }
+    if let Some(query) = parsed.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+    if let Some(fragment) = parsed.fragment() {
+        target.push('#');
+        target.push_str(fragment);
+    }
+    Ok(target)
+}
+
+/// Token for a normalized target, reusing the existing one if it was shortened before
+pub async fn shorten(pool: &PgPool, target: &str) -> Result<String, AppError> {
+    // A fresh token only fails on a collision with another token; retry a few times
+    for _ in 0..3 {
+        let result = sqlx::query_scalar::<_, String>(
+            r#"
+            INSERT INTO share_links (token, target)
+            VALUES ($1, $2)
+            ON CONFLICT (target) DO UPDATE SET target = EXCLUDED.target
+            RETURNING token
+            "#,
+        )
+        .bind(random_token())
+        .bind(target)
+        .fetch_one(pool)
+        .await;
+
+        match result {
+            Ok(token) => return Ok(token),
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Err(AppError::DatabaseError(
+        "Could not allocate a share link token".to_string(),
+    ))
+}
+
+/// Target of a token, counting the visit
+pub async fn resolve(pool: &PgPool, token: &str) -> Result<Option<String>, AppError> {
+    if !is_token(token) {
+        return Ok(None);
+    }
+
+    let target = sqlx::query_scalar::<_, String>(
+        r#"
+        UPDATE share_links
+        SET hits = hits + 1, last_used_at = CURRENT_TIMESTAMP
+        WHERE token = $1
+        RETURNING target
+        "#,
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(target)
+}
@@ -0,0 +1,112 @@
+//! Graceful shutdown: SIGTERM/SIGINT handling and draining of background jobs.
+
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time::{Instant, Interval};
+use tracing::{info, warn};
+
+static SHUTDOWN: OnceLock<watch::Sender<bool>> = OnceLock::new();
+
+/// Background jobs waited for (up to the deadline) before the pool is closed
+static JOBS: OnceLock<Mutex<JoinSet<()>>> = OnceLock::new();
+
+fn sender() -> &'static watch::Sender<bool> {
+    SHUTDOWN.get_or_init(|| watch::channel(false).0)
+}
+
+fn jobs() -> &'static Mutex<JoinSet<()>> {
+    JOBS.get_or_init(|| Mutex::new(JoinSet::new()))
+}
+
+/// Whether shutdown has started
+pub fn is_requested() -> bool {
+    *sender().borrow()
+}
+
+/// Resolves once shutdown has started
+pub async fn requested() {
+    let mut receiver = sender().subscribe();
+    let _ = receiver.wait_for(|&requested| requested).await;
+}
+
+/// Resolves on SIGTERM or Ctrl-C and starts the shutdown
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("⚠️ Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("⚠️ Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+
+    info!("🛑 Shutdown requested, no longer accepting connections");
+    sender().send_replace(true);
+}
+
+/// Run a background job that is waited for on shutdown
+///
+/// Jobs should stop at their next wait point once shutdown starts (see `tick`),
+/// so work already in progress finishes instead of being cut off.
+pub fn spawn_job<F>(job: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    if let Ok(mut jobs) = jobs().lock() {
+        jobs.spawn(job);
+    }
+}
+
+/// Wait for the next interval tick; None once shutdown has started
+pub async fn tick(interval: &mut Interval) -> Option<Instant> {
+    tokio::select! {
+        biased;
+        _ = requested() => None,
+        instant = interval.tick() => Some(instant),
+    }
+}
+
+/// Wait for background jobs to finish, aborting those still running after `deadline`
+pub async fn drain_jobs(deadline: Duration) {
+    let mut jobs = match jobs().lock() {
+        Ok(mut jobs) => std::mem::take(&mut *jobs),
+        Err(_) => return,
+    };
+
+    let finished = tokio::time::timeout(deadline, async {
+        while jobs.join_next().await.is_some() {}
+    })
+    .await;
+
+    if finished.is_err() {
+        warn!(
+            "⚠️ {} background job(s) still running after {}s, aborting them",
+            jobs.len(),
+            deadline.as_secs()
+        );
+        jobs.shutdown().await;
+    } else {
+        info!("✅ Background jobs stopped");
+    }
+}
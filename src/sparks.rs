@@ -0,0 +1,172 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// Highest factor ID expanded for level-only ("any factor at ★N") filters
+const MAX_WILDCARD_FACTOR_ID: i32 = 100;
+
+/// How spark values are packed into integers
+///
+/// The API always takes and returns v1 values; searches are translated to the
+/// active encoding so the switch to v2 is invisible to clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SparkEncoding {
+    /// factor_id * 10 + level, stored in the original columns
+    V1,
+    /// factor_id * 1000 + level, stored in the *_v2 columns
+    V2,
+}
+
+// 0 = not initialised yet, otherwise SparkEncoding as 1/2
+static ACTIVE: AtomicU8 = AtomicU8::new(0);
+
+impl SparkEncoding {
+    /// Encoding searches currently run against (SPARK_ENCODING, overridable at runtime)
+    pub fn active() -> Self {
+        match ACTIVE.load(Ordering::Relaxed) {
+            1 => SparkEncoding::V1,
+            2 => SparkEncoding::V2,
+            _ => {
+                let encoding = crate::config::get().spark_encoding;
+                encoding.activate();
+                encoding
+            }
+        }
+    }
+
+    /// Switch searches to this encoding
+    pub fn activate(self) {
+        let value = match self {
+            SparkEncoding::V1 => 1,
+            SparkEncoding::V2 => 2,
+        };
+        ACTIVE.store(value, Ordering::Relaxed);
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SparkEncoding::V1 => "v1",
+            SparkEncoding::V2 => "v2",
+        }
+    }
+
+    /// factor_id multiplier; `value % multiplier` is the level
+    pub fn multiplier(self) -> i32 {
+        match self {
+            SparkEncoding::V1 => 10,
+            SparkEncoding::V2 => 1000,
+        }
+    }
+
+    pub fn encode(self, factor_id: i32, level: i32) -> i32 {
+        factor_id * self.multiplier() + level
+    }
+
+    /// Split a stored value back into (factor_id, level)
+    pub fn decode(self, value: i32) -> (i32, i32) {
+        (value / self.multiplier(), value % self.multiplier())
+    }
+
+    /// Column holding this encoding, e.g. "i.blue_sparks" -> "i.blue_sparks_v2"
+    pub fn column(self, column: &str) -> String {
+        match self {
+            SparkEncoding::V1 => column.to_string(),
+            SparkEncoding::V2 => format!("{}_v2", column),
+        }
+    }
+
+    /// Translate a client-supplied (v1) value. Values below 10 are level-only
+    /// wildcards and are kept as is.
+    pub fn translate_v1(self, value: i32) -> i32 {
+        if value < 10 {
+            value
+        } else {
+            self.encode(value / 10, value % 10)
+        }
+    }
+
+    /// Every encoded value for the given levels across all known factors
+    pub fn expand_wildcard_levels(self, levels: &[i32]) -> Vec<i32> {
+        (1..=MAX_WILDCARD_FACTOR_ID)
+            .flat_map(|factor_id| {
+                levels
+                    .iter()
+                    .map(move |&level| self.encode(factor_id, level))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Same rules as the calculate_sparks_score SQL function: each matching
+    /// spark adds its level, each distinct matching factor adds 100.
+    fn score(encoding: SparkEncoding, sparks: &[i32], factor_ids: &[i32]) -> i32 {
+        let mut seen = Vec::new();
+        let mut score = 0;
+        for &spark in sparks {
+            let (factor_id, level) = encoding.decode(spark);
+            if factor_ids.contains(&factor_id) {
+                score += level;
+                if !seen.contains(&factor_id) {
+                    seen.push(factor_id);
+                    score += 100;
+                }
+            }
+        }
+        score
+    }
+
+    #[test]
+    fn encodes_known_values() {
+        assert_eq!(SparkEncoding::V1.encode(101, 3), 1013);
+        assert_eq!(SparkEncoding::V2.encode(101, 3), 101003);
+        assert_eq!(SparkEncoding::V2.encode(2_000, 12), 2_000_012);
+    }
+
+    #[test]
+    fn decodes_v2_values() {
+        assert_eq!(SparkEncoding::V2.decode(101003), (101, 3));
+        assert_eq!(SparkEncoding::V2.decode(2_000_012), (2_000, 12));
+        assert_eq!(SparkEncoding::V1.decode(1013), (101, 3));
+    }
+
+    #[test]
+    fn translates_v1_values() {
+        assert_eq!(SparkEncoding::V2.translate_v1(1013), 101003);
+        assert_eq!(SparkEncoding::V2.translate_v1(10), 1000);
+        assert_eq!(SparkEncoding::V1.translate_v1(1013), 1013);
+        // Level-only wildcards pass through untouched
+        assert_eq!(SparkEncoding::V2.translate_v1(3), 3);
+    }
+
+    #[test]
+    fn v2_columns_are_suffixed() {
+        assert_eq!(SparkEncoding::V1.column("i.blue_sparks"), "i.blue_sparks");
+        assert_eq!(SparkEncoding::V2.column("i.blue_sparks"), "i.blue_sparks_v2");
+    }
+
+    #[test]
+    fn expands_wildcards_over_all_factors() {
+        let values = SparkEncoding::V2.expand_wildcard_levels(&[3, 5]);
+        assert_eq!(values.len(), 2 * MAX_WILDCARD_FACTOR_ID as usize);
+        assert!(values.contains(&1003));
+        assert!(values.contains(&100005));
+        assert!(values.iter().all(|&v| matches!(v % 1000, 3 | 5)));
+    }
+
+    #[test]
+    fn scores_match_across_encodings() {
+        let v1 = [1013, 1012, 2021, 3033];
+        let v2: Vec<i32> = v1.iter().map(|&v| SparkEncoding::V2.translate_v1(v)).collect();
+        assert_eq!(v2, [101003, 101002, 202001, 303003]);
+
+        // Factor 101 twice (3 + 2 + 100), factor 202 once (1 + 100), 303 not requested
+        assert_eq!(score(SparkEncoding::V1, &v1, &[101, 202]), 206);
+        assert_eq!(score(SparkEncoding::V2, &v2, &[101, 202]), 206);
+        assert_eq!(score(SparkEncoding::V2, &v2, &[999]), 0);
+    }
+}
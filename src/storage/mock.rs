@@ -0,0 +1,351 @@
+use futures_util::future::{self, BoxFuture, FutureExt};
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+use super::{StatsCounts, Storage};
+use crate::errors::AppError;
+use crate::models::{
+    AccountId, Circle, CircleId, CircleListParams, CircleMemberFansMonthly, CircleQueryParams,
+    Inheritance, MemberInheritanceSummary, SupportCard, TaskOutcome, UnifiedAccountRecord,
+    UnifiedSearchParams, ViewerId,
+};
+
+const SEARCH_FIXTURES_JSON: &str = include_str!("../../data/mock/search.json");
+const CIRCLE_FIXTURES_JSON: &str = include_str!("../../data/mock/circles.json");
+const STATS_FIXTURES_JSON: &str = include_str!("../../data/mock/stats.json");
+
+#[derive(Deserialize)]
+struct CircleFixtures {
+    circles: Vec<Circle>,
+    members: Vec<CircleMemberFansMonthly>,
+}
+
+/// Storage serving the fixtures bundled under data/mock (MOCK_MODE=true).
+///
+/// Results are deterministic: fixtures keep their file order and only the
+/// basic filters are applied (search: trainer, main parent, support card and
+/// search_type; circles: name/query, min_members, max_rank and sorting).
+pub struct MockStorage {
+    records: Vec<UnifiedAccountRecord>,
+    circles: Vec<Circle>,
+    members: Vec<CircleMemberFansMonthly>,
+    stats: StatsCounts,
+}
+
+impl MockStorage {
+    pub fn load() -> serde_json::Result<Self> {
+        let circle_fixtures: CircleFixtures = serde_json::from_str(CIRCLE_FIXTURES_JSON)?;
+        Ok(Self {
+            records: serde_json::from_str(SEARCH_FIXTURES_JSON)?,
+            circles: circle_fixtures.circles,
+            members: circle_fixtures.members,
+            stats: serde_json::from_str(STATS_FIXTURES_JSON)?,
+        })
+    }
+
+    fn matching_records<'a>(
+        &'a self,
+        params: &'a UnifiedSearchParams,
+    ) -> impl Iterator<Item = &'a UnifiedAccountRecord> {
+        self.records.iter().filter(move |record| {
+            let inheritance = record.inheritance.as_ref();
+            let support_card = record.support_card.as_ref();
+
+            let type_matches = match params.search_type.as_deref() {
+                Some("inheritance") => inheritance.is_some(),
+                Some("support_cards") => support_card.is_some(),
+                Some("combined") => inheritance.is_some() && support_card.is_some(),
+                _ => true,
+            };
+
+            type_matches
+                && params
+                    .trainer_id
+                    .as_ref()
+                    .is_none_or(|id| &record.account_id == id)
+                && params.trainer_name.as_deref().is_none_or(|name| {
+                    record
+                        .trainer_name
+                        .to_lowercase()
+                        .contains(&name.to_lowercase())
+                })
+                && params.main_parent_id.is_none_or(|id| {
+                    inheritance.is_some_and(|i| i.main_parent_id.base() == id.base())
+                })
+                && params.support_card_id.is_none_or(|id| {
+                    support_card.is_some_and(|card| card.support_card_id == id)
+                })
+        })
+    }
+
+    /// Circle members for the requested month, or the latest fixture month
+    fn members_of(
+        &self,
+        circle_id: CircleId,
+        params: &CircleQueryParams,
+    ) -> Vec<CircleMemberFansMonthly> {
+        let circle_members = self.members.iter().filter(|m| m.circle_id == circle_id);
+        let month = match (params.year, params.month) {
+            (Some(year), Some(month)) => Some((year, month)),
+            _ => circle_members.clone().map(|m| (m.year, m.month)).max(),
+        };
+
+        circle_members
+            .filter(|m| Some((m.year, m.month)) == month)
+            .cloned()
+            .collect()
+    }
+}
+
+fn cmp_nulls_last<T: Ord>(a: Option<T>, b: Option<T>, desc: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if desc => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    }
+}
+
+fn inheritance_summary(inheritance: &Inheritance) -> MemberInheritanceSummary {
+    MemberInheritanceSummary {
+        inheritance_id: inheritance.inheritance_id,
+        main_parent_id: inheritance.main_parent_id,
+        parent_left_id: inheritance.parent_left_id,
+        parent_right_id: inheritance.parent_right_id,
+        parent_rank: inheritance.parent_rank,
+        parent_rarity: inheritance.parent_rarity,
+        win_count: inheritance.win_count,
+        affinity_score: inheritance.affinity_score.unwrap_or(0),
+        main_blue_factors: inheritance.main_blue_factors,
+        main_pink_factors: inheritance.main_pink_factors,
+        main_green_factors: inheritance.main_green_factors,
+        main_white_factors: inheritance.main_white_factors.clone(),
+    }
+}
+
+fn not_found(circle_id: CircleId) -> AppError {
+    AppError::NotFound(format!("Circle {} not found", circle_id))
+}
+
+impl Storage for MockStorage {
+    fn search_count<'a>(
+        &'a self,
+        params: &'a UnifiedSearchParams,
+    ) -> BoxFuture<'a, Result<i64, AppError>> {
+        future::ready(Ok(self.matching_records(params).count() as i64)).boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        params: &'a UnifiedSearchParams,
+        limit: i64,
+        offset: i64,
+    ) -> BoxFuture<'a, Result<Vec<UnifiedAccountRecord>, AppError>> {
+        let records = self
+            .matching_records(params)
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect();
+        future::ready(Ok(records)).boxed()
+    }
+
+    fn stats_counts(&self) -> BoxFuture<'_, Result<StatsCounts, AppError>> {
+        future::ready(Ok(self.stats.clone())).boxed()
+    }
+
+    fn circle_of_viewer(
+        &self,
+        viewer_id: ViewerId,
+    ) -> BoxFuture<'_, Result<Option<CircleId>, AppError>> {
+        let circle_id = self
+            .members
+            .iter()
+            .find(|m| m.viewer_id == viewer_id)
+            .map(|m| m.circle_id);
+        future::ready(Ok(circle_id)).boxed()
+    }
+
+    fn circle(&self, circle_id: CircleId) -> BoxFuture<'_, Result<Circle, AppError>> {
+        let circle = self
+            .circles
+            .iter()
+            .find(|c| c.circle_id == circle_id)
+            .cloned()
+            .map(crate::moderation::mask_circle)
+            .ok_or_else(|| not_found(circle_id));
+        future::ready(circle).boxed()
+    }
+
+    fn circle_version<'a>(
+        &'a self,
+        _circle_id: CircleId,
+        _params: &'a CircleQueryParams,
+    ) -> BoxFuture<'a, Result<Option<String>, AppError>> {
+        // Fixtures are already in memory
+        future::ready(Ok(None)).boxed()
+    }
+
+    fn circle_percentile(
+        &self,
+        circle_id: CircleId,
+    ) -> BoxFuture<'_, Result<Option<f64>, AppError>> {
+        let points: Vec<i64> = self
+            .circles
+            .iter()
+            .filter(|c| !c.archived.unwrap_or(false))
+            .filter_map(|c| c.monthly_point)
+            .collect();
+        let percentile = self
+            .circles
+            .iter()
+            .find(|c| c.circle_id == circle_id)
+            .and_then(|c| c.monthly_point)
+            .map(|point| {
+                // percent_rank(): share of the other circles with fewer points
+                let below = points.iter().filter(|&&p| p < point).count();
+                let others = points.len().saturating_sub(1).max(1);
+                (below as f64 / others as f64 * 1000.0).round() / 10.0
+            });
+        future::ready(Ok(percentile)).boxed()
+    }
+
+    fn circle_members<'a>(
+        &'a self,
+        circle_id: CircleId,
+        params: &'a CircleQueryParams,
+    ) -> BoxFuture<'a, Result<(Vec<CircleMemberFansMonthly>, i64), AppError>> {
+        let include_records = match crate::handlers::circles::includes_member_records(
+            params.include.as_deref(),
+        ) {
+            Ok(include_records) => include_records,
+            Err(e) => return future::ready(Err(e)).boxed(),
+        };
+
+        let mut members = self.members_of(circle_id, params);
+        let total = members.len() as i64;
+
+        if include_records {
+            for member in &mut members {
+                let account_id = member.viewer_id.to_account_id();
+                member.inheritance = self
+                    .records
+                    .iter()
+                    .find(|r| r.account_id == account_id)
+                    .and_then(|r| r.inheritance.as_ref())
+                    .map(inheritance_summary);
+            }
+        }
+
+        if let Some(limit) = params.limit {
+            let limit = limit.clamp(1, 100);
+            let offset = params.page.unwrap_or(0).max(0) * limit;
+            members = members
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect();
+        }
+        future::ready(Ok((members, total))).boxed()
+    }
+
+    fn queue_circle_fetch(
+        &self,
+        viewer_id: ViewerId,
+    ) -> BoxFuture<'_, Result<TaskOutcome, AppError>> {
+        tracing::info!("🧪 Mock mode: not queueing circle fetch for viewer {}", viewer_id);
+        future::ready(Ok(TaskOutcome::TaskCreated)).boxed()
+    }
+
+    fn list_circles<'a>(
+        &'a self,
+        params: &'a CircleListParams,
+        limit: i64,
+        offset: i64,
+    ) -> BoxFuture<'a, Result<(i64, Vec<Circle>), AppError>> {
+        let search = params
+            .query
+            .as_deref()
+            .or(params.name.as_deref())
+            .map(|q| q.trim().to_lowercase());
+
+        let mut circles: Vec<&Circle> = self
+            .circles
+            .iter()
+            .filter(|c| !c.archived.unwrap_or(false))
+            .filter(|c| {
+                search.as_deref().is_none_or(|q| {
+                    c.name.to_lowercase().contains(q) || c.circle_id.to_string() == q
+                })
+            })
+            .filter(|c| {
+                params
+                    .min_members
+                    .is_none_or(|min| c.member_count.unwrap_or(0) >= min)
+            })
+            .filter(|c| {
+                params
+                    .max_rank
+                    .is_none_or(|max| c.monthly_rank.is_some_and(|rank| rank <= max))
+            })
+            .collect();
+
+        // Stable sort keeps fixture order for ties; missing values sort last like NULLS LAST
+        let desc = params
+            .sort_dir
+            .as_deref()
+            .is_some_and(|dir| dir.eq_ignore_ascii_case("desc"));
+        circles.sort_by(|a, b| match params.sort_by.as_deref() {
+            Some("name") if desc => b.name.cmp(&a.name),
+            Some("name") => a.name.cmp(&b.name),
+            Some("member_count") => cmp_nulls_last(a.member_count, b.member_count, desc),
+            Some("monthly_point") => cmp_nulls_last(a.monthly_point, b.monthly_point, desc),
+            _ => cmp_nulls_last(a.monthly_rank, b.monthly_rank, desc),
+        });
+
+        let total = circles.len() as i64;
+        let page = circles
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect();
+        future::ready(Ok((total, page))).boxed()
+    }
+
+    fn inheritance_share<'a>(
+        &'a self,
+        account_id: &'a AccountId,
+    ) -> BoxFuture<'a, Result<Option<(String, Inheritance)>, AppError>> {
+        let share = self.records.iter().find(|r| &r.account_id == account_id).and_then(|r| {
+            r.inheritance
+                .clone()
+                .map(|inheritance| (r.trainer_name.clone(), inheritance))
+        });
+        future::ready(Ok(share)).boxed()
+    }
+
+    fn inheritance_share_by_id(
+        &self,
+        inheritance_id: i32,
+    ) -> BoxFuture<'_, Result<Option<(String, Inheritance)>, AppError>> {
+        let share = self.records.iter().find_map(|r| {
+            r.inheritance
+                .clone()
+                .filter(|inheritance| inheritance.inheritance_id == inheritance_id)
+                .map(|inheritance| (r.trainer_name.clone(), inheritance))
+        });
+        future::ready(Ok(share)).boxed()
+    }
+
+    fn support_card_share<'a>(
+        &'a self,
+        account_id: &'a AccountId,
+    ) -> BoxFuture<'a, Result<Option<(String, SupportCard)>, AppError>> {
+        let share = self.records.iter().find(|r| &r.account_id == account_id).and_then(|r| {
+            r.support_card
+                .clone()
+                .map(|support_card| (r.trainer_name.clone(), support_card))
+        });
+        future::ready(Ok(share)).boxed()
+    }
+}
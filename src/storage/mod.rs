@@ -0,0 +1,105 @@
+//! Data access behind the read-heavy public endpoints (search, circles, stats, shares).
+//!
+//! Handlers go through `AppState::storage` for these reads so the same routes can be
+//! served from Postgres or, with MOCK_MODE=true, from bundled fixtures.
+
+use futures_util::future::BoxFuture;
+use serde::Deserialize;
+
+use crate::errors::AppError;
+use crate::models::{
+    AccountId, Circle, CircleId, CircleListParams, CircleMemberFansMonthly, CircleQueryParams,
+    Inheritance, SupportCard, TaskOutcome, UnifiedAccountRecord, UnifiedSearchParams, ViewerId,
+};
+
+mod mock;
+mod postgres;
+
+pub use mock::MockStorage;
+pub use postgres::PgStorage;
+
+/// Headline counters shown by GET /api/stats
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatsCounts {
+    /// Distinct visitors estimated from the daily visitor sketches
+    pub unique_visitors_today: i64,
+    pub unique_visitors_7_day: f64,
+    pub unique_visitors_30_day: f64,
+    pub total_accounts_tracked: i64,
+    pub total_circles_tracked: i64,
+    pub total_characters: i64,
+}
+
+pub trait Storage: Send + Sync {
+    /// Number of records matching a search
+    fn search_count<'a>(
+        &'a self,
+        params: &'a UnifiedSearchParams,
+    ) -> BoxFuture<'a, Result<i64, AppError>>;
+
+    /// One page of search results
+    fn search<'a>(
+        &'a self,
+        params: &'a UnifiedSearchParams,
+        limit: i64,
+        offset: i64,
+    ) -> BoxFuture<'a, Result<Vec<UnifiedAccountRecord>, AppError>>;
+
+    fn stats_counts(&self) -> BoxFuture<'_, Result<StatsCounts, AppError>>;
+
+    /// Circle a viewer belongs to, if they have been seen in one
+    fn circle_of_viewer(&self, viewer_id: ViewerId)
+        -> BoxFuture<'_, Result<Option<CircleId>, AppError>>;
+
+    /// A single circle (masked); NotFound if it doesn't exist
+    fn circle(&self, circle_id: CircleId) -> BoxFuture<'_, Result<Circle, AppError>>;
+
+    /// Fingerprint of a circle and its members for the month in `params`; changes whenever
+    /// either is updated. None if the circle doesn't exist or responses shouldn't be cached.
+    fn circle_version<'a>(
+        &'a self,
+        circle_id: CircleId,
+        params: &'a CircleQueryParams,
+    ) -> BoxFuture<'a, Result<Option<String>, AppError>>;
+
+    /// Percent of this month's tracked circles the circle out-scores
+    fn circle_percentile(&self, circle_id: CircleId)
+        -> BoxFuture<'_, Result<Option<f64>, AppError>>;
+
+    /// Members of a circle for the month in `params`, and the member total before paging
+    fn circle_members<'a>(
+        &'a self,
+        circle_id: CircleId,
+        params: &'a CircleQueryParams,
+    ) -> BoxFuture<'a, Result<(Vec<CircleMemberFansMonthly>, i64), AppError>>;
+
+    /// Queue a fetch of a viewer's circle
+    fn queue_circle_fetch(&self, viewer_id: ViewerId)
+        -> BoxFuture<'_, Result<TaskOutcome, AppError>>;
+
+    /// Total matches and one page of the circle list (unmasked)
+    fn list_circles<'a>(
+        &'a self,
+        params: &'a CircleListParams,
+        limit: i64,
+        offset: i64,
+    ) -> BoxFuture<'a, Result<(i64, Vec<Circle>), AppError>>;
+
+    /// Trainer name and inheritance record for a share page
+    fn inheritance_share<'a>(
+        &'a self,
+        account_id: &'a AccountId,
+    ) -> BoxFuture<'a, Result<Option<(String, Inheritance)>, AppError>>;
+
+    /// Trainer name and a specific inheritance record for a share page
+    fn inheritance_share_by_id(
+        &self,
+        inheritance_id: i32,
+    ) -> BoxFuture<'_, Result<Option<(String, Inheritance)>, AppError>>;
+
+    /// Trainer name and best support card for a share page
+    fn support_card_share<'a>(
+        &'a self,
+        account_id: &'a AccountId,
+    ) -> BoxFuture<'a, Result<Option<(String, SupportCard)>, AppError>>;
+}
@@ -0,0 +1,117 @@
+use futures_util::future::{BoxFuture, FutureExt};
+use std::sync::Arc;
+
+use super::{StatsCounts, Storage};
+use crate::database::DbPools;
+use crate::errors::AppError;
+use crate::handlers::{circles, search, sharing, stats};
+use crate::models::{
+    AccountId, Circle, CircleId, CircleListParams, CircleMemberFansMonthly, CircleQueryParams,
+    Inheritance, SupportCard, TaskOutcome, UnifiedAccountRecord, UnifiedSearchParams, ViewerId,
+};
+use crate::sparks::SparkEncoding;
+
+/// Storage backed by the production database; reads go to a healthy read
+/// replica when there is one
+pub struct PgStorage {
+    pools: Arc<DbPools>,
+}
+
+impl PgStorage {
+    pub fn new(pools: Arc<DbPools>) -> Self {
+        Self { pools }
+    }
+}
+
+impl Storage for PgStorage {
+    fn search_count<'a>(
+        &'a self,
+        params: &'a UnifiedSearchParams,
+    ) -> BoxFuture<'a, Result<i64, AppError>> {
+        search::execute_count_query(self.pools.read(), params, SparkEncoding::active()).boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        params: &'a UnifiedSearchParams,
+        limit: i64,
+        offset: i64,
+    ) -> BoxFuture<'a, Result<Vec<UnifiedAccountRecord>, AppError>> {
+        search::execute_search_query(self.pools.read(), params, limit, offset).boxed()
+    }
+
+    fn stats_counts(&self) -> BoxFuture<'_, Result<StatsCounts, AppError>> {
+        stats::fetch_stats_counts(self.pools.read()).boxed()
+    }
+
+    fn circle_of_viewer(
+        &self,
+        viewer_id: ViewerId,
+    ) -> BoxFuture<'_, Result<Option<CircleId>, AppError>> {
+        circles::find_viewer_circle(self.pools.read(), viewer_id).boxed()
+    }
+
+    fn circle(&self, circle_id: CircleId) -> BoxFuture<'_, Result<Circle, AppError>> {
+        circles::fetch_circle_by_id(self.pools.read(), circle_id).boxed()
+    }
+
+    fn circle_version<'a>(
+        &'a self,
+        circle_id: CircleId,
+        params: &'a CircleQueryParams,
+    ) -> BoxFuture<'a, Result<Option<String>, AppError>> {
+        circles::fetch_circle_version(self.pools.read(), circle_id, params).boxed()
+    }
+
+    fn circle_percentile(
+        &self,
+        circle_id: CircleId,
+    ) -> BoxFuture<'_, Result<Option<f64>, AppError>> {
+        circles::fetch_circle_percentile(self.pools.read(), circle_id).boxed()
+    }
+
+    fn circle_members<'a>(
+        &'a self,
+        circle_id: CircleId,
+        params: &'a CircleQueryParams,
+    ) -> BoxFuture<'a, Result<(Vec<CircleMemberFansMonthly>, i64), AppError>> {
+        circles::fetch_circle_members(self.pools.read(), circle_id, params).boxed()
+    }
+
+    fn queue_circle_fetch(
+        &self,
+        viewer_id: ViewerId,
+    ) -> BoxFuture<'_, Result<TaskOutcome, AppError>> {
+        circles::add_viewer_to_tasks(&self.pools.primary, viewer_id).boxed()
+    }
+
+    fn list_circles<'a>(
+        &'a self,
+        params: &'a CircleListParams,
+        limit: i64,
+        offset: i64,
+    ) -> BoxFuture<'a, Result<(i64, Vec<Circle>), AppError>> {
+        circles::query_circle_list(self.pools.read(), params, limit, offset).boxed()
+    }
+
+    fn inheritance_share<'a>(
+        &'a self,
+        account_id: &'a AccountId,
+    ) -> BoxFuture<'a, Result<Option<(String, Inheritance)>, AppError>> {
+        sharing::fetch_inheritance_share(self.pools.read(), account_id).boxed()
+    }
+
+    fn inheritance_share_by_id(
+        &self,
+        inheritance_id: i32,
+    ) -> BoxFuture<'_, Result<Option<(String, Inheritance)>, AppError>> {
+        sharing::fetch_inheritance_share_by_id(self.pools.read(), inheritance_id).boxed()
+    }
+
+    fn support_card_share<'a>(
+        &'a self,
+        account_id: &'a AccountId,
+    ) -> BoxFuture<'a, Result<Option<(String, SupportCard)>, AppError>> {
+        sharing::fetch_support_card_share(self.pools.read(), account_id).boxed()
+    }
+}
@@ -0,0 +1,61 @@
+use axum::{
+    body::{Body, Bytes},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use futures_util::stream;
+use serde::Serialize;
+
+use crate::models::SearchResponse;
+
+/// Pages with at least this many items are streamed instead of buffered
+pub const STREAMING_THRESHOLD: i64 = 50;
+
+/// Number of items serialized into each body chunk
+const ITEMS_PER_CHUNK: usize = 16;
+
+/// Stream a search page as JSON, serializing items chunk by chunk as the client reads
+///
+/// Produces the same document as `Json(response)` (field order aside) without
+/// ever holding the whole serialized page in memory.
+pub fn stream_search_response<T>(response: SearchResponse<T>) -> Response
+where
+    T: Serialize + Send + 'static,
+{
+    let head = format!(
+        r#"{{"total":{},"page":{},"limit":{},"total_pages":{},"items":["#,
+        serde_json::Value::String(response.total),
+        response.page,
+        response.limit,
+        response.total_pages
+    );
+
+    let mut items = response.items.into_iter().peekable();
+    let mut first = true;
+
+    let body_chunks = stream::iter(std::iter::once(Ok(Bytes::from(head))).chain(
+        std::iter::from_fn(move || {
+            items.peek()?;
+
+            let mut buf = Vec::new();
+            for item in items.by_ref().take(ITEMS_PER_CHUNK) {
+                if !first {
+                    buf.push(b',');
+                }
+                first = false;
+                if let Err(e) = serde_json::to_writer(&mut buf, &item) {
+                    tracing::error!("❌ Failed to serialize streamed item: {}", e);
+                    return Some(Err(std::io::Error::other(e)));
+                }
+            }
+            Some(Ok(Bytes::from(buf)))
+        })
+        .chain(std::iter::once(Ok(Bytes::from_static(b"]}")))),
+    ));
+
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        Body::from_stream(body_chunks),
+    )
+        .into_response()
+}
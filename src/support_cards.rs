@@ -0,0 +1,57 @@
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use crate::models::{CardId, SupportCardMeta};
+
+/// Rows of the support_card_meta table, keyed by support card ID
+static META: OnceLock<RwLock<HashMap<CardId, SupportCardMeta>>> = OnceLock::new();
+
+fn get_meta() -> &'static RwLock<HashMap<CardId, SupportCardMeta>> {
+    META.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Master data of a support card, if it has been imported
+pub fn support_card_meta(support_card_id: CardId) -> Option<SupportCardMeta> {
+    get_meta()
+        .read()
+        .ok()
+        .and_then(|meta| meta.get(&support_card_id).cloned())
+}
+
+/// Rarity label as shown in game (R, SR, SSR)
+pub fn rarity_label(rarity: i32) -> String {
+    match rarity {
+        1 => "R".to_string(),
+        2 => "SR".to_string(),
+        3 => "SSR".to_string(),
+        _ => format!("Rarity {}", rarity),
+    }
+}
+
+/// Load (or reload) the support_card_meta table. Returns the number of cards loaded.
+pub async fn load_support_card_meta(pool: &PgPool) -> usize {
+    let rows = match sqlx::query_as::<_, SupportCardMeta>(
+        "SELECT support_card_id, name, rarity, card_type, chara_id FROM support_card_meta",
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!("⚠️ Failed to load support card metadata: {}", e);
+            return 0;
+        }
+    };
+
+    let count = rows.len();
+    if let Ok(mut meta) = get_meta().write() {
+        *meta = rows
+            .into_iter()
+            .map(|card| (card.support_card_id, card))
+            .collect();
+    }
+
+    tracing::info!("🃏 Loaded metadata for {} support cards", count);
+    count
+}
@@ -0,0 +1,83 @@
+//! Serving over a Unix domain socket, for reverse proxies on the same host.
+
+use axum::{body::Body, extract::ConnectInfo, http::Request, Router};
+use hyper::body::Incoming;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::{conn::auto::Builder, graceful::GracefulShutdown},
+    service::TowerToHyperService,
+};
+use std::io;
+use std::net::SocketAddr;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::Path;
+use tokio::net::UnixListener;
+use tower::ServiceExt;
+use tracing::{debug, warn};
+
+use crate::shutdown;
+
+/// Address handlers see in ConnectInfo for socket connections, which have no IP.
+/// Client IPs come from the proxy's forwarding headers instead.
+const UNIX_PEER_ADDR: SocketAddr = SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 0);
+
+/// Bind the socket, replacing a stale socket file left behind by a previous run.
+/// `mode` (e.g. 0o660) sets the file permissions so the proxy's user can connect.
+pub fn bind(path: &Path, mode: Option<u32>) -> io::Result<UnixListener> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    let listener = UnixListener::bind(path)?;
+    if let Some(mode) = mode {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    }
+    Ok(listener)
+}
+
+/// Serve `app` until shutdown starts, then wait for open connections to finish
+pub async fn serve(listener: UnixListener, app: Router) -> io::Result<()> {
+    let graceful = GracefulShutdown::new();
+
+    loop {
+        let stream = tokio::select! {
+            biased;
+            _ = shutdown::requested() => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("⚠️ Failed to accept Unix socket connection: {}", e);
+                    continue;
+                }
+            },
+        };
+
+        let service = app.clone().map_request(|request: Request<Incoming>| {
+            let mut request = request.map(Body::new);
+            request.extensions_mut().insert(ConnectInfo(UNIX_PEER_ADDR));
+            request
+        });
+        let watcher = graceful.watcher();
+
+        tokio::spawn(async move {
+            let builder = Builder::new(TokioExecutor::new());
+            let connection = builder
+                .serve_connection_with_upgrades(TokioIo::new(stream), TowerToHyperService::new(service));
+            if let Err(e) = watcher.watch(connection).await {
+                debug!("Unix socket connection ended with an error: {}", e);
+            }
+        });
+    }
+
+    drop(listener);
+    graceful.shutdown().await;
+    Ok(())
+}
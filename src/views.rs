@@ -0,0 +1,131 @@
+use chrono::NaiveDateTime;
+use dashmap::DashMap;
+use sqlx::PgPool;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::models::MaterializedViewStatus;
+
+/// A materialized view refreshed by the in-process scheduler
+pub struct MaterializedView {
+    pub name: &'static str,
+    /// Environment variable overriding the refresh interval (seconds)
+    pub(crate) interval_env: &'static str,
+    pub(crate) default_interval_secs: u64,
+}
+
+pub static MATERIALIZED_VIEWS: &[MaterializedView] = &[
+    MaterializedView {
+        name: "stats_counts",
+        interval_env: "STATS_COUNTS_REFRESH_SECS",
+        default_interval_secs: 3600,
+    },
+    MaterializedView {
+        name: "circle_live_ranks",
+        interval_env: "CIRCLE_LIVE_RANKS_REFRESH_SECS",
+        default_interval_secs: 300,
+    },
+];
+
+/// Outcome of the most recent refresh attempt per view
+#[derive(Clone, Default)]
+struct RefreshState {
+    last_refreshed_at: Option<NaiveDateTime>,
+    last_error: Option<String>,
+}
+
+static STATE: OnceLock<DashMap<&'static str, RefreshState>> = OnceLock::new();
+
+fn get_state() -> &'static DashMap<&'static str, RefreshState> {
+    STATE.get_or_init(DashMap::new)
+}
+
+impl MaterializedView {
+    pub fn interval(&self) -> Duration {
+        crate::config::get()
+            .view_refresh_intervals
+            .get(self.name)
+            .copied()
+            .unwrap_or(Duration::from_secs(self.default_interval_secs))
+    }
+
+    pub fn status(&self) -> MaterializedViewStatus {
+        let state = get_state()
+            .get(self.name)
+            .map(|state| state.clone())
+            .unwrap_or_default();
+        MaterializedViewStatus {
+            view: self.name.to_string(),
+            refresh_interval_secs: self.interval().as_secs(),
+            last_refreshed_at: state.last_refreshed_at,
+            last_error: state.last_error,
+        }
+    }
+
+    /// Refresh the view, concurrently if possible, and record the outcome
+    pub async fn refresh(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        // CONCURRENTLY needs a unique index and a populated view; fall back to a plain refresh
+        let concurrent = format!("REFRESH MATERIALIZED VIEW CONCURRENTLY {}", self.name);
+        let result = match execute_long_running(pool, &concurrent).await {
+            Ok(()) => Ok(()),
+            Err(_) => {
+                execute_long_running(pool, &format!("REFRESH MATERIALIZED VIEW {}", self.name))
+                    .await
+            }
+        };
+
+        let mut state = get_state().entry(self.name).or_default();
+        match &result {
+            Ok(()) => {
+                state.last_refreshed_at = Some(chrono::Utc::now().naive_utc());
+                state.last_error = None;
+                // Cached stats embed the view's counts
+                if self.name == "stats_counts" {
+                    crate::cache::invalidate("stats:main");
+                }
+            }
+            Err(e) => state.last_error = Some(e.to_string()),
+        }
+        result
+    }
+}
+
+/// Run one statement under the long statement timeout
+async fn execute_long_running(pool: &PgPool, sql: &str) -> Result<(), sqlx::Error> {
+    let mut tx = crate::database::begin_long_running(pool).await?;
+    sqlx::query(sql).execute(&mut *tx).await?;
+    tx.commit().await
+}
+
+pub fn find(name: &str) -> Option<&'static MaterializedView> {
+    MATERIALIZED_VIEWS.iter().find(|view| view.name == name)
+}
+
+/// Refresh status of every scheduled view
+pub fn statuses() -> Vec<MaterializedViewStatus> {
+    MATERIALIZED_VIEWS.iter().map(MaterializedView::status).collect()
+}
+
+/// Background task refreshing one view on its configured cadence
+pub async fn refresh_task(pool: PgPool, view: &'static MaterializedView) {
+    let period = view.interval();
+    let mut interval = tokio::time::interval(period);
+
+    info!(
+        "🔄 Starting {} refresh background task (runs every {}s)",
+        view.name,
+        period.as_secs()
+    );
+
+    loop {
+        if crate::shutdown::tick(&mut interval).await.is_none() {
+            break;
+        }
+
+        match view.refresh(&pool).await {
+            Ok(()) => info!("✅ Materialized view {} refreshed successfully", view.name),
+            Err(e) => warn!("⚠️ Failed to refresh {}: {}", view.name, e),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::sync::OnceLock;
+use tracing::warn;
+
+/// Register index bits; 2^12 one-byte registers per day (~1.6% standard error)
+const PRECISION: u32 = 12;
+const REGISTERS: usize = 1 << PRECISION;
+
+static SALT: OnceLock<String> = OnceLock::new();
+
+/// Secret mixed into visitor fingerprints (VISITOR_HASH_SALT)
+///
+/// Without it a random per-process salt is used, so visitors are counted
+/// again after a restart.
+fn salt() -> &'static str {
+    SALT.get_or_init(|| match &crate::config::get().visitor_hash_salt {
+        Some(value) => value.clone(),
+        None => {
+            warn!("⚠️ VISITOR_HASH_SALT not set, using a random salt for this process");
+            uuid::Uuid::new_v4().to_string()
+        }
+    })
+}
+
+fn salted_digest(ip: &str, user_agent: &str) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(salt())
+        .chain_update([0])
+        .chain_update(ip)
+        .chain_update([0])
+        .chain_update(user_agent)
+        .finalize()
+        .into()
+}
+
+/// Stable per-client ID (salted hash of IP and user agent), for limiting
+/// actions to one per visitor without storing the IP
+pub fn fingerprint(ip: &str, user_agent: &str) -> String {
+    hex::encode(salted_digest(ip, user_agent))
+}
+
+/// Register index and rank a visitor contributes to the sketch
+///
+/// Only the rank (leading zeros + 1) of the salted hash is kept, so the
+/// stored sketch cannot be mapped back to an IP or user agent.
+fn register_for(ip: &str, user_agent: &str) -> (i32, i32) {
+    let digest = salted_digest(ip, user_agent);
+    let hash = u64::from_be_bytes(digest[..8].try_into().expect("8 byte prefix"));
+
+    let index = (hash >> (64 - PRECISION)) as i32;
+    let rank = ((hash << PRECISION).leading_zeros() + 1).min(64 - PRECISION + 1) as i32;
+    (index, rank)
+}
+
+/// Add a visitor to today's sketch
+///
+/// The row is only written when the visitor raises a register, which gets
+/// rarer as the day goes on, so repeat visits are read-only.
+pub async fn record_visit(pool: &PgPool, ip: &str, user_agent: &str) -> Result<(), sqlx::Error> {
+    let (index, rank) = register_for(ip, user_agent);
+
+    sqlx::query(
+        r#"
+        INSERT INTO visitor_sketches (date, registers)
+        VALUES (CURRENT_DATE, set_byte(decode(repeat('00', $3), 'hex'), $1, $2))
+        ON CONFLICT (date) DO UPDATE
+        SET registers = set_byte(visitor_sketches.registers, $1, $2)
+        WHERE get_byte(visitor_sketches.registers, $1) < $2
+        "#,
+    )
+    .bind(index)
+    .bind(rank)
+    .bind(REGISTERS as i32)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Estimated distinct visitors over the last `days` days (including today)
+pub async fn unique_visitors(pool: &PgPool, days: i32) -> Result<f64, sqlx::Error> {
+    let sketches = sqlx::query_scalar::<_, Vec<u8>>(
+        "SELECT registers FROM visitor_sketches WHERE date > CURRENT_DATE - $1::int",
+    )
+    .bind(days)
+    .fetch_all(pool)
+    .await?;
+
+    let mut registers = [0u8; REGISTERS];
+    for sketch in &sketches {
+        merge(&mut registers, sketch);
+    }
+
+    Ok(estimate(&registers))
+}
+
+/// Fold a sketch into `registers`; the union of sketches is their register-wise maximum
+fn merge(registers: &mut [u8], sketch: &[u8]) {
+    for (register, &value) in registers.iter_mut().zip(sketch) {
+        *register = (*register).max(value);
+    }
+}
+
+/// HyperLogLog cardinality estimate with the small-range (linear counting) correction
+fn estimate(registers: &[u8]) -> f64 {
+    let m = registers.len() as f64;
+    let alpha = 0.7213 / (1.0 + 1.079 / m);
+    let sum: f64 = registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+    let raw = alpha * m * m / sum;
+
+    let zeros = registers.iter().filter(|&&r| r == 0).count();
+    if raw <= 2.5 * m && zeros > 0 {
+        (m * (m / zeros as f64).ln()).round()
+    } else {
+        raw.round()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sketch_of(visitors: impl IntoIterator<Item = u32>) -> Vec<u8> {
+        let mut registers = vec![0u8; REGISTERS];
+        for visitor in visitors {
+            let (index, rank) = register_for(&format!("visitor-{visitor}"), "agent");
+            let register = &mut registers[index as usize];
+            *register = (*register).max(rank as u8);
+        }
+        registers
+    }
+
+    #[test]
+    fn empty_sketch_estimates_zero() {
+        assert_eq!(estimate(&[0u8; REGISTERS]), 0.0);
+    }
+
+    #[test]
+    fn small_ranges_use_linear_counting() {
+        // 100 registers at rank 1: raw HLL overshoots, linear counting gives m * ln(m / zeros)
+        let mut registers = [0u8; REGISTERS];
+        registers[..100].fill(1);
+
+        let m = REGISTERS as f64;
+        let expected = (m * (m / (m - 100.0)).ln()).round();
+        assert_eq!(estimate(&registers), expected);
+        assert_eq!(expected, 101.0);
+    }
+
+    #[test]
+    fn large_ranges_use_raw_estimate() {
+        // No empty registers left, so the raw estimate is used: alpha * m^2 / sum(2^-r)
+        let registers = [10u8; REGISTERS];
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        assert_eq!(estimate(&registers), (alpha * m * 1024.0).round());
+    }
+
+    #[test]
+    fn register_for_is_stable_and_in_range() {
+        crate::config::init_for_tests();
+
+        let (index, rank) = register_for("192.0.2.1", "agent");
+        assert_eq!(register_for("192.0.2.1", "agent"), (index, rank));
+        assert_ne!(register_for("192.0.2.1", "other agent"), (index, rank));
+        assert!((0..REGISTERS as i32).contains(&index));
+        assert!((1..=(64 - PRECISION as i32 + 1)).contains(&rank));
+    }
+
+    #[test]
+    fn merge_takes_register_wise_max() {
+        let mut registers = [0u8, 3, 1, 7];
+        merge(&mut registers, &[2, 1, 1, 9]);
+        assert_eq!(registers, [2, 3, 1, 9]);
+    }
+
+    #[test]
+    fn merged_sketches_count_overlapping_visitors_once() {
+        crate::config::init_for_tests();
+
+        // Days overlap on 1000..2000, so the union has 3000 distinct visitors
+        let monday = sketch_of(0..2000);
+        let tuesday = sketch_of(1000..3000);
+
+        let mut union = vec![0u8; REGISTERS];
+        merge(&mut union, &monday);
+        merge(&mut union, &tuesday);
+
+        assert_eq!(union, sketch_of(0..3000));
+        let estimate = estimate(&union);
+        // ~1.6% standard error; the salt is random per run, so allow a wide margin
+        assert!((estimate - 3000.0).abs() < 3000.0 * 0.1, "estimate {estimate}");
+    }
+}